@@ -20,6 +20,11 @@ fn main() {
             "op_app_show",
             "op_app_set_badge_count",
             "op_app_set_user_model_id",
+            "op_app_permission_grants",
+            "op_app_revoke_permission",
+            "op_app_audit_subscribe",
+            "op_app_audit_next",
+            "op_app_audit_unsubscribe",
         ])
         .generate_sdk_module("sdk")
         .use_inventory_types()
@@ -8,13 +8,17 @@
 //! - Window visibility control
 //! - Badge count management
 //! - Locale information
+//! - Permission grants remembered from runtime prompts (list/revoke)
 
 use deno_core::{op2, Extension, OpState};
 use forge_weld_macro::{weld_enum, weld_op, weld_struct};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tracing::{debug, error, info, warn};
 
 // Include the generated extension code from build.rs
@@ -57,6 +61,8 @@ pub enum AppErrorCode {
     NotSupported = 8313,
     /// App state not initialized (8314)
     NotInitialized = 8314,
+    /// Failed to update permission grants (8315)
+    GrantsFailed = 8315,
 }
 
 impl std::fmt::Display for AppErrorCode {
@@ -127,6 +133,10 @@ pub enum AppError {
     #[error("[{code}] App state not initialized: {message}")]
     #[class(generic)]
     NotInitialized { code: AppErrorCode, message: String },
+
+    #[error("[{code}] Failed to update permission grants: {message}")]
+    #[class(generic)]
+    GrantsFailed { code: AppErrorCode, message: String },
 }
 
 impl AppError {
@@ -234,6 +244,13 @@ impl AppError {
             message: message.into(),
         }
     }
+
+    pub fn grants_failed(message: impl Into<String>) -> Self {
+        Self::GrantsFailed {
+            code: AppErrorCode::GrantsFailed,
+            message: message.into(),
+        }
+    }
 }
 
 // ============================================================================
@@ -278,6 +295,78 @@ pub struct DefaultAppCapabilityChecker;
 
 impl AppCapabilityChecker for DefaultAppCapabilityChecker {}
 
+// ============================================================================
+// Permission Grants
+// ============================================================================
+
+/// A decision remembered from a runtime permission prompt
+#[weld_struct]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionGrant {
+    /// Capability name (e.g., "fs.read", "net", "process.spawn")
+    pub capability: String,
+    /// Resource the decision applies to (path, host or binary)
+    pub resource: String,
+    /// Whether access was allowed or denied
+    pub allowed: bool,
+    /// Whether the decision is persisted across launches
+    pub persisted: bool,
+    /// RFC 3339 timestamp of the decision
+    pub granted_at: String,
+}
+
+/// Store of prompt decisions, implemented by the runtime's permission system
+pub trait PermissionGrantStore: Send + Sync + 'static {
+    /// All remembered decisions (session and persisted)
+    fn grants(&self) -> Vec<PermissionGrant>;
+
+    /// Forget decisions for a capability, optionally limited to one resource.
+    /// Returns the number of decisions removed.
+    fn revoke(&self, capability: &str, resource: Option<&str>) -> Result<usize, String>;
+}
+
+// ============================================================================
+// Capability Audit
+// ============================================================================
+
+/// One allow/deny decision made by the runtime's capability checks
+#[weld_struct]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEvent {
+    /// RFC 3339 timestamp of the decision
+    pub timestamp: String,
+    /// Capability name (e.g., "fs.read", "net", "ui.channel")
+    pub capability: String,
+    /// Resource that was checked (path, host, binary, variable, channel)
+    pub resource: String,
+    /// Whether access was allowed
+    pub allowed: bool,
+    /// Extension whose op triggered the check, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+    /// Op that triggered the check, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub op: Option<String>,
+    /// Whether the decision was made in dev mode (always allowed)
+    #[serde(default)]
+    pub dev_mode: bool,
+}
+
+/// Source of audit events, implemented by the runtime's audit log
+pub trait AuditEventSource: Send + Sync + 'static {
+    /// Receive every decision recorded from now on
+    fn subscribe(&self) -> broadcast::Receiver<AuditEvent>;
+}
+
+/// Open `auditEvents()` streams, keyed by subscription id
+#[derive(Default)]
+struct AuditSubscriptions {
+    next_id: u32,
+    receivers: HashMap<u32, Rc<tokio::sync::Mutex<broadcast::Receiver<AuditEvent>>>>,
+}
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -447,6 +536,20 @@ pub fn init_app_state<C: AppCapabilityChecker>(
     state.put(checker);
 }
 
+/// Make the runtime's permission grants available to `runtime:app`
+///
+/// Without a store (e.g. in dev mode, where prompts never happen) the
+/// grant ops report no grants.
+pub fn init_permission_grants(state: &mut OpState, store: Arc<dyn PermissionGrantStore>) {
+    state.put(store);
+}
+
+/// Make the runtime's capability audit log streamable from `runtime:app`
+pub fn init_audit_log(state: &mut OpState, source: Arc<dyn AuditEventSource>) {
+    state.put(source);
+    state.put(RefCell::new(AuditSubscriptions::default()));
+}
+
 // ============================================================================
 // Operations
 // ============================================================================
@@ -923,6 +1026,96 @@ pub fn op_app_set_user_model_id(
     }
 }
 
+/// List decisions remembered from permission prompts
+#[weld_op]
+#[op2]
+#[serde]
+pub fn op_app_permission_grants(state: &mut OpState) -> Result<Vec<PermissionGrant>, AppError> {
+    Ok(state
+        .try_borrow::<Arc<dyn PermissionGrantStore>>()
+        .map(|store| store.grants())
+        .unwrap_or_default())
+}
+
+/// Revoke remembered permission decisions so the user is asked again
+#[weld_op]
+#[op2]
+pub fn op_app_revoke_permission(
+    state: &mut OpState,
+    #[string] capability: String,
+    #[string] resource: Option<String>,
+) -> Result<u32, AppError> {
+    debug!("Revoking permission grants: {} {:?}", capability, resource);
+
+    let Some(store) = state.try_borrow::<Arc<dyn PermissionGrantStore>>() else {
+        return Ok(0);
+    };
+    store
+        .revoke(&capability, resource.as_deref())
+        .map(|removed| removed as u32)
+        .map_err(AppError::grants_failed)
+}
+
+/// Start receiving capability audit events; returns a subscription id
+#[weld_op]
+#[op2(fast)]
+pub fn op_app_audit_subscribe(state: &mut OpState) -> Result<u32, AppError> {
+    let Some(source) = state.try_borrow::<Arc<dyn AuditEventSource>>() else {
+        return Err(AppError::not_initialized(
+            "Capability audit log is disabled",
+        ));
+    };
+    let receiver = source.subscribe();
+
+    let mut subscriptions = state.borrow::<RefCell<AuditSubscriptions>>().borrow_mut();
+    subscriptions.next_id += 1;
+    let id = subscriptions.next_id;
+    subscriptions
+        .receivers
+        .insert(id, Rc::new(tokio::sync::Mutex::new(receiver)));
+    Ok(id)
+}
+
+/// Wait for the next audit event; `null` once the subscription is closed
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+pub async fn op_app_audit_next(
+    state: std::rc::Rc<std::cell::RefCell<OpState>>,
+    id: u32,
+) -> Result<Option<AuditEvent>, AppError> {
+    let receiver = {
+        let state = state.borrow();
+        let Some(subscriptions) = state.try_borrow::<RefCell<AuditSubscriptions>>() else {
+            return Ok(None);
+        };
+        let Some(receiver) = subscriptions.borrow().receivers.get(&id).cloned() else {
+            return Ok(None);
+        };
+        receiver
+    };
+
+    let mut receiver = receiver.lock().await;
+    loop {
+        match receiver.recv().await {
+            Ok(event) => return Ok(Some(event)),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Audit subscription {} skipped {} events", id, skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(None),
+        }
+    }
+}
+
+/// Stop receiving capability audit events
+#[weld_op]
+#[op2(fast)]
+pub fn op_app_audit_unsubscribe(state: &mut OpState, id: u32) {
+    if let Some(subscriptions) = state.try_borrow::<RefCell<AuditSubscriptions>>() {
+        subscriptions.borrow_mut().receivers.remove(&id);
+    }
+}
+
 // ============================================================================
 // Extension Export
 // ============================================================================
@@ -949,6 +1142,7 @@ mod tests {
         assert_eq!(AppErrorCode::PathFailed as i32, 8304);
         assert_eq!(AppErrorCode::LockFailed as i32, 8305);
         assert_eq!(AppErrorCode::NotInitialized as i32, 8314);
+        assert_eq!(AppErrorCode::GrantsFailed as i32, 8315);
     }
 
     #[test]
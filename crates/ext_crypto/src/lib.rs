@@ -19,7 +19,7 @@ use tracing::debug;
 // Error Types with Structured Codes
 // ============================================================================
 
-/// Error codes for crypto operations (8000-8009)
+/// Error codes for crypto operations (8000-8010)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum CryptoErrorCode {
@@ -43,6 +43,8 @@ pub enum CryptoErrorCode {
     KeyDerivationFailed = 8008,
     /// Verification failed
     VerificationFailed = 8009,
+    /// Crypto access not permitted
+    PermissionDenied = 8010,
 }
 
 /// Custom error type for crypto operations
@@ -87,6 +89,10 @@ pub enum CryptoError {
     #[error("[{code}] Verification failed: {message}")]
     #[class(generic)]
     VerificationFailed { code: u32, message: String },
+
+    #[error("[{code}] Permission denied: {message}")]
+    #[class(generic)]
+    PermissionDenied { code: u32, message: String },
 }
 
 impl CryptoError {
@@ -159,6 +165,13 @@ impl CryptoError {
             message: message.into(),
         }
     }
+
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        Self::PermissionDenied {
+            code: CryptoErrorCode::PermissionDenied as u32,
+            message: message.into(),
+        }
+    }
 }
 
 // ============================================================================
@@ -446,11 +459,22 @@ fn verify_impl(
 // Operations (Deno ops that delegate to internal implementations)
 // ============================================================================
 
+fn check_crypto(state: &OpState) -> Result<(), CryptoError> {
+    if let Some(caps) = state.try_borrow::<CryptoCapabilities>() {
+        caps.checker
+            .check_crypto()
+            .map_err(CryptoError::permission_denied)
+    } else {
+        Ok(())
+    }
+}
+
 /// Generate cryptographically secure random bytes
 #[weld_op]
 #[op2]
 #[serde]
-fn op_crypto_random_bytes(#[smi] size: u32) -> Result<Vec<u8>, CryptoError> {
+fn op_crypto_random_bytes(state: &OpState, #[smi] size: u32) -> Result<Vec<u8>, CryptoError> {
+    check_crypto(state)?;
     debug!(size = size, "crypto.random_bytes");
     random_bytes_impl(size)
 }
@@ -459,9 +483,10 @@ fn op_crypto_random_bytes(#[smi] size: u32) -> Result<Vec<u8>, CryptoError> {
 #[weld_op]
 #[op2]
 #[string]
-fn op_crypto_random_uuid() -> String {
+fn op_crypto_random_uuid(state: &OpState) -> Result<String, CryptoError> {
+    check_crypto(state)?;
     debug!("crypto.random_uuid");
-    random_uuid_impl()
+    Ok(random_uuid_impl())
 }
 
 /// Hash data using specified algorithm
@@ -469,9 +494,11 @@ fn op_crypto_random_uuid() -> String {
 #[op2]
 #[serde]
 fn op_crypto_hash(
+    state: &OpState,
     #[string] algorithm: String,
     #[serde] data: Vec<u8>,
 ) -> Result<Vec<u8>, CryptoError> {
+    check_crypto(state)?;
     debug!(algorithm = %algorithm, len = data.len(), "crypto.hash");
     compute_hash(&algorithm, &data)
 }
@@ -481,9 +508,11 @@ fn op_crypto_hash(
 #[op2]
 #[string]
 fn op_crypto_hash_hex(
+    state: &OpState,
     #[string] algorithm: String,
     #[serde] data: Vec<u8>,
 ) -> Result<String, CryptoError> {
+    check_crypto(state)?;
     debug!(algorithm = %algorithm, len = data.len(), "crypto.hash_hex");
     let hash = compute_hash(&algorithm, &data)?;
     Ok(hex::encode(hash))
@@ -494,10 +523,12 @@ fn op_crypto_hash_hex(
 #[op2]
 #[serde]
 fn op_crypto_hmac(
+    state: &OpState,
     #[string] algorithm: String,
     #[serde] key: Vec<u8>,
     #[serde] data: Vec<u8>,
 ) -> Result<Vec<u8>, CryptoError> {
+    check_crypto(state)?;
     debug!(algorithm = %algorithm, key_len = key.len(), data_len = data.len(), "crypto.hmac");
     hmac_impl(&algorithm, &key, &data)
 }
@@ -507,11 +538,13 @@ fn op_crypto_hmac(
 #[op2]
 #[serde]
 fn op_crypto_encrypt(
+    state: &OpState,
     #[string] algorithm: String,
     #[serde] key: Vec<u8>,
     #[serde] data: Vec<u8>,
     #[serde] iv: Option<Vec<u8>>,
 ) -> Result<EncryptedData, CryptoError> {
+    check_crypto(state)?;
     debug!(algorithm = %algorithm, key_len = key.len(), data_len = data.len(), "crypto.encrypt");
     encrypt_impl(&algorithm, &key, &data, iv.as_deref())
 }
@@ -521,10 +554,12 @@ fn op_crypto_encrypt(
 #[op2]
 #[serde]
 fn op_crypto_decrypt(
+    state: &OpState,
     #[string] algorithm: String,
     #[serde] key: Vec<u8>,
     #[serde] encrypted: EncryptedData,
 ) -> Result<Vec<u8>, CryptoError> {
+    check_crypto(state)?;
     debug!(algorithm = %algorithm, key_len = key.len(), "crypto.decrypt");
     decrypt_impl(&algorithm, &key, &encrypted)
 }
@@ -534,9 +569,11 @@ fn op_crypto_decrypt(
 #[op2]
 #[serde]
 fn op_crypto_generate_key(
+    state: &OpState,
     #[string] algorithm: String,
     #[smi] length: Option<u32>,
 ) -> Result<Vec<u8>, CryptoError> {
+    check_crypto(state)?;
     debug!(algorithm = %algorithm, length = ?length, "crypto.generate_key");
     generate_key_impl(&algorithm, length)
 }
@@ -546,11 +583,13 @@ fn op_crypto_generate_key(
 #[op2]
 #[serde]
 fn op_crypto_derive_key(
+    state: &OpState,
     #[string] password: String,
     #[serde] salt: Vec<u8>,
     #[smi] iterations: u32,
     #[smi] key_length: u32,
 ) -> Result<Vec<u8>, CryptoError> {
+    check_crypto(state)?;
     debug!(
         iterations = iterations,
         key_length = key_length,
@@ -563,11 +602,13 @@ fn op_crypto_derive_key(
 #[weld_op]
 #[op2]
 fn op_crypto_verify(
+    state: &OpState,
     #[string] algorithm: String,
     #[serde] key: Vec<u8>,
     #[serde] data: Vec<u8>,
     #[serde] signature: Vec<u8>,
 ) -> Result<bool, CryptoError> {
+    check_crypto(state)?;
     debug!(algorithm = %algorithm, "crypto.verify");
     verify_impl(&algorithm, &key, &data, &signature)
 }
@@ -42,6 +42,15 @@ fn main() {
             "op_database_migrate",
             "op_database_migration_status",
             "op_database_migrate_down",
+            // Encryption, Backup and Dumps (8 ops)
+            "op_database_rekey",
+            "op_database_backup",
+            "op_database_backup_progress",
+            "op_database_backup_finish",
+            "op_database_attach",
+            "op_database_detach",
+            "op_database_export",
+            "op_database_import",
         ])
         .generate_sdk_module("sdk")
         .use_inventory_types()
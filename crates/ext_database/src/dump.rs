@@ -0,0 +1,306 @@
+//! SQL dump export and import
+//!
+//! The dump follows the layout of the `sqlite3` shell's `.dump`: tables and
+//! their rows inside one transaction, then indexes, triggers and views, so
+//! triggers don't fire while rows are restored.
+
+use crate::DatabaseError;
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Options for exporting a SQL dump
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportOptions {
+    /// Only dump these tables, with their indexes and triggers (default: all)
+    pub tables: Option<Vec<String>>,
+    /// Leave out rows, dumping only the schema (default: false)
+    pub schema_only: Option<bool>,
+}
+
+/// Quote an identifier for use in SQL
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_text(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Render a column value as a SQL literal
+fn sql_literal(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) if f.is_nan() => "NULL".to_string(),
+        ValueRef::Real(f) if f.is_infinite() => {
+            if f > 0.0 { "1e999" } else { "-1e999" }.to_string()
+        }
+        // Debug formatting round-trips and always keeps a decimal point or exponent
+        ValueRef::Real(f) => format!("{:?}", f),
+        ValueRef::Text(bytes) => quote_text(&String::from_utf8_lossy(bytes)),
+        ValueRef::Blob(bytes) => {
+            let mut hex = String::with_capacity(bytes.len() * 2 + 3);
+            hex.push_str("X'");
+            for b in bytes {
+                let _ = write!(hex, "{:02X}", b);
+            }
+            hex.push('\'');
+            hex
+        }
+    }
+}
+
+/// Append an INSERT for every row of `table`
+fn dump_rows(conn: &Connection, table: &str, out: &mut String) -> Result<(), DatabaseError> {
+    // Generated and hidden columns can't be inserted into
+    let mut stmt = conn.prepare(&format!(
+        "SELECT name FROM pragma_table_xinfo({}) WHERE hidden = 0 ORDER BY cid",
+        quote_text(table)
+    ))?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    if columns.is_empty() {
+        return Ok(());
+    }
+
+    let column_list = columns
+        .iter()
+        .map(|c| quote_ident(c))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM {}",
+        column_list,
+        quote_ident(table)
+    ))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let values = (0..columns.len())
+            .map(|i| row.get_ref(i).map(sql_literal))
+            .collect::<Result<Vec<_>, _>>()?;
+        let _ = writeln!(
+            out,
+            "INSERT INTO {}({}) VALUES({});",
+            quote_ident(table),
+            column_list,
+            values.join(",")
+        );
+    }
+    Ok(())
+}
+
+/// Export the main database as SQL text
+pub(crate) fn export_sql(conn: &Connection, opts: &ExportOptions) -> Result<String, DatabaseError> {
+    let wanted: Option<HashSet<&str>> = opts
+        .tables
+        .as_ref()
+        .map(|tables| tables.iter().map(String::as_str).collect());
+    let schema_only = opts.schema_only.unwrap_or(false);
+
+    // Virtual tables keep their data in shadow tables, which recreate themselves
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_list WHERE schema = 'main' AND type = 'shadow'")?;
+    let shadow: HashSet<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT type, name, tbl_name, sql FROM sqlite_schema WHERE sql IS NOT NULL ORDER BY rowid",
+    )?;
+    let entries: Vec<(String, String, String, String)> = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    let included = |table: &str| wanted.as_ref().is_none_or(|w| w.contains(table));
+
+    let mut out = String::from("PRAGMA foreign_keys=OFF;\nBEGIN TRANSACTION;\n");
+    let mut has_sequence = false;
+
+    for (kind, name, _, sql) in &entries {
+        // Never named in `tables`; its rows are filtered per table below
+        if name == "sqlite_sequence" {
+            has_sequence = true;
+            continue;
+        }
+        if kind != "table" || !included(name) || shadow.contains(name) {
+            continue;
+        }
+        if name.starts_with("sqlite_") {
+            continue;
+        }
+        let _ = writeln!(out, "{};", sql);
+        if !schema_only {
+            dump_rows(conn, name, &mut out)?;
+        }
+    }
+
+    // AUTOINCREMENT counters, for the tables that were dumped
+    if has_sequence && !schema_only {
+        let mut stmt = conn.prepare("SELECT name, seq FROM sqlite_sequence")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let name: String = row.get(0)?;
+            if !included(&name) {
+                continue;
+            }
+            let seq: i64 = row.get(1)?;
+            let _ = writeln!(
+                out,
+                "DELETE FROM sqlite_sequence WHERE name = {0};\nINSERT INTO sqlite_sequence(name,seq) VALUES({0},{1});",
+                quote_text(&name),
+                seq
+            );
+        }
+    }
+
+    for (kind, _, table, sql) in &entries {
+        if kind != "table" && included(table) && !shadow.contains(table) {
+            let _ = writeln!(out, "{};", sql);
+        }
+    }
+
+    out.push_str("COMMIT;\n");
+    Ok(out)
+}
+
+/// Run a SQL dump against the connection
+///
+/// A failed import is rolled back, and the connection's foreign key setting,
+/// which dumps switch off, is restored either way.
+pub(crate) fn import_sql(conn: &Connection, sql: &str) -> Result<(), DatabaseError> {
+    let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+
+    let result = conn.execute_batch(sql);
+    if result.is_err() && !conn.is_autocommit() {
+        let _ = conn.execute_batch("ROLLBACK");
+    }
+    conn.pragma_update(None, "foreign_keys", foreign_keys)?;
+
+    result.map_err(DatabaseError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT, score REAL, avatar BLOB);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id), body TEXT,
+                                 len INTEGER GENERATED ALWAYS AS (length(body)) VIRTUAL);
+             CREATE INDEX idx_posts_user ON posts(user_id);
+             CREATE TRIGGER posts_touch AFTER INSERT ON posts BEGIN UPDATE users SET score = score + 1 WHERE id = NEW.user_id; END;
+             CREATE VIEW user_posts AS SELECT users.name, posts.body FROM users JOIN posts ON posts.user_id = users.id;
+             INSERT INTO users (name, score, avatar) VALUES ('O''Brien', 1.5, X'00FF'), ('Zoe', NULL, NULL);
+             INSERT INTO posts (user_id, body) VALUES (1, 'line one
+line two');
+             DELETE FROM users WHERE name = 'Zoe';",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn rows(conn: &Connection, sql: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        let count = stmt.column_count();
+        stmt.query_map([], |row| {
+            Ok((0..count)
+                .map(|i| sql_literal(row.get_ref(i).unwrap()))
+                .collect::<Vec<_>>()
+                .join("|"))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+    }
+
+    #[test]
+    fn dump_round_trips_schema_rows_and_sequences() {
+        let src = sample();
+        let dump = export_sql(&src, &ExportOptions::default()).unwrap();
+
+        let dst = Connection::open_in_memory().unwrap();
+        dst.pragma_update(None, "foreign_keys", true).unwrap();
+        import_sql(&dst, &dump).unwrap();
+
+        for sql in [
+            "SELECT * FROM users",
+            "SELECT * FROM posts",
+            "SELECT * FROM user_posts",
+            "SELECT * FROM sqlite_sequence",
+            "SELECT type, name FROM sqlite_schema ORDER BY name",
+        ] {
+            assert_eq!(rows(&src, sql), rows(&dst, sql), "{}", sql);
+        }
+        // The trigger fired once while building the sample, but not again on import
+        assert_eq!(rows(&dst, "SELECT score FROM users"), vec!["2.5"]);
+        let fk: bool = dst
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert!(fk);
+    }
+
+    #[test]
+    fn dump_filters_tables_and_schema_only() {
+        let src = sample();
+        let opts = ExportOptions {
+            tables: Some(vec!["posts".to_string()]),
+            schema_only: Some(true),
+        };
+        let dump = export_sql(&src, &opts).unwrap();
+
+        assert!(dump.contains("CREATE TABLE posts"));
+        assert!(dump.contains("CREATE INDEX idx_posts_user"));
+        assert!(!dump.contains("CREATE TABLE users"));
+        assert!(!dump.contains("INSERT INTO"));
+    }
+
+    #[test]
+    fn dump_filtered_tables_keep_their_sequences() {
+        let src = sample();
+        let opts = ExportOptions {
+            tables: Some(vec!["users".to_string()]),
+            schema_only: None,
+        };
+        let dump = export_sql(&src, &opts).unwrap();
+        assert!(!dump.contains("CREATE TABLE posts"));
+
+        let dst = Connection::open_in_memory().unwrap();
+        import_sql(&dst, &dump).unwrap();
+        let sql = "SELECT * FROM sqlite_sequence";
+        assert_eq!(rows(&dst, sql), vec!["'users'|2"]);
+        assert_eq!(rows(&src, sql), rows(&dst, sql));
+    }
+
+    #[test]
+    fn failed_import_rolls_back() {
+        let dst = Connection::open_in_memory().unwrap();
+        let err = import_sql(
+            &dst,
+            "BEGIN TRANSACTION;\nCREATE TABLE t (x);\nINSERT INTO t VALUES (1);\nNOT SQL;\nCOMMIT;",
+        );
+
+        assert!(err.is_err());
+        assert!(dst.is_autocommit());
+        let tables: i64 = dst
+            .query_row("SELECT COUNT(*) FROM sqlite_schema", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+
+    #[test]
+    fn literals_escape_text_blobs_and_special_floats() {
+        assert_eq!(sql_literal(ValueRef::Text(b"it's")), "'it''s'");
+        assert_eq!(sql_literal(ValueRef::Blob(&[0xab, 0x01])), "X'AB01'");
+        assert_eq!(sql_literal(ValueRef::Real(2.0)), "2.0");
+        assert_eq!(sql_literal(ValueRef::Real(f64::NEG_INFINITY)), "-1e999");
+        assert_eq!(sql_literal(ValueRef::Real(f64::NAN)), "NULL");
+        assert_eq!(quote_ident("a\"b"), "\"a\"\"b\"");
+    }
+}
@@ -1,7 +1,8 @@
 //! # `runtime:database` - Full-Featured SQLite Database Extension
 //!
 //! Provides complete SQLite database access for Forge applications with multiple named databases,
-//! transactions, prepared statements, result streaming, and schema migrations.
+//! transactions, prepared statements, result streaming, schema migrations, encryption at rest,
+//! online backups and SQL dumps.
 //!
 //! ## Overview
 //!
@@ -16,6 +17,10 @@
 //! - **Prepared Statements**: Compile SQL once, execute multiple times for performance
 //! - **Result Streaming**: Process large result sets in batches to avoid memory issues
 //! - **Schema Migrations**: Versioned up/down migrations with automatic tracking
+//! - **Encryption**: Optional SQLCipher-compatible encryption at rest, with rekeying
+//! - **Online Backups**: Copy a live database with the SQLite backup API and progress reporting
+//! - **Attach/Detach**: Query across managed databases with `ATTACH DATABASE`
+//! - **SQL Dumps**: Export a database as SQL text and import it elsewhere
 //! - **WAL Mode**: Write-Ahead Logging enabled by default for better concurrency
 //! - **Foreign Keys**: Foreign key constraints enabled by default for referential integrity
 //! - **Type Conversion**: Automatic conversion between SQLite and JavaScript types
@@ -23,7 +28,7 @@
 //!
 //! ## TypeScript API
 //!
-//! The extension exposes 31 operations through the `runtime:database` module organized into 7 categories:
+//! The extension exposes 39 operations through the `runtime:database` module organized into 8 categories:
 //!
 //! ### 1. Connection Management (6 ops)
 //! - `open(name, opts?)` - Open/create a database with configuration options
@@ -70,6 +75,16 @@
 //! - `migrationStatus(dbId)` - Get current version and applied/pending migrations
 //! - `migrateDown(dbId, targetVersion?)` - Rollback migrations (down SQL)
 //!
+//! ### 8. Encryption, Backup and Dumps (8 ops)
+//! - `rekey(dbId, key)` - Change the encryption key of an encrypted database
+//! - `backup(dbId, target, opts?)` - Start an online backup into another managed database
+//! - `backupProgress(backupId)` - Wait for the next backup progress update
+//! - `backupFinish(backupId)` - Wait for a backup to complete
+//! - `attach(dbId, name, alias, key?)` - Attach another managed database under an alias
+//! - `detach(dbId, alias)` - Detach an attached database
+//! - `exportSql(dbId, opts?)` - Export the database as a SQL dump
+//! - `importSql(dbId, sql)` - Run a SQL dump against the database
+//!
 //! ## TypeScript Usage Examples
 //!
 //! ### Basic Query Operations
@@ -168,6 +183,24 @@
 //! console.log(`Database at version ${status.currentVersion}`);
 //! ```
 //!
+//! ### Encryption and Backups
+//!
+//! ```typescript
+//! // Encrypted at rest; the key is needed every time the database is opened
+//! const db = await open("customers", { key: secret });
+//!
+//! // Copy the live database, re-encrypting the copy with its own key
+//! await db.backup("customers-backup", {
+//!   key: backupSecret,
+//!   onProgress: (p) => console.log(`${p.pagesDone}/${p.pagesTotal} pages`),
+//! });
+//!
+//! // Encrypt an existing plain database by moving its contents through a dump
+//! const plain = await open("legacy");
+//! const encrypted = await open("legacy-encrypted", { key: secret });
+//! await encrypted.importSql(await plain.exportSql());
+//! ```
+//!
 //! ## Database Location
 //!
 //! Databases are stored in platform-specific app data directories:
@@ -200,6 +233,8 @@
 //! | `8413` | MigrationError         | Migration failed or invalid version              |
 //! | `8414` | InvalidParameter       | Wrong parameter count or invalid value           |
 //! | `8415` | StreamError            | Stream is closed or invalid                      |
+//! | `8416` | EncryptionError        | Wrong key, or key operation on a plain database  |
+//! | `8417` | BackupError            | Backup target is open or cannot be written       |
 //!
 //! ## Database Features
 //!
@@ -221,6 +256,31 @@
 //!
 //! Disable with `foreignKeys: false` in open options if needed.
 //!
+//! ### Encryption
+//!
+//! Passing `key` in open options encrypts the database with SQLCipher (AES-256, with
+//! SQLCipher 4 defaults), so files are readable by other SQLCipher 4 tools. A `key` of the
+//! form `x'<64 hex digits>'` is used as a raw key instead of a passphrase.
+//! - Opening an encrypted database without its key, or with the wrong one, fails with `8416`
+//! - `rekey()` re-encrypts the database in place with a new key
+//! - A plain database can't be encrypted in place; export it and import the dump into a
+//!   database opened with a key
+//!
+//! Encryption needs the `encryption` cargo feature, which builds SQLCipher and a vendored
+//! OpenSSL from source; that adds a few minutes and a C toolchain requirement to clean builds.
+//! Cargo unifies features, so with it every crate in the build that links SQLite, such as
+//! `ext_storage`, uses SQLCipher too. Without the feature any `key` fails with `8416` rather
+//! than being ignored.
+//!
+//! ### Online Backups
+//!
+//! `backup()` copies the database into another managed database with the SQLite backup API.
+//! It reads through its own connection, so the app keeps working while the backup runs; if the
+//! database is written mid-backup, the copy restarts. The copy of an encrypted database is
+//! encrypted with the source key unless `key` gives another one. The backup API can't copy
+//! between plain and encrypted databases. The target can't be opened until the backup has
+//! finished.
+//!
 //! ### Busy Timeout
 //!
 //! Default: 5000ms (5 seconds)
@@ -301,7 +361,7 @@
 //!
 //! - [`ext_storage`](../ext_storage) - Simple key-value storage (simpler alternative for basic needs)
 //! - [`ext_crypto`](../ext_crypto) - Encryption for sensitive database fields
-//! - [`ext_fs`](../ext_fs) - File operations for database backup/restore
+//! - [`ext_fs`](../ext_fs) - File operations for moving backups and dumps out of the app directory
 
 use deno_core::{op2, Extension, OpState};
 use forge_weld_macro::{weld_op, weld_struct};
@@ -309,14 +369,23 @@ use rusqlite::{params_from_iter, Connection, Row, ToSql};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::debug;
 
+mod backup;
+mod dump;
+
+use backup::run_backup;
+pub use backup::{BackupOptions, BackupProgress};
+pub use dump::ExportOptions;
+use dump::{export_sql, import_sql, quote_ident};
+
 // =============================================================================
-// Error Types (8400-8415)
+// Error Types (8400-8417)
 // =============================================================================
 
 /// Error codes for database operations
@@ -339,6 +408,8 @@ pub enum DatabaseErrorCode {
     MigrationError = 8413,
     InvalidParameter = 8414,
     StreamError = 8415,
+    EncryptionError = 8416,
+    BackupError = 8417,
 }
 
 /// Database operation errors
@@ -407,6 +478,14 @@ pub enum DatabaseError {
     #[error("[{code}] Stream error: {message}")]
     #[class(generic)]
     StreamError { code: u32, message: String },
+
+    #[error("[{code}] Encryption error: {message}")]
+    #[class(generic)]
+    EncryptionError { code: u32, message: String },
+
+    #[error("[{code}] Backup error: {message}")]
+    #[class(generic)]
+    BackupError { code: u32, message: String },
 }
 
 impl DatabaseError {
@@ -466,6 +545,13 @@ impl DatabaseError {
         }
     }
 
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        Self::PermissionDenied {
+            code: DatabaseErrorCode::PermissionDenied as u32,
+            message: message.into(),
+        }
+    }
+
     pub fn io_error(message: impl Into<String>) -> Self {
         Self::IoError {
             code: DatabaseErrorCode::IoError as u32,
@@ -493,6 +579,20 @@ impl DatabaseError {
             message: message.into(),
         }
     }
+
+    pub fn encryption_error(message: impl Into<String>) -> Self {
+        Self::EncryptionError {
+            code: DatabaseErrorCode::EncryptionError as u32,
+            message: message.into(),
+        }
+    }
+
+    pub fn backup_error(message: impl Into<String>) -> Self {
+        Self::BackupError {
+            code: DatabaseErrorCode::BackupError as u32,
+            message: message.into(),
+        }
+    }
 }
 
 impl From<rusqlite::Error> for DatabaseError {
@@ -515,6 +615,11 @@ impl From<rusqlite::Error> for DatabaseError {
                         code: DatabaseErrorCode::TypeMismatch as u32,
                         message,
                     },
+                    // What SQLCipher reports for a missing or wrong key
+                    rusqlite::ErrorCode::NotADatabase => DatabaseError::encryption_error(format!(
+                        "{} (wrong key, or the database is encrypted)",
+                        message
+                    )),
                     _ => DatabaseError::generic(message),
                 }
             }
@@ -552,6 +657,8 @@ pub struct OpenOptions {
     pub busy_timeout_ms: Option<u32>,
     /// Enable foreign keys (default: true)
     pub foreign_keys: Option<bool>,
+    /// SQLCipher encryption key; a new database is created encrypted (default: none)
+    pub key: Option<String>,
 }
 
 /// Options for batch execution
@@ -590,6 +697,7 @@ pub struct DatabaseInfo {
     pub size_bytes: u64,
     pub tables: Vec<String>,
     pub readonly: bool,
+    pub encrypted: bool,
 }
 
 /// Column information
@@ -713,6 +821,22 @@ pub struct DatabaseHandle {
     pub path: PathBuf,
     pub readonly: bool,
     pub next_stmt_id: u64,
+    /// Encryption key, reused when backing up or reopening the file
+    key: Option<String>,
+}
+
+impl DatabaseHandle {
+    pub fn encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+}
+
+/// Online backup running on a blocking thread
+pub struct BackupJob {
+    /// Name of the database being written, reserved until the backup is finished
+    pub target: String,
+    pub progress: Arc<Mutex<tokio::sync::watch::Receiver<BackupProgress>>>,
+    pub task: tokio::task::JoinHandle<Result<BackupProgress, DatabaseError>>,
 }
 
 /// Streaming query state
@@ -730,8 +854,10 @@ pub struct StreamState {
 pub struct DatabaseState {
     pub databases: HashMap<String, DatabaseHandle>,
     pub streams: HashMap<String, StreamState>,
+    pub backups: HashMap<String, BackupJob>,
     pub next_db_id: u64,
     pub next_stream_id: u64,
+    pub next_backup_id: u64,
     pub max_connections: usize,
     pub app_identifier: String,
 }
@@ -741,8 +867,10 @@ impl DatabaseState {
         Self {
             databases: HashMap::new(),
             streams: HashMap::new(),
+            backups: HashMap::new(),
             next_db_id: 1,
             next_stream_id: 1,
+            next_backup_id: 1,
             max_connections,
             app_identifier,
         }
@@ -771,6 +899,28 @@ impl DatabaseState {
         self.next_stream_id += 1;
         id
     }
+
+    pub fn generate_backup_id(&mut self) -> String {
+        let id = format!("backup_{}", self.next_backup_id);
+        self.next_backup_id += 1;
+        id
+    }
+
+    /// Whether a managed database is currently open under any handle
+    pub fn is_open(&self, name: &str) -> bool {
+        self.databases.values().any(|handle| handle.name == name)
+    }
+
+    /// Fail if a backup is writing `name`; its file is replaced when it finishes
+    pub fn check_not_backup_target(&self, name: &str) -> Result<(), DatabaseError> {
+        if self.backups.values().any(|job| job.target == name) {
+            return Err(DatabaseError::backup_error(format!(
+                "Database '{}' is being written by a backup; wait for it to finish",
+                name
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// Capability checker for database operations
@@ -812,6 +962,84 @@ fn get_db_state_mut(state: &mut OpState) -> &mut DatabaseState {
     state.borrow_mut::<DatabaseState>()
 }
 
+fn check_database(state: &OpState, name: &str) -> Result<(), DatabaseError> {
+    if let Some(caps) = state.try_borrow::<DatabaseCapabilities>() {
+        caps.checker
+            .check_database(name)
+            .map_err(DatabaseError::permission_denied)
+    } else {
+        Ok(())
+    }
+}
+
+/// Plain SQLite files start with this; SQLCipher files start with a random salt
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Whether a database file on disk is encrypted
+///
+/// Empty and missing files count as plain: they have no content to protect yet.
+fn is_encrypted_file(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) => &header != SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+/// Check a key can be used; plain SQLite would silently ignore it
+fn check_key(key: &str) -> Result<(), DatabaseError> {
+    if key.is_empty() {
+        return Err(DatabaseError::invalid_parameter(
+            "Encryption key must not be empty",
+        ));
+    }
+    if !cfg!(feature = "encryption") {
+        return Err(DatabaseError::encryption_error(
+            "Encryption is not available; build ext_database with the `encryption` feature",
+        ));
+    }
+    Ok(())
+}
+
+/// Open a connection and, with a key, check it can read the file
+///
+/// An encrypted file is refused without a key.
+fn open_connection(
+    path: &Path,
+    flags: rusqlite::OpenFlags,
+    key: Option<&str>,
+) -> Result<Connection, DatabaseError> {
+    if key.is_none() && is_encrypted_file(path) {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        return Err(DatabaseError::encryption_error(format!(
+            "Database '{}' is encrypted; open it with its key",
+            name
+        )));
+    }
+    let conn = Connection::open_with_flags(path, flags)?;
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", key)?;
+        // SQLCipher only notices a wrong key on the first read
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))?;
+    }
+    Ok(conn)
+}
+
+/// Re-encrypt the database behind `conn` with a new key
+fn rekey_connection(conn: &Connection, key: &str) -> Result<(), DatabaseError> {
+    conn.pragma_update(None, "rekey", key)?;
+    Ok(())
+}
+
+fn get_connection(state: &OpState, db_id: &str) -> Result<Arc<Mutex<Connection>>, DatabaseError> {
+    let db_state = get_db_state(state);
+    let handle = db_state
+        .databases
+        .get(db_id)
+        .ok_or_else(|| DatabaseError::invalid_handle(format!("Database '{}' not found", db_id)))?;
+    Ok(handle.connection.clone())
+}
+
 fn json_to_sql_params(params: &[serde_json::Value]) -> Vec<Box<dyn ToSql>> {
     params
         .iter()
@@ -883,10 +1111,16 @@ pub async fn op_database_open(
     let wal_mode = opts.wal_mode.unwrap_or(true);
     let busy_timeout_ms = opts.busy_timeout_ms.unwrap_or(5000);
     let foreign_keys = opts.foreign_keys.unwrap_or(true);
+    let key = opts.key;
+    if let Some(key) = &key {
+        check_key(key)?;
+    }
 
     let (db_dir, db_id, db_path) = {
         let mut s = state.borrow_mut();
+        check_database(&s, &name)?;
         let db_state = get_db_state_mut(&mut s);
+        db_state.check_not_backup_target(&name)?;
 
         if !db_state.can_open() {
             return Err(DatabaseError::too_many_connections(format!(
@@ -914,10 +1148,11 @@ pub async fn op_database_open(
         )));
     }
 
-    debug!(name = %name, path = %db_path.display(), "database.open");
+    debug!(name = %name, path = %db_path.display(), encrypted = key.is_some(), "database.open");
 
     // Open connection in blocking task
     let path_clone = db_path.clone();
+    let key_clone = key.clone();
     let connection = tokio::task::spawn_blocking(move || -> Result<Connection, DatabaseError> {
         let flags = if readonly {
             rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY
@@ -925,7 +1160,8 @@ pub async fn op_database_open(
             rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE | rusqlite::OpenFlags::SQLITE_OPEN_CREATE
         };
 
-        let conn = Connection::open_with_flags(&path_clone, flags)?;
+        // The key has to be set before anything else reads the file
+        let conn = open_connection(&path_clone, flags, key_clone.as_deref())?;
 
         // Configure connection
         conn.busy_timeout(std::time::Duration::from_millis(busy_timeout_ms as u64))?;
@@ -935,7 +1171,8 @@ pub async fn op_database_open(
         }
 
         if wal_mode && !readonly {
-            conn.execute("PRAGMA journal_mode = WAL", [])?;
+            // Setting journal_mode returns the new mode as a row, which execute() rejects
+            conn.pragma_update(None, "journal_mode", "WAL")?;
         }
 
         Ok(conn)
@@ -949,6 +1186,8 @@ pub async fn op_database_open(
     {
         let mut s = state.borrow_mut();
         let db_state = get_db_state_mut(&mut s);
+        // A backup into this database may have started while it was opening
+        db_state.check_not_backup_target(&name)?;
         db_state.databases.insert(
             db_id.clone(),
             DatabaseHandle {
@@ -957,6 +1196,7 @@ pub async fn op_database_open(
                 path: db_path,
                 readonly,
                 next_stmt_id: 1,
+                key,
             },
         );
     }
@@ -1018,13 +1258,20 @@ pub async fn op_database_list(
                 .and_then(|s| s.to_str())
                 .unwrap_or("")
                 .to_string();
+            // Databases the app may not open are not listed either
+            if check_database(&state.borrow(), &name).is_err() {
+                continue;
+            }
             let metadata = tokio::fs::metadata(&path).await?;
             let size_bytes = metadata.len();
 
-            // Get table list from database
+            // Get table list from database; encrypted ones can't be read without their key
             let path_clone = path.clone();
-            let tables =
-                tokio::task::spawn_blocking(move || -> Result<Vec<String>, DatabaseError> {
+            let (tables, encrypted) =
+                tokio::task::spawn_blocking(move || -> Result<(Vec<String>, bool), DatabaseError> {
+                    if is_encrypted_file(&path_clone) {
+                        return Ok((Vec::new(), true));
+                    }
                     let conn = Connection::open_with_flags(
                         &path_clone,
                         rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
@@ -1036,7 +1283,7 @@ pub async fn op_database_list(
                         .query_map([], |row| row.get(0))?
                         .filter_map(|r| r.ok())
                         .collect();
-                    Ok(tables)
+                    Ok((tables, false))
                 })
                 .await
                 .map_err(|e| DatabaseError::generic(e.to_string()))??;
@@ -1047,6 +1294,7 @@ pub async fn op_database_list(
                 size_bytes,
                 tables,
                 readonly: false,
+                encrypted,
             });
         }
     }
@@ -1062,6 +1310,7 @@ pub async fn op_database_delete(
 ) -> Result<bool, DatabaseError> {
     let db_path = {
         let s = state.borrow();
+        check_database(&s, &name)?;
         let db_state = get_db_state(&s);
         db_state.get_database_dir().join(format!("{}.db", name))
     };
@@ -1090,6 +1339,7 @@ pub async fn op_database_exists(
 ) -> Result<bool, DatabaseError> {
     let db_path = {
         let s = state.borrow();
+        check_database(&s, &name)?;
         let db_state = get_db_state(&s);
         db_state.get_database_dir().join(format!("{}.db", name))
     };
@@ -1101,6 +1351,7 @@ pub async fn op_database_exists(
 #[op2]
 #[string]
 pub fn op_database_path(state: &OpState, #[string] name: String) -> Result<String, DatabaseError> {
+    check_database(state, &name)?;
     let db_state = get_db_state(state);
     let db_path = db_state.get_database_dir().join(format!("{}.db", name));
     Ok(db_path.to_string_lossy().to_string())
@@ -2157,6 +2408,345 @@ pub async fn op_database_migrate_down(
     .map_err(|e| DatabaseError::generic(e.to_string()))?
 }
 
+// =============================================================================
+// Encryption, Backup and Dump Operations
+// =============================================================================
+
+/// Re-encrypt an encrypted database with a new key
+#[weld_op(async)]
+#[op2(async)]
+pub async fn op_database_rekey(
+    state: Rc<RefCell<OpState>>,
+    #[string] db_id: String,
+    #[string] key: String,
+) -> Result<(), DatabaseError> {
+    check_key(&key)?;
+    let conn = {
+        let s = state.borrow();
+        let db_state = get_db_state(&s);
+        let handle = db_state.databases.get(&db_id).ok_or_else(|| {
+            DatabaseError::invalid_handle(format!("Database '{}' not found", db_id))
+        })?;
+        if handle.readonly {
+            return Err(DatabaseError::permission_denied(format!(
+                "Database '{}' is open read-only",
+                handle.name
+            )));
+        }
+        if !handle.encrypted() {
+            return Err(DatabaseError::encryption_error(format!(
+                "Database '{}' is not encrypted; import a dump of it into a database opened with a key",
+                handle.name
+            )));
+        }
+        handle.connection.clone()
+    };
+
+    debug!(db_id = %db_id, "database.rekey");
+
+    let new_key = key.clone();
+    tokio::task::spawn_blocking(move || rekey_connection(&conn.blocking_lock(), &new_key))
+        .await
+        .map_err(|e| DatabaseError::generic(e.to_string()))??;
+
+    let mut s = state.borrow_mut();
+    if let Some(handle) = get_db_state_mut(&mut s).databases.get_mut(&db_id) {
+        handle.key = Some(key);
+    }
+    Ok(())
+}
+
+/// Start an online backup into another managed database and return its ID
+///
+/// The copy is written next to the target and renamed over it once complete,
+/// so an interrupted backup leaves the previous one intact.
+#[weld_op(async)]
+#[op2(async)]
+#[string]
+pub async fn op_database_backup(
+    state: Rc<RefCell<OpState>>,
+    #[string] db_id: String,
+    #[string] target: String,
+    #[serde] opts: Option<BackupOptions>,
+) -> Result<String, DatabaseError> {
+    let opts = opts.unwrap_or_default();
+    if let Some(key) = &opts.key {
+        check_key(key)?;
+    }
+
+    let (src_path, src_key, dst_path, backup_id) = {
+        let mut s = state.borrow_mut();
+        check_database(&s, &target)?;
+        let db_state = get_db_state_mut(&mut s);
+        let handle = db_state.databases.get(&db_id).ok_or_else(|| {
+            DatabaseError::invalid_handle(format!("Database '{}' not found", db_id))
+        })?;
+        let (src_path, src_key) = (handle.path.clone(), handle.key.clone());
+        if db_state.is_open(&target) {
+            return Err(DatabaseError::backup_error(format!(
+                "Database '{}' is open; close it before backing up into it",
+                target
+            )));
+        }
+        db_state.check_not_backup_target(&target)?;
+        let dst_path = db_state.get_database_dir().join(format!("{}.db", target));
+        (src_path, src_key, dst_path, db_state.generate_backup_id())
+    };
+
+    if src_key.is_none() && opts.key.is_some() {
+        return Err(DatabaseError::encryption_error(
+            "A backup can't encrypt a plain database; import a dump of it into a database opened with a key",
+        ));
+    }
+    let dst_key = opts.key.clone().or_else(|| src_key.clone());
+
+    debug!(db_id = %db_id, backup_id = %backup_id, target = %target, "database.backup");
+
+    let (tx, rx) = tokio::sync::watch::channel(BackupProgress::default());
+    let task = tokio::task::spawn_blocking(move || {
+        backup_file(
+            &src_path,
+            src_key.as_deref(),
+            &dst_path,
+            dst_key.as_deref(),
+            &opts,
+            &tx,
+        )
+    });
+
+    let mut s = state.borrow_mut();
+    get_db_state_mut(&mut s).backups.insert(
+        backup_id.clone(),
+        BackupJob {
+            target,
+            progress: Arc::new(Mutex::new(rx)),
+            task,
+        },
+    );
+    Ok(backup_id)
+}
+
+/// Back up the database at `src_path` into `dst_path`
+///
+/// The copy is written next to the target and renamed over it once complete.
+fn backup_file(
+    src_path: &Path,
+    src_key: Option<&str>,
+    dst_path: &Path,
+    dst_key: Option<&str>,
+    opts: &BackupOptions,
+    progress: &tokio::sync::watch::Sender<BackupProgress>,
+) -> Result<BackupProgress, DatabaseError> {
+    let tmp_path = dst_path.with_extension("db-backup");
+    let _ = std::fs::remove_file(&tmp_path);
+
+    let result = (|| {
+        let src = open_connection(
+            src_path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+            src_key,
+        )?;
+        let mut dst = open_connection(
+            &tmp_path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE | rusqlite::OpenFlags::SQLITE_OPEN_CREATE,
+            dst_key,
+        )?;
+        run_backup(&src, &mut dst, opts, progress)
+    })();
+
+    match result {
+        Ok(progress) => {
+            // A stale WAL of the old target would be replayed into the new copy
+            let _ = std::fs::remove_file(dst_path.with_extension("db-wal"));
+            let _ = std::fs::remove_file(dst_path.with_extension("db-shm"));
+            std::fs::rename(&tmp_path, dst_path)?;
+            Ok(progress)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+/// Wait for the next progress update of a backup
+///
+/// Returns `None` once the backup has finished; intermediate updates a slow
+/// caller misses are skipped.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+pub async fn op_database_backup_progress(
+    state: Rc<RefCell<OpState>>,
+    #[string] backup_id: String,
+) -> Result<Option<BackupProgress>, DatabaseError> {
+    let progress = {
+        let s = state.borrow();
+        let db_state = get_db_state(&s);
+        let job = db_state.backups.get(&backup_id).ok_or_else(|| {
+            DatabaseError::backup_error(format!("Backup '{}' not found", backup_id))
+        })?;
+        job.progress.clone()
+    };
+
+    let mut progress = progress.lock().await;
+    Ok(match progress.changed().await {
+        Ok(()) => Some(progress.borrow_and_update().clone()),
+        Err(_) => None,
+    })
+}
+
+/// Wait for a backup to finish and return its final progress
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+pub async fn op_database_backup_finish(
+    state: Rc<RefCell<OpState>>,
+    #[string] backup_id: String,
+) -> Result<BackupProgress, DatabaseError> {
+    let job = {
+        let mut s = state.borrow_mut();
+        let db_state = get_db_state_mut(&mut s);
+        db_state.backups.remove(&backup_id).ok_or_else(|| {
+            DatabaseError::backup_error(format!("Backup '{}' not found", backup_id))
+        })?
+    };
+
+    debug!(backup_id = %backup_id, "database.backup_finish");
+    job.task
+        .await
+        .map_err(|e| DatabaseError::generic(e.to_string()))?
+}
+
+/// Attach another managed database to a connection under an alias
+///
+/// Without a key, SQLCipher uses the connection's own key; an empty key
+/// attaches a plain database to an encrypted one.
+#[weld_op(async)]
+#[op2(async)]
+pub async fn op_database_attach(
+    state: Rc<RefCell<OpState>>,
+    #[string] db_id: String,
+    #[string] name: String,
+    #[string] alias: String,
+    #[string] key: Option<String>,
+) -> Result<(), DatabaseError> {
+    // An empty key is allowed: it attaches a plain database
+    if let Some(key) = key.as_deref().filter(|key| !key.is_empty()) {
+        check_key(key)?;
+    }
+    let (conn, path) = {
+        let s = state.borrow();
+        check_database(&s, &name)?;
+        let conn = get_connection(&s, &db_id)?;
+        let path = get_db_state(&s)
+            .get_database_dir()
+            .join(format!("{}.db", name));
+        (conn, path)
+    };
+
+    // ATTACH would quietly create a missing file
+    if !path.exists() {
+        return Err(DatabaseError::not_found(format!(
+            "Database '{}' not found",
+            name
+        )));
+    }
+
+    debug!(db_id = %db_id, name = %name, alias = %alias, "database.attach");
+
+    tokio::task::spawn_blocking(move || {
+        attach_database(&conn.blocking_lock(), &path, &alias, key.as_deref())
+    })
+    .await
+    .map_err(|e| DatabaseError::generic(e.to_string()))??;
+
+    Ok(())
+}
+
+fn attach_database(
+    conn: &Connection,
+    path: &Path,
+    alias: &str,
+    key: Option<&str>,
+) -> Result<(), DatabaseError> {
+    let path = path.to_string_lossy().to_string();
+    let sql = format!("ATTACH DATABASE ?1 AS {}", quote_ident(alias));
+    // Plain SQLite has no KEY clause; an empty key already means no encryption
+    match key.filter(|key| cfg!(feature = "encryption") || !key.is_empty()) {
+        Some(key) => conn.execute(&format!("{} KEY ?2", sql), [path.as_str(), key])?,
+        None => conn.execute(&sql, [path])?,
+    };
+    Ok(())
+}
+
+/// Detach a database attached with `op_database_attach`
+#[weld_op(async)]
+#[op2(async)]
+pub async fn op_database_detach(
+    state: Rc<RefCell<OpState>>,
+    #[string] db_id: String,
+    #[string] alias: String,
+) -> Result<(), DatabaseError> {
+    let conn = get_connection(&state.borrow(), &db_id)?;
+
+    debug!(db_id = %db_id, alias = %alias, "database.detach");
+
+    tokio::task::spawn_blocking(move || {
+        let conn = conn.blocking_lock();
+        conn.execute(&format!("DETACH DATABASE {}", quote_ident(&alias)), [])?;
+        Ok::<_, DatabaseError>(())
+    })
+    .await
+    .map_err(|e| DatabaseError::generic(e.to_string()))??;
+
+    Ok(())
+}
+
+/// Export a database as a SQL dump
+///
+/// The dump is plain text, even for an encrypted database.
+#[weld_op(async)]
+#[op2(async)]
+#[string]
+pub async fn op_database_export(
+    state: Rc<RefCell<OpState>>,
+    #[string] db_id: String,
+    #[serde] opts: Option<ExportOptions>,
+) -> Result<String, DatabaseError> {
+    let conn = get_connection(&state.borrow(), &db_id)?;
+    let opts = opts.unwrap_or_default();
+
+    debug!(db_id = %db_id, ?opts, "database.export");
+
+    tokio::task::spawn_blocking(move || {
+        let conn = conn.blocking_lock();
+        export_sql(&conn, &opts)
+    })
+    .await
+    .map_err(|e| DatabaseError::generic(e.to_string()))?
+}
+
+/// Run a SQL dump against a database, rolling back if it fails
+#[weld_op(async)]
+#[op2(async)]
+pub async fn op_database_import(
+    state: Rc<RefCell<OpState>>,
+    #[string] db_id: String,
+    #[string] sql: String,
+) -> Result<(), DatabaseError> {
+    let conn = get_connection(&state.borrow(), &db_id)?;
+
+    debug!(db_id = %db_id, bytes = sql.len(), "database.import");
+
+    tokio::task::spawn_blocking(move || {
+        let conn = conn.blocking_lock();
+        import_sql(&conn, &sql)
+    })
+    .await
+    .map_err(|e| DatabaseError::generic(e.to_string()))?
+}
+
 // =============================================================================
 // State Initialization
 // =============================================================================
@@ -2186,3 +2776,157 @@ include!(concat!(env!("OUT_DIR"), "/extension.rs"));
 pub fn database_extension() -> Extension {
     runtime_database::ext()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_write() -> rusqlite::OpenFlags {
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE | rusqlite::OpenFlags::SQLITE_OPEN_CREATE
+    }
+
+    #[cfg(feature = "encryption")]
+    fn is_encryption_error(result: Result<Connection, DatabaseError>) -> bool {
+        matches!(
+            result,
+            Err(DatabaseError::EncryptionError { code: 8416, .. })
+        )
+    }
+
+    #[cfg(feature = "encryption")]
+    fn encrypted_db(path: &Path, key: &str) {
+        let conn = open_connection(path, read_write(), Some(key)).unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('secret');")
+            .unwrap();
+    }
+
+    fn read_value(conn: &Connection, table: &str) -> String {
+        conn.query_row(&format!("SELECT v FROM {}", table), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn open_needs_the_right_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.db");
+        encrypted_db(&path, "hunter2");
+
+        assert!(is_encrypted_file(&path));
+        assert!(is_encryption_error(open_connection(
+            &path,
+            read_write(),
+            Some("wrong")
+        )));
+        assert!(is_encryption_error(open_connection(
+            &path,
+            read_write(),
+            None
+        )));
+        let conn = open_connection(&path, read_write(), Some("hunter2")).unwrap();
+        assert_eq!(read_value(&conn, "t"), "secret");
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn rekey_replaces_the_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.db");
+        encrypted_db(&path, "old");
+
+        let conn = open_connection(&path, read_write(), Some("old")).unwrap();
+        rekey_connection(&conn, "new").unwrap();
+        drop(conn);
+
+        assert!(is_encryption_error(open_connection(
+            &path,
+            read_write(),
+            Some("old")
+        )));
+        let conn = open_connection(&path, read_write(), Some("new")).unwrap();
+        assert_eq!(read_value(&conn, "t"), "secret");
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn attach_encrypted_database_with_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.db");
+        encrypted_db(&path, "hunter2");
+
+        let conn = open_connection(&dir.path().join("main.db"), read_write(), None).unwrap();
+        attach_database(&conn, &path, "vault", Some("hunter2")).unwrap();
+        assert_eq!(read_value(&conn, "vault.t"), "secret");
+    }
+
+    #[test]
+    fn attach_plain_database_with_empty_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.db");
+        let conn = open_connection(&path, read_write(), None).unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('open');")
+            .unwrap();
+        drop(conn);
+
+        let conn = open_connection(&dir.path().join("main.db"), read_write(), None).unwrap();
+        attach_database(&conn, &path, "plain", Some("")).unwrap();
+        assert_eq!(read_value(&conn, "plain.t"), "open");
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn backup_encrypted_database_with_another_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dst) = (dir.path().join("vault.db"), dir.path().join("copy.db"));
+        encrypted_db(&src, "hunter2");
+
+        let (tx, _rx) = tokio::sync::watch::channel(BackupProgress::default());
+        let opts = BackupOptions::default();
+        backup_file(&src, Some("hunter2"), &dst, Some("other"), &opts, &tx).unwrap();
+
+        assert!(!dir.path().join("copy.db-backup").exists());
+        assert!(is_encryption_error(open_connection(
+            &dst,
+            read_write(),
+            Some("hunter2")
+        )));
+        let conn = open_connection(&dst, read_write(), Some("other")).unwrap();
+        assert_eq!(read_value(&conn, "t"), "secret");
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    #[test]
+    fn keys_need_the_encryption_feature() {
+        assert!(matches!(
+            check_key("hunter2"),
+            Err(DatabaseError::EncryptionError { code: 8416, .. })
+        ));
+    }
+
+    #[test]
+    fn backup_target_is_reserved_until_finished() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let mut state = DatabaseState::new("test".to_string(), 10);
+        let (_tx, rx) = tokio::sync::watch::channel(BackupProgress::default());
+        let task = runtime.spawn_blocking(|| Ok(BackupProgress::default()));
+        state.backups.insert(
+            "backup-1".to_string(),
+            BackupJob {
+                target: "copy".to_string(),
+                progress: Arc::new(Mutex::new(rx)),
+                task,
+            },
+        );
+
+        assert!(matches!(
+            state.check_not_backup_target("copy"),
+            Err(DatabaseError::BackupError { code: 8417, .. })
+        ));
+        assert!(state.check_not_backup_target("other").is_ok());
+
+        state.backups.remove("backup-1");
+        assert!(state.check_not_backup_target("copy").is_ok());
+    }
+}
@@ -27,6 +27,39 @@ fn main() {
             "op_fs_real_path",
             "op_fs_temp_file",
             "op_fs_temp_dir",
+            // File handles
+            "op_fs_open",
+            "op_fs_file_read",
+            "op_fs_file_write",
+            "op_fs_file_seek",
+            "op_fs_file_truncate",
+            "op_fs_file_sync",
+            "op_fs_file_stat",
+            "op_fs_file_close",
+            // File streams
+            "op_fs_read_stream_open",
+            "op_fs_read_stream_next",
+            "op_fs_read_stream_close",
+            "op_fs_write_stream_open",
+            "op_fs_write_stream_write",
+            "op_fs_write_stream_close",
+            "op_fs_write_stream_abort",
+            // Walk, glob and bulk operations
+            "op_fs_walk_open",
+            "op_fs_walk_next",
+            "op_fs_walk_close",
+            "op_fs_glob",
+            "op_fs_copy_dir",
+            "op_fs_move",
+            "op_fs_transfer_progress",
+            "op_fs_transfer_finish",
+            // Permissions, ownership, timestamps and locks
+            "op_fs_chmod",
+            "op_fs_chown",
+            "op_fs_utime",
+            "op_fs_link",
+            "op_fs_lock",
+            "op_fs_unlock",
         ])
         .generate_sdk_module("sdk")
         .use_inventory_types()
@@ -7,9 +7,12 @@
 //!
 //! This extension provides a complete filesystem API including:
 //! - **File I/O**: Text and binary read/write operations
+//! - **File Handles**: Random-access reads and writes on open files
 //! - **Directory Management**: Create, read, and remove directories
+//! - **Walking**: Recursive walks, globbing, and directory copy/move
 //! - **File Watching**: Real-time filesystem event notifications
-//! - **Symbolic Links**: Create and resolve symlinks
+//! - **Symbolic Links**: Create and resolve symlinks, and create hard links
+//! - **Permissions & Locks**: chmod, chown, timestamps and advisory file locks
 //! - **Metadata Access**: File stats, timestamps, and permissions
 //! - **Temporary Files**: Secure temporary file and directory creation
 //! - **Path Resolution**: Canonical path resolution with symlink following
@@ -25,6 +28,20 @@
 //! - `readBytes()` / `writeBytes()` - Binary data operations
 //! - `appendTextFile()` / `appendBytes()` - Append to existing files
 //!
+//! ### File Handles
+//! Random access without loading whole files into memory:
+//! - `open()` - Open a file with read/write/append/create/truncate flags
+//! - `file.read()` / `file.write()` - Read into a buffer or write at the current position
+//! - `file.seek()` / `file.truncate()` / `file.sync()` / `file.stat()`
+//! - `file.close()` - Handles are also closed when garbage-collected
+//!
+//! ### File Streams
+//! Chunked reads and writes without buffering whole files:
+//! - `openReadStream()` - Async-iterable chunks with configurable size and byte range
+//! - `openWriteStream()` / `writeStream()` - Write chunks as they are produced
+//! - `toReadableStream()` / `toWritableStream()` adapt them to Web Streams
+//!   when the runtime provides those globals
+//!
 //! ### Directory Operations
 //! Managing filesystem directory structures:
 //! - `readDir()` - List directory contents
@@ -33,6 +50,16 @@
 //! - `copy()` - Copy files
 //! - `rename()` - Move/rename files or directories
 //!
+//! ### Walking and Bulk Operations
+//! Recursive operations running on blocking threads:
+//! - `walk()` - Async iterator over a tree with depth limits, include/exclude
+//!   globs, symlink following and `.gitignore` support
+//! - `glob()` - Paths matching a glob pattern
+//! - `copyDir()` / `move()` - Copy or move trees with progress callbacks
+//!
+//! Every path visited goes through the capability checker; see the `walk`
+//! module for how denied entries and symlinks are handled.
+//!
 //! ### Metadata & Information
 //! Querying filesystem entity properties:
 //! - `stat()` - Get file statistics (size, type, readonly)
@@ -44,6 +71,8 @@
 //! - `watch()` - Watch file or directory for changes
 //! - Returns async iterator yielding `WatchEvent` objects
 //! - Events: create, modify, remove, rename
+//! - Changes are debounced per path, filtered by include/exclude globs, and
+//!   rename halves are paired into one event with `from`/`to`
 //!
 //! ### Symbolic Links
 //! Cross-platform symlink support:
@@ -51,6 +80,14 @@
 //! - `readLink()` - Read symlink target
 //! - `realPath()` - Resolve to canonical absolute path
 //!
+//! ### Permissions, Ownership and Locks
+//! Changing file attributes and coordinating processes:
+//! - `chmod()` / `chown()` - Permission bits and ownership (`chown` is Unix only)
+//! - `utime()` - Set access and modification times
+//! - `link()` - Create hard links
+//! - `lock()` / `tryLock()` - `flock`-style shared or exclusive advisory locks,
+//!   with an optional timeout; waiting polls without blocking the event loop
+//!
 //! ### Temporary Files
 //! Secure temporary filesystem entities:
 //! - `tempFile()` - Create temporary file with optional prefix/suffix
@@ -163,7 +200,7 @@
 //!
 //! ## Error Codes
 //!
-//! All filesystem operations use structured error codes (3000-3009 range):
+//! All filesystem operations use structured error codes (3000-3013 range):
 //!
 //! | Code | Error | Description |
 //! |------|-------|-------------|
@@ -177,6 +214,10 @@
 //! | 3007 | `InvalidWatchId` | Invalid or closed watch ID |
 //! | 3008 | `Symlink` | Symbolic link creation or resolution error |
 //! | 3009 | `TempError` | Temporary file/directory creation error |
+//! | 3010 | `InvalidHandle` | Invalid or closed file handle |
+//! | 3011 | `InvalidPattern` | Invalid glob pattern |
+//! | 3012 | `LockTimeout` | Advisory lock not acquired before the timeout |
+//! | 3013 | `Unsupported` | Operation not supported on this platform |
 //!
 //! ## Permission System
 //!
@@ -228,6 +269,7 @@
 //! ### State Management
 //! This extension maintains state in `OpState`:
 //! - `FsWatchState`: Manages active file watchers and their receivers
+//! - Open file handles, streams, walks, transfers and locks live in the `ResourceTable`
 //! - `FsCapabilities`: Holds the capability checker for permission validation
 //!
 //! ### Async I/O
@@ -236,6 +278,8 @@
 //!
 //! ### File Watching
 //! Implemented using the `notify` crate with platform-optimal backends:
+//! - Raw events are debounced and merged on a watcher thread by the `watch`
+//!   module; [`watch_debounced`] is also used by the runtime's HMR server
 //! - Watchers use async channels (`mpsc::Receiver`) for event delivery
 //! - Events are buffered (64-event capacity) to handle bursts
 //! - Must call `close()` to clean up resources and stop the watcher
@@ -251,8 +295,8 @@
 //! - [`ext_process`](../ext_process/index.html) - Child process management
 //! - [`ext_storage`](../ext_storage/index.html) - Persistent key-value storage
 
-use deno_core::{op2, Extension, OpState};
-use forge_weld_macro::{weld_op, weld_struct};
+use deno_core::{op2, Extension, JsBuffer, OpState, Resource, ResourceId};
+use forge_weld_macro::{weld_enum, weld_op, weld_struct};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -261,6 +305,16 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::debug;
 
+mod walk;
+mod watch;
+
+use walk::{glob_walk_opts, plan_transfer, run_copy, run_move, split_glob, Walker};
+pub use walk::{TransferOpts, TransferProgress, WalkEntry, WalkOpts};
+pub use watch::{
+    watch_debounced, WatchEvent, WatchEventKind, WatchOpts, DEFAULT_WATCH_DEBOUNCE_MS,
+    MAX_WAIT_WINDOWS,
+};
+
 // ============================================================================
 // Error Types with Structured Codes
 // ============================================================================
@@ -289,6 +343,14 @@ pub enum FsErrorCode {
     Symlink = 3008,
     /// Temp file/dir error
     TempError = 3009,
+    /// Invalid or closed file handle
+    InvalidHandle = 3010,
+    /// Invalid glob pattern
+    InvalidPattern = 3011,
+    /// Lock not acquired before the timeout
+    LockTimeout = 3012,
+    /// Operation not supported on this platform
+    Unsupported = 3013,
 }
 
 /// Custom error type for FS operations
@@ -333,6 +395,22 @@ pub enum FsError {
     #[error("[{code}] Temp error: {message}")]
     #[class(generic)]
     TempError { code: u32, message: String },
+
+    #[error("[{code}] Invalid file handle: {message}")]
+    #[class(generic)]
+    InvalidHandle { code: u32, message: String },
+
+    #[error("[{code}] Invalid pattern: {message}")]
+    #[class(generic)]
+    InvalidPattern { code: u32, message: String },
+
+    #[error("[{code}] Lock timeout: {message}")]
+    #[class(generic)]
+    LockTimeout { code: u32, message: String },
+
+    #[error("[{code}] Unsupported: {message}")]
+    #[class(generic)]
+    Unsupported { code: u32, message: String },
 }
 
 impl FsError {
@@ -405,6 +483,34 @@ impl FsError {
             message: message.into(),
         }
     }
+
+    pub fn invalid_handle(message: impl Into<String>) -> Self {
+        Self::InvalidHandle {
+            code: FsErrorCode::InvalidHandle as u32,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_pattern(message: impl Into<String>) -> Self {
+        Self::InvalidPattern {
+            code: FsErrorCode::InvalidPattern as u32,
+            message: message.into(),
+        }
+    }
+
+    pub fn lock_timeout(message: impl Into<String>) -> Self {
+        Self::LockTimeout {
+            code: FsErrorCode::LockTimeout as u32,
+            message: message.into(),
+        }
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::Unsupported {
+            code: FsErrorCode::Unsupported as u32,
+            message: message.into(),
+        }
+    }
 }
 
 impl From<std::io::Error> for FsError {
@@ -414,6 +520,8 @@ impl From<std::io::Error> for FsError {
             std::io::ErrorKind::AlreadyExists => Self::already_exists(e.to_string()),
             std::io::ErrorKind::PermissionDenied => Self::permission_denied(e.to_string()),
             std::io::ErrorKind::IsADirectory => Self::is_directory(e.to_string()),
+            std::io::ErrorKind::NotADirectory => Self::is_file(e.to_string()),
+            std::io::ErrorKind::Unsupported => Self::unsupported(e.to_string()),
             _ => Self::io(e.to_string()),
         }
     }
@@ -423,17 +531,9 @@ impl From<std::io::Error> for FsError {
 // Types
 // ============================================================================
 
-/// File event from watch
-#[weld_struct]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileEvent {
-    pub kind: String,
-    pub paths: Vec<String>,
-}
-
 /// Entry for a file watcher
 pub struct WatchEntry {
-    pub receiver: mpsc::Receiver<FileEvent>,
+    pub receiver: mpsc::Receiver<WatchEvent>,
     pub watcher: notify::RecommendedWatcher,
 }
 
@@ -483,6 +583,85 @@ pub struct RemoveOpts {
     pub recursive: Option<bool>,
 }
 
+/// Options for opening a file handle
+#[weld_struct]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOpts {
+    pub read: Option<bool>,
+    pub write: Option<bool>,
+    pub append: Option<bool>,
+    pub create: Option<bool>,
+    pub create_new: Option<bool>,
+    pub truncate: Option<bool>,
+}
+
+impl OpenOpts {
+    /// Whether the handle reads and/or modifies the file
+    ///
+    /// With no flags set the file is opened read-only.
+    fn access(&self) -> (bool, bool) {
+        let write = self.write.unwrap_or(false) || self.append.unwrap_or(false);
+        let modifies = write
+            || self.create.unwrap_or(false)
+            || self.create_new.unwrap_or(false)
+            || self.truncate.unwrap_or(false);
+        (self.read.unwrap_or(!write), modifies)
+    }
+}
+
+/// Options for streaming a file's contents
+#[weld_struct]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadStreamOpts {
+    /// Bytes per chunk (default: 64 KiB)
+    pub chunk_size: Option<usize>,
+    /// Offset to start reading at
+    pub start: Option<u64>,
+    /// Offset to stop reading at (exclusive)
+    pub end: Option<u64>,
+}
+
+/// Options for streaming data into a file
+#[weld_struct]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteStreamOpts {
+    /// Append to the file instead of replacing its contents
+    pub append: Option<bool>,
+    /// Create the file if missing (default: true)
+    pub create: Option<bool>,
+    /// Fail if the file already exists
+    pub create_new: Option<bool>,
+}
+
+/// Options for acquiring an advisory file lock
+#[weld_struct]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockOpts {
+    /// Exclusive (writer) lock instead of a shared (reader) lock (default: true)
+    pub exclusive: Option<bool>,
+    /// Wait while another process holds a conflicting lock (default: true)
+    pub wait: Option<bool>,
+    /// Give up waiting after this many milliseconds
+    pub timeout: Option<u64>,
+    /// Create the lock file if it does not exist (default: true)
+    pub create: Option<bool>,
+}
+
+/// Origin of a seek on a file handle
+#[weld_enum]
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SeekWhence {
+    #[default]
+    Start,
+    Current,
+    End,
+}
+
 /// Extended file metadata with timestamps and permissions
 #[weld_struct]
 #[derive(Debug, Serialize)]
@@ -578,6 +757,14 @@ fn check_fs_write(state: &OpState, path: &str) -> Result<(), FsError> {
     }
 }
 
+/// Capability checker for work done off the JS thread
+fn fs_checker(state: &OpState) -> Arc<dyn FsCapabilityChecker> {
+    match state.try_borrow::<FsCapabilities>() {
+        Some(caps) => caps.checker.clone(),
+        None => Arc::new(PermissiveChecker),
+    }
+}
+
 // ============================================================================
 // Operations
 // ============================================================================
@@ -810,47 +997,30 @@ async fn op_fs_exists(
     Ok(tokio::fs::try_exists(&path).await.unwrap_or(false))
 }
 
-// File watching operations using notify crate
+// File watching operations, debounced by the watch module
 #[weld_op(async)]
 #[op2(async)]
 #[string]
 async fn op_fs_watch(
     state: Rc<RefCell<OpState>>,
     #[string] path: String,
+    #[serde] opts: WatchOpts,
 ) -> Result<String, FsError> {
-    use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
-    use std::path::Path;
-
     // Check capabilities
     {
         let s = state.borrow();
         check_fs_read(&s, &path)?;
     }
 
-    debug!(path = %path, "fs.watch");
-
-    let (tx, rx) = mpsc::channel::<FileEvent>(64);
+    debug!(path = %path, ?opts, "fs.watch");
 
-    let watcher_tx = tx.clone();
-    let mut watcher = RecommendedWatcher::new(
-        move |res: Result<notify::Event, notify::Error>| {
-            if let Ok(event) = res {
-                let kind = format!("{:?}", event.kind);
-                let paths: Vec<String> = event
-                    .paths
-                    .iter()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .collect();
-                let _ = watcher_tx.blocking_send(FileEvent { kind, paths });
-            }
-        },
-        Config::default(),
-    )
-    .map_err(|e| FsError::watch(e.to_string()))?;
-
-    watcher
-        .watch(Path::new(&path), RecursiveMode::Recursive)
-        .map_err(|e| FsError::watch(e.to_string()))?;
+    let (tx, rx) = mpsc::channel::<WatchEvent>(64);
+    let watcher = watch_debounced(std::path::Path::new(&path), opts, move |events| {
+        // Stop once the receiver is gone
+        events
+            .into_iter()
+            .all(|event| tx.blocking_send(event).is_ok())
+    })?;
 
     // Generate watch ID and store both watcher and receiver
     let watch_id = {
@@ -897,7 +1067,7 @@ async fn op_fs_watch(
 async fn op_fs_watch_next(
     state: Rc<RefCell<OpState>>,
     #[string] watch_id: String,
-) -> Result<Option<FileEvent>, FsError> {
+) -> Result<Option<WatchEvent>, FsError> {
     // Take the entry out of state temporarily
     let maybe_entry = {
         let mut s = state.borrow_mut();
@@ -1212,6 +1382,954 @@ async fn op_fs_temp_dir(
     })
 }
 
+// ============================================================================
+// File Handles
+// ============================================================================
+
+/// Open file behind a `FsFile` handle in JS
+struct FileResource {
+    path: String,
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl Resource for FileResource {
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed("fsFile")
+    }
+}
+
+fn get_file(state: &Rc<RefCell<OpState>>, rid: ResourceId) -> Result<Rc<FileResource>, FsError> {
+    state
+        .borrow()
+        .resource_table
+        .get::<FileResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))
+}
+
+/// Open a file and return a handle for random access
+#[weld_op(async)]
+#[op2(async)]
+#[smi]
+async fn op_fs_open(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[serde] opts: OpenOpts,
+) -> Result<ResourceId, FsError> {
+    let (read, modifies) = opts.access();
+    // Check capabilities
+    {
+        let s = state.borrow();
+        if read {
+            check_fs_read(&s, &path)?;
+        }
+        if modifies {
+            check_fs_write(&s, &path)?;
+        }
+    }
+
+    debug!(path = %path, ?opts, "fs.open");
+    let file = tokio::fs::OpenOptions::new()
+        .read(read)
+        .write(opts.write.unwrap_or(false))
+        .append(opts.append.unwrap_or(false))
+        .create(opts.create.unwrap_or(false))
+        .create_new(opts.create_new.unwrap_or(false))
+        .truncate(opts.truncate.unwrap_or(false))
+        .open(&path)
+        .await?;
+
+    let rid = state.borrow_mut().resource_table.add(FileResource {
+        path,
+        file: tokio::sync::Mutex::new(file),
+    });
+    Ok(rid)
+}
+
+/// Read into `buf` at the current position
+///
+/// Returns the number of bytes read, or `null` at end of file.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_fs_file_read(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+    #[buffer] buf: JsBuffer,
+) -> Result<Option<usize>, FsError> {
+    use tokio::io::AsyncReadExt;
+
+    let resource = get_file(&state, rid)?;
+    let mut buf = buf;
+    let read = resource.file.lock().await.read(&mut buf).await?;
+    Ok(if read == 0 && !buf.is_empty() {
+        None
+    } else {
+        Some(read)
+    })
+}
+
+/// Write all of `data` at the current position
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_file_write(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+    #[buffer] data: JsBuffer,
+) -> Result<u32, FsError> {
+    use tokio::io::AsyncWriteExt;
+
+    let resource = get_file(&state, rid)?;
+    let mut file = resource.file.lock().await;
+    file.write_all(&data).await?;
+    file.flush().await?;
+    Ok(data.len() as u32)
+}
+
+/// Move the file position, returning the new offset from the start
+#[weld_op(async)]
+#[op2(async)]
+#[number]
+async fn op_fs_file_seek(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+    #[number] offset: i64,
+    #[serde] whence: Option<SeekWhence>,
+) -> Result<u64, FsError> {
+    use std::io::SeekFrom;
+    use tokio::io::AsyncSeekExt;
+
+    let pos = match whence.unwrap_or_default() {
+        SeekWhence::Start => {
+            SeekFrom::Start(u64::try_from(offset).map_err(|_| FsError::io("negative seek offset"))?)
+        }
+        SeekWhence::Current => SeekFrom::Current(offset),
+        SeekWhence::End => SeekFrom::End(offset),
+    };
+    let resource = get_file(&state, rid)?;
+    let position = resource.file.lock().await.seek(pos).await?;
+    Ok(position)
+}
+
+/// Truncate or extend the file to `len` bytes
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_file_truncate(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+    #[number] len: u64,
+) -> Result<(), FsError> {
+    let resource = get_file(&state, rid)?;
+    debug!(path = %resource.path, len, "fs.file_truncate");
+    resource.file.lock().await.set_len(len).await?;
+    Ok(())
+}
+
+/// Flush file contents and metadata to disk
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_file_sync(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<(), FsError> {
+    let resource = get_file(&state, rid)?;
+    resource.file.lock().await.sync_all().await?;
+    Ok(())
+}
+
+/// Stat an open file
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_fs_file_stat(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<FileStat, FsError> {
+    let resource = get_file(&state, rid)?;
+    let metadata = resource.file.lock().await.metadata().await?;
+    Ok(FileStat {
+        is_file: metadata.is_file(),
+        is_dir: metadata.is_dir(),
+        size: metadata.len(),
+        readonly: metadata.permissions().readonly(),
+    })
+}
+
+/// Close a file handle
+///
+/// Operations still running on the handle finish before the file is closed.
+#[weld_op]
+#[op2(fast)]
+fn op_fs_file_close(state: &mut OpState, #[smi] rid: ResourceId) -> Result<(), FsError> {
+    let resource = state
+        .resource_table
+        .take::<FileResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+    debug!(path = %resource.path, "fs.file_close");
+    Ok(())
+}
+
+// ============================================================================
+// File Streams
+// ============================================================================
+
+/// Chunk size used when a read stream does not ask for one
+pub const DEFAULT_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest chunk a read stream hands to JS at once
+const MAX_STREAM_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+struct ReadStreamInner {
+    file: tokio::fs::File,
+    /// Bytes left before `end`, if the stream has one
+    remaining: Option<u64>,
+}
+
+impl ReadStreamInner {
+    /// Read up to `chunk_size` bytes, only returning less at the end
+    async fn next_chunk(&mut self, chunk_size: usize) -> std::io::Result<Vec<u8>> {
+        use tokio::io::AsyncReadExt;
+
+        let want = match self.remaining {
+            Some(remaining) => remaining.min(chunk_size as u64) as usize,
+            None => chunk_size,
+        };
+        let mut chunk = vec![0; want];
+        let mut filled = 0;
+        while filled < want {
+            let read = self.file.read(&mut chunk[filled..]).await?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+        chunk.truncate(filled);
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= filled as u64;
+        }
+        Ok(chunk)
+    }
+}
+
+/// File being read chunk by chunk by a JS read stream
+struct FileReadStreamResource {
+    path: String,
+    chunk_size: usize,
+    inner: tokio::sync::Mutex<ReadStreamInner>,
+}
+
+impl Resource for FileReadStreamResource {
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed("fsReadStream")
+    }
+}
+
+/// File being written chunk by chunk by a JS write stream
+struct FileWriteStreamResource {
+    path: String,
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl Resource for FileWriteStreamResource {
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed("fsWriteStream")
+    }
+}
+
+/// Open a file for chunked reading and return the stream ID
+///
+/// Nothing is read ahead: each `op_fs_read_stream_next` reads one chunk, so
+/// a consumer that stops pulling stops disk reads.
+#[weld_op(async)]
+#[op2(async)]
+#[smi]
+async fn op_fs_read_stream_open(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[serde] opts: ReadStreamOpts,
+) -> Result<ResourceId, FsError> {
+    use tokio::io::AsyncSeekExt;
+
+    // Check capabilities
+    {
+        let s = state.borrow();
+        check_fs_read(&s, &path)?;
+    }
+
+    debug!(path = %path, ?opts, "fs.read_stream_open");
+    let start = opts.start.unwrap_or(0);
+    let mut file = tokio::fs::File::open(&path).await?;
+    if start > 0 {
+        file.seek(std::io::SeekFrom::Start(start)).await?;
+    }
+
+    let rid = state
+        .borrow_mut()
+        .resource_table
+        .add(FileReadStreamResource {
+            path,
+            chunk_size: opts
+                .chunk_size
+                .unwrap_or(DEFAULT_STREAM_CHUNK_SIZE)
+                .clamp(1, MAX_STREAM_CHUNK_SIZE),
+            inner: tokio::sync::Mutex::new(ReadStreamInner {
+                file,
+                remaining: opts.end.map(|end| end.saturating_sub(start)),
+            }),
+        });
+    Ok(rid)
+}
+
+/// Read the next chunk of a read stream
+///
+/// Returns an empty buffer once the end of the file (or range) is reached.
+#[weld_op(async)]
+#[op2(async)]
+#[buffer]
+async fn op_fs_read_stream_next(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<Vec<u8>, FsError> {
+    let resource = state
+        .borrow()
+        .resource_table
+        .get::<FileReadStreamResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+
+    let chunk = resource
+        .inner
+        .lock()
+        .await
+        .next_chunk(resource.chunk_size)
+        .await?;
+    Ok(chunk)
+}
+
+/// Stop a read stream and close its file
+#[weld_op]
+#[op2(fast)]
+fn op_fs_read_stream_close(state: &mut OpState, #[smi] rid: ResourceId) {
+    if let Ok(resource) = state.resource_table.take::<FileReadStreamResource>(rid) {
+        debug!(path = %resource.path, "fs.read_stream_close");
+    }
+}
+
+/// Open a file for chunked writing and return the stream ID
+#[weld_op(async)]
+#[op2(async)]
+#[smi]
+async fn op_fs_write_stream_open(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[serde] opts: WriteStreamOpts,
+) -> Result<ResourceId, FsError> {
+    // Check capabilities
+    {
+        let s = state.borrow();
+        check_fs_write(&s, &path)?;
+    }
+
+    debug!(path = %path, ?opts, "fs.write_stream_open");
+    let append = opts.append.unwrap_or(false);
+    let file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .create(opts.create.unwrap_or(true))
+        .create_new(opts.create_new.unwrap_or(false))
+        .open(&path)
+        .await?;
+
+    let rid = state
+        .borrow_mut()
+        .resource_table
+        .add(FileWriteStreamResource {
+            path,
+            file: tokio::sync::Mutex::new(file),
+        });
+    Ok(rid)
+}
+
+/// Write a chunk to a write stream
+///
+/// Resolves once the chunk is written, so awaiting each write keeps at most
+/// one chunk in flight per stream.
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_write_stream_write(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+    #[buffer] chunk: JsBuffer,
+) -> Result<(), FsError> {
+    use tokio::io::AsyncWriteExt;
+
+    let resource = state
+        .borrow()
+        .resource_table
+        .get::<FileWriteStreamResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+    resource.file.lock().await.write_all(&chunk).await?;
+    Ok(())
+}
+
+/// Finish a write stream, flushing everything to disk
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_write_stream_close(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<(), FsError> {
+    use tokio::io::AsyncWriteExt;
+
+    let resource = state
+        .borrow_mut()
+        .resource_table
+        .take::<FileWriteStreamResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+    debug!(path = %resource.path, "fs.write_stream_close");
+    let mut file = resource.file.lock().await;
+    file.flush().await?;
+    file.sync_all().await?;
+    Ok(())
+}
+
+/// Abandon a write stream, closing its file without flushing or syncing
+///
+/// Whatever was already written stays in the file.
+#[weld_op]
+#[op2(fast)]
+fn op_fs_write_stream_abort(state: &mut OpState, #[smi] rid: ResourceId) {
+    if let Ok(resource) = state.resource_table.take::<FileWriteStreamResource>(rid) {
+        debug!(path = %resource.path, "fs.write_stream_abort");
+    }
+}
+
+// ============================================================================
+// Walk, Glob and Bulk Operations
+// ============================================================================
+
+/// Entries buffered ahead of JS by a walk
+const WALK_BUFFER: usize = 1024;
+
+/// Most entries returned by one `op_fs_walk_next`
+const WALK_BATCH_SIZE: usize = 256;
+
+/// Walk running on a blocking thread, buffering entries for JS
+struct WalkResource {
+    path: String,
+    entries: tokio::sync::Mutex<mpsc::Receiver<WalkEntry>>,
+}
+
+impl Resource for WalkResource {
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed("fsWalk")
+    }
+}
+
+/// Copy or move running on a blocking thread
+struct TransferResource {
+    progress: tokio::sync::Mutex<tokio::sync::watch::Receiver<TransferProgress>>,
+    task: RefCell<Option<tokio::task::JoinHandle<Result<TransferProgress, FsError>>>>,
+}
+
+impl Resource for TransferResource {
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed("fsTransfer")
+    }
+}
+
+/// Start a recursive walk and return its ID
+///
+/// Entries the capability checker denies are skipped. The walk stays at
+/// most `WALK_BUFFER` entries ahead of JS, and stops once closed.
+#[weld_op(async)]
+#[op2(async)]
+#[smi]
+async fn op_fs_walk_open(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[serde] opts: WalkOpts,
+) -> Result<ResourceId, FsError> {
+    let checker = {
+        let s = state.borrow();
+        check_fs_read(&s, &path)?;
+        fs_checker(&s)
+    };
+
+    debug!(path = %path, ?opts, "fs.walk_open");
+    let walker = Walker::new(&path, opts, checker)?;
+    let (tx, rx) = mpsc::channel(WALK_BUFFER);
+    tokio::task::spawn_blocking(move || {
+        for entry in walker {
+            if tx.blocking_send(entry).is_err() {
+                // Closed by JS
+                break;
+            }
+        }
+    });
+
+    let rid = state.borrow_mut().resource_table.add(WalkResource {
+        path,
+        entries: tokio::sync::Mutex::new(rx),
+    });
+    Ok(rid)
+}
+
+/// Take the next batch of walk entries
+///
+/// Waits for at least one entry; returns an empty batch once the walk is done.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_fs_walk_next(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<Vec<WalkEntry>, FsError> {
+    let resource = state
+        .borrow()
+        .resource_table
+        .get::<WalkResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+
+    let mut entries = resource.entries.lock().await;
+    let mut batch = Vec::new();
+    if let Some(entry) = entries.recv().await {
+        batch.push(entry);
+        while batch.len() < WALK_BATCH_SIZE {
+            match entries.try_recv() {
+                Ok(entry) => batch.push(entry),
+                Err(_) => break,
+            }
+        }
+    }
+    Ok(batch)
+}
+
+/// Stop a walk
+#[weld_op]
+#[op2(fast)]
+fn op_fs_walk_close(state: &mut OpState, #[smi] rid: ResourceId) {
+    if let Ok(resource) = state.resource_table.take::<WalkResource>(rid) {
+        debug!(path = %resource.path, "fs.walk_close");
+    }
+}
+
+/// List the paths matching a glob pattern
+///
+/// The walk starts at the pattern's literal directory prefix, which needs
+/// read access; matches the capability checker denies are left out.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_fs_glob(
+    state: Rc<RefCell<OpState>>,
+    #[string] pattern: String,
+) -> Result<Vec<String>, FsError> {
+    let (base, rest) = split_glob(&pattern);
+    let root = if base.is_empty() { "." } else { base.as_str() };
+    let checker = {
+        let s = state.borrow();
+        check_fs_read(&s, root)?;
+        fs_checker(&s)
+    };
+
+    debug!(pattern = %pattern, "fs.glob");
+    let Some(rest) = rest else {
+        // No glob syntax: the pattern names a single path
+        let exists = tokio::fs::symlink_metadata(&pattern).await.is_ok();
+        return Ok(if exists { vec![pattern] } else { Vec::new() });
+    };
+
+    let walker = Walker::new(root, glob_walk_opts(&rest), checker)?;
+    let strip_dot = base.is_empty();
+    let paths = tokio::task::spawn_blocking(move || {
+        walker
+            .map(|entry| match entry.path.strip_prefix("./") {
+                Some(rel) if strip_dot => rel.to_string(),
+                _ => entry.path,
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| FsError::io(e.to_string()))?;
+    Ok(paths)
+}
+
+/// Start a copy or move on a blocking thread and register it
+fn start_transfer(
+    state: &Rc<RefCell<OpState>>,
+    from: String,
+    to: String,
+    opts: TransferOpts,
+    moving: bool,
+) -> Result<ResourceId, FsError> {
+    let checker = {
+        let s = state.borrow();
+        check_fs_read(&s, &from)?;
+        if moving {
+            check_fs_write(&s, &from)?;
+        }
+        check_fs_write(&s, &to)?;
+        fs_checker(&s)
+    };
+
+    let overwrite = opts.overwrite.unwrap_or(false);
+    let (tx, rx) = tokio::sync::watch::channel(TransferProgress::default());
+    let task = tokio::task::spawn_blocking(move || {
+        let (from, to) = (std::path::Path::new(&from), std::path::Path::new(&to));
+        if !moving && !from.is_dir() {
+            return Err(FsError::is_file(from.display().to_string()));
+        }
+        let plan = plan_transfer(from, to, checker.as_ref(), moving, overwrite)?;
+        if moving {
+            run_move(from, to, plan, &tx)
+        } else {
+            run_copy(plan, &tx)
+        }
+    });
+
+    let rid = state.borrow_mut().resource_table.add(TransferResource {
+        progress: tokio::sync::Mutex::new(rx),
+        task: RefCell::new(Some(task)),
+    });
+    Ok(rid)
+}
+
+/// Start copying a directory tree and return the transfer ID
+#[weld_op(async)]
+#[op2(async)]
+#[smi]
+async fn op_fs_copy_dir(
+    state: Rc<RefCell<OpState>>,
+    #[string] from: String,
+    #[string] to: String,
+    #[serde] opts: TransferOpts,
+) -> Result<ResourceId, FsError> {
+    debug!(from = %from, to = %to, ?opts, "fs.copy_dir");
+    start_transfer(&state, from, to, opts, false)
+}
+
+/// Start moving a file or directory tree and return the transfer ID
+///
+/// Renames when possible and falls back to copy-then-delete across devices.
+#[weld_op(async)]
+#[op2(async)]
+#[smi]
+async fn op_fs_move(
+    state: Rc<RefCell<OpState>>,
+    #[string] from: String,
+    #[string] to: String,
+    #[serde] opts: TransferOpts,
+) -> Result<ResourceId, FsError> {
+    debug!(from = %from, to = %to, ?opts, "fs.move");
+    start_transfer(&state, from, to, opts, true)
+}
+
+/// Wait for the next progress update of a transfer
+///
+/// Returns `None` once the transfer has finished; intermediate updates a
+/// slow caller misses are skipped.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_fs_transfer_progress(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<Option<TransferProgress>, FsError> {
+    let resource = state
+        .borrow()
+        .resource_table
+        .get::<TransferResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+
+    let mut progress = resource.progress.lock().await;
+    Ok(match progress.changed().await {
+        Ok(()) => Some(progress.borrow_and_update().clone()),
+        Err(_) => None,
+    })
+}
+
+/// Wait for a transfer to finish and return its final progress
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_fs_transfer_finish(
+    state: Rc<RefCell<OpState>>,
+    #[smi] rid: ResourceId,
+) -> Result<TransferProgress, FsError> {
+    let resource = state
+        .borrow_mut()
+        .resource_table
+        .take::<TransferResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+    let task = resource
+        .task
+        .borrow_mut()
+        .take()
+        .ok_or_else(|| FsError::invalid_handle(rid.to_string()))?;
+    task.await.map_err(|e| FsError::io(e.to_string()))?
+}
+
+// ============================================================================
+// Permissions, Ownership, Timestamps and Locks
+// ============================================================================
+
+/// Change the permission bits of a file or directory
+///
+/// On Windows only the write bits are meaningful: clearing all of them makes
+/// the file read-only.
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_chmod(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[smi] mode: u32,
+) -> Result<(), FsError> {
+    // Check capabilities
+    {
+        let s = state.borrow();
+        check_fs_write(&s, &path)?;
+    }
+
+    debug!(path = %path, mode = format_args!("{mode:o}"), "fs.chmod");
+
+    #[cfg(unix)]
+    let permissions = {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::Permissions::from_mode(mode)
+    };
+
+    #[cfg(not(unix))]
+    let permissions = {
+        let mut permissions = tokio::fs::metadata(&path).await?.permissions();
+        permissions.set_readonly(mode & 0o222 == 0);
+        permissions
+    };
+
+    tokio::fs::set_permissions(&path, permissions).await?;
+    Ok(())
+}
+
+/// Change the owner and/or group of a file or directory (Unix only)
+///
+/// A `None` ID is left unchanged.
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_chown(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[serde] uid: Option<u32>,
+    #[serde] gid: Option<u32>,
+) -> Result<(), FsError> {
+    // Check capabilities
+    {
+        let s = state.borrow();
+        check_fs_write(&s, &path)?;
+    }
+
+    debug!(path = %path, ?uid, ?gid, "fs.chown");
+
+    #[cfg(unix)]
+    {
+        tokio::task::spawn_blocking(move || std::os::unix::fs::chown(&path, uid, gid))
+            .await
+            .map_err(|e| FsError::io(e.to_string()))??;
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (uid, gid);
+        Err(FsError::unsupported(format!(
+            "chown is not supported on this platform: {path}"
+        )))
+    }
+}
+
+/// Convert a JS timestamp in milliseconds since the Unix epoch
+fn system_time_from_ms(ms: f64) -> Result<std::time::SystemTime, FsError> {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let offset = Duration::try_from_secs_f64(ms.abs() / 1000.0)
+        .map_err(|_| FsError::io(format!("invalid timestamp: {ms}")))?;
+    let time = if ms < 0.0 {
+        UNIX_EPOCH.checked_sub(offset)
+    } else {
+        UNIX_EPOCH.checked_add(offset)
+    };
+    time.ok_or_else(|| FsError::io(format!("timestamp out of range: {ms}")))
+}
+
+/// Set the access and modification times of a file or directory
+///
+/// Times are milliseconds since the Unix epoch.
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_utime(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    atime: f64,
+    mtime: f64,
+) -> Result<(), FsError> {
+    // Check capabilities
+    {
+        let s = state.borrow();
+        check_fs_write(&s, &path)?;
+    }
+
+    debug!(path = %path, atime, mtime, "fs.utime");
+    let times = std::fs::FileTimes::new()
+        .set_accessed(system_time_from_ms(atime)?)
+        .set_modified(system_time_from_ms(mtime)?);
+
+    tokio::task::spawn_blocking(move || {
+        #[cfg(unix)]
+        let file = std::fs::File::open(&path)?;
+
+        #[cfg(windows)]
+        let file = {
+            use std::os::windows::fs::OpenOptionsExt;
+            // FILE_WRITE_ATTRIBUTES, and FILE_FLAG_BACKUP_SEMANTICS so
+            // directories can be opened too
+            std::fs::OpenOptions::new()
+                .access_mode(0x0100)
+                .custom_flags(0x0200_0000)
+                .open(&path)?
+        };
+
+        file.set_times(times)
+    })
+    .await
+    .map_err(|e| FsError::io(e.to_string()))??;
+    Ok(())
+}
+
+/// Create a hard link `path` to the existing file `target`
+#[weld_op(async)]
+#[op2(async)]
+async fn op_fs_link(
+    state: Rc<RefCell<OpState>>,
+    #[string] target: String,
+    #[string] path: String,
+) -> Result<(), FsError> {
+    // Check capabilities
+    {
+        let s = state.borrow();
+        check_fs_read(&s, &target)?;
+        check_fs_write(&s, &path)?;
+    }
+
+    debug!(target = %target, path = %path, "fs.link");
+    tokio::fs::hard_link(&target, &path).await?;
+    Ok(())
+}
+
+/// Shortest wait between attempts to take a contended lock
+const LOCK_POLL_MIN: std::time::Duration = std::time::Duration::from_millis(5);
+
+/// Longest wait between attempts to take a contended lock
+const LOCK_POLL_MAX: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Advisory lock held on a file until released or closed
+struct FileLockResource {
+    path: String,
+    file: std::fs::File,
+}
+
+impl Resource for FileLockResource {
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed("fsLock")
+    }
+}
+
+/// Try to lock a file without blocking; `false` if a conflicting lock is held
+fn try_lock_file(file: &std::fs::File, exclusive: bool) -> Result<bool, FsError> {
+    let result = if exclusive {
+        file.try_lock()
+    } else {
+        file.try_lock_shared()
+    };
+    match result {
+        Ok(()) => Ok(true),
+        Err(std::fs::TryLockError::WouldBlock) => Ok(false),
+        Err(std::fs::TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+/// Acquire an advisory lock on a file and return the lock ID
+///
+/// Locks are `flock`-style: they coordinate cooperating processes and do not
+/// stop other reads or writes. A contended lock is retried with backoff, so
+/// waiting never blocks the event loop. Returns `None` if `wait` is false
+/// and the lock is held elsewhere.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_fs_lock(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[serde] opts: LockOpts,
+) -> Result<Option<ResourceId>, FsError> {
+    // Check capabilities; creating the lock file also needs write access
+    {
+        let s = state.borrow();
+        check_fs_read(&s, &path)?;
+    }
+    let create = opts.create.unwrap_or(true) && !tokio::fs::try_exists(&path).await?;
+    if create {
+        let s = state.borrow();
+        check_fs_write(&s, &path)?;
+    }
+
+    debug!(path = %path, ?opts, "fs.lock");
+    let file = tokio::fs::OpenOptions::new()
+        .read(true)
+        .write(create)
+        .create(create)
+        .open(&path)
+        .await?
+        .into_std()
+        .await;
+
+    let exclusive = opts.exclusive.unwrap_or(true);
+    let deadline = opts
+        .timeout
+        .map(|ms| tokio::time::Instant::now() + std::time::Duration::from_millis(ms));
+    let mut delay = LOCK_POLL_MIN;
+    while !try_lock_file(&file, exclusive)? {
+        if !opts.wait.unwrap_or(true) {
+            return Ok(None);
+        }
+        let mut wake = tokio::time::Instant::now() + delay;
+        if let Some(deadline) = deadline {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(FsError::lock_timeout(path));
+            }
+            wake = wake.min(deadline);
+        }
+        tokio::time::sleep_until(wake).await;
+        delay = (delay * 2).min(LOCK_POLL_MAX);
+    }
+
+    let rid = state
+        .borrow_mut()
+        .resource_table
+        .add(FileLockResource { path, file });
+    Ok(Some(rid))
+}
+
+/// Release an advisory lock
+#[weld_op]
+#[op2(fast)]
+fn op_fs_unlock(state: &mut OpState, #[smi] rid: ResourceId) -> Result<(), FsError> {
+    let resource = state
+        .resource_table
+        .take::<FileLockResource>(rid)
+        .map_err(|_| FsError::invalid_handle(rid.to_string()))?;
+    debug!(path = %resource.path, "fs.unlock");
+    resource.file.unlock()?;
+    Ok(())
+}
+
 // ============================================================================
 // State Initialization
 // ============================================================================
@@ -1262,6 +2380,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_open_opts_access() {
+        let opts = OpenOpts::default();
+        assert_eq!(opts.access(), (true, false));
+
+        let opts = OpenOpts {
+            append: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(opts.access(), (false, true));
+
+        let opts = OpenOpts {
+            read: Some(true),
+            create: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(opts.access(), (true, true));
+
+        let opts: OpenOpts =
+            serde_json::from_value(serde_json::json!({"write": true, "createNew": true})).unwrap();
+        assert_eq!(opts.create_new, Some(true));
+        assert_eq!(opts.access(), (false, true));
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_chunks_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, (0u8..10).collect::<Vec<_>>()).unwrap();
+
+        let mut file = tokio::fs::File::open(&path).await.unwrap();
+        tokio::io::AsyncSeekExt::seek(&mut file, std::io::SeekFrom::Start(2))
+            .await
+            .unwrap();
+        let mut inner = ReadStreamInner {
+            file,
+            remaining: Some(7),
+        };
+
+        let mut chunks = Vec::new();
+        loop {
+            let chunk = inner.next_chunk(3).await.unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks, vec![vec![2, 3, 4], vec![5, 6, 7], vec![8]]);
+    }
+
+    #[test]
+    fn test_system_time_from_ms() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        assert_eq!(
+            system_time_from_ms(1_500.0).unwrap(),
+            UNIX_EPOCH + Duration::from_millis(1_500)
+        );
+        assert_eq!(
+            system_time_from_ms(-2_000.0).unwrap(),
+            UNIX_EPOCH - Duration::from_secs(2)
+        );
+        assert!(system_time_from_ms(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_advisory_locks_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.lock");
+        std::fs::write(&path, "").unwrap();
+        let open = || std::fs::File::open(&path).unwrap();
+
+        // Separate opens conflict like separate processes would
+        let (a, b) = (open(), open());
+        assert!(try_lock_file(&a, false).unwrap());
+        assert!(try_lock_file(&b, false).unwrap());
+        let c = open();
+        assert!(!try_lock_file(&c, true).unwrap());
+
+        drop((a, b));
+        assert!(try_lock_file(&c, true).unwrap());
+        assert!(!try_lock_file(&open(), false).unwrap());
+    }
+
     #[test]
     fn test_io_error_conversion() {
         let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
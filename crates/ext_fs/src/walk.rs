@@ -0,0 +1,604 @@
+//! Recursive directory walking and bulk copy/move
+//!
+//! Everything here runs on blocking threads. Every path visited is passed to
+//! the [`FsCapabilityChecker`]:
+//!
+//! - A walk skips entries the checker denies instead of failing, because read
+//!   globs such as `./data/public/**` allow only part of a tree. Denied
+//!   directories are still descended into so allowed paths below them are
+//!   found.
+//! - When symlinks are followed, entries reached through a link are also
+//!   checked at their resolved location, so a link cannot expose files outside
+//!   the allowed globs.
+//! - A copy or move checks every source and destination path before anything
+//!   is written, and fails as a whole if one of them is denied.
+
+use crate::{FsCapabilityChecker, FsError};
+use forge_weld_macro::weld_struct;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::debug;
+
+/// Options for a recursive walk
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkOpts {
+    /// Deepest level to descend to; children of the root are at depth 1
+    pub max_depth: Option<usize>,
+    /// Only yield entries whose path relative to the root matches one of these globs
+    pub include: Option<Vec<String>>,
+    /// Skip entries matching one of these globs, without descending into them
+    pub exclude: Option<Vec<String>>,
+    /// Descend into symlinked directories (default: false)
+    pub follow_symlinks: Option<bool>,
+    /// Skip paths ignored by `.gitignore`, `.ignore` and `.git/info/exclude` (default: false)
+    pub gitignore: Option<bool>,
+    /// Yield files and symlinks (default: true)
+    pub include_files: Option<bool>,
+    /// Yield directories (default: true)
+    pub include_dirs: Option<bool>,
+}
+
+/// Entry yielded by a walk
+#[weld_struct]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkEntry {
+    /// Path of the entry, starting with the walk root
+    pub path: String,
+    /// File name of the entry
+    pub name: String,
+    /// Depth below the root (1 for the root's children)
+    pub depth: usize,
+    pub is_file: bool,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+}
+
+pub(crate) fn glob_set(patterns: &[String]) -> Result<GlobSet, FsError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| FsError::invalid_pattern(e.to_string()))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| FsError::invalid_pattern(e.to_string()))
+}
+
+fn walk_error(e: ignore::Error) -> FsError {
+    match e.io_error() {
+        Some(io) => std::io::Error::new(io.kind(), e.to_string()).into(),
+        None => FsError::io(e.to_string()),
+    }
+}
+
+/// Iterator over the entries below a root that the checker allows
+pub(crate) struct Walker {
+    root: PathBuf,
+    inner: ignore::Walk,
+    include: Option<GlobSet>,
+    include_files: bool,
+    include_dirs: bool,
+    follow_symlinks: bool,
+    /// Symlinked directories descended into, with their resolved targets
+    links: Vec<(PathBuf, PathBuf)>,
+    checker: Arc<dyn FsCapabilityChecker>,
+}
+
+impl Walker {
+    pub(crate) fn new(
+        root: &str,
+        opts: WalkOpts,
+        checker: Arc<dyn FsCapabilityChecker>,
+    ) -> Result<Self, FsError> {
+        let root = PathBuf::from(root);
+        let include = opts.include.as_deref().map(glob_set).transpose()?;
+        let exclude = opts.exclude.as_deref().map(glob_set).transpose()?;
+        let gitignore = opts.gitignore.unwrap_or(false);
+        let follow_symlinks = opts.follow_symlinks.unwrap_or(false);
+
+        let mut builder = WalkBuilder::new(&root);
+        builder
+            .standard_filters(false)
+            .hidden(false)
+            .git_ignore(gitignore)
+            .git_exclude(gitignore)
+            .ignore(gitignore)
+            .parents(gitignore)
+            .require_git(false)
+            .follow_links(follow_symlinks)
+            .max_depth(opts.max_depth);
+        if let Some(exclude) = exclude {
+            let prefix = root.clone();
+            builder.filter_entry(move |entry| {
+                entry
+                    .path()
+                    .strip_prefix(&prefix)
+                    .map_or(true, |rel| !exclude.is_match(rel))
+            });
+        }
+
+        Ok(Self {
+            root,
+            inner: builder.build(),
+            include,
+            include_files: opts.include_files.unwrap_or(true),
+            include_dirs: opts.include_dirs.unwrap_or(true),
+            follow_symlinks,
+            links: Vec::new(),
+            checker,
+        })
+    }
+
+    /// Where an entry reached through a followed symlink really lives
+    fn resolved_path(&mut self, entry: &ignore::DirEntry) -> Option<PathBuf> {
+        if !self.follow_symlinks {
+            return None;
+        }
+        if entry.path_is_symlink() {
+            let target = std::fs::canonicalize(entry.path()).ok()?;
+            if entry.file_type().is_some_and(|t| t.is_dir()) {
+                self.links
+                    .push((entry.path().to_path_buf(), target.clone()));
+            }
+            return Some(target);
+        }
+        // The innermost link is the most recently descended one
+        self.links.iter().rev().find_map(|(link, target)| {
+            entry
+                .path()
+                .strip_prefix(link)
+                .ok()
+                .map(|rest| target.join(rest))
+        })
+    }
+
+    fn allowed(&mut self, entry: &ignore::DirEntry) -> bool {
+        let resolved = self.resolved_path(entry);
+        let allowed = |path: &Path| self.checker.check_read(&path.to_string_lossy()).is_ok();
+        allowed(entry.path()) && resolved.as_deref().is_none_or(allowed)
+    }
+}
+
+impl Iterator for Walker {
+    type Item = WalkEntry;
+
+    fn next(&mut self) -> Option<WalkEntry> {
+        loop {
+            let entry = match self.inner.next()? {
+                Ok(entry) => entry,
+                Err(e) => {
+                    debug!(root = %self.root.display(), error = %e, "fs.walk skipped entry");
+                    continue;
+                }
+            };
+            if entry.depth() == 0 || !self.allowed(&entry) {
+                continue;
+            }
+
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            let wanted = if is_dir {
+                self.include_dirs
+            } else {
+                self.include_files
+            };
+            if !wanted {
+                continue;
+            }
+            if let Some(include) = &self.include {
+                let rel = entry
+                    .path()
+                    .strip_prefix(&self.root)
+                    .unwrap_or(entry.path());
+                if !include.is_match(rel) {
+                    continue;
+                }
+            }
+
+            return Some(WalkEntry {
+                path: entry.path().to_string_lossy().to_string(),
+                name: entry.file_name().to_string_lossy().to_string(),
+                depth: entry.depth(),
+                is_file: entry.file_type().is_some_and(|t| t.is_file()),
+                is_dir,
+                is_symlink: entry.path_is_symlink(),
+            });
+        }
+    }
+}
+
+/// Split a glob into the literal directory to walk and the pattern below it
+///
+/// Returns no pattern when `pattern` contains no glob syntax at all.
+pub(crate) fn split_glob(pattern: &str) -> (String, Option<String>) {
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let Some(first_glob) = parts
+        .iter()
+        .position(|part| part.contains(['*', '?', '[', '{']))
+    else {
+        return (pattern.to_string(), None);
+    };
+
+    let base = match parts[..first_glob].join("/") {
+        base if !base.is_empty() => base,
+        _ if pattern.starts_with('/') => "/".to_string(),
+        _ => String::new(),
+    };
+    (base, Some(parts[first_glob..].join("/")))
+}
+
+/// Walk options matching `pattern` below the directory returned by [`split_glob`]
+pub(crate) fn glob_walk_opts(pattern: &str) -> WalkOpts {
+    WalkOpts {
+        max_depth: (!pattern.contains("**")).then(|| pattern.split('/').count()),
+        include: Some(vec![pattern.to_string()]),
+        ..Default::default()
+    }
+}
+
+/// Options for copying or moving a directory tree
+#[weld_struct]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferOpts {
+    /// Replace an existing destination instead of failing (default: false)
+    pub overwrite: Option<bool>,
+}
+
+/// Progress of a copy or move
+#[weld_struct]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferProgress {
+    pub files_done: u64,
+    pub files_total: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// Source path of the file last copied
+    pub current: Option<String>,
+}
+
+/// Everything a copy will create, computed before writing anything
+#[derive(Debug, Default)]
+pub(crate) struct TransferPlan {
+    /// Destination directories, parents first
+    dirs: Vec<PathBuf>,
+    /// Source file, destination and size
+    files: Vec<(PathBuf, PathBuf, u64)>,
+    /// Source symlink and destination; links are recreated, not followed
+    links: Vec<(PathBuf, PathBuf)>,
+    bytes_total: u64,
+}
+
+impl TransferPlan {
+    fn progress(&self) -> TransferProgress {
+        TransferProgress {
+            files_total: (self.files.len() + self.links.len()) as u64,
+            bytes_total: self.bytes_total,
+            ..Default::default()
+        }
+    }
+}
+
+fn entries(root: &Path) -> impl Iterator<Item = Result<ignore::DirEntry, FsError>> {
+    WalkBuilder::new(root)
+        .standard_filters(false)
+        .hidden(false)
+        .follow_links(false)
+        .build()
+        .map(|entry| entry.map_err(walk_error))
+}
+
+fn check(result: Result<(), String>) -> Result<(), FsError> {
+    result.map_err(FsError::permission_denied)
+}
+
+/// Check every path a copy or move touches and list what it will create
+///
+/// Moving also requires write access to every source path, since the
+/// sources are removed, and to everything under a destination it replaces.
+pub(crate) fn plan_transfer(
+    from: &Path,
+    to: &Path,
+    checker: &dyn FsCapabilityChecker,
+    moving: bool,
+    overwrite: bool,
+) -> Result<TransferPlan, FsError> {
+    if std::fs::symlink_metadata(to).is_ok() {
+        if !overwrite {
+            return Err(FsError::already_exists(to.display().to_string()));
+        }
+        if moving {
+            for entry in entries(to) {
+                check(checker.check_write(&entry?.path().to_string_lossy()))?;
+            }
+        }
+    }
+
+    let mut plan = TransferPlan::default();
+    for entry in entries(from) {
+        let entry = entry?;
+        let source = entry.path();
+        let dest = match source.strip_prefix(from) {
+            Ok(rel) if !rel.as_os_str().is_empty() => to.join(rel),
+            _ => to.to_path_buf(),
+        };
+
+        check(checker.check_read(&source.to_string_lossy()))?;
+        if moving {
+            check(checker.check_write(&source.to_string_lossy()))?;
+        }
+        check(checker.check_write(&dest.to_string_lossy()))?;
+
+        let file_type = entry
+            .file_type()
+            .ok_or_else(|| FsError::io(format!("unknown file type: {}", source.display())))?;
+        if file_type.is_dir() {
+            plan.dirs.push(dest);
+        } else if file_type.is_symlink() {
+            plan.links.push((source.to_path_buf(), dest));
+        } else {
+            let size = entry.metadata().map_err(walk_error)?.len();
+            plan.bytes_total += size;
+            plan.files.push((source.to_path_buf(), dest, size));
+        }
+    }
+    Ok(plan)
+}
+
+fn copy_symlink(source: &Path, dest: &Path) -> std::io::Result<()> {
+    let target = std::fs::read_link(source)?;
+    if std::fs::symlink_metadata(dest).is_ok() {
+        std::fs::remove_file(dest)?;
+    }
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&target, dest)
+    }
+
+    #[cfg(windows)]
+    {
+        if std::fs::metadata(source).is_ok_and(|m| m.is_dir()) {
+            std::os::windows::fs::symlink_dir(&target, dest)
+        } else {
+            std::os::windows::fs::symlink_file(&target, dest)
+        }
+    }
+}
+
+/// Carry out a copy, publishing progress after each file
+pub(crate) fn run_copy(
+    plan: TransferPlan,
+    progress: &watch::Sender<TransferProgress>,
+) -> Result<TransferProgress, FsError> {
+    let mut current = plan.progress();
+    progress.send_replace(current.clone());
+
+    for dir in &plan.dirs {
+        std::fs::create_dir_all(dir)?;
+    }
+    for (source, dest, size) in &plan.files {
+        std::fs::copy(source, dest)?;
+        current.files_done += 1;
+        current.bytes_done += size;
+        current.current = Some(source.to_string_lossy().to_string());
+        progress.send_replace(current.clone());
+    }
+    for (source, dest) in &plan.links {
+        copy_symlink(source, dest)?;
+        current.files_done += 1;
+        current.current = Some(source.to_string_lossy().to_string());
+        progress.send_replace(current.clone());
+    }
+    Ok(current)
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Carry out a move: a rename when possible, otherwise copy then delete
+pub(crate) fn run_move(
+    from: &Path,
+    to: &Path,
+    plan: TransferPlan,
+    progress: &watch::Sender<TransferProgress>,
+) -> Result<TransferProgress, FsError> {
+    if std::fs::symlink_metadata(to).is_ok() {
+        remove_path(to)?;
+    }
+
+    match std::fs::rename(from, to) {
+        Ok(()) => {
+            let mut done = plan.progress();
+            done.files_done = done.files_total;
+            done.bytes_done = done.bytes_total;
+            progress.send_replace(done.clone());
+            Ok(done)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            debug!(from = %from.display(), to = %to.display(), "fs.move falling back to copy");
+            let done = run_copy(plan, progress)?;
+            remove_path(from)?;
+            Ok(done)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Allows reads of everything except paths containing `deny`
+    struct DenyChecker(&'static str);
+
+    impl FsCapabilityChecker for DenyChecker {
+        fn check_read(&self, path: &str) -> Result<(), String> {
+            if path.contains(self.0) {
+                Err(format!("denied: {path}"))
+            } else {
+                Ok(())
+            }
+        }
+        fn check_write(&self, path: &str) -> Result<(), String> {
+            self.check_read(path)
+        }
+    }
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["a.ts", "b.md", "src/c.ts", "src/secret/d.ts", "target/e.ts"] {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file).unwrap();
+        }
+        dir
+    }
+
+    fn walk(root: &Path, opts: WalkOpts, deny: &'static str) -> Vec<String> {
+        let mut paths: Vec<String> =
+            Walker::new(&root.to_string_lossy(), opts, Arc::new(DenyChecker(deny)))
+                .unwrap()
+                .map(|entry| {
+                    Path::new(&entry.path)
+                        .strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_walk_filters() {
+        let dir = tree();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+
+        let files = WalkOpts {
+            include_dirs: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            walk(dir.path(), files.clone(), "secret"),
+            vec![".gitignore", "a.ts", "b.md", "src/c.ts", "target/e.ts"]
+        );
+
+        let opts = WalkOpts {
+            include: Some(vec!["**/*.ts".to_string()]),
+            exclude: Some(vec!["src/secret".to_string()]),
+            gitignore: Some(true),
+            ..files.clone()
+        };
+        assert_eq!(walk(dir.path(), opts, "\0"), vec!["a.ts", "src/c.ts"]);
+
+        let opts = WalkOpts {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            walk(dir.path(), opts, "\0"),
+            vec![".gitignore", "a.ts", "b.md", "src", "target"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_checks_symlink_targets() {
+        let dir = tree();
+        let outside = tempfile::tempdir().unwrap();
+        let secret = outside.path().join("secret");
+        std::fs::create_dir(&secret).unwrap();
+        std::fs::write(secret.join("key.pem"), "x").unwrap();
+        std::os::unix::fs::symlink(&secret, dir.path().join("link")).unwrap();
+
+        let opts = WalkOpts {
+            follow_symlinks: Some(true),
+            include_dirs: Some(false),
+            ..Default::default()
+        };
+        let paths = walk(dir.path(), opts.clone(), "\0");
+        assert!(paths.contains(&"link/key.pem".to_string()));
+
+        // The link path itself is allowed, but its target is not
+        let paths = walk(dir.path(), opts, "secret");
+        assert!(!paths.iter().any(|p| p.starts_with("link")));
+    }
+
+    #[test]
+    fn test_split_glob() {
+        assert_eq!(
+            split_glob("./src/**/*.ts"),
+            ("./src".to_string(), Some("**/*.ts".to_string()))
+        );
+        assert_eq!(
+            split_glob("*.md"),
+            (String::new(), Some("*.md".to_string()))
+        );
+        assert_eq!(
+            split_glob("/*.md"),
+            ("/".to_string(), Some("*.md".to_string()))
+        );
+        assert_eq!(split_glob("./a/b.txt"), ("./a/b.txt".to_string(), None));
+        assert_eq!(glob_walk_opts("*/*.ts").max_depth, Some(2));
+        assert_eq!(glob_walk_opts("**/*.ts").max_depth, None);
+    }
+
+    #[test]
+    fn test_copy_and_move_tree() {
+        let dir = tree();
+        let copy = dir.path().join("copy");
+        let checker = DenyChecker("\0");
+
+        let plan = plan_transfer(&dir.path().join("src"), &copy, &checker, false, false).unwrap();
+        let (tx, rx) = watch::channel(TransferProgress::default());
+        let done = run_copy(plan, &tx).unwrap();
+        assert_eq!(done.files_done, 2);
+        assert_eq!(done.bytes_done, done.bytes_total);
+        assert_eq!(*rx.borrow(), done);
+        assert_eq!(
+            std::fs::read_to_string(copy.join("secret/d.ts")).unwrap(),
+            "src/secret/d.ts"
+        );
+
+        // Existing destination without overwrite, or a denied path: nothing happens
+        assert!(matches!(
+            plan_transfer(&dir.path().join("src"), &copy, &checker, false, false),
+            Err(FsError::AlreadyExists { .. })
+        ));
+        assert!(matches!(
+            plan_transfer(
+                &copy,
+                &dir.path().join("moved"),
+                &DenyChecker("secret"),
+                true,
+                false
+            ),
+            Err(FsError::PermissionDenied { .. })
+        ));
+        assert!(copy.join("secret/d.ts").exists());
+
+        let moved = dir.path().join("moved");
+        let plan = plan_transfer(&copy, &moved, &checker, true, false).unwrap();
+        let done = run_move(&copy, &moved, plan, &tx).unwrap();
+        assert_eq!(done.files_done, 2);
+        assert!(!copy.exists());
+        assert!(moved.join("c.ts").exists());
+    }
+}
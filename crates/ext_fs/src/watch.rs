@@ -0,0 +1,533 @@
+//! Debounced, filtered file watching
+//!
+//! `notify` reports raw OS events. One editor save can produce several
+//! modify events, and a rename arrives in one of three forms:
+//!
+//! - two halves paired by a tracker cookie (inotify)
+//! - a single event with both paths
+//! - unpaired events per path (FSEvents, Windows)
+//!
+//! [`Debouncer`] holds the changes to each path until the path has been quiet
+//! for the debounce interval and merges them into one event. A path that never
+//! goes quiet, such as a log being appended to, is still reported every
+//! [`MAX_WAIT_WINDOWS`] intervals. It also pairs
+//! rename halves into a single [`WatchEventKind::Rename`]. A half that is
+//! never paired becomes a remove (moved out of the watched tree) or a create
+//! (moved in).
+
+use crate::walk::glob_set;
+use crate::FsError;
+use forge_weld_macro::{weld_enum, weld_struct};
+use globset::GlobSet;
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+
+/// Debounce interval used when a watch does not ask for one
+pub const DEFAULT_WATCH_DEBOUNCE_MS: u64 = 50;
+
+/// Longest a change is held back, in debounce intervals, however busy its path
+pub const MAX_WAIT_WINDOWS: u32 = 10;
+
+/// Options for watching a file or directory
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOpts {
+    /// Quiet period before a path's changes are reported (default: 50 ms)
+    pub debounce_ms: Option<u64>,
+    /// Only report paths, relative to the watched path, matching one of these globs
+    pub include: Option<Vec<String>>,
+    /// Never report paths matching one of these globs
+    pub exclude: Option<Vec<String>>,
+    /// Watch subdirectories too (default: true)
+    pub recursive: Option<bool>,
+}
+
+/// Kind of a debounced watch event
+#[weld_enum]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WatchEventKind {
+    Create,
+    Modify,
+    Remove,
+    Rename,
+}
+
+/// Debounced change to one path, or a rename between two
+#[weld_struct]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchEvent {
+    pub kind: WatchEventKind,
+    /// Affected paths; for a rename, the old path then the new one
+    pub paths: Vec<String>,
+    /// Old path of a rename
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// New path of a rename
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+impl WatchEvent {
+    fn single(kind: WatchEventKind, path: &Path) -> Self {
+        Self {
+            kind,
+            paths: vec![path.to_string_lossy().to_string()],
+            from: None,
+            to: None,
+        }
+    }
+
+    fn rename(from: &Path, to: &Path) -> Self {
+        let (from, to) = (
+            from.to_string_lossy().to_string(),
+            to.to_string_lossy().to_string(),
+        );
+        Self {
+            kind: WatchEventKind::Rename,
+            paths: vec![from.clone(), to.clone()],
+            from: Some(from),
+            to: Some(to),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    Create,
+    Modify,
+    Remove,
+    Rename { from: PathBuf },
+}
+
+struct Pending {
+    path: PathBuf,
+    change: Change,
+    /// When the first of the merged changes happened
+    first: Instant,
+    last: Instant,
+}
+
+/// First half of a rename waiting for its second half
+struct RenameFrom {
+    path: PathBuf,
+    tracker: Option<usize>,
+    at: Instant,
+}
+
+/// Coalesces raw `notify` events into debounced [`WatchEvent`]s
+pub(crate) struct Debouncer {
+    window: Duration,
+    max_wait: Duration,
+    /// Watched path, as given and canonicalized; FSEvents reports canonical paths
+    roots: Vec<PathBuf>,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    pending: Vec<Pending>,
+    rename_from: Vec<RenameFrom>,
+}
+
+impl Debouncer {
+    pub(crate) fn new(root: &Path, opts: &WatchOpts) -> Result<Self, FsError> {
+        let mut roots = vec![root.to_path_buf()];
+        roots.extend(std::fs::canonicalize(root).ok());
+        let window = Duration::from_millis(opts.debounce_ms.unwrap_or(DEFAULT_WATCH_DEBOUNCE_MS));
+        Ok(Self {
+            window,
+            max_wait: window * MAX_WAIT_WINDOWS,
+            roots,
+            include: opts.include.as_deref().map(glob_set).transpose()?,
+            exclude: opts.exclude.as_deref().map(glob_set).transpose()?,
+            pending: Vec::new(),
+            rename_from: Vec::new(),
+        })
+    }
+
+    /// Record a raw event
+    pub(crate) fn push(&mut self, event: notify::Event, now: Instant) {
+        let tracker = event.attrs.tracker();
+        let mut paths = event.paths.into_iter();
+        match event.kind {
+            EventKind::Create(_) => paths.for_each(|p| self.record(p, Change::Create, now)),
+            EventKind::Remove(_) => paths.for_each(|p| self.record(p, Change::Remove, now)),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                if let (Some(from), Some(to)) = (paths.next(), paths.next()) {
+                    self.rename(from, to, now);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for path in paths {
+                    self.rename_from.push(RenameFrom {
+                        path,
+                        tracker,
+                        at: now,
+                    });
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                for path in paths {
+                    match self.take_rename_from(tracker) {
+                        Some(from) => self.rename(from, path, now),
+                        None => self.record(path, Change::Create, now),
+                    }
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                // Unpaired rename events: the old path no longer exists
+                for path in paths {
+                    if path.exists() {
+                        match self.take_rename_from(None) {
+                            Some(from) => self.rename(from, path, now),
+                            None => self.record(path, Change::Create, now),
+                        }
+                    } else {
+                        self.rename_from.push(RenameFrom {
+                            path,
+                            tracker: None,
+                            at: now,
+                        });
+                    }
+                }
+            }
+            EventKind::Access(_) => {}
+            EventKind::Modify(_) | EventKind::Any | EventKind::Other => {
+                paths.for_each(|p| self.record(p, Change::Modify, now))
+            }
+        }
+    }
+
+    fn take_rename_from(&mut self, tracker: Option<usize>) -> Option<PathBuf> {
+        let index = self
+            .rename_from
+            .iter()
+            .rposition(|from| from.tracker == tracker)?;
+        Some(self.rename_from.remove(index).path)
+    }
+
+    fn take_pending(&mut self, path: &Path) -> Option<Pending> {
+        let index = self.pending.iter().position(|p| p.path == path)?;
+        Some(self.pending.remove(index))
+    }
+
+    fn record(&mut self, path: PathBuf, change: Change, now: Instant) {
+        let Some(mut pending) = self.take_pending(&path) else {
+            self.pending.push(Pending {
+                path,
+                change,
+                first: now,
+                last: now,
+            });
+            return;
+        };
+
+        pending.change = match (pending.change, change) {
+            // Created and removed within the window: nothing to report
+            (Change::Create, Change::Remove) => return,
+            // Renamed away and then removed: the original path is gone
+            (Change::Rename { from }, Change::Remove) => {
+                pending.path = from;
+                Change::Remove
+            }
+            // Removed and recreated, as by an atomic save
+            (Change::Remove, Change::Create | Change::Modify) => Change::Modify,
+            (_, Change::Remove) => Change::Remove,
+            (existing, _) => existing,
+        };
+        pending.last = now;
+        self.pending.push(pending);
+    }
+
+    fn rename(&mut self, from: PathBuf, to: PathBuf, now: Instant) {
+        let pending = self.take_pending(&from);
+        let first = pending.as_ref().map_or(now, |p| p.first);
+        let original = match pending.map(|p| p.change) {
+            // A file created within the window is just created at its new path
+            Some(Change::Create) => return self.record(to, Change::Create, now),
+            // Chained renames collapse into one
+            Some(Change::Rename { from: original }) => original,
+            _ => from,
+        };
+        self.take_pending(&to);
+        if original == to {
+            // Renamed back: the file was at most modified
+            return self.record(to, Change::Modify, now);
+        }
+        self.pending.push(Pending {
+            path: to,
+            change: Change::Rename { from: original },
+            first,
+            last: now,
+        });
+    }
+
+    /// When a pending change is reported: once quiet, or after the max wait
+    fn deadline(&self, pending: &Pending) -> Instant {
+        (pending.last + self.window).min(pending.first + self.max_wait)
+    }
+
+    /// When the next pending change becomes reportable
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        let pending = self.pending.iter().map(|p| self.deadline(p));
+        let renames = self.rename_from.iter().map(|r| r.at + self.window);
+        pending.chain(renames).min()
+    }
+
+    /// Take the changes to paths that have been quiet for the debounce interval
+    pub(crate) fn flush(&mut self, now: Instant) -> Vec<WatchEvent> {
+        // Rename halves that were never paired left the watched tree
+        let (expired, waiting) = std::mem::take(&mut self.rename_from)
+            .into_iter()
+            .partition::<Vec<_>, _>(|from| now.duration_since(from.at) >= self.window);
+        self.rename_from = waiting;
+        for from in expired {
+            self.record(from.path, Change::Remove, from.at);
+        }
+
+        let (ready, waiting) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition::<Vec<_>, _>(|p| now >= self.deadline(p));
+        self.pending = waiting;
+        ready
+            .into_iter()
+            .filter_map(|pending| self.to_event(pending))
+            .collect()
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let relative = self
+            .roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.is_match(relative))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|exclude| exclude.is_match(relative))
+    }
+
+    fn to_event(&self, pending: Pending) -> Option<WatchEvent> {
+        let path = pending.path.as_path();
+        let kind = match pending.change {
+            Change::Create => WatchEventKind::Create,
+            Change::Modify => WatchEventKind::Modify,
+            Change::Remove => WatchEventKind::Remove,
+            // A rename across the filter boundary looks like a create or remove
+            Change::Rename { from } => {
+                return match (self.matches(&from), self.matches(path)) {
+                    (true, true) => Some(WatchEvent::rename(&from, path)),
+                    (false, true) => Some(WatchEvent::single(WatchEventKind::Create, path)),
+                    (true, false) => Some(WatchEvent::single(WatchEventKind::Remove, &from)),
+                    (false, false) => None,
+                };
+            }
+        };
+        self.matches(path).then(|| WatchEvent::single(kind, path))
+    }
+}
+
+/// Watch `path`, calling `on_events` with each batch of debounced events
+///
+/// Batches are delivered from a background thread until `on_events` returns
+/// `false` or the returned watcher is dropped.
+pub fn watch_debounced<F>(
+    path: &Path,
+    opts: WatchOpts,
+    mut on_events: F,
+) -> Result<RecommendedWatcher, FsError>
+where
+    F: FnMut(Vec<WatchEvent>) -> bool + Send + 'static,
+{
+    let mut debouncer = Debouncer::new(path, &opts)?;
+    let (raw_tx, raw_rx) = std::sync::mpsc::channel::<notify::Event>();
+
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<notify::Event, notify::Error>| {
+            if let Ok(event) = res {
+                let _ = raw_tx.send(event);
+            }
+        },
+        Config::default(),
+    )
+    .map_err(|e| FsError::watch(e.to_string()))?;
+
+    let mode = if opts.recursive.unwrap_or(true) {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher
+        .watch(path, mode)
+        .map_err(|e| FsError::watch(e.to_string()))?;
+
+    std::thread::Builder::new()
+        .name("fs-watch".to_string())
+        .spawn(move || loop {
+            let received = match debouncer.next_deadline() {
+                Some(deadline) => {
+                    raw_rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => raw_rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(event) => debouncer.push(event, Instant::now()),
+                Err(RecvTimeoutError::Timeout) => {}
+                // The watcher was dropped
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let events = debouncer.flush(Instant::now());
+            if !events.is_empty() && !on_events(events) {
+                break;
+            }
+        })
+        .map_err(|e| FsError::watch(e.to_string()))?;
+
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+
+    const WINDOW: Duration = Duration::from_millis(50);
+
+    fn debouncer(opts: WatchOpts) -> Debouncer {
+        Debouncer::new(
+            Path::new("/app"),
+            &WatchOpts {
+                debounce_ms: Some(WINDOW.as_millis() as u64),
+                ..opts
+            },
+        )
+        .unwrap()
+    }
+
+    fn event(kind: EventKind, path: &str) -> notify::Event {
+        notify::Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    fn modify(path: &str) -> notify::Event {
+        event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            path,
+        )
+    }
+
+    #[test]
+    fn test_burst_coalesces_after_quiet_period() {
+        let mut d = debouncer(WatchOpts::default());
+        let start = Instant::now();
+        for ms in [0, 10, 20] {
+            d.push(modify("/app/a.ts"), start + Duration::from_millis(ms));
+        }
+
+        assert!(d.flush(start + Duration::from_millis(40)).is_empty());
+        assert_eq!(
+            d.next_deadline(),
+            Some(start + Duration::from_millis(20) + WINDOW)
+        );
+        assert_eq!(
+            d.flush(start + Duration::from_millis(70)),
+            vec![WatchEvent::single(
+                WatchEventKind::Modify,
+                Path::new("/app/a.ts")
+            )]
+        );
+        assert_eq!(d.next_deadline(), None);
+    }
+
+    #[test]
+    fn test_busy_path_reported_after_max_wait() {
+        let mut d = debouncer(WatchOpts::default());
+        let start = Instant::now();
+        let max_wait = WINDOW * MAX_WAIT_WINDOWS;
+
+        // Modified every 10 ms, so never quiet for a whole window
+        let mut reported = Vec::new();
+        for ms in (0..=700).step_by(10) {
+            let now = start + Duration::from_millis(ms);
+            d.push(modify("/app/app.log"), now);
+            if !d.flush(now).is_empty() {
+                reported.push(now.duration_since(start));
+            }
+        }
+        assert_eq!(reported, vec![max_wait]);
+        assert_eq!(
+            d.next_deadline(),
+            Some(start + Duration::from_millis(700) + WINDOW)
+        );
+    }
+
+    #[test]
+    fn test_merges_and_pairs_renames() {
+        let mut d = debouncer(WatchOpts::default());
+        let now = Instant::now();
+
+        // Transient file: nothing reported
+        d.push(event(EventKind::Create(CreateKind::File), "/app/tmp"), now);
+        d.push(modify("/app/tmp"), now);
+        d.push(event(EventKind::Remove(RemoveKind::File), "/app/tmp"), now);
+
+        // Atomic save: remove then create is a modify
+        d.push(event(EventKind::Remove(RemoveKind::File), "/app/b.ts"), now);
+        d.push(event(EventKind::Create(CreateKind::File), "/app/b.ts"), now);
+
+        // Rename halves paired by tracker
+        let from = EventKind::Modify(ModifyKind::Name(RenameMode::From));
+        let to = EventKind::Modify(ModifyKind::Name(RenameMode::To));
+        d.push(event(from, "/app/old.ts").set_tracker(7), now);
+        d.push(event(to, "/app/new.ts").set_tracker(7), now);
+
+        // Unpaired halves: moved out of and into the tree
+        d.push(event(from, "/app/gone.ts").set_tracker(8), now);
+        d.push(event(to, "/app/arrived.ts").set_tracker(9), now);
+
+        let events = d.flush(now + WINDOW);
+        assert_eq!(
+            events,
+            vec![
+                WatchEvent::single(WatchEventKind::Modify, Path::new("/app/b.ts")),
+                WatchEvent::rename(Path::new("/app/old.ts"), Path::new("/app/new.ts")),
+                WatchEvent::single(WatchEventKind::Create, Path::new("/app/arrived.ts")),
+                WatchEvent::single(WatchEventKind::Remove, Path::new("/app/gone.ts")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filters_relative_to_root() {
+        let mut d = debouncer(WatchOpts {
+            include: Some(vec!["src/**".to_string()]),
+            exclude: Some(vec!["**/*.tmp".to_string()]),
+            ..Default::default()
+        });
+        let now = Instant::now();
+        d.push(modify("/app/src/a.ts"), now);
+        d.push(modify("/app/src/a.tmp"), now);
+        d.push(modify("/app/dist/a.js"), now);
+        d.push(
+            notify::Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+                .add_path(PathBuf::from("/app/src/b.ts"))
+                .add_path(PathBuf::from("/app/dist/b.ts")),
+            now,
+        );
+
+        assert_eq!(
+            d.flush(now + WINDOW),
+            vec![
+                WatchEvent::single(WatchEventKind::Modify, Path::new("/app/src/a.ts")),
+                WatchEvent::single(WatchEventKind::Remove, Path::new("/app/src/b.ts")),
+            ]
+        );
+    }
+}
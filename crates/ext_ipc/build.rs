@@ -3,7 +3,21 @@ use forge_weld::ExtensionBuilder;
 fn main() {
     ExtensionBuilder::new("runtime_ipc", "runtime:ipc")
         .ts_path("ts/init.ts")
-        .ops(&["op_ipc_send", "op_ipc_recv"])
+        .ops(&[
+            "op_ipc_send",
+            "op_ipc_recv",
+            "op_ipc_handle",
+            "op_ipc_reply",
+            "op_ipc_send_binary",
+            "op_ipc_stream_open",
+            "op_ipc_stream_write",
+            "op_ipc_stream_close",
+            "op_ipc_stream_abort",
+            "op_ipc_subscribe",
+            "op_ipc_subscription_recv",
+            "op_ipc_unsubscribe",
+            "op_ipc_broadcast",
+        ])
         .generate_sdk_types("sdk")
         .generate_sdk_module("sdk")
         .use_inventory_types()
@@ -33,8 +47,12 @@ declare module "runtime:ipc" {
     channel: string;
     /** Event payload data */
     payload: unknown;
-    /** Event type for window system events */
-    type?: "close" | "focus" | "blur" | "resize" | "move";
+    /** Event type for window system events, or "cancel" for a cancelled invoke */
+    type?: "close" | "focus" | "blur" | "resize" | "move" | "cancel";
+    /** Correlation ID when the renderer called `window.host.invoke()` */
+    invokeId?: string;
+    /** Events this subscription's overflow policy discarded before this one */
+    dropped?: number;
   }
 
   /** Callback function for IPC event handlers */
@@ -43,6 +61,75 @@ declare module "runtime:ipc" {
   /** Callback function for channel-specific handlers */
   export type ChannelCallback = (payload: unknown, windowId: string) => void;
 
+  /** Context passed to invoke handlers */
+  export interface InvokeContext {
+    /** Window ID that made the invoke */
+    windowId: string;
+    /** Aborted when the renderer cancels the invoke or it times out */
+    signal: AbortSignal;
+  }
+
+  /** Handler for `window.host.invoke(channel, payload)` calls */
+  export type InvokeHandler = (payload: unknown, context: InvokeContext) => unknown | Promise<unknown>;
+
+  /** Options for `handle()` */
+  export interface HandleOptions {
+    /** Fail the invoke with IPC_INVOKE_TIMEOUT if the handler takes longer (ms) */
+    timeoutMs?: number;
+  }
+
+  /** What a full subscription queue does with a new event */
+  export type OverflowPolicy = "dropOldest" | "dropNewest" | "close";
+
+  /** Options for `subscribe()` */
+  export interface SubscribeOptions {
+    /** Only receive events on this channel */
+    channel?: string;
+    /** Only receive events from this window */
+    windowId?: string;
+    /** Maximum queued events (default: 256) */
+    capacity?: number;
+    /** Behavior when the queue is full (default: "dropOldest") */
+    overflow?: OverflowPolicy;
+  }
+
+  /** A filtered view of renderer events with its own bounded queue */
+  export interface Subscription extends AsyncIterable<IpcEvent> {
+    /** Subscription ID */
+    readonly id: number;
+    /** Receive the next event, or null once the subscription is closed */
+    recv(): Promise<IpcEvent | null>;
+    /** Stop receiving; a pending `recv()` resolves to null */
+    close(): void;
+  }
+
+  /** Options for `openStream()` and `sendStream()` */
+  export interface StreamOptions {
+    /** Chunks buffered for the renderer before `write()` waits (default: 8) */
+    highWaterMark?: number;
+  }
+
+  /** Writable side of a byte stream to a renderer */
+  export interface BinaryStreamWriter {
+    /** Stream ID */
+    readonly id: string;
+    /** Queue a chunk; waits while the renderer is `highWaterMark` chunks behind */
+    write(chunk: Uint8Array | ArrayBuffer): Promise<void>;
+    /** End the stream after the renderer reads the queued chunks */
+    close(): void;
+    /** Abort the stream; the renderer's reader errors */
+    abort(): void;
+  }
+
+  /** Error codes carried by rejected invokes */
+  export const IPC_INVOKE_TIMEOUT: 7004;
+  export const IPC_INVOKE_CANCELLED: 7005;
+  export const IPC_NO_HANDLER: 7006;
+  export const IPC_HANDLER_ERROR: 7007;
+
+  /** Error code of a subscription closed by the "close" overflow policy */
+  export const IPC_SUBSCRIPTION_OVERFLOW: 7010;
+
   // ============================================================================
   // Core Functions
   // ============================================================================
@@ -78,7 +165,15 @@ declare module "runtime:ipc" {
   export function windowEvents(): AsyncGenerator<IpcEvent, void, unknown>;
 
   /**
-   * Filter events for a specific window.
+   * Subscribe to renderer events with an independent bounded queue.
+   * Each event goes to every subscription whose filters match.
+   *
+   * @param options - Channel/window filters and queue settings
+   */
+  export function subscribe(options?: SubscribeOptions): Subscription;
+
+  /**
+   * Events from a specific window, on their own subscription.
    * @param windowId - The window ID to filter for
    */
   export function windowEventsFor(
@@ -86,7 +181,7 @@ declare module "runtime:ipc" {
   ): AsyncGenerator<IpcEvent, void, unknown>;
 
   /**
-   * Filter events for a specific channel.
+   * Events on a specific channel, on their own subscription.
    * @param channel - The channel name to filter for
    */
   export function channelEvents(
@@ -116,6 +211,54 @@ declare module "runtime:ipc" {
    */
   export function onChannel(channel: string, callback: ChannelCallback): () => void;
 
+  /**
+   * Handle `window.host.invoke(channel, payload)` calls from renderers.
+   * The handler's result is the reply; a thrown error rejects the renderer's
+   * promise with the same name, message, code and data.
+   *
+   * @param channel - The channel name to answer
+   * @param handler - Called with (payload, { windowId, signal })
+   * @param options - Optional handler timeout
+   * @returns Function that removes the handler
+   */
+  export function handle(
+    channel: string,
+    handler: InvokeHandler,
+    options?: HandleOptions
+  ): () => void;
+
+  // ============================================================================
+  // Binary Functions
+  // ============================================================================
+
+  /**
+   * Send bytes to a window without JSON or base64 encoding.
+   * Renderer listeners receive a `Uint8Array`.
+   */
+  export function sendBinary(
+    windowId: string,
+    channel: string,
+    data: Uint8Array | ArrayBuffer
+  ): Promise<void>;
+
+  /**
+   * Open a byte stream to a window.
+   * Renderer listeners receive a `ReadableStream<Uint8Array>`.
+   */
+  export function openStream(
+    windowId: string,
+    channel: string,
+    options?: StreamOptions
+  ): Promise<BinaryStreamWriter>;
+
+  /** Stream every chunk of an (async) iterable to a window, then close the stream */
+  export function sendStream(
+    windowId: string,
+    channel: string,
+    source: AsyncIterable<Uint8Array | ArrayBuffer> | Iterable<Uint8Array | ArrayBuffer>,
+    options?: StreamOptions
+  ): Promise<void>;
+
   // ============================================================================
   // Broadcast Functions
   // ============================================================================
@@ -132,6 +275,15 @@ declare module "runtime:ipc" {
     channel: string,
     payload?: unknown
   ): Promise<void>;
+
+  /**
+   * Send a message to every open window.
+   * Windows whose channel allowlist excludes the channel do not receive it.
+   *
+   * @param channel - The channel name for the message
+   * @param payload - Optional payload data to send
+   */
+  export function broadcastAll(channel: string, payload?: unknown): Promise<void>;
 }
 "#
     .to_string()
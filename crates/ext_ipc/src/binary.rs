@@ -0,0 +1,347 @@
+//! Binary payloads and streams from Deno to renderers
+//!
+//! JSON messages go through `evaluate_script`, so bytes would have to be
+//! base64-encoded. Instead, binary data is parked in a [`BinaryStore`] shared
+//! with the window manager, and the renderer receives a small descriptor
+//! (`{"__forgeBinary": id}` or `{"__forgeStream": id}`). The preload then
+//! fetches the bytes from `app://__forge_ipc__/blob/<id>` or pulls stream
+//! chunks from `app://__forge_ipc__/stream/<id>`.
+//!
+//! Streams apply backpressure: a writer waits once `high_water_mark` chunks
+//! are queued and the renderer has not pulled them yet. A pull that finds the
+//! queue empty is parked and answered by the next write.
+//!
+//! Entries belong to the window they were sent to; other windows cannot read
+//! them. Unfetched blobs expire after [`BLOB_TTL`], and everything owned by a
+//! window is dropped when it closes.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Host part of the renderer URLs serving binary IPC data
+pub const BINARY_IPC_HOST: &str = "__forge_ipc__";
+
+/// How long a blob waits to be fetched before it is discarded
+pub const BLOB_TTL: Duration = Duration::from_secs(60);
+
+/// Chunks a stream buffers before writers wait
+pub const DEFAULT_HIGH_WATER_MARK: usize = 8;
+
+/// Result of a renderer pulling from a stream
+#[derive(Debug, PartialEq, Eq)]
+pub enum StreamRead {
+    /// Next chunk of data
+    Chunk(Vec<u8>),
+    /// The writer closed the stream and every chunk was read
+    End,
+    /// Unknown stream, owned by another window, or cancelled
+    Gone,
+}
+
+/// Callback answering a renderer pull, possibly from another thread
+pub type StreamReader = Box<dyn FnOnce(StreamRead) + Send>;
+
+/// Error returned when writing to a stream that no longer accepts data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamClosed;
+
+struct Blob {
+    window_id: String,
+    data: Vec<u8>,
+    created: Instant,
+}
+
+struct StreamEntry {
+    window_id: String,
+    queue: VecDeque<Vec<u8>>,
+    high_water_mark: usize,
+    closed: bool,
+    reader: Option<StreamReader>,
+    space: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    next_id: u64,
+    blobs: HashMap<String, Blob>,
+    streams: HashMap<String, StreamEntry>,
+}
+
+impl StoreInner {
+    fn next_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("{:x}-{}", std::process::id(), self.next_id)
+    }
+}
+
+/// Binary payloads and streams waiting to be fetched by renderers
+#[derive(Clone, Default)]
+pub struct BinaryStore {
+    inner: Arc<Mutex<StoreInner>>,
+}
+
+impl BinaryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StoreInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Park a payload for `window_id` and return its ID
+    pub fn put_blob(&self, window_id: &str, data: Vec<u8>) -> String {
+        let mut inner = self.lock();
+        inner
+            .blobs
+            .retain(|_, blob| blob.created.elapsed() < BLOB_TTL);
+        let id = inner.next_id();
+        inner.blobs.insert(
+            id.clone(),
+            Blob {
+                window_id: window_id.to_string(),
+                data,
+                created: Instant::now(),
+            },
+        );
+        id
+    }
+
+    /// Remove and return a payload if it belongs to `window_id`
+    pub fn take_blob(&self, id: &str, window_id: &str) -> Option<Vec<u8>> {
+        let mut inner = self.lock();
+        if inner.blobs.get(id)?.window_id != window_id {
+            return None;
+        }
+        inner.blobs.remove(id).map(|blob| blob.data)
+    }
+
+    /// Create a stream to `window_id` and return its ID
+    pub fn open_stream(&self, window_id: &str, high_water_mark: usize) -> String {
+        let mut inner = self.lock();
+        let id = inner.next_id();
+        inner.streams.insert(
+            id.clone(),
+            StreamEntry {
+                window_id: window_id.to_string(),
+                queue: VecDeque::new(),
+                high_water_mark: high_water_mark.max(1),
+                closed: false,
+                reader: None,
+                space: Arc::new(Notify::new()),
+            },
+        );
+        id
+    }
+
+    /// Queue a chunk, waiting while the stream is at its high water mark
+    ///
+    /// Any number of writers may wait; they all fail once the stream is
+    /// closed or cancelled.
+    pub async fn write(&self, id: &str, chunk: Vec<u8>) -> Result<(), StreamClosed> {
+        loop {
+            let space;
+            let mut notified = {
+                let mut inner = self.lock();
+                let entry = match inner.streams.get_mut(id) {
+                    Some(entry) if !entry.closed => entry,
+                    _ => return Err(StreamClosed),
+                };
+                if let Some(reader) = entry.reader.take() {
+                    drop(inner);
+                    reader(StreamRead::Chunk(chunk));
+                    return Ok(());
+                }
+                if entry.queue.len() < entry.high_water_mark {
+                    entry.queue.push_back(chunk);
+                    return Ok(());
+                }
+                // Register before unlocking so a wakeup can't slip in before the await
+                space = entry.space.clone();
+                let mut notified = Box::pin(space.notified());
+                notified.as_mut().enable();
+                notified
+            };
+            notified.as_mut().await;
+        }
+    }
+
+    /// Mark the end of a stream; queued chunks can still be read
+    pub fn close_stream(&self, id: &str) {
+        let mut inner = self.lock();
+        let Some(entry) = inner.streams.get_mut(id) else {
+            return;
+        };
+        entry.closed = true;
+        // Blocked writers fail now instead of waiting for the reader
+        entry.space.notify_waiters();
+        if entry.queue.is_empty() {
+            if let Some(entry) = inner.streams.remove(id) {
+                drop(inner);
+                if let Some(reader) = entry.reader {
+                    reader(StreamRead::End);
+                }
+            }
+        }
+    }
+
+    /// Drop a stream, failing pending writes and reads
+    ///
+    /// With `window_id`, only cancels the stream if that window owns it.
+    pub fn cancel_stream(&self, id: &str, window_id: Option<&str>) {
+        let mut inner = self.lock();
+        match inner.streams.get(id) {
+            Some(entry) if window_id.is_none_or(|w| entry.window_id == w) => {}
+            _ => return,
+        }
+        if let Some(entry) = inner.streams.remove(id) {
+            drop(inner);
+            // Every blocked writer wakes, finds the stream gone and fails
+            entry.space.notify_waiters();
+            if let Some(reader) = entry.reader {
+                reader(StreamRead::Gone);
+            }
+        }
+    }
+
+    /// Answer a renderer pull now, or once the next chunk is written
+    pub fn read_stream(&self, id: &str, window_id: &str, reader: StreamReader) {
+        let mut inner = self.lock();
+        let entry = match inner.streams.get_mut(id) {
+            Some(entry) if entry.window_id == window_id => entry,
+            _ => {
+                drop(inner);
+                reader(StreamRead::Gone);
+                return;
+            }
+        };
+
+        if let Some(chunk) = entry.queue.pop_front() {
+            entry.space.notify_one();
+            drop(inner);
+            reader(StreamRead::Chunk(chunk));
+        } else if entry.closed {
+            inner.streams.remove(id);
+            drop(inner);
+            reader(StreamRead::End);
+        } else {
+            // Only one pull is outstanding per stream; a newer one wins
+            let previous = entry.reader.replace(reader);
+            drop(inner);
+            if let Some(previous) = previous {
+                previous(StreamRead::Gone);
+            }
+        }
+    }
+
+    /// Drop every blob and stream owned by a closed window
+    pub fn remove_window(&self, window_id: &str) {
+        let mut inner = self.lock();
+        inner.blobs.retain(|_, blob| blob.window_id != window_id);
+        let ids: Vec<String> = inner
+            .streams
+            .iter()
+            .filter(|(_, entry)| entry.window_id == window_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+        drop(inner);
+        for id in ids {
+            self.cancel_stream(&id, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn reader() -> (StreamReader, mpsc::Receiver<StreamRead>) {
+        let (tx, rx) = mpsc::channel();
+        (
+            Box::new(move |read| {
+                let _ = tx.send(read);
+            }),
+            rx,
+        )
+    }
+
+    #[test]
+    fn test_blob_is_window_bound_and_single_use() {
+        let store = BinaryStore::new();
+        let id = store.put_blob("win-1", vec![1, 2, 3]);
+
+        assert_eq!(store.take_blob(&id, "win-2"), None);
+        assert_eq!(store.take_blob(&id, "win-1"), Some(vec![1, 2, 3]));
+        assert_eq!(store.take_blob(&id, "win-1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_stream_backpressure_and_end() {
+        let store = BinaryStore::new();
+        let id = store.open_stream("win-1", 1);
+
+        store.write(&id, vec![1]).await.unwrap();
+        // Queue is full: the second write waits for a pull
+        let blocked = tokio::time::timeout(Duration::from_millis(50), store.write(&id, vec![2]));
+        assert!(blocked.await.is_err());
+
+        let (read, rx) = reader();
+        store.read_stream(&id, "win-1", read);
+        assert_eq!(rx.recv().unwrap(), StreamRead::Chunk(vec![1]));
+        store.write(&id, vec![2]).await.unwrap();
+
+        store.close_stream(&id);
+        assert_eq!(store.write(&id, vec![3]).await, Err(StreamClosed));
+
+        let (read, rx) = reader();
+        store.read_stream(&id, "win-1", read);
+        assert_eq!(rx.recv().unwrap(), StreamRead::Chunk(vec![2]));
+        let (read, rx) = reader();
+        store.read_stream(&id, "win-1", read);
+        assert_eq!(rx.recv().unwrap(), StreamRead::End);
+    }
+
+    #[tokio::test]
+    async fn test_parked_read_and_cancel() {
+        let store = BinaryStore::new();
+        let id = store.open_stream("win-1", DEFAULT_HIGH_WATER_MARK);
+
+        let (read, rx) = reader();
+        store.read_stream(&id, "win-1", read);
+        assert!(rx.try_recv().is_err());
+        store.write(&id, vec![7]).await.unwrap();
+        assert_eq!(rx.recv().unwrap(), StreamRead::Chunk(vec![7]));
+
+        let (read, rx) = reader();
+        store.read_stream(&id, "win-2", read);
+        assert_eq!(rx.recv().unwrap(), StreamRead::Gone);
+
+        store.remove_window("win-1");
+        assert_eq!(store.write(&id, vec![8]).await, Err(StreamClosed));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_fails_every_blocked_writer() {
+        let store = BinaryStore::new();
+        let id = store.open_stream("win-1", 1);
+        store.write(&id, vec![1]).await.unwrap();
+
+        let writers: Vec<_> = (2..4)
+            .map(|n| {
+                let (store, id) = (store.clone(), id.clone());
+                tokio::spawn(async move { store.write(&id, vec![n]).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(writers.iter().all(|writer| !writer.is_finished()));
+
+        store.cancel_stream(&id, None);
+        for writer in writers {
+            let result = tokio::time::timeout(Duration::from_secs(1), writer).await;
+            assert_eq!(result.unwrap().unwrap(), Err(StreamClosed));
+        }
+    }
+}
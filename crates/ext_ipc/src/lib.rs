@@ -1,3 +1,15 @@
+mod binary;
+mod router;
+
+pub use binary::{
+    BinaryStore, StreamClosed, StreamRead, StreamReader, BINARY_IPC_HOST, BLOB_TTL,
+    DEFAULT_HIGH_WATER_MARK,
+};
+pub use router::{
+    IpcRouter, OverflowPolicy, Overflowed, Received, SubscriptionFilter,
+    DEFAULT_SUBSCRIPTION_CAPACITY,
+};
+
 use deno_core::{op2, Extension, OpState};
 use forge_weld_macro::{weld_op, weld_struct};
 use serde::{Deserialize, Serialize};
@@ -22,6 +34,20 @@ pub enum IpcErrorCode {
     PermissionDenied = 7002,
     /// Window not found
     WindowNotFound = 7003,
+    /// Invoke did not receive a reply in time
+    InvokeTimeout = 7004,
+    /// Invoke was cancelled by the caller
+    InvokeCancelled = 7005,
+    /// No handler is registered for the invoked channel
+    NoHandler = 7006,
+    /// Handler threw while processing an invoke
+    HandlerError = 7007,
+    /// Reply for an invoke that is not pending
+    UnknownInvoke = 7008,
+    /// Write to a binary stream that was closed or cancelled by the renderer
+    StreamClosed = 7009,
+    /// Subscription closed because its queue overflowed
+    SubscriptionOverflow = 7010,
 }
 
 /// Custom error type for IPC operations
@@ -42,6 +68,18 @@ pub enum IpcError {
     #[error("[{code}] Window not found: {window_id}")]
     #[class(generic)]
     WindowNotFound { code: u32, window_id: String },
+
+    #[error("[{code}] Unknown invoke: {invoke_id}")]
+    #[class(generic)]
+    UnknownInvoke { code: u32, invoke_id: String },
+
+    #[error("[{code}] Stream closed: {stream_id}")]
+    #[class(generic)]
+    StreamClosed { code: u32, stream_id: String },
+
+    #[error("[{code}] Subscription overflowed: {subscription_id}")]
+    #[class(generic)]
+    SubscriptionOverflow { code: u32, subscription_id: u32 },
 }
 
 impl IpcError {
@@ -72,12 +110,42 @@ impl IpcError {
             window_id: window_id.into(),
         }
     }
+
+    pub fn unknown_invoke(invoke_id: impl Into<String>) -> Self {
+        Self::UnknownInvoke {
+            code: IpcErrorCode::UnknownInvoke as u32,
+            invoke_id: invoke_id.into(),
+        }
+    }
+
+    pub fn stream_closed(stream_id: impl Into<String>) -> Self {
+        Self::StreamClosed {
+            code: IpcErrorCode::StreamClosed as u32,
+            stream_id: stream_id.into(),
+        }
+    }
+
+    pub fn subscription_overflow(subscription_id: u32) -> Self {
+        Self::SubscriptionOverflow {
+            code: IpcErrorCode::SubscriptionOverflow as u32,
+            subscription_id,
+        }
+    }
 }
 
 // ============================================================================
 // Data Types
 // ============================================================================
 
+/// Reserved channel carrying invoke replies to the renderer
+pub const INVOKE_REPLY_CHANNEL: &str = "__invoke_reply__";
+
+/// Reserved channel the renderer uses to cancel a pending invoke
+pub const INVOKE_CANCEL_CHANNEL: &str = "__invoke_cancel__";
+
+/// Reserved channel carrying window lifecycle events (focus, resize, close...)
+pub const WINDOW_EVENT_CHANNEL: &str = "__window__";
+
 /// Event sent from renderer (WebView) to Deno
 #[weld_struct]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +155,9 @@ pub struct IpcEvent {
     pub payload: serde_json::Value,
     /// Event type for window events: "close", "focus", "blur", "resize", "move"
     pub event_type: Option<String>,
+    /// Correlation ID when the renderer expects a reply (`window.host.invoke`)
+    #[serde(default)]
+    pub invoke_id: Option<String>,
 }
 
 /// Command sent from Deno to renderer (WebView)
@@ -97,6 +168,62 @@ pub enum ToRendererCmd {
         channel: String,
         payload: serde_json::Value,
     },
+    /// Settle a pending `window.host.invoke` call
+    ///
+    /// Delivered on [`INVOKE_REPLY_CHANNEL`], bypassing per-window channel
+    /// filtering: the channel was already checked when the invoke arrived.
+    Reply {
+        window_id: String,
+        reply: serde_json::Value,
+    },
+    /// Send a message to every open window
+    ///
+    /// Per-window channel filtering still applies to each window.
+    Broadcast {
+        channel: String,
+        payload: serde_json::Value,
+    },
+}
+
+/// Build a successful invoke reply
+pub fn invoke_ok_reply(invoke_id: &str, value: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "invokeId": invoke_id,
+        "ok": true,
+        "value": value,
+    })
+}
+
+/// Build a failed invoke reply that the renderer rejects with
+pub fn invoke_error_reply(
+    invoke_id: &str,
+    code: IpcErrorCode,
+    message: impl Into<String>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "invokeId": invoke_id,
+        "ok": false,
+        "error": {
+            "name": "IpcInvokeError",
+            "code": code as u32,
+            "message": message.into(),
+        },
+    })
+}
+
+/// Options for `op_ipc_subscribe`
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeOptions {
+    /// Only receive events on this channel
+    pub channel: Option<String>,
+    /// Only receive events from this window
+    pub window_id: Option<String>,
+    /// Maximum queued events (default: 256)
+    pub capacity: Option<usize>,
+    /// "dropOldest" (default), "dropNewest" or "close"
+    pub overflow: Option<OverflowPolicy>,
 }
 
 // ============================================================================
@@ -106,7 +233,12 @@ pub enum ToRendererCmd {
 /// State stored in OpState for IPC operations
 pub struct IpcState {
     pub to_renderer_tx: mpsc::Sender<ToRendererCmd>,
-    pub to_deno_rx: Rc<RefCell<Option<mpsc::Receiver<IpcEvent>>>>,
+    /// Fans renderer events out to subscriptions and tracks pending invokes
+    pub router: IpcRouter,
+    /// Catch-all subscription read by `op_ipc_recv`
+    default_subscription: u32,
+    /// Binary payloads and streams shared with the window manager
+    pub binary: BinaryStore,
 }
 
 // ============================================================================
@@ -210,51 +342,274 @@ async fn op_ipc_send(
 }
 
 /// Receive the next event from any window (blocking)
+///
+/// Reads the catch-all subscription created with the IPC state. Invokes carry
+/// an `invokeId` and are tracked until answered with `op_ipc_reply`. A
+/// renderer-side cancellation of a still-pending invoke is surfaced as an
+/// event with `type: "cancel"` on the original channel; cancellations of
+/// invokes that were already answered are dropped.
 #[weld_op(async)]
 #[op2(async)]
 #[serde]
 async fn op_ipc_recv(state: Rc<RefCell<OpState>>) -> Result<Option<serde_json::Value>, IpcError> {
-    let maybe_rx = {
+    let (router, id) = {
         let s = state.borrow();
         let ipc_state = s.borrow::<IpcState>();
-        let result = ipc_state.to_deno_rx.borrow_mut().take();
-        result
+        (ipc_state.router.clone(), ipc_state.default_subscription)
     };
+    recv_subscription(&router, id).await
+}
 
-    if let Some(mut rx) = maybe_rx {
-        let result = rx.recv().await;
+/// Create a subscription with its own bounded queue and return its ID
+///
+/// Events are delivered to every subscription whose filters match, so a slow
+/// subscriber only fills its own queue.
+#[weld_op]
+#[op2]
+fn op_ipc_subscribe(
+    state: &mut OpState,
+    #[serde] options: SubscribeOptions,
+) -> Result<u32, IpcError> {
+    if let Some(channel) = &options.channel {
+        check_ipc_capability(state, channel)?;
+    }
+    let filter = SubscriptionFilter {
+        channel: options.channel,
+        window_id: options.window_id,
+        capacity: options.capacity.unwrap_or(DEFAULT_SUBSCRIPTION_CAPACITY),
+        overflow: options.overflow.unwrap_or_default(),
+    };
+    Ok(state.borrow::<IpcState>().router.subscribe(filter))
+}
 
-        // Put the receiver back
-        {
-            let s = state.borrow();
-            let ipc_state = s.borrow::<IpcState>();
-            *ipc_state.to_deno_rx.borrow_mut() = Some(rx);
-        }
+/// Receive the next event of a subscription
+///
+/// Returns `null` once the subscription is closed. Events carry `dropped`
+/// when the overflow policy discarded events since the previous receive.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_ipc_subscription_recv(
+    state: Rc<RefCell<OpState>>,
+    id: u32,
+) -> Result<Option<serde_json::Value>, IpcError> {
+    let router = state.borrow().borrow::<IpcState>().router.clone();
+    recv_subscription(&router, id).await
+}
 
-        match result {
-            Some(event) => {
-                tracing::debug!(
-                    window_id = %event.window_id,
-                    channel = %event.channel,
-                    "Received IPC event from renderer"
-                );
-
-                let mut json = serde_json::json!({
-                    "windowId": event.window_id,
-                    "channel": event.channel,
-                    "payload": event.payload,
-                });
-                // Include event_type if present (for window system events)
-                if let Some(ref event_type) = event.event_type {
-                    json["type"] = serde_json::json!(event_type);
-                }
-                Ok(Some(json))
-            }
-            None => Ok(None),
-        }
-    } else {
-        Ok(None)
+/// Close a subscription; a pending receive on it resolves to `null`
+#[weld_op]
+#[op2(fast)]
+fn op_ipc_unsubscribe(state: &OpState, id: u32) {
+    state.borrow::<IpcState>().router.unsubscribe(id);
+}
+
+async fn recv_subscription(
+    router: &IpcRouter,
+    id: u32,
+) -> Result<Option<serde_json::Value>, IpcError> {
+    match router.recv(id).await {
+        Ok(received) => Ok(received.map(|received| event_to_js(received.event, received.dropped))),
+        Err(Overflowed) => Err(IpcError::subscription_overflow(id)),
+    }
+}
+
+/// Convert a routed renderer event to its JS shape
+fn event_to_js(event: IpcEvent, dropped: u64) -> serde_json::Value {
+    tracing::debug!(
+        window_id = %event.window_id,
+        channel = %event.channel,
+        "Received IPC event from renderer"
+    );
+
+    let mut json = serde_json::json!({
+        "windowId": event.window_id,
+        "channel": event.channel,
+        "payload": event.payload,
+    });
+    // Include event_type if present (for window system events)
+    if let Some(ref event_type) = event.event_type {
+        json["type"] = serde_json::json!(event_type);
+    }
+    if let Some(ref invoke_id) = event.invoke_id {
+        json["invokeId"] = serde_json::json!(invoke_id);
+    }
+    if dropped > 0 {
+        json["dropped"] = serde_json::json!(dropped);
+    }
+    json
+}
+
+/// Send a message to every open window
+#[weld_op(async)]
+#[op2(async)]
+async fn op_ipc_broadcast(
+    state: Rc<RefCell<OpState>>,
+    #[string] channel: String,
+    #[serde] payload: serde_json::Value,
+) -> Result<(), IpcError> {
+    let to_renderer_tx = {
+        let s = state.borrow();
+        check_ipc_capability(&s, &channel)?;
+        s.borrow::<IpcState>().to_renderer_tx.clone()
+    };
+
+    to_renderer_tx
+        .send(ToRendererCmd::Broadcast { channel, payload })
+        .await
+        .map_err(|e| IpcError::channel_send(e.to_string()))
+}
+
+/// Declare that Deno handles invokes on a channel
+///
+/// Fails early when the channel is not allowed by the capability checker, so
+/// a misconfigured `handle()` is reported at registration instead of on the
+/// first invoke.
+#[weld_op]
+#[op2(fast)]
+fn op_ipc_handle(state: &OpState, #[string] channel: String) -> Result<(), IpcError> {
+    check_ipc_capability(state, &channel)
+}
+
+/// Settle a pending invoke from a renderer
+///
+/// `reply` is `{ ok: true, value }` or `{ ok: false, error: { name, code,
+/// message, data } }`; the invoke ID is filled in here.
+#[weld_op(async)]
+#[op2(async)]
+async fn op_ipc_reply(
+    state: Rc<RefCell<OpState>>,
+    #[string] window_id: String,
+    #[string] invoke_id: String,
+    #[serde] reply: serde_json::Value,
+) -> Result<(), IpcError> {
+    let to_renderer_tx = {
+        let s = state.borrow();
+        let ipc_state = s.borrow::<IpcState>();
+        let channel = ipc_state
+            .router
+            .take_invoke(&window_id, &invoke_id)
+            .ok_or_else(|| IpcError::unknown_invoke(&invoke_id))?;
+        check_ipc_capability(&s, &channel)?;
+        ipc_state.to_renderer_tx.clone()
+    };
+
+    let mut reply = match reply {
+        serde_json::Value::Object(map) => serde_json::Value::Object(map),
+        value => invoke_ok_reply(&invoke_id, value),
+    };
+    reply["invokeId"] = serde_json::json!(invoke_id);
+
+    to_renderer_tx
+        .send(ToRendererCmd::Reply { window_id, reply })
+        .await
+        .map_err(|e| IpcError::channel_send(e.to_string()))
+}
+
+/// Send bytes to a window without JSON encoding
+///
+/// The renderer receives the payload as a `Uint8Array`.
+#[weld_op(async)]
+#[op2(async)]
+async fn op_ipc_send_binary(
+    state: Rc<RefCell<OpState>>,
+    #[string] window_id: String,
+    #[string] channel: String,
+    #[buffer(copy)] data: Vec<u8>,
+) -> Result<(), IpcError> {
+    let (to_renderer_tx, payload) = {
+        let s = state.borrow();
+        check_ipc_capability(&s, &channel)?;
+        let ipc_state = s.borrow::<IpcState>();
+        let size = data.len();
+        let id = ipc_state.binary.put_blob(&window_id, data);
+        (
+            ipc_state.to_renderer_tx.clone(),
+            serde_json::json!({ "__forgeBinary": id, "size": size }),
+        )
+    };
+
+    to_renderer_tx
+        .send(ToRendererCmd::Send {
+            window_id,
+            channel,
+            payload,
+        })
+        .await
+        .map_err(|e| IpcError::channel_send(e.to_string()))
+}
+
+/// Open a byte stream to a window and return its ID
+///
+/// The renderer receives a `ReadableStream<Uint8Array>` on `channel`.
+#[weld_op(async)]
+#[op2(async)]
+#[string]
+async fn op_ipc_stream_open(
+    state: Rc<RefCell<OpState>>,
+    #[string] window_id: String,
+    #[string] channel: String,
+    #[serde] high_water_mark: Option<usize>,
+) -> Result<String, IpcError> {
+    let (to_renderer_tx, id) = {
+        let s = state.borrow();
+        check_ipc_capability(&s, &channel)?;
+        let ipc_state = s.borrow::<IpcState>();
+        let id = ipc_state.binary.open_stream(
+            &window_id,
+            high_water_mark.unwrap_or(DEFAULT_HIGH_WATER_MARK),
+        );
+        (ipc_state.to_renderer_tx.clone(), id)
+    };
+
+    let sent = to_renderer_tx
+        .send(ToRendererCmd::Send {
+            window_id,
+            channel,
+            payload: serde_json::json!({ "__forgeStream": id }),
+        })
+        .await;
+    if let Err(e) = sent {
+        state
+            .borrow()
+            .borrow::<IpcState>()
+            .binary
+            .cancel_stream(&id, None);
+        return Err(IpcError::channel_send(e.to_string()));
     }
+    Ok(id)
+}
+
+/// Write a chunk to a stream, waiting while the renderer is behind
+#[weld_op(async)]
+#[op2(async)]
+async fn op_ipc_stream_write(
+    state: Rc<RefCell<OpState>>,
+    #[string] stream_id: String,
+    #[buffer(copy)] chunk: Vec<u8>,
+) -> Result<(), IpcError> {
+    let binary = state.borrow().borrow::<IpcState>().binary.clone();
+    binary
+        .write(&stream_id, chunk)
+        .await
+        .map_err(|_| IpcError::stream_closed(&stream_id))
+}
+
+/// Finish a stream; the renderer reads the remaining chunks, then ends
+#[weld_op]
+#[op2(fast)]
+fn op_ipc_stream_close(state: &OpState, #[string] stream_id: String) {
+    state.borrow::<IpcState>().binary.close_stream(&stream_id);
+}
+
+/// Abort a stream; the renderer's reader errors
+#[weld_op]
+#[op2(fast)]
+fn op_ipc_stream_abort(state: &OpState, #[string] stream_id: String) {
+    state
+        .borrow::<IpcState>()
+        .binary
+        .cancel_stream(&stream_id, None);
 }
 
 // ============================================================================
@@ -270,14 +625,31 @@ pub fn ipc_extension() -> Extension {
 }
 
 /// Initialize IPC state in OpState - must be called after creating JsRuntime
+///
+/// Spawns the task routing `to_deno_rx` to subscriptions, so it must be
+/// called inside the Tokio runtime that drives the JsRuntime. `binary` must
+/// be the same store the window manager serves `app://__forge_ipc__/`
+/// requests from, and `router` the one it tells about closed windows.
 pub fn init_ipc_state(
     op_state: &mut OpState,
     to_renderer_tx: mpsc::Sender<ToRendererCmd>,
     to_deno_rx: mpsc::Receiver<IpcEvent>,
+    binary: BinaryStore,
+    router: IpcRouter,
 ) {
+    // Same capacity and drop behavior as the channel recv() used to read
+    let default_subscription = router.subscribe(SubscriptionFilter {
+        capacity: DEFAULT_SUBSCRIPTION_CAPACITY,
+        overflow: OverflowPolicy::DropNewest,
+        ..Default::default()
+    });
+    tokio::spawn(router.clone().run(to_deno_rx));
+
     op_state.put(IpcState {
         to_renderer_tx,
-        to_deno_rx: Rc::new(RefCell::new(Some(to_deno_rx))),
+        router,
+        default_subscription,
+        binary,
     });
 }
 
@@ -305,6 +677,10 @@ mod tests {
         assert_eq!(IpcErrorCode::ChannelRecv as u32, 7001);
         assert_eq!(IpcErrorCode::PermissionDenied as u32, 7002);
         assert_eq!(IpcErrorCode::WindowNotFound as u32, 7003);
+        assert_eq!(IpcErrorCode::InvokeTimeout as u32, 7004);
+        assert_eq!(IpcErrorCode::UnknownInvoke as u32, 7008);
+        assert_eq!(IpcErrorCode::StreamClosed as u32, 7009);
+        assert_eq!(IpcErrorCode::SubscriptionOverflow as u32, 7010);
     }
 
     #[test]
@@ -329,6 +705,7 @@ mod tests {
             channel: "test-channel".to_string(),
             payload: serde_json::json!({"key": "value"}),
             event_type: None,
+            invoke_id: None,
         };
 
         let json = serde_json::to_string(&event).unwrap();
@@ -347,6 +724,7 @@ mod tests {
             channel: "window".to_string(),
             payload: serde_json::Value::Null,
             event_type: Some("close".to_string()),
+            invoke_id: None,
         };
 
         let json = serde_json::to_string(&event).unwrap();
@@ -355,4 +733,98 @@ mod tests {
         let parsed: IpcEvent = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.event_type, Some("close".to_string()));
     }
+
+    #[test]
+    fn test_ipc_event_invoke_id_defaults() {
+        let parsed: IpcEvent = serde_json::from_str(
+            r#"{"window_id":"win-1","channel":"c","payload":null,"event_type":null}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.invoke_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_bookkeeping() {
+        let router = IpcRouter::new();
+        let id = router.subscribe(SubscriptionFilter {
+            capacity: DEFAULT_SUBSCRIPTION_CAPACITY,
+            ..Default::default()
+        });
+
+        let invoke = IpcEvent {
+            window_id: "win-1".to_string(),
+            channel: "get-user".to_string(),
+            payload: serde_json::json!({"id": 1}),
+            event_type: None,
+            invoke_id: Some("i-1".to_string()),
+        };
+        router.route(invoke);
+        let json = recv_subscription(&router, id).await.unwrap().unwrap();
+        assert_eq!(json["invokeId"], "i-1");
+
+        let cancel = IpcEvent {
+            window_id: "win-1".to_string(),
+            channel: INVOKE_CANCEL_CHANNEL.to_string(),
+            payload: serde_json::Value::Null,
+            event_type: None,
+            invoke_id: Some("i-1".to_string()),
+        };
+        router.route(cancel.clone());
+        let json = recv_subscription(&router, id).await.unwrap().unwrap();
+        assert_eq!(json["channel"], "get-user");
+        assert_eq!(json["type"], "cancel");
+
+        // Already settled: the second cancellation is dropped
+        router.route(cancel);
+        router.close();
+        assert!(recv_subscription(&router, id).await.unwrap().is_none());
+        assert!(router.take_invoke("win-1", "i-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscription_overflow_error() {
+        let router = IpcRouter::new();
+        let id = router.subscribe(SubscriptionFilter {
+            capacity: 1,
+            overflow: OverflowPolicy::Close,
+            ..Default::default()
+        });
+        for n in 0..2 {
+            router.route(IpcEvent {
+                window_id: "win-1".to_string(),
+                channel: "tick".to_string(),
+                payload: serde_json::json!(n),
+                event_type: None,
+                invoke_id: None,
+            });
+        }
+
+        let err = recv_subscription(&router, id).await.unwrap_err();
+        assert!(err.to_string().contains("7010"));
+    }
+
+    #[test]
+    fn test_subscribe_options_deserialize() {
+        let options: SubscribeOptions = serde_json::from_value(serde_json::json!({
+            "channel": "chat",
+            "windowId": "main",
+            "overflow": "dropNewest",
+        }))
+        .unwrap();
+        assert_eq!(options.window_id.as_deref(), Some("main"));
+        assert_eq!(options.overflow, Some(OverflowPolicy::DropNewest));
+        assert_eq!(options.capacity, None);
+    }
+
+    #[test]
+    fn test_invoke_error_reply() {
+        let reply = invoke_error_reply("i-2", IpcErrorCode::NoHandler, "no handler for 'x'");
+        assert_eq!(reply["invokeId"], "i-2");
+        assert_eq!(reply["ok"], false);
+        assert_eq!(reply["error"]["code"], 7006);
+
+        let reply = invoke_ok_reply("i-3", serde_json::json!(42));
+        assert_eq!(reply["ok"], true);
+        assert_eq!(reply["value"], 42);
+    }
 }
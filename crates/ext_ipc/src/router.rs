@@ -0,0 +1,545 @@
+//! Routing of renderer events to subscriptions
+//!
+//! Every event sent by a renderer is fanned out to each subscription whose
+//! channel and window filters match, and each subscription has its own
+//! bounded queue. A subscriber that falls behind only affects its own queue;
+//! what happens when that queue is full is decided by its
+//! [`OverflowPolicy`].
+//!
+//! The router also tracks pending invokes so a renderer's
+//! `__invoke_cancel__` can be delivered on the channel the invoke was made
+//! on, and so replies can be validated. A renderer that navigates away or
+//! hangs never cancels, so invokes left unanswered for the invoke TTL are
+//! dropped, and their handlers see a cancellation as if the renderer had
+//! sent one. Closing a window does the same for all of its invokes at once.
+
+use crate::{IpcEvent, INVOKE_CANCEL_CHANNEL, WINDOW_EVENT_CHANNEL};
+use forge_weld_macro::weld_enum;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify};
+
+/// Queue size used when a subscription does not ask for one
+pub const DEFAULT_SUBSCRIPTION_CAPACITY: usize = 256;
+
+/// How long an unanswered invoke is tracked before it is dropped
+pub const DEFAULT_INVOKE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// What a full subscription queue does with a new event
+#[weld_enum]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued event to make room
+    #[default]
+    DropOldest,
+    /// Discard the new event
+    DropNewest,
+    /// Close the subscription; its next receive fails
+    Close,
+}
+
+/// Filters and queue settings for a subscription
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionFilter {
+    /// Only events on this channel (all channels when `None`)
+    pub channel: Option<String>,
+    /// Only events from this window (all windows when `None`)
+    pub window_id: Option<String>,
+    /// Maximum queued events
+    pub capacity: usize,
+    /// Behavior when the queue is full
+    pub overflow: OverflowPolicy,
+}
+
+/// An event taken from a subscription queue
+#[derive(Debug)]
+pub struct Received {
+    pub event: IpcEvent,
+    /// Events discarded by the overflow policy since the previous receive
+    pub dropped: u64,
+}
+
+/// Error receiving from a subscription closed by [`OverflowPolicy::Close`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflowed;
+
+struct Subscription {
+    filter: SubscriptionFilter,
+    queue: VecDeque<IpcEvent>,
+    dropped: u64,
+    overflowed: bool,
+    notify: Arc<Notify>,
+}
+
+impl Subscription {
+    fn matches(&self, event: &IpcEvent) -> bool {
+        self.filter
+            .channel
+            .as_deref()
+            .is_none_or(|channel| channel == event.channel)
+            && self
+                .filter
+                .window_id
+                .as_deref()
+                .is_none_or(|window_id| window_id == event.window_id)
+    }
+
+    fn push(&mut self, event: IpcEvent) {
+        if self.overflowed {
+            return;
+        }
+        if self.queue.len() >= self.filter.capacity {
+            match self.filter.overflow {
+                OverflowPolicy::DropOldest => {
+                    self.queue.pop_front();
+                    self.dropped += 1;
+                }
+                OverflowPolicy::DropNewest => {
+                    self.dropped += 1;
+                    return;
+                }
+                OverflowPolicy::Close => {
+                    self.overflowed = true;
+                    self.queue.clear();
+                    self.notify.notify_one();
+                    return;
+                }
+            }
+        }
+        self.queue.push_back(event);
+        self.notify.notify_one();
+    }
+}
+
+/// An invoke awaiting a reply
+struct PendingInvoke {
+    channel: String,
+    received: Instant,
+}
+
+/// The event a handler sees when its invoke is cancelled
+fn cancel_event(window_id: &str, invoke_id: &str, channel: &str) -> IpcEvent {
+    IpcEvent {
+        window_id: window_id.to_string(),
+        channel: channel.to_string(),
+        payload: serde_json::Value::Null,
+        event_type: Some("cancel".to_string()),
+        invoke_id: Some(invoke_id.to_string()),
+    }
+}
+
+struct RouterInner {
+    next_id: u32,
+    subscriptions: HashMap<u32, Subscription>,
+    /// Invokes awaiting a reply, keyed by (window ID, invoke ID)
+    pending_invokes: HashMap<(String, String), PendingInvoke>,
+    invoke_ttl: Duration,
+    last_expiry: Instant,
+    /// The renderer side is gone; no more events will arrive
+    closed: bool,
+}
+
+impl RouterInner {
+    /// Drop invokes older than the TTL, returning their cancellations
+    ///
+    /// Runs at most every quarter TTL, so routing stays cheap.
+    fn expire_invokes(&mut self, now: Instant) -> Vec<IpcEvent> {
+        if now.duration_since(self.last_expiry) < self.invoke_ttl / 4 {
+            return Vec::new();
+        }
+        self.last_expiry = now;
+
+        let ttl = self.invoke_ttl;
+        let mut cancelled = Vec::new();
+        self.pending_invokes
+            .retain(|(window_id, invoke_id), pending| {
+                if now.duration_since(pending.received) < ttl {
+                    return true;
+                }
+                cancelled.push(cancel_event(window_id, invoke_id, &pending.channel));
+                false
+            });
+        cancelled
+    }
+
+    /// Drop every invoke from a window, returning their cancellations
+    fn forget_window(&mut self, window_id: &str) -> Vec<IpcEvent> {
+        let mut cancelled = Vec::new();
+        self.pending_invokes.retain(|(id, invoke_id), pending| {
+            if id != window_id {
+                return true;
+            }
+            cancelled.push(cancel_event(id, invoke_id, &pending.channel));
+            false
+        });
+        cancelled
+    }
+
+    fn deliver(&mut self, events: Vec<IpcEvent>) {
+        for event in events {
+            for subscription in self.subscriptions.values_mut() {
+                if subscription.matches(&event) {
+                    subscription.push(event.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Fans renderer events out to subscription queues
+#[derive(Clone)]
+pub struct IpcRouter {
+    inner: Arc<Mutex<RouterInner>>,
+}
+
+impl Default for IpcRouter {
+    fn default() -> Self {
+        Self::with_invoke_ttl(DEFAULT_INVOKE_TTL)
+    }
+}
+
+impl IpcRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a router that drops invokes unanswered for `ttl`
+    pub fn with_invoke_ttl(ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(RouterInner {
+                next_id: 0,
+                subscriptions: HashMap::new(),
+                pending_invokes: HashMap::new(),
+                invoke_ttl: ttl,
+                last_expiry: Instant::now(),
+                closed: false,
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RouterInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Route events from the renderer channel until it closes
+    pub async fn run(self, mut rx: mpsc::Receiver<IpcEvent>) {
+        while let Some(event) = rx.recv().await {
+            self.route(event);
+        }
+        self.close();
+    }
+
+    /// Create a subscription and return its ID
+    pub fn subscribe(&self, mut filter: SubscriptionFilter) -> u32 {
+        filter.capacity = filter.capacity.max(1);
+        let mut inner = self.lock();
+        inner.next_id += 1;
+        let id = inner.next_id;
+        inner.subscriptions.insert(
+            id,
+            Subscription {
+                filter,
+                queue: VecDeque::new(),
+                dropped: 0,
+                overflowed: false,
+                notify: Arc::new(Notify::new()),
+            },
+        );
+        id
+    }
+
+    /// Remove a subscription; a pending receive on it returns `None`
+    pub fn unsubscribe(&self, id: u32) {
+        if let Some(subscription) = self.lock().subscriptions.remove(&id) {
+            subscription.notify.notify_waiters();
+            subscription.notify.notify_one();
+        }
+    }
+
+    /// Deliver an event to every matching subscription
+    pub fn route(&self, mut event: IpcEvent) {
+        let mut inner = self.lock();
+        let now = Instant::now();
+        let mut events = inner.expire_invokes(now);
+
+        if event.channel == INVOKE_CANCEL_CHANNEL {
+            // Only cancellations of invokes that are still pending are delivered,
+            // on the channel the invoke was made on
+            let pending = event.invoke_id.clone().and_then(|invoke_id| {
+                inner
+                    .pending_invokes
+                    .remove(&(event.window_id.clone(), invoke_id))
+            });
+            if let Some(pending) = pending {
+                event.channel = pending.channel;
+                event.payload = serde_json::Value::Null;
+                event.event_type = Some("cancel".to_string());
+                events.push(event);
+            }
+        } else if event.channel == WINDOW_EVENT_CHANNEL
+            && event.event_type.as_deref() == Some("close")
+        {
+            // Routed after everything the window sent, so no invoke is left behind
+            events.extend(inner.forget_window(&event.window_id));
+            events.push(event);
+        } else {
+            if let Some(invoke_id) = &event.invoke_id {
+                inner.pending_invokes.insert(
+                    (event.window_id.clone(), invoke_id.clone()),
+                    PendingInvoke {
+                        channel: event.channel.clone(),
+                        received: now,
+                    },
+                );
+            }
+            events.push(event);
+        }
+
+        inner.deliver(events);
+    }
+
+    /// Cancel every invoke still pending from a closed window
+    pub fn forget_window(&self, window_id: &str) {
+        let mut inner = self.lock();
+        let cancelled = inner.forget_window(window_id);
+        inner.deliver(cancelled);
+    }
+
+    /// Wait for the next event of a subscription
+    ///
+    /// Returns `Ok(None)` once the subscription is removed, or the renderer
+    /// side has closed and the queue is drained.
+    pub async fn recv(&self, id: u32) -> Result<Option<Received>, Overflowed> {
+        loop {
+            let notify = {
+                let mut inner = self.lock();
+                let closed = inner.closed;
+                let Some(subscription) = inner.subscriptions.get_mut(&id) else {
+                    return Ok(None);
+                };
+                if subscription.overflowed {
+                    inner.subscriptions.remove(&id);
+                    return Err(Overflowed);
+                }
+                if let Some(event) = subscription.queue.pop_front() {
+                    let dropped = std::mem::take(&mut subscription.dropped);
+                    return Ok(Some(Received { event, dropped }));
+                }
+                if closed {
+                    return Ok(None);
+                }
+                subscription.notify.clone()
+            };
+            notify.notified().await;
+        }
+    }
+
+    /// Forget a pending invoke, returning the channel it was made on
+    ///
+    /// Invokes older than the TTL are treated as unknown, even if they haven't
+    /// been swept yet.
+    pub fn take_invoke(&self, window_id: &str, invoke_id: &str) -> Option<String> {
+        let mut inner = self.lock();
+        let ttl = inner.invoke_ttl;
+        inner
+            .pending_invokes
+            .remove(&(window_id.to_string(), invoke_id.to_string()))
+            .filter(|pending| pending.received.elapsed() < ttl)
+            .map(|pending| pending.channel)
+    }
+
+    /// Mark the renderer side as gone and wake every receiver
+    pub fn close(&self) {
+        let mut inner = self.lock();
+        inner.closed = true;
+        for subscription in inner.subscriptions.values() {
+            subscription.notify.notify_waiters();
+            subscription.notify.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(window_id: &str, channel: &str, n: u64) -> IpcEvent {
+        IpcEvent {
+            window_id: window_id.to_string(),
+            channel: channel.to_string(),
+            payload: serde_json::json!(n),
+            event_type: None,
+            invoke_id: None,
+        }
+    }
+
+    fn filter(channel: Option<&str>, window_id: Option<&str>) -> SubscriptionFilter {
+        SubscriptionFilter {
+            channel: channel.map(str::to_string),
+            window_id: window_id.map(str::to_string),
+            capacity: DEFAULT_SUBSCRIPTION_CAPACITY,
+            overflow: OverflowPolicy::DropOldest,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_channel_and_window() {
+        let router = IpcRouter::new();
+        let all = router.subscribe(filter(None, None));
+        let chat = router.subscribe(filter(Some("chat"), None));
+        let main_chat = router.subscribe(filter(Some("chat"), Some("main")));
+
+        router.route(event("main", "chat", 1));
+        router.route(event("other", "chat", 2));
+        router.route(event("main", "log", 3));
+
+        let payloads = |id| {
+            let router = router.clone();
+            async move {
+                let mut seen = Vec::new();
+                router.close();
+                while let Some(received) = router.recv(id).await.unwrap() {
+                    seen.push(received.event.payload.as_u64().unwrap());
+                }
+                seen
+            }
+        };
+        assert_eq!(payloads(all).await, vec![1, 2, 3]);
+        assert_eq!(payloads(chat).await, vec![1, 2]);
+        assert_eq!(payloads(main_chat).await, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policies() {
+        let router = IpcRouter::new();
+        let oldest = router.subscribe(SubscriptionFilter {
+            capacity: 2,
+            ..filter(None, None)
+        });
+        let newest = router.subscribe(SubscriptionFilter {
+            capacity: 2,
+            overflow: OverflowPolicy::DropNewest,
+            ..filter(None, None)
+        });
+        let close = router.subscribe(SubscriptionFilter {
+            capacity: 2,
+            overflow: OverflowPolicy::Close,
+            ..filter(None, None)
+        });
+
+        for n in 1..=3 {
+            router.route(event("main", "tick", n));
+        }
+
+        let first = router.recv(oldest).await.unwrap().unwrap();
+        assert_eq!(first.event.payload, 2);
+        assert_eq!(first.dropped, 1);
+
+        let first = router.recv(newest).await.unwrap().unwrap();
+        assert_eq!(first.event.payload, 1);
+        assert_eq!(first.dropped, 1);
+        assert_eq!(router.recv(newest).await.unwrap().unwrap().dropped, 0);
+
+        assert_eq!(router.recv(close).await.unwrap_err(), Overflowed);
+        assert!(router.recv(close).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invoke_cancel_rewritten_to_original_channel() {
+        let router = IpcRouter::new();
+        let sub = router.subscribe(filter(Some("get-user"), None));
+
+        let mut invoke = event("main", "get-user", 1);
+        invoke.invoke_id = Some("i-1".to_string());
+        router.route(invoke);
+
+        let mut cancel = event("main", INVOKE_CANCEL_CHANNEL, 0);
+        cancel.invoke_id = Some("i-1".to_string());
+        router.route(cancel.clone());
+        // Already cancelled: dropped
+        router.route(cancel);
+
+        let received = router.recv(sub).await.unwrap().unwrap();
+        assert_eq!(received.event.invoke_id.as_deref(), Some("i-1"));
+        let received = router.recv(sub).await.unwrap().unwrap();
+        assert_eq!(received.event.event_type.as_deref(), Some("cancel"));
+        assert_eq!(received.event.channel, "get-user");
+        assert!(router.take_invoke("main", "i-1").is_none());
+
+        router.close();
+        assert!(router.recv(sub).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_invokes_expire() {
+        let router = IpcRouter::with_invoke_ttl(Duration::from_millis(20));
+        let sub = router.subscribe(filter(Some("slow"), None));
+
+        let mut invoke = event("main", "slow", 1);
+        invoke.invoke_id = Some("i-1".to_string());
+        router.route(invoke);
+        std::thread::sleep(Duration::from_millis(30));
+        // Too late to reply, even before the next sweep
+        assert!(router.take_invoke("main", "i-1").is_none());
+
+        let mut invoke = event("main", "slow", 2);
+        invoke.invoke_id = Some("i-2".to_string());
+        router.route(invoke);
+        std::thread::sleep(Duration::from_millis(30));
+        // Any routed event sweeps expired invokes and cancels their handlers
+        router.route(event("main", "other", 3));
+
+        assert_eq!(router.recv(sub).await.unwrap().unwrap().event.payload, 1);
+        assert_eq!(router.recv(sub).await.unwrap().unwrap().event.payload, 2);
+        let cancelled = router.recv(sub).await.unwrap().unwrap().event;
+        assert_eq!(cancelled.event_type.as_deref(), Some("cancel"));
+        assert_eq!(cancelled.invoke_id.as_deref(), Some("i-2"));
+        assert!(router.take_invoke("main", "i-2").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_closed_window_invokes_are_cancelled() {
+        let router = IpcRouter::new();
+        let sub = router.subscribe(filter(Some("slow"), None));
+
+        for (window_id, invoke_id) in [("main", "i-1"), ("other", "i-2"), ("main", "i-3")] {
+            let mut invoke = event(window_id, "slow", 0);
+            invoke.invoke_id = Some(invoke_id.to_string());
+            router.route(invoke);
+        }
+        let mut close = event("main", WINDOW_EVENT_CHANNEL, 0);
+        close.event_type = Some("close".to_string());
+        router.route(close);
+        router.forget_window("other");
+
+        let mut cancelled = Vec::new();
+        router.close();
+        while let Some(received) = router.recv(sub).await.unwrap() {
+            if received.event.event_type.as_deref() == Some("cancel") {
+                cancelled.push(received.event.invoke_id.unwrap());
+            }
+        }
+        cancelled.sort();
+        assert_eq!(cancelled, ["i-1", "i-2", "i-3"]);
+        assert!(router.take_invoke("main", "i-1").is_none());
+        assert!(router.take_invoke("other", "i-2").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_wakes_receiver() {
+        let router = IpcRouter::new();
+        let sub = router.subscribe(filter(None, None));
+
+        let waiting = tokio::spawn({
+            let router = router.clone();
+            async move { router.recv(sub).await }
+        });
+        tokio::task::yield_now().await;
+        router.unsubscribe(sub);
+
+        assert!(waiting.await.unwrap().unwrap().is_none());
+    }
+}
@@ -211,6 +211,8 @@
 //! `op_monitor_processes` returns only the top 50 processes sorted by CPU usage
 //! to prevent overwhelming the runtime. Full process list access would require
 //! querying thousands of processes on typical systems, which is expensive.
+//! Listing other processes also requires `permissions.monitor.processes`
+//! when the runtime installs a [`MonitorCapabilityChecker`].
 //!
 //! ## Platform Support
 //!
@@ -322,6 +324,10 @@ pub enum MonitorError {
     #[class(generic)]
     ProcessNotFound { code: u32, message: String },
 
+    #[error("[{code}] Permission denied: {message}")]
+    #[class(generic)]
+    PermissionDenied { code: u32, message: String },
+
     #[error("[{code}] Invalid subscription: {message}")]
     #[class(generic)]
     InvalidSubscription { code: u32, message: String },
@@ -357,6 +363,13 @@ impl MonitorError {
         }
     }
 
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        Self::PermissionDenied {
+            code: MonitorErrorCode::PermissionDenied as u32,
+            message: message.into(),
+        }
+    }
+
     pub fn invalid_subscription(message: impl Into<String>) -> Self {
         Self::InvalidSubscription {
             code: MonitorErrorCode::InvalidSubscription as u32,
@@ -724,10 +737,58 @@ impl Default for MonitorState {
     }
 }
 
+// ============================================================================
+// Capability Checker
+// ============================================================================
+
+/// Capability checker trait for monitor operations
+pub trait MonitorCapabilityChecker: Send + Sync {
+    /// Check whether other processes on the system may be enumerated
+    fn check_processes(&self) -> Result<(), String>;
+}
+
+/// Default permissive checker
+pub struct PermissiveChecker;
+
+impl MonitorCapabilityChecker for PermissiveChecker {
+    fn check_processes(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Wrapper to store capability checker in OpState
+pub struct MonitorCapabilities {
+    pub checker: Arc<dyn MonitorCapabilityChecker>,
+}
+
+impl Default for MonitorCapabilities {
+    fn default() -> Self {
+        Self {
+            checker: Arc::new(PermissiveChecker),
+        }
+    }
+}
+
 /// Initialize monitor state in OpState
-pub fn init_monitor_state(op_state: &mut OpState) {
+pub fn init_monitor_state(
+    op_state: &mut OpState,
+    capabilities: Option<Arc<dyn MonitorCapabilityChecker>>,
+) {
     debug!("Initializing monitor state");
     op_state.put(MonitorState::default());
+    if let Some(caps) = capabilities {
+        op_state.put(MonitorCapabilities { checker: caps });
+    }
+}
+
+fn check_monitor_processes(state: &OpState) -> Result<(), MonitorError> {
+    if let Some(caps) = state.try_borrow::<MonitorCapabilities>() {
+        caps.checker
+            .check_processes()
+            .map_err(MonitorError::permission_denied)
+    } else {
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -895,6 +956,8 @@ pub fn op_monitor_process_self(state: &mut OpState) -> Result<ProcessInfo, Monit
 #[op2]
 #[serde]
 pub fn op_monitor_processes(state: &mut OpState) -> Result<Vec<ProcessInfo>, MonitorError> {
+    check_monitor_processes(state)?;
+
     let monitor_state = state.borrow_mut::<MonitorState>();
     monitor_state.system.refresh_processes_specifics(
         ProcessesToUpdate::All,
@@ -13,6 +13,40 @@ fn main() {
             "op_net_ws_close",
             // Streaming fetch
             "op_net_fetch_stream",
+            // Uploads, progress and downloads
+            "op_net_body_open",
+            "op_net_body_write",
+            "op_net_body_bytes",
+            "op_net_body_close",
+            "op_net_progress_open",
+            "op_net_progress_next",
+            "op_net_progress_close",
+            "op_net_download",
+            // Cookies
+            "op_net_cookies_clear",
+            // HTTP server
+            "op_net_serve",
+            "op_net_server_next",
+            "op_net_server_body_read",
+            "op_net_server_respond",
+            "op_net_server_response_write",
+            "op_net_server_response_close",
+            "op_net_server_shutdown",
+            // Sockets
+            "op_net_tcp_connect",
+            "op_net_tcp_listen",
+            "op_net_unix_connect",
+            "op_net_unix_listen",
+            "op_net_listener_accept",
+            "op_net_listener_close",
+            "op_net_conn_read",
+            "op_net_conn_write",
+            "op_net_conn_close_write",
+            "op_net_conn_close",
+            "op_net_udp_bind",
+            "op_net_udp_send",
+            "op_net_udp_recv",
+            "op_net_udp_close",
         ])
         .generate_sdk_module("sdk")
         .use_inventory_types()
@@ -0,0 +1,566 @@
+//! HTTP client configuration
+//!
+//! The manifest's `[http]` table configures the app's HTTP client: proxies
+//! (system settings, an explicit proxy or a PAC script), extra root
+//! certificates, a client identity for mutual TLS, the cookie jar, redirects
+//! and HTTP/2. A fetch can override any of these through its `client` option.
+//! One `reqwest::Client` is built per distinct configuration and reused, so
+//! overrides keep connection pooling; only the most recently used override
+//! clients are kept. A `user_agent` override is sent as a request header
+//! instead, so it never needs a client of its own.
+//!
+//! Certificates and keys are PEM text. The runtime reads the files named in
+//! the manifest before the client is built; requests from JavaScript must pass
+//! the PEM itself, so they cannot read files the app has no access to.
+
+use crate::cookies::CookieJar;
+use crate::pac::{system_pac_url, PacResolver};
+use crate::{NetCapabilityChecker, NetError};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// User agent unless the configuration names another
+pub const DEFAULT_USER_AGENT: &str = "Forge/0.1";
+/// Redirects followed before a request fails
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+/// Clients kept for per-request configurations
+const MAX_OVERRIDE_CLIENTS: usize = 16;
+
+const PEM_PREFIX: &str = "-----BEGIN";
+
+/// How requests find their proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Proxy variables and OS settings, including a system PAC URL
+    System,
+    /// Never use a proxy
+    Direct,
+    /// The proxies given in `url`, `http` and `https`
+    Manual,
+    /// Ask the PAC script at `pac_url`
+    Pac,
+}
+
+/// Proxy settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Defaults to `manual` when a proxy URL is given, `pac` when `pac_url`
+    /// is given and `system` otherwise
+    pub mode: Option<ProxyMode>,
+    /// Proxy for every scheme, e.g. `http://proxy.corp:8080`
+    pub url: Option<String>,
+    /// Proxy for `http://` requests only
+    pub http: Option<String>,
+    /// Proxy for `https://` requests only
+    pub https: Option<String>,
+    /// Hosts reached directly (`NO_PROXY` syntax: domains, IPs, CIDR ranges)
+    pub no_proxy: Option<Vec<String>>,
+    /// PAC script URL (http, https or file); detected from the OS in `pac`
+    /// mode when omitted
+    pub pac_url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl ProxyConfig {
+    pub fn mode(&self) -> ProxyMode {
+        match self.mode {
+            Some(mode) => mode,
+            None if self.url.is_some() || self.http.is_some() || self.https.is_some() => {
+                ProxyMode::Manual
+            }
+            None if self.pac_url.is_some() => ProxyMode::Pac,
+            None => ProxyMode::System,
+        }
+    }
+
+    /// The PAC script consulted in `pac` and `system` mode
+    fn script_url(&self) -> Option<String> {
+        match self.mode() {
+            ProxyMode::Direct | ProxyMode::Manual => None,
+            ProxyMode::Pac => self.pac_url.clone().or_else(system_pac_url),
+            ProxyMode::System => system_pac_url(),
+        }
+    }
+}
+
+/// What happens when a response redirects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectMode {
+    /// Follow up to `max_redirects` hops to hosts the app may reach
+    Follow,
+    /// Return the 3xx response as is
+    Manual,
+    /// Fail the request
+    Error,
+}
+
+/// HTTP version preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Http2Mode {
+    /// HTTP/2 when the server offers it over TLS, HTTP/1.1 otherwise
+    Auto,
+    /// Only HTTP/1.1
+    Http1,
+    /// HTTP/2 without negotiation (prior knowledge), also over plain HTTP
+    Http2,
+}
+
+/// HTTP client settings from the manifest's `[http]` table or a fetch's
+/// `client` option. Unset fields fall back to the manifest, then to the
+/// defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    pub user_agent: Option<String>,
+    pub proxy: Option<ProxyConfig>,
+    /// Extra trusted root certificates (PEM; file paths in the manifest)
+    pub ca_certs: Option<Vec<String>>,
+    /// Trust the operating system's certificate store (default: true)
+    pub system_certs: Option<bool>,
+    /// Client certificate chain for mutual TLS (PEM; a path in the manifest)
+    pub client_cert: Option<String>,
+    /// Private key for `client_cert` (PEM; a path in the manifest)
+    pub client_key: Option<String>,
+    /// Send and store cookies using the app's jar (default: false)
+    pub cookies: Option<bool>,
+    /// Redirect handling (default: follow)
+    pub redirect: Option<RedirectMode>,
+    /// Most redirects followed (default: 10)
+    pub max_redirects: Option<usize>,
+    /// HTTP version preference (default: auto)
+    pub http2: Option<Http2Mode>,
+}
+
+fn read_pem(value: &str, base_dir: &Path) -> Result<String, NetError> {
+    if value.trim_start().starts_with(PEM_PREFIX) {
+        return Ok(value.to_string());
+    }
+    let path = base_dir.join(value);
+    std::fs::read_to_string(&path)
+        .map_err(|e| NetError::tls_config(format!("{}: {}", path.display(), e)))
+}
+
+fn pem_bytes<'a>(value: &'a str, field: &str) -> Result<&'a [u8], NetError> {
+    if value.trim_start().starts_with(PEM_PREFIX) {
+        Ok(value.as_bytes())
+    } else {
+        Err(NetError::tls_config(format!(
+            "{} must be PEM text; certificate files can only be named in the manifest",
+            field
+        )))
+    }
+}
+
+impl HttpClientConfig {
+    /// Replace certificate and key file paths with their PEM contents.
+    /// Relative paths are resolved against `base_dir` (the app directory).
+    pub fn load_files(&mut self, base_dir: &Path) -> Result<(), NetError> {
+        if let Some(certs) = &mut self.ca_certs {
+            for cert in certs.iter_mut() {
+                *cert = read_pem(cert, base_dir)?;
+            }
+        }
+        if let Some(cert) = &mut self.client_cert {
+            *cert = read_pem(cert, base_dir)?;
+        }
+        if let Some(key) = &mut self.client_key {
+            *key = read_pem(key, base_dir)?;
+        }
+        Ok(())
+    }
+
+    /// This configuration with the fields set in `overrides` replaced.
+    pub fn merged(&self, overrides: &HttpClientConfig) -> HttpClientConfig {
+        HttpClientConfig {
+            user_agent: overrides.user_agent.clone().or(self.user_agent.clone()),
+            proxy: overrides.proxy.clone().or(self.proxy.clone()),
+            ca_certs: overrides.ca_certs.clone().or(self.ca_certs.clone()),
+            system_certs: overrides.system_certs.or(self.system_certs),
+            client_cert: overrides.client_cert.clone().or(self.client_cert.clone()),
+            client_key: overrides.client_key.clone().or(self.client_key.clone()),
+            cookies: overrides.cookies.or(self.cookies),
+            redirect: overrides.redirect.or(self.redirect),
+            max_redirects: overrides.max_redirects.or(self.max_redirects),
+            http2: overrides.http2.or(self.http2),
+        }
+    }
+
+    /// The PAC script requests consult, if any
+    fn pac_url(&self) -> Option<String> {
+        match &self.proxy {
+            Some(proxy) => proxy.script_url(),
+            None => system_pac_url(),
+        }
+    }
+
+    /// Proxy URLs named by this configuration, for permission checks
+    pub fn proxy_urls(&self) -> Vec<&str> {
+        let Some(proxy) = &self.proxy else {
+            return Vec::new();
+        };
+        [&proxy.url, &proxy.http, &proxy.https, &proxy.pac_url]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+fn proxy_error(e: reqwest::Error) -> NetError {
+    NetError::request_build_error(format!("Invalid proxy: {}", e))
+}
+
+/// Shared state needed to build clients
+struct ClientContext {
+    cookie_jar: Arc<CookieJar>,
+    checker: Option<Arc<dyn NetCapabilityChecker>>,
+    /// PAC resolvers by script URL, shared by every client using the script
+    pac: Mutex<HashMap<String, Arc<PacResolver>>>,
+}
+
+impl ClientContext {
+    fn pac_resolver(&self, pac_url: &str) -> Result<Arc<PacResolver>, NetError> {
+        let mut pac = self.pac.lock().unwrap();
+        if let Some(resolver) = pac.get(pac_url) {
+            return Ok(resolver.clone());
+        }
+        let resolver = Arc::new(PacResolver::spawn(pac_url)?);
+        pac.insert(pac_url.to_string(), resolver.clone());
+        Ok(resolver)
+    }
+
+    fn apply_proxy(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+        config: &ProxyConfig,
+    ) -> Result<reqwest::ClientBuilder, NetError> {
+        let no_proxy = config
+            .no_proxy
+            .as_ref()
+            .and_then(|hosts| reqwest::NoProxy::from_string(&hosts.join(",")));
+        let auth = |proxy: reqwest::Proxy| match (&config.username, &config.password) {
+            (Some(username), password) => {
+                proxy.basic_auth(username, password.as_deref().unwrap_or(""))
+            }
+            _ => proxy,
+        };
+
+        match config.mode() {
+            ProxyMode::Direct => return Ok(builder.no_proxy()),
+            ProxyMode::Manual => {
+                let proxies = [
+                    config.url.as_deref().map(reqwest::Proxy::all),
+                    config.http.as_deref().map(reqwest::Proxy::http),
+                    config.https.as_deref().map(reqwest::Proxy::https),
+                ];
+                for proxy in proxies.into_iter().flatten() {
+                    let proxy = proxy.map_err(proxy_error)?;
+                    builder = builder.proxy(auth(proxy).no_proxy(no_proxy.clone()));
+                }
+                return Ok(builder);
+            }
+            // reqwest reads the proxy variables and OS settings itself in
+            // system mode, unless the OS names a PAC script
+            ProxyMode::Pac | ProxyMode::System => {}
+        }
+
+        match config.script_url() {
+            Some(pac_url) => {
+                debug!(url = %pac_url, "net.http using PAC script");
+                let resolver = self.pac_resolver(&pac_url)?;
+                let proxy = reqwest::Proxy::custom(move |url| resolver.proxy_for(url));
+                Ok(builder.proxy(auth(proxy).no_proxy(no_proxy)))
+            }
+            None if config.mode() == ProxyMode::Pac => Err(NetError::request_build_error(
+                "PAC proxy mode needs pac_url; none is configured in the OS",
+            )),
+            None => Ok(builder),
+        }
+    }
+
+    fn redirect_policy(&self, config: &HttpClientConfig) -> reqwest::redirect::Policy {
+        let max = config.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS);
+        match config.redirect.unwrap_or(RedirectMode::Follow) {
+            RedirectMode::Manual => reqwest::redirect::Policy::none(),
+            RedirectMode::Error => reqwest::redirect::Policy::custom(|attempt| {
+                let location = attempt.url().to_string();
+                attempt.error(format!("Redirected to {}", location))
+            }),
+            RedirectMode::Follow => {
+                let checker = self.checker.clone();
+                reqwest::redirect::Policy::custom(move |attempt| {
+                    if attempt.previous().len() > max {
+                        return attempt.error(format!("Too many redirects (limit {})", max));
+                    }
+                    // Every hop must be a host the app may reach
+                    if let Some(checker) = &checker {
+                        let host = crate::extract_host(attempt.url().as_str())
+                            .map_err(|e| e.to_string())
+                            .and_then(|host| checker.check_connect(&host));
+                        if let Err(e) = host {
+                            return attempt.error(format!("Redirect blocked: {}", e));
+                        }
+                    }
+                    attempt.follow()
+                })
+            }
+        }
+    }
+
+    fn build(&self, config: &HttpClientConfig) -> Result<reqwest::Client, NetError> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))
+            .redirect(self.redirect_policy(config))
+            .tls_built_in_native_certs(config.system_certs.unwrap_or(true));
+
+        if let Some(proxy) = &config.proxy {
+            builder = self.apply_proxy(builder, proxy)?;
+        } else if let Some(pac_url) = system_pac_url() {
+            builder = self.apply_proxy(
+                builder,
+                &ProxyConfig {
+                    pac_url: Some(pac_url),
+                    ..Default::default()
+                },
+            )?;
+        }
+
+        for pem in config.ca_certs.iter().flatten() {
+            let certs = reqwest::Certificate::from_pem_bundle(pem_bytes(pem, "ca_certs")?)
+                .map_err(|e| NetError::tls_config(format!("Invalid CA certificate: {}", e)))?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        match (&config.client_cert, &config.client_key) {
+            (Some(cert), Some(key)) => {
+                let mut pem = pem_bytes(cert, "client_cert")?.to_vec();
+                pem.push(b'\n');
+                pem.extend_from_slice(pem_bytes(key, "client_key")?);
+                let identity = reqwest::Identity::from_pem(&pem)
+                    .map_err(|e| NetError::tls_config(format!("Invalid client identity: {}", e)))?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(NetError::tls_config(
+                    "client_cert and client_key must be set together",
+                ))
+            }
+        }
+
+        if config.cookies.unwrap_or(false) {
+            builder = builder.cookie_provider(self.cookie_jar.clone());
+        }
+
+        builder = match config.http2.unwrap_or(Http2Mode::Auto) {
+            Http2Mode::Auto => builder,
+            Http2Mode::Http1 => builder.http1_only(),
+            Http2Mode::Http2 => builder.http2_prior_knowledge(),
+        };
+
+        builder
+            .build()
+            .map_err(|e| NetError::request_build_error(e.to_string()))
+    }
+}
+
+/// The app's HTTP clients (reused for connection pooling)
+pub struct NetHttpClient {
+    /// Client for the manifest configuration
+    pub client: reqwest::Client,
+    /// The manifest configuration
+    pub config: HttpClientConfig,
+    context: ClientContext,
+    /// Recently used clients for per-request configurations, keyed by their
+    /// JSON form
+    overrides: Mutex<LruCache<String, reqwest::Client>>,
+}
+
+impl NetHttpClient {
+    /// Build the client for `config`. Cookies are saved at `cookie_path`;
+    /// without one the jar lives in memory. Redirect hops are checked with
+    /// `checker`.
+    pub fn new(
+        config: HttpClientConfig,
+        cookie_path: Option<PathBuf>,
+        checker: Option<Arc<dyn NetCapabilityChecker>>,
+    ) -> Result<Self, NetError> {
+        let context = ClientContext {
+            cookie_jar: Arc::new(CookieJar::new(cookie_path)),
+            checker,
+            pac: Mutex::new(HashMap::new()),
+        };
+        let client = context.build(&config)?;
+        Ok(Self {
+            client,
+            config,
+            context,
+            overrides: Mutex::new(LruCache::new(
+                NonZeroUsize::new(MAX_OVERRIDE_CLIENTS).unwrap(),
+            )),
+        })
+    }
+
+    /// The client for a request, applying its `client` overrides except
+    /// `user_agent`, which the request sets as a header.
+    pub fn client_for(
+        &self,
+        overrides: Option<&HttpClientConfig>,
+    ) -> Result<reqwest::Client, NetError> {
+        let overrides = overrides.map(|o| HttpClientConfig {
+            user_agent: None,
+            ..o.clone()
+        });
+        let Some(overrides) = overrides.filter(|o| *o != HttpClientConfig::default()) else {
+            return Ok(self.client.clone());
+        };
+        let config = self.config.merged(&overrides);
+        let key = serde_json::to_string(&config)
+            .map_err(|e| NetError::request_build_error(e.to_string()))?;
+
+        let mut clients = self.overrides.lock().unwrap();
+        if let Some(client) = clients.get(&key) {
+            return Ok(client.clone());
+        }
+        let client = self.context.build(&config)?;
+        clients.put(key, client.clone());
+        Ok(client)
+    }
+
+    /// The PAC resolver the client for a request consults, if any. Only
+    /// resolvers of clients already built are returned.
+    pub fn pac_for(&self, overrides: Option<&HttpClientConfig>) -> Option<Arc<PacResolver>> {
+        let config = match overrides {
+            Some(overrides) => self.config.merged(overrides),
+            None => self.config.clone(),
+        };
+        let pac_url = config.pac_url()?;
+        self.context.pac.lock().unwrap().get(&pac_url).cloned()
+    }
+
+    /// The app's cookie jar
+    pub fn cookie_jar(&self) -> &Arc<CookieJar> {
+        &self.context.cookie_jar
+    }
+}
+
+impl Default for NetHttpClient {
+    fn default() -> Self {
+        Self::new(HttpClientConfig::default(), None, None).expect("Failed to build HTTP client")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_and_proxy_mode() {
+        let manifest = HttpClientConfig {
+            user_agent: Some("App/1.0".to_string()),
+            cookies: Some(true),
+            proxy: Some(ProxyConfig {
+                url: Some("http://proxy.corp:8080".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let request = HttpClientConfig {
+            cookies: Some(false),
+            proxy: Some(ProxyConfig {
+                mode: Some(ProxyMode::Direct),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let merged = manifest.merged(&request);
+        assert_eq!(merged.user_agent.as_deref(), Some("App/1.0"));
+        assert_eq!(merged.cookies, Some(false));
+        assert_eq!(merged.proxy.as_ref().unwrap().mode(), ProxyMode::Direct);
+        assert_eq!(manifest.proxy.as_ref().unwrap().mode(), ProxyMode::Manual);
+        assert_eq!(manifest.proxy_urls(), vec!["http://proxy.corp:8080"]);
+
+        let pac = ProxyConfig {
+            pac_url: Some("http://wpad/wpad.dat".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pac.mode(), ProxyMode::Pac);
+        assert_eq!(ProxyConfig::default().mode(), ProxyMode::System);
+    }
+
+    #[test]
+    fn test_override_clients_are_bounded() {
+        let http = NetHttpClient::default();
+        for max_redirects in 0..MAX_OVERRIDE_CLIENTS + 4 {
+            let overrides = HttpClientConfig {
+                max_redirects: Some(max_redirects),
+                ..Default::default()
+            };
+            http.client_for(Some(&overrides)).unwrap();
+        }
+        assert_eq!(http.overrides.lock().unwrap().len(), MAX_OVERRIDE_CLIENTS);
+
+        // A user agent alone is sent per request
+        let overrides = HttpClientConfig {
+            user_agent: Some("Custom/1.0".to_string()),
+            ..Default::default()
+        };
+        http.client_for(Some(&overrides)).unwrap();
+        assert_eq!(http.overrides.lock().unwrap().len(), MAX_OVERRIDE_CLIENTS);
+    }
+
+    #[test]
+    fn test_pem_files() {
+        let dir = std::env::temp_dir().join(format!("forge-net-pem-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pem = "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n";
+        std::fs::write(dir.join("root.pem"), pem).unwrap();
+
+        let mut config = HttpClientConfig {
+            ca_certs: Some(vec!["root.pem".to_string(), pem.to_string()]),
+            ..Default::default()
+        };
+        config.load_files(&dir).unwrap();
+        assert_eq!(
+            config.ca_certs,
+            Some(vec![pem.to_string(), pem.to_string()])
+        );
+
+        let mut missing = HttpClientConfig {
+            client_key: Some("missing.key".to_string()),
+            ..Default::default()
+        };
+        assert!(missing.load_files(&dir).is_err());
+
+        // Requests must pass PEM text, not paths
+        assert!(pem_bytes("/etc/ssl/private/key.pem", "client_key").is_err());
+        assert!(pem_bytes(pem, "ca_certs").is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_config_from_toml_shape() {
+        let config: HttpClientConfig = serde_json::from_value(serde_json::json!({
+            "proxy": { "mode": "pac", "pac_url": "file:///etc/proxy.pac", "no_proxy": ["localhost"] },
+            "redirect": "manual",
+            "http2": "http1",
+            "max_redirects": 3
+        }))
+        .unwrap();
+        assert_eq!(config.redirect, Some(RedirectMode::Manual));
+        assert_eq!(config.http2, Some(Http2Mode::Http1));
+        assert_eq!(config.proxy.unwrap().mode(), ProxyMode::Pac);
+    }
+}
@@ -0,0 +1,415 @@
+//! Persistent cookie jar
+//!
+//! Cookies are kept per app: clients built with `cookies = true` share one
+//! jar, and cookies with an expiry are saved to the app's data directory so
+//! logins survive restarts. Session cookies live in memory only. Matching
+//! follows RFC 6265 (domain, path, `Secure`, expiry). A `Domain` attribute
+//! must cover the responding host and may not be a public suffix such as
+//! `co.uk`, checked against the Public Suffix List.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+use url::Url;
+
+/// A stored cookie
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    /// Lowercase domain without a leading dot
+    pub domain: String,
+    /// Only sent to exactly `domain` (no `Domain` attribute was given)
+    pub host_only: bool,
+    pub path: String,
+    pub secure: bool,
+    pub http_only: bool,
+    /// Expiry in seconds since the Unix epoch; `None` for session cookies
+    pub expires: Option<u64>,
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn same_identity(&self, other: &StoredCookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+
+    fn matches(&self, url: &Url, now: u64) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+        let domain_ok = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+        domain_ok
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired(now)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// The directory of the request path, used when `Path` is missing
+fn default_path(url: &Url) -> String {
+    let path = url.path();
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(i) => path[..i].to_string(),
+    }
+}
+
+/// Parse an `Expires` date; legacy dates use dashes between the fields.
+fn parse_expires(value: &str) -> Option<SystemTime> {
+    httpdate::parse_http_date(value)
+        .or_else(|_| httpdate::parse_http_date(&value.replace('-', " ")))
+        .ok()
+}
+
+/// Parse a `Set-Cookie` header received from `url`. Returns `None` when the
+/// header is malformed or names a domain the host may not set cookies for.
+pub fn parse_set_cookie(header: &str, url: &Url, now: u64) -> Option<StoredCookie> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = StoredCookie {
+        name: name.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        domain: host.clone(),
+        host_only: true,
+        path: default_path(url),
+        secure: false,
+        http_only: false,
+        expires: None,
+    };
+    let mut max_age = None;
+
+    for attribute in parts {
+        let (key, value) = match attribute.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (attribute.trim(), ""),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "domain" if !value.is_empty() => {
+                let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                if !domain_matches(&host, &domain) {
+                    return None;
+                }
+                // A public suffix would cover every site under it; a host
+                // that is one itself gets a host-only cookie
+                if psl::suffix_str(&domain) == Some(domain.as_str()) {
+                    if domain != host {
+                        return None;
+                    }
+                    continue;
+                }
+                cookie.domain = domain;
+                cookie.host_only = false;
+            }
+            "path" if value.starts_with('/') => cookie.path = value.to_string(),
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            "max-age" => max_age = value.parse::<i64>().ok(),
+            "expires" if cookie.expires.is_none() => {
+                cookie.expires = parse_expires(value).map(|time| {
+                    time.duration_since(UNIX_EPOCH)
+                        .unwrap_or(Duration::ZERO)
+                        .as_secs()
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Max-Age wins over Expires; zero or less deletes the cookie
+    if let Some(seconds) = max_age {
+        cookie.expires = Some(if seconds <= 0 {
+            0
+        } else {
+            now.saturating_add(seconds as u64)
+        });
+    }
+
+    Some(cookie)
+}
+
+/// Cookie jar shared by the app's HTTP clients
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    cookies: Mutex<Vec<StoredCookie>>,
+    /// Where cookies with an expiry are saved
+    path: Option<PathBuf>,
+}
+
+impl CookieJar {
+    /// Create a jar, loading cookies saved at `path`.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let now = now_secs();
+        let cookies = path
+            .as_ref()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<Vec<StoredCookie>>(&bytes).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .collect();
+
+        Self {
+            cookies: Mutex::new(cookies),
+            path,
+        }
+    }
+
+    /// Store the cookies from `Set-Cookie` headers received from `url`.
+    pub fn store<'a>(&self, headers: impl IntoIterator<Item = &'a str>, url: &Url) {
+        let now = now_secs();
+        let mut cookies = self.cookies.lock().unwrap();
+        let mut persist = false;
+
+        for header in headers {
+            let Some(cookie) = parse_set_cookie(header, url, now) else {
+                continue;
+            };
+            let existing = cookies.iter().position(|c| c.same_identity(&cookie));
+            persist |=
+                cookie.expires.is_some() || existing.is_some_and(|i| cookies[i].expires.is_some());
+            if let Some(i) = existing {
+                cookies.remove(i);
+            }
+            if !cookie.is_expired(now) {
+                cookies.push(cookie);
+            }
+        }
+
+        cookies.retain(|cookie| !cookie.is_expired(now));
+        if persist {
+            self.save(&cookies);
+        }
+    }
+
+    /// The `Cookie` header value for a request to `url`, if any cookie matches.
+    pub fn header(&self, url: &Url) -> Option<String> {
+        let now = now_secs();
+        let cookies = self.cookies.lock().unwrap();
+        let mut matching: Vec<&StoredCookie> =
+            cookies.iter().filter(|c| c.matches(url, now)).collect();
+        if matching.is_empty() {
+            return None;
+        }
+        // Longer paths first, as browsers do
+        matching.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        Some(
+            matching
+                .iter()
+                .map(|c| format!("{}={}", c.name, c.value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+
+    /// All cookies that are not expired
+    pub fn cookies(&self) -> Vec<StoredCookie> {
+        let now = now_secs();
+        self.cookies
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| !c.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    /// Remove every cookie, or only those whose domain covers `domain`.
+    pub fn clear(&self, domain: Option<&str>) {
+        let mut cookies = self.cookies.lock().unwrap();
+        match domain {
+            Some(domain) => {
+                let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+                cookies.retain(|c| !domain_matches(&c.domain, &domain));
+            }
+            None => cookies.clear(),
+        }
+        self.save(&cookies);
+    }
+
+    fn save(&self, cookies: &[StoredCookie]) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let persistent: Vec<&StoredCookie> =
+            cookies.iter().filter(|c| c.expires.is_some()).collect();
+        let result = serde_json::to_vec(&persistent)
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, json)
+            });
+        if let Err(e) = result {
+            warn!(path = %path.display(), "Failed to save cookies: {}", e);
+        }
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(
+        &self,
+        cookie_headers: &mut dyn Iterator<Item = &reqwest::header::HeaderValue>,
+        url: &Url,
+    ) {
+        self.store(cookie_headers.filter_map(|h| h.to_str().ok()), url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<reqwest::header::HeaderValue> {
+        self.header(url)
+            .and_then(|header| reqwest::header::HeaderValue::from_str(&header).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_set_cookie() {
+        let now = 1_000;
+        let cookie = parse_set_cookie(
+            "sid=abc; Domain=.Example.com; Path=/app; Secure; HttpOnly; Max-Age=60",
+            &url("https://www.example.com/login"),
+            now,
+        )
+        .unwrap();
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only && cookie.secure && cookie.http_only);
+        assert_eq!(cookie.path, "/app");
+        assert_eq!(cookie.expires, Some(1_060));
+
+        let cookie = parse_set_cookie(
+            "theme=\"dark\"; Expires=Wed, 21-Oct-2015 07:28:00 GMT",
+            &url("http://example.com/settings/page"),
+            now,
+        )
+        .unwrap();
+        assert_eq!(cookie.value, "dark");
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/settings");
+        assert_eq!(cookie.expires, Some(1_445_412_480));
+
+        // Foreign domains and public suffixes are rejected
+        assert!(
+            parse_set_cookie("a=1; Domain=other.com", &url("https://example.com/"), now).is_none()
+        );
+        assert!(parse_set_cookie("a=1; Domain=com", &url("https://example.com/"), now).is_none());
+        assert!(parse_set_cookie("a=1; Domain=co.uk", &url("https://a.co.uk/"), now).is_none());
+        assert!(
+            parse_set_cookie("a=1; Domain=github.io", &url("https://app.github.io/"), now)
+                .is_none()
+        );
+        let cookie = parse_set_cookie("a=1; Domain=a.co.uk", &url("https://www.a.co.uk/"), now);
+        assert!(!cookie.unwrap().host_only);
+        let cookie = parse_set_cookie("a=1; Domain=localhost", &url("http://localhost/"), now);
+        assert!(cookie.unwrap().host_only);
+        assert!(parse_set_cookie("=1", &url("https://example.com/"), now).is_none());
+    }
+
+    #[test]
+    fn test_jar_matching() {
+        let jar = CookieJar::new(None);
+        jar.store(
+            [
+                "a=1; Path=/",
+                "b=2; Path=/api; Secure",
+                "c=3; Domain=example.com",
+            ],
+            &url("https://example.com/"),
+        );
+
+        assert_eq!(
+            jar.header(&url("https://example.com/api/items")).as_deref(),
+            Some("b=2; a=1; c=3")
+        );
+        assert_eq!(
+            jar.header(&url("http://example.com/api")).as_deref(),
+            Some("a=1; c=3")
+        );
+        assert_eq!(
+            jar.header(&url("https://example.com/apix")).as_deref(),
+            Some("a=1; c=3")
+        );
+        // Host-only cookies stay on their host; domain cookies cover subdomains
+        assert_eq!(
+            jar.header(&url("https://sub.example.com/")).as_deref(),
+            Some("c=3")
+        );
+        assert_eq!(jar.header(&url("https://other.com/")), None);
+
+        // Replacing and deleting
+        jar.store(
+            ["a=9; Path=/", "c=; Domain=example.com; Max-Age=0"],
+            &url("https://example.com/"),
+        );
+        assert_eq!(
+            jar.header(&url("http://example.com/")).as_deref(),
+            Some("a=9")
+        );
+
+        jar.clear(Some("example.com"));
+        assert!(jar.cookies().is_empty());
+    }
+
+    #[test]
+    fn test_jar_persistence() {
+        let path = std::env::temp_dir().join(format!("forge-cookies-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let jar = CookieJar::new(Some(path.clone()));
+        jar.store(
+            ["session=s", "remember=r; Max-Age=3600"],
+            &url("https://example.com/"),
+        );
+        drop(jar);
+
+        let jar = CookieJar::new(Some(path.clone()));
+        assert_eq!(
+            jar.header(&url("https://example.com/")).as_deref(),
+            Some("remember=r")
+        );
+
+        jar.clear(None);
+        assert!(CookieJar::new(Some(path.clone())).cookies().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,13 +1,17 @@
 //! runtime:net extension - Network operations for Forge apps
 //!
-//! Provides HTTP fetch and WebSocket support with capability-based security.
+//! Provides HTTP fetch with streamed and multipart uploads, progress and
+//! resumable downloads, WebSocket, a local HTTP server and raw TCP, UDP and
+//! Unix domain sockets with capability-based security.
 
-use deno_core::{op2, Extension, OpState};
+use bytes::Bytes;
+use deno_core::{op2, Extension, JsBuffer, OpState};
 use forge_weld_macro::{weld_op, weld_struct};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -15,6 +19,33 @@ use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::debug;
 use url::Url;
 
+mod client;
+mod cookies;
+mod pac;
+mod server;
+mod socket;
+mod transfer;
+
+pub use client::{
+    Http2Mode, HttpClientConfig, NetHttpClient, ProxyConfig, ProxyMode, RedirectMode,
+    DEFAULT_MAX_REDIRECTS, DEFAULT_USER_AGENT,
+};
+pub use cookies::{CookieJar, StoredCookie};
+
+pub use server::{
+    HttpServer, PendingRequest, ServeOpts, ServerInfo, ServerRequest, ServerResponseInit,
+    ServerState, DEFAULT_SERVE_HOSTNAME, DEFAULT_SHUTDOWN_GRACE_MS,
+};
+pub use socket::{
+    host_port, ConnInfo, Datagram, ListenerInfo, NetAddr, SocketBindOpts, SocketConn,
+    SocketListener, SocketState, TcpConnectOpts, UdpEndpoint, DEFAULT_READ_SIZE, MAX_DATAGRAM_SIZE,
+};
+pub use transfer::{
+    attach_body, download_to_file, multipart_body, read_body, BodyChunk, DownloadOpts,
+    DownloadResult, FetchProgress, MultipartPart, ProgressReporter, RequestBody, TransferState,
+    BODY_STREAM_BUFFER, UPLOAD_CHUNK_SIZE,
+};
+
 // ============================================================================
 // Error Types with Structured Codes
 // ============================================================================
@@ -49,6 +80,12 @@ pub enum NetErrorCode {
     WebSocketNotFound = 1011,
     /// Streaming error
     StreamError = 1012,
+    /// Server or server request not found
+    ServerNotFound = 1013,
+    /// Invalid TLS certificate or key
+    TlsConfig = 1014,
+    /// Socket connection, listener or UDP socket not found
+    SocketNotFound = 1015,
 }
 
 /// Custom error type for Net operations
@@ -105,6 +142,18 @@ pub enum NetError {
     #[error("[{code}] Stream error: {message}")]
     #[class(generic)]
     StreamError { code: u32, message: String },
+
+    #[error("[{code}] Server not found: {message}")]
+    #[class(generic)]
+    ServerNotFound { code: u32, message: String },
+
+    #[error("[{code}] TLS configuration error: {message}")]
+    #[class(generic)]
+    TlsConfig { code: u32, message: String },
+
+    #[error("[{code}] Socket not found: {message}")]
+    #[class(generic)]
+    SocketNotFound { code: u32, message: String },
 }
 
 impl NetError {
@@ -198,6 +247,27 @@ impl NetError {
             message: message.into(),
         }
     }
+
+    pub fn server_not_found(message: impl Into<String>) -> Self {
+        Self::ServerNotFound {
+            code: NetErrorCode::ServerNotFound as u32,
+            message: message.into(),
+        }
+    }
+
+    pub fn tls_config(message: impl Into<String>) -> Self {
+        Self::TlsConfig {
+            code: NetErrorCode::TlsConfig as u32,
+            message: message.into(),
+        }
+    }
+
+    pub fn socket_not_found(message: impl Into<String>) -> Self {
+        Self::SocketNotFound {
+            code: NetErrorCode::SocketNotFound as u32,
+            message: message.into(),
+        }
+    }
 }
 
 impl From<std::io::Error> for NetError {
@@ -243,8 +313,20 @@ impl From<tokio_tungstenite::tungstenite::Error> for NetError {
 pub struct FetchOpts {
     pub method: Option<String>,
     pub headers: Option<HashMap<String, String>>,
+    /// Text body. At most one of `body`, `body_buffer`, `body_stream` and
+    /// `multipart` is set.
     pub body: Option<String>,
+    /// Id of a binary body from `op_net_body_bytes`
+    pub body_buffer: Option<u64>,
+    /// Id of a body stream from `op_net_body_open`
+    pub body_stream: Option<u64>,
+    /// multipart/form-data parts; sets the Content-Type
+    pub multipart: Option<Vec<MultipartPart>>,
+    /// Id of a progress channel from `op_net_progress_open`
+    pub progress_id: Option<u64>,
     pub timeout_ms: Option<u64>,
+    /// Client settings for this request, over the manifest's `[http]` table
+    pub client: Option<HttpClientConfig>,
 }
 
 /// HTTP response
@@ -285,14 +367,6 @@ pub struct WebSocketMessage {
     pub binary: Option<Vec<u8>>,
 }
 
-/// Streaming fetch chunk
-#[weld_struct]
-#[derive(Debug, Clone, Serialize)]
-pub struct StreamChunk {
-    pub done: bool,
-    pub data: Option<Vec<u8>>,
-}
-
 // ============================================================================
 // Capability Checker
 // ============================================================================
@@ -301,6 +375,12 @@ pub struct StreamChunk {
 pub trait NetCapabilityChecker: Send + Sync {
     fn check_connect(&self, runtime: &str) -> Result<(), String>;
     fn check_listen(&self, port: u16) -> Result<(), String>;
+    /// Check a Unix domain socket path (connect or listen)
+    fn check_socket_path(&self, path: &str) -> Result<(), String>;
+    /// Check a file uploaded as a multipart part
+    fn check_read_path(&self, path: &str) -> Result<(), String>;
+    /// Check a file a download writes to
+    fn check_write_path(&self, path: &str) -> Result<(), String>;
 }
 
 /// Default permissive checker (for dev mode)
@@ -313,6 +393,15 @@ impl NetCapabilityChecker for PermissiveNetChecker {
     fn check_listen(&self, _port: u16) -> Result<(), String> {
         Ok(())
     }
+    fn check_socket_path(&self, _path: &str) -> Result<(), String> {
+        Ok(())
+    }
+    fn check_read_path(&self, _path: &str) -> Result<(), String> {
+        Ok(())
+    }
+    fn check_write_path(&self, _path: &str) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 /// Wrapper to store in OpState
@@ -328,22 +417,6 @@ impl Default for NetCapabilities {
     }
 }
 
-/// Shared HTTP client (reused for connection pooling)
-pub struct NetHttpClient {
-    pub client: reqwest::Client,
-}
-
-impl Default for NetHttpClient {
-    fn default() -> Self {
-        Self {
-            client: reqwest::Client::builder()
-                .user_agent("Forge/0.1")
-                .build()
-                .expect("Failed to build HTTP client"),
-        }
-    }
-}
-
 /// Type alias for WebSocket stream
 type WsStream =
     tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
@@ -430,57 +503,143 @@ fn check_net_connect(state: &OpState, host: &str) -> Result<(), NetError> {
     }
 }
 
-/// Get or create HTTP client
-fn get_http_client(state: &mut OpState) -> reqwest::Client {
-    if let Some(client_state) = state.try_borrow::<NetHttpClient>() {
-        client_state.client.clone()
+/// Check net listen capability
+fn check_net_listen(state: &OpState, port: u16) -> Result<(), NetError> {
+    if let Some(caps) = state.try_borrow::<NetCapabilities>() {
+        caps.checker
+            .check_listen(port)
+            .map_err(NetError::permission_denied)
     } else {
-        let client_state = NetHttpClient::default();
-        let client = client_state.client.clone();
-        state.put(client_state);
-        client
+        Ok(())
     }
 }
 
-// ============================================================================
-// Operations
-// ============================================================================
+/// Check Unix socket path capability
+fn check_net_socket_path(state: &OpState, path: &str) -> Result<(), NetError> {
+    if let Some(caps) = state.try_borrow::<NetCapabilities>() {
+        caps.checker
+            .check_socket_path(path)
+            .map_err(NetError::permission_denied)
+    } else {
+        Ok(())
+    }
+}
 
-/// Fetch a URL with full HTTP support
-#[weld_op(async)]
-#[op2(async)]
-#[serde]
-async fn op_net_fetch(
-    state: Rc<RefCell<OpState>>,
-    #[string] url: String,
-    #[serde] opts: Option<FetchOpts>,
-) -> Result<FetchResponse, NetError> {
-    let opts = opts.unwrap_or_default();
+/// Get or create socket state
+fn get_socket_state(state: &Rc<RefCell<OpState>>) -> SocketState {
+    let mut s = state.borrow_mut();
+    if s.try_borrow::<SocketState>().is_none() {
+        s.put(SocketState::default());
+    }
+    s.borrow::<SocketState>().clone()
+}
+
+/// Get or create server state
+fn get_server_state(state: &Rc<RefCell<OpState>>) -> ServerState {
+    let mut s = state.borrow_mut();
+    if s.try_borrow::<ServerState>().is_none() {
+        s.put(ServerState::default());
+    }
+    s.borrow::<ServerState>().clone()
+}
+
+/// Check a file read for an upload
+fn check_net_read_path(state: &OpState, path: &str) -> Result<(), NetError> {
+    if let Some(caps) = state.try_borrow::<NetCapabilities>() {
+        caps.checker
+            .check_read_path(path)
+            .map_err(NetError::permission_denied)
+    } else {
+        Ok(())
+    }
+}
+
+/// Check a file written by a download
+fn check_net_write_path(state: &OpState, path: &str) -> Result<(), NetError> {
+    if let Some(caps) = state.try_borrow::<NetCapabilities>() {
+        caps.checker
+            .check_write_path(path)
+            .map_err(NetError::permission_denied)
+    } else {
+        Ok(())
+    }
+}
+
+/// Get or create transfer state
+fn get_transfer_state(state: &Rc<RefCell<OpState>>) -> TransferState {
+    let mut s = state.borrow_mut();
+    if s.try_borrow::<TransferState>().is_none() {
+        s.put(TransferState::default());
+    }
+    s.borrow::<TransferState>().clone()
+}
+
+/// Check the proxies a request's client overrides would connect to
+fn check_client_overrides(
+    state: &OpState,
+    overrides: Option<&HttpClientConfig>,
+) -> Result<(), NetError> {
+    for proxy in overrides.map(|o| o.proxy_urls()).unwrap_or_default() {
+        check_net_connect(state, &extract_host(proxy)?)?;
+    }
+    Ok(())
+}
 
+/// Get or create the HTTP client for a request
+fn get_http_client(
+    state: &mut OpState,
+    overrides: Option<&HttpClientConfig>,
+) -> Result<reqwest::Client, NetError> {
+    if state.try_borrow::<NetHttpClient>().is_none() {
+        let checker = state
+            .try_borrow::<NetCapabilities>()
+            .map(|caps| caps.checker.clone());
+        state.put(NetHttpClient::new(
+            HttpClientConfig::default(),
+            None,
+            checker,
+        )?);
+    }
+    state.borrow::<NetHttpClient>().client_for(overrides)
+}
+
+/// Check permissions and build a request from fetch options. Returns the
+/// progress reporter the options name, if any.
+async fn prepare_request(
+    state: &Rc<RefCell<OpState>>,
+    url: &str,
+    opts: FetchOpts,
+) -> Result<(reqwest::RequestBuilder, Option<ProgressReporter>), NetError> {
     // Extract host and check capabilities
-    let host = extract_host(&url)?;
+    let host = extract_host(url)?;
     {
         let s = state.borrow();
         check_net_connect(&s, &host)?;
+        check_client_overrides(&s, opts.client.as_ref())?;
     }
 
-    debug!(url = %url, method = ?opts.method, "net.fetch");
-
     // Get HTTP client
-    let client = {
+    let (client, pac) = {
         let mut s = state.borrow_mut();
-        get_http_client(&mut s)
+        let client = get_http_client(&mut s, opts.client.as_ref())?;
+        let pac = s.borrow::<NetHttpClient>().pac_for(opts.client.as_ref());
+        (client, pac)
     };
 
+    // Ask the PAC script now; reqwest only reads its cached answer
+    if let (Some(pac), Ok(url)) = (pac, Url::parse(url)) {
+        pac.resolve(&url).await;
+    }
+
     // Build request
     let method = opts.method.as_deref().unwrap_or("GET").to_uppercase();
     let mut request_builder = match method.as_str() {
-        "GET" => client.get(&url),
-        "POST" => client.post(&url),
-        "PUT" => client.put(&url),
-        "DELETE" => client.delete(&url),
-        "PATCH" => client.patch(&url),
-        "HEAD" => client.head(&url),
+        "GET" => client.get(url),
+        "POST" => client.post(url),
+        "PUT" => client.put(url),
+        "DELETE" => client.delete(url),
+        "PATCH" => client.patch(url),
+        "HEAD" => client.head(url),
         _ => {
             return Err(NetError::request_build_error(format!(
                 "Unsupported method: {}",
@@ -489,16 +648,67 @@ async fn op_net_fetch(
         }
     };
 
-    // Add headers
+    let transfers = get_transfer_state(state);
+    let body = match (
+        opts.body,
+        opts.body_buffer,
+        opts.body_stream,
+        opts.multipart,
+    ) {
+        (None, None, None, None) => None,
+        (Some(text), None, None, None) => Some(RequestBody::Bytes(text.into())),
+        (None, Some(id), None, None) => Some(RequestBody::Bytes(transfers.take_bytes(id).await?)),
+        (None, None, Some(id), None) => Some(RequestBody::Stream(transfers.take_body(id).await?)),
+        (None, None, None, Some(parts)) => {
+            let mut buffers = HashMap::new();
+            for id in parts.iter().filter_map(|part| part.buffer) {
+                buffers.insert(id, transfers.take_bytes(id).await?);
+            }
+            Some(RequestBody::Multipart(parts, buffers))
+        }
+        _ => {
+            return Err(NetError::request_build_error(
+                "Only one of body, body_buffer, body_stream and multipart can be set",
+            ));
+        }
+    };
+    if let Some(body) = &body {
+        let s = state.borrow();
+        for path in body.file_paths() {
+            check_net_read_path(&s, path)?;
+        }
+    }
+
+    // A per-request user agent is a header rather than a client of its own
+    let user_agent = opts.client.as_ref().and_then(|c| c.user_agent.as_deref());
+    let has_user_agent = opts.headers.as_ref().is_some_and(|headers| {
+        headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case("user-agent"))
+    });
+    if let (Some(user_agent), false) = (user_agent, has_user_agent) {
+        request_builder = request_builder.header(reqwest::header::USER_AGENT, user_agent);
+    }
+
+    // Add headers; a multipart body sets its own Content-Type with the boundary
+    let multipart = matches!(body, Some(RequestBody::Multipart(..)));
     if let Some(headers) = opts.headers {
         for (key, value) in headers {
+            if multipart && key.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
             request_builder = request_builder.header(&key, &value);
         }
     }
 
+    let progress = match opts.progress_id {
+        Some(id) => Some(transfers.take_reporter(id).await?),
+        None => None,
+    };
+
     // Add body
-    if let Some(body) = opts.body {
-        request_builder = request_builder.body(body);
+    if let Some(body) = body {
+        request_builder = attach_body(request_builder, body, progress.as_ref())?;
     }
 
     // Set timeout
@@ -506,8 +716,30 @@ async fn op_net_fetch(
         request_builder = request_builder.timeout(std::time::Duration::from_millis(timeout_ms));
     }
 
+    Ok((request_builder, progress))
+}
+
+// ============================================================================
+// Operations
+// ============================================================================
+
+/// Fetch a URL with full HTTP support
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_fetch(
+    state: Rc<RefCell<OpState>>,
+    #[string] url: String,
+    #[serde] opts: Option<FetchOpts>,
+) -> Result<FetchResponse, NetError> {
+    let opts = opts.unwrap_or_default();
+
+    debug!(url = %url, method = ?opts.method, "net.fetch");
+
+    let (request, progress) = prepare_request(&state, &url, opts).await?;
+
     // Execute request
-    let response = request_builder.send().await?;
+    let response = request.send().await?;
 
     // Extract response data
     let status = response.status().as_u16();
@@ -528,7 +760,12 @@ async fn op_net_fetch(
     }
 
     // Read body as text
-    let body = response.text().await?;
+    let body = match &progress {
+        Some(progress) => {
+            String::from_utf8_lossy(&read_body(response, Some(progress)).await?).into_owned()
+        }
+        None => response.text().await?,
+    };
 
     debug!(status = status, body_len = body.len(), "net.fetch complete");
 
@@ -553,57 +790,12 @@ async fn op_net_fetch_bytes(
 ) -> Result<FetchBytesResponse, NetError> {
     let opts = opts.unwrap_or_default();
 
-    // Extract host and check capabilities
-    let host = extract_host(&url)?;
-    {
-        let s = state.borrow();
-        check_net_connect(&s, &host)?;
-    }
-
     debug!(url = %url, "net.fetch_bytes");
 
-    // Get HTTP client
-    let client = {
-        let mut s = state.borrow_mut();
-        get_http_client(&mut s)
-    };
-
-    // Build request
-    let method = opts.method.as_deref().unwrap_or("GET").to_uppercase();
-    let mut request_builder = match method.as_str() {
-        "GET" => client.get(&url),
-        "POST" => client.post(&url),
-        "PUT" => client.put(&url),
-        "DELETE" => client.delete(&url),
-        "PATCH" => client.patch(&url),
-        "HEAD" => client.head(&url),
-        _ => {
-            return Err(NetError::request_build_error(format!(
-                "Unsupported method: {}",
-                method
-            )));
-        }
-    };
-
-    // Add headers
-    if let Some(headers) = opts.headers {
-        for (key, value) in headers {
-            request_builder = request_builder.header(&key, &value);
-        }
-    }
-
-    // Add body
-    if let Some(body) = opts.body {
-        request_builder = request_builder.body(body);
-    }
-
-    // Set timeout
-    if let Some(timeout_ms) = opts.timeout_ms {
-        request_builder = request_builder.timeout(std::time::Duration::from_millis(timeout_ms));
-    }
+    let (request, progress) = prepare_request(&state, &url, opts).await?;
 
     // Execute request
-    let response = request_builder.send().await?;
+    let response = request.send().await?;
 
     // Extract response data
     let status = response.status().as_u16();
@@ -624,7 +816,7 @@ async fn op_net_fetch_bytes(
     }
 
     // Read body as bytes
-    let body = response.bytes().await?.to_vec();
+    let body = read_body(response, progress.as_ref()).await?;
 
     debug!(
         status = status,
@@ -906,57 +1098,13 @@ async fn op_net_fetch_stream(
 ) -> Result<StreamResponse, NetError> {
     let opts = opts.unwrap_or_default();
 
-    // Extract host and check capabilities
-    let host = extract_host(&url)?;
-    {
-        let s = state.borrow();
-        check_net_connect(&s, &host)?;
-    }
-
     debug!(url = %url, "net.fetch_stream");
 
-    // Get HTTP client
-    let client = {
-        let mut s = state.borrow_mut();
-        get_http_client(&mut s)
-    };
-
-    // Build request
-    let method = opts.method.as_deref().unwrap_or("GET").to_uppercase();
-    let mut request_builder = match method.as_str() {
-        "GET" => client.get(&url),
-        "POST" => client.post(&url),
-        "PUT" => client.put(&url),
-        "DELETE" => client.delete(&url),
-        "PATCH" => client.patch(&url),
-        "HEAD" => client.head(&url),
-        _ => {
-            return Err(NetError::request_build_error(format!(
-                "Unsupported method: {}",
-                method
-            )));
-        }
-    };
-
-    // Add headers
-    if let Some(headers) = opts.headers {
-        for (key, value) in headers {
-            request_builder = request_builder.header(&key, &value);
-        }
-    }
-
-    // Add body
-    if let Some(body) = opts.body {
-        request_builder = request_builder.body(body);
-    }
-
-    // Set timeout
-    if let Some(timeout_ms) = opts.timeout_ms {
-        request_builder = request_builder.timeout(std::time::Duration::from_millis(timeout_ms));
-    }
+    // Only the upload is tracked; the body is read later, chunk by chunk
+    let (request, _progress) = prepare_request(&state, &url, opts).await?;
 
     // Execute request
-    let response = request_builder.send().await?;
+    let response = request.send().await?;
 
     // Extract response info
     let status = response.status().as_u16();
@@ -1011,14 +1159,593 @@ async fn op_net_fetch_stream(
     })
 }
 
+// ============================================================================
+// Upload, Progress and Download Operations
+// ============================================================================
+
+/// Open a stream for a request body written from JavaScript
+#[weld_op(async)]
+#[op2(async)]
+#[bigint]
+async fn op_net_body_open(state: Rc<RefCell<OpState>>) -> Result<u64, NetError> {
+    Ok(get_transfer_state(&state).open_body().await)
+}
+
+/// Write a chunk to a body stream; waits while the request catches up
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_body_write(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] stream_id: u64,
+    #[buffer] data: JsBuffer,
+) -> Result<(), NetError> {
+    get_transfer_state(&state)
+        .write_body(stream_id, Bytes::copy_from_slice(&data))
+        .await
+}
+
+/// Stage a binary body or multipart file for a fetch and return its id;
+/// `op_net_body_close` drops it if no fetch took it
+#[weld_op(async)]
+#[op2(async)]
+#[bigint]
+async fn op_net_body_bytes(
+    state: Rc<RefCell<OpState>>,
+    #[buffer] data: JsBuffer,
+) -> Result<u64, NetError> {
+    Ok(get_transfer_state(&state)
+        .stage_bytes(Bytes::copy_from_slice(&data))
+        .await)
+}
+
+/// End a body stream; with `error` the request is aborted
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_body_close(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] stream_id: u64,
+    #[string] error: Option<String>,
+) -> Result<(), NetError> {
+    get_transfer_state(&state)
+        .close_body(stream_id, error)
+        .await;
+    Ok(())
+}
+
+/// Open a progress channel for a request
+#[weld_op(async)]
+#[op2(async)]
+#[bigint]
+async fn op_net_progress_open(state: Rc<RefCell<OpState>>) -> Result<u64, NetError> {
+    Ok(get_transfer_state(&state).open_progress().await)
+}
+
+/// Wait for the next progress update; `None` once the request is done
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_progress_next(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] progress_id: u64,
+) -> Result<Option<FetchProgress>, NetError> {
+    Ok(get_transfer_state(&state).next_progress(progress_id).await)
+}
+
+/// Close a progress channel and return its last value
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_progress_close(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] progress_id: u64,
+) -> Result<Option<FetchProgress>, NetError> {
+    Ok(get_transfer_state(&state).close_progress(progress_id).await)
+}
+
+/// Download a URL to a file, optionally resuming a partial download
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_download(
+    state: Rc<RefCell<OpState>>,
+    #[string] url: String,
+    #[string] path: String,
+    #[serde] opts: Option<FetchOpts>,
+    #[serde] download: Option<DownloadOpts>,
+) -> Result<DownloadResult, NetError> {
+    let opts = opts.unwrap_or_default();
+    let download = download.unwrap_or_default();
+    check_net_write_path(&state.borrow(), &path)?;
+
+    debug!(url = %url, path = %path, resume = ?download.resume, "net.download");
+
+    let (request, progress) = prepare_request(&state, &url, opts).await?;
+    let result = download_to_file(
+        request,
+        std::path::Path::new(&path),
+        &download,
+        progress.as_ref(),
+    )
+    .await?;
+
+    debug!(
+        size = result.size,
+        received = result.received,
+        resumed = result.resumed,
+        "net.download complete"
+    );
+    Ok(result)
+}
+
+// ============================================================================
+// Cookie Operations
+// ============================================================================
+
+/// Clear the app's cookie jar, or only the cookies for `domain` and its
+/// subdomains
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_cookies_clear(
+    state: Rc<RefCell<OpState>>,
+    #[string] domain: Option<String>,
+) -> Result<(), NetError> {
+    let jar = {
+        let mut s = state.borrow_mut();
+        get_http_client(&mut s, None)?;
+        s.borrow::<NetHttpClient>().cookie_jar().clone()
+    };
+
+    debug!(domain = ?domain, "net.cookies clear");
+
+    jar.clear(domain.as_deref());
+    Ok(())
+}
+
+// ============================================================================
+// HTTP Server Operations
+// ============================================================================
+
+/// Start an HTTP(S) server; requests are taken with `op_net_server_next`
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_serve(
+    state: Rc<RefCell<OpState>>,
+    #[serde] opts: Option<ServeOpts>,
+) -> Result<ServerInfo, NetError> {
+    let opts = opts.unwrap_or_default();
+    let port = opts.port.unwrap_or(0);
+    {
+        let s = state.borrow();
+        check_net_listen(&s, port)?;
+    }
+
+    debug!(port = port, hostname = ?opts.hostname, "net.serve");
+
+    let server_state = get_server_state(&state);
+    let id = server_state.next_id().await;
+    let server = HttpServer::bind(id, &opts).await?;
+    let info = server.info.clone();
+    server_state
+        .servers
+        .lock()
+        .await
+        .insert(id, Arc::new(server));
+
+    Ok(info)
+}
+
+/// Wait for the next request; `None` once the server has shut down
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_server_next(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] server_id: u64,
+) -> Result<Option<ServerRequest>, NetError> {
+    let server_state = get_server_state(&state);
+    let server = server_state.server(server_id).await?;
+
+    let id = server_state.next_id().await;
+    let Some((request, pending)) = server.next_request(id).await else {
+        return Ok(None);
+    };
+    server_state
+        .requests
+        .lock()
+        .await
+        .insert(id, Arc::new(Mutex::new(pending)));
+
+    debug!(server = server_id, id = id, method = %request.method, path = %request.path, "net.serve request");
+
+    Ok(Some(request))
+}
+
+/// Read the next chunk of a request body
+///
+/// Returns an empty buffer once the body has been read to its end.
+#[weld_op(async)]
+#[op2(async)]
+#[buffer]
+async fn op_net_server_body_read(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] request_id: u64,
+) -> Result<Vec<u8>, NetError> {
+    let server_state = get_server_state(&state);
+    let pending = server_state.request(request_id).await?;
+    let chunk = pending.lock().await.read_body().await?;
+
+    Ok(chunk.map(Vec::from).unwrap_or_default())
+}
+
+/// Send the response to a request
+///
+/// `body` is ignored when the response is streamed.
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_server_respond(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] request_id: u64,
+    #[serde] init: ServerResponseInit,
+    #[buffer] body: JsBuffer,
+) -> Result<(), NetError> {
+    let server_state = get_server_state(&state);
+    let pending = server_state.request(request_id).await?;
+
+    let streaming = {
+        let mut pending = pending.lock().await;
+        let result = pending.respond(init, Bytes::copy_from_slice(&body));
+        result.map(|_| pending.is_streaming())
+    };
+
+    // A complete response ends the request; a streamed one ends on close
+    if !matches!(streaming, Ok(true)) {
+        server_state.requests.lock().await.remove(&request_id);
+    }
+    streaming.map(|_| ())
+}
+
+/// Write a chunk of a streamed response body
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_server_response_write(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] request_id: u64,
+    #[buffer] chunk: JsBuffer,
+) -> Result<(), NetError> {
+    let server_state = get_server_state(&state);
+    let pending = server_state.request(request_id).await?;
+    let result = pending
+        .lock()
+        .await
+        .write(Bytes::copy_from_slice(&chunk))
+        .await;
+
+    if result.is_err() {
+        server_state.requests.lock().await.remove(&request_id);
+    }
+    result
+}
+
+/// Finish a streamed response
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_server_response_close(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] request_id: u64,
+) -> Result<(), NetError> {
+    let server_state = get_server_state(&state);
+    // Dropping the writer ends the body
+    server_state.requests.lock().await.remove(&request_id);
+    Ok(())
+}
+
+/// Stop a server, letting in-flight requests finish within the grace period
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_server_shutdown(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] server_id: u64,
+    #[serde] grace_ms: Option<u64>,
+) -> Result<(), NetError> {
+    let server_state = get_server_state(&state);
+    let server = server_state.server(server_id).await?;
+
+    debug!(id = server_id, "net.serve shutdown");
+
+    let grace = grace_ms.unwrap_or(DEFAULT_SHUTDOWN_GRACE_MS);
+    server
+        .shutdown(std::time::Duration::from_millis(grace))
+        .await;
+    server_state.servers.lock().await.remove(&server_id);
+    Ok(())
+}
+
+// ============================================================================
+// Socket Operations
+// ============================================================================
+
+/// Open a TCP connection
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_tcp_connect(
+    state: Rc<RefCell<OpState>>,
+    #[serde] opts: TcpConnectOpts,
+) -> Result<ConnInfo, NetError> {
+    let target = host_port(&opts.hostname, opts.port);
+    {
+        let s = state.borrow();
+        check_net_connect(&s, &target)?;
+    }
+
+    debug!(target = %target, "net.tcp connect");
+
+    let (conn, local, remote) = socket::tcp_connect(&opts).await?;
+    Ok(get_socket_state(&state).add_conn(conn, local, remote).await)
+}
+
+/// Bind a TCP listener
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_tcp_listen(
+    state: Rc<RefCell<OpState>>,
+    #[serde] opts: Option<SocketBindOpts>,
+) -> Result<ListenerInfo, NetError> {
+    let opts = opts.unwrap_or_default();
+    let port = opts.port.unwrap_or(0);
+    {
+        let s = state.borrow();
+        check_net_listen(&s, port)?;
+    }
+
+    debug!(port = port, hostname = ?opts.hostname, "net.tcp listen");
+
+    let listener = socket::tcp_listen(&opts).await?;
+    add_listener(&state, listener).await
+}
+
+/// Connect to a Unix domain socket
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_unix_connect(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+) -> Result<ConnInfo, NetError> {
+    {
+        let s = state.borrow();
+        check_net_socket_path(&s, &path)?;
+    }
+
+    debug!(path = %path, "net.unix connect");
+
+    let (conn, local, remote) = socket::unix_connect(&path).await?;
+    Ok(get_socket_state(&state).add_conn(conn, local, remote).await)
+}
+
+/// Listen on a Unix domain socket
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_unix_listen(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+) -> Result<ListenerInfo, NetError> {
+    {
+        let s = state.borrow();
+        check_net_socket_path(&s, &path)?;
+    }
+
+    debug!(path = %path, "net.unix listen");
+
+    let listener = socket::unix_listen(&path).await?;
+    add_listener(&state, listener).await
+}
+
+async fn add_listener(
+    state: &Rc<RefCell<OpState>>,
+    listener: SocketListener,
+) -> Result<ListenerInfo, NetError> {
+    let socket_state = get_socket_state(state);
+    let id = socket_state.next_id().await;
+    let addr = listener.addr.clone();
+    socket_state
+        .listeners
+        .lock()
+        .await
+        .insert(id, Arc::new(listener));
+    Ok(ListenerInfo { id, addr })
+}
+
+/// Wait for the next connection; `None` once the listener is closed
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_listener_accept(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] listener_id: u64,
+) -> Result<Option<ConnInfo>, NetError> {
+    let socket_state = get_socket_state(&state);
+    let listener = socket_state.listener(listener_id).await?;
+
+    let Some((conn, local, remote)) = listener.accept().await? else {
+        return Ok(None);
+    };
+    Ok(Some(socket_state.add_conn(conn, local, remote).await))
+}
+
+/// Close a listener
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_listener_close(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] listener_id: u64,
+) -> Result<(), NetError> {
+    let socket_state = get_socket_state(&state);
+    let listener = socket_state
+        .listeners
+        .lock()
+        .await
+        .remove(&listener_id)
+        .ok_or_else(|| NetError::socket_not_found(format!("Listener {} not found", listener_id)))?;
+    listener.close();
+    Ok(())
+}
+
+/// Read from a connection
+///
+/// Returns an empty buffer at end of stream.
+#[weld_op(async)]
+#[op2(async)]
+#[buffer]
+async fn op_net_conn_read(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] conn_id: u64,
+    #[serde] max_bytes: Option<usize>,
+) -> Result<Vec<u8>, NetError> {
+    let conn = get_socket_state(&state).conn(conn_id).await?;
+    let data = conn.read(max_bytes.unwrap_or(DEFAULT_READ_SIZE)).await?;
+
+    Ok(data.unwrap_or_default())
+}
+
+/// Write to a connection
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_conn_write(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] conn_id: u64,
+    #[buffer] data: JsBuffer,
+) -> Result<(), NetError> {
+    let conn = get_socket_state(&state).conn(conn_id).await?;
+    conn.write(&data).await
+}
+
+/// Shut down the write half of a connection
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_conn_close_write(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] conn_id: u64,
+) -> Result<(), NetError> {
+    let conn = get_socket_state(&state).conn(conn_id).await?;
+    conn.close_write().await
+}
+
+/// Close a connection
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_conn_close(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] conn_id: u64,
+) -> Result<(), NetError> {
+    let socket_state = get_socket_state(&state);
+    let conn = socket_state
+        .conns
+        .lock()
+        .await
+        .remove(&conn_id)
+        .ok_or_else(|| NetError::socket_not_found(format!("Connection {} not found", conn_id)))?;
+    conn.close();
+    Ok(())
+}
+
+/// Bind a UDP socket
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_udp_bind(
+    state: Rc<RefCell<OpState>>,
+    #[serde] opts: Option<SocketBindOpts>,
+) -> Result<ListenerInfo, NetError> {
+    let opts = opts.unwrap_or_default();
+    let port = opts.port.unwrap_or(0);
+    {
+        let s = state.borrow();
+        check_net_listen(&s, port)?;
+    }
+
+    debug!(port = port, hostname = ?opts.hostname, "net.udp bind");
+
+    let endpoint = socket::udp_bind(&opts).await?;
+    let socket_state = get_socket_state(&state);
+    let id = socket_state.next_id().await;
+    let addr = endpoint.addr.clone();
+    socket_state.udp.lock().await.insert(id, Arc::new(endpoint));
+    Ok(ListenerInfo { id, addr })
+}
+
+/// Send a datagram, returning the number of bytes sent
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_udp_send(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] socket_id: u64,
+    #[string] hostname: String,
+    #[smi] port: u16,
+    #[buffer] data: JsBuffer,
+) -> Result<usize, NetError> {
+    {
+        let s = state.borrow();
+        check_net_connect(&s, &host_port(&hostname, port))?;
+    }
+
+    let endpoint = get_socket_state(&state).udp(socket_id).await?;
+    endpoint.send_to(&data, &hostname, port).await
+}
+
+/// Receive a datagram; `None` once the socket is closed
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_net_udp_recv(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] socket_id: u64,
+    #[serde] max_bytes: Option<usize>,
+) -> Result<Option<Datagram>, NetError> {
+    let endpoint = get_socket_state(&state).udp(socket_id).await?;
+    let datagram = endpoint
+        .recv(max_bytes.unwrap_or(MAX_DATAGRAM_SIZE))
+        .await?;
+
+    Ok(datagram.map(|(data, remote_addr)| Datagram {
+        data: data.into(),
+        remote_addr,
+    }))
+}
+
+/// Close a UDP socket
+#[weld_op(async)]
+#[op2(async)]
+async fn op_net_udp_close(
+    state: Rc<RefCell<OpState>>,
+    #[bigint] socket_id: u64,
+) -> Result<(), NetError> {
+    let socket_state = get_socket_state(&state);
+    let endpoint = socket_state
+        .udp
+        .lock()
+        .await
+        .remove(&socket_id)
+        .ok_or_else(|| NetError::socket_not_found(format!("UDP socket {} not found", socket_id)))?;
+    endpoint.close();
+    Ok(())
+}
+
 // ============================================================================
 // State Initialization
 // ============================================================================
 
 /// Initialize net state in OpState
 pub fn init_net_state(op_state: &mut OpState, capabilities: Option<Arc<dyn NetCapabilityChecker>>) {
-    // Initialize HTTP client
-    op_state.put(NetHttpClient::default());
+    // Initialize HTTP client; redirects are checked against the capabilities
+    op_state.put(
+        NetHttpClient::new(HttpClientConfig::default(), None, capabilities.clone())
+            .expect("Failed to build HTTP client"),
+    );
 
     // Initialize WebSocket state
     op_state.put(WebSocketState::default());
@@ -1026,12 +1753,33 @@ pub fn init_net_state(op_state: &mut OpState, capabilities: Option<Arc<dyn NetCa
     // Initialize stream state
     op_state.put(StreamState::default());
 
+    // Initialize server state
+    op_state.put(ServerState::default());
+
+    // Initialize socket state
+    op_state.put(SocketState::default());
+
     // Set capabilities
     if let Some(caps) = capabilities {
         op_state.put(NetCapabilities { checker: caps });
     }
 }
 
+/// Configure the HTTP client from the manifest's `[http]` table. Certificate
+/// files must already be loaded (`HttpClientConfig::load_files`). Cookies are
+/// saved at `cookie_path`. Call after `init_net_state`.
+pub fn configure_http_client(
+    op_state: &mut OpState,
+    config: HttpClientConfig,
+    cookie_path: Option<PathBuf>,
+) -> Result<(), NetError> {
+    let checker = op_state
+        .try_borrow::<NetCapabilities>()
+        .map(|caps| caps.checker.clone());
+    op_state.put(NetHttpClient::new(config, cookie_path, checker)?);
+    Ok(())
+}
+
 // ============================================================================
 // Extension Registration
 // ============================================================================
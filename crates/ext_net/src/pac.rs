@@ -0,0 +1,535 @@
+//! Proxy auto-config (PAC)
+//!
+//! PAC scripts are JavaScript served by the network, so they run in their own
+//! deno_core runtime on a dedicated thread and never see the app's isolate or
+//! ops. The thread loads the script once and then answers `FindProxyForURL`
+//! queries; answers are cached per scheme and host. As in browsers, a script
+//! that cannot be loaded, throws or runs too long means a direct connection.
+//! Those fallbacks are not cached, so the host is asked about again next time.
+//!
+//! reqwest looks the proxy up synchronously while connecting, so fetches ask
+//! the script with [`PacResolver::resolve`] before they are sent and reqwest
+//! only reads the cache.
+
+use crate::NetError;
+use deno_core::{op2, v8, JsRuntime, OpState, RuntimeOptions};
+use std::collections::HashMap;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{debug, warn};
+use url::Url;
+
+/// Helper functions every PAC script may call
+const PAC_UTILS: &str = include_str!("pac_utils.js");
+
+/// How long a lookup waits for the script before connecting directly
+const PAC_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long fetching the script may take; lookups made meanwhile wait for it
+const PAC_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+type PacCache = Arc<Mutex<HashMap<String, Option<Url>>>>;
+
+struct PacQuery {
+    id: u64,
+    url: String,
+    host: String,
+    /// Told when the query has been answered or has failed
+    done: Option<oneshot::Sender<()>>,
+}
+
+/// The query the script is evaluating, so a timed-out lookup interrupts
+/// only itself
+#[derive(Default)]
+struct Running {
+    isolate: Option<v8::IsolateHandle>,
+    query: Option<u64>,
+}
+
+/// Evaluates a PAC script for outgoing requests
+pub struct PacResolver {
+    queries: mpsc::Sender<PacQuery>,
+    /// Answers from the script by scheme and authority
+    cache: PacCache,
+    running: Arc<Mutex<Running>>,
+    /// Set once the script has loaded or failed to
+    loaded: Arc<AtomicBool>,
+    next_query: AtomicU64,
+    query_timeout: Duration,
+}
+
+impl PacResolver {
+    /// Start the PAC thread for the script at `pac_url` (http, https, file
+    /// URL or a path). The script is loaded in the background.
+    pub fn spawn(pac_url: &str) -> Result<Self, NetError> {
+        let (queries, rx) = mpsc::channel();
+        let cache = PacCache::default();
+        let running = Arc::new(Mutex::new(Running::default()));
+        let loaded = Arc::new(AtomicBool::new(false));
+        let thread = PacThread {
+            cache: cache.clone(),
+            running: running.clone(),
+            loaded: loaded.clone(),
+        };
+        let pac_url = pac_url.to_string();
+        std::thread::Builder::new()
+            .name("forge-pac".to_string())
+            .spawn(move || thread.run(&pac_url, rx))?;
+
+        Ok(Self {
+            queries,
+            cache,
+            running,
+            loaded,
+            next_query: AtomicU64::new(1),
+            query_timeout: PAC_QUERY_TIMEOUT,
+        })
+    }
+
+    /// The cached proxy for `url`, or `None` to connect directly.
+    ///
+    /// Never waits for the script. A URL it has not answered yet, such as a
+    /// redirect to another host, connects directly while the script is
+    /// asked in the background.
+    pub fn proxy_for(&self, url: &Url) -> Option<Url> {
+        let (target, host) = pac_target(url)?;
+        if let Some(cached) = self.cached(&target) {
+            return cached;
+        }
+        debug!(url = %target, "PAC answer pending; connecting directly");
+        self.query(target, host, None);
+        None
+    }
+
+    /// Ask the script for the proxy to use for `url` and cache its answer.
+    ///
+    /// Waits for a script that is still loading, then at most the query
+    /// timeout; an evaluation that runs longer is interrupted.
+    pub async fn resolve(&self, url: &Url) -> Option<Url> {
+        let (target, host) = pac_target(url)?;
+        if let Some(cached) = self.cached(&target) {
+            return cached;
+        }
+
+        let (done, answered) = oneshot::channel();
+        let id = self.query(target.clone(), host, Some(done))?;
+        let timeout = if self.loaded.load(Ordering::Acquire) {
+            self.query_timeout
+        } else {
+            PAC_FETCH_TIMEOUT + self.query_timeout
+        };
+        if tokio::time::timeout(timeout, answered).await.is_err() {
+            warn!(url = %target, "PAC script timed out; connecting directly");
+            let running = self.running.lock().unwrap();
+            if running.query == Some(id) {
+                if let Some(isolate) = &running.isolate {
+                    isolate.terminate_execution();
+                }
+            }
+            return None;
+        }
+        self.cached(&target).flatten()
+    }
+
+    fn cached(&self, target: &str) -> Option<Option<Url>> {
+        self.cache.lock().unwrap().get(target).cloned()
+    }
+
+    fn query(&self, url: String, host: String, done: Option<oneshot::Sender<()>>) -> Option<u64> {
+        let id = self.next_query.fetch_add(1, Ordering::Relaxed);
+        let query = PacQuery {
+            id,
+            url,
+            host,
+            done,
+        };
+        self.queries.send(query).ok().map(|_| id)
+    }
+}
+
+/// The URL and host passed to the script: like browsers, only the scheme
+/// and authority
+fn pac_target(url: &Url) -> Option<(String, String)> {
+    let host = url.host_str()?;
+    let target = match url.port() {
+        Some(port) => format!("{}://{}:{}/", url.scheme(), host, port),
+        None => format!("{}://{}/", url.scheme(), host),
+    };
+    Some((target, host.to_string()))
+}
+
+/// Pick the first proxy from a `FindProxyForURL` result such as
+/// `"PROXY proxy.corp:8080; DIRECT"`. `None` means connect directly.
+/// SOCKS entries are skipped.
+pub fn parse_pac_result(result: &str) -> Option<Url> {
+    for entry in result.split(';') {
+        let mut parts = entry.split_whitespace();
+        let Some(kind) = parts.next() else {
+            continue;
+        };
+        let scheme = match kind.to_ascii_uppercase().as_str() {
+            "DIRECT" => return None,
+            "PROXY" | "HTTP" => "http",
+            "HTTPS" => "https",
+            _ => continue,
+        };
+        if let Some(Ok(url)) = parts
+            .next()
+            .map(|target| Url::parse(&format!("{}://{}", scheme, target)))
+        {
+            return Some(url);
+        }
+    }
+    None
+}
+
+// ============================================================================
+// PAC Thread
+// ============================================================================
+
+/// Result of the last `FindProxyForURL` call
+struct PacResult(String);
+
+#[op2]
+#[string]
+fn op_pac_dns_resolve(#[string] host: String) -> Option<String> {
+    (host.as_str(), 0)
+        .to_socket_addrs()
+        .ok()?
+        .find(|addr| addr.is_ipv4())
+        .map(|addr| addr.ip().to_string())
+}
+
+#[op2]
+#[string]
+fn op_pac_my_ip() -> String {
+    // Connecting a UDP socket sends nothing but picks the outgoing interface
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("8.8.8.8:80")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|_| "127.0.0.1".to_string())
+}
+
+#[op2]
+fn op_pac_result(state: &mut OpState, #[string] result: String) {
+    state.put(PacResult(result));
+}
+
+deno_core::extension!(
+    forge_pac,
+    ops = [op_pac_dns_resolve, op_pac_my_ip, op_pac_result]
+);
+
+/// State the PAC thread shares with its resolver
+struct PacThread {
+    cache: PacCache,
+    running: Arc<Mutex<Running>>,
+    loaded: Arc<AtomicBool>,
+}
+
+impl PacThread {
+    fn run(self, pac_url: &str, queries: mpsc::Receiver<PacQuery>) {
+        let mut runtime = match load_pac_script(pac_url).and_then(|script| {
+            let mut runtime = JsRuntime::new(RuntimeOptions {
+                extensions: vec![forge_pac::ext()],
+                ..Default::default()
+            });
+            runtime
+                .execute_script("[pac]", format!("{}\n{}", PAC_UTILS, script))
+                .map_err(|e| e.to_string())?;
+            Ok(runtime)
+        }) {
+            Ok(mut runtime) => {
+                self.running.lock().unwrap().isolate =
+                    Some(runtime.v8_isolate().thread_safe_handle());
+                Some(runtime)
+            }
+            Err(e) => {
+                warn!(url = %pac_url, "PAC script unavailable, connecting directly: {}", e);
+                None
+            }
+        };
+        self.loaded.store(true, Ordering::Release);
+
+        for query in queries {
+            // Queries made while another for the same target was queued
+            let cached = self.cache.lock().unwrap().contains_key(&query.url);
+            if let (Some(runtime), false) = (runtime.as_mut(), cached) {
+                self.answer(runtime, &query);
+            }
+            if let Some(done) = query.done {
+                let _ = done.send(());
+            }
+        }
+    }
+
+    /// Evaluate one query and cache the answer; failures are not cached
+    fn answer(&self, runtime: &mut JsRuntime, query: &PacQuery) {
+        self.running.lock().unwrap().query = Some(query.id);
+        let result = find_proxy(runtime, &query.url, &query.host);
+        {
+            // A timed-out lookup terminates only its own evaluation, so
+            // clearing the termination here keeps later queries running
+            let mut running = self.running.lock().unwrap();
+            running.query = None;
+            runtime.v8_isolate().cancel_terminate_execution();
+        }
+
+        match result {
+            Ok(result) => {
+                debug!(url = %query.url, result = %result, "pac lookup");
+                let proxy = parse_pac_result(&result);
+                self.cache.lock().unwrap().insert(query.url.clone(), proxy);
+            }
+            Err(e) => warn!(url = %query.url, "PAC script failed: {}", e),
+        }
+    }
+}
+
+fn find_proxy(runtime: &mut JsRuntime, url: &str, host: &str) -> Result<String, String> {
+    let call = format!(
+        "Deno.core.ops.op_pac_result(String(FindProxyForURL({}, {})));",
+        serde_json::to_string(url).map_err(|e| e.to_string())?,
+        serde_json::to_string(host).map_err(|e| e.to_string())?,
+    );
+    runtime
+        .execute_script("[pac]", call)
+        .map_err(|e| e.to_string())?;
+    runtime
+        .op_state()
+        .borrow_mut()
+        .try_take::<PacResult>()
+        .map(|result| result.0)
+        .ok_or_else(|| "FindProxyForURL returned nothing".to_string())
+}
+
+fn load_pac_script(pac_url: &str) -> Result<String, String> {
+    let url = match Url::parse(pac_url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+        Ok(url) if url.scheme() == "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|_| format!("Invalid file URL: {}", pac_url))?;
+            return std::fs::read_to_string(path).map_err(|e| e.to_string());
+        }
+        _ => return std::fs::read_to_string(pac_url).map_err(|e| e.to_string()),
+    };
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    rt.block_on(async {
+        // The script decides the proxy, so it is always fetched directly
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .timeout(PAC_FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?;
+        response.text().await.map_err(|e| e.to_string())
+    })
+}
+
+// ============================================================================
+// System PAC Detection
+// ============================================================================
+
+/// The PAC URL configured in the operating system's proxy settings, if any.
+/// Looked up once per process.
+pub fn system_pac_url() -> Option<String> {
+    static PAC_URL: OnceLock<Option<String>> = OnceLock::new();
+    PAC_URL
+        .get_or_init(|| {
+            let url = detect_system_pac_url();
+            if let Some(url) = &url {
+                debug!(url = %url, "system PAC URL");
+            }
+            url
+        })
+        .clone()
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn detect_system_pac_url() -> Option<String> {
+    parse_scutil_proxy(&command_output("scutil", &["--proxy"])?)
+}
+
+#[cfg(windows)]
+fn detect_system_pac_url() -> Option<String> {
+    parse_reg_auto_config(&command_output(
+        "reg",
+        &[
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings",
+            "/v",
+            "AutoConfigURL",
+        ],
+    )?)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn detect_system_pac_url() -> Option<String> {
+    // GNOME proxy settings; other desktops rely on the proxy variables
+    let mode = command_output("gsettings", &["get", "org.gnome.system.proxy", "mode"])?;
+    if parse_gsettings_string(&mode)? != "auto" {
+        return None;
+    }
+    let url = command_output(
+        "gsettings",
+        &["get", "org.gnome.system.proxy", "autoconfig-url"],
+    )?;
+    parse_gsettings_string(&url).filter(|url| !url.is_empty())
+}
+
+/// `scutil --proxy` prints a dictionary of `Key : Value` lines
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_scutil_proxy(output: &str) -> Option<String> {
+    let value = |key: &str| {
+        output.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim().to_string())
+        })
+    };
+    if value("ProxyAutoConfigEnable").as_deref() != Some("1") {
+        return None;
+    }
+    value("ProxyAutoConfigURLString").filter(|url| !url.is_empty())
+}
+
+/// `reg query` prints `    AutoConfigURL    REG_SZ    <url>`
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_reg_auto_config(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        if parts.next()? != "AutoConfigURL" || parts.next()? != "REG_SZ" {
+            return None;
+        }
+        parts.next().map(str::to_string)
+    })
+}
+
+/// `gsettings get` prints strings in single quotes
+#[cfg_attr(any(target_os = "macos", windows), allow(dead_code))]
+fn parse_gsettings_string(output: &str) -> Option<String> {
+    let value = output.trim();
+    value
+        .strip_prefix('\'')
+        .and_then(|v| v.strip_suffix('\''))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pac_result() {
+        assert_eq!(parse_pac_result("DIRECT"), None);
+        assert_eq!(
+            parse_pac_result("PROXY proxy.corp:8080; DIRECT").map(|u| u.to_string()),
+            Some("http://proxy.corp:8080/".to_string())
+        );
+        assert_eq!(
+            parse_pac_result("SOCKS5 socks:1080; HTTPS secure.corp:443").map(|u| u.to_string()),
+            Some("https://secure.corp/".to_string())
+        );
+        assert_eq!(parse_pac_result("SOCKS socks:1080"), None);
+        assert_eq!(parse_pac_result(""), None);
+    }
+
+    #[test]
+    fn test_parse_system_settings() {
+        let scutil = "<dictionary> {\n  ProxyAutoConfigEnable : 1\n  ProxyAutoConfigURLString : http://wpad.corp/proxy.pac\n}\n";
+        assert_eq!(
+            parse_scutil_proxy(scutil).as_deref(),
+            Some("http://wpad.corp/proxy.pac")
+        );
+        assert_eq!(
+            parse_scutil_proxy("<dictionary> {\n  HTTPEnable : 0\n}\n"),
+            None
+        );
+
+        let reg = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Internet Settings\r\n    AutoConfigURL    REG_SZ    http://wpad.corp/wpad.dat\r\n";
+        assert_eq!(
+            parse_reg_auto_config(reg).as_deref(),
+            Some("http://wpad.corp/wpad.dat")
+        );
+
+        assert_eq!(parse_gsettings_string("'auto'\n").as_deref(), Some("auto"));
+        assert_eq!(parse_gsettings_string("''\n").as_deref(), Some(""));
+    }
+
+    /// Serve `script` once, after `delay`
+    async fn serve_script(script: &'static str, delay: Duration) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/proxy.pac", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            tokio::time::sleep(delay).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                script.len(),
+                script
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_late_script_and_slow_lookups() {
+        let script = r#"
+            function FindProxyForURL(url, host) {
+                if (host == "slow.test") { while (true) {} }
+                return "PROXY proxy.test:8080";
+            }
+        "#;
+        let url = serve_script(script, Duration::from_millis(400)).await;
+        let mut resolver = PacResolver::spawn(&url).unwrap();
+        resolver.query_timeout = Duration::from_millis(200);
+        let proxy = Url::parse("http://proxy.test:8080").ok();
+
+        // Lookups made while the script loads wait for it
+        let fast = Url::parse("https://fast.test/path").unwrap();
+        assert_eq!(resolver.resolve(&fast).await, proxy);
+        assert_eq!(resolver.proxy_for(&fast), proxy);
+
+        // A lookup that runs too long connects directly, is not cached and
+        // does not break the lookups after it
+        let slow = Url::parse("https://slow.test/").unwrap();
+        assert_eq!(resolver.resolve(&slow).await, None);
+        assert_eq!(resolver.cached("https://slow.test/"), None);
+        let other = Url::parse("http://other.test:8000/").unwrap();
+        assert_eq!(resolver.resolve(&other).await, proxy);
+    }
+}
@@ -0,0 +1,143 @@
+// Standard helper functions available to proxy auto-config scripts.
+// DNS lookups and the local address come from ops on the PAC thread.
+
+function dnsResolve(host) {
+  return Deno.core.ops.op_pac_dns_resolve(String(host));
+}
+
+function myIpAddress() {
+  return Deno.core.ops.op_pac_my_ip();
+}
+
+function isPlainHostName(host) {
+  return host.indexOf(".") === -1;
+}
+
+function dnsDomainIs(host, domain) {
+  return host.length >= domain.length &&
+    host.substring(host.length - domain.length) === domain;
+}
+
+function localHostOrDomainIs(host, hostdom) {
+  return host === hostdom || hostdom.lastIndexOf(host + ".", 0) === 0;
+}
+
+function isResolvable(host) {
+  return dnsResolve(host) !== null;
+}
+
+function convertAddr(ip) {
+  const parts = ip.split(".");
+  if (parts.length !== 4) return null;
+  let value = 0;
+  for (const part of parts) {
+    const n = Number(part);
+    if (!/^\d+$/.test(part) || n > 255) return null;
+    value = value * 256 + n;
+  }
+  return value;
+}
+
+function isInNet(host, pattern, mask) {
+  const ip = convertAddr(host) === null ? dnsResolve(host) : host;
+  if (ip === null) return false;
+  const addr = convertAddr(ip);
+  const net = convertAddr(pattern);
+  const bits = convertAddr(mask);
+  if (addr === null || net === null || bits === null) return false;
+  // Masking with >>> 0 keeps the values unsigned
+  return ((addr & bits) >>> 0) === ((net & bits) >>> 0);
+}
+
+function dnsDomainLevels(host) {
+  return host.split(".").length - 1;
+}
+
+function shExpMatch(str, shexp) {
+  const pattern = shexp
+    .replace(/[.+^${}()|[\]\\]/g, "\\$&")
+    .replace(/\*/g, ".*")
+    .replace(/\?/g, ".");
+  return new RegExp("^" + pattern + "$").test(str);
+}
+
+const PAC_DAYS = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+const PAC_MONTHS = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+// Split trailing "GMT" off range arguments
+function pacRangeArgs(args) {
+  const list = Array.prototype.slice.call(args);
+  const gmt = list.length > 0 && list[list.length - 1] === "GMT";
+  if (gmt) list.pop();
+  return { list, now: new Date(), gmt };
+}
+
+function pacInRange(value, start, end) {
+  return start <= end ? value >= start && value <= end : value >= start || value <= end;
+}
+
+function weekdayRange() {
+  const { list, now, gmt } = pacRangeArgs(arguments);
+  const day = gmt ? now.getUTCDay() : now.getDay();
+  const start = PAC_DAYS.indexOf(list[0]);
+  const end = list.length > 1 ? PAC_DAYS.indexOf(list[1]) : start;
+  if (start === -1 || end === -1) return false;
+  return pacInRange(day, start, end);
+}
+
+function dateRange() {
+  const { list, now, gmt } = pacRangeArgs(arguments);
+  const date = gmt ? now.getUTCDate() : now.getDate();
+  const month = gmt ? now.getUTCMonth() : now.getMonth();
+  const year = gmt ? now.getUTCFullYear() : now.getFullYear();
+
+  // Each argument is a day (1-31), a month name or a year (four digits)
+  const parse = (value) => {
+    if (typeof value === "string") return { month: PAC_MONTHS.indexOf(value) };
+    return value > 31 ? { year: value } : { date: value };
+  };
+  const values = list.map(parse);
+  const key = (v) => ("year" in v ? "year" : "month" in v ? "month" : "date");
+  const current = { date, month, year };
+
+  if (values.length === 1) {
+    const v = values[0];
+    return v[key(v)] === current[key(v)];
+  }
+
+  // A range: the first half of the arguments is the start, the second the end
+  const half = values.length / 2;
+  const start = values.slice(0, half);
+  const end = values.slice(half);
+  const order = ["year", "month", "date"];
+  const fields = order.filter((f) => start.some((v) => f in v));
+  const toNumber = (vals) =>
+    fields.reduce((n, f) => n * 100 + vals.find((v) => f in v)[f], 0);
+  const today = fields.reduce((n, f) => n * 100 + current[f], 0);
+  return pacInRange(today, toNumber(start), toNumber(end));
+}
+
+function timeRange() {
+  const { list, now, gmt } = pacRangeArgs(arguments);
+  const hour = gmt ? now.getUTCHours() : now.getHours();
+  const minute = gmt ? now.getUTCMinutes() : now.getMinutes();
+  const second = gmt ? now.getUTCSeconds() : now.getSeconds();
+  const current = hour * 3600 + minute * 60 + second;
+
+  switch (list.length) {
+    case 1:
+      return hour === list[0];
+    case 2:
+      return list[0] === list[1] ? hour === list[0] : pacInRange(hour, list[0], list[1] - 1);
+    case 4:
+      return pacInRange(current, list[0] * 3600 + list[1] * 60, list[2] * 3600 + list[3] * 60);
+    case 6:
+      return pacInRange(
+        current,
+        list[0] * 3600 + list[1] * 60 + list[2],
+        list[3] * 3600 + list[4] * 60 + list[5],
+      );
+    default:
+      return false;
+  }
+}
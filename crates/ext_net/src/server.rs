@@ -0,0 +1,659 @@
+//! Local HTTP servers
+//!
+//! Apps serve OAuth redirect callbacks, local APIs for companion tools and
+//! webhook receivers. hyper runs the connections; each request is handed to
+//! JavaScript through a queue and answered through a oneshot channel, so the
+//! handler can take as long as it needs without blocking other connections.
+//!
+//! Request bodies are read on demand and response bodies can be streamed,
+//! both through bounded channels so a slow side applies backpressure.
+//! Shutdown stops accepting, lets in-flight requests finish and closes idle
+//! keep-alive connections, up to a grace period.
+
+use crate::NetError;
+use bytes::Bytes;
+use forge_weld_macro::weld_struct;
+use futures_util::stream;
+use http_body_util::{combinators::BoxBody, BodyExt, Full, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use hyper_util::server::graceful::GracefulShutdown;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+use tokio::task::JoinSet;
+use tokio_rustls::TlsAcceptor;
+use tracing::debug;
+
+/// Address servers bind to unless told otherwise (loopback only)
+pub const DEFAULT_SERVE_HOSTNAME: &str = "127.0.0.1";
+/// How long shutdown waits for in-flight requests by default
+pub const DEFAULT_SHUTDOWN_GRACE_MS: u64 = 10_000;
+
+/// Requests queued for the handler before connections wait
+const REQUEST_QUEUE: usize = 64;
+/// Response chunks buffered before `write` waits for the client
+const RESPONSE_BUFFER: usize = 16;
+
+type ResponseBody = BoxBody<Bytes, Infallible>;
+
+/// Options for starting a server
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServeOpts {
+    /// Port to listen on; 0 picks a free port (default: 0)
+    pub port: Option<u16>,
+    /// Interface to bind (default: 127.0.0.1)
+    pub hostname: Option<String>,
+    /// PEM certificate chain; serves HTTPS together with `key`
+    pub cert: Option<String>,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key: Option<String>,
+}
+
+/// A running server
+#[weld_struct]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerInfo {
+    pub id: u64,
+    pub hostname: String,
+    /// The bound port, resolved when 0 was requested
+    pub port: u16,
+    pub tls: bool,
+    /// Base URL of the server, e.g. `http://127.0.0.1:8080`
+    pub url: String,
+}
+
+/// A request waiting for a response
+#[weld_struct]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerRequest {
+    pub id: u64,
+    pub method: String,
+    /// Full URL including the query string
+    pub url: String,
+    pub path: String,
+    pub query: Option<String>,
+    /// Header names are lowercase; repeated headers are joined with ", "
+    pub headers: HashMap<String, String>,
+    pub remote_addr: String,
+}
+
+/// Status and headers of a response
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerResponseInit {
+    /// Status code (default: 200)
+    pub status: Option<u16>,
+    pub headers: Option<HashMap<String, String>>,
+    /// Stream the body with `op_net_server_response_write`
+    pub stream: Option<bool>,
+}
+
+/// A request received by a connection, before it has an id.
+struct QueuedRequest {
+    request: Request<Incoming>,
+    remote_addr: SocketAddr,
+    respond: oneshot::Sender<Response<ResponseBody>>,
+}
+
+/// A request handed to JavaScript.
+pub struct PendingRequest {
+    body: Option<Incoming>,
+    respond: Option<oneshot::Sender<Response<ResponseBody>>>,
+    /// Set once a streaming response has started
+    writer: Option<mpsc::Sender<Bytes>>,
+}
+
+/// A listening server and its request queue.
+pub struct HttpServer {
+    pub info: ServerInfo,
+    requests: Mutex<mpsc::Receiver<QueuedRequest>>,
+    /// Ask the accept loop to stop, with the grace period for open connections
+    stop: watch::Sender<Option<Duration>>,
+    /// Fires once every connection has finished after a stop
+    stopped: watch::Receiver<bool>,
+}
+
+impl HttpServer {
+    /// Bind a listener and start accepting connections.
+    ///
+    /// The caller checks the listen permission for the requested port first.
+    pub async fn bind(id: u64, opts: &ServeOpts) -> Result<Self, NetError> {
+        let hostname = opts
+            .hostname
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SERVE_HOSTNAME.to_string());
+        let acceptor = match (&opts.cert, &opts.key) {
+            (Some(cert), Some(key)) => Some(load_tls_acceptor(cert, key)?),
+            (None, None) => None,
+            _ => return Err(NetError::tls_config("cert and key must be given together")),
+        };
+
+        let listener = TcpListener::bind((hostname.as_str(), opts.port.unwrap_or(0)))
+            .await
+            .map_err(|e| NetError::io(format!("Failed to bind {}: {}", hostname, e)))?;
+        let port = listener.local_addr()?.port();
+        let scheme = if acceptor.is_some() { "https" } else { "http" };
+        let host = if hostname.contains(':') {
+            format!("[{}]", hostname)
+        } else {
+            hostname.clone()
+        };
+        let info = ServerInfo {
+            id,
+            url: format!("{}://{}:{}", scheme, host, port),
+            hostname,
+            port,
+            tls: acceptor.is_some(),
+        };
+
+        let (request_tx, request_rx) = mpsc::channel(REQUEST_QUEUE);
+        let (stop_tx, stop_rx) = watch::channel(None);
+        let (stopped_tx, stopped_rx) = watch::channel(false);
+        tokio::spawn(async move {
+            accept_loop(listener, acceptor, request_tx, stop_rx).await;
+            let _ = stopped_tx.send(true);
+        });
+
+        debug!(id = id, url = %info.url, "net.serve listening");
+
+        Ok(Self {
+            info,
+            requests: Mutex::new(request_rx),
+            stop: stop_tx,
+            stopped: stopped_rx,
+        })
+    }
+
+    /// Wait for the next request, or `None` once the server has stopped.
+    pub async fn next_request(&self, id: u64) -> Option<(ServerRequest, PendingRequest)> {
+        let incoming = self.requests.lock().await.recv().await?;
+        let (parts, body) = incoming.request.into_parts();
+
+        let mut headers: HashMap<String, String> = HashMap::new();
+        for (name, value) in &parts.headers {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            headers
+                .entry(name.as_str().to_string())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+
+        let host = headers
+            .get("host")
+            .cloned()
+            .unwrap_or_else(|| format!("{}:{}", self.info.hostname, self.info.port));
+        let scheme = if self.info.tls { "https" } else { "http" };
+        let path_and_query = parts
+            .uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/");
+
+        let request = ServerRequest {
+            id,
+            method: parts.method.to_string(),
+            url: format!("{}://{}{}", scheme, host, path_and_query),
+            path: parts.uri.path().to_string(),
+            query: parts.uri.query().map(str::to_string),
+            headers,
+            remote_addr: incoming.remote_addr.to_string(),
+        };
+        let pending = PendingRequest {
+            body: Some(body),
+            respond: Some(incoming.respond),
+            writer: None,
+        };
+        Some((request, pending))
+    }
+
+    /// Stop accepting connections and wait for open ones to finish.
+    ///
+    /// Connections still busy after `grace` are dropped.
+    pub async fn shutdown(&self, grace: Duration) {
+        let _ = self.stop.send(Some(grace));
+        let mut stopped = self.stopped.clone();
+        let _ = stopped.wait_for(|stopped| *stopped).await;
+        // Requests still queued will never be answered
+        self.requests.lock().await.close();
+        debug!(id = self.info.id, "net.serve stopped");
+    }
+}
+
+impl PendingRequest {
+    /// Read the next chunk of the request body, or `None` at its end.
+    pub async fn read_body(&mut self) -> Result<Option<Bytes>, NetError> {
+        let Some(body) = self.body.as_mut() else {
+            return Ok(None);
+        };
+        while let Some(frame) = body.frame().await {
+            let frame = frame.map_err(|e| NetError::stream_error(e.to_string()))?;
+            // Trailers are not exposed
+            if let Ok(data) = frame.into_data() {
+                if !data.is_empty() {
+                    return Ok(Some(data));
+                }
+            }
+        }
+        self.body = None;
+        Ok(None)
+    }
+
+    /// Send the response head, and `body` unless the response is streamed.
+    pub fn respond(&mut self, init: ServerResponseInit, body: Bytes) -> Result<(), NetError> {
+        let respond = self
+            .respond
+            .take()
+            .ok_or_else(|| NetError::stream_error("Response already sent"))?;
+
+        let body = if init.stream.unwrap_or(false) {
+            let (tx, rx) = mpsc::channel::<Bytes>(RESPONSE_BUFFER);
+            self.writer = Some(tx);
+            let chunks = stream::unfold(rx, |mut rx| async move {
+                rx.recv()
+                    .await
+                    .map(|chunk| (Ok::<_, Infallible>(Frame::data(chunk)), rx))
+            });
+            BodyExt::boxed(StreamBody::new(chunks))
+        } else {
+            Full::new(body).boxed()
+        };
+
+        let mut response = Response::new(body);
+        *response.status_mut() = StatusCode::from_u16(init.status.unwrap_or(200))
+            .map_err(|e| NetError::request_build_error(e.to_string()))?;
+        for (name, value) in init.headers.unwrap_or_default() {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| NetError::request_build_error(format!("{}: {}", name, e)))?;
+            let value = HeaderValue::from_str(&value)
+                .map_err(|e| NetError::request_build_error(format!("{}: {}", name, e)))?;
+            response.headers_mut().append(name, value);
+        }
+
+        respond
+            .send(response)
+            .map_err(|_| NetError::connection_failed("Client disconnected"))
+    }
+
+    /// Whether the response is being streamed.
+    pub fn is_streaming(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Send a chunk of a streamed response body.
+    pub async fn write(&self, chunk: Bytes) -> Result<(), NetError> {
+        let writer = self
+            .writer
+            .as_ref()
+            .ok_or_else(|| NetError::stream_error("Response is not streaming"))?;
+        writer
+            .send(chunk)
+            .await
+            .map_err(|_| NetError::connection_failed("Client disconnected"))
+    }
+}
+
+/// Accept connections until asked to stop, then drain them.
+async fn accept_loop(
+    listener: TcpListener,
+    acceptor: Option<TlsAcceptor>,
+    requests: mpsc::Sender<QueuedRequest>,
+    mut stop: watch::Receiver<Option<Duration>>,
+) {
+    let graceful = GracefulShutdown::new();
+    let mut connections = JoinSet::new();
+
+    let grace = loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, remote_addr) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        debug!(error = %e, "net.serve accept failed");
+                        continue;
+                    }
+                };
+                let watcher = graceful.watcher();
+                let requests = requests.clone();
+                let acceptor = acceptor.clone();
+                connections.spawn(async move {
+                    match acceptor {
+                        Some(acceptor) => match acceptor.accept(stream).await {
+                            Ok(tls) => serve_connection(tls, remote_addr, requests, watcher).await,
+                            Err(e) => debug!(error = %e, "net.serve TLS handshake failed"),
+                        },
+                        None => serve_connection(stream, remote_addr, requests, watcher).await,
+                    }
+                });
+            }
+            // Reap finished connections
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            changed = stop.changed() => {
+                match changed {
+                    Ok(()) => break stop.borrow().unwrap_or_default(),
+                    // The server handle is gone; nobody can answer requests
+                    Err(_) => break Duration::ZERO,
+                }
+            }
+        }
+    };
+
+    drop(listener);
+    drop(requests);
+    tokio::select! {
+        _ = graceful.shutdown() => {}
+        _ = tokio::time::sleep(grace) => {
+            debug!("net.serve grace period over, dropping connections");
+        }
+    }
+    connections.abort_all();
+    while connections.join_next().await.is_some() {}
+}
+
+/// Serve HTTP/1.1 on one connection, passing each request to the queue.
+async fn serve_connection<S>(
+    stream: S,
+    remote_addr: SocketAddr,
+    requests: mpsc::Sender<QueuedRequest>,
+    watcher: hyper_util::server::graceful::Watcher,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let service = service_fn(move |request: Request<Incoming>| {
+        let requests = requests.clone();
+        async move {
+            let (respond, response) = oneshot::channel();
+            let incoming = QueuedRequest {
+                request,
+                remote_addr,
+                respond,
+            };
+            if requests.send(incoming).await.is_err() {
+                return Ok::<_, Infallible>(plain_response(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Server is shutting down",
+                ));
+            }
+            // The handler dropped the request without answering it
+            Ok(response.await.unwrap_or_else(|_| {
+                plain_response(StatusCode::INTERNAL_SERVER_ERROR, "No response")
+            }))
+        }
+    });
+
+    let connection =
+        hyper::server::conn::http1::Builder::new().serve_connection(TokioIo::new(stream), service);
+    if let Err(e) = watcher.watch(connection).await {
+        debug!(error = %e, remote = %remote_addr, "net.serve connection error");
+    }
+}
+
+fn plain_response(status: StatusCode, message: &'static str) -> Response<ResponseBody> {
+    let mut response = Response::new(Full::new(Bytes::from_static(message.as_bytes())).boxed());
+    *response.status_mut() = status;
+    response
+}
+
+/// PEM text passed in from JS
+///
+/// File paths are refused: the runtime would read them without the fs
+/// permission checks, so scripts read the files through `runtime:fs` instead.
+fn pem_text<'a>(value: &'a str, field: &str) -> Result<&'a [u8], NetError> {
+    if value.trim_start().starts_with("-----BEGIN") {
+        Ok(value.as_bytes())
+    } else {
+        Err(NetError::tls_config(format!(
+            "{} must be PEM text, not a file path",
+            field
+        )))
+    }
+}
+
+/// Build a TLS acceptor from a PEM certificate chain and private key.
+fn load_tls_acceptor(cert: &str, key: &str) -> Result<TlsAcceptor, NetError> {
+    let certs = CertificateDer::pem_slice_iter(pem_text(cert, "cert")?)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| NetError::tls_config(format!("cert: {}", e)))?;
+    if certs.is_empty() {
+        return Err(NetError::tls_config("cert: no certificates found"));
+    }
+    let key = PrivateKeyDer::from_pem_slice(pem_text(key, "key")?)
+        .map_err(|e| NetError::tls_config(format!("key: {}", e)))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| NetError::tls_config(e.to_string()))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| NetError::tls_config(e.to_string()))?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Servers and the requests they are waiting to answer
+#[derive(Clone)]
+pub struct ServerState {
+    pub servers: Arc<Mutex<HashMap<u64, Arc<HttpServer>>>>,
+    pub requests: Arc<Mutex<HashMap<u64, Arc<Mutex<PendingRequest>>>>>,
+    pub next_id: Arc<Mutex<u64>>,
+}
+
+impl Default for ServerState {
+    fn default() -> Self {
+        Self {
+            servers: Arc::new(Mutex::new(HashMap::new())),
+            requests: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+impl ServerState {
+    /// Allocate an id for a server or request.
+    pub async fn next_id(&self) -> u64 {
+        let mut next_id = self.next_id.lock().await;
+        let id = *next_id;
+        *next_id += 1;
+        id
+    }
+
+    pub async fn server(&self, id: u64) -> Result<Arc<HttpServer>, NetError> {
+        self.servers
+            .lock()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| NetError::server_not_found(format!("Server {} not found", id)))
+    }
+
+    pub async fn request(&self, id: u64) -> Result<Arc<Mutex<PendingRequest>>, NetError> {
+        self.requests
+            .lock()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| NetError::server_not_found(format!("Request {} not found", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    async fn roundtrip(port: u16, raw: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(raw.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_request_and_response() {
+        let server = Arc::new(HttpServer::bind(1, &ServeOpts::default()).await.unwrap());
+        let port = server.info.port;
+        assert!(port != 0);
+        assert_eq!(server.info.url, format!("http://127.0.0.1:{}", port));
+
+        let client = tokio::spawn(roundtrip(
+            port,
+            "POST /hook?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+        ));
+
+        let (request, mut pending) = server.next_request(7).await.unwrap();
+        assert_eq!(request.id, 7);
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/hook");
+        assert_eq!(request.query.as_deref(), Some("x=1"));
+        assert_eq!(request.url, "http://localhost/hook?x=1");
+        assert_eq!(request.headers["content-length"], "5");
+
+        let mut body = Vec::new();
+        while let Some(chunk) = pending.read_body().await.unwrap() {
+            body.extend_from_slice(&chunk);
+        }
+        assert_eq!(body, b"hello");
+
+        pending
+            .respond(
+                ServerResponseInit {
+                    status: Some(201),
+                    headers: Some(HashMap::from([("x-test".to_string(), "1".to_string())])),
+                    stream: None,
+                },
+                Bytes::from_static(b"created"),
+            )
+            .unwrap();
+        assert!(pending
+            .respond(ServerResponseInit::default(), Bytes::new())
+            .is_err());
+
+        let response = client.await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+        assert!(response.contains("x-test: 1\r\n"));
+        assert!(response.ends_with("\r\n\r\ncreated"));
+
+        server.shutdown(Duration::from_secs(1)).await;
+        assert!(server.next_request(8).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_response() {
+        let server = HttpServer::bind(1, &ServeOpts::default()).await.unwrap();
+        let client = tokio::spawn(roundtrip(
+            server.info.port,
+            "GET /events HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        ));
+
+        let (_, mut pending) = server.next_request(1).await.unwrap();
+        pending
+            .respond(
+                ServerResponseInit {
+                    stream: Some(true),
+                    ..Default::default()
+                },
+                Bytes::new(),
+            )
+            .unwrap();
+        assert!(pending.is_streaming());
+        pending.write(Bytes::from_static(b"one,")).await.unwrap();
+        pending.write(Bytes::from_static(b"two")).await.unwrap();
+        drop(pending);
+
+        let response = client.await.unwrap();
+        assert!(response.contains("transfer-encoding: chunked"));
+        assert!(response.contains("4\r\none,\r\n3\r\ntwo\r\n0\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_finishes_requests() {
+        let server = Arc::new(HttpServer::bind(1, &ServeOpts::default()).await.unwrap());
+        let port = server.info.port;
+        let client = tokio::spawn(roundtrip(
+            port,
+            "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        ));
+        let (_, mut pending) = server.next_request(1).await.unwrap();
+
+        // Shutdown waits for the request in flight
+        let shutdown = tokio::spawn({
+            let server = server.clone();
+            async move { server.shutdown(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!shutdown.is_finished());
+        assert!(TcpStream::connect(("127.0.0.1", port)).await.is_err());
+
+        pending
+            .respond(ServerResponseInit::default(), Bytes::from_static(b"bye"))
+            .unwrap();
+        assert!(client.await.unwrap().ends_with("bye"));
+        shutdown.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tls_options_validated() {
+        let opts = ServeOpts {
+            cert: Some("-----BEGIN CERTIFICATE-----".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            HttpServer::bind(1, &opts).await,
+            Err(NetError::TlsConfig { .. })
+        ));
+
+        let opts = ServeOpts {
+            cert: Some("/nonexistent/cert.pem".to_string()),
+            key: Some("/nonexistent/key.pem".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            HttpServer::bind(1, &opts).await,
+            Err(NetError::TlsConfig { .. })
+        ));
+
+        // Files are never read on the script's behalf
+        let dir = std::env::temp_dir().join(format!("forge-serve-tls-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert = dir.join("cert.pem");
+        std::fs::write(&cert, "-----BEGIN CERTIFICATE-----\n").unwrap();
+        let opts = ServeOpts {
+            cert: Some(cert.to_string_lossy().to_string()),
+            key: Some(cert.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        match HttpServer::bind(1, &opts).await {
+            Err(NetError::TlsConfig { message, .. }) => {
+                assert!(message.contains("not a file path"))
+            }
+            _ => panic!("expected a TLS config error"),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,571 @@
+//! Raw TCP, UDP and Unix domain sockets
+//!
+//! Connections and listeners are stream resources addressed by id, like
+//! WebSockets and streamed fetches. A connection is split into read and write
+//! halves so a pending read never blocks a write; closing a resource wakes any
+//! read, write or accept still waiting on it.
+//!
+//! Permissions are checked by the ops: remote hosts as `host:port` through
+//! `check_connect`, bound ports through `check_listen` and Unix socket paths
+//! through `check_socket_path`.
+
+use crate::{NetError, DEFAULT_SERVE_HOSTNAME};
+use deno_core::ToJsBuffer;
+use forge_weld_macro::weld_struct;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::{watch, Mutex};
+
+/// Bytes returned by a read unless the caller asks for another size
+pub const DEFAULT_READ_SIZE: usize = 64 * 1024;
+/// Largest UDP payload
+pub const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// Address of a socket endpoint
+#[weld_struct]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NetAddr {
+    /// "tcp", "udp" or "unix"
+    pub transport: String,
+    pub hostname: Option<String>,
+    pub port: Option<u16>,
+    /// Socket path for Unix sockets; `None` for unnamed peers
+    pub path: Option<String>,
+}
+
+impl NetAddr {
+    fn inet(transport: &str, addr: SocketAddr) -> Self {
+        Self {
+            transport: transport.to_string(),
+            hostname: Some(addr.ip().to_string()),
+            port: Some(addr.port()),
+            path: None,
+        }
+    }
+
+    #[cfg(unix)]
+    fn unix(addr: &tokio::net::unix::SocketAddr) -> Self {
+        Self {
+            transport: "unix".to_string(),
+            hostname: None,
+            port: None,
+            path: addr
+                .as_pathname()
+                .map(|path| path.to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// Options for opening a TCP connection
+#[weld_struct]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpConnectOpts {
+    pub hostname: String,
+    pub port: u16,
+    /// Give up connecting after this many milliseconds
+    pub timeout_ms: Option<u64>,
+    /// Disable Nagle's algorithm (default: true)
+    pub no_delay: Option<bool>,
+}
+
+/// Options for binding a TCP listener or UDP socket
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketBindOpts {
+    /// Port to bind; 0 picks a free port (default: 0)
+    pub port: Option<u16>,
+    /// Interface to bind (default: 127.0.0.1)
+    pub hostname: Option<String>,
+    /// Allow sending UDP broadcasts (UDP only)
+    pub broadcast: Option<bool>,
+}
+
+/// An open connection
+#[weld_struct]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnInfo {
+    pub id: u64,
+    pub local_addr: NetAddr,
+    pub remote_addr: NetAddr,
+}
+
+/// A bound listener or UDP socket
+#[weld_struct]
+#[derive(Debug, Clone, Serialize)]
+pub struct ListenerInfo {
+    pub id: u64,
+    pub addr: NetAddr,
+}
+
+/// A received UDP datagram
+#[weld_struct]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Datagram {
+    pub data: ToJsBuffer,
+    pub remote_addr: NetAddr,
+}
+
+/// The `host:port` form checked against the net allow list
+pub fn host_port(hostname: &str, port: u16) -> String {
+    if hostname.contains(':') && !hostname.starts_with('[') {
+        format!("[{}]:{}", hostname, port)
+    } else {
+        format!("{}:{}", hostname, port)
+    }
+}
+
+/// Run `fut` unless `closed` is set first; `None` means the resource closed.
+async fn until_closed<T>(closed: &watch::Sender<bool>, fut: impl Future<Output = T>) -> Option<T> {
+    let mut rx = closed.subscribe();
+    tokio::select! {
+        biased;
+        _ = rx.wait_for(|closed| *closed) => None,
+        result = fut => Some(result),
+    }
+}
+
+/// Byte stream a connection can be built from
+trait SocketStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> SocketStream for T {}
+
+/// A TCP or Unix stream connection
+pub struct SocketConn {
+    reader: Mutex<ReadHalf<Box<dyn SocketStream>>>,
+    writer: Mutex<WriteHalf<Box<dyn SocketStream>>>,
+    closed: watch::Sender<bool>,
+}
+
+impl SocketConn {
+    fn new(stream: impl AsyncRead + AsyncWrite + Send + Unpin + 'static) -> Self {
+        let (reader, writer) = tokio::io::split(Box::new(stream) as Box<dyn SocketStream>);
+        Self {
+            reader: Mutex::new(reader),
+            writer: Mutex::new(writer),
+            closed: watch::channel(false).0,
+        }
+    }
+
+    /// Read up to `max` bytes; `None` at end of stream or after `close`.
+    pub async fn read(&self, max: usize) -> Result<Option<Vec<u8>>, NetError> {
+        let mut buf = vec![0u8; max.max(1)];
+        let read = until_closed(&self.closed, async {
+            self.reader.lock().await.read(&mut buf).await
+        })
+        .await;
+
+        match read {
+            None | Some(Ok(0)) => Ok(None),
+            Some(Ok(n)) => {
+                buf.truncate(n);
+                Ok(Some(buf))
+            }
+            Some(Err(e)) => Err(e.into()),
+        }
+    }
+
+    /// Write all of `data`.
+    pub async fn write(&self, data: &[u8]) -> Result<(), NetError> {
+        until_closed(&self.closed, async {
+            let mut writer = self.writer.lock().await;
+            writer.write_all(data).await?;
+            writer.flush().await
+        })
+        .await
+        .ok_or_else(|| NetError::io("Connection closed"))?
+        .map_err(NetError::from)
+    }
+
+    /// Shut down the write half; the peer sees end of stream.
+    pub async fn close_write(&self) -> Result<(), NetError> {
+        self.writer.lock().await.shutdown().await?;
+        Ok(())
+    }
+
+    /// Wake pending reads and writes; the socket closes once they return.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+/// Removes a Unix socket file when its listener goes away
+#[cfg(unix)]
+struct SocketFile(std::path::PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+enum ListenerKind {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix {
+        listener: tokio::net::UnixListener,
+        /// Held so the socket file is removed with the listener
+        _file: SocketFile,
+    },
+}
+
+/// A TCP or Unix listener
+pub struct SocketListener {
+    kind: ListenerKind,
+    pub addr: NetAddr,
+    closed: watch::Sender<bool>,
+}
+
+impl SocketListener {
+    fn new(kind: ListenerKind, addr: NetAddr) -> Self {
+        Self {
+            kind,
+            addr,
+            closed: watch::channel(false).0,
+        }
+    }
+
+    /// Wait for the next connection; `None` after `close`.
+    pub async fn accept(&self) -> Result<Option<(SocketConn, NetAddr, NetAddr)>, NetError> {
+        let accepted = until_closed(&self.closed, async {
+            match &self.kind {
+                ListenerKind::Tcp(listener) => {
+                    let (stream, remote) = listener.accept().await?;
+                    let _ = stream.set_nodelay(true);
+                    let local = stream.local_addr()?;
+                    Ok::<_, std::io::Error>((
+                        SocketConn::new(stream),
+                        NetAddr::inet("tcp", local),
+                        NetAddr::inet("tcp", remote),
+                    ))
+                }
+                #[cfg(unix)]
+                ListenerKind::Unix { listener, .. } => {
+                    let (stream, remote) = listener.accept().await?;
+                    let local = stream.local_addr()?;
+                    Ok((
+                        SocketConn::new(stream),
+                        NetAddr::unix(&local),
+                        NetAddr::unix(&remote),
+                    ))
+                }
+            }
+        })
+        .await;
+
+        accepted.transpose().map_err(NetError::from)
+    }
+
+    /// Stop accepting and wake a pending `accept`.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+/// A bound UDP socket
+pub struct UdpEndpoint {
+    socket: UdpSocket,
+    pub addr: NetAddr,
+    closed: watch::Sender<bool>,
+}
+
+impl UdpEndpoint {
+    /// Receive one datagram of at most `max` bytes and its sender; `None`
+    /// after `close`.
+    pub async fn recv(&self, max: usize) -> Result<Option<(Vec<u8>, NetAddr)>, NetError> {
+        let mut buf = vec![0u8; max.clamp(1, MAX_DATAGRAM_SIZE)];
+        match until_closed(&self.closed, self.socket.recv_from(&mut buf)).await {
+            None => Ok(None),
+            Some(Ok((n, remote))) => {
+                buf.truncate(n);
+                Ok(Some((buf, NetAddr::inet("udp", remote))))
+            }
+            Some(Err(e)) => Err(e.into()),
+        }
+    }
+
+    /// Send one datagram, returning the number of bytes sent.
+    pub async fn send_to(&self, data: &[u8], hostname: &str, port: u16) -> Result<usize, NetError> {
+        until_closed(&self.closed, self.socket.send_to(data, (hostname, port)))
+            .await
+            .ok_or_else(|| NetError::io("Socket closed"))?
+            .map_err(NetError::from)
+    }
+
+    /// Wake pending receives and sends.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+/// Open a TCP connection.
+pub async fn tcp_connect(
+    opts: &TcpConnectOpts,
+) -> Result<(SocketConn, NetAddr, NetAddr), NetError> {
+    let connect = TcpStream::connect((opts.hostname.as_str(), opts.port));
+    let stream = match opts.timeout_ms {
+        Some(ms) => tokio::time::timeout(Duration::from_millis(ms), connect)
+            .await
+            .map_err(|_| {
+                NetError::timeout(format!(
+                    "Connecting to {} timed out",
+                    host_port(&opts.hostname, opts.port)
+                ))
+            })?,
+        None => connect.await,
+    }
+    .map_err(|e| {
+        NetError::connection_failed(format!("{}: {}", host_port(&opts.hostname, opts.port), e))
+    })?;
+
+    stream.set_nodelay(opts.no_delay.unwrap_or(true))?;
+    let local = NetAddr::inet("tcp", stream.local_addr()?);
+    let remote = NetAddr::inet("tcp", stream.peer_addr()?);
+    Ok((SocketConn::new(stream), local, remote))
+}
+
+/// Bind a TCP listener.
+pub async fn tcp_listen(opts: &SocketBindOpts) -> Result<SocketListener, NetError> {
+    let hostname = opts.hostname.as_deref().unwrap_or(DEFAULT_SERVE_HOSTNAME);
+    let listener = TcpListener::bind((hostname, opts.port.unwrap_or(0))).await?;
+    let addr = NetAddr::inet("tcp", listener.local_addr()?);
+    Ok(SocketListener::new(ListenerKind::Tcp(listener), addr))
+}
+
+/// Bind a UDP socket.
+pub async fn udp_bind(opts: &SocketBindOpts) -> Result<UdpEndpoint, NetError> {
+    let hostname = opts.hostname.as_deref().unwrap_or(DEFAULT_SERVE_HOSTNAME);
+    let socket = UdpSocket::bind((hostname, opts.port.unwrap_or(0))).await?;
+    if let Some(broadcast) = opts.broadcast {
+        socket.set_broadcast(broadcast)?;
+    }
+    let addr = NetAddr::inet("udp", socket.local_addr()?);
+    Ok(UdpEndpoint {
+        socket,
+        addr,
+        closed: watch::channel(false).0,
+    })
+}
+
+/// Connect to a Unix domain socket.
+#[cfg(unix)]
+pub async fn unix_connect(path: &str) -> Result<(SocketConn, NetAddr, NetAddr), NetError> {
+    let stream = tokio::net::UnixStream::connect(path)
+        .await
+        .map_err(|e| NetError::connection_failed(format!("{}: {}", path, e)))?;
+    let local = NetAddr::unix(&stream.local_addr()?);
+    let remote = NetAddr::unix(&stream.peer_addr()?);
+    Ok((SocketConn::new(stream), local, remote))
+}
+
+/// Listen on a Unix domain socket. The socket file is removed when the
+/// listener is closed.
+#[cfg(unix)]
+pub async fn unix_listen(path: &str) -> Result<SocketListener, NetError> {
+    let listener = tokio::net::UnixListener::bind(path)?;
+    let addr = NetAddr::unix(&listener.local_addr()?);
+    let kind = ListenerKind::Unix {
+        listener,
+        _file: SocketFile(path.into()),
+    };
+    Ok(SocketListener::new(kind, addr))
+}
+
+#[cfg(not(unix))]
+pub async fn unix_connect(_path: &str) -> Result<(SocketConn, NetAddr, NetAddr), NetError> {
+    Err(NetError::io(
+        "Unix domain sockets are not supported on this platform",
+    ))
+}
+
+#[cfg(not(unix))]
+pub async fn unix_listen(_path: &str) -> Result<SocketListener, NetError> {
+    Err(NetError::io(
+        "Unix domain sockets are not supported on this platform",
+    ))
+}
+
+/// Socket state manager
+#[derive(Clone)]
+pub struct SocketState {
+    pub conns: Arc<Mutex<HashMap<u64, Arc<SocketConn>>>>,
+    pub listeners: Arc<Mutex<HashMap<u64, Arc<SocketListener>>>>,
+    pub udp: Arc<Mutex<HashMap<u64, Arc<UdpEndpoint>>>>,
+    pub next_id: Arc<Mutex<u64>>,
+}
+
+impl Default for SocketState {
+    fn default() -> Self {
+        Self {
+            conns: Arc::new(Mutex::new(HashMap::new())),
+            listeners: Arc::new(Mutex::new(HashMap::new())),
+            udp: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+impl SocketState {
+    /// Allocate an id for a connection, listener or UDP socket.
+    pub async fn next_id(&self) -> u64 {
+        let mut next_id = self.next_id.lock().await;
+        let id = *next_id;
+        *next_id += 1;
+        id
+    }
+
+    /// Register a connection and describe it.
+    pub async fn add_conn(&self, conn: SocketConn, local: NetAddr, remote: NetAddr) -> ConnInfo {
+        let id = self.next_id().await;
+        self.conns.lock().await.insert(id, Arc::new(conn));
+        ConnInfo {
+            id,
+            local_addr: local,
+            remote_addr: remote,
+        }
+    }
+
+    pub async fn conn(&self, id: u64) -> Result<Arc<SocketConn>, NetError> {
+        self.conns
+            .lock()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| NetError::socket_not_found(format!("Connection {} not found", id)))
+    }
+
+    pub async fn listener(&self, id: u64) -> Result<Arc<SocketListener>, NetError> {
+        self.listeners
+            .lock()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| NetError::socket_not_found(format!("Listener {} not found", id)))
+    }
+
+    pub async fn udp(&self, id: u64) -> Result<Arc<UdpEndpoint>, NetError> {
+        self.udp
+            .lock()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| NetError::socket_not_found(format!("UDP socket {} not found", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_port() {
+        assert_eq!(host_port("localhost", 5432), "localhost:5432");
+        assert_eq!(host_port("::1", 53), "[::1]:53");
+        assert_eq!(host_port("[::1]", 53), "[::1]:53");
+    }
+
+    #[tokio::test]
+    async fn test_tcp_echo() {
+        let listener = tcp_listen(&SocketBindOpts::default()).await.unwrap();
+        let port = listener.addr.port.unwrap();
+
+        let connect = TcpConnectOpts {
+            hostname: "127.0.0.1".to_string(),
+            port,
+            timeout_ms: Some(5000),
+            no_delay: None,
+        };
+        let (client, _, remote) = tcp_connect(&connect).await.unwrap();
+        assert_eq!(remote.port, Some(port));
+
+        let (server, _, _) = listener.accept().await.unwrap().unwrap();
+        client.write(b"ping").await.unwrap();
+        let received = server.read(DEFAULT_READ_SIZE).await.unwrap().unwrap();
+        server.write(&received).await.unwrap();
+        assert_eq!(client.read(2).await.unwrap().unwrap(), b"pi");
+        assert_eq!(client.read(16).await.unwrap().unwrap(), b"ng");
+
+        client.close_write().await.unwrap();
+        assert_eq!(server.read(16).await.unwrap(), None);
+
+        listener.close();
+        assert!(listener.accept().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_close_wakes_pending_read() {
+        let listener = Arc::new(tcp_listen(&SocketBindOpts::default()).await.unwrap());
+        let connect = TcpConnectOpts {
+            hostname: "127.0.0.1".to_string(),
+            port: listener.addr.port.unwrap(),
+            timeout_ms: None,
+            no_delay: Some(false),
+        };
+        let (client, _, _) = tcp_connect(&connect).await.unwrap();
+        let client = Arc::new(client);
+
+        let reader = {
+            let client = client.clone();
+            tokio::spawn(async move { client.read(16).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        client.close();
+
+        let read = tokio::time::timeout(Duration::from_secs(5), reader)
+            .await
+            .expect("close should wake the read")
+            .unwrap();
+        assert_eq!(read.unwrap(), None);
+        assert!(client.write(b"late").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_udp_roundtrip() {
+        let a = udp_bind(&SocketBindOpts::default()).await.unwrap();
+        let b = udp_bind(&SocketBindOpts::default()).await.unwrap();
+
+        let sent = a
+            .send_to(b"hello", "127.0.0.1", b.addr.port.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(sent, 5);
+
+        let (data, remote_addr) = b.recv(MAX_DATAGRAM_SIZE).await.unwrap().unwrap();
+        assert_eq!(data, b"hello");
+        assert_eq!(remote_addr, a.addr);
+
+        b.close();
+        assert!(b.recv(MAX_DATAGRAM_SIZE).await.unwrap().is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket() {
+        let path = std::env::temp_dir().join(format!("forge-net-{}.sock", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let listener = unix_listen(&path_str).await.unwrap();
+        assert_eq!(listener.addr.path.as_deref(), Some(path_str.as_str()));
+
+        let (client, _, remote) = unix_connect(&path_str).await.unwrap();
+        assert_eq!(remote.path.as_deref(), Some(path_str.as_str()));
+        let (server, _, _) = listener.accept().await.unwrap().unwrap();
+
+        client.write(b"over unix").await.unwrap();
+        assert_eq!(server.read(64).await.unwrap().unwrap(), b"over unix");
+
+        drop(listener);
+        assert!(!path.exists(), "socket file should be removed");
+    }
+}
@@ -0,0 +1,912 @@
+//! Request bodies, transfer progress and resumable downloads
+//!
+//! A fetch body can be text, bytes, a stream written chunk by chunk from
+//! JavaScript, or multipart/form-data whose file parts are read from disk
+//! while the request is sent. Body streams, staged bytes and progress
+//! channels are resources addressed by id, like WebSockets and sockets.
+//! Binary bodies and multipart file contents are staged as buffers before
+//! the fetch, so they never cross the op boundary as JSON.
+//!
+//! A request given a progress id reports the bytes it has sent and received
+//! on a watch channel that JavaScript polls; updates a slow reader misses are
+//! coalesced. Downloads to a file can continue a partial file with a `Range`
+//! request.
+
+use crate::NetError;
+use bytes::Bytes;
+use forge_weld_macro::weld_struct;
+use futures_util::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use reqwest::header::{
+    CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{mpsc, watch, Mutex};
+
+/// Chunks a body stream buffers before writes from JavaScript wait
+pub const BODY_STREAM_BUFFER: usize = 8;
+/// Size of the chunks file parts and tracked bodies are sent in
+pub const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A chunk of a request body; an error aborts the request
+pub type BodyChunk = Result<Bytes, std::io::Error>;
+
+/// One part of a multipart/form-data body. Exactly one of `value`, `buffer`
+/// and `path` must be set.
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MultipartPart {
+    pub name: String,
+    /// Text field value
+    pub value: Option<String>,
+    /// Id of the file contents staged with `op_net_body_bytes`
+    pub buffer: Option<u64>,
+    /// File read from disk as the request is sent
+    pub path: Option<String>,
+    /// File name for `buffer` or `path` parts (default: the path's file name)
+    pub filename: Option<String>,
+    /// Defaults to application/octet-stream for files
+    pub content_type: Option<String>,
+}
+
+/// Bytes a request has sent and received so far
+#[weld_struct]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FetchProgress {
+    pub uploaded: u64,
+    /// `None` for bodies of unknown length (streams)
+    pub upload_total: Option<u64>,
+    /// Includes the part of a resumed download already on disk
+    pub downloaded: u64,
+    /// `None` when the response has no length
+    pub download_total: Option<u64>,
+}
+
+/// Options for downloading to a file
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DownloadOpts {
+    /// Continue from the length of an existing file (default: false)
+    pub resume: Option<bool>,
+    /// ETag or Last-Modified value from an earlier attempt; the server sends
+    /// the whole file again if it changed since
+    pub if_range: Option<String>,
+}
+
+/// A finished download
+#[weld_struct]
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadResult {
+    pub path: String,
+    pub status: u16,
+    pub url: String,
+    /// File size after the download
+    pub size: u64,
+    /// Bytes received by this request
+    pub received: u64,
+    /// Whether an earlier partial file was continued
+    pub resumed: bool,
+    /// Validators to pass as `if_range` when resuming later
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Sending side of a request's progress channel
+#[derive(Clone)]
+pub struct ProgressReporter(Arc<watch::Sender<FetchProgress>>);
+
+impl ProgressReporter {
+    pub fn upload_started(&self, total: Option<u64>) {
+        self.0.send_modify(|p| p.upload_total = total);
+    }
+
+    pub fn uploaded(&self, bytes: u64) {
+        self.0.send_modify(|p| p.uploaded += bytes);
+    }
+
+    /// `offset` counts bytes already on disk from an earlier attempt.
+    pub fn download_started(&self, offset: u64, total: Option<u64>) {
+        self.0.send_modify(|p| {
+            p.downloaded = offset;
+            p.download_total = total;
+        });
+    }
+
+    pub fn downloaded(&self, bytes: u64) {
+        self.0.send_modify(|p| p.downloaded += bytes);
+    }
+}
+
+/// A request body before it is attached to a request
+pub enum RequestBody {
+    Bytes(Bytes),
+    /// Chunks written from JavaScript
+    Stream(mpsc::Receiver<BodyChunk>),
+    /// Parts and the staged contents of their `buffer`s
+    Multipart(Vec<MultipartPart>, HashMap<u64, Bytes>),
+}
+
+impl RequestBody {
+    /// File paths a multipart body reads, for permission checks
+    pub fn file_paths(&self) -> Vec<&str> {
+        match self {
+            RequestBody::Multipart(parts, _) => parts
+                .iter()
+                .filter_map(|part| part.path.as_deref())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Attach `body` to `request`. Bodies are streamed when they are read from
+/// JavaScript or disk, or when progress is reported.
+pub fn attach_body(
+    request: reqwest::RequestBuilder,
+    body: RequestBody,
+    progress: Option<&ProgressReporter>,
+) -> Result<reqwest::RequestBuilder, NetError> {
+    let (mut request, chunks, length): (_, BoxStream<'static, BodyChunk>, _) = match body {
+        RequestBody::Bytes(data) if progress.is_none() => return Ok(request.body(data)),
+        RequestBody::Bytes(data) => {
+            let length = data.len() as u64;
+            (request, byte_chunks(data).boxed(), Some(length))
+        }
+        RequestBody::Stream(rx) => (request, channel_chunks(rx).boxed(), None),
+        RequestBody::Multipart(parts, buffers) => {
+            let boundary = multipart_boundary();
+            let (chunks, length) = multipart_body(parts, buffers, &boundary)?;
+            let request = request.header(
+                CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", boundary),
+            );
+            (request, chunks, Some(length))
+        }
+    };
+
+    // A streamed body is sent chunked unless its length is given up front
+    if let Some(length) = length {
+        request = request.header(CONTENT_LENGTH, length);
+    }
+    let chunks = match progress {
+        Some(progress) => {
+            progress.upload_started(length);
+            let progress = progress.clone();
+            chunks
+                .inspect_ok(move |chunk| progress.uploaded(chunk.len() as u64))
+                .boxed()
+        }
+        None => chunks,
+    };
+    Ok(request.body(reqwest::Body::wrap_stream(chunks)))
+}
+
+fn byte_chunks(data: Bytes) -> impl futures_util::Stream<Item = BodyChunk> {
+    let chunks: Vec<BodyChunk> = (0..data.len())
+        .step_by(UPLOAD_CHUNK_SIZE)
+        .map(|start| Ok(data.slice(start..(start + UPLOAD_CHUNK_SIZE).min(data.len()))))
+        .collect();
+    stream::iter(chunks)
+}
+
+fn channel_chunks(rx: mpsc::Receiver<BodyChunk>) -> impl futures_util::Stream<Item = BodyChunk> {
+    stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    })
+}
+
+fn file_chunks(file: tokio::fs::File) -> impl futures_util::Stream<Item = BodyChunk> {
+    stream::try_unfold(file, |mut file| async move {
+        let mut buf = vec![0; UPLOAD_CHUNK_SIZE];
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buf.truncate(read);
+        Ok(Some((Bytes::from(buf), file)))
+    })
+}
+
+// ============================================================================
+// Multipart
+// ============================================================================
+
+enum Segment {
+    Data(Bytes),
+    File(PathBuf),
+}
+
+fn multipart_boundary() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!(
+        "forge-boundary-{:x}{:x}",
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Quote a header parameter the way browsers encode form data
+fn escape_param(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Encode `parts` as multipart/form-data, taking `buffer` parts from
+/// `buffers`. Returns the body and its length; file parts are read when the
+/// stream reaches them.
+pub fn multipart_body(
+    parts: Vec<MultipartPart>,
+    mut buffers: HashMap<u64, Bytes>,
+    boundary: &str,
+) -> Result<(BoxStream<'static, BodyChunk>, u64), NetError> {
+    let mut segments = Vec::new();
+    let mut length = 0u64;
+
+    for part in parts {
+        let bytes = part.buffer.and_then(|id| buffers.remove(&id));
+        let (content, content_length, filename) = match (part.value, bytes, part.path) {
+            (Some(value), None, None) => {
+                let length = value.len() as u64;
+                (Segment::Data(Bytes::from(value)), length, part.filename)
+            }
+            (None, Some(bytes), None) => {
+                let length = bytes.len() as u64;
+                let filename = part.filename.unwrap_or_else(|| "blob".to_string());
+                (Segment::Data(bytes), length, Some(filename))
+            }
+            (None, None, Some(path)) => {
+                let metadata = std::fs::metadata(&path)
+                    .map_err(|e| NetError::io(format!("{}: {}", path, e)))?;
+                if !metadata.is_file() {
+                    return Err(NetError::request_build_error(format!(
+                        "Multipart file '{}' is not a file",
+                        path
+                    )));
+                }
+                let path = PathBuf::from(path);
+                let filename = part.filename.or_else(|| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                });
+                (Segment::File(path), metadata.len(), filename)
+            }
+            _ => {
+                return Err(NetError::request_build_error(format!(
+                    "Multipart part '{}' needs exactly one of value, buffer or path",
+                    part.name
+                )));
+            }
+        };
+
+        let mut head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            boundary,
+            escape_param(&part.name)
+        );
+        if let Some(filename) = &filename {
+            head.push_str(&format!("; filename=\"{}\"", escape_param(filename)));
+        }
+        let content_type = match (&part.content_type, &filename) {
+            (Some(content_type), _) => Some(content_type.as_str()),
+            (None, Some(_)) => Some("application/octet-stream"),
+            (None, None) => None,
+        };
+        if let Some(content_type) = content_type {
+            head.push_str(&format!("\r\nContent-Type: {}", content_type));
+        }
+        head.push_str("\r\n\r\n");
+
+        length += head.len() as u64 + content_length + 2;
+        segments.push(Segment::Data(Bytes::from(head)));
+        segments.push(content);
+        segments.push(Segment::Data(Bytes::from_static(b"\r\n")));
+    }
+
+    let tail = format!("--{}--\r\n", boundary);
+    length += tail.len() as u64;
+    segments.push(Segment::Data(Bytes::from(tail)));
+
+    let body = stream::iter(segments)
+        .then(|segment| async move {
+            match segment {
+                Segment::Data(data) => Ok(stream::once(async move { Ok(data) }).boxed()),
+                Segment::File(path) => tokio::fs::File::open(&path)
+                    .await
+                    .map(|file| file_chunks(file).boxed()),
+            }
+        })
+        .try_flatten()
+        .boxed();
+    Ok((body, length))
+}
+
+// ============================================================================
+// Responses and Downloads
+// ============================================================================
+
+/// Read a response body, reporting download progress
+pub async fn read_body(
+    mut response: reqwest::Response,
+    progress: Option<&ProgressReporter>,
+) -> Result<Vec<u8>, NetError> {
+    let Some(progress) = progress else {
+        return Ok(response.bytes().await?.to_vec());
+    };
+
+    progress.download_started(0, response.content_length());
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        progress.downloaded(chunk.len() as u64);
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// A parsed `Content-Range: bytes start-end/total` header
+#[derive(Debug, PartialEq)]
+struct ContentRange {
+    range: Option<(u64, u64)>,
+    total: Option<u64>,
+}
+
+fn parse_content_range(value: &str) -> Option<ContentRange> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    let range = match range {
+        "*" => None,
+        range => {
+            let (start, end) = range.split_once('-')?;
+            Some((start.parse().ok()?, end.parse().ok()?))
+        }
+    };
+    Some(ContentRange { range, total })
+}
+
+fn header_string(
+    response: &reqwest::Response,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+/// Send `request` and write the response body to `path`. With `resume`, an
+/// existing file is continued from its length when the server honors the
+/// range; otherwise the file is replaced.
+pub async fn download_to_file(
+    request: reqwest::RequestBuilder,
+    path: &Path,
+    opts: &DownloadOpts,
+    progress: Option<&ProgressReporter>,
+) -> Result<DownloadResult, NetError> {
+    let offset = if opts.resume.unwrap_or(false) {
+        match tokio::fs::metadata(path).await {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => 0,
+        }
+    } else {
+        0
+    };
+
+    let mut request = request;
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={}-", offset));
+        if let Some(validator) = &opts.if_range {
+            request = request.header(IF_RANGE, validator);
+        }
+    }
+
+    let mut response = request.send().await?;
+    let status = response.status();
+    let url = response.url().to_string();
+    let content_range = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range);
+    let result = |size, received, resumed| DownloadResult {
+        path: path.to_string_lossy().to_string(),
+        status: status.as_u16(),
+        url: url.clone(),
+        size,
+        received,
+        resumed,
+        etag: header_string(&response, ETAG),
+        last_modified: header_string(&response, LAST_MODIFIED),
+    };
+
+    // Asking for bytes past the end of a complete file
+    if status == StatusCode::RANGE_NOT_SATISFIABLE
+        && offset > 0
+        && content_range.as_ref().and_then(|r| r.total) == Some(offset)
+    {
+        if let Some(progress) = progress {
+            progress.download_started(offset, Some(offset));
+        }
+        return Ok(result(offset, 0, true));
+    }
+    if !status.is_success() {
+        return Err(NetError::http_error(format!("{} from {}", status, url)));
+    }
+
+    let resumed = status == StatusCode::PARTIAL_CONTENT;
+    let total = if resumed {
+        match &content_range {
+            Some(ContentRange {
+                range: Some((start, _)),
+                total,
+            }) if offset > 0 && *start == offset => {
+                total.or_else(|| response.content_length().map(|len| offset + len))
+            }
+            _ => {
+                return Err(NetError::http_error(format!(
+                    "Unexpected range in response from {}",
+                    url
+                )));
+            }
+        }
+    } else {
+        response.content_length()
+    };
+    let result = result(0, 0, resumed);
+
+    let mut file = if resumed {
+        tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .await?
+    } else {
+        tokio::fs::File::create(path).await?
+    };
+    let start = if resumed { offset } else { 0 };
+    if let Some(progress) = progress {
+        progress.download_started(start, total);
+    }
+
+    let mut received = 0u64;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+        received += chunk.len() as u64;
+        if let Some(progress) = progress {
+            progress.downloaded(chunk.len() as u64);
+        }
+    }
+    file.flush().await?;
+
+    Ok(DownloadResult {
+        size: start + received,
+        received,
+        ..result
+    })
+}
+
+// ============================================================================
+// State
+// ============================================================================
+
+struct BodyStreamEntry {
+    tx: Option<mpsc::Sender<BodyChunk>>,
+    rx: Option<mpsc::Receiver<BodyChunk>>,
+}
+
+struct ProgressEntry {
+    reporter: Option<ProgressReporter>,
+    rx: Arc<Mutex<watch::Receiver<FetchProgress>>>,
+    /// Read without marking updates seen, for the final value on close
+    latest: watch::Receiver<FetchProgress>,
+    /// Dropped with the entry, waking a pending `next_progress`
+    open: watch::Sender<()>,
+}
+
+/// Body streams and progress channels
+#[derive(Clone)]
+pub struct TransferState {
+    bodies: Arc<Mutex<HashMap<u64, BodyStreamEntry>>>,
+    /// Binary bodies and multipart contents staged for a fetch
+    staged: Arc<Mutex<HashMap<u64, Bytes>>>,
+    progress: Arc<Mutex<HashMap<u64, ProgressEntry>>>,
+    next_id: Arc<Mutex<u64>>,
+}
+
+impl Default for TransferState {
+    fn default() -> Self {
+        Self {
+            bodies: Arc::new(Mutex::new(HashMap::new())),
+            staged: Arc::new(Mutex::new(HashMap::new())),
+            progress: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+impl TransferState {
+    async fn next_id(&self) -> u64 {
+        let mut next_id = self.next_id.lock().await;
+        let id = *next_id;
+        *next_id += 1;
+        id
+    }
+
+    /// Open a body stream; a fetch takes the reading side by id.
+    pub async fn open_body(&self) -> u64 {
+        let (tx, rx) = mpsc::channel(BODY_STREAM_BUFFER);
+        let id = self.next_id().await;
+        self.bodies.lock().await.insert(
+            id,
+            BodyStreamEntry {
+                tx: Some(tx),
+                rx: Some(rx),
+            },
+        );
+        id
+    }
+
+    /// Take the reading side of a body stream for a request.
+    pub async fn take_body(&self, id: u64) -> Result<mpsc::Receiver<BodyChunk>, NetError> {
+        let mut bodies = self.bodies.lock().await;
+        let entry = bodies.get_mut(&id);
+        let rx = entry
+            .and_then(|entry| entry.rx.take())
+            .ok_or_else(|| NetError::stream_error(format!("Body stream {} not found", id)))?;
+        if bodies.get(&id).is_some_and(|entry| entry.tx.is_none()) {
+            bodies.remove(&id);
+        }
+        Ok(rx)
+    }
+
+    /// Stage bytes for a fetch; the fetch takes them by id.
+    pub async fn stage_bytes(&self, data: Bytes) -> u64 {
+        let id = self.next_id().await;
+        self.staged.lock().await.insert(id, data);
+        id
+    }
+
+    /// Take staged bytes for a request.
+    pub async fn take_bytes(&self, id: u64) -> Result<Bytes, NetError> {
+        self.staged
+            .lock()
+            .await
+            .remove(&id)
+            .ok_or_else(|| NetError::stream_error(format!("Staged body {} not found", id)))
+    }
+
+    /// Queue a chunk, waiting while the buffer is full.
+    pub async fn write_body(&self, id: u64, data: Bytes) -> Result<(), NetError> {
+        let tx = self
+            .bodies
+            .lock()
+            .await
+            .get(&id)
+            .and_then(|entry| entry.tx.clone())
+            .ok_or_else(|| NetError::stream_error(format!("Body stream {} not found", id)))?;
+        tx.send(Ok(data))
+            .await
+            .map_err(|_| NetError::stream_error("The request is no longer reading its body"))
+    }
+
+    /// End a body stream; with `error` the request fails instead of sending
+    /// a truncated body. Staged bytes no request has taken are dropped.
+    pub async fn close_body(&self, id: u64, error: Option<String>) {
+        self.staged.lock().await.remove(&id);
+        let tx = {
+            let mut bodies = self.bodies.lock().await;
+            let Some(entry) = bodies.get_mut(&id) else {
+                return;
+            };
+            let tx = entry.tx.take();
+            // Aborting also drops a reader no request has taken, so writes
+            // still waiting on it fail
+            if error.is_some() || entry.rx.is_none() {
+                bodies.remove(&id);
+            }
+            tx
+        };
+        if let (Some(tx), Some(error)) = (tx, error) {
+            let _ = tx.send(Err(std::io::Error::other(error))).await;
+        }
+    }
+
+    /// Open a progress channel; a fetch takes the reporter by id.
+    pub async fn open_progress(&self) -> u64 {
+        let (tx, rx) = watch::channel(FetchProgress::default());
+        let id = self.next_id().await;
+        self.progress.lock().await.insert(
+            id,
+            ProgressEntry {
+                reporter: Some(ProgressReporter(Arc::new(tx))),
+                latest: rx.clone(),
+                rx: Arc::new(Mutex::new(rx)),
+                open: watch::channel(()).0,
+            },
+        );
+        id
+    }
+
+    pub async fn take_reporter(&self, id: u64) -> Result<ProgressReporter, NetError> {
+        self.progress
+            .lock()
+            .await
+            .get_mut(&id)
+            .and_then(|entry| entry.reporter.take())
+            .ok_or_else(|| NetError::stream_error(format!("Progress channel {} not found", id)))
+    }
+
+    /// Wait for the next update; `None` once the request has finished or the
+    /// channel was closed.
+    pub async fn next_progress(&self, id: u64) -> Option<FetchProgress> {
+        let (rx, mut open) = {
+            let progress = self.progress.lock().await;
+            let entry = progress.get(&id)?;
+            (entry.rx.clone(), entry.open.subscribe())
+        };
+        let mut rx = rx.lock().await;
+        // A body still held by the connection can outlive the request, so
+        // closing the channel also ends the wait
+        tokio::select! {
+            biased;
+            changed = rx.changed() => changed.ok().map(|()| rx.borrow_and_update().clone()),
+            _ = open.changed() => None,
+        }
+    }
+
+    /// Close a progress channel and return its last value.
+    pub async fn close_progress(&self, id: u64) -> Option<FetchProgress> {
+        let entry = self.progress.lock().await.remove(&id)?;
+        let latest = entry.latest.borrow().clone();
+        Some(latest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/1000"),
+            Some(ContentRange {
+                range: Some((100, 199)),
+                total: Some(1000)
+            })
+        );
+        assert_eq!(
+            parse_content_range("bytes */42"),
+            Some(ContentRange {
+                range: None,
+                total: Some(42)
+            })
+        );
+        assert_eq!(parse_content_range("bytes 0-9/*").unwrap().total, None);
+        assert!(parse_content_range("items 0-9/10").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_multipart_body() {
+        let path =
+            std::env::temp_dir().join(format!("forge-net-upload-{}.txt", std::process::id()));
+        std::fs::write(&path, b"file contents").unwrap();
+
+        let parts = vec![
+            MultipartPart {
+                name: "title".to_string(),
+                value: Some("hello".to_string()),
+                ..Default::default()
+            },
+            MultipartPart {
+                name: "raw".to_string(),
+                buffer: Some(7),
+                content_type: Some("image/png".to_string()),
+                ..Default::default()
+            },
+            MultipartPart {
+                name: "doc\"".to_string(),
+                path: Some(path.to_string_lossy().to_string()),
+                ..Default::default()
+            },
+        ];
+        let buffers = HashMap::from([(7, Bytes::from_static(&[1, 2, 3]))]);
+        let (body, length) = multipart_body(parts, buffers, "XYZ").unwrap();
+        let chunks: Vec<Bytes> = body.try_collect().await.unwrap();
+        let body = chunks.concat();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(body.len() as u64, length);
+        let text = String::from_utf8_lossy(&body);
+        assert!(text.starts_with(
+            "--XYZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nhello\r\n"
+        ));
+        assert!(text.contains(
+            "name=\"raw\"; filename=\"blob\"\r\nContent-Type: image/png\r\n\r\n\u{1}\u{2}\u{3}\r\n"
+        ));
+        assert!(text.contains("name=\"doc%22\"; filename=\"forge-net-upload-"));
+        assert!(text.contains("Content-Type: application/octet-stream\r\n\r\nfile contents\r\n"));
+        assert!(text.ends_with("--XYZ--\r\n"));
+
+        let invalid = vec![MultipartPart {
+            name: "empty".to_string(),
+            ..Default::default()
+        }];
+        assert!(multipart_body(invalid, HashMap::new(), "XYZ").is_err());
+
+        // A buffer that was never staged
+        let missing = vec![MultipartPart {
+            name: "raw".to_string(),
+            buffer: Some(8),
+            ..Default::default()
+        }];
+        assert!(multipart_body(missing, HashMap::new(), "XYZ").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_body_stream_and_progress() {
+        let state = TransferState::default();
+
+        let id = state.open_body().await;
+        let chunk = Bytes::from_static(b"ab");
+        state.write_body(id, chunk.clone()).await.unwrap();
+        state.close_body(id, None).await;
+        let mut rx = state.take_body(id).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap(), Bytes::from_static(b"ab"));
+        assert!(rx.recv().await.is_none());
+        assert!(state.take_body(id).await.is_err());
+
+        // Staged bytes are taken once; closing drops bytes nothing took
+        let id = state.stage_bytes(chunk.clone()).await;
+        assert_eq!(state.take_bytes(id).await.unwrap(), chunk);
+        assert!(state.take_bytes(id).await.is_err());
+        let id = state.stage_bytes(chunk.clone()).await;
+        state.close_body(id, None).await;
+        assert!(state.take_bytes(id).await.is_err());
+
+        let id = state.open_progress().await;
+        let reporter = state.take_reporter(id).await.unwrap();
+        reporter.upload_started(Some(10));
+        reporter.uploaded(4);
+        let progress = state.next_progress(id).await.unwrap();
+        assert_eq!(progress.uploaded, 4);
+        assert_eq!(progress.upload_total, Some(10));
+        reporter.downloaded(7);
+        drop(reporter);
+        assert_eq!(state.next_progress(id).await.unwrap().downloaded, 7);
+        assert!(state.next_progress(id).await.is_none());
+        assert_eq!(state.close_progress(id).await.unwrap().uploaded, 4);
+        assert!(state.next_progress(id).await.is_none());
+
+        // Closing wakes a wait even while the reporter is still alive
+        let id = state.open_progress().await;
+        let _reporter = state.take_reporter(id).await.unwrap();
+        let waiting = {
+            let state = state.clone();
+            tokio::spawn(async move { state.next_progress(id).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        state.close_progress(id).await;
+        assert!(waiting.await.unwrap().is_none());
+
+        // Aborting a stream no request took fails writes waiting on it
+        let id = state.open_body().await;
+        for _ in 0..BODY_STREAM_BUFFER {
+            state.write_body(id, chunk.clone()).await.unwrap();
+        }
+        let blocked = {
+            let state = state.clone();
+            tokio::spawn(async move { state.write_body(id, Bytes::new()).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        state.close_body(id, Some("cancelled".to_string())).await;
+        assert!(blocked.await.unwrap().is_err());
+        assert!(state.take_body(id).await.is_err());
+    }
+
+    /// Serve "0123456789", honoring `Range: bytes=N-`
+    async fn range_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = socket.read(&mut buf).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let start = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("range: bytes="))
+                    .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+                let data = "0123456789";
+                let response = match start {
+                    Some(start) if start >= data.len() => format!(
+                        "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\n\r\n",
+                        data.len()
+                    ),
+                    Some(start) => format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n{}",
+                        start,
+                        data.len() - 1,
+                        data.len(),
+                        data.len() - start,
+                        &data[start..]
+                    ),
+                    None => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n{}",
+                        data.len(),
+                        data
+                    ),
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_download_resume() {
+        let port = range_server().await;
+        let url = format!("http://127.0.0.1:{}/file", port);
+        let path =
+            std::env::temp_dir().join(format!("forge-net-download-{}.bin", std::process::id()));
+        std::fs::write(&path, b"01234").unwrap();
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        let state = TransferState::default();
+        let id = state.open_progress().await;
+        let reporter = state.take_reporter(id).await.unwrap();
+        let resume = DownloadOpts {
+            resume: Some(true),
+            if_range: None,
+        };
+        let result = download_to_file(client.get(&url), &path, &resume, Some(&reporter))
+            .await
+            .unwrap();
+        assert!(result.resumed);
+        assert_eq!(result.status, 206);
+        assert_eq!((result.size, result.received), (10, 5));
+        assert_eq!(result.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+        let progress = state.next_progress(id).await.unwrap();
+        assert_eq!(
+            (progress.downloaded, progress.download_total),
+            (10, Some(10))
+        );
+
+        // Already complete: nothing is fetched
+        let result = download_to_file(client.get(&url), &path, &resume, None)
+            .await
+            .unwrap();
+        assert_eq!((result.size, result.received), (10, 0));
+
+        // Without resume the file is replaced
+        std::fs::write(&path, b"stale data that is longer").unwrap();
+        let result = download_to_file(client.get(&url), &path, &DownloadOpts::default(), None)
+            .await
+            .unwrap();
+        assert!(!result.resumed);
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -11,6 +11,18 @@ fn main() {
             "op_process_write_stdin",
             "op_process_read_stdout",
             "op_process_read_stderr",
+            "op_process_write_stdin_bytes",
+            "op_process_close_stdin",
+            "op_process_read_stdout_bytes",
+            "op_process_read_stderr_bytes",
+            "op_process_wait_with_output",
+            "op_process_spawn_pty",
+            "op_process_pty_read",
+            "op_process_pty_write",
+            "op_process_pty_resize",
+            "op_process_pty_wait",
+            "op_process_pty_kill",
+            "op_process_pty_close",
         ])
         .generate_sdk_module("sdk")
         .use_inventory_types()
@@ -12,15 +12,25 @@
 //! - **I/O Management**: Pipe, inherit, or discard stdin/stdout/stderr streams
 //! - **Bidirectional Communication**: Write to stdin and read from stdout/stderr
 //! - **Async Streams**: Async iterators for line-by-line output consumption
+//! - **Binary I/O**: Raw byte-chunk reads of stdout/stderr and binary stdin writes
+//! - **Output Capture**: Collect both streams while waiting, with size caps
+//! - **Native Pipes**: Feed one process's stdout into another's stdin via an OS pipe
+//! - **Pseudo-Terminals**: Run interactive programs on a PTY with resize and
+//!   raw byte I/O (see the `pty` module)
 //! - **Lifecycle Management**: Kill, wait, and check process status
+//! - **Process Trees**: Optional process group or session per child, so `kill`
+//!   and timeouts reach grandchildren; all children are killed when the app
+//!   exits (see the `tree` module)
+//! - **Timeouts**: Per-process wall-clock limit, SIGTERM then SIGKILL
 //! - **Cross-Platform Signals**: Unix signal support with graceful fallback on Windows
 //! - **Capability-Based Security**: Permission checking via `ProcessCapabilityChecker`
-//! - **Resource Limits**: Configurable maximum concurrent processes
+//! - **Resource Limits**: Configurable maximum concurrent processes, plus
+//!   per-process CPU time, address space and open file limits on Linux
 //! - **Structured Errors**: Machine-readable error codes (4000-4009 range)
 //!
 //! ## API Overview
 //!
-//! The extension exposes 7 operations grouped into functional categories:
+//! The extension exposes 19 operations grouped into functional categories:
 //!
 //! ### Process Lifecycle
 //! - `op_process_spawn` - Create new child process with options
@@ -32,6 +42,20 @@
 //! - `op_process_write_stdin` - Write data to process's standard input
 //! - `op_process_read_stdout` - Read line from standard output
 //! - `op_process_read_stderr` - Read line from standard error
+//! - `op_process_write_stdin_bytes` - Write raw bytes to standard input
+//! - `op_process_close_stdin` - Close standard input (end of input)
+//! - `op_process_read_stdout_bytes` - Read next raw chunk from standard output
+//! - `op_process_read_stderr_bytes` - Read next raw chunk from standard error
+//! - `op_process_wait_with_output` - Wait for exit, collecting both streams up to size caps
+//!
+//! ### Pseudo-Terminals
+//! - `op_process_spawn_pty` - Spawn a process attached to a new PTY
+//! - `op_process_pty_read` - Read the next chunk of raw terminal output
+//! - `op_process_pty_write` - Write raw bytes to the terminal
+//! - `op_process_pty_resize` - Change the terminal size
+//! - `op_process_pty_wait` - Wait for exit and return code/signal
+//! - `op_process_pty_kill` - Send a signal to the PTY process
+//! - `op_process_pty_close` - Close the PTY and release its process slot
 //!
 //! ## TypeScript Usage
 //!
@@ -151,22 +175,34 @@
 //! - Full signal support: SIGTERM, SIGKILL, SIGINT, SIGHUP, SIGUSR1, SIGUSR2
 //! - Exit status includes signal information
 //! - Child processes inherit file descriptors (use appropriate stdio config)
+//! - `processGroup` uses `setpgid`, `newSession` uses `setsid`; group kills
+//!   use `killpg`
+//! - `limits` uses `setrlimit` and is only available on Linux
 //!
 //! ### Windows
 //! - Limited signal support (kill uses TerminateProcess API)
 //! - Signal parameter ignored in `kill()` operations
 //! - Exit codes use Windows process exit conventions
+//! - `processGroup`/`newSession` create a new process group; group kills use
+//!   `taskkill /T /F`, which terminates the whole tree
 //!
 //! ## Implementation Details
 //!
 //! ### State Management
 //! The extension maintains a `ProcessState` structure in Deno's `OpState`:
-//! - **Process Registry**: HashMap of active processes by handle ID
+//! - **Process Registry**: HashMaps of active processes and PTYs by handle ID
 //! - **Resource Limits**: Configurable `max_processes` (default: 10)
 //! - **Auto-cleanup**: Processes removed after kill/wait operations
+//! - **Exit cleanup**: A global `ProcessTreeTracker` records every live child
+//!   and kills them from an `atexit` hook, since the app exits through
+//!   `std::process::exit` and never drops `OpState`; the runtime also kills
+//!   them on termination signals and event loop shutdown
 //!
 //! ### I/O Streaming
-//! - Uses Tokio's `BufReader` for line-buffered reads
+//! - Uses Tokio's `BufReader` (64 KiB) for both line reads and raw chunk reads,
+//!   so the two can be mixed on one stream without losing data
+//! - `stdinFrom` hands the source's stdout pipe to the new child, so piped data
+//!   never passes through the runtime
 //! - Stdout/stderr wrapped in `Arc<Mutex<>>` for concurrent access
 //! - Stdin uses `AsyncWriteExt` with explicit flushing
 //!
@@ -186,15 +222,22 @@
 //! - Astro docs: [ext_process guide](../../../site/src/content/docs/crates/ext-process.md)
 //! - Tests: [`tests`](#tests) module below
 
-use deno_core::{op2, Extension, OpState};
+mod pty;
+mod tree;
+
+pub use pty::{PtyExit, PtyHandle, PtyOpts, DEFAULT_PTY_COLS, DEFAULT_PTY_ROWS};
+pub use tree::{ProcessTree, ProcessTreeTracker, ResourceLimits, TimeoutGuard, TIMEOUT_KILL_GRACE};
+
+use deno_core::{op2, Extension, JsBuffer, OpState, ToJsBuffer};
 use forge_weld_macro::{weld_op, weld_struct};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::io::Write as _;
 use std::process::Stdio;
 use std::rc::Rc;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
 use tokio::sync::Mutex;
 use tracing::debug;
@@ -369,6 +412,55 @@ pub struct SpawnOpts {
     pub stdout: Option<String>, // "piped", "inherit", "null"
     pub stderr: Option<String>,
     pub stdin: Option<String>,
+    /// Handle of a spawned process whose stdout becomes this process's stdin
+    #[serde(rename = "stdinFrom")]
+    pub stdin_from: Option<String>,
+    /// Start the process in a new process group, so kill and timeout reach its children
+    #[serde(rename = "processGroup")]
+    pub process_group: Option<bool>,
+    /// Start the process in a new session (Unix; implies a new process group)
+    #[serde(rename = "newSession")]
+    pub new_session: Option<bool>,
+    /// Terminate the process (and its group) after this many milliseconds
+    #[serde(rename = "timeoutMs")]
+    pub timeout_ms: Option<u64>,
+    /// Resource limits applied at spawn (Linux only)
+    pub limits: Option<ResourceLimits>,
+}
+
+/// Buffer size of piped stdout/stderr, and the largest chunk a byte read returns
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Output kept per stream by `op_process_wait_with_output` unless capped otherwise
+pub const DEFAULT_OUTPUT_CAP: usize = 16 * 1024 * 1024;
+
+/// Size caps for `op_process_wait_with_output`
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputOpts {
+    /// Most stdout bytes kept (default: 16 MiB); the rest is read and discarded
+    pub max_stdout: Option<usize>,
+    /// Most stderr bytes kept (default: 16 MiB); the rest is read and discarded
+    pub max_stderr: Option<usize>,
+}
+
+/// Exit status and collected output of a process
+#[weld_struct]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedOutput {
+    pub success: bool,
+    pub code: Option<i32>,
+    pub signal: Option<String>,
+    pub stdout: ToJsBuffer,
+    pub stderr: ToJsBuffer,
+    /// More stdout was produced than `max_stdout`
+    pub stdout_truncated: bool,
+    /// More stderr was produced than `max_stderr`
+    pub stderr_truncated: bool,
+    /// The process was terminated by its `timeout_ms`
+    pub timed_out: bool,
 }
 
 /// Result of spawning a process
@@ -386,6 +478,7 @@ pub struct ProcessStatus {
     pub running: bool,
     pub exit_code: Option<i32>,
     pub signal: Option<String>,
+    pub timed_out: bool,
 }
 
 /// Output from reading stdout/stderr
@@ -410,11 +503,36 @@ pub struct ProcessHandle {
     pub stdin: Option<Arc<Mutex<ChildStdin>>>,
     pub exited: bool,
     pub exit_code: Option<i32>,
+    /// The process, plus its group when spawned with `processGroup`/`newSession`
+    pub tree: ProcessTree,
+    pub timeout: Option<TimeoutGuard>,
+}
+
+impl ProcessHandle {
+    /// Record that the process has been reaped
+    pub fn mark_exited(&mut self, exit_code: Option<i32>) {
+        self.exited = true;
+        self.exit_code = exit_code;
+        // A reaped PID can be reused; a group stays tracked (and its timeout
+        // armed) until killed, since grandchildren may still be running
+        if !self.tree.group {
+            if let Some(timeout) = &self.timeout {
+                timeout.cancel();
+            }
+            ProcessTreeTracker::global().untrack(self.pid);
+        }
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.timeout.as_ref().is_some_and(TimeoutGuard::timed_out)
+    }
 }
 
 /// State for tracking spawned processes
 pub struct ProcessState {
     pub processes: HashMap<String, ProcessHandle>,
+    /// Processes running on a PTY; they count towards `max_processes` too
+    pub ptys: HashMap<String, PtyHandle>,
     pub next_id: u64,
     pub max_processes: usize,
 }
@@ -423,13 +541,92 @@ impl ProcessState {
     pub fn new(max_processes: usize) -> Self {
         Self {
             processes: HashMap::new(),
+            ptys: HashMap::new(),
             next_id: 1,
             max_processes,
         }
     }
 
     pub fn can_spawn(&self) -> bool {
-        self.processes.len() < self.max_processes
+        self.processes.len() + self.ptys.len() < self.max_processes
+    }
+
+    /// Detach the stdout pipe of `handle` so it can become another child's stdin
+    ///
+    /// If that child fails to spawn, hand the pipe back with
+    /// [`restore_stdout_pipe`](Self::restore_stdout_pipe).
+    pub fn take_stdout_pipe(&mut self, handle: &str) -> Result<StdoutPipe, ProcessError> {
+        let process = self
+            .processes
+            .get_mut(handle)
+            .ok_or_else(|| ProcessError::invalid_handle(handle))?;
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ProcessError::output_not_captured(format!("stdout of {} not captured", handle))
+        })?;
+        let reader = match Arc::try_unwrap(stdout) {
+            Ok(reader) => reader.into_inner(),
+            Err(stdout) => {
+                process.stdout = Some(stdout);
+                return Err(ProcessError::io(format!(
+                    "stdout of {} is being read",
+                    handle
+                )));
+            }
+        };
+        // Bytes already pulled into the buffer would be lost by the pipe
+        if !reader.buffer().is_empty() {
+            process.stdout = Some(Arc::new(Mutex::new(reader)));
+            return Err(ProcessError::io(format!(
+                "stdout of {} has already been read from",
+                handle
+            )));
+        }
+        StdoutPipe::new(reader.into_inner())
+    }
+
+    /// Give a pipe from [`take_stdout_pipe`](Self::take_stdout_pipe) back to its process
+    pub fn restore_stdout_pipe(&mut self, handle: &str, pipe: StdoutPipe) {
+        let Some(process) = self.processes.get_mut(handle) else {
+            return;
+        };
+        if let Ok(stdout) = pipe.into_stdout() {
+            process.stdout = Some(Arc::new(Mutex::new(BufReader::with_capacity(
+                STREAM_CHUNK_SIZE,
+                stdout,
+            ))));
+        }
+    }
+}
+
+/// A process's stdout pipe, detached to become another process's stdin
+///
+/// The command being spawned gets a duplicate and the original is kept, so
+/// the pipe can go back to its process if the spawn fails.
+pub struct StdoutPipe {
+    #[cfg(unix)]
+    pipe: std::os::fd::OwnedFd,
+    #[cfg(windows)]
+    pipe: std::os::windows::io::OwnedHandle,
+}
+
+impl StdoutPipe {
+    fn new(stdout: ChildStdout) -> Result<Self, ProcessError> {
+        #[cfg(unix)]
+        let pipe = stdout.into_owned_fd()?;
+        #[cfg(windows)]
+        let pipe = stdout.into_owned_handle()?;
+        Ok(Self { pipe })
+    }
+
+    /// A copy of the pipe to use as a command's stdin
+    pub fn stdio(&self) -> Result<Stdio, ProcessError> {
+        Ok(Stdio::from(self.pipe.try_clone()?))
+    }
+
+    fn into_stdout(self) -> Result<ChildStdout, ProcessError> {
+        Ok(ChildStdout::from_std(std::process::ChildStdout::from(
+            self.pipe,
+        ))?)
     }
 }
 
@@ -498,6 +695,101 @@ fn check_env(state: &OpState, key: &str) -> Result<(), ProcessError> {
     }
 }
 
+fn check_can_spawn(state: &OpState) -> Result<(), ProcessError> {
+    match state.try_borrow::<ProcessState>() {
+        Some(ps) if !ps.can_spawn() => Err(ProcessError::too_many_processes(format!(
+            "Maximum of {} concurrent processes reached",
+            ps.max_processes
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn get_process<'a>(state: &'a OpState, handle: &str) -> Result<&'a ProcessHandle, ProcessError> {
+    state
+        .try_borrow::<ProcessState>()
+        .and_then(|ps| ps.processes.get(handle))
+        .ok_or_else(|| ProcessError::invalid_handle(handle))
+}
+
+fn get_pty<'a>(state: &'a OpState, handle: &str) -> Result<&'a PtyHandle, ProcessError> {
+    state
+        .try_borrow::<ProcessState>()
+        .and_then(|ps| ps.ptys.get(handle))
+        .ok_or_else(|| ProcessError::invalid_handle(handle))
+}
+
+#[cfg(unix)]
+fn parse_signal(signal: Option<&str>) -> Result<nix::sys::signal::Signal, ProcessError> {
+    use nix::sys::signal::Signal;
+
+    match signal {
+        Some("SIGTERM") | None => Ok(Signal::SIGTERM),
+        Some("SIGKILL") => Ok(Signal::SIGKILL),
+        Some("SIGINT") => Ok(Signal::SIGINT),
+        Some("SIGHUP") => Ok(Signal::SIGHUP),
+        Some("SIGUSR1") => Ok(Signal::SIGUSR1),
+        Some("SIGUSR2") => Ok(Signal::SIGUSR2),
+        Some(s) => Err(ProcessError::io(format!("Unknown signal: {}", s))),
+    }
+}
+
+fn exit_signal(status: &std::process::ExitStatus) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal().map(|s| format!("signal {}", s))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
+/// Take whatever is buffered, reading more first if nothing is
+///
+/// Returns an empty chunk at end of stream. Chunks follow the read
+/// boundaries of the pipe, so binary data and unterminated lines come
+/// through as soon as they are written.
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut BufReader<R>) -> std::io::Result<Vec<u8>> {
+    let chunk = reader.fill_buf().await?.to_vec();
+    reader.consume(chunk.len());
+    Ok(chunk)
+}
+
+/// Read a stream to its end, keeping at most `cap` bytes
+///
+/// Reading continues past the cap so the child never blocks on a full pipe.
+async fn read_capped<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    cap: usize,
+) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    loop {
+        let buf = reader.fill_buf().await?;
+        if buf.is_empty() {
+            return Ok((kept, truncated));
+        }
+        let len = buf.len();
+        let take = len.min(cap - kept.len());
+        kept.extend_from_slice(&buf[..take]);
+        truncated |= take < len;
+        reader.consume(len);
+    }
+}
+
+async fn read_all_capped<R: AsyncRead + Unpin>(
+    reader: Option<Arc<Mutex<BufReader<R>>>>,
+    cap: usize,
+) -> Result<(Vec<u8>, bool), ProcessError> {
+    match reader {
+        Some(reader) => Ok(read_capped(&mut *reader.lock().await, cap).await?),
+        None => Ok((Vec::new(), false)),
+    }
+}
+
 fn parse_stdio(s: Option<&String>) -> Stdio {
     match s.map(|s| s.as_str()) {
         Some("piped") => Stdio::piped(),
@@ -536,14 +828,7 @@ async fn op_process_spawn(
         }
 
         // Check if we can spawn more processes
-        if let Some(ps) = s.try_borrow::<ProcessState>() {
-            if !ps.can_spawn() {
-                return Err(ProcessError::too_many_processes(format!(
-                    "Maximum of {} concurrent processes reached",
-                    ps.max_processes
-                )));
-            }
-        }
+        check_can_spawn(&s)?;
     }
 
     debug!(binary = %binary, args = ?opts.args, "process.spawn");
@@ -577,10 +862,50 @@ async fn op_process_spawn(
     cmd.stderr(parse_stdio(opts.stderr.as_ref()));
     cmd.stdin(parse_stdio(opts.stdin.as_ref()));
 
+    let group = opts.process_group.unwrap_or(false) || opts.new_session.unwrap_or(false);
+    tree::configure_command(
+        &mut cmd,
+        opts.process_group.unwrap_or(false),
+        opts.new_session.unwrap_or(false),
+        opts.limits.as_ref(),
+    )?;
+
+    // Connect another process's stdout directly, without copying through the runtime
+    let stdin_source = match &opts.stdin_from {
+        Some(source) => {
+            let mut s = state.borrow_mut();
+            let ps = s
+                .try_borrow_mut::<ProcessState>()
+                .ok_or_else(|| ProcessError::invalid_handle(source))?;
+            let pipe = ps.take_stdout_pipe(source)?;
+            match pipe.stdio() {
+                Ok(stdin) => cmd.stdin(stdin),
+                Err(e) => {
+                    ps.restore_stdout_pipe(source, pipe);
+                    return Err(e);
+                }
+            };
+            Some((source, pipe))
+        }
+        None => None,
+    };
+    let stdin_piped = stdin_piped && stdin_source.is_none();
+
     // Spawn the process
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| ProcessError::failed_to_spawn(e.to_string()))?;
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            // The source keeps its output for another reader
+            if let Some((source, pipe)) = stdin_source {
+                if let Some(ps) = state.borrow_mut().try_borrow_mut::<ProcessState>() {
+                    ps.restore_stdout_pipe(source, pipe);
+                }
+            }
+            return Err(ProcessError::failed_to_spawn(e.to_string()));
+        }
+    };
+    // Only the child holds the pipe now
+    drop(stdin_source);
 
     // Get PID
     let pid = child
@@ -592,7 +917,7 @@ async fn op_process_spawn(
         child
             .stdout
             .take()
-            .map(|s| Arc::new(Mutex::new(BufReader::new(s))))
+            .map(|s| Arc::new(Mutex::new(BufReader::with_capacity(STREAM_CHUNK_SIZE, s))))
     } else {
         None
     };
@@ -601,7 +926,7 @@ async fn op_process_spawn(
         child
             .stderr
             .take()
-            .map(|s| Arc::new(Mutex::new(BufReader::new(s))))
+            .map(|s| Arc::new(Mutex::new(BufReader::with_capacity(STREAM_CHUNK_SIZE, s))))
     } else {
         None
     };
@@ -612,6 +937,12 @@ async fn op_process_spawn(
         None
     };
 
+    let tree = ProcessTree { pid, group };
+    ProcessTreeTracker::global().track(tree);
+    let timeout = opts
+        .timeout_ms
+        .map(|ms| TimeoutGuard::start(tree, std::time::Duration::from_millis(ms)));
+
     // Generate handle ID and store process
     let handle_id = {
         let mut s = state.borrow_mut();
@@ -631,6 +962,8 @@ async fn op_process_spawn(
                         stdin,
                         exited: false,
                         exit_code: None,
+                        tree,
+                        timeout,
                     },
                 );
                 id
@@ -651,6 +984,8 @@ async fn op_process_spawn(
                         stdin,
                         exited: false,
                         exit_code: None,
+                        tree,
+                        timeout,
                     },
                 );
                 s.put(ps);
@@ -675,44 +1010,31 @@ async fn op_process_kill(
     debug!(handle = %handle, signal = ?signal, "process.kill");
 
     // Get the child process
-    let child_arc = {
+    let (child_arc, tree) = {
         let s = state.borrow();
-        let ps = s
-            .try_borrow::<ProcessState>()
-            .ok_or_else(|| ProcessError::invalid_handle(&handle))?;
-        let process = ps
-            .processes
-            .get(&handle)
-            .ok_or_else(|| ProcessError::invalid_handle(&handle))?;
-        Arc::clone(&process.child)
+        let process = get_process(&s, &handle)?;
+        (Arc::clone(&process.child), process.tree)
     };
 
     #[cfg(unix)]
     {
-        use nix::sys::signal::{kill, Signal};
-        use nix::unistd::Pid;
-
+        // A group is signalled even after its leader has been reaped, so
+        // grandchildren left behind are still reached
         let child = child_arc.lock().await;
-        if let Some(pid) = child.id() {
-            let sig = match signal.as_deref() {
-                Some("SIGTERM") | None => Signal::SIGTERM,
-                Some("SIGKILL") => Signal::SIGKILL,
-                Some("SIGINT") => Signal::SIGINT,
-                Some("SIGHUP") => Signal::SIGHUP,
-                Some("SIGUSR1") => Signal::SIGUSR1,
-                Some("SIGUSR2") => Signal::SIGUSR2,
-                Some(s) => return Err(ProcessError::io(format!("Unknown signal: {}", s))),
-            };
-
-            kill(Pid::from_raw(pid as i32), sig).map_err(|e| ProcessError::io(e.to_string()))?;
+        if child.id().is_some() || tree.group {
+            tree.signal(parse_signal(signal.as_deref())?)?;
         }
     }
 
     #[cfg(not(unix))]
     {
         let _ = signal; // Ignore signal on non-Unix
-        let mut child = child_arc.lock().await;
-        child.kill().await.map_err(ProcessError::from)?;
+        if tree.group {
+            tree.kill()?;
+        } else {
+            let mut child = child_arc.lock().await;
+            child.kill().await.map_err(ProcessError::from)?;
+        }
     }
 
     // Remove from state
@@ -722,6 +1044,7 @@ async fn op_process_kill(
             ps.processes.remove(&handle);
         }
     }
+    ProcessTreeTracker::global().untrack(tree.pid);
 
     Ok(())
 }
@@ -757,8 +1080,7 @@ async fn op_process_wait(
         let mut s = state.borrow_mut();
         if let Some(ps) = s.try_borrow_mut::<ProcessState>() {
             if let Some(process) = ps.processes.get_mut(&handle) {
-                process.exited = true;
-                process.exit_code = Some(exit_code);
+                process.mark_exited(Some(exit_code));
             }
         }
     }
@@ -779,7 +1101,7 @@ async fn op_process_status(
     debug!(handle = %handle, "process.status");
 
     // First check if we already know it exited
-    let (already_exited, exit_code, child_arc) = {
+    let (already_exited, exit_code, child_arc, timed_out) = {
         let s = state.borrow();
         let ps = s
             .try_borrow::<ProcessState>()
@@ -793,6 +1115,7 @@ async fn op_process_status(
             process.exited,
             process.exit_code,
             Arc::clone(&process.child),
+            process.timed_out(),
         )
     };
 
@@ -801,6 +1124,7 @@ async fn op_process_status(
             running: false,
             exit_code,
             signal: None,
+            timed_out,
         });
     }
 
@@ -809,25 +1133,14 @@ async fn op_process_status(
     match child.try_wait() {
         Ok(Some(status)) => {
             let code = status.code();
-            let signal = {
-                #[cfg(unix)]
-                {
-                    use std::os::unix::process::ExitStatusExt;
-                    status.signal().map(|s| format!("signal {}", s))
-                }
-                #[cfg(not(unix))]
-                {
-                    None
-                }
-            };
+            let signal = exit_signal(&status);
 
             // Update state
             {
                 let mut s = state.borrow_mut();
                 if let Some(ps) = s.try_borrow_mut::<ProcessState>() {
                     if let Some(process) = ps.processes.get_mut(&handle) {
-                        process.exited = true;
-                        process.exit_code = code;
+                        process.mark_exited(code);
                     }
                 }
             }
@@ -836,12 +1149,14 @@ async fn op_process_status(
                 running: false,
                 exit_code: code,
                 signal,
+                timed_out,
             })
         }
         Ok(None) => Ok(ProcessStatus {
             running: true,
             exit_code: None,
             signal: None,
+            timed_out,
         }),
         Err(e) => Err(ProcessError::io(e.to_string())),
     }
@@ -992,6 +1307,314 @@ async fn op_process_read_stderr(
     }
 }
 
+/// Write raw bytes to process stdin
+#[weld_op(async)]
+#[op2(async)]
+async fn op_process_write_stdin_bytes(
+    state: Rc<RefCell<OpState>>,
+    #[string] handle: String,
+    #[buffer] data: JsBuffer,
+) -> Result<(), ProcessError> {
+    debug!(handle = %handle, len = data.len(), "process.write_stdin_bytes");
+
+    let stdin_arc = get_process(&state.borrow(), &handle)?
+        .stdin
+        .clone()
+        .ok_or_else(|| ProcessError::stdin_closed("stdin not captured"))?;
+
+    let mut stdin = stdin_arc.lock().await;
+    stdin.write_all(&data).await.map_err(ProcessError::from)?;
+    stdin.flush().await.map_err(ProcessError::from)?;
+
+    Ok(())
+}
+
+/// Close process stdin so the process sees end of input
+///
+/// A write still in flight finishes first.
+#[weld_op]
+#[op2(fast)]
+fn op_process_close_stdin(state: &mut OpState, #[string] handle: &str) -> Result<(), ProcessError> {
+    debug!(handle = %handle, "process.close_stdin");
+
+    let process = state
+        .try_borrow_mut::<ProcessState>()
+        .and_then(|ps| ps.processes.get_mut(handle))
+        .ok_or_else(|| ProcessError::invalid_handle(handle))?;
+    process.stdin = None;
+    Ok(())
+}
+
+/// Read the next chunk of raw bytes from process stdout
+///
+/// Returns an empty buffer at end of stream.
+#[weld_op(async)]
+#[op2(async)]
+#[buffer]
+async fn op_process_read_stdout_bytes(
+    state: Rc<RefCell<OpState>>,
+    #[string] handle: String,
+) -> Result<Vec<u8>, ProcessError> {
+    let stdout_arc = get_process(&state.borrow(), &handle)?
+        .stdout
+        .clone()
+        .ok_or_else(|| ProcessError::output_not_captured("stdout not captured"))?;
+
+    let chunk = read_chunk(&mut *stdout_arc.lock().await).await?;
+    Ok(chunk)
+}
+
+/// Read the next chunk of raw bytes from process stderr
+///
+/// Returns an empty buffer at end of stream.
+#[weld_op(async)]
+#[op2(async)]
+#[buffer]
+async fn op_process_read_stderr_bytes(
+    state: Rc<RefCell<OpState>>,
+    #[string] handle: String,
+) -> Result<Vec<u8>, ProcessError> {
+    let stderr_arc = get_process(&state.borrow(), &handle)?
+        .stderr
+        .clone()
+        .ok_or_else(|| ProcessError::output_not_captured("stderr not captured"))?;
+
+    let chunk = read_chunk(&mut *stderr_arc.lock().await).await?;
+    Ok(chunk)
+}
+
+/// Close stdin, then collect stdout and stderr while waiting for exit
+///
+/// Both streams are read concurrently, so a process filling one pipe while
+/// the other is being drained cannot deadlock. Output not piped is empty.
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_process_wait_with_output(
+    state: Rc<RefCell<OpState>>,
+    #[string] handle: String,
+    #[serde] opts: Option<OutputOpts>,
+) -> Result<CapturedOutput, ProcessError> {
+    let opts = opts.unwrap_or_default();
+    debug!(handle = %handle, ?opts, "process.wait_with_output");
+
+    let (child_arc, stdout, stderr) = {
+        let mut s = state.borrow_mut();
+        let process = s
+            .try_borrow_mut::<ProcessState>()
+            .and_then(|ps| ps.processes.get_mut(&handle))
+            .ok_or_else(|| ProcessError::invalid_handle(&handle))?;
+        // Like std's wait_with_output: a process reading stdin would never exit otherwise
+        process.stdin = None;
+        (
+            Arc::clone(&process.child),
+            process.stdout.clone(),
+            process.stderr.clone(),
+        )
+    };
+
+    let (stdout, stderr, status) = tokio::join!(
+        read_all_capped(stdout, opts.max_stdout.unwrap_or(DEFAULT_OUTPUT_CAP)),
+        read_all_capped(stderr, opts.max_stderr.unwrap_or(DEFAULT_OUTPUT_CAP)),
+        async { child_arc.lock().await.wait().await },
+    );
+    let status = status.map_err(ProcessError::from)?;
+    let (stdout, stdout_truncated) = stdout?;
+    let (stderr, stderr_truncated) = stderr?;
+
+    let mut timed_out = false;
+    {
+        let mut s = state.borrow_mut();
+        if let Some(ps) = s.try_borrow_mut::<ProcessState>() {
+            if let Some(process) = ps.processes.get_mut(&handle) {
+                process.mark_exited(status.code());
+                timed_out = process.timed_out();
+            }
+        }
+    }
+
+    debug!(
+        handle = %handle,
+        stdout = stdout.len(),
+        stderr = stderr.len(),
+        "process.wait_with_output complete"
+    );
+
+    Ok(CapturedOutput {
+        success: status.success(),
+        code: status.code(),
+        signal: exit_signal(&status),
+        stdout: stdout.into(),
+        stderr: stderr.into(),
+        stdout_truncated,
+        stderr_truncated,
+        timed_out,
+    })
+}
+
+// ============================================================================
+// PTY Operations
+// ============================================================================
+
+/// Spawn a process attached to a new pseudo-terminal
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_process_spawn_pty(
+    state: Rc<RefCell<OpState>>,
+    #[string] binary: String,
+    #[serde] opts: Option<PtyOpts>,
+) -> Result<SpawnResult, ProcessError> {
+    let opts = opts.unwrap_or_default();
+
+    // Check capabilities
+    {
+        let s = state.borrow();
+        check_spawn(&s, &binary)?;
+        if let Some(env) = &opts.env {
+            for key in env.keys() {
+                check_env(&s, key)?;
+            }
+        }
+        check_can_spawn(&s)?;
+    }
+
+    debug!(binary = %binary, args = ?opts.args, cols = ?opts.cols, rows = ?opts.rows, "process.spawn_pty");
+
+    let pty = PtyHandle::spawn(&binary, &opts)?;
+    let pid = pty.pid;
+
+    let handle_id = {
+        let mut s = state.borrow_mut();
+        if !s.has::<ProcessState>() {
+            s.put(ProcessState::default());
+        }
+        let ps = s.borrow_mut::<ProcessState>();
+        let id = format!("pty-{}", ps.next_id);
+        ps.next_id += 1;
+        ps.ptys.insert(id.clone(), pty);
+        id
+    };
+
+    debug!(binary = %binary, pid = %pid, handle = %handle_id, "process.spawn_pty complete");
+
+    Ok(SpawnResult { id: handle_id, pid })
+}
+
+/// Read the next chunk of terminal output
+///
+/// Returns an empty buffer once the terminal is closed.
+#[weld_op(async)]
+#[op2(async)]
+#[buffer]
+async fn op_process_pty_read(
+    state: Rc<RefCell<OpState>>,
+    #[string] handle: String,
+) -> Result<Vec<u8>, ProcessError> {
+    let output = get_pty(&state.borrow(), &handle)?.output();
+    let chunk = output.lock().await.recv().await;
+    Ok(chunk.unwrap_or_default())
+}
+
+/// Write raw bytes to the terminal input
+#[weld_op(async)]
+#[op2(async)]
+async fn op_process_pty_write(
+    state: Rc<RefCell<OpState>>,
+    #[string] handle: String,
+    #[buffer] data: JsBuffer,
+) -> Result<(), ProcessError> {
+    let writer = get_pty(&state.borrow(), &handle)?.writer();
+    let data = data.to_vec();
+
+    // The PTY buffer is small and a busy child may not drain it right away
+    tokio::task::spawn_blocking(move || {
+        let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer.write_all(&data)?;
+        writer.flush()
+    })
+    .await
+    .map_err(|e| ProcessError::io(e.to_string()))?
+    .map_err(|e| ProcessError::stdin_closed(e.to_string()))
+}
+
+/// Change the terminal size
+#[weld_op]
+#[op2(fast)]
+fn op_process_pty_resize(
+    state: &mut OpState,
+    #[string] handle: &str,
+    cols: u16,
+    rows: u16,
+) -> Result<(), ProcessError> {
+    debug!(handle = %handle, cols, rows, "process.pty_resize");
+    get_pty(state, handle)?.resize(cols, rows)
+}
+
+/// Wait for a PTY process to exit
+#[weld_op(async)]
+#[op2(async)]
+#[serde]
+async fn op_process_pty_wait(
+    state: Rc<RefCell<OpState>>,
+    #[string] handle: String,
+) -> Result<PtyExit, ProcessError> {
+    debug!(handle = %handle, "process.pty_wait");
+
+    let mut exit = get_pty(&state.borrow(), &handle)?.exit();
+    let exit = exit
+        .wait_for(Option::is_some)
+        .await
+        .map_err(|_| ProcessError::invalid_handle(&handle))?
+        .clone();
+    exit.ok_or_else(|| ProcessError::invalid_handle(&handle))
+}
+
+/// Send a signal to a PTY process
+///
+/// The handle stays valid so remaining output and the exit status can still
+/// be read; `op_process_pty_close` releases it.
+#[weld_op]
+#[op2]
+fn op_process_pty_kill(
+    state: &mut OpState,
+    #[string] handle: String,
+    #[string] signal: Option<String>,
+) -> Result<(), ProcessError> {
+    debug!(handle = %handle, signal = ?signal, "process.pty_kill");
+
+    let pty = get_pty(state, &handle)?;
+
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::kill;
+        use nix::unistd::Pid;
+
+        let sig = parse_signal(signal.as_deref())?;
+        if pty.exit().borrow().is_none() {
+            kill(Pid::from_raw(pty.pid as i32), sig)
+                .map_err(|e| ProcessError::io(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = signal; // Ignore signal on non-Unix
+        pty.kill()
+    }
+}
+
+/// Close a PTY, killing its process if it is still running
+#[weld_op]
+#[op2(fast)]
+fn op_process_pty_close(state: &mut OpState, #[string] handle: &str) {
+    debug!(handle = %handle, "process.pty_close");
+    if let Some(ps) = state.try_borrow_mut::<ProcessState>() {
+        ps.ptys.remove(handle);
+    }
+}
+
 // ============================================================================
 // State Initialization
 // ============================================================================
@@ -1064,6 +1687,86 @@ mod tests {
         assert!(state.can_spawn()); // Can spawn when under limit
     }
 
+    #[tokio::test]
+    async fn test_read_chunk_keeps_binary_and_partial_lines() {
+        let data: &[u8] = b"\x00\xffno newline";
+        let mut reader = BufReader::with_capacity(4, data);
+
+        let mut read = Vec::new();
+        loop {
+            let chunk = read_chunk(&mut reader).await.unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            assert!(chunk.len() <= 4);
+            read.extend(chunk);
+        }
+        assert_eq!(read, data);
+    }
+
+    #[tokio::test]
+    async fn test_read_capped_drains_past_cap() {
+        let data = [7u8; 100];
+        let mut reader = BufReader::with_capacity(16, &data[..]);
+        let (kept, truncated) = read_capped(&mut reader, 40).await.unwrap();
+        assert_eq!(kept, vec![7u8; 40]);
+        assert!(truncated);
+        assert!(read_chunk(&mut reader).await.unwrap().is_empty());
+
+        let mut reader = BufReader::new(&data[..]);
+        let (kept, truncated) = read_capped(&mut reader, 100).await.unwrap();
+        assert_eq!(kept.len(), 100);
+        assert!(!truncated);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdout_piped_into_another_process() {
+        let mut state = ProcessState::default();
+        let mut child = tokio::process::Command::new("printf")
+            .arg("a\\000b")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let pid = child.id().unwrap();
+        state.processes.insert(
+            "proc-1".to_string(),
+            ProcessHandle {
+                pid,
+                child: Arc::new(Mutex::new(child)),
+                binary: "printf".to_string(),
+                stdout: Some(Arc::new(Mutex::new(BufReader::new(stdout)))),
+                stderr: None,
+                stdin: None,
+                exited: false,
+                exit_code: None,
+                tree: ProcessTree { pid, group: false },
+                timeout: None,
+            },
+        );
+
+        let pipe = state.take_stdout_pipe("proc-1").unwrap();
+        assert!(state.processes["proc-1"].stdout.is_none());
+        assert!(state.take_stdout_pipe("proc-1").is_err());
+
+        // A failed spawn gives the pipe back
+        let spawned = tokio::process::Command::new("forge-no-such-binary")
+            .stdin(pipe.stdio().unwrap())
+            .spawn();
+        assert!(spawned.is_err());
+        state.restore_stdout_pipe("proc-1", pipe);
+        let pipe = state.take_stdout_pipe("proc-1").unwrap();
+
+        let output = tokio::process::Command::new("wc")
+            .arg("-c")
+            .stdin(pipe.stdio().unwrap())
+            .output()
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "3");
+    }
+
     #[test]
     fn test_parse_stdio() {
         // These just test the parsing logic
@@ -0,0 +1,337 @@
+//! Processes attached to a pseudo-terminal
+//!
+//! Interactive programs (shells, `ssh`, REPLs, `top`) check whether their
+//! stdio is a terminal and change behavior when it is not: they buffer
+//! output, skip prompts or refuse to start. A PTY gives the child a real
+//! terminal on all three streams, with a size it can query and a resize
+//! signal when that size changes.
+//!
+//! `portable-pty` blocks on reads and exit, so each PTY gets two threads:
+//! one pulling output into a bounded channel (a slow reader stops the child
+//! once the kernel buffer fills) and one waiting for the exit status.
+
+use crate::{ProcessError, ProcessTree, ProcessTreeTracker};
+use forge_weld_macro::weld_struct;
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+
+/// Terminal size used when a PTY does not ask for one
+pub const DEFAULT_PTY_COLS: u16 = 80;
+pub const DEFAULT_PTY_ROWS: u16 = 24;
+
+/// Bytes read from the PTY at a time
+const READ_CHUNK: usize = 8 * 1024;
+/// Chunks buffered before the reader thread stops pulling output
+const READ_BUFFER: usize = 64;
+
+/// Options for spawning a process on a PTY
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyOpts {
+    pub args: Option<Vec<String>>,
+    pub env: Option<HashMap<String, String>>,
+    pub cwd: Option<String>,
+    /// Terminal width in columns (default: 80)
+    pub cols: Option<u16>,
+    /// Terminal height in rows (default: 24)
+    pub rows: Option<u16>,
+}
+
+/// How a PTY process exited
+#[weld_struct]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PtyExit {
+    pub success: bool,
+    /// Exit code, or `None` if the process was killed by a signal
+    pub code: Option<i32>,
+    pub signal: Option<String>,
+}
+
+/// A process running on a PTY
+pub struct PtyHandle {
+    pub pid: u32,
+    pub binary: String,
+    /// `None` once the pseudo-console has been closed after the child exited
+    master: Arc<Mutex<Option<Box<dyn MasterPty + Send>>>>,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    output: Arc<tokio::sync::Mutex<mpsc::Receiver<Vec<u8>>>>,
+    killer: Mutex<Box<dyn ChildKiller + Send + Sync>>,
+    exit: watch::Receiver<Option<PtyExit>>,
+}
+
+fn pty_size(cols: u16, rows: u16) -> Result<PtySize, ProcessError> {
+    if cols == 0 || rows == 0 {
+        return Err(ProcessError::io(format!(
+            "Invalid terminal size {}x{}",
+            cols, rows
+        )));
+    }
+    Ok(PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    })
+}
+
+impl PtyHandle {
+    /// Open a PTY and spawn `binary` on it
+    pub fn spawn(binary: &str, opts: &PtyOpts) -> Result<Self, ProcessError> {
+        let size = pty_size(
+            opts.cols.unwrap_or(DEFAULT_PTY_COLS),
+            opts.rows.unwrap_or(DEFAULT_PTY_ROWS),
+        )?;
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(|e| ProcessError::failed_to_spawn(format!("Failed to open PTY: {}", e)))?;
+
+        let mut cmd = CommandBuilder::new(binary);
+        if let Some(args) = &opts.args {
+            cmd.args(args);
+        }
+        if let Some(cwd) = &opts.cwd {
+            cmd.cwd(cwd);
+        }
+        // Apps started from a desktop launcher have no TERM, and full-screen
+        // programs refuse to run without one
+        if cmd.get_env("TERM").is_none() {
+            cmd.env("TERM", "xterm-256color");
+        }
+        if let Some(env) = &opts.env {
+            for (key, value) in env {
+                cmd.env(key, value);
+            }
+        }
+
+        let mut child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| ProcessError::failed_to_spawn(e.to_string()))?;
+        // Only the child keeps the slave side open, so reads end once it exits
+        drop(pair.slave);
+
+        let pid = child
+            .process_id()
+            .ok_or_else(|| ProcessError::failed_to_spawn("Process has no PID"))?;
+        let killer = child.clone_killer();
+        let reader = pair.master.try_clone_reader();
+        let writer = pair.master.take_writer();
+        let (reader, writer) = match (reader, writer) {
+            (Ok(reader), Ok(writer)) => (reader, writer),
+            (Err(e), _) | (_, Err(e)) => {
+                let _ = child.kill();
+                return Err(ProcessError::failed_to_spawn(e.to_string()));
+            }
+        };
+
+        // portable-pty starts the child in its own session on Unix
+        ProcessTreeTracker::global().track(ProcessTree {
+            pid,
+            group: cfg!(unix),
+        });
+
+        let (output_tx, output_rx) = mpsc::channel(READ_BUFFER);
+        std::thread::Builder::new()
+            .name("pty-read".to_string())
+            .spawn(move || pump_output(reader, output_tx))?;
+
+        let master = Arc::new(Mutex::new(Some(pair.master)));
+        let wait_master = master.clone();
+        let (exit_tx, exit_rx) = watch::channel(None);
+        std::thread::Builder::new()
+            .name("pty-wait".to_string())
+            .spawn(move || {
+                let exit = match child.wait() {
+                    Ok(status) => {
+                        let signal = status.signal().map(str::to_string);
+                        PtyExit {
+                            success: status.success(),
+                            code: signal.is_none().then_some(status.exit_code() as i32),
+                            signal,
+                        }
+                    }
+                    Err(_) => PtyExit {
+                        success: false,
+                        code: None,
+                        signal: None,
+                    },
+                };
+                // ConPTY keeps the output pipe open until the pseudo-console
+                // is closed, even after the child exits, so the reader would
+                // never see EOF. On Unix the reader holds its own descriptor
+                // and ends by itself.
+                if cfg!(windows) {
+                    wait_master.lock().unwrap_or_else(|e| e.into_inner()).take();
+                }
+                exit_tx.send_replace(Some(exit));
+            })?;
+
+        Ok(Self {
+            pid,
+            binary: binary.to_string(),
+            master,
+            writer: Arc::new(Mutex::new(writer)),
+            output: Arc::new(tokio::sync::Mutex::new(output_rx)),
+            killer: Mutex::new(killer),
+            exit: exit_rx,
+        })
+    }
+
+    /// Output chunks; the channel closes once the terminal does
+    pub fn output(&self) -> Arc<tokio::sync::Mutex<mpsc::Receiver<Vec<u8>>>> {
+        self.output.clone()
+    }
+
+    /// Writer for the terminal input; blocks while the PTY buffer is full
+    pub fn writer(&self) -> Arc<Mutex<Box<dyn Write + Send>>> {
+        self.writer.clone()
+    }
+
+    /// Exit status, once the process has exited
+    pub fn exit(&self) -> watch::Receiver<Option<PtyExit>> {
+        self.exit.clone()
+    }
+
+    /// Change the terminal size; the process receives `SIGWINCH`
+    pub fn resize(&self, cols: u16, rows: u16) -> Result<(), ProcessError> {
+        self.master
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .ok_or_else(|| ProcessError::io("Terminal is closed"))?
+            .resize(pty_size(cols, rows)?)
+            .map_err(|e| ProcessError::io(e.to_string()))
+    }
+
+    /// Terminate the process if it is still running
+    pub fn kill(&self) -> Result<(), ProcessError> {
+        if self.exit.borrow().is_some() {
+            return Ok(());
+        }
+        self.killer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .kill()
+            .map_err(ProcessError::from)
+    }
+}
+
+impl Drop for PtyHandle {
+    fn drop(&mut self) {
+        let _ = self.kill();
+        ProcessTreeTracker::global().untrack(self.pid);
+    }
+}
+
+/// Copy PTY output into `tx` until the terminal closes or nobody is reading
+fn pump_output(mut reader: Box<dyn Read + Send>, tx: mpsc::Sender<Vec<u8>>) {
+    let mut buf = vec![0u8; READ_CHUNK];
+    loop {
+        match reader.read(&mut buf) {
+            // Linux reports EIO rather than EOF once the child side is closed
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if tx.blocking_send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read_all(pty: &PtyHandle) -> Vec<u8> {
+        let output = pty.output();
+        let mut output = output.lock().await;
+        let mut all = Vec::new();
+        while let Some(chunk) = output.recv().await {
+            all.extend_from_slice(&chunk);
+        }
+        all
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_child_sees_a_terminal_of_the_requested_size() {
+        let pty = PtyHandle::spawn(
+            "sh",
+            &PtyOpts {
+                args: Some(vec![
+                    "-c".to_string(),
+                    "test -t 0 && test -t 1 && stty size; exit 3".to_string(),
+                ]),
+                cols: Some(100),
+                rows: Some(40),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let output = String::from_utf8_lossy(&read_all(&pty).await).to_string();
+        assert_eq!(output.trim(), "40 100");
+
+        let mut exit = pty.exit();
+        let exit = exit
+            .wait_for(Option::is_some)
+            .await
+            .unwrap()
+            .clone()
+            .unwrap();
+        assert_eq!(exit.code, Some(3));
+        assert!(!exit.success);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_and_resize() {
+        let pty = PtyHandle::spawn(
+            "sh",
+            &PtyOpts {
+                args: Some(vec![
+                    "-c".to_string(),
+                    "read line; stty size; echo \"got $line\"".to_string(),
+                ]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        pty.resize(120, 50).unwrap();
+        assert!(pty.resize(0, 50).is_err());
+        pty.writer().lock().unwrap().write_all(b"hello\r").unwrap();
+
+        let output = String::from_utf8_lossy(&read_all(&pty).await).to_string();
+        assert!(output.contains("50 120"), "{:?}", output);
+        assert!(output.contains("got hello"), "{:?}", output);
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn test_output_ends_when_the_child_exits() {
+        let pty = PtyHandle::spawn(
+            "cmd.exe",
+            &PtyOpts {
+                args: Some(vec!["/c".to_string(), "echo hello & exit 3".to_string()]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let output = tokio::time::timeout(std::time::Duration::from_secs(10), read_all(&pty))
+            .await
+            .expect("output should end once the child exits");
+        assert!(String::from_utf8_lossy(&output).contains("hello"));
+        let mut exit = pty.exit();
+        let exit = exit.wait_for(Option::is_some).await.unwrap().clone();
+        assert_eq!(exit.unwrap().code, Some(3));
+        assert!(pty.resize(100, 40).is_err());
+    }
+}
@@ -0,0 +1,412 @@
+//! Process groups, resource limits, timeouts and cleanup at exit
+//!
+//! Killing a PID does not reach the processes it forked: `npm run dev`
+//! leaves its server running when only `npm` is killed. A child spawned
+//! with `processGroup` or `newSession` leads its own process group, and
+//! [`ProcessTree`] signals that whole group instead (`killpg` on Unix,
+//! `taskkill /T` on Windows).
+//!
+//! Like `ext_shell`'s `ChildProcessTracker`, every spawned child is recorded
+//! by [`ProcessTreeTracker`] so it can be killed when the app exits. The app
+//! leaves through `std::process::exit` (from `runtime:app` or the event loop),
+//! which skips destructors, so the tracker hooks `atexit` instead of relying
+//! on `Drop`. A fatal signal skips `atexit` too, so the runtime also calls
+//! [`ProcessTreeTracker::kill_all`] when SIGINT, SIGTERM or SIGHUP arrives and
+//! when its event loop shuts down.
+
+use crate::ProcessError;
+use forge_weld_macro::weld_struct;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, Once};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Time a timed-out process gets between `SIGTERM` and `SIGKILL`
+pub const TIMEOUT_KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Resource limits applied to a child at spawn (Linux only)
+#[weld_struct]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    /// CPU time in seconds (`RLIMIT_CPU`); the child gets `SIGXCPU`, then `SIGKILL`
+    pub cpu_seconds: Option<u64>,
+    /// Virtual memory in bytes (`RLIMIT_AS`); allocations beyond it fail
+    pub address_space: Option<u64>,
+    /// Open file descriptors (`RLIMIT_NOFILE`)
+    pub open_files: Option<u64>,
+}
+
+/// Set up the process group, session and resource limits of a command
+pub(crate) fn configure_command(
+    cmd: &mut tokio::process::Command,
+    process_group: bool,
+    new_session: bool,
+    limits: Option<&ResourceLimits>,
+) -> Result<(), ProcessError> {
+    #[cfg(unix)]
+    {
+        if new_session {
+            // SAFETY: setsid is async-signal-safe and touches no parent state
+            unsafe {
+                cmd.pre_exec(|| {
+                    if libc::setsid() == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        } else if process_group {
+            cmd.process_group(0);
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        if process_group || new_session {
+            cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+        }
+    }
+
+    if let Some(limits) = limits {
+        apply_limits(cmd, limits)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn apply_limits(
+    cmd: &mut tokio::process::Command,
+    limits: &ResourceLimits,
+) -> Result<(), ProcessError> {
+    let limits = [
+        (libc::RLIMIT_CPU, limits.cpu_seconds),
+        (libc::RLIMIT_AS, limits.address_space),
+        (libc::RLIMIT_NOFILE, limits.open_files),
+    ];
+    // SAFETY: setrlimit is async-signal-safe and the closure only reads
+    // values copied into it
+    unsafe {
+        cmd.pre_exec(move || {
+            for (resource, value) in limits {
+                if let Some(value) = value {
+                    let limit = libc::rlimit {
+                        rlim_cur: value as libc::rlim_t,
+                        rlim_max: value as libc::rlim_t,
+                    };
+                    if libc::setrlimit(resource, &limit) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn apply_limits(
+    _cmd: &mut tokio::process::Command,
+    _limits: &ResourceLimits,
+) -> Result<(), ProcessError> {
+    Err(ProcessError::failed_to_spawn(
+        "Resource limits are only supported on Linux",
+    ))
+}
+
+/// A spawned process, and its process group when it leads one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessTree {
+    pub pid: u32,
+    /// The process leads its own group; signals go to the whole group
+    pub group: bool,
+}
+
+impl ProcessTree {
+    /// Send a signal to the process, or to its whole group
+    #[cfg(unix)]
+    pub fn signal(&self, signal: nix::sys::signal::Signal) -> Result<(), ProcessError> {
+        use nix::sys::signal::{kill, killpg};
+        use nix::unistd::Pid;
+
+        let pid = Pid::from_raw(self.pid as i32);
+        let result = if self.group {
+            killpg(pid, signal)
+        } else {
+            kill(pid, signal)
+        };
+        result.map_err(|e| ProcessError::io(e.to_string()))
+    }
+
+    /// Forcefully end the process, and its whole group when it leads one
+    pub fn kill(&self) -> Result<(), ProcessError> {
+        #[cfg(unix)]
+        {
+            self.signal(nix::sys::signal::Signal::SIGKILL)
+        }
+
+        #[cfg(not(unix))]
+        {
+            let mut cmd = std::process::Command::new("taskkill");
+            cmd.arg("/F");
+            if self.group {
+                cmd.arg("/T");
+            }
+            let status = cmd
+                .args(["/PID", &self.pid.to_string()])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status()?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(ProcessError::io(format!(
+                    "taskkill failed for PID {}",
+                    self.pid
+                )))
+            }
+        }
+    }
+
+    /// Ask the process (tree) to stop, then kill it after `grace`
+    pub async fn terminate(&self, grace: Duration) {
+        #[cfg(unix)]
+        {
+            if self.signal(nix::sys::signal::Signal::SIGTERM).is_err() {
+                return;
+            }
+            tokio::time::sleep(grace).await;
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = grace;
+        }
+        let _ = self.kill();
+    }
+}
+
+/// Wall-clock timeout of a spawned process
+///
+/// Terminates the process tree once the timeout passes, unless cancelled
+/// first. Dropping the guard cancels it.
+#[derive(Debug)]
+pub struct TimeoutGuard {
+    cancel: Arc<Notify>,
+    timed_out: Arc<AtomicBool>,
+}
+
+impl TimeoutGuard {
+    pub fn start(tree: ProcessTree, timeout: Duration) -> Self {
+        let cancel = Arc::new(Notify::new());
+        let timed_out = Arc::new(AtomicBool::new(false));
+        tokio::spawn({
+            let cancel = cancel.clone();
+            let timed_out = timed_out.clone();
+            async move {
+                tokio::select! {
+                    _ = cancel.notified() => {}
+                    _ = tokio::time::sleep(timeout) => {
+                        tracing::debug!(pid = tree.pid, group = tree.group, "process timed out");
+                        timed_out.store(true, Ordering::SeqCst);
+                        tree.terminate(TIMEOUT_KILL_GRACE).await;
+                    }
+                }
+            }
+        });
+        Self { cancel, timed_out }
+    }
+
+    /// The timeout passed and the process was terminated
+    pub fn timed_out(&self) -> bool {
+        self.timed_out.load(Ordering::SeqCst)
+    }
+
+    /// Stop the timer, e.g. because the process has exited
+    pub fn cancel(&self) {
+        self.cancel.notify_one();
+    }
+}
+
+impl Drop for TimeoutGuard {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Children still alive, killed when the app exits
+#[derive(Debug, Default)]
+pub struct ProcessTreeTracker {
+    children: Mutex<HashMap<u32, ProcessTree>>,
+}
+
+static TRACKER: LazyLock<ProcessTreeTracker> = LazyLock::new(ProcessTreeTracker::default);
+static REGISTER_AT_EXIT: Once = Once::new();
+
+extern "C" fn kill_children_at_exit() {
+    TRACKER.kill_all();
+}
+
+impl ProcessTreeTracker {
+    /// The process-wide tracker
+    pub fn global() -> &'static Self {
+        REGISTER_AT_EXIT.call_once(|| {
+            // SAFETY: registering a plain extern "C" fn with no captured state
+            unsafe {
+                libc::atexit(kill_children_at_exit);
+            }
+        });
+        &TRACKER
+    }
+
+    /// Record a child so it is killed at exit
+    pub fn track(&self, tree: ProcessTree) {
+        self.lock().insert(tree.pid, tree);
+    }
+
+    /// Forget a child that has been reaped or killed
+    pub fn untrack(&self, pid: u32) {
+        self.lock().remove(&pid);
+    }
+
+    /// Tracked children
+    pub fn trees(&self) -> Vec<ProcessTree> {
+        self.lock().values().copied().collect()
+    }
+
+    /// Kill every tracked child (and its group) and forget them
+    pub fn kill_all(&self) {
+        // Never block at exit on a lock held by a thread that is going away
+        let children = match self.children.try_lock() {
+            Ok(mut children) => std::mem::take(&mut *children),
+            Err(std::sync::TryLockError::Poisoned(e)) => std::mem::take(&mut *e.into_inner()),
+            Err(std::sync::TryLockError::WouldBlock) => return,
+        };
+        for tree in children.values() {
+            let _ = tree.kill();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u32, ProcessTree>> {
+        self.children.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    fn spawn_sh(script: &str, group: bool) -> tokio::process::Child {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", script]).stdout(Stdio::piped());
+        configure_command(&mut cmd, group, false, None).unwrap();
+        cmd.spawn().unwrap()
+    }
+
+    async fn first_line(child: &mut tokio::process::Child) -> String {
+        use tokio::io::AsyncBufReadExt;
+        let stdout = child.stdout.take().unwrap();
+        let mut line = String::new();
+        tokio::io::BufReader::new(stdout)
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        line.trim().to_string()
+    }
+
+    /// Running, as opposed to gone or a zombie waiting for whoever adopted it
+    fn alive(pid: i32) -> bool {
+        let exists = nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None).is_ok();
+        let zombie = std::fs::read_to_string(format!("/proc/{}/stat", pid)).is_ok_and(|stat| {
+            stat.rsplit(')')
+                .next()
+                .is_some_and(|s| s.trim_start().starts_with('Z'))
+        });
+        exists && !zombie
+    }
+
+    #[tokio::test]
+    async fn test_kill_reaches_grandchildren_in_group() {
+        // The grandchild prints its PID and outlives the shell unless the group is killed
+        let mut child = spawn_sh("sleep 30 & echo $!; wait", true);
+        let grandchild: i32 = first_line(&mut child).await.parse().unwrap();
+        let tree = ProcessTree {
+            pid: child.id().unwrap(),
+            group: true,
+        };
+
+        tree.kill().unwrap();
+        child.wait().await.unwrap();
+        for _ in 0..100 {
+            if !alive(grandchild) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("grandchild {} survived the group kill", grandchild);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_terminates_and_cancel_does_not() {
+        let mut child = spawn_sh("sleep 30", true);
+        let guard = TimeoutGuard::start(
+            ProcessTree {
+                pid: child.id().unwrap(),
+                group: true,
+            },
+            Duration::from_millis(50),
+        );
+        let status = child.wait().await.unwrap();
+        assert!(!status.success());
+        assert!(guard.timed_out());
+
+        let mut child = spawn_sh("sleep 0.2", false);
+        let guard = TimeoutGuard::start(
+            ProcessTree {
+                pid: child.id().unwrap(),
+                group: false,
+            },
+            Duration::from_millis(50),
+        );
+        guard.cancel();
+        assert!(child.wait().await.unwrap().success());
+        assert!(!guard.timed_out());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_resource_limits_applied() {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "ulimit -n; ulimit -t"]);
+        let limits = ResourceLimits {
+            cpu_seconds: Some(7),
+            open_files: Some(64),
+            ..Default::default()
+        };
+        configure_command(&mut cmd, false, false, Some(&limits)).unwrap();
+        let output = cmd.output().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "64\n7\n");
+    }
+
+    #[test]
+    fn test_tracker_kill_all_forgets_children() {
+        let tracker = ProcessTreeTracker::default();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        tracker.track(ProcessTree {
+            pid: child.id(),
+            group: false,
+        });
+        assert_eq!(tracker.trees().len(), 1);
+
+        tracker.kill_all();
+        assert!(tracker.trees().is_empty());
+        assert!(!child.wait().unwrap().success());
+    }
+}
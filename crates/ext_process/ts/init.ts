@@ -11,8 +11,11 @@
  * - Spawn child processes with configurable stdio
  * - Bidirectional communication via stdin/stdout/stderr
  * - Process lifecycle management (kill, wait, status)
- * - Async iteration over stdout/stderr streams
+ * - Async iteration over stdout/stderr streams, as lines or raw byte chunks
+ * - Output capture with size caps, and native pipes between processes
+ * - Process groups/sessions, whole-tree kill, timeouts and Linux resource limits
  * - Cross-platform signal handling
+ * - Pseudo-terminals for interactive programs (shells, REPLs, `ssh`, `top`)
  *
  * ## Permissions
  * Requires `process.spawn` permission in manifest.app.toml:
@@ -61,6 +64,19 @@ declare const Deno: {
       op_process_write_stdin(handle: string, data: string): Promise<void>;
       op_process_read_stdout(handle: string): Promise<ReadOutput>;
       op_process_read_stderr(handle: string): Promise<ReadOutput>;
+      op_process_write_stdin_bytes(handle: string, data: Uint8Array): Promise<void>;
+      op_process_close_stdin(handle: string): void;
+      op_process_read_stdout_bytes(handle: string): Promise<Uint8Array>;
+      op_process_read_stderr_bytes(handle: string): Promise<Uint8Array>;
+      op_process_wait_with_output(handle: string, opts: OutputOptions | undefined): Promise<CapturedOutput>;
+      // Pseudo-terminals
+      op_process_spawn_pty(binary: string, opts: PtyOptions | undefined): Promise<SpawnResult>;
+      op_process_pty_read(handle: string): Promise<Uint8Array>;
+      op_process_pty_write(handle: string, data: Uint8Array): Promise<void>;
+      op_process_pty_resize(handle: string, cols: number, rows: number): void;
+      op_process_pty_wait(handle: string): Promise<WaitResult>;
+      op_process_pty_kill(handle: string, signal: string | undefined): void;
+      op_process_pty_close(handle: string): void;
     };
   };
 };
@@ -130,6 +146,96 @@ export interface SpawnOptions {
    * @default "piped"
    */
   stderr?: "piped" | "inherit" | "null";
+
+  /**
+   * Connect the stdout of an already spawned process to this process's
+   * stdin with an OS pipe, like `a | b` in a shell. Data does not pass
+   * through the runtime. The source must have been spawned with
+   * `stdout: "piped"` and not read from yet; its `stdout` can no longer be
+   * read afterwards. Overrides `stdin`.
+   *
+   * @example
+   * ```typescript
+   * const tar = await spawn("tar", { args: ["-c", "dir"], stdout: "piped" });
+   * const gzip = await spawn("gzip", { stdinFrom: tar, stdout: "piped" });
+   * ```
+   */
+  stdinFrom?: ProcessHandle | string;
+
+  /**
+   * Start the process in a new process group. `kill()` and `timeoutMs` then
+   * reach everything it spawned (e.g. the server started by `npm run dev`),
+   * not just the process itself.
+   *
+   * @default false
+   */
+  processGroup?: boolean;
+
+  /**
+   * Start the process in a new session, detached from the app's terminal.
+   * Implies a new process group. On Windows this behaves like `processGroup`.
+   *
+   * @default false
+   */
+  newSession?: boolean;
+
+  /**
+   * Wall-clock limit in milliseconds. When it passes, the process (and its
+   * group, if any) receives SIGTERM and, 2 seconds later, SIGKILL.
+   * `status()` and `waitWithOutput()` then report `timedOut: true`.
+   */
+  timeoutMs?: number;
+
+  /**
+   * Resource limits applied to the process at spawn.
+   *
+   * @platform Linux only; spawning fails with error 4003 elsewhere
+   */
+  limits?: ResourceLimits;
+}
+
+/**
+ * Resource limits for a spawned process (Linux `setrlimit`).
+ */
+export interface ResourceLimits {
+  /** CPU time in seconds; the process is killed once it is used up */
+  cpuSeconds?: number;
+  /** Virtual memory in bytes; allocations beyond it fail */
+  addressSpace?: number;
+  /** Maximum number of open file descriptors */
+  openFiles?: number;
+}
+
+/**
+ * Size caps for `waitWithOutput()`.
+ */
+export interface OutputOptions {
+  /**
+   * Most stdout bytes kept; the rest is read and discarded.
+   * @default 16 MiB
+   */
+  maxStdout?: number;
+  /**
+   * Most stderr bytes kept; the rest is read and discarded.
+   * @default 16 MiB
+   */
+  maxStderr?: number;
+}
+
+/**
+ * Exit status and collected output of a process.
+ */
+export interface CapturedOutput extends WaitResult {
+  /** Collected stdout (empty if stdout was not piped) */
+  stdout: Uint8Array;
+  /** Collected stderr (empty if stderr was not piped) */
+  stderr: Uint8Array;
+  /** More stdout was produced than `maxStdout` */
+  stdoutTruncated: boolean;
+  /** More stderr was produced than `maxStderr` */
+  stderrTruncated: boolean;
+  /** The process was terminated because `timeoutMs` passed */
+  timedOut: boolean;
 }
 
 /**
@@ -174,6 +280,7 @@ interface RawProcessStatus {
   running: boolean;
   exit_code?: number | null;
   signal?: string | null;
+  timed_out: boolean;
 }
 
 /**
@@ -195,6 +302,11 @@ export interface ProcessStatus {
    * or undefined if still running or exited normally.
    */
   signal?: string;
+
+  /**
+   * Whether the process was terminated because its `timeoutMs` passed.
+   */
+  timedOut: boolean;
 }
 
 /**
@@ -229,6 +341,22 @@ export interface StdioIterator {
   [Symbol.asyncIterator](): AsyncGenerator<string, void, unknown>;
 }
 
+/**
+ * Async iterator over raw output chunks.
+ * Chunks follow pipe reads, so binary data and output without newlines
+ * arrive as soon as the process writes them.
+ *
+ * @example
+ * ```typescript
+ * for await (const chunk of proc.stdoutBytes) {
+ *   await file.write(chunk);
+ * }
+ * ```
+ */
+export interface ByteStreamIterator {
+  [Symbol.asyncIterator](): AsyncGenerator<Uint8Array, void, unknown>;
+}
+
 /**
  * Handle to a spawned child process.
  *
@@ -273,6 +401,8 @@ export interface ProcessHandle {
    *
    * @param signal - Signal to send (e.g., "SIGTERM", "SIGKILL").
    *                 Default behavior is platform-specific.
+   *                 When spawned with `processGroup` or `newSession`, the
+   *                 signal goes to the whole group.
    * @returns True if the signal was sent successfully
    * @throws Error (4006) if process handle is invalid
    *
@@ -326,6 +456,7 @@ export interface ProcessHandle {
 
   /**
    * Writes data to the process's standard input.
+   * Strings are written as UTF-8; `Uint8Array`s are written as-is.
    *
    * @param data - Data to write to stdin
    * @throws Error (4007) if stdin is closed
@@ -338,7 +469,15 @@ export interface ProcessHandle {
    * await proc.writeStdin("Hello, process!\n");
    * ```
    */
-  writeStdin(data: string): Promise<void>;
+  writeStdin(data: string | Uint8Array): Promise<void>;
+
+  /**
+   * Closes the process's standard input so it sees end of input.
+   * Needed by programs that read stdin to the end, such as `gzip` or `sort`.
+   *
+   * @throws Error (4006) if process handle is invalid
+   */
+  closeStdin(): void;
 
   /**
    * Reads available data from the process's standard output.
@@ -379,6 +518,43 @@ export interface ProcessHandle {
    */
   readStderr(): Promise<ReadOutput>;
 
+  /**
+   * Reads the next chunk of raw bytes from standard output.
+   * Unlike `readStdout()`, binary data is kept intact and output without a
+   * trailing newline is returned as soon as it is written.
+   *
+   * @returns Bytes read, or null at end of stream
+   * @throws Error (4008) if stdout was not configured as "piped"
+   */
+  readStdoutBytes(): Promise<Uint8Array | null>;
+
+  /**
+   * Reads the next chunk of raw bytes from standard error.
+   *
+   * @returns Bytes read, or null at end of stream
+   * @throws Error (4008) if stderr was not configured as "piped"
+   */
+  readStderrBytes(): Promise<Uint8Array | null>;
+
+  /**
+   * Closes stdin, then collects stdout and stderr until the process exits.
+   * Both streams are drained concurrently, and output beyond the caps is
+   * discarded (with `stdoutTruncated`/`stderrTruncated` set) so the process
+   * is never blocked on a full pipe.
+   *
+   * @param opts - Per-stream size caps
+   * @returns Exit status and collected output
+   * @throws Error (4006) if process handle is invalid
+   *
+   * @example
+   * ```typescript
+   * const proc = await spawn("tar", { args: ["-c", "dir"], stdout: "piped", stderr: "piped" });
+   * const { success, stdout, stderr } = await proc.waitWithOutput({ maxStdout: 100 * 1024 * 1024 });
+   * if (!success) console.error(new TextDecoder().decode(stderr));
+   * ```
+   */
+  waitWithOutput(opts?: OutputOptions): Promise<CapturedOutput>;
+
   /**
    * Async iterator for reading standard output line by line.
    * Automatically handles EOF and completes when the stream closes.
@@ -409,9 +585,117 @@ export interface ProcessHandle {
    * ```
    */
   stderr: StdioIterator;
+
+  /**
+   * Async iterator over raw standard output chunks.
+   *
+   * @throws Error (4008) if stdout was not configured as "piped"
+   */
+  stdoutBytes: ByteStreamIterator;
+
+  /**
+   * Async iterator over raw standard error chunks.
+   *
+   * @throws Error (4008) if stderr was not configured as "piped"
+   */
+  stderrBytes: ByteStreamIterator;
+}
+
+/**
+ * Options for spawning a process on a pseudo-terminal.
+ */
+export interface PtyOptions {
+  /** Command-line arguments to pass to the process */
+  args?: string[];
+  /** Working directory for the process */
+  cwd?: string;
+  /**
+   * Extra environment variables. The parent environment is inherited, and
+   * `TERM` defaults to "xterm-256color" when it is not set.
+   */
+  env?: Record<string, string>;
+  /**
+   * Terminal width in columns.
+   * @default 80
+   */
+  cols?: number;
+  /**
+   * Terminal height in rows.
+   * @default 24
+   */
+  rows?: number;
+}
+
+/**
+ * Handle to a process running on a pseudo-terminal.
+ *
+ * The terminal merges stdout and stderr into one raw byte stream, including
+ * escape sequences, and echoes input unless the program turns echo off. Feed
+ * the output to a terminal emulator and send keystrokes with `write()`.
+ *
+ * Call `close()` once done: the handle counts towards the process limit
+ * until it is closed, even after the process has exited.
+ *
+ * @example
+ * ```typescript
+ * const pty = await spawnPty("bash", { cols: 120, rows: 30 });
+ * await pty.write("ls --color\r");
+ * for await (const chunk of pty) {
+ *   terminal.write(chunk);
+ * }
+ * console.log("Shell exited:", await pty.wait());
+ * pty.close();
+ * ```
+ */
+export interface PtyProcess extends AsyncIterable<Uint8Array> {
+  /** Internal PTY handle identifier */
+  readonly id: string;
+  /** Operating system process ID (PID) */
+  readonly pid: number;
+  /**
+   * Reads the next chunk of terminal output.
+   * @returns Output bytes, or null once the terminal is closed
+   */
+  read(): Promise<Uint8Array | null>;
+  /**
+   * Writes input to the terminal. Strings are encoded as UTF-8.
+   * @throws Error (4007) if the terminal no longer accepts input
+   */
+  write(data: Uint8Array | string): Promise<void>;
+  /**
+   * Changes the terminal size; the process is notified with `SIGWINCH`.
+   */
+  resize(cols: number, rows: number): void;
+  /**
+   * Waits for the process to exit. Remaining output can still be read
+   * afterwards.
+   */
+  wait(): Promise<WaitResult>;
+  /**
+   * Sends a signal to the process (default "SIGTERM"; ignored on Windows,
+   * where the process is terminated).
+   */
+  kill(signal?: string): void;
+  /**
+   * Closes the terminal, killing the process if it is still running.
+   */
+  close(): void;
 }
 
 const core = Deno.core;
+const encoder = new TextEncoder();
+
+function byteChunks(read: () => Promise<Uint8Array>): ByteStreamIterator {
+  return {
+    async *[Symbol.asyncIterator](): AsyncGenerator<Uint8Array, void, unknown> {
+      while (true) {
+        const chunk = await read();
+        if (chunk.length === 0) break;
+        yield chunk;
+      }
+    },
+  };
+}
 
 /**
  * Spawns a new child process and returns a handle for interacting with it.
@@ -471,7 +755,13 @@ const core = Deno.core;
  * @see {@link ProcessHandle} for available operations on spawned processes
  */
 export async function spawn(binary: string, opts?: SpawnOptions): Promise<ProcessHandle> {
-  const result = await core.ops.op_process_spawn(binary, opts);
+  const stdinFrom = opts?.stdinFrom;
+  const result = await core.ops.op_process_spawn(
+    binary,
+    stdinFrom === undefined
+      ? opts
+      : { ...opts, stdinFrom: typeof stdinFrom === "string" ? stdinFrom : stdinFrom.id },
+  );
   const handle = result.id;
 
   return {
@@ -492,11 +782,16 @@ export async function spawn(binary: string, opts?: SpawnOptions): Promise<Proces
         running: raw.running,
         exitCode: raw.exit_code ?? undefined,
         signal: raw.signal ?? undefined,
+        timedOut: raw.timed_out,
       };
     },
 
-    async writeStdin(data: string): Promise<void> {
-      return await core.ops.op_process_write_stdin(handle, data);
+    async writeStdin(data: string | Uint8Array): Promise<void> {
+      return await writeStdin(handle, data);
+    },
+
+    closeStdin(): void {
+      core.ops.op_process_close_stdin(handle);
     },
 
     async readStdout(): Promise<ReadOutput> {
@@ -507,6 +802,18 @@ export async function spawn(binary: string, opts?: SpawnOptions): Promise<Proces
       return await core.ops.op_process_read_stderr(handle);
     },
 
+    readStdoutBytes(): Promise<Uint8Array | null> {
+      return readStdoutBytes(handle);
+    },
+
+    readStderrBytes(): Promise<Uint8Array | null> {
+      return readStderrBytes(handle);
+    },
+
+    async waitWithOutput(opts?: OutputOptions): Promise<CapturedOutput> {
+      return await core.ops.op_process_wait_with_output(handle, opts);
+    },
+
     stdout: {
       async *[Symbol.asyncIterator](): AsyncGenerator<string, void, unknown> {
         while (true) {
@@ -526,6 +833,10 @@ export async function spawn(binary: string, opts?: SpawnOptions): Promise<Proces
         }
       },
     },
+
+    stdoutBytes: byteChunks(() => core.ops.op_process_read_stdout_bytes(handle)),
+
+    stderrBytes: byteChunks(() => core.ops.op_process_read_stderr_bytes(handle)),
   };
 }
 
@@ -600,6 +911,7 @@ export async function status(handle: string): Promise<ProcessStatus> {
     running: raw.running,
     exitCode: raw.exit_code ?? undefined,
     signal: raw.signal ?? undefined,
+    timedOut: raw.timed_out,
   };
 }
 
@@ -610,7 +922,7 @@ export async function status(handle: string): Promise<ProcessStatus> {
  * the handle returned by `spawn()`.
  *
  * @param handle - Internal process handle identifier
- * @param data - Data to write to stdin
+ * @param data - Data to write to stdin; strings are written as UTF-8
  *
  * @throws Error (4007) if stdin is closed
  * @throws Error (4008) if stdin was not configured as "piped"
@@ -622,8 +934,24 @@ export async function status(handle: string): Promise<ProcessStatus> {
  * await writeStdin(proc.id, "line1\nline2\npattern line\n");
  * ```
  */
-export async function writeStdin(handle: string, data: string): Promise<void> {
-  return await core.ops.op_process_write_stdin(handle, data);
+export async function writeStdin(handle: string, data: string | Uint8Array): Promise<void> {
+  if (typeof data === "string") {
+    return await core.ops.op_process_write_stdin(handle, data);
+  }
+  return await core.ops.op_process_write_stdin_bytes(handle, data);
+}
+
+/**
+ * Closes a process's standard input by handle ID.
+ *
+ * This is a low-level function. Prefer using `ProcessHandle.closeStdin()`.
+ *
+ * @param handle - Internal process handle identifier
+ *
+ * @throws Error (4006) if process handle is invalid
+ */
+export function closeStdin(handle: string): void {
+  core.ops.op_process_close_stdin(handle);
 }
 
 /**
@@ -673,3 +1001,124 @@ export async function readStdout(handle: string): Promise<ReadOutput> {
 export async function readStderr(handle: string): Promise<ReadOutput> {
   return await core.ops.op_process_read_stderr(handle);
 }
+
+/**
+ * Reads the next chunk of raw bytes from a process's standard output by handle ID.
+ *
+ * This is a low-level function. Prefer using `ProcessHandle.readStdoutBytes()`
+ * or iterating over `ProcessHandle.stdoutBytes`.
+ *
+ * @param handle - Internal process handle identifier
+ * @returns Bytes read, or null at end of stream
+ *
+ * @throws Error (4008) if stdout was not configured as "piped"
+ * @throws Error (4006) if process handle is invalid
+ */
+export async function readStdoutBytes(handle: string): Promise<Uint8Array | null> {
+  const chunk = await core.ops.op_process_read_stdout_bytes(handle);
+  return chunk.length === 0 ? null : chunk;
+}
+
+/**
+ * Reads the next chunk of raw bytes from a process's standard error by handle ID.
+ *
+ * This is a low-level function. Prefer using `ProcessHandle.readStderrBytes()`
+ * or iterating over `ProcessHandle.stderrBytes`.
+ *
+ * @param handle - Internal process handle identifier
+ * @returns Bytes read, or null at end of stream
+ *
+ * @throws Error (4008) if stderr was not configured as "piped"
+ * @throws Error (4006) if process handle is invalid
+ */
+export async function readStderrBytes(handle: string): Promise<Uint8Array | null> {
+  const chunk = await core.ops.op_process_read_stderr_bytes(handle);
+  return chunk.length === 0 ? null : chunk;
+}
+
+/**
+ * Closes stdin and collects a process's output until it exits, by handle ID.
+ *
+ * This is a low-level function. Prefer using `ProcessHandle.waitWithOutput()`.
+ *
+ * @param handle - Internal process handle identifier
+ * @param opts - Per-stream size caps
+ * @returns Exit status and collected output
+ *
+ * @throws Error (4006) if process handle is invalid
+ */
+export async function waitWithOutput(handle: string, opts?: OutputOptions): Promise<CapturedOutput> {
+  return await core.ops.op_process_wait_with_output(handle, opts);
+}
+
+/**
+ * Spawns a process attached to a new pseudo-terminal.
+ *
+ * Use this instead of `spawn()` for interactive programs such as shells,
+ * `ssh`, REPLs and full-screen tools, which behave differently (or hang)
+ * when their stdio is a pipe. Permission checks and the concurrent process
+ * limit are the same as for `spawn()`.
+ *
+ * @param binary - Path to the executable or command name (must be in PATH)
+ * @param opts - Arguments, environment, working directory and terminal size
+ * @returns Handle for reading output, writing input, resizing and waiting
+ *
+ * @throws Error (4001) if permission denied to spawn the binary
+ * @throws Error (4003) if the PTY could not be opened or the process failed to start
+ * @throws Error (4009) if too many processes already spawned
+ *
+ * @example
+ * ```typescript
+ * const pty = await spawnPty("python3", { rows: 40 });
+ * await pty.write("print(6 * 7)\r");
+ * const output = await pty.read();
+ * console.log(new TextDecoder().decode(output!));
+ * pty.resize(100, 40);
+ * pty.kill("SIGINT");
+ * pty.close();
+ * ```
+ */
+export async function spawnPty(binary: string, opts?: PtyOptions): Promise<PtyProcess> {
+  const result = await core.ops.op_process_spawn_pty(binary, opts);
+  const handle = result.id;
+
+  const read = async (): Promise<Uint8Array | null> => {
+    const chunk = await core.ops.op_process_pty_read(handle);
+    return chunk.length === 0 ? null : chunk;
+  };
+
+  return {
+    id: handle,
+    pid: result.pid,
+    read,
+
+    async write(data: Uint8Array | string): Promise<void> {
+      const bytes = typeof data === "string" ? encoder.encode(data) : data;
+      return await core.ops.op_process_pty_write(handle, bytes);
+    },
+
+    resize(cols: number, rows: number): void {
+      core.ops.op_process_pty_resize(handle, cols, rows);
+    },
+
+    async wait(): Promise<WaitResult> {
+      return await core.ops.op_process_pty_wait(handle);
+    },
+
+    kill(signal?: string): void {
+      core.ops.op_process_pty_kill(handle, signal);
+    },
+
+    close(): void {
+      core.ops.op_process_pty_close(handle);
+    },
+
+    async *[Symbol.asyncIterator](): AsyncGenerator<Uint8Array, void, unknown> {
+      while (true) {
+        const chunk = await read();
+        if (chunk === null) break;
+        yield chunk;
+      }
+    },
+  };
+}
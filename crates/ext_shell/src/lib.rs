@@ -169,17 +169,22 @@
 //! - **Quoting**: `'literal'`, `"expansion $VAR"`
 //! - **Globs**: `*.ts`, `**/*.js`, `file[0-9].txt`
 //! - **Background**: `cmd &`
+//! - **Control Flow**: `if`/`elif`/`else`, `for`, `while`, `until`, `case`, `break`, `continue`
+//! - **Grouping**: `{ cmd1; cmd2; }`, `(subshell)`, with redirections on the group
+//! - **Functions**: `name() { ...; }` with `$1`, `$#`, `$@`, `local` and `return`
+//! - **Here-Documents**: `cat <<EOF`, `<<'EOF'` (no expansion), `<<-EOF` (strip tabs)
 //!
 //! ## Built-in Commands
 //!
 //! Cross-platform built-in commands (no external binaries required):
 //!
-//! - **File Operations**: `cat`, `cp`, `mv`, `rm`, `mkdir`, `ls`
+//! - **File Operations**: `cat`, `cp`, `mv`, `rm`, `mkdir`, `ls`, `touch`, `find`
 //! - **Navigation**: `cd`, `pwd`
-//! - **Output**: `echo`
-//! - **Environment**: `export`, `unset`
-//! - **Utilities**: `sleep`, `which`, `exit`
-//! - **Piping**: `head`, `xargs`
+//! - **Output**: `echo`, `printf`
+//! - **Environment**: `export`, `unset`, `env`
+//! - **Utilities**: `sleep`, `which`, `exit`, `test`/`[`
+//! - **Scripting**: `break`, `continue`, `return`, `local`
+//! - **Piping**: `head`, `tail`, `wc`, `grep`, `sort`, `uniq`, `tee`, `xargs`
 //!
 //! Built-ins provide consistent behavior across platforms and don't require
 //! external dependencies.
@@ -276,7 +281,9 @@
 //! - **Command Injection**: Commands are parsed, not passed directly to shell
 //! - **Path Validation**: Paths are checked for existence before operations
 //! - **URL Validation**: URLs must start with http://, https://, or mailto:
-//! - **Capability Checks**: All operations verify permissions before execution
+//! - **Capability Checks**: All operations verify permissions before execution;
+//!   `execute()` also checks every command of the script via
+//!   `ShellCapabilityChecker::can_run_command`
 //! - **Timeout Protection**: Commands can be terminated if they run too long
 //!
 //! ## Testing
@@ -615,6 +622,14 @@ pub trait ShellCapabilityChecker: Send + Sync + 'static {
     fn can_spawn(&self) -> bool {
         true
     }
+
+    /// Check if `op_shell_execute` may run a command
+    ///
+    /// Called for every command in the script: builtins receive `None` as
+    /// `path`, external commands the resolved executable.
+    fn can_run_command(&self, _name: &str, _path: Option<&Path>) -> bool {
+        true
+    }
 }
 
 /// Default capability checker that allows all operations
@@ -739,11 +754,8 @@ impl Default for SpawnedProcessState {
 // ============================================================================
 
 /// Initialize shell state in the OpState
-pub fn init_shell_state<C: ShellCapabilityChecker>(state: &mut OpState, checker: Option<C>) {
-    let checker: Box<dyn ShellCapabilityChecker> = match checker {
-        Some(c) => Box::new(c),
-        None => Box::new(DefaultShellCapabilityChecker),
-    };
+pub fn init_shell_state(state: &mut OpState, checker: Option<Arc<dyn ShellCapabilityChecker>>) {
+    let checker = checker.unwrap_or_else(|| Arc::new(DefaultShellCapabilityChecker));
     state.put(checker);
     state.put(SpawnedProcessState::new());
 }
@@ -762,7 +774,7 @@ pub async fn op_shell_open_external(
     // Check capability
     {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>();
         if !checker.can_open_external() {
             return Err(ShellError::permission_denied(
                 "Opening external URLs is not allowed",
@@ -798,7 +810,7 @@ pub async fn op_shell_open_path(
     // Check capability
     {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>();
         if !checker.can_open_path() {
             return Err(ShellError::permission_denied(
                 "Opening paths is not allowed",
@@ -834,7 +846,7 @@ pub async fn op_shell_show_item_in_folder(
     // Check capability
     {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>();
         if !checker.can_show_item() {
             return Err(ShellError::permission_denied(
                 "Showing items in folder is not allowed",
@@ -907,7 +919,7 @@ pub async fn op_shell_move_to_trash(
     // Check capability
     {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>();
         if !checker.can_trash() {
             return Err(ShellError::permission_denied(
                 "Moving to trash is not allowed",
@@ -987,7 +999,7 @@ pub async fn op_shell_get_file_icon(
     // Check capability
     {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>();
         if !checker.can_get_icon() {
             return Err(ShellError::permission_denied(
                 "Getting file icons is not allowed",
@@ -1043,7 +1055,7 @@ pub async fn op_shell_get_default_app(
     // Check capability
     {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>();
         if !checker.can_open_path() {
             return Err(ShellError::permission_denied(
                 "Querying default apps is not allowed",
@@ -1190,15 +1202,16 @@ pub async fn op_shell_execute(
     #[serde] options: Option<ExecuteOptions>,
 ) -> Result<ExecuteOutput, ShellError> {
     // Check capability
-    {
+    let checker = {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>().clone();
         if !checker.can_execute() {
             return Err(ShellError::permission_denied(
                 "Shell command execution is not allowed",
             ));
         }
-    }
+        checker
+    };
 
     debug!("Executing shell command: {}", command);
 
@@ -1207,8 +1220,12 @@ pub async fn op_shell_execute(
     // Parse the command
     let parsed = parse(&command).map_err(|e| ShellError::parse_error(format!("{:?}", e)))?;
 
-    // Create shell state
-    let shell_state = Rc::new(shell::types::ShellState::new_default());
+    // Create shell state; every command the script runs goes through the checker
+    let mut shell_state = shell::types::ShellState::new_default();
+    shell_state.set_command_filter(Rc::new(move |name: &str, path: Option<&Path>| {
+        checker.can_run_command(name, path)
+    }));
+    let shell_state = Rc::new(shell_state);
 
     // Set working directory
     if let Some(cwd) = options.cwd {
@@ -1291,7 +1308,7 @@ pub async fn op_shell_kill(
     // Check capability
     {
         let state = state.borrow();
-        let checker = state.borrow::<Box<dyn ShellCapabilityChecker>>();
+        let checker = state.borrow::<Arc<dyn ShellCapabilityChecker>>();
         if !checker.can_spawn() {
             return Err(ShellError::permission_denied(
                 "Killing processes is not allowed",
@@ -1433,6 +1450,7 @@ mod tests {
         assert!(checker.can_show_item());
         assert!(checker.can_trash());
         assert!(checker.can_get_icon());
+        assert!(checker.can_run_command("git", Some(Path::new("/usr/bin/git"))));
     }
 
     #[test]
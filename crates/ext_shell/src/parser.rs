@@ -3,6 +3,9 @@
 //! Parses shell command strings into an AST for execution.
 //! Inspired by deno_task_shell but implemented from scratch.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+
 use monch::{ParseError, ParseErrorFailure, ParseResult};
 
 // ============================================================================
@@ -49,6 +52,8 @@ pub struct Pipeline {
 #[derive(Debug, Clone, PartialEq)]
 pub struct PipelineCommand {
     pub inner: Command,
+    /// Redirections of a compound command (simple commands keep their own)
+    pub redirects: Vec<Redirect>,
 }
 
 /// The different types of commands.
@@ -58,8 +63,18 @@ pub enum Command {
     Simple(SimpleCommand),
     /// Subshell: ( commands )
     Subshell(Box<SequentialList>),
-    /// If statement (not yet implemented)
+    /// Brace group: { commands; }
+    BraceGroup(Box<SequentialList>),
+    /// If statement: if ...; then ...; elif ...; else ...; fi
     If(IfClause),
+    /// For loop: for name in words; do ...; done
+    For(ForClause),
+    /// While or until loop: while ...; do ...; done
+    While(WhileClause),
+    /// Case statement: case word in pattern) ...;; esac
+    Case(CaseClause),
+    /// Function definition: name() { ...; }
+    FunctionDef(FunctionDef),
 }
 
 /// A simple command with optional assignments, command name, and args.
@@ -207,11 +222,11 @@ pub enum RedirectTarget {
     File(Word),
     /// Redirect to/from file descriptor
     Fd(u32),
-    /// Here-document content
-    HereDoc(String),
+    /// Here-document content (single-quoted if the delimiter was quoted)
+    HereDoc(Word),
 }
 
-/// If clause (basic support).
+/// If clause; `elif` is an if clause nested in the else part.
 #[derive(Debug, Clone, PartialEq)]
 pub struct IfClause {
     pub condition: Box<SequentialList>,
@@ -219,6 +234,47 @@ pub struct IfClause {
     pub else_part: Option<Box<SequentialList>>,
 }
 
+/// For loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForClause {
+    /// Loop variable
+    pub name: String,
+    /// Words to iterate over (None = the positional parameters)
+    pub items: Option<Vec<Word>>,
+    pub body: Box<SequentialList>,
+}
+
+/// While or until loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhileClause {
+    pub condition: Box<SequentialList>,
+    pub body: Box<SequentialList>,
+    /// Run while the condition fails (`until`)
+    pub until: bool,
+}
+
+/// Case statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseClause {
+    pub word: Word,
+    pub arms: Vec<CaseArm>,
+}
+
+/// One `pattern | pattern) commands ;;` arm of a case statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseArm {
+    pub patterns: Vec<Word>,
+    pub body: SequentialList,
+}
+
+/// Function definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDef {
+    pub name: String,
+    /// Compound command run on each call, with its redirections
+    pub body: Box<PipelineCommand>,
+}
+
 // ============================================================================
 // Parser Implementation
 // ============================================================================
@@ -230,17 +286,23 @@ pub fn parse(input: &str) -> Result<SequentialList, ParseError<'_>> {
         return Ok(SequentialList { items: vec![] });
     }
 
-    match parse_sequential_list(input) {
+    HEREDOCS.with(|docs| docs.borrow_mut().push(scan_heredocs(input)));
+    let result = match parse_sequential_list(input) {
         Ok(("", result)) => Ok(result),
         Ok((remaining, _)) => Err(ParseError::Failure(ParseErrorFailure::new(
             remaining,
             format!("Unexpected trailing input: '{}'", remaining),
         ))),
         Err(e) => Err(e),
-    }
+    };
+    HEREDOCS.with(|docs| docs.borrow_mut().pop());
+    result
 }
 
 /// Parse a sequential list (commands separated by ; or newlines, or connected by && ||).
+///
+/// The list ends at input that closes an enclosing construct: `)`, `;;`,
+/// or a reserved word such as `then`, `do`, `done`, `fi`, `esac` or `}`.
 fn parse_sequential_list(input: &str) -> ParseResult<'_, SequentialList> {
     let input = skip_whitespace(input);
     if input.is_empty() {
@@ -252,13 +314,22 @@ fn parse_sequential_list(input: &str) -> ParseResult<'_, SequentialList> {
 
     loop {
         let remaining_trimmed = skip_whitespace(remaining);
-        if remaining_trimmed.is_empty() {
+        if remaining_trimmed.is_empty() || at_list_end(remaining_trimmed) {
+            remaining = remaining_trimmed;
             break;
         }
 
-        // Skip leading semicolons or newlines
-        if remaining_trimmed.starts_with(';') || remaining_trimmed.starts_with('\n') {
-            remaining = &remaining_trimmed[1..];
+        // Skip leading semicolons, newlines and comments
+        if let Some(rest) = remaining_trimmed.strip_prefix(';') {
+            remaining = rest;
+            continue;
+        }
+        if remaining_trimmed.starts_with('\n') {
+            remaining = skip_newline(remaining_trimmed);
+            continue;
+        }
+        if remaining_trimmed.starts_with('#') {
+            remaining = skip_comment(remaining_trimmed);
             continue;
         }
 
@@ -269,15 +340,19 @@ fn parse_sequential_list(input: &str) -> ParseResult<'_, SequentialList> {
                 remaining = skip_whitespace(rest);
 
                 // Check for separator
-                if remaining.starts_with(';') || remaining.starts_with('\n') {
+                if remaining.starts_with(';') && !remaining.starts_with(";;") {
                     remaining = &remaining[1..];
-                } else if remaining.is_empty()
-                    || remaining.starts_with(')')
-                    || remaining.starts_with('}')
-                {
+                } else if remaining.starts_with('\n') {
+                    remaining = skip_newline(remaining);
+                } else if remaining.starts_with('#') {
+                    remaining = skip_comment(remaining);
+                } else if remaining.is_empty() || at_list_end(remaining) {
                     break;
                 }
             }
+            Err(ParseError::Failure(e)) if items.is_empty() => {
+                return Err(ParseError::Failure(e));
+            }
             Err(_) if items.is_empty() => {
                 return Err(ParseError::Failure(ParseErrorFailure::new(
                     remaining_trimmed,
@@ -300,7 +375,7 @@ fn parse_sequence(input: &str) -> ParseResult<'_, Sequence> {
 
     // Check for && or ||
     let (remaining, next) = if let Some(stripped) = remaining.strip_prefix("&&") {
-        let remaining = skip_whitespace(stripped);
+        let remaining = skip_linebreaks(stripped);
         let (remaining, next_seq) = parse_sequence(remaining)?;
         (
             remaining,
@@ -310,7 +385,7 @@ fn parse_sequence(input: &str) -> ParseResult<'_, Sequence> {
             })),
         )
     } else if let Some(stripped) = remaining.strip_prefix("||") {
-        let remaining = skip_whitespace(stripped);
+        let remaining = skip_linebreaks(stripped);
         let (remaining, next_seq) = parse_sequence(remaining)?;
         (
             remaining,
@@ -354,7 +429,7 @@ fn parse_pipeline(input: &str) -> ParseResult<'_, Pipeline> {
         let rest = skip_whitespace(rest);
         if let Some(stripped) = rest.strip_prefix('|') {
             if !stripped.starts_with('|') {
-                remaining = skip_whitespace(stripped);
+                remaining = skip_linebreaks(stripped);
             } else {
                 remaining = rest;
                 break;
@@ -372,20 +447,56 @@ fn parse_pipeline(input: &str) -> ParseResult<'_, Pipeline> {
 fn parse_pipeline_command(input: &str) -> ParseResult<'_, PipelineCommand> {
     let input = skip_whitespace(input);
 
-    // Check for subshell
-    if input.starts_with('(') {
-        let (remaining, cmd) = parse_subshell(input)?;
-        return Ok((remaining, PipelineCommand { inner: cmd }));
+    let (remaining, inner) = match parse_compound_command(input) {
+        Ok(result) => result,
+        Err(ParseError::Backtrace) => match parse_function_def(input) {
+            Ok(result) => result,
+            Err(ParseError::Backtrace) => {
+                // Parse simple command
+                let (remaining, simple) = parse_simple_command(input)?;
+                return Ok((
+                    remaining,
+                    PipelineCommand {
+                        inner: Command::Simple(simple),
+                        redirects: vec![],
+                    },
+                ));
+            }
+            Err(e) => return Err(e),
+        },
+        Err(e) => return Err(e),
+    };
+
+    // Redirections after a compound command apply to all of it
+    let mut redirects = Vec::new();
+    let mut remaining = remaining;
+    while let Ok((rest, redirect)) = parse_redirect(remaining) {
+        redirects.push(redirect);
+        remaining = rest;
     }
 
-    // Parse simple command
-    let (remaining, simple) = parse_simple_command(input)?;
-    Ok((
-        remaining,
-        PipelineCommand {
-            inner: Command::Simple(simple),
-        },
-    ))
+    Ok((remaining, PipelineCommand { inner, redirects }))
+}
+
+/// Parse a compound command: subshell, brace group, if, loop or case.
+fn parse_compound_command(input: &str) -> ParseResult<'_, Command> {
+    if input.starts_with('(') {
+        parse_subshell(input)
+    } else if let Some(rest) = strip_keyword(input, "{") {
+        parse_brace_group(rest)
+    } else if let Some(rest) = strip_keyword(input, "if") {
+        parse_if(rest)
+    } else if let Some(rest) = strip_keyword(input, "for") {
+        parse_for(rest)
+    } else if let Some(rest) = strip_keyword(input, "while") {
+        parse_while(rest, false)
+    } else if let Some(rest) = strip_keyword(input, "until") {
+        parse_while(rest, true)
+    } else if let Some(rest) = strip_keyword(input, "case") {
+        parse_case(rest)
+    } else {
+        Err(ParseError::Backtrace)
+    }
 }
 
 /// Parse a subshell: ( commands )
@@ -408,6 +519,212 @@ fn parse_subshell(input: &str) -> ParseResult<'_, Command> {
     Ok((&remaining[1..], Command::Subshell(Box::new(list))))
 }
 
+/// Parse a brace group after its `{`: commands; }
+fn parse_brace_group(input: &str) -> ParseResult<'_, Command> {
+    let (remaining, list) = parse_sequential_list(input)?;
+    let remaining = expect_keyword(remaining, "}")?;
+    Ok((remaining, Command::BraceGroup(Box::new(list))))
+}
+
+/// Parse an if statement after its `if`.
+fn parse_if(input: &str) -> ParseResult<'_, Command> {
+    let (remaining, condition) = parse_sequential_list(input)?;
+    let remaining = expect_keyword(remaining, "then")?;
+    let (remaining, then_part) = parse_sequential_list(remaining)?;
+    let remaining = skip_separators(remaining);
+
+    let (remaining, else_part) = if let Some(rest) = strip_keyword(remaining, "elif") {
+        // The nested if consumes the shared `fi`
+        let (remaining, nested) = parse_if(rest)?;
+        let clause = IfClause {
+            condition: Box::new(condition),
+            then_part: Box::new(then_part),
+            else_part: Some(Box::new(single_command_list(nested))),
+        };
+        return Ok((remaining, Command::If(clause)));
+    } else if let Some(rest) = strip_keyword(remaining, "else") {
+        let (remaining, list) = parse_sequential_list(rest)?;
+        (remaining, Some(Box::new(list)))
+    } else {
+        (remaining, None)
+    };
+
+    let remaining = expect_keyword(remaining, "fi")?;
+    Ok((
+        remaining,
+        Command::If(IfClause {
+            condition: Box::new(condition),
+            then_part: Box::new(then_part),
+            else_part,
+        }),
+    ))
+}
+
+/// Parse a for loop after its `for`: name [in words]; do commands; done
+fn parse_for(input: &str) -> ParseResult<'_, Command> {
+    let input = skip_whitespace(input);
+    let (remaining, name) = parse_name(input).ok_or_else(|| {
+        ParseError::Failure(ParseErrorFailure::new(
+            input,
+            "Expected variable name after 'for'",
+        ))
+    })?;
+
+    let remaining = skip_linebreaks(remaining);
+    let (remaining, items) = match strip_keyword(remaining, "in") {
+        Some(rest) => {
+            let mut items = Vec::new();
+            let mut remaining = skip_whitespace(rest);
+            while let Ok((rest, word)) = parse_word(remaining) {
+                items.push(word);
+                remaining = skip_whitespace(rest);
+            }
+            (remaining, Some(items))
+        }
+        None => (remaining, None),
+    };
+
+    let remaining = expect_keyword(remaining, "do")?;
+    let (remaining, body) = parse_sequential_list(remaining)?;
+    let remaining = expect_keyword(remaining, "done")?;
+
+    Ok((
+        remaining,
+        Command::For(ForClause {
+            name: name.to_string(),
+            items,
+            body: Box::new(body),
+        }),
+    ))
+}
+
+/// Parse a while or until loop after its keyword: commands; do commands; done
+fn parse_while(input: &str, until: bool) -> ParseResult<'_, Command> {
+    let (remaining, condition) = parse_sequential_list(input)?;
+    let remaining = expect_keyword(remaining, "do")?;
+    let (remaining, body) = parse_sequential_list(remaining)?;
+    let remaining = expect_keyword(remaining, "done")?;
+
+    Ok((
+        remaining,
+        Command::While(WhileClause {
+            condition: Box::new(condition),
+            body: Box::new(body),
+            until,
+        }),
+    ))
+}
+
+/// Parse a case statement after its `case`: word in [(]pattern[|pattern]) commands;; ... esac
+fn parse_case(input: &str) -> ParseResult<'_, Command> {
+    let input = skip_whitespace(input);
+    let (remaining, word) = parse_word(input).map_err(|_| {
+        ParseError::Failure(ParseErrorFailure::new(input, "Expected word after 'case'"))
+    })?;
+    let mut remaining = expect_keyword(remaining, "in")?;
+
+    let mut arms = Vec::new();
+    loop {
+        remaining = skip_separators(remaining);
+        if let Some(rest) = strip_keyword(remaining, "esac") {
+            return Ok((rest, Command::Case(CaseClause { word, arms })));
+        }
+        if remaining.is_empty() {
+            return Err(ParseError::Failure(ParseErrorFailure::new(
+                input,
+                "Expected 'esac'",
+            )));
+        }
+
+        // Patterns, with an optional leading (
+        if let Some(rest) = remaining.strip_prefix('(') {
+            remaining = skip_whitespace(rest);
+        }
+        let mut patterns = Vec::new();
+        loop {
+            let (rest, pattern) = parse_word(remaining).map_err(|_| {
+                ParseError::Failure(ParseErrorFailure::new(remaining, "Expected case pattern"))
+            })?;
+            patterns.push(pattern);
+            let rest = skip_whitespace(rest);
+            if let Some(rest) = rest.strip_prefix('|') {
+                remaining = skip_whitespace(rest);
+                continue;
+            }
+            remaining = rest.strip_prefix(')').ok_or_else(|| {
+                ParseError::Failure(ParseErrorFailure::new(
+                    rest,
+                    "Expected ')' after case pattern",
+                ))
+            })?;
+            break;
+        }
+
+        let (rest, body) = parse_sequential_list(remaining)?;
+        arms.push(CaseArm { patterns, body });
+
+        // The last arm may leave out its ;;
+        let rest = skip_separators(rest);
+        remaining = rest.strip_prefix(";;").unwrap_or(rest);
+    }
+}
+
+/// Parse a function definition: `name() body` or `function name [()] body`.
+///
+/// The body must be a compound command, usually a brace group.
+fn parse_function_def(input: &str) -> ParseResult<'_, Command> {
+    let (remaining, name) = if let Some(rest) = strip_keyword(input, "function") {
+        let rest = skip_whitespace(rest);
+        let (rest, name) = parse_function_name(rest).ok_or_else(|| {
+            ParseError::Failure(ParseErrorFailure::new(rest, "Expected function name"))
+        })?;
+        let rest = skip_whitespace(rest);
+        (rest.strip_prefix("()").unwrap_or(rest), name)
+    } else {
+        let (rest, name) = parse_function_name(input).ok_or(ParseError::Backtrace)?;
+        let rest = skip_whitespace(rest)
+            .strip_prefix('(')
+            .ok_or(ParseError::Backtrace)?;
+        let rest = skip_whitespace(rest)
+            .strip_prefix(')')
+            .ok_or(ParseError::Backtrace)?;
+        (rest, name)
+    };
+
+    let remaining = skip_linebreaks(remaining);
+    let (remaining, body) = parse_pipeline_command(remaining)?;
+    if matches!(body.inner, Command::Simple(_)) {
+        return Err(ParseError::Failure(ParseErrorFailure::new(
+            input,
+            "Expected a compound command as the function body",
+        )));
+    }
+
+    Ok((
+        remaining,
+        Command::FunctionDef(FunctionDef {
+            name: name.to_string(),
+            body: Box::new(body),
+        }),
+    ))
+}
+
+/// Wrap a single command in a list.
+fn single_command_list(command: Command) -> SequentialList {
+    SequentialList {
+        items: vec![Sequence {
+            current: Pipeline {
+                commands: vec![PipelineCommand {
+                    inner: command,
+                    redirects: vec![],
+                }],
+                negated: false,
+            },
+            next: None,
+        }],
+    }
+}
+
 /// Parse a simple command.
 fn parse_simple_command(input: &str) -> ParseResult<'_, SimpleCommand> {
     let mut env_vars = Vec::new();
@@ -576,11 +893,18 @@ fn parse_word(input: &str) -> ParseResult<'_, Word> {
                 parts.push(WordPart::Glob(GlobPart::Question));
                 remaining = &remaining[1..];
             }
-            '[' => {
-                let (rest, class) = parse_char_class(remaining)?;
-                parts.push(WordPart::Glob(GlobPart::CharClass(class)));
-                remaining = rest;
-            }
+            '[' => match parse_char_class(remaining) {
+                Ok((rest, class)) => {
+                    parts.push(WordPart::Glob(GlobPart::CharClass(class)));
+                    remaining = rest;
+                }
+                // Not a class (e.g. the `[` command): a literal bracket
+                Err(ParseError::Backtrace) => {
+                    parts.push(WordPart::Text("[".to_string()));
+                    remaining = &remaining[1..];
+                }
+                Err(e) => return Err(e),
+            },
             // Backtick command substitution
             '`' => {
                 let (rest, cmd) = parse_backtick_substitution(remaining)?;
@@ -628,22 +952,41 @@ fn parse_double_quoted(input: &str) -> ParseResult<'_, Vec<WordPart>> {
         return Err(ParseError::Backtrace);
     }
 
+    parse_expandable_text(&input[1..], Some('"')).map_err(|e| match e {
+        ParseError::Failure(_) => {
+            ParseError::Failure(ParseErrorFailure::new(input, "Unterminated double quote"))
+        }
+        e => e,
+    })
+}
+
+/// Parse text with `$` and backtick expansions, up to a closing quote.
+///
+/// Without a quote (here-document bodies) the whole input is parsed and
+/// `\"` is not an escape.
+fn parse_expandable_text(input: &str, quote: Option<char>) -> ParseResult<'_, Vec<WordPart>> {
     let mut parts = Vec::new();
-    let mut remaining = &input[1..];
+    let mut remaining = input;
     let mut current_text = String::new();
 
     loop {
         if remaining.is_empty() {
-            return Err(ParseError::Failure(ParseErrorFailure::new(
-                input,
-                "Unterminated double quote",
-            )));
+            if quote.is_some() {
+                return Err(ParseError::Failure(ParseErrorFailure::new(
+                    input,
+                    "Unterminated double quote",
+                )));
+            }
+            if !current_text.is_empty() {
+                parts.push(WordPart::Text(current_text));
+            }
+            return Ok((remaining, parts));
         }
 
         let first = remaining.chars().next().unwrap();
 
         match first {
-            '"' => {
+            c if Some(c) == quote => {
                 if !current_text.is_empty() {
                     parts.push(WordPart::Text(current_text));
                 }
@@ -654,6 +997,11 @@ fn parse_double_quoted(input: &str) -> ParseResult<'_, Vec<WordPart>> {
                     let escaped = remaining.chars().nth(1).unwrap();
                     // In double quotes, only certain escapes are special
                     match escaped {
+                        '"' if quote.is_none() => {
+                            current_text.push('\\');
+                            current_text.push(escaped);
+                            remaining = &remaining[2..];
+                        }
                         '"' | '\\' | '$' | '`' | '\n' => {
                             current_text.push(escaped);
                             remaining = &remaining[2..];
@@ -876,6 +1224,9 @@ fn parse_tilde(input: &str) -> ParseResult<'_, WordPart> {
 }
 
 /// Parse character class [...].
+///
+/// A `[` without a closing `]` in the same word is not a class, and
+/// backtracks so the caller can treat it as literal text.
 fn parse_char_class(input: &str) -> ParseResult<'_, String> {
     if !input.starts_with('[') {
         return Err(ParseError::Backtrace);
@@ -899,6 +1250,9 @@ fn parse_char_class(input: &str) -> ParseResult<'_, String> {
     }
 
     for c in chars {
+        if c.is_whitespace() || is_special_char(c) {
+            break;
+        }
         end += c.len_utf8();
         if c == ']' {
             found_end = true;
@@ -907,10 +1261,7 @@ fn parse_char_class(input: &str) -> ParseResult<'_, String> {
     }
 
     if !found_end {
-        return Err(ParseError::Failure(ParseErrorFailure::new(
-            input,
-            "Unterminated character class",
-        )));
+        return Err(ParseError::Backtrace);
     }
 
     let class = &content[..end - 1]; // Exclude closing ]
@@ -964,6 +1315,9 @@ fn parse_redirect(input: &str) -> ParseResult<'_, Redirect> {
         (None, input)
     };
 
+    // Here-document bodies are looked up by the position of the operator
+    let op_position = remaining.len();
+
     // Parse redirect operator (order matters - check longer patterns first)
     let (op, remaining) = if let Some(r) = remaining.strip_prefix("<<<") {
         (RedirectOp::HereString, r)
@@ -1016,9 +1370,31 @@ fn parse_redirect(input: &str) -> ParseResult<'_, Redirect> {
             }
         }
         RedirectOp::HereDoc => {
-            // TODO: Proper here-doc parsing
-            let (rest, word) = parse_word(remaining)?;
-            (rest, RedirectTarget::File(word))
+            // `<<-` strips leading tabs, which scan_heredocs already did
+            let delimiter = skip_whitespace(remaining.strip_prefix('-').unwrap_or(remaining));
+            let (rest, _) = parse_word(delimiter)?;
+            let body = here_doc_body(op_position).ok_or_else(|| {
+                ParseError::Failure(ParseErrorFailure::new(input, "Missing here-document body"))
+            })?;
+            let word = if body.quoted {
+                Word {
+                    parts: vec![WordPart::SingleQuoted(body.text)],
+                }
+            } else {
+                let parts = match parse_expandable_text(&body.text, None) {
+                    Ok((_, parts)) => parts,
+                    Err(_) => {
+                        return Err(ParseError::Failure(ParseErrorFailure::new(
+                            input,
+                            "Invalid here-document body",
+                        )))
+                    }
+                };
+                Word {
+                    parts: vec![WordPart::DoubleQuoted(parts)],
+                }
+            };
+            (rest, RedirectTarget::HereDoc(word))
         }
         RedirectOp::HereString => {
             let (rest, word) = parse_word(remaining)?;
@@ -1033,6 +1409,168 @@ fn parse_redirect(input: &str) -> ParseResult<'_, Redirect> {
     Ok((remaining, Redirect { fd, op, target }))
 }
 
+// ============================================================================
+// Here-Documents
+// ============================================================================
+
+/// Here-document bodies of an input being parsed.
+///
+/// Bodies follow the line of their command, so they are collected before
+/// parsing. Every slice the parser sees is a suffix of its input, so
+/// positions are recorded as the remaining length.
+#[derive(Debug, Default)]
+struct HereDocs {
+    /// Body of each here-document, by the position of its `<<`
+    bodies: HashMap<usize, HereDocBody>,
+    /// For newlines followed by bodies, where parsing resumes
+    resume: HashMap<usize, usize>,
+}
+
+/// The body of one here-document.
+#[derive(Debug, Clone)]
+struct HereDocBody {
+    text: String,
+    /// The delimiter was quoted, so the body is not expanded
+    quoted: bool,
+}
+
+thread_local! {
+    /// Here-documents of the inputs being parsed; substitutions nest
+    static HEREDOCS: RefCell<Vec<HereDocs>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Look up the body of the here-document whose `<<` is at `position`.
+fn here_doc_body(position: usize) -> Option<HereDocBody> {
+    HEREDOCS.with(|docs| {
+        docs.borrow()
+            .last()
+            .and_then(|docs| docs.bodies.get(&position).cloned())
+    })
+}
+
+/// Find the here-documents of an input and collect their bodies.
+fn scan_heredocs(input: &str) -> HereDocs {
+    let mut docs = HereDocs::default();
+    // (position, delimiter, strip leading tabs, quoted) of each `<<` on the current line
+    let mut pending: Vec<(usize, String, bool, bool)> = Vec::new();
+    let bytes = input.as_bytes();
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(b'\''), _) => {}
+            (_, b'\\') => i += 1,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(c),
+            (None, b'#')
+                if i == 0
+                    || bytes[i - 1].is_ascii_whitespace()
+                    || b";&|()".contains(&bytes[i - 1]) =>
+            {
+                while i + 1 < bytes.len() && bytes[i + 1] != b'\n' {
+                    i += 1;
+                }
+            }
+            (None, b'<') if input[i..].starts_with("<<<") => i += 2,
+            (None, b'<') if input[i..].starts_with("<<") => {
+                let position = input.len() - i;
+                let mut j = i + 2;
+                let strip_tabs = bytes.get(j) == Some(&b'-');
+                if strip_tabs {
+                    j += 1;
+                }
+                while matches!(bytes.get(j), Some(b' ' | b'\t')) {
+                    j += 1;
+                }
+                let (delimiter, quoted, len) = read_delimiter(&input[j..]);
+                pending.push((position, delimiter, strip_tabs, quoted));
+                i = j + len;
+                continue;
+            }
+            (None, b'\n') if !pending.is_empty() => {
+                // The bodies follow this line, one after another
+                let mut start = i + 1;
+                for (position, delimiter, strip_tabs, quoted) in pending.drain(..) {
+                    let mut text = String::new();
+                    while start < input.len() {
+                        let end = input[start..].find('\n').map_or(input.len(), |n| start + n);
+                        let line = &input[start..end];
+                        let line = if strip_tabs {
+                            line.trim_start_matches('\t')
+                        } else {
+                            line
+                        };
+                        start = (end + 1).min(input.len());
+                        if line == delimiter {
+                            break;
+                        }
+                        text.push_str(line);
+                        text.push('\n');
+                    }
+                    docs.bodies.insert(position, HereDocBody { text, quoted });
+                }
+                docs.resume.insert(input.len() - i, input.len() - start);
+                i = start;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    // Here-documents on the last line have empty bodies
+    for (position, _, _, quoted) in pending {
+        docs.bodies.insert(
+            position,
+            HereDocBody {
+                text: String::new(),
+                quoted,
+            },
+        );
+    }
+
+    docs
+}
+
+/// Read a here-document delimiter with its quotes removed.
+///
+/// Returns the delimiter, whether any of it was quoted, and its length in the input.
+fn read_delimiter(input: &str) -> (String, bool, usize) {
+    let mut delimiter = String::new();
+    let mut quoted = false;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in input.char_indices() {
+        if escaped {
+            delimiter.push(c);
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => delimiter.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                quoted = true;
+            }
+            (None, '\\') => {
+                escaped = true;
+                quoted = true;
+            }
+            (None, _) if c.is_whitespace() || is_special_char(c) => {
+                return (delimiter, quoted, i);
+            }
+            (None, _) => delimiter.push(c),
+        }
+    }
+
+    (delimiter, quoted, input.len())
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -1042,6 +1580,106 @@ fn skip_whitespace(input: &str) -> &str {
     input.trim_start_matches([' ', '\t'])
 }
 
+/// Skip whitespace, newlines and comments (after `&&`, `||` and `|`).
+fn skip_linebreaks(input: &str) -> &str {
+    let mut remaining = input;
+    loop {
+        remaining = skip_whitespace(remaining);
+        if remaining.starts_with('\n') {
+            remaining = skip_newline(remaining);
+        } else if remaining.starts_with('#') {
+            remaining = skip_comment(remaining);
+        } else {
+            return remaining;
+        }
+    }
+}
+
+/// Skip whitespace, newlines, comments and `;` between parts of a compound command.
+fn skip_separators(input: &str) -> &str {
+    let mut remaining = input;
+    loop {
+        remaining = skip_linebreaks(remaining);
+        if remaining.starts_with(';') && !remaining.starts_with(";;") {
+            remaining = &remaining[1..];
+        } else {
+            return remaining;
+        }
+    }
+}
+
+/// Skip a newline, and any here-document bodies that start after it.
+fn skip_newline(input: &str) -> &str {
+    let resume = HEREDOCS.with(|docs| {
+        docs.borrow()
+            .last()
+            .and_then(|docs| docs.resume.get(&input.len()).copied())
+    });
+    match resume {
+        Some(len) if len < input.len() => &input[input.len() - len..],
+        _ => &input[1..],
+    }
+}
+
+/// Skip a comment up to (not including) the end of the line.
+fn skip_comment(input: &str) -> &str {
+    &input[input.find('\n').unwrap_or(input.len())..]
+}
+
+/// Strip a reserved word, if the input starts with it as a whole word.
+fn strip_keyword<'a>(input: &'a str, word: &str) -> Option<&'a str> {
+    let rest = input.strip_prefix(word)?;
+    match rest.chars().next() {
+        None => Some(rest),
+        Some(c) if c.is_whitespace() || is_special_char(c) => Some(rest),
+        Some(_) => None,
+    }
+}
+
+/// Expect a reserved word, after any separators.
+fn expect_keyword<'a>(input: &'a str, word: &str) -> Result<&'a str, ParseError<'a>> {
+    let input = skip_separators(input);
+    strip_keyword(input, word).ok_or_else(|| {
+        ParseError::Failure(ParseErrorFailure::new(
+            input,
+            format!("Expected '{}'", word),
+        ))
+    })
+}
+
+/// Check if the input closes an enclosing compound command.
+fn at_list_end(input: &str) -> bool {
+    input.starts_with(')')
+        || input.starts_with(";;")
+        || ["then", "elif", "else", "fi", "do", "done", "esac", "}"]
+            .iter()
+            .any(|word| strip_keyword(input, word).is_some())
+}
+
+/// Parse a variable name.
+fn parse_name(input: &str) -> Option<(&str, &str)> {
+    let first = input.chars().next()?;
+    if !first.is_ascii_alphabetic() && first != '_' {
+        return None;
+    }
+    let end = input
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(input.len());
+    Some((&input[end..], &input[..end]))
+}
+
+/// Parse a function name (a variable name that may also contain `-`).
+fn parse_function_name(input: &str) -> Option<(&str, &str)> {
+    let first = input.chars().next()?;
+    if !first.is_ascii_alphabetic() && first != '_' {
+        return None;
+    }
+    let end = input
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-')
+        .unwrap_or(input.len());
+    Some((&input[end..], &input[..end]))
+}
+
 /// Check if char is a special shell character.
 fn is_special_char(c: char) -> bool {
     matches!(c, '|' | '&' | ';' | '(' | ')' | '<' | '>' | '\n' | '#')
@@ -1211,4 +1849,197 @@ mod tests {
         };
         assert_eq!(cmd.args.len(), 2);
     }
+
+    #[test]
+    fn test_bracket_command() {
+        let result = parse("[ -f x ] && ls [ab].rs").unwrap();
+        let first = match &result.items[0].current.commands[0].inner {
+            Command::Simple(s) => s,
+            _ => panic!("Expected simple command"),
+        };
+        assert_eq!(first.args.len(), 4);
+        assert_eq!(first.args[0].parts, vec![WordPart::Text("[".to_string())]);
+
+        let second = match &result.items[0]
+            .next
+            .as_ref()
+            .unwrap()
+            .sequence
+            .current
+            .commands[0]
+            .inner
+        {
+            Command::Simple(s) => s,
+            _ => panic!("Expected simple command"),
+        };
+        assert!(matches!(
+            &second.args[1].parts[0],
+            WordPart::Glob(GlobPart::CharClass(c)) if c == "ab"
+        ));
+    }
+
+    #[test]
+    fn test_if_elif_else() {
+        let result = parse("if a; then b; elif c\nthen d\nelse e; fi").unwrap();
+        let if_clause = match &result.items[0].current.commands[0].inner {
+            Command::If(i) => i,
+            _ => panic!("Expected if clause"),
+        };
+        assert_eq!(if_clause.then_part.items.len(), 1);
+        let elif = match &if_clause.else_part.as_ref().unwrap().items[0]
+            .current
+            .commands[0]
+            .inner
+        {
+            Command::If(i) => i,
+            _ => panic!("Expected nested if for elif"),
+        };
+        assert!(elif.else_part.is_some());
+    }
+
+    #[test]
+    fn test_for_loop() {
+        let result = parse("for f in a.txt *.rs; do\n  echo $f\ndone; echo end").unwrap();
+        assert_eq!(result.items.len(), 2);
+        let for_clause = match &result.items[0].current.commands[0].inner {
+            Command::For(f) => f,
+            _ => panic!("Expected for loop"),
+        };
+        assert_eq!(for_clause.name, "f");
+        assert_eq!(for_clause.items.as_ref().unwrap().len(), 2);
+        assert_eq!(for_clause.body.items.len(), 1);
+
+        let result = parse("for arg do echo $arg; done").unwrap();
+        assert!(matches!(
+            &result.items[0].current.commands[0].inner,
+            Command::For(f) if f.items.is_none()
+        ));
+    }
+
+    #[test]
+    fn test_while_until() {
+        let result = parse("while test -f lock; do sleep 1; done").unwrap();
+        assert!(matches!(
+            &result.items[0].current.commands[0].inner,
+            Command::While(w) if !w.until && w.body.items.len() == 1
+        ));
+
+        let result = parse("until false\ndo\n  break\ndone").unwrap();
+        assert!(matches!(
+            &result.items[0].current.commands[0].inner,
+            Command::While(w) if w.until
+        ));
+
+        assert!(parse("while true; do echo").is_err());
+    }
+
+    #[test]
+    fn test_case() {
+        let result = parse(
+            "case $x in\n  a|b) echo ab ;;\n  *.rs)\n    echo rust\n    ;;\n  *) echo other\nesac",
+        )
+        .unwrap();
+        let case_clause = match &result.items[0].current.commands[0].inner {
+            Command::Case(c) => c,
+            _ => panic!("Expected case statement"),
+        };
+        assert_eq!(case_clause.arms.len(), 3);
+        assert_eq!(case_clause.arms[0].patterns.len(), 2);
+        assert_eq!(case_clause.arms[1].body.items.len(), 1);
+        assert!(matches!(
+            case_clause.arms[2].patterns[0].parts[0],
+            WordPart::Glob(GlobPart::Star)
+        ));
+    }
+
+    #[test]
+    fn test_function_definition() {
+        let result = parse("greet() {\n  echo hello $1\n}\ngreet world").unwrap();
+        assert_eq!(result.items.len(), 2);
+        let function = match &result.items[0].current.commands[0].inner {
+            Command::FunctionDef(f) => f,
+            _ => panic!("Expected function definition"),
+        };
+        assert_eq!(function.name, "greet");
+        assert!(matches!(function.body.inner, Command::BraceGroup(_)));
+
+        let result = parse("function build-all ( make )").unwrap();
+        assert!(matches!(
+            &result.items[0].current.commands[0].inner,
+            Command::FunctionDef(f) if f.name == "build-all"
+        ));
+    }
+
+    #[test]
+    fn test_brace_group_redirect() {
+        let result = parse("{ echo a; echo b; } > out.txt 2>&1 | cat").unwrap();
+        let group = &result.items[0].current.commands[0];
+        assert!(matches!(&group.inner, Command::BraceGroup(l) if l.items.len() == 2));
+        assert_eq!(group.redirects.len(), 2);
+        assert_eq!(result.items[0].current.commands.len(), 2);
+
+        // `{` is only a keyword as a separate word
+        let result = parse("echo {a}").unwrap();
+        assert!(matches!(
+            &result.items[0].current.commands[0].inner,
+            Command::Simple(_)
+        ));
+    }
+
+    #[test]
+    fn test_heredoc() {
+        let result =
+            parse("cat <<EOF | wc -l; echo after\nhello $USER\n  two\nEOF\necho next").unwrap();
+        assert_eq!(result.items.len(), 3);
+        let cmd = match &result.items[0].current.commands[0].inner {
+            Command::Simple(s) => s,
+            _ => panic!("Expected simple command"),
+        };
+        assert_eq!(cmd.redirects[0].op, RedirectOp::HereDoc);
+        let body = match &cmd.redirects[0].target {
+            RedirectTarget::HereDoc(word) => word,
+            _ => panic!("Expected here-document"),
+        };
+        match &body.parts[..] {
+            [WordPart::DoubleQuoted(parts)] => {
+                assert_eq!(parts[0], WordPart::Text("hello ".to_string()));
+                assert_eq!(parts[1], WordPart::Variable("USER".to_string()));
+                assert_eq!(parts[2], WordPart::Text("\n  two\n".to_string()));
+            }
+            parts => panic!("Unexpected body {:?}", parts),
+        }
+    }
+
+    #[test]
+    fn test_heredoc_quoted_and_tabs() {
+        let result = parse("cat <<-'END'\n\t$HOME\n\tEND").unwrap();
+        let cmd = match &result.items[0].current.commands[0].inner {
+            Command::Simple(s) => s,
+            _ => panic!("Expected simple command"),
+        };
+        assert_eq!(
+            cmd.redirects[0].target,
+            RedirectTarget::HereDoc(Word {
+                parts: vec![WordPart::SingleQuoted("$HOME\n".to_string())]
+            })
+        );
+
+        // A here-document inside a loop body
+        let result = parse("for i in 1 2; do\n  cat <<EOF\n$i\nEOF\ndone").unwrap();
+        assert!(matches!(
+            &result.items[0].current.commands[0].inner,
+            Command::For(f) if f.body.items.len() == 1
+        ));
+    }
+
+    #[test]
+    fn test_comments() {
+        let result = parse("# setup\necho a # trailing\n\n# done\necho b#not-a-comment").unwrap();
+        assert_eq!(result.items.len(), 2);
+        let cmd = match &result.items[1].current.commands[0].inner {
+            Command::Simple(s) => s,
+            _ => panic!("Expected simple command"),
+        };
+        assert_eq!(cmd.args.len(), 2);
+    }
 }
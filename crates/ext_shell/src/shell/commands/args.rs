@@ -145,6 +145,113 @@ impl ArgParser {
     }
 }
 
+/// An option found by [`parse_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandOption {
+    /// Short option (e.g., -n), with its value if it takes one
+    Short(char, Option<String>),
+    /// Long option (e.g., --lines), with its value if it takes one
+    Long(String, Option<String>),
+}
+
+/// Options and operands of a command line.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedOptions {
+    /// Options in the order they were given
+    pub options: Vec<CommandOption>,
+    /// Everything that is not an option
+    pub operands: Vec<OsString>,
+}
+
+impl ParsedOptions {
+    /// Check if a short or long flag was given.
+    pub fn has(&self, short: char, long: &str) -> bool {
+        self.options.iter().any(|o| match o {
+            CommandOption::Short(c, _) => *c == short,
+            CommandOption::Long(l, _) => l == long,
+        })
+    }
+
+    /// Get the last value given for an option.
+    pub fn value(&self, short: char, long: &str) -> Option<&str> {
+        self.options.iter().rev().find_map(|o| match o {
+            CommandOption::Short(c, v) if *c == short => v.as_deref(),
+            CommandOption::Long(l, v) if l == long => v.as_deref(),
+            _ => None,
+        })
+    }
+}
+
+/// Parse a command line the way GNU tools do.
+///
+/// Short flags can be combined (`-rn`). Short options listed in
+/// `short_with_value` take a value, attached (`-n5`) or as the next
+/// argument (`-n 5`); long options in `long_with_value` take one as
+/// `--lines=5` or `--lines 5`. Options may follow operands, `--` ends
+/// option parsing and a lone `-` is an operand (stdin).
+pub fn parse_options(
+    args: &[OsString],
+    short_with_value: &str,
+    long_with_value: &[&str],
+) -> Result<ParsedOptions, String> {
+    let mut parsed = ParsedOptions::default();
+    let mut iter = args.iter().skip(1);
+
+    while let Some(arg) = iter.next() {
+        let s = arg.to_string_lossy();
+
+        if s == "--" {
+            parsed.operands.extend(iter.cloned());
+            break;
+        }
+
+        if let Some(long) = s.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let value = if long_with_value.contains(&name) && value.is_none() {
+                match iter.next() {
+                    Some(v) => Some(v.to_string_lossy().to_string()),
+                    None => return Err(format!("option '--{}' requires an argument", name)),
+                }
+            } else {
+                value
+            };
+            parsed
+                .options
+                .push(CommandOption::Long(name.to_string(), value));
+            continue;
+        }
+
+        if s.len() > 1 && s.starts_with('-') {
+            let flags = &s[1..];
+            for (i, c) in flags.char_indices() {
+                if !short_with_value.contains(c) {
+                    parsed.options.push(CommandOption::Short(c, None));
+                    continue;
+                }
+                let rest = &flags[i + c.len_utf8()..];
+                let value = if !rest.is_empty() {
+                    rest.to_string()
+                } else {
+                    match iter.next() {
+                        Some(v) => v.to_string_lossy().to_string(),
+                        None => return Err(format!("option requires an argument -- '{}'", c)),
+                    }
+                };
+                parsed.options.push(CommandOption::Short(c, Some(value)));
+                break;
+            }
+            continue;
+        }
+
+        parsed.operands.push(arg.clone());
+    }
+
+    Ok(parsed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,4 +284,33 @@ mod tests {
         assert!(ArgParser::has_flag(&args, 'f', "force"));
         assert!(!ArgParser::has_flag(&args, 'v', "verbose"));
     }
+
+    #[test]
+    fn test_parse_options() {
+        let args: Vec<OsString> = vec![
+            "sort".into(),
+            "-rn".into(),
+            "-k2".into(),
+            "file.txt".into(),
+            "-t".into(),
+            ",".into(),
+            "--output=out.txt".into(),
+            "--".into(),
+            "-x".into(),
+        ];
+
+        let parsed = parse_options(&args, "kt", &["output"]).unwrap();
+        assert!(parsed.has('r', "reverse"));
+        assert!(parsed.has('n', "numeric-sort"));
+        assert_eq!(parsed.value('k', "key"), Some("2"));
+        assert_eq!(parsed.value('t', "field-separator"), Some(","));
+        assert_eq!(parsed.value('o', "output"), Some("out.txt"));
+        assert_eq!(
+            parsed.operands,
+            vec![OsString::from("file.txt"), OsString::from("-x")]
+        );
+
+        let args: Vec<OsString> = vec!["tail".into(), "-n".into()];
+        assert!(parse_options(&args, "n", &[]).is_err());
+    }
 }
@@ -0,0 +1,90 @@
+//! Break, continue and return command implementations
+//!
+//! Leave loops and functions early.
+
+use super::{ShellCommand, ShellCommandContext};
+use crate::shell::types::{ControlFlow, ExecuteResult, FutureExecuteResult};
+
+/// The `break` command - leaves the innermost N loops.
+pub struct BreakCommand;
+
+/// The `continue` command - starts the next iteration of the Nth enclosing loop.
+pub struct ContinueCommand;
+
+/// The `return` command - returns from a shell function.
+pub struct ReturnCommand;
+
+/// Run `break` or `continue`, which share their argument handling.
+fn loop_control(
+    mut context: ShellCommandContext,
+    name: &str,
+    control: fn(usize) -> ControlFlow,
+) -> ExecuteResult {
+    let levels = match context.args.get(1) {
+        None => 1,
+        Some(arg) => match arg.to_string_lossy().parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => {
+                let _ = context.stderr.write_line(&format!(
+                    "{}: {}: loop count out of range",
+                    name,
+                    arg.to_string_lossy()
+                ));
+                return ExecuteResult::from_exit_code(1);
+            }
+        },
+    };
+
+    let depth = context.state.loop_depth();
+    if depth == 0 {
+        let _ = context.stderr.write_line(&format!(
+            "{}: only meaningful in a `for', `while', or `until' loop",
+            name
+        ));
+        return ExecuteResult::from_exit_code(0);
+    }
+
+    ExecuteResult::Control(control(levels.min(depth)), Vec::new())
+}
+
+impl ShellCommand for BreakCommand {
+    fn execute(&self, context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move { loop_control(context, "break", ControlFlow::Break) })
+    }
+}
+
+impl ShellCommand for ContinueCommand {
+    fn execute(&self, context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move { loop_control(context, "continue", ControlFlow::Continue) })
+    }
+}
+
+impl ShellCommand for ReturnCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            if context.state.function_depth() == 0 {
+                let _ = context
+                    .stderr
+                    .write_line("return: can only `return' from a function");
+                return ExecuteResult::from_exit_code(1);
+            }
+
+            // Default is the exit code of the last command
+            let exit_code = match context.args.get(1) {
+                None => context.state.last_exit_code(),
+                Some(arg) => match arg.to_string_lossy().parse::<i32>() {
+                    Ok(code) => code,
+                    Err(_) => {
+                        let _ = context.stderr.write_line(&format!(
+                            "return: {}: numeric argument required",
+                            arg.to_string_lossy()
+                        ));
+                        2
+                    }
+                },
+            };
+
+            ExecuteResult::Control(ControlFlow::Return(exit_code), Vec::new())
+        })
+    }
+}
@@ -0,0 +1,111 @@
+//! Env command implementation
+//!
+//! Prints the environment, or runs a command in a modified environment.
+
+use std::ffi::OsString;
+
+use super::{ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `env` command - prints or modifies the environment for a command.
+pub struct EnvCommand;
+
+impl ShellCommand for EnvCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let state = context.state.clone();
+            let mut args = context.args.iter().skip(1).peekable();
+
+            // Options end at the first assignment or command name
+            while let Some(arg) = args.peek() {
+                let s = arg.to_string_lossy().to_string();
+                match s.as_str() {
+                    "-i" | "-" | "--ignore-environment" => {
+                        for name in state.env_vars().keys() {
+                            state.unset_var(name);
+                        }
+                    }
+                    "-u" | "--unset" => {
+                        args.next();
+                        match args.peek() {
+                            Some(name) => state.unset_var(name),
+                            None => {
+                                let _ = context
+                                    .stderr
+                                    .write_line("env: option requires an argument -- 'u'");
+                                return ExecuteResult::from_exit_code(125);
+                            }
+                        }
+                    }
+                    "--" => {
+                        args.next();
+                        break;
+                    }
+                    _ if s.starts_with("-u") => state.unset_var(&OsString::from(&s[2..])),
+                    _ if s.starts_with('-') && s.len() > 1 => {
+                        let _ = context
+                            .stderr
+                            .write_line(&format!("env: invalid option '{}'", s));
+                        return ExecuteResult::from_exit_code(125);
+                    }
+                    _ => break,
+                }
+                args.next();
+            }
+
+            // Then NAME=VALUE assignments
+            while let Some(arg) = args.peek() {
+                let s = arg.to_string_lossy();
+                match s.split_once('=') {
+                    Some((name, value)) if !name.is_empty() => {
+                        state.set_env_var(name, value);
+                        args.next();
+                    }
+                    _ => break,
+                }
+            }
+
+            let command: Vec<OsString> = args.cloned().collect();
+
+            if command.is_empty() {
+                let mut vars: Vec<(OsString, OsString)> = state.env_vars().into_iter().collect();
+                vars.sort();
+                for (name, value) in vars {
+                    if context
+                        .stdout
+                        .write_line(&format!(
+                            "{}={}",
+                            name.to_string_lossy(),
+                            value.to_string_lossy()
+                        ))
+                        .is_err()
+                    {
+                        return ExecuteResult::from_exit_code(1);
+                    }
+                }
+                return ExecuteResult::from_exit_code(0);
+            }
+
+            let executor = context.execute_command_args;
+            let sub_context = ShellCommandContext {
+                args: command,
+                state,
+                stdin: context.stdin,
+                stdout: context.stdout,
+                stderr: context.stderr,
+                execute_command_args: Box::new(|_| {
+                    Box::pin(async { ExecuteResult::from_exit_code(0) })
+                }),
+            };
+
+            // The environment change only applies to the command
+            match executor(sub_context).await {
+                ExecuteResult::Exit(code, handles) => ExecuteResult::Exit(code, handles),
+                ExecuteResult::Continue(code, _, handles) => {
+                    ExecuteResult::Continue(code, Vec::new(), handles)
+                }
+                control => control,
+            }
+        })
+    }
+}
@@ -0,0 +1,518 @@
+//! Find command implementation
+//!
+//! Searches directory trees for files matching an expression.
+
+use std::cmp::Ordering;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use glob::{MatchOptions, Pattern};
+
+use super::{ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `find` command - searches directory trees.
+pub struct FindCommand;
+
+/// A parsed find expression.
+#[derive(Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    True,
+    False,
+    /// `-name`/`-iname`: glob against the file name
+    Name(Pattern, bool),
+    /// `-path`/`-ipath`: glob against the whole path
+    Path(Pattern, bool),
+    /// `-type`: f, d, l, p, s, b or c
+    Type(char),
+    Empty,
+    Newer(SystemTime),
+    /// `-size`: comparison, size in units (rounded up), unit in bytes
+    Size(Ordering, u64, u64),
+    Print,
+    Print0,
+    Prune,
+    Delete,
+}
+
+/// Settings that apply to the whole search.
+#[derive(Debug, Default)]
+struct FindOptions {
+    max_depth: Option<usize>,
+    min_depth: usize,
+    /// Visit directory contents before the directory (`-depth`, `-delete`)
+    depth_first: bool,
+}
+
+/// Parses the expression part of the command line.
+struct ExprParser<'a> {
+    args: &'a [String],
+    pos: usize,
+    cwd: &'a Path,
+    options: FindOptions,
+    has_action: bool,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.args.get(self.pos).map(String::as_str)
+    }
+
+    fn value(&mut self, primary: &str) -> Result<String, String> {
+        let value = self
+            .args
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| format!("missing argument to '{}'", primary))?;
+        self.pos += 1;
+        Ok(value)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while matches!(self.peek(), Some("-o" | "-or")) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_not()?;
+        loop {
+            match self.peek() {
+                None | Some(")" | "-o" | "-or") => break,
+                Some("-a" | "-and") => self.pos += 1,
+                // Adjacent primaries are joined by an implicit -a
+                Some(_) => {}
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if matches!(self.peek(), Some("!" | "-not")) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.peek().map(String::from) else {
+            return Err("expected an expression".to_string());
+        };
+        self.pos += 1;
+
+        let pattern = |p: &str| Pattern::new(p).map_err(|e| format!("{}: {}", p, e));
+
+        Ok(match token.as_str() {
+            "(" => {
+                let expr = self.parse_or()?;
+                if self.peek() != Some(")") {
+                    return Err("missing ')'".to_string());
+                }
+                self.pos += 1;
+                expr
+            }
+            "-name" => Expr::Name(pattern(&self.value(&token)?)?, true),
+            "-iname" => Expr::Name(pattern(&self.value(&token)?)?, false),
+            "-path" | "-wholename" => Expr::Path(pattern(&self.value(&token)?)?, true),
+            "-ipath" | "-iwholename" => Expr::Path(pattern(&self.value(&token)?)?, false),
+            "-type" => {
+                let value = self.value(&token)?;
+                match value.as_str() {
+                    "f" | "d" | "l" | "p" | "s" | "b" | "c" => {
+                        Expr::Type(value.chars().next().unwrap())
+                    }
+                    _ => return Err(format!("unknown argument to -type: {}", value)),
+                }
+            }
+            "-maxdepth" | "-mindepth" => {
+                let value = self.value(&token)?;
+                let depth: usize = value
+                    .parse()
+                    .map_err(|_| format!("invalid argument '{}' to '{}'", value, token))?;
+                if token == "-maxdepth" {
+                    self.options.max_depth = Some(depth);
+                } else {
+                    self.options.min_depth = depth;
+                }
+                Expr::True
+            }
+            "-depth" => {
+                self.options.depth_first = true;
+                Expr::True
+            }
+            "-empty" => Expr::Empty,
+            "-newer" => {
+                let file = self.value(&token)?;
+                let modified = fs::metadata(self.cwd.join(&file))
+                    .and_then(|m| m.modified())
+                    .map_err(|e| format!("'{}': {}", file, e))?;
+                Expr::Newer(modified)
+            }
+            "-size" => {
+                let value = self.value(&token)?;
+                parse_size(&value).ok_or_else(|| format!("invalid -size '{}'", value))?
+            }
+            "-true" => Expr::True,
+            "-false" => Expr::False,
+            "-print" | "-print0" | "-delete" => {
+                self.has_action = true;
+                match token.as_str() {
+                    "-print" => Expr::Print,
+                    "-print0" => Expr::Print0,
+                    _ => {
+                        self.options.depth_first = true;
+                        Expr::Delete
+                    }
+                }
+            }
+            "-prune" => Expr::Prune,
+            other => return Err(format!("unknown predicate '{}'", other)),
+        })
+    }
+}
+
+/// Parse `-size [+-]N[cwbkMG]`.
+fn parse_size(value: &str) -> Option<Expr> {
+    let (cmp, rest) = match value.as_bytes().first()? {
+        b'+' => (Ordering::Greater, &value[1..]),
+        b'-' => (Ordering::Less, &value[1..]),
+        _ => (Ordering::Equal, value),
+    };
+    let (digits, unit) = match rest.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&rest[..i], c),
+        _ => (rest, 'b'),
+    };
+    let unit = match unit {
+        'c' => 1,
+        'w' => 2,
+        'b' => 512,
+        'k' => 1024,
+        'M' => 1024 * 1024,
+        'G' => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some(Expr::Size(cmp, digits.parse().ok()?, unit))
+}
+
+/// A file being visited.
+struct Entry<'a> {
+    path: &'a Path,
+    display: &'a str,
+    metadata: &'a Metadata,
+}
+
+/// Walks the tree, evaluating the expression for each file.
+struct Walker<'a, F: FnMut(&[u8])> {
+    expr: &'a Expr,
+    options: &'a FindOptions,
+    output: F,
+    errors: Vec<String>,
+    prune: bool,
+}
+
+impl<F: FnMut(&[u8])> Walker<'_, F> {
+    fn walk(&mut self, path: &Path, display: &str, depth: usize) {
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.errors.push(format!("'{}': {}", display, e));
+                return;
+            }
+        };
+        let entry = Entry {
+            path,
+            display,
+            metadata: &metadata,
+        };
+        let visit = depth >= self.options.min_depth;
+
+        self.prune = false;
+        if visit && !self.options.depth_first {
+            self.eval(self.expr, &entry);
+        }
+
+        let descend = metadata.is_dir()
+            && !self.prune
+            && self.options.max_depth.is_none_or(|max| depth < max);
+        if descend {
+            match fs::read_dir(path) {
+                Ok(entries) => {
+                    let mut children: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+                    children.sort_by_key(|e| e.file_name());
+                    for child in children {
+                        let name = child.file_name();
+                        let child_display = if display.ends_with('/') {
+                            format!("{}{}", display, name.to_string_lossy())
+                        } else {
+                            format!("{}/{}", display, name.to_string_lossy())
+                        };
+                        self.walk(&child.path(), &child_display, depth + 1);
+                    }
+                }
+                Err(e) => self.errors.push(format!("'{}': {}", display, e)),
+            }
+        }
+
+        if visit && self.options.depth_first {
+            self.eval(self.expr, &entry);
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr, entry: &Entry<'_>) -> bool {
+        let match_options = |case_sensitive| MatchOptions {
+            case_sensitive,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+
+        match expr {
+            Expr::And(a, b) => self.eval(a, entry) && self.eval(b, entry),
+            Expr::Or(a, b) => self.eval(a, entry) || self.eval(b, entry),
+            Expr::Not(a) => !self.eval(a, entry),
+            Expr::True => true,
+            Expr::False => false,
+            Expr::Name(pattern, case_sensitive) => {
+                let name = Path::new(entry.display)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| entry.display.to_string());
+                pattern.matches_with(&name, match_options(*case_sensitive))
+            }
+            Expr::Path(pattern, case_sensitive) => {
+                pattern.matches_with(entry.display, match_options(*case_sensitive))
+            }
+            Expr::Type(kind) => file_type_matches(*kind, entry.metadata),
+            Expr::Empty => {
+                if entry.metadata.is_dir() {
+                    fs::read_dir(entry.path)
+                        .map(|mut d| d.next().is_none())
+                        .unwrap_or(false)
+                } else {
+                    entry.metadata.is_file() && entry.metadata.len() == 0
+                }
+            }
+            Expr::Newer(time) => entry
+                .metadata
+                .modified()
+                .map(|m| m > *time)
+                .unwrap_or(false),
+            Expr::Size(cmp, n, unit) => {
+                let size = entry.metadata.len().div_ceil(*unit);
+                size.cmp(n) == *cmp
+            }
+            Expr::Print => {
+                (self.output)(format!("{}\n", entry.display).as_bytes());
+                true
+            }
+            Expr::Print0 => {
+                (self.output)(format!("{}\0", entry.display).as_bytes());
+                true
+            }
+            Expr::Prune => {
+                self.prune = true;
+                true
+            }
+            Expr::Delete => {
+                let result = if entry.metadata.is_dir() {
+                    fs::remove_dir(entry.path)
+                } else {
+                    fs::remove_file(entry.path)
+                };
+                match result {
+                    Ok(_) => true,
+                    Err(e) => {
+                        self.errors
+                            .push(format!("cannot delete '{}': {}", entry.display, e));
+                        false
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn file_type_matches(kind: char, metadata: &Metadata) -> bool {
+    let file_type = metadata.file_type();
+    match kind {
+        'f' => file_type.is_file(),
+        'd' => file_type.is_dir(),
+        'l' => file_type.is_symlink(),
+        #[cfg(unix)]
+        other => {
+            use std::os::unix::fs::FileTypeExt;
+            match other {
+                'p' => file_type.is_fifo(),
+                's' => file_type.is_socket(),
+                'b' => file_type.is_block_device(),
+                'c' => file_type.is_char_device(),
+                _ => false,
+            }
+        }
+        #[cfg(not(unix))]
+        _ => false,
+    }
+}
+
+/// Parse the command line and run the search, sending output to `output`.
+///
+/// Returns the error messages, one per problem.
+fn find(args: &[String], cwd: &Path, output: impl FnMut(&[u8])) -> Result<Vec<String>, String> {
+    // Paths come first, up to the first thing that looks like an expression
+    let split = args
+        .iter()
+        .position(|a| a.starts_with('-') || a == "(" || a == "!")
+        .unwrap_or(args.len());
+    let (paths, expr_args) = args.split_at(split);
+
+    let mut parser = ExprParser {
+        args: expr_args,
+        pos: 0,
+        cwd,
+        options: FindOptions::default(),
+        has_action: false,
+    };
+    let expr = if expr_args.is_empty() {
+        Expr::True
+    } else {
+        let expr = parser.parse_or()?;
+        if let Some(extra) = parser.peek() {
+            return Err(format!("unexpected '{}'", extra));
+        }
+        expr
+    };
+    // Without an action, matching files are printed
+    let expr = if parser.has_action {
+        expr
+    } else {
+        Expr::And(Box::new(expr), Box::new(Expr::Print))
+    };
+
+    let paths: Vec<String> = if paths.is_empty() {
+        vec![".".to_string()]
+    } else {
+        paths.to_vec()
+    };
+
+    let mut walker = Walker {
+        expr: &expr,
+        options: &parser.options,
+        output,
+        errors: Vec::new(),
+        prune: false,
+    };
+    for path in &paths {
+        let full: PathBuf = cwd.join(path);
+        walker.walk(&full, path, 0);
+    }
+    Ok(walker.errors)
+}
+
+impl ShellCommand for FindCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let args: Vec<String> = context.args_str().into_iter().skip(1).collect();
+            let cwd = context.state.cwd();
+
+            let mut stdout = context.stdout.clone();
+            let mut write_failed = false;
+            let result = find(&args, &cwd, |bytes| {
+                if !write_failed && stdout.write_all(bytes).is_err() {
+                    write_failed = true;
+                }
+            });
+
+            match result {
+                Ok(errors) => {
+                    for error in &errors {
+                        let _ = context.stderr.write_line(&format!("find: {}", error));
+                    }
+                    let failed = !errors.is_empty() || write_failed;
+                    ExecuteResult::from_exit_code(if failed { 1 } else { 0 })
+                }
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("find: {}", e));
+                    ExecuteResult::from_exit_code(1)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &str, cwd: &Path) -> Vec<String> {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        let mut out = Vec::new();
+        let errors = find(&args, cwd, |b| out.extend_from_slice(b)).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_find() {
+        let root = std::env::temp_dir().join(format!("forge-find-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("src/nested/Lib.RS"), "").unwrap();
+        fs::write(root.join("target/out.rs"), "x").unwrap();
+        fs::write(root.join("README.md"), "readme").unwrap();
+
+        assert_eq!(
+            run(". -name *.rs", &root),
+            vec!["./src/main.rs", "./target/out.rs"]
+        );
+        assert_eq!(
+            run("src -iname *.rs -type f", &root),
+            vec!["src/main.rs", "src/nested/Lib.RS"]
+        );
+        assert_eq!(
+            run(". -maxdepth 1 -type d", &root),
+            vec![".", "./src", "./target"]
+        );
+        assert_eq!(
+            run(". -path ./target -prune -o -type f -print", &root),
+            vec!["./README.md", "./src/main.rs", "./src/nested/Lib.RS"]
+        );
+        assert_eq!(run(". -empty", &root), vec!["./src/nested/Lib.RS"]);
+        assert_eq!(
+            run(". -type f ! ( -name *.rs -o -name *.RS )", &root),
+            vec!["./README.md"]
+        );
+        assert_eq!(
+            run(". -size +0 -size -2c -type f", &root),
+            vec!["./target/out.rs"]
+        );
+
+        run("target -delete", &root);
+        assert!(!root.join("target").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        let cwd = std::env::temp_dir();
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert!(find(&args(". -bogus"), &cwd, |_| {}).is_err());
+        assert!(find(&args(". -name"), &cwd, |_| {}).is_err());
+        assert!(find(&args(". ( -name x"), &cwd, |_| {}).is_err());
+        assert!(find(&args(". -type q"), &cwd, |_| {}).is_err());
+    }
+}
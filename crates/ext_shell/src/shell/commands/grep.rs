@@ -0,0 +1,437 @@
+//! Grep command implementation
+//!
+//! Prints lines matching a pattern.
+
+use std::fs;
+use std::path::Path;
+
+use glob::Pattern;
+use regex::{Regex, RegexBuilder};
+
+use super::{parse_options, CommandOption, ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult, ShellPipeWriter};
+
+/// The `grep` command - prints lines matching a pattern.
+pub struct GrepCommand;
+
+/// Matching and output options.
+#[derive(Default)]
+struct GrepOptions {
+    invert: bool,
+    line_numbers: bool,
+    count: bool,
+    files_with_matches: bool,
+    files_without_match: bool,
+    only_matching: bool,
+    max_count: Option<usize>,
+    quiet: bool,
+    with_filename: bool,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+/// Translate a POSIX basic regular expression to the `regex` crate's syntax.
+///
+/// In a BRE `( ) { } | + ?` are literals and their backslashed forms are
+/// operators; in the `regex` crate it is the other way around.
+fn bre_to_ere(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    let mut in_bracket = false;
+
+    // A leading `*` is a literal in a BRE
+    if chars.peek() == Some(&'*') {
+        chars.next();
+        out.push_str("\\*");
+    }
+
+    while let Some(c) = chars.next() {
+        if in_bracket {
+            out.push(c);
+            if c == ']' {
+                in_bracket = false;
+            }
+            continue;
+        }
+        match c {
+            '[' => {
+                out.push(c);
+                in_bracket = true;
+                // `]` right after `[` or `[^` is part of the set
+                if chars.peek() == Some(&'^') {
+                    out.push(chars.next().unwrap());
+                }
+                if chars.peek() == Some(&']') {
+                    out.push_str("\\]");
+                    chars.next();
+                }
+            }
+            '\\' => match chars.next() {
+                Some(op @ ('(' | ')' | '{' | '}' | '|' | '+' | '?')) => out.push(op),
+                Some(other) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push_str("\\\\"),
+            },
+            '(' | ')' | '{' | '}' | '|' | '+' | '?' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn build_regex(
+    patterns: &[String],
+    fixed: bool,
+    extended: bool,
+    ignore_case: bool,
+    word: bool,
+    line: bool,
+) -> Result<Regex, regex::Error> {
+    let alternatives: Vec<String> = patterns
+        .iter()
+        .map(|p| {
+            let p = if fixed {
+                regex::escape(p)
+            } else if extended {
+                p.clone()
+            } else {
+                bre_to_ere(p)
+            };
+            if line {
+                format!("^(?:{})$", p)
+            } else if word {
+                format!("\\b(?:{})\\b", p)
+            } else {
+                format!("(?:{})", p)
+            }
+        })
+        .collect();
+
+    RegexBuilder::new(&alternatives.join("|"))
+        .case_insensitive(ignore_case)
+        .build()
+}
+
+impl ShellCommand for GrepCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed = match parse_options(
+                &context.args,
+                "efm",
+                &["regexp", "file", "max-count", "include", "exclude"],
+            ) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("grep: {}", e));
+                    return ExecuteResult::from_exit_code(2);
+                }
+            };
+
+            // Patterns come from -e and -f, or else from the first operand
+            let mut patterns: Vec<String> = Vec::new();
+            for option in &parsed.options {
+                let (name, value) = match option {
+                    CommandOption::Short(c, Some(value)) => (c.to_string(), value),
+                    CommandOption::Long(name, Some(value)) => (name.clone(), value),
+                    _ => continue,
+                };
+                match name.as_str() {
+                    "e" | "regexp" => patterns.push(value.clone()),
+                    "f" | "file" => match fs::read_to_string(context.resolve_path(value)) {
+                        Ok(content) => patterns.extend(content.lines().map(String::from)),
+                        Err(e) => {
+                            let _ = context
+                                .stderr
+                                .write_line(&format!("grep: {}: {}", value, e));
+                            return ExecuteResult::from_exit_code(2);
+                        }
+                    },
+                    _ => {}
+                }
+            }
+            let mut operands = parsed.operands.clone();
+            if patterns.is_empty() {
+                if operands.is_empty() {
+                    let _ = context
+                        .stderr
+                        .write_line("usage: grep [OPTION]... PATTERN [FILE]...");
+                    return ExecuteResult::from_exit_code(2);
+                }
+                patterns.push(operands.remove(0).to_string_lossy().to_string());
+            }
+
+            let regex = match build_regex(
+                &patterns,
+                parsed.has('F', "fixed-strings"),
+                parsed.has('E', "extended-regexp"),
+                parsed.has('i', "ignore-case"),
+                parsed.has('w', "word-regexp"),
+                parsed.has('x', "line-regexp"),
+            ) {
+                Ok(regex) => regex,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("grep: {}", e));
+                    return ExecuteResult::from_exit_code(2);
+                }
+            };
+
+            let recursive =
+                parsed.has('r', "recursive") || parsed.has('R', "dereference-recursive");
+            let globs = |name: &str| -> Vec<Pattern> {
+                parsed
+                    .options
+                    .iter()
+                    .filter_map(|o| match o {
+                        CommandOption::Long(n, Some(v)) if n == name => Pattern::new(v).ok(),
+                        _ => None,
+                    })
+                    .collect()
+            };
+            let mut options = GrepOptions {
+                invert: parsed.has('v', "invert-match"),
+                line_numbers: parsed.has('n', "line-number"),
+                count: parsed.has('c', "count"),
+                files_with_matches: parsed.has('l', "files-with-matches"),
+                files_without_match: parsed.has('L', "files-without-match"),
+                only_matching: parsed.has('o', "only-matching"),
+                max_count: match parsed.value('m', "max-count").map(str::parse) {
+                    None => None,
+                    Some(Ok(n)) => Some(n),
+                    Some(Err(_)) => {
+                        let _ = context.stderr.write_line("grep: invalid max count");
+                        return ExecuteResult::from_exit_code(2);
+                    }
+                },
+                quiet: parsed.has('q', "quiet") || parsed.has('q', "silent"),
+                with_filename: false,
+                include: globs("include"),
+                exclude: globs("exclude"),
+            };
+            let no_messages = parsed.has('s', "no-messages");
+
+            if recursive && operands.is_empty() {
+                operands.push(".".into());
+            }
+
+            let mut matched = false;
+            let mut error = false;
+
+            if operands.is_empty() {
+                let mut buf = Vec::new();
+                if let Err(e) = context.stdin.pipe_to(&mut buf) {
+                    let _ = context.stderr.write_line(&format!("grep: {}", e));
+                    return ExecuteResult::from_exit_code(2);
+                }
+                matched = grep_content(
+                    &mut context.stdout,
+                    &regex,
+                    &buf,
+                    "(standard input)",
+                    &options,
+                );
+            } else {
+                options.with_filename = parsed.has('H', "with-filename")
+                    || ((operands.len() > 1 || recursive) && !parsed.has('h', "no-filename"));
+
+                let mut files = Vec::new();
+                for operand in &operands {
+                    let name = operand.to_string_lossy().to_string();
+                    let path = context.resolve_path(operand);
+                    if path.is_dir() {
+                        if recursive {
+                            let follow = parsed.has('R', "dereference-recursive");
+                            collect_files(&path, &name, follow, &options, &mut files);
+                        } else if !no_messages {
+                            let _ = context
+                                .stderr
+                                .write_line(&format!("grep: {}: Is a directory", name));
+                        }
+                        continue;
+                    }
+                    files.push((path, name));
+                }
+
+                for (path, name) in files {
+                    match fs::read(&path) {
+                        Ok(buf) => {
+                            matched |=
+                                grep_content(&mut context.stdout, &regex, &buf, &name, &options);
+                            if matched && options.quiet {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            if !no_messages {
+                                let _ =
+                                    context.stderr.write_line(&format!("grep: {}: {}", name, e));
+                            }
+                            error = true;
+                        }
+                    }
+                }
+            }
+
+            let exit_code = if matched && (options.quiet || !error) {
+                0
+            } else if error {
+                2
+            } else {
+                1
+            };
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
+
+/// Collect the files under `dir` for a recursive search, in name order.
+fn collect_files(
+    dir: &Path,
+    display: &str,
+    follow_links: bool,
+    options: &GrepOptions,
+    files: &mut Vec<(std::path::PathBuf, String)>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        let child = format!("{}/{}", display.trim_end_matches('/'), name);
+        let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+        if is_symlink && !follow_links {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, &child, follow_links, options, files);
+            continue;
+        }
+        if !options.include.is_empty() && !options.include.iter().any(|p| p.matches(&name)) {
+            continue;
+        }
+        if options.exclude.iter().any(|p| p.matches(&name)) {
+            continue;
+        }
+        files.push((path, child));
+    }
+}
+
+/// Search one input and print results; returns whether any line was selected.
+fn grep_content(
+    stdout: &mut ShellPipeWriter,
+    regex: &Regex,
+    content: &[u8],
+    name: &str,
+    options: &GrepOptions,
+) -> bool {
+    let text = String::from_utf8_lossy(content);
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+    let prefix = |line_number: usize| {
+        let mut prefix = String::new();
+        if options.with_filename {
+            prefix.push_str(name);
+            prefix.push(':');
+        }
+        if options.line_numbers {
+            prefix.push_str(&line_number.to_string());
+            prefix.push(':');
+        }
+        prefix
+    };
+
+    let mut count = 0;
+    if !content.is_empty() && options.max_count != Some(0) {
+        for (i, line) in text.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if regex.is_match(line) == options.invert {
+                continue;
+            }
+            count += 1;
+            if options.quiet || options.files_with_matches || options.files_without_match {
+                break;
+            }
+            if options.count {
+                if options.max_count == Some(count) {
+                    break;
+                }
+                continue;
+            }
+            if options.only_matching && !options.invert {
+                for m in regex.find_iter(line).filter(|m| !m.is_empty()) {
+                    let _ = stdout.write_line(&format!("{}{}", prefix(i + 1), m.as_str()));
+                }
+            } else {
+                let _ = stdout.write_line(&format!("{}{}", prefix(i + 1), line));
+            }
+            if options.max_count == Some(count) {
+                break;
+            }
+        }
+    }
+
+    if options.quiet {
+        // Nothing is printed in quiet mode
+    } else if options.files_with_matches {
+        if count > 0 {
+            let _ = stdout.write_line(name);
+        }
+    } else if options.files_without_match {
+        if count == 0 {
+            let _ = stdout.write_line(name);
+        }
+    } else if options.count {
+        if options.with_filename {
+            let _ = stdout.write_line(&format!("{}:{}", name, count));
+        } else {
+            let _ = stdout.write_line(&count.to_string());
+        }
+    }
+
+    count > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bre_to_ere() {
+        assert_eq!(bre_to_ere(r"a\(b\)*c"), "a(b)*c");
+        assert_eq!(bre_to_ere("f(x)+1"), r"f\(x\)\+1");
+        assert_eq!(bre_to_ere(r"a\{2\}"), "a{2}");
+        assert_eq!(bre_to_ere("[()]x"), "[()]x");
+        assert_eq!(bre_to_ere("[]a]"), r"[\]a]");
+        assert_eq!(bre_to_ere("*a"), r"\*a");
+    }
+
+    #[test]
+    fn test_build_regex() {
+        let patterns = vec!["foo".to_string(), "ba+r".to_string()];
+        let re = build_regex(&patterns, false, true, false, false, false).unwrap();
+        assert!(re.is_match("a baaar"));
+        assert!(re.is_match("food"));
+
+        let re = build_regex(&patterns, false, false, false, false, false).unwrap();
+        assert!(!re.is_match("baar"));
+        assert!(re.is_match("ba+r"));
+
+        let re = build_regex(&["a.b".to_string()], true, false, true, false, false).unwrap();
+        assert!(re.is_match("xA.By"));
+        assert!(!re.is_match("axb"));
+
+        let re = build_regex(&["foo".to_string()], false, false, false, true, false).unwrap();
+        assert!(re.is_match("a foo b"));
+        assert!(!re.is_match("food"));
+
+        let re = build_regex(&["foo".to_string()], false, false, false, false, true).unwrap();
+        assert!(!re.is_match("a foo"));
+    }
+}
@@ -0,0 +1,40 @@
+//! Local command implementation
+//!
+//! Declares variables local to the running shell function.
+
+use std::ffi::OsString;
+
+use super::{ShellCommand, ShellCommandContext};
+use crate::shell::types::{EnvChange, ExecuteResult, FutureExecuteResult};
+
+/// The `local` command - declares function-local variables.
+pub struct LocalCommand;
+
+impl ShellCommand for LocalCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            if context.state.function_depth() == 0 {
+                let _ = context
+                    .stderr
+                    .write_line("local: can only be used in a function");
+                return ExecuteResult::from_exit_code(1);
+            }
+
+            let mut changes = Vec::new();
+
+            for arg in context.args.iter().skip(1) {
+                let arg = arg.to_string_lossy();
+                let (name, value) = match arg.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (arg.as_ref(), None),
+                };
+                changes.push(EnvChange::DeclareLocal(OsString::from(name)));
+                if let Some(value) = value {
+                    changes.push(EnvChange::SetShellVar(name.into(), value.into()));
+                }
+            }
+
+            ExecuteResult::Continue(0, changes, Vec::new())
+        })
+    }
+}
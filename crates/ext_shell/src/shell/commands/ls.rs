@@ -0,0 +1,237 @@
+//! Ls command implementation
+//!
+//! Lists directory contents.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{parse_options, ShellCommand, ShellCommandContext};
+use crate::shell::fs_util::{human_size, list_directory, ListEntry, ListOptions};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult, ShellPipeWriter};
+
+/// The `ls` command - lists directory contents.
+pub struct LsCommand;
+
+impl ShellCommand for LsCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed = match parse_options(&context.args, "", &[]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("ls: {}", e));
+                    return ExecuteResult::from_exit_code(2);
+                }
+            };
+
+            let options = ListOptions {
+                all: parsed.has('a', "all") || parsed.has('A', "almost-all"),
+                all_including_dots: parsed.has('a', "all"),
+                long: parsed.has('l', "long"),
+                human_readable: parsed.has('h', "human-readable"),
+                sort_by_time: parsed.has('t', "time"),
+                reverse: parsed.has('r', "reverse"),
+                recursive: parsed.has('R', "recursive"),
+            };
+            let directory_itself = parsed.has('d', "directory");
+
+            let operands: Vec<String> = if parsed.operands.is_empty() {
+                vec![".".to_string()]
+            } else {
+                parsed
+                    .operands
+                    .iter()
+                    .map(|a| a.to_string_lossy().to_string())
+                    .collect()
+            };
+
+            let mut exit_code = 0;
+            let mut files = Vec::new();
+            let mut dirs = Vec::new();
+
+            for operand in &operands {
+                let path = context.resolve_path(operand);
+                match path.symlink_metadata() {
+                    Ok(metadata) => {
+                        let is_dir = path.is_dir();
+                        let entry = ListEntry {
+                            name: operand.clone(),
+                            path,
+                            is_symlink: metadata.file_type().is_symlink(),
+                            metadata: Some(metadata),
+                            is_dir,
+                        };
+                        if is_dir && !directory_itself {
+                            dirs.push(entry);
+                        } else {
+                            files.push(entry);
+                        }
+                    }
+                    Err(e) => {
+                        let _ = context
+                            .stderr
+                            .write_line(&format!("ls: cannot access '{}': {}", operand, e));
+                        exit_code = 2;
+                    }
+                }
+            }
+
+            if write_entries(&mut context.stdout, &files, &options).is_err() {
+                return ExecuteResult::from_exit_code(1);
+            }
+
+            let show_headers = operands.len() > 1 || options.recursive;
+            let mut first = files.is_empty();
+            for dir in dirs {
+                if let Err(code) = list_tree(
+                    &mut context,
+                    &dir.name,
+                    &dir.path,
+                    &options,
+                    show_headers,
+                    &mut first,
+                ) {
+                    exit_code = exit_code.max(code);
+                }
+            }
+
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
+
+/// List one directory, then its subdirectories when recursive.
+fn list_tree(
+    context: &mut ShellCommandContext,
+    display: &str,
+    path: &Path,
+    options: &ListOptions,
+    show_header: bool,
+    first: &mut bool,
+) -> Result<(), i32> {
+    let entries = match list_directory(path, options) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let _ = context
+                .stderr
+                .write_line(&format!("ls: cannot open directory '{}': {}", display, e));
+            return Err(2);
+        }
+    };
+
+    if show_header {
+        if !*first {
+            let _ = context.stdout.write_line("");
+        }
+        let _ = context.stdout.write_line(&format!("{}:", display));
+    }
+    *first = false;
+
+    write_entries(&mut context.stdout, &entries, options).map_err(|_| 1)?;
+
+    let mut result = Ok(());
+    if options.recursive {
+        for entry in entries {
+            if entry.is_dir && !entry.is_symlink && entry.name != "." && entry.name != ".." {
+                let child = format!("{}/{}", display.trim_end_matches('/'), entry.name);
+                if let Err(code) = list_tree(context, &child, &entry.path, options, true, first) {
+                    result = Err(code);
+                }
+            }
+        }
+    }
+    result
+}
+
+fn write_entries(
+    stdout: &mut ShellPipeWriter,
+    entries: &[ListEntry],
+    options: &ListOptions,
+) -> anyhow::Result<()> {
+    if !options.long {
+        for entry in entries {
+            stdout.write_line(&entry.name)?;
+        }
+        return Ok(());
+    }
+
+    let sizes: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            if options.human_readable {
+                human_size(e.size())
+            } else {
+                e.size().to_string()
+            }
+        })
+        .collect();
+    let width = sizes.iter().map(String::len).max().unwrap_or(0);
+
+    for (entry, size) in entries.iter().zip(sizes) {
+        let modified = entry
+            .metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .map(format_time)
+            .unwrap_or_default();
+        let mut line = format!(
+            "{} {:>width$} {} {}",
+            entry.permissions_string(),
+            size,
+            modified,
+            entry.name,
+            width = width
+        );
+        if entry.is_symlink {
+            if let Ok(target) = std::fs::read_link(&entry.path) {
+                line.push_str(&format!(" -> {}", target.display()));
+            }
+        }
+        stdout.write_line(&line)?;
+    }
+    Ok(())
+}
+
+/// Format a timestamp as `YYYY-MM-DD HH:MM` (UTC).
+fn format_time(time: SystemTime) -> String {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(UNIX_EPOCH), "1970-01-01 00:00");
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            "2024-02-29 12:34"
+        );
+    }
+}
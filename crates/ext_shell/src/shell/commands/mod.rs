@@ -3,27 +3,45 @@
 //! This module provides:
 //! - `ShellCommand` trait for implementing commands
 //! - `ShellCommandContext` for command execution context
-//! - Built-in commands: echo, cd, pwd, exit, export, unset, cat, head, mkdir, rm, cp, mv, sleep, xargs
+//! - Built-in commands: echo, cd, pwd, exit, export, unset, env, cat, head, tail, wc, ls, find,
+//!   grep, sort, uniq, tee, touch, mkdir, rm, cp, mv, printf, test/[, which, sleep, xargs
+//! - Control commands for scripts: break, continue, return, local
 //! - `builtin_commands()` function to get all built-in commands
 
 mod args;
 mod cat;
 mod cd;
+mod control;
 mod cp_mv;
 mod echo;
+mod env;
 mod executable;
 mod exit;
 mod export;
+mod find;
+mod grep;
 mod head;
+mod local;
+mod ls;
 mod mkdir;
+mod printf;
 mod pwd;
 mod rm;
 mod sleep;
+mod sort;
+mod tail;
+mod tee;
+mod test;
+mod touch;
+mod uniq;
 mod unset;
+mod wc;
+mod which;
 mod xargs;
 
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use super::types::{FutureExecuteResult, ShellPipeReader, ShellPipeWriter, ShellState};
@@ -34,17 +52,32 @@ pub use args::*;
 // Re-export command implementations
 pub use cat::CatCommand;
 pub use cd::CdCommand;
+pub use control::{BreakCommand, ContinueCommand, ReturnCommand};
 pub use cp_mv::{CpCommand, MvCommand};
 pub use echo::EchoCommand;
+pub use env::EnvCommand;
 pub use executable::{resolve_command, ExecutableCommand};
 pub use exit::ExitCommand;
 pub use export::ExportCommand;
+pub use find::FindCommand;
+pub use grep::GrepCommand;
 pub use head::HeadCommand;
+pub use local::LocalCommand;
+pub use ls::LsCommand;
 pub use mkdir::MkdirCommand;
+pub use printf::PrintfCommand;
 pub use pwd::PwdCommand;
 pub use rm::RmCommand;
 pub use sleep::SleepCommand;
+pub use sort::SortCommand;
+pub use tail::TailCommand;
+pub use tee::TeeCommand;
+pub use test::{BracketCommand, TestCommand};
+pub use touch::TouchCommand;
+pub use uniq::UniqCommand;
 pub use unset::UnsetCommand;
+pub use wc::WcCommand;
+pub use which::WhichCommand;
 pub use xargs::XargsCommand;
 
 /// Trait for implementing shell commands.
@@ -95,6 +128,16 @@ impl ShellCommandContext {
     pub fn write_line(&mut self, msg: &str) -> anyhow::Result<()> {
         self.stdout.write_line(msg)
     }
+
+    /// Resolve a path argument against the shell's working directory.
+    pub fn resolve_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.state.cwd().join(path)
+        }
+    }
 }
 
 /// True command - always returns exit code 0.
@@ -128,19 +171,41 @@ pub fn builtin_commands() -> HashMap<String, Rc<dyn ShellCommand>> {
     // Environment commands
     commands.insert("export".to_string(), Rc::new(ExportCommand));
     commands.insert("unset".to_string(), Rc::new(UnsetCommand));
+    commands.insert("env".to_string(), Rc::new(EnvCommand));
 
     // File commands
     commands.insert("cat".to_string(), Rc::new(CatCommand));
     commands.insert("head".to_string(), Rc::new(HeadCommand));
+    commands.insert("tail".to_string(), Rc::new(TailCommand));
+    commands.insert("wc".to_string(), Rc::new(WcCommand));
+    commands.insert("ls".to_string(), Rc::new(LsCommand));
+    commands.insert("find".to_string(), Rc::new(FindCommand));
+    commands.insert("touch".to_string(), Rc::new(TouchCommand));
+    commands.insert("tee".to_string(), Rc::new(TeeCommand));
     commands.insert("mkdir".to_string(), Rc::new(MkdirCommand));
     commands.insert("rm".to_string(), Rc::new(RmCommand));
     commands.insert("cp".to_string(), Rc::new(CpCommand));
     commands.insert("mv".to_string(), Rc::new(MvCommand));
 
+    // Text commands
+    commands.insert("grep".to_string(), Rc::new(GrepCommand));
+    commands.insert("sort".to_string(), Rc::new(SortCommand));
+    commands.insert("uniq".to_string(), Rc::new(UniqCommand));
+    commands.insert("printf".to_string(), Rc::new(PrintfCommand));
+
     // Utility commands
+    commands.insert("test".to_string(), Rc::new(TestCommand));
+    commands.insert("[".to_string(), Rc::new(BracketCommand));
+    commands.insert("which".to_string(), Rc::new(WhichCommand));
     commands.insert("sleep".to_string(), Rc::new(SleepCommand));
     commands.insert("xargs".to_string(), Rc::new(XargsCommand));
 
+    // Control commands
+    commands.insert("break".to_string(), Rc::new(BreakCommand));
+    commands.insert("continue".to_string(), Rc::new(ContinueCommand));
+    commands.insert("return".to_string(), Rc::new(ReturnCommand));
+    commands.insert("local".to_string(), Rc::new(LocalCommand));
+
     // Boolean commands
     commands.insert("true".to_string(), Rc::new(TrueCommand));
     commands.insert("false".to_string(), Rc::new(FalseCommand));
@@ -173,4 +238,16 @@ mod tests {
         assert!(commands.contains_key("true"));
         assert!(commands.contains_key("false"));
     }
+
+    #[test]
+    fn test_extended_commands_exist() {
+        let commands = builtin_commands();
+
+        for name in [
+            "ls", "tail", "wc", "grep", "touch", "which", "test", "[", "printf", "env", "sort",
+            "uniq", "tee", "find", "break", "continue", "return", "local",
+        ] {
+            assert!(commands.contains_key(name), "missing builtin '{}'", name);
+        }
+    }
 }
@@ -0,0 +1,525 @@
+//! Printf command implementation
+//!
+//! Formats and prints arguments.
+
+use super::{ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `printf` command - formats and prints arguments.
+pub struct PrintfCommand;
+
+impl ShellCommand for PrintfCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let mut args = context.args_str().into_iter().skip(1).peekable();
+            if args.peek().map(String::as_str) == Some("--") {
+                args.next();
+            }
+            let Some(format) = args.next() else {
+                let _ = context.stderr.write_line("printf: missing operand");
+                return ExecuteResult::from_exit_code(1);
+            };
+            let args: Vec<String> = args.collect();
+
+            let mut output = Output::default();
+            format_all(&format, &args, &mut output);
+
+            let mut exit_code = 0;
+            for error in &output.errors {
+                let _ = context.stderr.write_line(&format!("printf: {}", error));
+                exit_code = 1;
+            }
+            if context.stdout.write_all(output.text.as_bytes()).is_err() {
+                exit_code = 1;
+            }
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
+
+#[derive(Default)]
+struct Output {
+    text: String,
+    errors: Vec<String>,
+    /// Set by `\c`: print nothing more
+    stopped: bool,
+}
+
+/// Apply the format until the arguments run out.
+///
+/// The format is reused while arguments remain, as long as it consumes at
+/// least one per pass.
+fn format_all(format: &str, args: &[String], output: &mut Output) {
+    let mut next = 0;
+    loop {
+        let used = format_once(format, &args[next..], output);
+        next += used;
+        if output.stopped || used == 0 || next >= args.len() {
+            break;
+        }
+    }
+}
+
+/// Apply the format once; returns how many arguments were consumed.
+fn format_once(format: &str, args: &[String], output: &mut Output) -> usize {
+    let mut used = 0;
+    let mut chars = format.chars().peekable();
+    let next_arg = |used: &mut usize| -> Option<&String> {
+        let arg = args.get(*used);
+        *used += 1;
+        arg
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if escape(&mut chars, &mut output.text, false) {
+                    output.stopped = true;
+                    return used;
+                }
+            }
+            '%' => {
+                if chars.peek() == Some(&'%') {
+                    chars.next();
+                    output.text.push('%');
+                    continue;
+                }
+
+                let mut spec = Spec::default();
+                while let Some(&flag) = chars.peek() {
+                    match flag {
+                        '-' => spec.left = true,
+                        '+' => spec.plus = true,
+                        ' ' => spec.space = true,
+                        '0' => spec.zero = true,
+                        '#' => spec.alternate = true,
+                        _ => break,
+                    }
+                    chars.next();
+                }
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    let width = next_arg(&mut used)
+                        .map(|a| parse_int(a, output))
+                        .unwrap_or(0);
+                    spec.left |= width < 0;
+                    spec.width = width.unsigned_abs() as usize;
+                } else {
+                    spec.width = take_number(&mut chars);
+                }
+                if chars.peek() == Some(&'.') {
+                    chars.next();
+                    if chars.peek() == Some(&'*') {
+                        chars.next();
+                        let precision = next_arg(&mut used)
+                            .map(|a| parse_int(a, output))
+                            .unwrap_or(0);
+                        spec.precision = (precision >= 0).then_some(precision as usize);
+                    } else {
+                        spec.precision = Some(take_number(&mut chars));
+                    }
+                }
+
+                let Some(conversion) = chars.next() else {
+                    output.errors.push("missing format character".to_string());
+                    return used;
+                };
+                let arg = next_arg(&mut used).cloned();
+                let arg = arg.as_deref().unwrap_or("");
+                let body = match conversion {
+                    's' => match spec.precision {
+                        Some(p) => arg.chars().take(p).collect(),
+                        None => arg.to_string(),
+                    },
+                    'b' => {
+                        let mut text = String::new();
+                        let mut arg_chars = arg.chars().peekable();
+                        while let Some(c) = arg_chars.next() {
+                            if c == '\\' {
+                                if escape(&mut arg_chars, &mut text, true) {
+                                    output.stopped = true;
+                                    break;
+                                }
+                            } else {
+                                text.push(c);
+                            }
+                        }
+                        text
+                    }
+                    'c' => arg.chars().next().map(String::from).unwrap_or_default(),
+                    'd' | 'i' => {
+                        let n = parse_int(arg, output);
+                        spec.sign(n < 0, spec.digits(n.unsigned_abs().to_string()))
+                    }
+                    'u' | 'o' | 'x' | 'X' => {
+                        let n = parse_int(arg, output) as u64;
+                        let digits = match conversion {
+                            'u' => n.to_string(),
+                            'o' => format!("{:o}", n),
+                            'x' => format!("{:x}", n),
+                            _ => format!("{:X}", n),
+                        };
+                        let digits = spec.digits(digits);
+                        match conversion {
+                            'o' if spec.alternate && !digits.starts_with('0') => {
+                                format!("0{}", digits)
+                            }
+                            'x' if spec.alternate && n != 0 => format!("0x{}", digits),
+                            'X' if spec.alternate && n != 0 => format!("0X{}", digits),
+                            _ => digits,
+                        }
+                    }
+                    'f' | 'F' | 'e' | 'E' | 'g' | 'G' => {
+                        let n = parse_float(arg, output);
+                        let text = format_float(n.abs(), conversion, &spec);
+                        spec.sign(n.is_sign_negative() && n != 0.0, text)
+                    }
+                    other => {
+                        output
+                            .errors
+                            .push(format!("%{}: invalid conversion specification", other));
+                        return used;
+                    }
+                };
+                output.text.push_str(&spec.pad(body, conversion));
+                if output.stopped {
+                    return used;
+                }
+            }
+            _ => output.text.push(c),
+        }
+    }
+
+    used.min(args.len())
+}
+
+/// A parsed `%` conversion specification.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    zero: bool,
+    alternate: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    /// Apply an integer precision (minimum number of digits).
+    fn digits(&self, digits: String) -> String {
+        match self.precision {
+            Some(p) if digits.len() < p => format!("{}{}", "0".repeat(p - digits.len()), digits),
+            _ => digits,
+        }
+    }
+
+    fn sign(&self, negative: bool, body: String) -> String {
+        if negative {
+            format!("-{}", body)
+        } else if self.plus {
+            format!("+{}", body)
+        } else if self.space {
+            format!(" {}", body)
+        } else {
+            body
+        }
+    }
+
+    fn pad(&self, body: String, conversion: char) -> String {
+        let len = body.chars().count();
+        if len >= self.width {
+            return body;
+        }
+        let fill = self.width - len;
+        let numeric = !matches!(conversion, 's' | 'b' | 'c');
+        // Zero padding goes after the sign, and is ignored with a precision on integers
+        let zero = self.zero
+            && !self.left
+            && numeric
+            && (self.precision.is_none()
+                || matches!(conversion, 'f' | 'F' | 'e' | 'E' | 'g' | 'G'));
+
+        if self.left {
+            format!("{}{}", body, " ".repeat(fill))
+        } else if zero {
+            let sign_len = body
+                .find(|c: char| !matches!(c, '-' | '+' | ' '))
+                .unwrap_or(0);
+            let prefix_len =
+                if body[sign_len..].starts_with("0x") || body[sign_len..].starts_with("0X") {
+                    sign_len + 2
+                } else {
+                    sign_len
+                };
+            format!(
+                "{}{}{}",
+                &body[..prefix_len],
+                "0".repeat(fill),
+                &body[prefix_len..]
+            )
+        } else {
+            format!("{}{}", " ".repeat(fill), body)
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> usize {
+    let mut n = 0usize;
+    while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+        n = n.saturating_mul(10).saturating_add(d as usize);
+        chars.next();
+    }
+    n
+}
+
+/// Handle the escape after a backslash; returns true for `\c`.
+///
+/// In `%b` arguments octal escapes are written `\0NNN`; in the format they
+/// are `\NNN`.
+fn escape(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    out: &mut String,
+    in_argument: bool,
+) -> bool {
+    let Some(c) = chars.next() else {
+        out.push('\\');
+        return false;
+    };
+    match c {
+        'n' => out.push('\n'),
+        't' => out.push('\t'),
+        'r' => out.push('\r'),
+        'a' => out.push('\x07'),
+        'b' => out.push('\x08'),
+        'f' => out.push('\x0c'),
+        'v' => out.push('\x0b'),
+        'e' => out.push('\x1b'),
+        '\\' => out.push('\\'),
+        'c' => return true,
+        '0'..='7' => {
+            let mut value = c.to_digit(8).unwrap();
+            let max_digits = if in_argument && c == '0' { 3 } else { 2 };
+            for _ in 0..max_digits {
+                match chars.peek().and_then(|c| c.to_digit(8)) {
+                    Some(d) => {
+                        value = value * 8 + d;
+                        chars.next();
+                    }
+                    None => break,
+                }
+            }
+            out.push(char::from_u32(value & 0xff).unwrap_or('\0'));
+        }
+        'x' => {
+            let mut value = 0;
+            let mut digits = 0;
+            while digits < 2 {
+                match chars.peek().and_then(|c| c.to_digit(16)) {
+                    Some(d) => {
+                        value = value * 16 + d;
+                        chars.next();
+                        digits += 1;
+                    }
+                    None => break,
+                }
+            }
+            if digits == 0 {
+                out.push_str("\\x");
+            } else {
+                out.push(char::from_u32(value).unwrap_or('\0'));
+            }
+        }
+        '"' | '\'' if !in_argument => out.push(c),
+        other => {
+            out.push('\\');
+            out.push(other);
+        }
+    }
+    false
+}
+
+/// Parse a numeric argument; `'c` or `"c` gives the character's code.
+fn parse_int(arg: &str, output: &mut Output) -> i64 {
+    let trimmed = arg.trim();
+    if let Some(c) = trimmed
+        .strip_prefix('\'')
+        .or_else(|| trimmed.strip_prefix('"'))
+        .and_then(|rest| rest.chars().next())
+    {
+        return c as i64;
+    }
+    if trimmed.is_empty() {
+        return 0;
+    }
+
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let parsed = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8)
+    } else {
+        digits.parse()
+    };
+
+    match parsed {
+        Ok(n) if negative => -n,
+        Ok(n) => n,
+        Err(_) => {
+            output.errors.push(format!("{}: invalid number", arg));
+            0
+        }
+    }
+}
+
+fn parse_float(arg: &str, output: &mut Output) -> f64 {
+    let trimmed = arg.trim();
+    if let Some(c) = trimmed
+        .strip_prefix('\'')
+        .or_else(|| trimmed.strip_prefix('"'))
+        .and_then(|rest| rest.chars().next())
+    {
+        return c as u32 as f64;
+    }
+    if trimmed.is_empty() {
+        return 0.0;
+    }
+    trimmed.parse().unwrap_or_else(|_| {
+        output.errors.push(format!("{}: invalid number", arg));
+        0.0
+    })
+}
+
+/// Format a non-negative float for `%f`, `%e` or `%g`.
+fn format_float(n: f64, conversion: char, spec: &Spec) -> String {
+    let precision = spec.precision.unwrap_or(6);
+    let upper = conversion.is_ascii_uppercase();
+
+    if !n.is_finite() {
+        let text = if n.is_nan() { "nan" } else { "inf" };
+        return if upper {
+            text.to_uppercase()
+        } else {
+            text.to_string()
+        };
+    }
+
+    let text = match conversion.to_ascii_lowercase() {
+        'f' => format!("{:.*}", precision, n),
+        'e' => exponent_form(n, precision),
+        _ => {
+            // %g: the shorter of %e and %f with `precision` significant digits
+            let precision = precision.max(1);
+            let exponent = if n == 0.0 {
+                0
+            } else {
+                let e = exponent_form(n, precision - 1);
+                e[e.find('e').unwrap() + 1..].parse::<i32>().unwrap_or(0)
+            };
+            let text = if exponent < -4 || exponent >= precision as i32 {
+                exponent_form(n, precision - 1)
+            } else {
+                format!(
+                    "{:.*}",
+                    (precision as i32 - 1 - exponent).max(0) as usize,
+                    n
+                )
+            };
+            if spec.alternate {
+                text
+            } else {
+                strip_trailing_zeros(&text)
+            }
+        }
+    };
+
+    if upper {
+        text.to_uppercase()
+    } else {
+        text
+    }
+}
+
+/// `d.ddde+XX`, as C prints it (Rust prints `d.ddde2`).
+fn exponent_form(n: f64, precision: usize) -> String {
+    let text = format!("{:.*e}", precision, n);
+    let (mantissa, exponent) = text.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    format!(
+        "{}e{}{:02}",
+        mantissa,
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs()
+    )
+}
+
+fn strip_trailing_zeros(text: &str) -> String {
+    let (mantissa, exponent) = match text.find('e') {
+        Some(i) => (&text[..i], &text[i..]),
+        None => (text, ""),
+    };
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+    format!("{}{}", mantissa, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printf(format: &str, args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut output = Output::default();
+        format_all(format, &args, &mut output);
+        output.text
+    }
+
+    #[test]
+    fn test_strings_and_escapes() {
+        assert_eq!(printf("%s-%s\\n", &["a", "b"]), "a-b\n");
+        assert_eq!(printf("%s\\n", &["a", "b", "c"]), "a\nb\nc\n");
+        assert_eq!(printf("[%5s|%-5s]", &["ab", "cd"]), "[   ab|cd   ]");
+        assert_eq!(printf("%.2s", &["abcdef"]), "ab");
+        assert_eq!(printf("%b", &["x\\ty\\0101"]), "x\tyA");
+        assert_eq!(printf("a\\cb", &[]), "a");
+        assert_eq!(printf("100%%", &[]), "100%");
+        assert_eq!(printf("%s %s\\n", &["only"]), "only \n");
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(printf("%d %i", &["42", "-7"]), "42 -7");
+        assert_eq!(printf("%05d|%-4d|%+d", &["-42", "3", "5"]), "-0042|3   |+5");
+        assert_eq!(printf("%.3d", &["7"]), "007");
+        assert_eq!(
+            printf("%x %X %#x %o", &["255", "255", "255", "8"]),
+            "ff FF 0xff 10"
+        );
+        assert_eq!(printf("%d", &["'A"]), "65");
+        assert_eq!(printf("%*d", &["4", "1"]), "   1");
+        assert_eq!(printf("%.2f %f", &["3.14159", "2"]), "3.14 2.000000");
+        assert_eq!(printf("%e", &["12345.678"]), "1.234568e+04");
+        assert_eq!(
+            printf("%g %g %g", &["0.0001", "123456789", "2.5"]),
+            "0.0001 1.23457e+08 2.5"
+        );
+        assert_eq!(printf("%08.2f", &["-3.5"]), "-0003.50");
+    }
+
+    #[test]
+    fn test_invalid_number() {
+        let mut output = Output::default();
+        format_all("%d", &["abc".to_string()], &mut output);
+        assert_eq!(output.text, "0");
+        assert_eq!(output.errors.len(), 1);
+    }
+}
@@ -0,0 +1,387 @@
+//! Sort command implementation
+//!
+//! Sorts lines of text.
+
+use std::cmp::Ordering;
+use std::fs;
+
+use super::{parse_options, CommandOption, ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `sort` command - sorts lines of text.
+pub struct SortCommand;
+
+/// How two keys compare (`-n`, `-r`, `-f`, `-b`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct KeyOrder {
+    numeric: bool,
+    reverse: bool,
+    ignore_case: bool,
+    ignore_blanks: bool,
+}
+
+/// A `-k START[,END][OPTS]` sort key; fields are 1-based and inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SortKey {
+    start: usize,
+    end: Option<usize>,
+    ordering: KeyOrder,
+}
+
+/// Parse a key definition; modifiers on the key replace the global ones.
+fn parse_key(spec: &str, global: KeyOrder) -> Option<SortKey> {
+    let (fields, modifiers): (String, String) = spec
+        .chars()
+        .partition(|c| c.is_ascii_digit() || *c == ',' || *c == '.');
+    // Character offsets within fields (`-k 2.3`) are not supported
+    let field = |s: &str| s.split('.').next().unwrap_or("").parse::<usize>().ok();
+    let (start, end) = match fields.split_once(',') {
+        Some((start, end)) => (field(start)?, Some(field(end)?)),
+        None => (field(&fields)?, None),
+    };
+    if start == 0 || end == Some(0) {
+        return None;
+    }
+
+    let ordering = if modifiers.is_empty() {
+        global
+    } else {
+        let mut ordering = KeyOrder::default();
+        for m in modifiers.chars() {
+            match m {
+                'n' => ordering.numeric = true,
+                'r' => ordering.reverse = true,
+                'f' => ordering.ignore_case = true,
+                'b' => ordering.ignore_blanks = true,
+                _ => return None,
+            }
+        }
+        ordering
+    };
+
+    Some(SortKey {
+        start,
+        end,
+        ordering,
+    })
+}
+
+/// Extract fields `start..=end` of a line.
+///
+/// Without a separator, fields are runs of non-blanks and keep their
+/// leading blanks, as in POSIX `sort`.
+fn extract<'a>(line: &'a str, key: &SortKey, separator: Option<char>) -> &'a str {
+    let bounds: Vec<(usize, usize)> = match separator {
+        Some(sep) => {
+            let mut bounds = Vec::new();
+            let mut start = 0;
+            for (i, c) in line.char_indices() {
+                if c == sep {
+                    bounds.push((start, i));
+                    start = i + c.len_utf8();
+                }
+            }
+            bounds.push((start, line.len()));
+            bounds
+        }
+        None => {
+            let mut bounds = Vec::new();
+            let mut start = 0;
+            let mut in_field = false;
+            for (i, c) in line.char_indices() {
+                let blank = c == ' ' || c == '\t';
+                if in_field && blank {
+                    bounds.push((start, i));
+                    start = i;
+                    in_field = false;
+                } else if !blank {
+                    in_field = true;
+                }
+            }
+            if in_field || start < line.len() {
+                bounds.push((start, line.len()));
+            }
+            bounds
+        }
+    };
+
+    let Some(&(from, _)) = bounds.get(key.start - 1) else {
+        return "";
+    };
+    let to = key
+        .end
+        .and_then(|end| bounds.get(end - 1))
+        .map(|&(_, to)| to)
+        .unwrap_or(line.len());
+    &line[from..to.max(from)]
+}
+
+/// Leading numeric value of a string, as `sort -n` reads it.
+fn numeric_value(s: &str) -> f64 {
+    let s = s.trim_start();
+    let end = s
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map(|(i, _)| i)
+        .unwrap_or(s.len());
+    s[..end].parse().unwrap_or(0.0)
+}
+
+fn compare(a: &str, b: &str, ordering: KeyOrder) -> Ordering {
+    let (a, b) = if ordering.ignore_blanks || ordering.numeric {
+        (a.trim_start(), b.trim_start())
+    } else {
+        (a, b)
+    };
+    let result = if ordering.numeric {
+        numeric_value(a)
+            .partial_cmp(&numeric_value(b))
+            .unwrap_or(Ordering::Equal)
+    } else if ordering.ignore_case {
+        a.to_lowercase().cmp(&b.to_lowercase())
+    } else {
+        a.cmp(b)
+    };
+    if ordering.reverse {
+        result.reverse()
+    } else {
+        result
+    }
+}
+
+/// Sort settings for one invocation.
+struct Sorter {
+    keys: Vec<SortKey>,
+    global: KeyOrder,
+    separator: Option<char>,
+    unique: bool,
+    stable: bool,
+}
+
+impl Sorter {
+    /// Compare by keys only (whole line if there are none).
+    fn compare_keys(&self, a: &str, b: &str) -> Ordering {
+        if self.keys.is_empty() {
+            return compare(a, b, self.global);
+        }
+        for key in &self.keys {
+            let result = compare(
+                extract(a, key, self.separator),
+                extract(b, key, self.separator),
+                key.ordering,
+            );
+            if result != Ordering::Equal {
+                return result;
+            }
+        }
+        Ordering::Equal
+    }
+
+    fn sort(&self, lines: &mut Vec<String>) {
+        lines.sort_by(|a, b| {
+            let result = self.compare_keys(a, b);
+            // Equal keys fall back to the whole line unless -s or -u
+            if result == Ordering::Equal && !self.stable && !self.unique {
+                let fallback = a.cmp(b);
+                if self.global.reverse {
+                    fallback.reverse()
+                } else {
+                    fallback
+                }
+            } else {
+                result
+            }
+        });
+        if self.unique {
+            lines.dedup_by(|a, b| self.compare_keys(a, b) == Ordering::Equal);
+        }
+    }
+}
+
+impl ShellCommand for SortCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed =
+                match parse_options(&context.args, "kto", &["key", "field-separator", "output"]) {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!("sort: {}", e));
+                        return ExecuteResult::from_exit_code(2);
+                    }
+                };
+
+            let global = KeyOrder {
+                numeric: parsed.has('n', "numeric-sort"),
+                reverse: parsed.has('r', "reverse"),
+                ignore_case: parsed.has('f', "ignore-case"),
+                ignore_blanks: parsed.has('b', "ignore-leading-blanks"),
+            };
+
+            let separator = match parsed.value('t', "field-separator") {
+                Some(sep) if sep.chars().count() == 1 => sep.chars().next(),
+                Some(sep) => {
+                    let _ = context
+                        .stderr
+                        .write_line(&format!("sort: multi-character tab '{}'", sep));
+                    return ExecuteResult::from_exit_code(2);
+                }
+                None => None,
+            };
+
+            let mut keys = Vec::new();
+            for option in &parsed.options {
+                let spec = match option {
+                    CommandOption::Short('k', Some(spec)) => spec,
+                    CommandOption::Long(name, Some(spec)) if name == "key" => spec,
+                    _ => continue,
+                };
+                match parse_key(spec, global) {
+                    Some(key) => keys.push(key),
+                    None => {
+                        let _ = context
+                            .stderr
+                            .write_line(&format!("sort: invalid key '{}'", spec));
+                        return ExecuteResult::from_exit_code(2);
+                    }
+                }
+            }
+
+            let mut content = Vec::new();
+            if parsed.operands.is_empty() {
+                if let Err(e) = context.stdin.clone().pipe_to(&mut content) {
+                    let _ = context.stderr.write_line(&format!("sort: {}", e));
+                    return ExecuteResult::from_exit_code(2);
+                }
+            }
+            for file in &parsed.operands {
+                if file == "-" {
+                    if let Err(e) = context.stdin.clone().pipe_to(&mut content) {
+                        let _ = context.stderr.write_line(&format!("sort: {}", e));
+                        return ExecuteResult::from_exit_code(2);
+                    }
+                    continue;
+                }
+                match fs::read(context.resolve_path(file)) {
+                    Ok(mut buf) => {
+                        if !buf.is_empty() && !buf.ends_with(b"\n") {
+                            buf.push(b'\n');
+                        }
+                        content.extend(buf);
+                    }
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!(
+                            "sort: cannot read: {}: {}",
+                            file.to_string_lossy(),
+                            e
+                        ));
+                        return ExecuteResult::from_exit_code(2);
+                    }
+                }
+            }
+
+            let mut lines: Vec<String> = String::from_utf8_lossy(&content)
+                .lines()
+                .map(String::from)
+                .collect();
+
+            let sorter = Sorter {
+                keys,
+                global,
+                separator,
+                unique: parsed.has('u', "unique"),
+                stable: parsed.has('s', "stable"),
+            };
+            sorter.sort(&mut lines);
+
+            let mut output = lines.join("\n");
+            if !lines.is_empty() {
+                output.push('\n');
+            }
+
+            // -o may name an input file, so it is only written after reading
+            let result = match parsed.value('o', "output") {
+                Some(file) => fs::write(context.resolve_path(file), output)
+                    .map_err(|e| format!("{}: {}", file, e)),
+                None => context
+                    .stdout
+                    .write_all(output.as_bytes())
+                    .map_err(|e| e.to_string()),
+            };
+            match result {
+                Ok(_) => ExecuteResult::from_exit_code(0),
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("sort: {}", e));
+                    ExecuteResult::from_exit_code(2)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(lines: &[&str], sorter: Sorter) -> Vec<String> {
+        let mut lines: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
+        sorter.sort(&mut lines);
+        lines
+    }
+
+    fn sorter(global: KeyOrder, keys: &[&str], separator: Option<char>, unique: bool) -> Sorter {
+        Sorter {
+            keys: keys.iter().map(|k| parse_key(k, global).unwrap()).collect(),
+            global,
+            separator,
+            unique,
+            stable: false,
+        }
+    }
+
+    #[test]
+    fn test_sort_lines() {
+        let numeric = KeyOrder {
+            numeric: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            sorted(&["10", "9", "-1", "x"], sorter(numeric, &[], None, false)),
+            vec!["-1", "x", "9", "10"]
+        );
+
+        let reverse = KeyOrder {
+            reverse: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            sorted(&["b", "a", "c", "a"], sorter(reverse, &[], None, true)),
+            vec!["c", "b", "a"]
+        );
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let lines = ["bob,32", "alice,7", "carol,100"];
+        assert_eq!(
+            sorted(
+                &lines,
+                sorter(KeyOrder::default(), &["2n"], Some(','), false)
+            ),
+            vec!["alice,7", "bob,32", "carol,100"]
+        );
+        assert_eq!(
+            sorted(
+                &lines,
+                sorter(KeyOrder::default(), &["2,2nr"], Some(','), false)
+            ),
+            vec!["carol,100", "bob,32", "alice,7"]
+        );
+
+        let key = parse_key("2", KeyOrder::default()).unwrap();
+        assert_eq!(extract("a  b c", &key, None), "  b c");
+        let key = parse_key("2,2", KeyOrder::default()).unwrap();
+        assert_eq!(extract("a  b c", &key, None), "  b");
+        assert!(parse_key("0", KeyOrder::default()).is_none());
+        assert!(parse_key("1z", KeyOrder::default()).is_none());
+    }
+}
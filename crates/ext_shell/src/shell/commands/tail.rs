@@ -0,0 +1,214 @@
+//! Tail command implementation
+//!
+//! Prints the last N lines (or bytes) of a file.
+
+use std::ffi::OsString;
+use std::fs;
+
+use super::{parse_options, ShellCommand, ShellCommandContext};
+use crate::shell::fs_util::{read_lines, read_tail};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `tail` command - prints the last N lines of a file.
+pub struct TailCommand;
+
+/// What to print, from `-n`/`-c`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Count {
+    /// The last N lines
+    LastLines(usize),
+    /// Everything from line N (`-n +N`)
+    FromLine(usize),
+    /// The last N bytes
+    LastBytes(usize),
+    /// Everything from byte N (`-c +N`)
+    FromByte(usize),
+}
+
+fn parse_count(value: &str, lines: bool) -> Option<Count> {
+    let (from, digits) = match value.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('-').unwrap_or(value)),
+    };
+    let n: usize = digits.parse().ok()?;
+    Some(match (lines, from) {
+        (true, false) => Count::LastLines(n),
+        (true, true) => Count::FromLine(n),
+        (false, false) => Count::LastBytes(n),
+        (false, true) => Count::FromByte(n),
+    })
+}
+
+/// Rewrite the historical `tail -5` form to `tail -n 5`.
+fn expand_legacy_count(args: &[OsString]) -> Vec<OsString> {
+    args.iter()
+        .enumerate()
+        .flat_map(|(i, arg)| {
+            let s = arg.to_string_lossy();
+            match s.strip_prefix('-') {
+                Some(n) if i > 0 && !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => {
+                    vec![OsString::from("-n"), OsString::from(n)]
+                }
+                _ => vec![arg.clone()],
+            }
+        })
+        .collect()
+}
+
+/// Apply a count to in-memory content.
+fn select(content: &[u8], count: Count) -> &[u8] {
+    match count {
+        Count::LastBytes(n) => &content[content.len().saturating_sub(n)..],
+        Count::FromByte(n) => &content[n.saturating_sub(1).min(content.len())..],
+        Count::LastLines(n) => {
+            // Ignore the final newline when counting back
+            let body = content.strip_suffix(b"\n").unwrap_or(content);
+            if n == 0 {
+                return &content[content.len()..];
+            }
+            let mut seen = 0;
+            for (i, b) in body.iter().enumerate().rev() {
+                if *b == b'\n' {
+                    seen += 1;
+                    if seen == n {
+                        return &content[i + 1..];
+                    }
+                }
+            }
+            content
+        }
+        Count::FromLine(n) => {
+            let mut line = 1;
+            for (i, b) in content.iter().enumerate() {
+                if line >= n {
+                    return &content[i..];
+                }
+                if *b == b'\n' {
+                    line += 1;
+                }
+            }
+            &content[content.len()..]
+        }
+    }
+}
+
+impl ShellCommand for TailCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let args = expand_legacy_count(&context.args);
+            let parsed = match parse_options(&args, "nc", &["lines", "bytes"]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("tail: {}", e));
+                    return ExecuteResult::from_exit_code(1);
+                }
+            };
+
+            let count = match (parsed.value('c', "bytes"), parsed.value('n', "lines")) {
+                (Some(bytes), _) => parse_count(bytes, false),
+                (None, Some(lines)) => parse_count(lines, true),
+                (None, None) => Some(Count::LastLines(10)),
+            };
+            let Some(count) = count else {
+                let _ = context
+                    .stderr
+                    .write_line("tail: invalid number of lines or bytes");
+                return ExecuteResult::from_exit_code(1);
+            };
+
+            let show_headers = (parsed.operands.len() > 1 || parsed.has('v', "verbose"))
+                && !parsed.has('q', "quiet");
+            let files = parsed.operands;
+
+            // If no files, read from stdin
+            if files.is_empty() {
+                let mut buf = Vec::new();
+                if let Err(e) = context.stdin.pipe_to(&mut buf) {
+                    let _ = context.stderr.write_line(&format!("tail: {}", e));
+                    return ExecuteResult::from_exit_code(1);
+                }
+                return match context.stdout.write_all(select(&buf, count)) {
+                    Ok(_) => ExecuteResult::from_exit_code(0),
+                    Err(_) => ExecuteResult::from_exit_code(1),
+                };
+            }
+
+            let mut exit_code = 0;
+
+            for (i, file) in files.iter().enumerate() {
+                let path = context.resolve_path(file);
+                if show_headers {
+                    if i > 0 {
+                        let _ = context.stdout.write_line("");
+                    }
+                    let _ = context
+                        .stdout
+                        .write_line(&format!("==> {} <==", file.to_string_lossy()));
+                }
+
+                let result = match count {
+                    Count::LastLines(n) => read_tail(&path, n).and_then(|lines| {
+                        for line in lines {
+                            context
+                                .stdout
+                                .write_line(&line)
+                                .map_err(std::io::Error::other)?;
+                        }
+                        Ok(())
+                    }),
+                    Count::FromLine(n) => read_lines(&path).and_then(|lines| {
+                        for line in lines.skip(n.saturating_sub(1)) {
+                            context
+                                .stdout
+                                .write_line(&line?)
+                                .map_err(std::io::Error::other)?;
+                        }
+                        Ok(())
+                    }),
+                    Count::LastBytes(_) | Count::FromByte(_) => fs::read(&path).and_then(|buf| {
+                        context
+                            .stdout
+                            .write_all(select(&buf, count))
+                            .map_err(std::io::Error::other)
+                    }),
+                };
+
+                if let Err(e) = result {
+                    let _ = context.stderr.write_line(&format!(
+                        "tail: {}: {}",
+                        file.to_string_lossy(),
+                        e
+                    ));
+                    exit_code = 1;
+                }
+            }
+
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let content = b"one\ntwo\nthree\n";
+        assert_eq!(select(content, Count::LastLines(2)), b"two\nthree\n");
+        assert_eq!(select(content, Count::LastLines(5)), content);
+        assert_eq!(select(content, Count::LastLines(0)), b"");
+        assert_eq!(select(content, Count::FromLine(2)), b"two\nthree\n");
+        assert_eq!(select(content, Count::LastBytes(3)), b"ee\n");
+        assert_eq!(select(content, Count::FromByte(5)), b"two\nthree\n");
+        assert_eq!(select(b"a\nb", Count::LastLines(1)), b"b");
+    }
+
+    #[test]
+    fn test_legacy_count() {
+        let args: Vec<OsString> = vec!["tail".into(), "-5".into(), "f".into()];
+        let parsed = parse_options(&expand_legacy_count(&args), "nc", &[]).unwrap();
+        assert_eq!(parsed.value('n', "lines"), Some("5"));
+        assert_eq!(parse_count("+3", true), Some(Count::FromLine(3)));
+    }
+}
@@ -0,0 +1,74 @@
+//! Tee command implementation
+//!
+//! Copies standard input to standard output and to files.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+use super::{parse_options, ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `tee` command - copies stdin to stdout and files.
+pub struct TeeCommand;
+
+impl ShellCommand for TeeCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed = match parse_options(&context.args, "", &[]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("tee: {}", e));
+                    return ExecuteResult::from_exit_code(1);
+                }
+            };
+            let append = parsed.has('a', "append");
+
+            let mut exit_code = 0;
+            let mut files: Vec<(String, File)> = Vec::new();
+
+            for file in &parsed.operands {
+                let name = file.to_string_lossy().to_string();
+                let result = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .append(append)
+                    .truncate(!append)
+                    .open(context.resolve_path(file));
+                match result {
+                    Ok(handle) => files.push((name, handle)),
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!("tee: {}: {}", name, e));
+                        exit_code = 1;
+                    }
+                }
+            }
+
+            // Copy as data arrives, so `tee` works on long-running producers
+            let mut buffer = [0u8; 8192];
+            loop {
+                let n = match context.stdin.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!("tee: {}", e));
+                        exit_code = 1;
+                        break;
+                    }
+                };
+
+                // Keep writing to the files even if stdout goes away
+                let _ = context.stdout.write_all(&buffer[..n]);
+                files.retain_mut(|(name, file)| match file.write_all(&buffer[..n]) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!("tee: {}: {}", name, e));
+                        exit_code = 1;
+                        false
+                    }
+                });
+            }
+
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
@@ -0,0 +1,322 @@
+//! Test and [ command implementations
+//!
+//! Evaluate conditional expressions on files, strings and integers.
+
+use std::fs::{self, Metadata};
+use std::path::Path;
+
+use super::{ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `test` command - evaluates a conditional expression.
+pub struct TestCommand;
+
+/// The `[` command - `test` with a closing `]`.
+pub struct BracketCommand;
+
+impl ShellCommand for TestCommand {
+    fn execute(&self, context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let args = context.args_str();
+            run_test(context, &args[1..], "test")
+        })
+    }
+}
+
+impl ShellCommand for BracketCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let args = context.args_str();
+            if args.last().map(String::as_str) != Some("]") {
+                let _ = context.stderr.write_line("[: missing ']'");
+                return ExecuteResult::from_exit_code(2);
+            }
+            run_test(context, &args[1..args.len() - 1], "[")
+        })
+    }
+}
+
+fn run_test(mut context: ShellCommandContext, args: &[String], name: &str) -> ExecuteResult {
+    let cwd = context.state.cwd();
+    let mut parser = ExprParser {
+        args,
+        pos: 0,
+        cwd: &cwd,
+    };
+
+    let result = if args.is_empty() {
+        Ok(false)
+    } else {
+        parser.parse_or().and_then(|value| {
+            if parser.pos < args.len() {
+                Err(format!("unexpected argument '{}'", args[parser.pos]))
+            } else {
+                Ok(value)
+            }
+        })
+    };
+
+    match result {
+        Ok(true) => ExecuteResult::from_exit_code(0),
+        Ok(false) => ExecuteResult::from_exit_code(1),
+        Err(e) => {
+            let _ = context.stderr.write_line(&format!("{}: {}", name, e));
+            ExecuteResult::from_exit_code(2)
+        }
+    }
+}
+
+const UNARY_OPS: &[&str] = &[
+    "-e", "-f", "-d", "-r", "-w", "-x", "-s", "-L", "-h", "-p", "-S", "-b", "-c", "-z", "-n",
+];
+
+const BINARY_OPS: &[&str] = &[
+    "=", "==", "!=", "<", ">", "-eq", "-ne", "-lt", "-le", "-gt", "-ge", "-nt", "-ot", "-ef",
+];
+
+/// Recursive-descent parser over the expression arguments.
+///
+/// Precedence from lowest: `-o`, `-a`, `!`, then primaries.
+struct ExprParser<'a> {
+    args: &'a [String],
+    pos: usize,
+    cwd: &'a Path,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self, offset: usize) -> Option<&str> {
+        self.args.get(self.pos + offset).map(String::as_str)
+    }
+
+    fn parse_or(&mut self) -> Result<bool, String> {
+        let mut value = self.parse_and()?;
+        while self.peek(0) == Some("-o") {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            value = value || rhs;
+        }
+        Ok(value)
+    }
+
+    fn parse_and(&mut self) -> Result<bool, String> {
+        let mut value = self.parse_not()?;
+        while self.peek(0) == Some("-a") {
+            self.pos += 1;
+            let rhs = self.parse_not()?;
+            value = value && rhs;
+        }
+        Ok(value)
+    }
+
+    fn parse_not(&mut self) -> Result<bool, String> {
+        // `! = x` compares the string "!", it does not negate
+        if self.peek(0) == Some("!") && !self.peek(1).is_some_and(|op| BINARY_OPS.contains(&op)) {
+            self.pos += 1;
+            return Ok(!self.parse_not()?);
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<bool, String> {
+        let Some(first) = self.peek(0) else {
+            return Err("argument expected".to_string());
+        };
+
+        // Binary operators take priority, so `[ -n = -n ]` compares strings
+        if let (Some(op), Some(rhs)) = (self.peek(1), self.peek(2)) {
+            if BINARY_OPS.contains(&op) {
+                let (lhs, op, rhs) = (first.to_string(), op.to_string(), rhs.to_string());
+                self.pos += 3;
+                return self.binary(&lhs, &op, &rhs);
+            }
+        }
+
+        if first == "(" {
+            self.pos += 1;
+            let value = self.parse_or()?;
+            if self.peek(0) != Some(")") {
+                return Err("missing ')'".to_string());
+            }
+            self.pos += 1;
+            return Ok(value);
+        }
+
+        if UNARY_OPS.contains(&first) {
+            if let Some(operand) = self.peek(1) {
+                let (op, operand) = (first.to_string(), operand.to_string());
+                self.pos += 2;
+                return Ok(self.unary(&op, &operand));
+            }
+        }
+
+        // A lone word is true when it is not empty
+        let value = !first.is_empty();
+        self.pos += 1;
+        Ok(value)
+    }
+
+    fn unary(&self, op: &str, operand: &str) -> bool {
+        match op {
+            "-z" => return operand.is_empty(),
+            "-n" => return !operand.is_empty(),
+            _ => {}
+        }
+
+        let path = self.cwd.join(operand);
+        if op == "-L" || op == "-h" {
+            return fs::symlink_metadata(&path)
+                .map(|m| m.file_type().is_symlink())
+                .unwrap_or(false);
+        }
+
+        let Ok(metadata) = fs::metadata(&path) else {
+            return false;
+        };
+        match op {
+            "-e" => true,
+            "-f" => metadata.is_file(),
+            "-d" => metadata.is_dir(),
+            "-s" => metadata.len() > 0,
+            "-r" => is_readable(&metadata),
+            "-w" => !metadata.permissions().readonly(),
+            "-x" => is_executable(&path, &metadata),
+            _ => is_special(op, &metadata),
+        }
+    }
+
+    fn binary(&self, lhs: &str, op: &str, rhs: &str) -> Result<bool, String> {
+        Ok(match op {
+            "=" | "==" => lhs == rhs,
+            "!=" => lhs != rhs,
+            "<" => lhs < rhs,
+            ">" => lhs > rhs,
+            "-nt" | "-ot" => {
+                let modified = |p: &str| fs::metadata(self.cwd.join(p)).and_then(|m| m.modified());
+                match (modified(lhs), modified(rhs)) {
+                    (Ok(a), Ok(b)) if op == "-nt" => a > b,
+                    (Ok(a), Ok(b)) => a < b,
+                    // An existing file is newer than a missing one
+                    (Ok(_), Err(_)) => op == "-nt",
+                    (Err(_), Ok(_)) => op == "-ot",
+                    (Err(_), Err(_)) => false,
+                }
+            }
+            "-ef" => {
+                let canonical = |p: &str| fs::canonicalize(self.cwd.join(p));
+                matches!((canonical(lhs), canonical(rhs)), (Ok(a), Ok(b)) if a == b)
+            }
+            _ => {
+                let parse = |s: &str| {
+                    s.trim()
+                        .parse::<i64>()
+                        .map_err(|_| format!("{}: integer expression expected", s))
+                };
+                let (a, b) = (parse(lhs)?, parse(rhs)?);
+                match op {
+                    "-eq" => a == b,
+                    "-ne" => a != b,
+                    "-lt" => a < b,
+                    "-le" => a <= b,
+                    "-gt" => a > b,
+                    _ => a >= b,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(unix)]
+fn is_readable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o444 != 0
+}
+
+#[cfg(not(unix))]
+fn is_readable(_metadata: &Metadata) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn is_executable(_path: &Path, metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path, metadata: &Metadata) -> bool {
+    metadata.is_dir()
+        || path
+            .extension()
+            .map(|ext| {
+                let ext = ext.to_string_lossy().to_ascii_lowercase();
+                matches!(ext.as_str(), "exe" | "cmd" | "bat" | "com")
+            })
+            .unwrap_or(false)
+}
+
+/// `-p`, `-S`, `-b` and `-c`: FIFOs, sockets and device files.
+#[cfg(unix)]
+fn is_special(op: &str, metadata: &Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    let file_type = metadata.file_type();
+    match op {
+        "-p" => file_type.is_fifo(),
+        "-S" => file_type.is_socket(),
+        "-b" => file_type.is_block_device(),
+        "-c" => file_type.is_char_device(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_special(_op: &str, _metadata: &Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expr: &str) -> Result<bool, String> {
+        let args: Vec<String> = expr.split_whitespace().map(String::from).collect();
+        let cwd = std::env::current_dir().unwrap();
+        let mut parser = ExprParser {
+            args: &args,
+            pos: 0,
+            cwd: &cwd,
+        };
+        parser.parse_or()
+    }
+
+    #[test]
+    fn test_strings_and_integers() {
+        assert_eq!(eval("abc"), Ok(true));
+        assert_eq!(eval("-n abc"), Ok(true));
+        assert_eq!(eval("-z abc"), Ok(false));
+        assert_eq!(eval("a = a"), Ok(true));
+        assert_eq!(eval("a != a"), Ok(false));
+        assert_eq!(eval("-n = -n"), Ok(true));
+        assert_eq!(eval("-f"), Ok(true));
+        assert_eq!(eval("10 -gt 9"), Ok(true));
+        assert_eq!(eval("10 -le 9"), Ok(false));
+        assert!(eval("x -eq 1").is_err());
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(eval("! a = b"), Ok(true));
+        assert_eq!(eval("a = b -o 1 -eq 1"), Ok(true));
+        assert_eq!(eval("a = a -a 1 -eq 2"), Ok(false));
+        assert_eq!(eval("( a = b -o a = a ) -a ! x = y"), Ok(true));
+        assert!(eval("( a = a").is_err());
+    }
+
+    #[test]
+    fn test_files() {
+        let dir = std::env::temp_dir();
+        let dir = dir.to_string_lossy();
+        assert_eq!(eval(&format!("-d {}", dir)), Ok(true));
+        assert_eq!(eval(&format!("-f {}", dir)), Ok(false));
+        assert_eq!(eval("-e /definitely/not/here"), Ok(false));
+    }
+}
@@ -0,0 +1,122 @@
+//! Touch command implementation
+//!
+//! Creates files or updates their access and modification times.
+
+use std::fs::{self, FileTimes, OpenOptions};
+use std::path::Path;
+use std::time::SystemTime;
+
+use super::{parse_options, ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `touch` command - creates files or updates their timestamps.
+pub struct TouchCommand;
+
+/// Set the access and/or modification time of an existing file or directory.
+fn set_times(path: &Path, times: FileTimes) -> std::io::Result<()> {
+    #[cfg(unix)]
+    let file = fs::File::open(path)?;
+
+    #[cfg(windows)]
+    let file = {
+        use std::os::windows::fs::OpenOptionsExt;
+        // FILE_WRITE_ATTRIBUTES, and FILE_FLAG_BACKUP_SEMANTICS so
+        // directories can be opened too
+        OpenOptions::new()
+            .access_mode(0x0100)
+            .custom_flags(0x0200_0000)
+            .open(path)?
+    };
+
+    file.set_times(times)
+}
+
+impl ShellCommand for TouchCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed = match parse_options(&context.args, "r", &["reference"]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("touch: {}", e));
+                    return ExecuteResult::from_exit_code(1);
+                }
+            };
+
+            let no_create = parsed.has('c', "no-create");
+            let only_access = parsed.has('a', "access");
+            let only_modify = parsed.has('m', "modify");
+            // Neither -a nor -m means both
+            let (set_access, set_modify) = if only_access || only_modify {
+                (only_access, only_modify)
+            } else {
+                (true, true)
+            };
+
+            // Times come from the reference file, or are now
+            let (atime, mtime) = match parsed.value('r', "reference") {
+                Some(reference) => match fs::metadata(context.resolve_path(reference)) {
+                    Ok(metadata) => (
+                        metadata.accessed().unwrap_or_else(|_| SystemTime::now()),
+                        metadata.modified().unwrap_or_else(|_| SystemTime::now()),
+                    ),
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!(
+                            "touch: failed to get attributes of '{}': {}",
+                            reference, e
+                        ));
+                        return ExecuteResult::from_exit_code(1);
+                    }
+                },
+                None => {
+                    let now = SystemTime::now();
+                    (now, now)
+                }
+            };
+
+            if parsed.operands.is_empty() {
+                let _ = context.stderr.write_line("touch: missing file operand");
+                return ExecuteResult::from_exit_code(1);
+            }
+
+            let mut times = FileTimes::new();
+            if set_access {
+                times = times.set_accessed(atime);
+            }
+            if set_modify {
+                times = times.set_modified(mtime);
+            }
+
+            let mut exit_code = 0;
+
+            for file in &parsed.operands {
+                let path = context.resolve_path(file);
+
+                if !path.exists() {
+                    if no_create {
+                        continue;
+                    }
+                    if let Err(e) = OpenOptions::new().create(true).append(true).open(&path) {
+                        let _ = context.stderr.write_line(&format!(
+                            "touch: cannot touch '{}': {}",
+                            file.to_string_lossy(),
+                            e
+                        ));
+                        exit_code = 1;
+                        continue;
+                    }
+                }
+
+                if let Err(e) = set_times(&path, times) {
+                    let _ = context.stderr.write_line(&format!(
+                        "touch: setting times of '{}': {}",
+                        file.to_string_lossy(),
+                        e
+                    ));
+                    exit_code = 1;
+                }
+            }
+
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
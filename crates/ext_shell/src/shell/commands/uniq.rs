@@ -0,0 +1,212 @@
+//! Uniq command implementation
+//!
+//! Filters adjacent repeated lines.
+
+use std::fs;
+
+use super::{parse_options, ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `uniq` command - filters adjacent repeated lines.
+pub struct UniqCommand;
+
+/// Comparison and output options.
+#[derive(Default)]
+struct UniqOptions {
+    count: bool,
+    repeated_only: bool,
+    unique_only: bool,
+    ignore_case: bool,
+    skip_fields: usize,
+    skip_chars: usize,
+}
+
+impl UniqOptions {
+    /// The part of a line that is compared (`-f`, `-s`, `-i`).
+    fn key(&self, line: &str) -> String {
+        let mut rest = line;
+        for _ in 0..self.skip_fields {
+            rest = rest.trim_start_matches([' ', '\t']);
+            rest = rest.trim_start_matches(|c| c != ' ' && c != '\t');
+        }
+        let key: String = rest.chars().skip(self.skip_chars).collect();
+        if self.ignore_case {
+            key.to_lowercase()
+        } else {
+            key
+        }
+    }
+}
+
+/// Group adjacent lines with equal keys and format the output.
+fn uniq(lines: &[&str], options: &UniqOptions) -> Vec<String> {
+    let mut output = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let key = options.key(lines[i]);
+        let mut n = 1;
+        while i + n < lines.len() && options.key(lines[i + n]) == key {
+            n += 1;
+        }
+
+        let keep = match (options.repeated_only, options.unique_only) {
+            (true, true) => false,
+            (true, false) => n > 1,
+            (false, true) => n == 1,
+            (false, false) => true,
+        };
+        if keep {
+            if options.count {
+                output.push(format!("{:>7} {}", n, lines[i]));
+            } else {
+                output.push(lines[i].to_string());
+            }
+        }
+        i += n;
+    }
+    output
+}
+
+impl ShellCommand for UniqCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed = match parse_options(&context.args, "fs", &["skip-fields", "skip-chars"]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("uniq: {}", e));
+                    return ExecuteResult::from_exit_code(1);
+                }
+            };
+
+            let number = |short: char, long: &str| -> Result<usize, String> {
+                match parsed.value(short, long) {
+                    Some(v) => v
+                        .parse()
+                        .map_err(|_| format!("{}: invalid number to skip", v)),
+                    None => Ok(0),
+                }
+            };
+            let (skip_fields, skip_chars) =
+                match (number('f', "skip-fields"), number('s', "skip-chars")) {
+                    (Ok(f), Ok(s)) => (f, s),
+                    (Err(e), _) | (_, Err(e)) => {
+                        let _ = context.stderr.write_line(&format!("uniq: {}", e));
+                        return ExecuteResult::from_exit_code(1);
+                    }
+                };
+            let options = UniqOptions {
+                count: parsed.has('c', "count"),
+                repeated_only: parsed.has('d', "repeated"),
+                unique_only: parsed.has('u', "unique"),
+                ignore_case: parsed.has('i', "ignore-case"),
+                skip_fields,
+                skip_chars,
+            };
+
+            if parsed.operands.len() > 2 {
+                let _ = context.stderr.write_line(&format!(
+                    "uniq: extra operand '{}'",
+                    parsed.operands[2].to_string_lossy()
+                ));
+                return ExecuteResult::from_exit_code(1);
+            }
+
+            let content = match parsed.operands.first() {
+                Some(input) if input != "-" => match fs::read(context.resolve_path(input)) {
+                    Ok(buf) => buf,
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!(
+                            "uniq: {}: {}",
+                            input.to_string_lossy(),
+                            e
+                        ));
+                        return ExecuteResult::from_exit_code(1);
+                    }
+                },
+                _ => {
+                    let mut buf = Vec::new();
+                    if let Err(e) = context.stdin.clone().pipe_to(&mut buf) {
+                        let _ = context.stderr.write_line(&format!("uniq: {}", e));
+                        return ExecuteResult::from_exit_code(1);
+                    }
+                    buf
+                }
+            };
+
+            let text = String::from_utf8_lossy(&content);
+            let lines: Vec<&str> = text.lines().collect();
+            let mut output = uniq(&lines, &options).join("\n");
+            if !output.is_empty() {
+                output.push('\n');
+            }
+
+            let result = match parsed.operands.get(1) {
+                Some(file) => fs::write(context.resolve_path(file), output)
+                    .map_err(|e| format!("{}: {}", file.to_string_lossy(), e)),
+                None => context
+                    .stdout
+                    .write_all(output.as_bytes())
+                    .map_err(|e| e.to_string()),
+            };
+            match result {
+                Ok(_) => ExecuteResult::from_exit_code(0),
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("uniq: {}", e));
+                    ExecuteResult::from_exit_code(1)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniq() {
+        let lines = ["a", "a", "b", "B", "c", "c", "c"];
+        assert_eq!(
+            uniq(&lines, &UniqOptions::default()),
+            vec!["a", "b", "B", "c"]
+        );
+
+        let options = UniqOptions {
+            count: true,
+            ignore_case: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            uniq(&lines, &options),
+            vec!["      2 a", "      2 b", "      3 c"]
+        );
+
+        let repeated = UniqOptions {
+            repeated_only: true,
+            ..Default::default()
+        };
+        assert_eq!(uniq(&lines, &repeated), vec!["a", "c"]);
+
+        let unique = UniqOptions {
+            unique_only: true,
+            ..Default::default()
+        };
+        assert_eq!(uniq(&lines, &unique), vec!["b", "B"]);
+    }
+
+    #[test]
+    fn test_skip_fields_and_chars() {
+        let lines = ["1 apple", "2 apple", "3 pear"];
+        let options = UniqOptions {
+            skip_fields: 1,
+            ..Default::default()
+        };
+        assert_eq!(uniq(&lines, &options), vec!["1 apple", "3 pear"]);
+
+        let options = UniqOptions {
+            skip_chars: 1,
+            ..Default::default()
+        };
+        assert_eq!(uniq(&["xa", "ya", "zb"], &options), vec!["xa", "zb"]);
+    }
+}
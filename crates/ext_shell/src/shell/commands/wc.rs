@@ -0,0 +1,164 @@
+//! Wc command implementation
+//!
+//! Counts lines, words and bytes.
+
+use std::fs;
+
+use super::{parse_options, ShellCommand, ShellCommandContext};
+use crate::shell::fs_util::word_count;
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+
+/// The `wc` command - counts lines, words and bytes.
+pub struct WcCommand;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Counts {
+    lines: usize,
+    words: usize,
+    chars: usize,
+    bytes: usize,
+}
+
+impl Counts {
+    fn of(content: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(content);
+        Self {
+            lines: content.iter().filter(|b| **b == b'\n').count(),
+            words: text.split_whitespace().count(),
+            chars: text.chars().count(),
+            bytes: content.len(),
+        }
+    }
+
+    fn add(&mut self, other: Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.chars += other.chars;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Which columns to print, in `wc`'s fixed order.
+struct Columns {
+    lines: bool,
+    words: bool,
+    chars: bool,
+    bytes: bool,
+}
+
+impl Columns {
+    fn values(&self, counts: &Counts) -> Vec<usize> {
+        [
+            (self.lines, counts.lines),
+            (self.words, counts.words),
+            (self.chars, counts.chars),
+            (self.bytes, counts.bytes),
+        ]
+        .into_iter()
+        .filter_map(|(on, value)| on.then_some(value))
+        .collect()
+    }
+}
+
+impl ShellCommand for WcCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed = match parse_options(&context.args, "", &[]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("wc: {}", e));
+                    return ExecuteResult::from_exit_code(1);
+                }
+            };
+
+            let mut columns = Columns {
+                lines: parsed.has('l', "lines"),
+                words: parsed.has('w', "words"),
+                chars: parsed.has('m', "chars"),
+                bytes: parsed.has('c', "bytes"),
+            };
+            if !(columns.lines || columns.words || columns.chars || columns.bytes) {
+                columns.lines = true;
+                columns.words = true;
+                columns.bytes = true;
+            }
+
+            let mut rows: Vec<(Counts, Option<String>)> = Vec::new();
+            let mut exit_code = 0;
+
+            if parsed.operands.is_empty() {
+                let mut buf = Vec::new();
+                if let Err(e) = context.stdin.clone().pipe_to(&mut buf) {
+                    let _ = context.stderr.write_line(&format!("wc: {}", e));
+                    return ExecuteResult::from_exit_code(1);
+                }
+                rows.push((Counts::of(&buf), None));
+            }
+
+            for file in &parsed.operands {
+                let name = file.to_string_lossy().to_string();
+                let path = context.resolve_path(file);
+                let counts = if columns.chars {
+                    fs::read(&path).map(|buf| Counts::of(&buf))
+                } else {
+                    // Binary files are not valid UTF-8; fall back to reading bytes
+                    word_count(&path)
+                        .map(|(lines, words, bytes)| Counts {
+                            lines,
+                            words,
+                            chars: 0,
+                            bytes,
+                        })
+                        .or_else(|_| fs::read(&path).map(|buf| Counts::of(&buf)))
+                };
+                match counts {
+                    Ok(counts) => rows.push((counts, Some(name))),
+                    Err(e) => {
+                        let _ = context.stderr.write_line(&format!("wc: {}: {}", name, e));
+                        exit_code = 1;
+                    }
+                }
+            }
+
+            if rows.len() > 1 {
+                let mut total = Counts::default();
+                for (counts, _) in &rows {
+                    total.add(*counts);
+                }
+                rows.push((total, Some("total".to_string())));
+            }
+
+            let width = rows
+                .iter()
+                .flat_map(|(counts, _)| columns.values(counts))
+                .map(|v| v.to_string().len())
+                .max()
+                .unwrap_or(1);
+            let pad = rows.len() > 1 || columns.values(&Counts::default()).len() > 1;
+
+            for (counts, name) in &rows {
+                let mut line = columns
+                    .values(counts)
+                    .iter()
+                    .map(|v| {
+                        if pad {
+                            format!("{:>width$}", v, width = width)
+                        } else {
+                            v.to_string()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if let Some(name) = name {
+                    line.push(' ');
+                    line.push_str(name);
+                }
+                if context.stdout.write_line(&line).is_err() {
+                    return ExecuteResult::from_exit_code(1);
+                }
+            }
+
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
@@ -0,0 +1,63 @@
+//! Which command implementation
+//!
+//! Shows how a command name resolves: to a built-in or a PATH executable.
+
+use super::{parse_options, ShellCommand, ShellCommandContext};
+use crate::shell::types::{ExecuteResult, FutureExecuteResult};
+use crate::shell::which::{which, which_all};
+
+/// The `which` command - locates a command.
+pub struct WhichCommand;
+
+impl ShellCommand for WhichCommand {
+    fn execute(&self, mut context: ShellCommandContext) -> FutureExecuteResult {
+        Box::pin(async move {
+            let parsed = match parse_options(&context.args, "", &[]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = context.stderr.write_line(&format!("which: {}", e));
+                    return ExecuteResult::from_exit_code(2);
+                }
+            };
+            let all = parsed.has('a', "all");
+            let path_env = context.state.get_env_var("PATH");
+
+            if parsed.operands.is_empty() {
+                return ExecuteResult::from_exit_code(1);
+            }
+
+            let mut exit_code = 0;
+
+            for name in &parsed.operands {
+                let mut found = false;
+
+                // Built-ins win over PATH, so report them first
+                if context.state.resolve_custom_command(name).is_some() {
+                    let _ = context.stdout.write_line(&format!(
+                        "{}: shell built-in command",
+                        name.to_string_lossy()
+                    ));
+                    found = true;
+                }
+
+                if all {
+                    for path in which_all(name, path_env.as_deref()) {
+                        let _ = context.stdout.write_line(&path.to_string_lossy());
+                        found = true;
+                    }
+                } else if !found {
+                    if let Some(path) = which(name, path_env.as_deref()) {
+                        let _ = context.stdout.write_line(&path.to_string_lossy());
+                        found = true;
+                    }
+                }
+
+                if !found {
+                    exit_code = 1;
+                }
+            }
+
+            ExecuteResult::from_exit_code(exit_code)
+        })
+    }
+}
@@ -11,14 +11,15 @@ use std::rc::Rc;
 use futures::future::LocalBoxFuture;
 
 use crate::parser::{
-    BooleanListOp, Command, GlobPart, Pipeline, PipelineCommand, Redirect, RedirectOp,
-    RedirectTarget, Sequence, SequentialList, SimpleCommand, Word, WordPart,
+    BooleanListOp, CaseClause, Command, ForClause, GlobPart, IfClause, Pipeline, PipelineCommand,
+    Redirect, RedirectOp, RedirectTarget, Sequence, SequentialList, SimpleCommand, WhileClause,
+    Word, WordPart,
 };
 use crate::shell::commands::{
     resolve_command, ExecutableCommand, ShellCommand, ShellCommandContext,
 };
 use crate::shell::types::{
-    pipe, EnvChange, ExecuteResult, ShellPipeReader, ShellPipeWriter, ShellState,
+    pipe, ControlFlow, EnvChange, ExecuteResult, ShellPipeReader, ShellPipeWriter, ShellState,
 };
 
 // ============================================================================
@@ -69,6 +70,21 @@ async fn execute_sequential_list_with_pipes(
     stdout: ShellPipeWriter,
     stderr: ShellPipeWriter,
 ) -> i32 {
+    execute_list(list, state, stdin, stdout, stderr)
+        .await
+        .exit_code()
+}
+
+/// Execute a list, stopping early for `exit`, `break`, `continue` and `return`.
+///
+/// `exit` skips the rest of the list and is reported, so enclosing loops stop.
+async fn execute_list(
+    list: SequentialList,
+    state: Rc<ShellState>,
+    stdin: ShellPipeReader,
+    stdout: ShellPipeWriter,
+    stderr: ShellPipeWriter,
+) -> ExecuteResult {
     let mut last_exit_code = 0;
 
     for sequence in list.items {
@@ -81,15 +97,17 @@ async fn execute_sequential_list_with_pipes(
         )
         .await;
 
-        last_exit_code = result.exit_code();
-
-        // Apply env changes to state
-        if let ExecuteResult::Continue(_, changes, _) = result {
-            apply_env_changes(&state, changes);
+        match result {
+            ExecuteResult::Continue(code, changes, _) => {
+                last_exit_code = code;
+                // Apply env changes to state
+                apply_env_changes(&state, changes);
+            }
+            stop => return stop,
         }
     }
 
-    last_exit_code
+    ExecuteResult::from_exit_code(last_exit_code)
 }
 
 // ============================================================================
@@ -115,6 +133,12 @@ fn execute_sequence<'a>(
         .await;
 
         let exit_code = result.exit_code();
+        state.set_shell_var("?", exit_code.to_string());
+
+        // break, continue and return skip the rest of the sequence
+        if let ExecuteResult::Control(..) = result {
+            return result;
+        }
 
         // Apply any env changes from this pipeline
         let mut all_changes = Vec::new();
@@ -145,10 +169,10 @@ fn execute_sequence<'a>(
 
         // Return with accumulated changes
         match result {
-            ExecuteResult::Exit(code, handles) => ExecuteResult::Exit(code, handles),
             ExecuteResult::Continue(code, _, handles) => {
                 ExecuteResult::Continue(code, all_changes, handles)
             }
+            result => result,
         }
     })
 }
@@ -187,6 +211,7 @@ async fn execute_pipeline(
                 ExecuteResult::Continue(_, changes, handles) => {
                     ExecuteResult::Continue(code, changes, handles)
                 }
+                control => control,
             };
         }
 
@@ -212,9 +237,8 @@ async fn execute_pipeline(
             return ExecuteResult::Exit(code, handles);
         }
 
-        if let ExecuteResult::Continue(_, _, h) = result {
-            handles.extend(h);
-        }
+        // break, continue and return only affect their own pipeline element
+        handles.extend(result.into_handles());
 
         prev_stdout = reader;
     }
@@ -242,6 +266,10 @@ async fn execute_pipeline(
             handles.extend(h);
             ExecuteResult::Continue(exit_code, changes, handles)
         }
+        ExecuteResult::Control(_, h) => {
+            handles.extend(h);
+            ExecuteResult::Continue(exit_code, vec![], handles)
+        }
     }
 }
 
@@ -256,6 +284,13 @@ async fn execute_pipeline_command(
     stdout: ShellPipeWriter,
     stderr: ShellPipeWriter,
 ) -> ExecuteResult {
+    // Redirections of a compound command apply to everything in it
+    let (stdin, stdout, stderr) = if cmd.redirects.is_empty() {
+        (stdin, stdout, stderr)
+    } else {
+        apply_redirects(stdin, stdout, stderr, &cmd.redirects, &state).await
+    };
+
     match cmd.inner {
         Command::Simple(simple) => {
             execute_simple_command(simple, state, stdin, stdout, stderr).await
@@ -267,32 +302,287 @@ async fn execute_pipeline_command(
                 execute_sequential_list_with_pipes(*list, sub_state, stdin, stdout, stderr).await;
             ExecuteResult::Continue(code, vec![], vec![])
         }
-        Command::If(if_clause) => {
-            // Execute condition
-            let cond_state = Rc::new(state.clone_for_subshell());
-            let cond_code = execute_sequential_list(*if_clause.condition, cond_state).await;
-
-            if cond_code == 0 {
-                // Execute then part
-                let code = execute_sequential_list_with_pipes(
-                    *if_clause.then_part,
-                    state,
-                    stdin,
-                    stdout,
-                    stderr,
-                )
-                .await;
-                ExecuteResult::Continue(code, vec![], vec![])
-            } else if let Some(else_part) = if_clause.else_part {
-                let code =
-                    execute_sequential_list_with_pipes(*else_part, state, stdin, stdout, stderr)
-                        .await;
-                ExecuteResult::Continue(code, vec![], vec![])
-            } else {
-                ExecuteResult::Continue(0, vec![], vec![])
+        Command::BraceGroup(list) => execute_list(*list, state, stdin, stdout, stderr).await,
+        Command::If(if_clause) => execute_if(if_clause, state, stdin, stdout, stderr).await,
+        Command::For(for_clause) => execute_for(for_clause, state, stdin, stdout, stderr).await,
+        Command::While(while_clause) => {
+            execute_while(while_clause, state, stdin, stdout, stderr).await
+        }
+        Command::Case(case_clause) => execute_case(case_clause, state, stdin, stdout, stderr).await,
+        Command::FunctionDef(function) => {
+            state.define_function(function.name, *function.body);
+            ExecuteResult::Continue(0, vec![], vec![])
+        }
+    }
+}
+
+// ============================================================================
+// Compound Commands
+// ============================================================================
+
+async fn execute_if(
+    if_clause: IfClause,
+    state: Rc<ShellState>,
+    stdin: ShellPipeReader,
+    stdout: ShellPipeWriter,
+    stderr: ShellPipeWriter,
+) -> ExecuteResult {
+    // Execute condition
+    let condition = execute_list(
+        *if_clause.condition,
+        state.clone(),
+        stdin.clone(),
+        stdout.clone(),
+        stderr.clone(),
+    )
+    .await;
+    if let ExecuteResult::Control(..) = condition {
+        return condition;
+    }
+
+    if condition.exit_code() == 0 {
+        // Execute then part
+        execute_list(*if_clause.then_part, state, stdin, stdout, stderr).await
+    } else if let Some(else_part) = if_clause.else_part {
+        execute_list(*else_part, state, stdin, stdout, stderr).await
+    } else {
+        ExecuteResult::Continue(0, vec![], vec![])
+    }
+}
+
+async fn execute_for(
+    for_clause: ForClause,
+    state: Rc<ShellState>,
+    stdin: ShellPipeReader,
+    stdout: ShellPipeWriter,
+    stderr: ShellPipeWriter,
+) -> ExecuteResult {
+    let items: Vec<OsString> = match &for_clause.items {
+        Some(words) => {
+            let mut items = Vec::new();
+            for word in words {
+                items.extend(expand_word_to_args(word, &state).await);
+            }
+            items
+        }
+        None => positional_args(&state)
+            .into_iter()
+            .map(OsString::from)
+            .collect(),
+    };
+
+    state.enter_loop();
+    let mut last_exit_code = 0;
+    let mut result = None;
+    for item in items {
+        state.apply_change(&EnvChange::SetShellVar(
+            for_clause.name.clone().into(),
+            item,
+        ));
+        let body = execute_list(
+            (*for_clause.body).clone(),
+            state.clone(),
+            stdin.clone(),
+            stdout.clone(),
+            stderr.clone(),
+        )
+        .await;
+        match loop_step(body, &mut last_exit_code) {
+            LoopStep::Next => {}
+            LoopStep::Stop => break,
+            LoopStep::Leave(leave) => {
+                result = Some(leave);
+                break;
+            }
+        }
+    }
+    state.leave_loop();
+
+    result.unwrap_or_else(|| ExecuteResult::from_exit_code(last_exit_code))
+}
+
+async fn execute_while(
+    while_clause: WhileClause,
+    state: Rc<ShellState>,
+    stdin: ShellPipeReader,
+    stdout: ShellPipeWriter,
+    stderr: ShellPipeWriter,
+) -> ExecuteResult {
+    state.enter_loop();
+    let mut last_exit_code = 0;
+    let result = loop {
+        let condition = execute_list(
+            (*while_clause.condition).clone(),
+            state.clone(),
+            stdin.clone(),
+            stdout.clone(),
+            stderr.clone(),
+        )
+        .await;
+        let cond_code = match condition {
+            ExecuteResult::Continue(code, _, _) => code,
+            other => match loop_step(other, &mut last_exit_code) {
+                LoopStep::Next => continue,
+                LoopStep::Stop => break None,
+                LoopStep::Leave(leave) => break Some(leave),
+            },
+        };
+        if (cond_code == 0) == while_clause.until {
+            break None;
+        }
+
+        let body = execute_list(
+            (*while_clause.body).clone(),
+            state.clone(),
+            stdin.clone(),
+            stdout.clone(),
+            stderr.clone(),
+        )
+        .await;
+        match loop_step(body, &mut last_exit_code) {
+            LoopStep::Next => {}
+            LoopStep::Stop => break None,
+            LoopStep::Leave(leave) => break Some(leave),
+        }
+
+        // Let timeouts and kill signals interrupt loops of builtins
+        if let Some(code) = state.kill_signal().aborted_code() {
+            break Some(ExecuteResult::Exit(code, vec![]));
+        }
+        tokio::task::yield_now().await;
+    };
+    state.leave_loop();
+
+    result.unwrap_or_else(|| ExecuteResult::from_exit_code(last_exit_code))
+}
+
+async fn execute_case(
+    case_clause: CaseClause,
+    state: Rc<ShellState>,
+    stdin: ShellPipeReader,
+    stdout: ShellPipeWriter,
+    stderr: ShellPipeWriter,
+) -> ExecuteResult {
+    let value = expand_word(&case_clause.word, &state).await;
+    for arm in case_clause.arms {
+        for pattern in &arm.patterns {
+            if case_pattern_matches(pattern, &value, &state).await {
+                return execute_list(arm.body, state, stdin, stdout, stderr).await;
             }
         }
     }
+    ExecuteResult::Continue(0, vec![], vec![])
+}
+
+/// What a loop does after running its condition or body.
+enum LoopStep {
+    /// Go on with the next iteration
+    Next,
+    /// Leave this loop (`break`)
+    Stop,
+    /// Leave this loop and pass the result outward (`exit`, `return`, `break 2`)
+    Leave(ExecuteResult),
+}
+
+fn loop_step(result: ExecuteResult, last_exit_code: &mut i32) -> LoopStep {
+    match result {
+        ExecuteResult::Continue(code, _, _) => {
+            *last_exit_code = code;
+            LoopStep::Next
+        }
+        ExecuteResult::Control(ControlFlow::Break(n), handles) if n > 1 => {
+            LoopStep::Leave(ExecuteResult::Control(ControlFlow::Break(n - 1), handles))
+        }
+        ExecuteResult::Control(ControlFlow::Break(_), _) => LoopStep::Stop,
+        ExecuteResult::Control(ControlFlow::Continue(n), handles) if n > 1 => LoopStep::Leave(
+            ExecuteResult::Control(ControlFlow::Continue(n - 1), handles),
+        ),
+        ExecuteResult::Control(ControlFlow::Continue(_), _) => LoopStep::Next,
+        result => LoopStep::Leave(result),
+    }
+}
+
+/// Whether a value matches a case pattern; quoted parts match literally.
+async fn case_pattern_matches(pattern: &Word, value: &str, state: &ShellState) -> bool {
+    let mut glob_pattern = String::new();
+    for part in &pattern.parts {
+        let expanded = expand_word_part(part, state).await;
+        match part {
+            WordPart::Glob(_) | WordPart::Variable(_) => glob_pattern.push_str(&expanded),
+            _ => glob_pattern.push_str(&glob::Pattern::escape(&expanded)),
+        }
+    }
+
+    match glob::Pattern::new(&glob_pattern) {
+        Ok(compiled) => compiled.matches(value),
+        Err(_) => glob_pattern == value,
+    }
+}
+
+// ============================================================================
+// Function Calls
+// ============================================================================
+
+/// Deepest allowed nesting of function calls.
+///
+/// Each call nests several futures, so runaway recursion has to fail well
+/// before it could overflow the stack of the thread running the shell.
+const MAX_FUNCTION_DEPTH: usize = 100;
+
+/// Run a shell function with `args[1..]` as its positional parameters.
+async fn execute_function(
+    body: Rc<PipelineCommand>,
+    args: Vec<OsString>,
+    env_vars: Vec<(String, String)>,
+    state: Rc<ShellState>,
+    stdin: ShellPipeReader,
+    stdout: ShellPipeWriter,
+    mut stderr: ShellPipeWriter,
+) -> ExecuteResult {
+    if state.function_depth() >= MAX_FUNCTION_DEPTH {
+        let _ = stderr.write_line(&format!(
+            "{}: maximum function nesting level exceeded ({})",
+            args[0].to_string_lossy(),
+            MAX_FUNCTION_DEPTH
+        ));
+        return ExecuteResult::Continue(1, vec![], vec![]);
+    }
+
+    state.push_local_scope();
+
+    // Positional parameters and `NAME=value f` assignments are locals of the call
+    let params = args.len() - 1;
+    let shadowed = positional_args(&state).len().max(params);
+    for name in std::iter::once("#".to_string()).chain((1..=shadowed).map(|i| i.to_string())) {
+        state.declare_local(name.as_ref());
+    }
+    state.set_shell_var("#", params.to_string());
+    for (i, arg) in args.into_iter().enumerate().skip(1) {
+        state.set_shell_var(i.to_string(), arg);
+    }
+    for (name, value) in env_vars {
+        state.declare_local(name.as_ref());
+        state.set_env_var(name, value);
+    }
+
+    let result = Box::pin(execute_pipeline_command(
+        (*body).clone(),
+        state.clone(),
+        stdin,
+        stdout,
+        stderr,
+    ))
+    .await;
+
+    state.pop_local_scope();
+
+    match result {
+        ExecuteResult::Control(ControlFlow::Return(code), handles) => {
+            ExecuteResult::Continue(code, vec![], handles)
+        }
+        ExecuteResult::Continue(code, _, handles) => ExecuteResult::Continue(code, vec![], handles),
+        result => result,
+    }
 }
 
 // ============================================================================
@@ -308,20 +598,18 @@ async fn execute_simple_command(
 ) -> ExecuteResult {
     // Handle variable-only commands (no command name, just assignments)
     if cmd.args.is_empty() {
-        let changes: Vec<EnvChange> = cmd
-            .env_vars
-            .iter()
-            .map(|e| {
-                EnvChange::SetEnvVar(e.name.clone().into(), expand_word(&e.value, &state).into())
-            })
-            .collect();
+        let mut changes = Vec::new();
+        for e in &cmd.env_vars {
+            let value = expand_word(&e.value, &state).await;
+            changes.push(EnvChange::SetEnvVar(e.name.clone().into(), value.into()));
+        }
         return ExecuteResult::Continue(0, changes, vec![]);
     }
 
     // Expand all arguments
     let mut expanded_args: Vec<OsString> = Vec::new();
     for arg in &cmd.args {
-        let expanded = expand_word_to_args(arg, &state);
+        let expanded = expand_word_to_args(arg, &state).await;
         expanded_args.extend(expanded);
     }
 
@@ -334,7 +622,25 @@ async fn execute_simple_command(
 
     // Set up redirections
     let (final_stdin, final_stdout, final_stderr) =
-        apply_redirects(stdin, stdout, stderr, &cmd.redirects, &state);
+        apply_redirects(stdin, stdout, stderr, &cmd.redirects, &state).await;
+
+    // Shell functions shadow builtins and external commands
+    if let Some(body) = state.function(&cmd_name.to_string_lossy()) {
+        let mut env_vars = Vec::new();
+        for e in &cmd.env_vars {
+            env_vars.push((e.name.clone(), expand_word(&e.value, &state).await));
+        }
+        return execute_function(
+            body,
+            expanded_args,
+            env_vars,
+            state,
+            final_stdin,
+            final_stdout,
+            final_stderr,
+        )
+        .await;
+    }
 
     // Create state with temporary env vars for this command
     let cmd_state = if cmd.env_vars.is_empty() {
@@ -342,7 +648,7 @@ async fn execute_simple_command(
     } else {
         let cloned = (*state).clone();
         for env_var in &cmd.env_vars {
-            let value = expand_word(&env_var.value, &state);
+            let value = expand_word(&env_var.value, &state).await;
             cloned.apply_env_var(
                 std::ffi::OsStr::new(&env_var.name),
                 std::ffi::OsStr::new(&value),
@@ -353,6 +659,10 @@ async fn execute_simple_command(
 
     // Look up the command as a builtin first
     if let Some(builtin) = state.resolve_custom_command(&cmd_name) {
+        if !state.is_command_allowed(&cmd_name, None) {
+            return command_not_allowed(&cmd_name, final_stderr);
+        }
+
         // Execute builtin command
         let context = ShellCommandContext {
             args: expanded_args,
@@ -368,6 +678,10 @@ async fn execute_simple_command(
 
     // Try to find external command
     if let Some(path) = resolve_command(cmd_name.as_os_str(), &state) {
+        if !state.is_command_allowed(&cmd_name, Some(&path)) {
+            return command_not_allowed(&cmd_name, final_stderr);
+        }
+
         let executable = ExecutableCommand::new(path);
 
         let context = ShellCommandContext {
@@ -391,6 +705,13 @@ async fn execute_simple_command(
     ExecuteResult::Continue(127, vec![], vec![])
 }
 
+/// Report a command rejected by the state's command filter.
+fn command_not_allowed(cmd_name: &OsString, mut stderr: ShellPipeWriter) -> ExecuteResult {
+    let _ = stderr
+        .write_all(format!("{}: command not allowed\n", cmd_name.to_string_lossy()).as_bytes());
+    ExecuteResult::Continue(126, vec![], vec![])
+}
+
 /// Callback for nested command execution (used by xargs, etc.)
 fn execute_command_args(context: ShellCommandContext) -> LocalBoxFuture<'static, ExecuteResult> {
     Box::pin(async move {
@@ -417,18 +738,30 @@ fn execute_command_args(context: ShellCommandContext) -> LocalBoxFuture<'static,
 // ============================================================================
 
 /// Expand a word to a string (single result).
-fn expand_word(word: &Word, state: &ShellState) -> String {
+async fn expand_word(word: &Word, state: &ShellState) -> String {
     let mut result = String::new();
 
     for part in &word.parts {
-        result.push_str(&expand_word_part(part, state));
+        result.push_str(&expand_word_part(part, state).await);
     }
 
     result
 }
 
 /// Expand a word to multiple arguments (for glob expansion).
-fn expand_word_to_args(word: &Word, state: &ShellState) -> Vec<OsString> {
+async fn expand_word_to_args(word: &Word, state: &ShellState) -> Vec<OsString> {
+    // `$@` and `"$@"` keep each positional parameter a separate argument
+    let all_args = WordPart::Variable("@".to_string());
+    match word.parts.as_slice() {
+        [part] if *part == all_args => {
+            return positional_args(state).into_iter().map(Into::into).collect();
+        }
+        [WordPart::DoubleQuoted(parts)] if parts.as_slice() == [all_args] => {
+            return positional_args(state).into_iter().map(Into::into).collect();
+        }
+        _ => {}
+    }
+
     let mut has_glob = false;
     let mut pattern = String::new();
 
@@ -436,14 +769,24 @@ fn expand_word_to_args(word: &Word, state: &ShellState) -> Vec<OsString> {
         match part {
             WordPart::Glob(_) => {
                 has_glob = true;
-                pattern.push_str(&expand_word_part(part, state));
+                pattern.push_str(&expand_word_part(part, state).await);
             }
             _ => {
-                pattern.push_str(&expand_word_part(part, state));
+                pattern.push_str(&expand_word_part(part, state).await);
             }
         }
     }
 
+    // Unquoted expansions that come out empty produce no argument
+    if pattern.is_empty()
+        && word
+            .parts
+            .iter()
+            .all(|part| matches!(part, WordPart::Variable(_)))
+    {
+        return vec![];
+    }
+
     if has_glob {
         // Try glob expansion
         match glob::glob(&pattern) {
@@ -468,58 +811,68 @@ fn expand_word_to_args(word: &Word, state: &ShellState) -> Vec<OsString> {
 }
 
 /// Expand a single word part.
-fn expand_word_part(part: &WordPart, state: &ShellState) -> String {
-    match part {
-        WordPart::Text(s) => s.clone(),
-        WordPart::SingleQuoted(s) => s.clone(),
-        WordPart::DoubleQuoted(parts) => {
-            let mut result = String::new();
-            for p in parts {
-                result.push_str(&expand_word_part(p, state));
+fn expand_word_part<'a>(part: &'a WordPart, state: &'a ShellState) -> LocalBoxFuture<'a, String> {
+    Box::pin(async move {
+        match part {
+            WordPart::Text(s) => s.clone(),
+            WordPart::SingleQuoted(s) => s.clone(),
+            WordPart::DoubleQuoted(parts) => {
+                let mut result = String::new();
+                for p in parts {
+                    result.push_str(&expand_word_part(p, state).await);
+                }
+                result
             }
-            result
-        }
-        WordPart::Variable(name) => {
-            // Handle special variables
-            match name.as_str() {
-                "?" => state.last_exit_code().to_string(),
-                "$" => std::process::id().to_string(),
-                "HOME" => state
+            WordPart::Variable(name) => {
+                // Handle special variables
+                match name.as_str() {
+                    "?" => state.last_exit_code().to_string(),
+                    "$" => std::process::id().to_string(),
+                    "#" => state.get_var_str("#").unwrap_or_else(|| "0".to_string()),
+                    "@" | "*" => positional_args(state).join(" "),
+                    "HOME" => state
+                        .home_dir()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    "PWD" => state.cwd().to_string_lossy().to_string(),
+                    "OLDPWD" => state.get_env_var("OLDPWD").unwrap_or_default(),
+                    _ => state.get_var_str(name).unwrap_or_default(),
+                }
+            }
+            WordPart::Tilde(suffix) => {
+                let home = state
                     .home_dir()
                     .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_default(),
-                "PWD" => state.cwd().to_string_lossy().to_string(),
-                "OLDPWD" => state.get_env_var("OLDPWD").unwrap_or_default(),
-                _ => state.get_var_str(name).unwrap_or_default(),
+                    .unwrap_or_else(|| "~".to_string());
+                match suffix {
+                    None => home,
+                    Some(s) => format!("{}/{}", home, s),
+                }
             }
-        }
-        WordPart::Tilde(suffix) => {
-            let home = state
-                .home_dir()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|| "~".to_string());
-            match suffix {
-                None => home,
-                Some(s) => format!("{}/{}", home, s),
+            WordPart::Glob(glob) => match glob {
+                GlobPart::Star => "*".to_string(),
+                GlobPart::Question => "?".to_string(),
+                GlobPart::DoubleStar => "**".to_string(),
+                GlobPart::CharClass(class) => format!("[{}]", class),
+            },
+            WordPart::CommandSubstitution(list) => execute_command_substitution(list, state).await,
+            WordPart::Arithmetic(expr) => {
+                // Simple arithmetic evaluation
+                evaluate_arithmetic(expr, state).unwrap_or_else(|| "0".to_string())
             }
         }
-        WordPart::Glob(glob) => match glob {
-            GlobPart::Star => "*".to_string(),
-            GlobPart::Question => "?".to_string(),
-            GlobPart::DoubleStar => "**".to_string(),
-            GlobPart::CharClass(class) => format!("[{}]", class),
-        },
-        WordPart::CommandSubstitution(list) => {
-            // Execute command substitution and capture output
-            // For complex substitutions, we serialize the AST and run through sh
-            // For simple cases, we can execute directly
-            execute_command_substitution(list, state)
-        }
-        WordPart::Arithmetic(expr) => {
-            // Simple arithmetic evaluation
-            evaluate_arithmetic(expr, state).unwrap_or_else(|| "0".to_string())
-        }
-    }
+    })
+}
+
+/// The positional parameters `$1..$#` of the current function call.
+fn positional_args(state: &ShellState) -> Vec<String> {
+    let count: usize = state
+        .get_var_str("#")
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0);
+    (1..=count)
+        .map(|i| state.get_var_str(&i.to_string()).unwrap_or_default())
+        .collect()
 }
 
 /// Simple arithmetic evaluation.
@@ -540,172 +893,30 @@ fn evaluate_arithmetic(expr: &str, state: &ShellState) -> Option<String> {
 
 /// Execute a command substitution and return its output.
 ///
-/// This runs the command synchronously and captures stdout.
-/// The output is trimmed of trailing newlines (standard shell behavior).
-fn execute_command_substitution(list: &SequentialList, state: &ShellState) -> String {
-    // Serialize the command to a string for shell execution
-    let command_str = serialize_sequential_list(list);
-
-    // Use the system shell to execute (handles complex cases like pipes)
-    let output = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(&command_str)
-        .current_dir(state.cwd())
-        .envs(state.env_vars().iter().map(|(k, v)| {
-            (
-                k.to_string_lossy().to_string(),
-                v.to_string_lossy().to_string(),
-            )
-        }))
-        .output();
-
-    match output {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            // Shell convention: strip trailing newlines
-            stdout.trim_end_matches('\n').to_string()
-        }
-        Err(_) => String::new(),
-    }
-}
-
-/// Serialize a SequentialList back to shell command string.
-fn serialize_sequential_list(list: &SequentialList) -> String {
-    list.items
-        .iter()
-        .map(serialize_sequence)
-        .collect::<Vec<_>>()
-        .join("; ")
-}
-
-fn serialize_sequence(seq: &Sequence) -> String {
-    let mut result = serialize_pipeline(&seq.current);
-    if let Some(next) = &seq.next {
-        let op_str = match next.op {
-            BooleanListOp::And => " && ",
-            BooleanListOp::Or => " || ",
-        };
-        result.push_str(op_str);
-        result.push_str(&serialize_sequence(&next.sequence));
-    }
-    result
-}
-
-fn serialize_pipeline(pipeline: &Pipeline) -> String {
-    pipeline
-        .commands
-        .iter()
-        .map(serialize_pipeline_command)
-        .collect::<Vec<_>>()
-        .join(" | ")
-}
-
-fn serialize_pipeline_command(cmd: &PipelineCommand) -> String {
-    match &cmd.inner {
-        Command::Simple(simple) => serialize_simple_command(simple),
-        Command::Subshell(list) => format!("({})", serialize_sequential_list(list)),
-        Command::If(if_clause) => serialize_if_clause(if_clause),
-    }
-}
-
-fn serialize_if_clause(if_clause: &crate::parser::IfClause) -> String {
-    let mut result = format!(
-        "if {}; then {}",
-        serialize_sequential_list(&if_clause.condition),
-        serialize_sequential_list(&if_clause.then_part)
-    );
-    if let Some(else_part) = &if_clause.else_part {
-        result.push_str(&format!("; else {}", serialize_sequential_list(else_part)));
-    }
-    result.push_str("; fi");
-    result
-}
-
-fn serialize_simple_command(cmd: &SimpleCommand) -> String {
-    let mut parts = Vec::new();
-
-    // Add env vars
-    for env in &cmd.env_vars {
-        parts.push(format!("{}={}", env.name, serialize_word(&env.value)));
-    }
-
-    // Add args
-    for arg in &cmd.args {
-        parts.push(serialize_word(arg));
-    }
-
-    // Add redirects
-    for redirect in &cmd.redirects {
-        parts.push(serialize_redirect(redirect));
-    }
-
-    parts.join(" ")
-}
-
-fn serialize_word(word: &Word) -> String {
-    let mut result = String::new();
-    for part in &word.parts {
-        result.push_str(&serialize_word_part(part));
-    }
-    result
-}
-
-fn serialize_word_part(part: &WordPart) -> String {
-    match part {
-        WordPart::Text(s) => s.clone(),
-        WordPart::SingleQuoted(s) => format!("'{}'", s),
-        WordPart::DoubleQuoted(parts) => {
-            let inner: String = parts.iter().map(serialize_word_part).collect();
-            format!("\"{}\"", inner)
-        }
-        WordPart::Variable(name) => format!("${}", name),
-        WordPart::Tilde(suffix) => match suffix {
-            Some(s) => format!("~{}", s),
-            None => "~".to_string(),
-        },
-        WordPart::Glob(glob) => match glob {
-            GlobPart::Star => "*".to_string(),
-            GlobPart::Question => "?".to_string(),
-            GlobPart::DoubleStar => "**".to_string(),
-            GlobPart::CharClass(class) => format!("[{}]", class),
-        },
-        WordPart::CommandSubstitution(list) => {
-            format!("$({})", serialize_sequential_list(list))
-        }
-        WordPart::Arithmetic(expr) => format!("$(({}))", expr),
-    }
-}
-
-fn serialize_redirect(redirect: &Redirect) -> String {
-    let op_str = match redirect.op {
-        RedirectOp::Input => "<",
-        RedirectOp::Output => ">",
-        RedirectOp::Append => ">>",
-        RedirectOp::HereDoc => "<<",
-        RedirectOp::HereString => "<<<",
-        RedirectOp::OutputBoth => "&>",
-        RedirectOp::DupOutput => ">&",
-        RedirectOp::DupInput => "<&",
-    };
-
-    let target_str = match &redirect.target {
-        RedirectTarget::File(word) => serialize_word(word),
-        RedirectTarget::Fd(fd) => format!("{}", fd),
-        RedirectTarget::HereDoc(content) => content.clone(),
-    };
+/// The commands run in a subshell of this interpreter, so the state's command
+/// filter applies to them as to any other command. The output is trimmed of
+/// trailing newlines (standard shell behavior).
+async fn execute_command_substitution(list: &SequentialList, state: &ShellState) -> String {
+    let (reader, writer) = pipe();
+    let output = reader.pipe_to_string_handle();
+    execute_sequential_list_with_pipes(
+        list.clone(),
+        Rc::new(state.clone_for_subshell()),
+        ShellPipeReader::from_string(String::new()),
+        writer,
+        ShellPipeWriter::stderr(),
+    )
+    .await;
 
-    if let Some(fd) = redirect.fd {
-        format!("{}{}{}", fd, op_str, target_str)
-    } else {
-        format!("{}{}", op_str, target_str)
-    }
+    let output = output.await.unwrap_or_default();
+    output.trim_end_matches('\n').to_string()
 }
 
 // ============================================================================
 // Redirection Handling
 // ============================================================================
 
-fn apply_redirects(
+async fn apply_redirects(
     stdin: ShellPipeReader,
     stdout: ShellPipeWriter,
     stderr: ShellPipeWriter,
@@ -720,7 +931,7 @@ fn apply_redirects(
         match redirect.op {
             RedirectOp::Input => {
                 if let RedirectTarget::File(word) = &redirect.target {
-                    let path = expand_word(word, state);
+                    let path = expand_word(word, state).await;
                     let full_path = if PathBuf::from(&path).is_absolute() {
                         PathBuf::from(&path)
                     } else {
@@ -735,7 +946,7 @@ fn apply_redirects(
             RedirectOp::Output => {
                 let fd = redirect.fd.unwrap_or(1);
                 if let RedirectTarget::File(word) = &redirect.target {
-                    let path = expand_word(word, state);
+                    let path = expand_word(word, state).await;
                     let full_path = if PathBuf::from(&path).is_absolute() {
                         PathBuf::from(&path)
                     } else {
@@ -754,7 +965,7 @@ fn apply_redirects(
             RedirectOp::Append => {
                 let fd = redirect.fd.unwrap_or(1);
                 if let RedirectTarget::File(word) = &redirect.target {
-                    let path = expand_word(word, state);
+                    let path = expand_word(word, state).await;
                     let full_path = if PathBuf::from(&path).is_absolute() {
                         PathBuf::from(&path)
                     } else {
@@ -776,7 +987,7 @@ fn apply_redirects(
             }
             RedirectOp::OutputBoth => {
                 if let RedirectTarget::File(word) = &redirect.target {
-                    let path = expand_word(word, state);
+                    let path = expand_word(word, state).await;
                     let full_path = if PathBuf::from(&path).is_absolute() {
                         PathBuf::from(&path)
                     } else {
@@ -805,10 +1016,15 @@ fn apply_redirects(
             RedirectOp::DupInput => {
                 // <& style - less common
             }
-            RedirectOp::HereDoc | RedirectOp::HereString => {
-                // Here documents
+            RedirectOp::HereDoc => {
+                if let RedirectTarget::HereDoc(word) = &redirect.target {
+                    let content = expand_word(word, state).await;
+                    final_stdin = ShellPipeReader::from_string(content);
+                }
+            }
+            RedirectOp::HereString => {
                 if let RedirectTarget::File(word) = &redirect.target {
-                    let content = expand_word(word, state);
+                    let content = expand_word(word, state).await;
                     final_stdin = ShellPipeReader::from_string(content);
                 }
             }
@@ -871,6 +1087,200 @@ mod tests {
         let word = Word {
             parts: vec![WordPart::Variable("FOO".to_string())],
         };
-        assert_eq!(expand_word(&word, &state), "bar");
+        assert_eq!(expand_word(&word, &state).await, "bar");
+    }
+
+    #[tokio::test]
+    async fn test_command_filter() {
+        let mut state = ShellState::new_default();
+        state.set_command_filter(Rc::new(|name: &str, _: Option<&std::path::Path>| {
+            name != "exit"
+        }));
+        let state = Rc::new(state);
+
+        assert_eq!(execute_str("echo allowed", state.clone()).await.unwrap(), 0);
+        assert_eq!(execute_str("exit 3", state.clone()).await.unwrap(), 126);
+        assert_eq!(
+            execute_str("exit 3 || echo fallback", state).await.unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_substitutions_are_filtered() {
+        let root = std::env::temp_dir().join(format!("forge-subst-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let mut state = ShellState::new_default();
+        state.set_cwd(root.clone());
+        state.set_command_filter(Rc::new(|name: &str, _: Option<&std::path::Path>| {
+            name != "touch"
+        }));
+        let state = Rc::new(state);
+
+        let (_, output) = execute_capture("echo \"$(echo hi) `echo there`\"", state.clone()).await;
+        assert_eq!(output, "hi there\n");
+
+        // Substitutions run in the interpreter, however deeply nested, so
+        // denied commands never run and `eval` is not a way around the filter
+        for script in [
+            "echo $(touch a)",
+            "echo `touch b`",
+            "echo $(echo $(touch c))",
+            "x=$(touch d)",
+            "echo $(eval touch e)",
+        ] {
+            execute_capture(script, state.clone()).await;
+        }
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Run a command and capture what it writes to stdout.
+    async fn execute_capture(command: &str, state: Rc<ShellState>) -> (i32, String) {
+        let list = crate::parser::parse(command).unwrap();
+        let (reader, writer) = pipe();
+        let code = execute_with_pipes(
+            list,
+            state,
+            ShellPipeReader::stdin(),
+            writer,
+            ShellPipeWriter::stderr(),
+        )
+        .await;
+        let mut output = Vec::new();
+        reader.pipe_to(&mut output).unwrap();
+        (code, String::from_utf8(output).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_text_builtins() {
+        let state = create_test_state();
+
+        let (code, output) =
+            execute_capture("printf 'b\\na\\nb\\n' | sort | uniq -c", state.clone()).await;
+        assert_eq!(code, 0);
+        assert_eq!(output, "      1 a\n      2 b\n");
+
+        let (code, output) =
+            execute_capture("printf '%s\\n' one two three | grep -n t", state.clone()).await;
+        assert_eq!(code, 0);
+        assert_eq!(output, "2:two\n3:three\n");
+
+        let (code, _) = execute_capture("[ -d / ] && test 2 -gt 1", state.clone()).await;
+        assert_eq!(code, 0);
+        let (code, _) = execute_capture("[ abc = abd ]", state).await;
+        assert_eq!(code, 1);
+    }
+
+    #[tokio::test]
+    async fn test_loops() {
+        let state = create_test_state();
+
+        let (code, output) = execute_capture(
+            "for i in a b c d; do\n  if [ $i = b ]; then continue; fi\n  if [ $i = d ]; then break; fi\n  echo $i\ndone",
+            state.clone(),
+        )
+        .await;
+        assert_eq!(code, 0);
+        assert_eq!(output, "a\nc\n");
+
+        let (_, output) = execute_capture(
+            "for i in 1 2; do for j in x y; do echo $i$j; break 2; done; done",
+            state.clone(),
+        )
+        .await;
+        assert_eq!(output, "1x\n");
+
+        let (_, output) = execute_capture(
+            "n=; while [ \"$n\" != xxx ]; do n=x$n; done; echo $n; until true; do echo never; done",
+            state.clone(),
+        )
+        .await;
+        assert_eq!(output, "xxx\n");
+
+        let (code, _) = execute_capture("while true; do exit 3; done", state.clone()).await;
+        assert_eq!(code, 3);
+
+        let (code, output) = execute_capture(
+            "for i in 1 2; do
+  exit 3
+  echo $i
+done
+echo after",
+            state,
+        )
+        .await;
+        assert_eq!(code, 3);
+        assert_eq!(output, "");
+    }
+
+    #[tokio::test]
+    async fn test_exit_stops_the_script() {
+        let state = create_test_state();
+
+        let (code, output) = execute_capture("exit 3; echo hi", state.clone()).await;
+        assert_eq!(code, 3);
+        assert_eq!(output, "");
+
+        let (code, output) = execute_capture("echo one\nexit 0\necho two", state).await;
+        assert_eq!(code, 0);
+        assert_eq!(output, "one\n");
+    }
+
+    #[tokio::test]
+    async fn test_case() {
+        let state = create_test_state();
+        let script = "for f in main.rs README.md x; do\n  case $f in\n    *.rs) echo rust ;;\n    *.md | *.txt) echo text ;;\n    *) echo other\n  esac\ndone";
+
+        let (code, output) = execute_capture(script, state.clone()).await;
+        assert_eq!(code, 0);
+        assert_eq!(output, "rust\ntext\nother\n");
+
+        // Quoted patterns match literally
+        let (_, output) = execute_capture("case 'a*' in 'a*') echo quoted;; esac", state).await;
+        assert_eq!(output, "quoted\n");
+    }
+
+    #[tokio::test]
+    async fn test_functions() {
+        let state = create_test_state();
+        let script = "x=outer\ngreet() {\n  local x=$1\n  echo \"hello $x ($#)\"\n  return 4\n  echo unreachable\n}\ngreet world extra; echo \"$? $x\"";
+
+        let (code, output) = execute_capture(script, state.clone()).await;
+        assert_eq!(code, 0);
+        assert_eq!(output, "hello world (2)\n4 outer\n");
+
+        let (_, output) = execute_capture(
+            "count() { if [ $# -gt 0 ]; then echo $1; count $2 $3; fi; }; count 3 2 1",
+            state.clone(),
+        )
+        .await;
+        assert_eq!(output, "3\n2\n1\n");
+
+        let (code, _) = execute_capture("return 2", state.clone()).await;
+        assert_eq!(code, 1);
+        let (code, _) = execute_capture("local x=1", state).await;
+        assert_eq!(code, 1);
+    }
+
+    #[tokio::test]
+    async fn test_heredocs_and_groups() {
+        let state = create_test_state();
+        state.set_shell_var("name", "forge");
+
+        let (_, output) = execute_capture(
+            "cat <<EOF; cat <<'RAW'\nhi $name\nEOF\n$name\nRAW\necho done",
+            state.clone(),
+        )
+        .await;
+        assert_eq!(output, "hi forge\n$name\ndone\n");
+
+        let (_, output) = execute_capture("{ echo a; echo b; } | wc -l", state.clone()).await;
+        assert_eq!(output.trim(), "2");
+
+        // Brace groups share the shell's variables, subshells do not
+        let (_, output) = execute_capture("{ v=1; }; (v=2; w=3); echo \"$v$w\"", state).await;
+        assert_eq!(output, "1\n");
     }
 }
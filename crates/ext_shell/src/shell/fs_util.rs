@@ -171,7 +171,7 @@ pub fn list_directory(path: &Path, options: &ListOptions) -> FsResult<Vec<ListEn
         });
     } else {
         // Sort by name (case-insensitive)
-        entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        entries.sort_by_key(|e| e.name.to_lowercase());
     }
 
     if options.reverse {
@@ -228,9 +228,12 @@ pub fn read_tail(path: &Path, n: usize) -> FsResult<Vec<String>> {
 }
 
 /// Count lines, words, and bytes in a file.
+///
+/// Lines are newline characters, as `wc` counts them, so a final line
+/// without a newline is not counted.
 pub fn word_count(path: &Path) -> FsResult<(usize, usize, usize)> {
     let content = fs::read_to_string(path)?;
-    let lines = content.lines().count();
+    let lines = content.matches('\n').count();
     let words = content.split_whitespace().count();
     let bytes = content.len();
     Ok((lines, words, bytes))
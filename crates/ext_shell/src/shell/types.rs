@@ -3,6 +3,7 @@
 //! This module provides the fundamental data structures for shell execution:
 //! - `ShellState` - Holds environment, cwd, and command registry
 //! - `ExecuteResult` - Result of command execution
+//! - `ControlFlow` - `break`, `continue` and `return` leaving enclosing constructs
 //! - `EnvChange` - Environment modifications from commands
 //! - `ShellPipeReader`/`ShellPipeWriter` - Pipe abstractions
 //! - `KillSignal` - Hierarchical signal propagation
@@ -28,6 +29,7 @@ use tokio::task::JoinHandle;
 
 use super::child_process_tracker::ChildProcessTracker;
 use super::commands::ShellCommand;
+use crate::parser::PipelineCommand;
 
 // ============================================================================
 // Tree Exit Code Cell
@@ -59,10 +61,24 @@ impl TreeExitCodeCell {
 // Shell State
 // ============================================================================
 
+/// Decides whether a command may run.
+///
+/// Receives the command name as written (`git`, `rm`) and, for external
+/// commands, the resolved executable path.
+pub type CommandFilter = Rc<dyn Fn(&str, Option<&Path>) -> bool>;
+
+/// Value of a variable shadowed by `local`, restored when the function returns.
+#[derive(Debug, Clone)]
+struct SavedVar {
+    value: OsString,
+    /// Whether it was an environment variable rather than a shell variable
+    exported: bool,
+}
+
 /// Central state container for shell execution.
 ///
 /// Holds environment variables, shell-local variables, current working directory,
-/// registered commands, and signal handling infrastructure.
+/// registered commands, shell functions, and signal handling infrastructure.
 ///
 /// Uses `RefCell` for interior mutability to support mutation through `Rc<ShellState>`.
 #[derive(Clone)]
@@ -81,6 +97,14 @@ pub struct ShellState {
     process_tracker: ChildProcessTracker,
     /// Exit code for async command trees
     tree_exit_code_cell: TreeExitCodeCell,
+    /// Restricts which commands may run (None = all)
+    command_filter: Option<CommandFilter>,
+    /// Shell functions defined by the script
+    functions: RefCell<HashMap<String, Rc<PipelineCommand>>>,
+    /// One scope per running function call: variables to restore on return
+    local_scopes: RefCell<Vec<HashMap<OsString, Option<SavedVar>>>>,
+    /// Number of enclosing loops, for `break` and `continue`
+    loop_depth: Cell<usize>,
 }
 
 impl ShellState {
@@ -110,6 +134,10 @@ impl ShellState {
             kill_signal,
             process_tracker: ChildProcessTracker::new(),
             tree_exit_code_cell: TreeExitCodeCell::default(),
+            command_filter: None,
+            functions: RefCell::new(HashMap::new()),
+            local_scopes: RefCell::new(Vec::new()),
+            loop_depth: Cell::new(0),
         };
 
         // Normalize environment variables
@@ -185,6 +213,7 @@ impl ShellState {
                 self.shell_vars.borrow_mut().remove(name);
                 self.env_vars.borrow_mut().remove(name);
             }
+            EnvChange::DeclareLocal(name) => self.declare_local(name),
             EnvChange::Cd(new_dir) => {
                 self.set_cwd(new_dir.clone());
             }
@@ -235,6 +264,98 @@ impl ShellState {
         self.env_vars.borrow_mut().remove(name);
     }
 
+    /// Define (or replace) a shell function.
+    pub fn define_function(&self, name: impl Into<String>, body: PipelineCommand) {
+        self.functions
+            .borrow_mut()
+            .insert(name.into(), Rc::new(body));
+    }
+
+    /// Look up a shell function by name.
+    pub fn function(&self, name: &str) -> Option<Rc<PipelineCommand>> {
+        self.functions.borrow().get(name).cloned()
+    }
+
+    /// Start a function call's scope for `local` variables.
+    pub fn push_local_scope(&self) {
+        self.local_scopes.borrow_mut().push(HashMap::new());
+    }
+
+    /// End a function call's scope, restoring the variables it shadowed.
+    pub fn pop_local_scope(&self) {
+        let Some(scope) = self.local_scopes.borrow_mut().pop() else {
+            return;
+        };
+        for (name, saved) in scope {
+            self.shell_vars.borrow_mut().remove(&name);
+            self.env_vars.borrow_mut().remove(&name);
+            match saved {
+                Some(SavedVar {
+                    value,
+                    exported: true,
+                }) => {
+                    self.env_vars.borrow_mut().insert(name, value);
+                }
+                Some(SavedVar {
+                    value,
+                    exported: false,
+                }) => {
+                    self.shell_vars.borrow_mut().insert(name, value);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Number of function calls in progress.
+    pub fn function_depth(&self) -> usize {
+        self.local_scopes.borrow().len()
+    }
+
+    /// Make a variable local to the current function call, starting out unset.
+    ///
+    /// Outside a function, or if the variable is already local, this does nothing.
+    pub fn declare_local(&self, name: &OsStr) {
+        let mut scopes = self.local_scopes.borrow_mut();
+        let Some(scope) = scopes.last_mut() else {
+            return;
+        };
+        if scope.contains_key(name) {
+            return;
+        }
+
+        let saved = if let Some(value) = self.env_vars.borrow_mut().remove(name) {
+            Some(SavedVar {
+                value,
+                exported: true,
+            })
+        } else {
+            self.shell_vars
+                .borrow_mut()
+                .remove(name)
+                .map(|value| SavedVar {
+                    value,
+                    exported: false,
+                })
+        };
+        scope.insert(name.to_os_string(), saved);
+    }
+
+    /// Number of loops enclosing the command being run.
+    pub fn loop_depth(&self) -> usize {
+        self.loop_depth.get()
+    }
+
+    /// Note that a loop has started.
+    pub fn enter_loop(&self) {
+        self.loop_depth.set(self.loop_depth.get() + 1);
+    }
+
+    /// Note that a loop has finished.
+    pub fn leave_loop(&self) {
+        self.loop_depth.set(self.loop_depth.get().saturating_sub(1));
+    }
+
     /// Get the kill signal.
     pub fn kill_signal(&self) -> &KillSignal {
         &self.kill_signal
@@ -250,6 +371,19 @@ impl ShellState {
         &self.tree_exit_code_cell
     }
 
+    /// Restrict which commands this state (and its subshells) may run.
+    pub fn set_command_filter(&mut self, filter: CommandFilter) {
+        self.command_filter = Some(filter);
+    }
+
+    /// Whether a command may run under the configured filter.
+    pub fn is_command_allowed(&self, name: &OsStr, path: Option<&Path>) -> bool {
+        match &self.command_filter {
+            Some(filter) => filter(&name.to_string_lossy(), path),
+            None => true,
+        }
+    }
+
     /// Resolve a command by name.
     pub fn resolve_custom_command(&self, name: &OsStr) -> Option<Rc<dyn ShellCommand>> {
         name.to_str()
@@ -340,6 +474,8 @@ pub enum EnvChange {
     UnsetVar(OsString),
     /// Change directory: `cd path`
     Cd(PathBuf),
+    /// Make a variable local to the running function: `local VAR`
+    DeclareLocal(OsString),
 }
 
 // ============================================================================
@@ -356,6 +492,19 @@ pub enum ExecuteResult {
     Exit(i32, Vec<JoinHandle<i32>>),
     /// Continue execution with exit code, env changes, and async handles
     Continue(i32, Vec<EnvChange>, Vec<JoinHandle<i32>>),
+    /// Leave enclosing loops or the current function
+    Control(ControlFlow, Vec<JoinHandle<i32>>),
+}
+
+/// How `break`, `continue` and `return` leave the constructs around them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// Leave this many enclosing loops: `break [n]`
+    Break(usize),
+    /// Start the next iteration of the nth enclosing loop: `continue [n]`
+    Continue(usize),
+    /// Return from the current function with a code: `return [code]`
+    Return(i32),
 }
 
 impl ExecuteResult {
@@ -369,14 +518,18 @@ impl ExecuteResult {
         match self {
             ExecuteResult::Exit(code, _) => *code,
             ExecuteResult::Continue(code, _, _) => *code,
+            ExecuteResult::Control(ControlFlow::Return(code), _) => *code,
+            ExecuteResult::Control(_, _) => 0,
         }
     }
 
     /// Extract exit code and handles from the result.
     pub fn into_exit_code_and_handles(self) -> (i32, Vec<JoinHandle<i32>>) {
+        let code = self.exit_code();
         match self {
-            ExecuteResult::Exit(code, handles) => (code, handles),
-            ExecuteResult::Continue(code, _, handles) => (code, handles),
+            ExecuteResult::Exit(_, handles)
+            | ExecuteResult::Continue(_, _, handles)
+            | ExecuteResult::Control(_, handles) => (code, handles),
         }
     }
 
@@ -9,7 +9,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Mutex};
 use thiserror::Error;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
@@ -43,6 +43,22 @@ struct SignalSubscription {
     receiver: Option<mpsc::Receiver<SignalEvent>>,
     tasks: Vec<JoinHandle<()>>,
     running: Arc<AtomicBool>,
+    signals: Vec<String>,
+}
+
+/// Subscription count per signal name, across every runtime in the process
+static SUBSCRIBED: LazyLock<Mutex<HashMap<String, usize>>> = LazyLock::new(Default::default);
+
+fn subscribed() -> std::sync::MutexGuard<'static, HashMap<String, usize>> {
+    SUBSCRIBED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether the app is subscribed to `signal` (e.g. `"SIGTERM"`)
+///
+/// The host uses this to leave termination signals the app handles itself
+/// alone. Can be called from any thread.
+pub fn is_subscribed(signal: &str) -> bool {
+    subscribed().get(signal).is_some_and(|count| *count > 0)
 }
 
 /// Signals state stored in OpState - tracks signal subscriptions
@@ -136,6 +152,7 @@ async fn op_signals_subscribe(
         let (tx, rx) = mpsc::channel(64);
         let running = Arc::new(AtomicBool::new(true));
         let mut tasks = Vec::with_capacity(parsed.len());
+        let names: Vec<String> = parsed.iter().map(|(name, _)| name.clone()).collect();
 
         for (name, kind) in parsed {
             let mut stream = tokio::signal::unix::signal(kind)
@@ -178,10 +195,15 @@ async fn op_signals_subscribe(
                     receiver: Some(rx),
                     tasks,
                     running,
+                    signals: names.clone(),
                 },
             );
             next
         };
+        let mut subscribed = subscribed();
+        for name in names {
+            *subscribed.entry(name).or_default() += 1;
+        }
 
         Ok(id)
     }
@@ -238,6 +260,12 @@ fn op_signals_unsubscribe(state: &mut OpState, #[bigint] id: u64) -> bool {
     for handle in sub.tasks {
         handle.abort();
     }
+    let mut subscribed = subscribed();
+    for name in &sub.signals {
+        if let Some(count) = subscribed.get_mut(name) {
+            *count = count.saturating_sub(1);
+        }
+    }
 
     true
 }
@@ -436,13 +436,26 @@ impl Default for StorageCapabilities {
 // Helper Functions
 // ============================================================================
 
+fn check_storage(state: &OpState) -> Result<(), StorageError> {
+    if let Some(caps) = state.try_borrow::<StorageCapabilities>() {
+        caps.checker
+            .check_storage()
+            .map_err(StorageError::permission_denied)
+    } else {
+        Ok(())
+    }
+}
+
 /// Get or create the storage database connection
+///
+/// Every op goes through here, so this is also where access is checked.
 async fn get_connection(
     state: &Rc<RefCell<OpState>>,
 ) -> Result<Arc<Mutex<Connection>>, StorageError> {
     // Check if already connected
     {
         let s = state.borrow();
+        check_storage(&s)?;
         if let Some(conn) = s.try_borrow::<StorageConnection>() {
             return Ok(conn.connection.clone());
         }
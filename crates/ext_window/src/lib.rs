@@ -540,6 +540,12 @@ pub enum WindowCmd {
     RendererReady {
         window_id: String,
     },
+    /// Settle a renderer's `window.host.invoke` without going through Deno,
+    /// e.g. when the invoke channel is denied by the capability checker.
+    InvokeReply {
+        window_id: String,
+        reply: serde_json::Value,
+    },
 }
 
 // ============================================================================
@@ -10,6 +10,10 @@ use crate::{
 use deno_ast::{MediaType, ParseParams, TranspileModuleOptions, TranspileOptions};
 use deno_core::ModuleSpecifier;
 pub use ext_ipc::IpcEvent;
+use ext_ipc::{
+    invoke_error_reply, BinaryStore, IpcErrorCode, IpcRouter, StreamRead, BINARY_IPC_HOST,
+    INVOKE_CANCEL_CHANNEL, INVOKE_REPLY_CHANNEL,
+};
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
@@ -81,6 +85,12 @@ pub struct WindowManager<U: 'static> {
     // Asset provider (provided by forge-host)
     asset_provider: Arc<dyn AssetProvider>,
 
+    // Binary IPC payloads and streams, shared with ext_ipc
+    binary_store: BinaryStore,
+
+    // ext_ipc's event router, told when a window is closed programmatically
+    ipc_router: Option<IpcRouter>,
+
     // Renderer ready tracking - windows signal when their JS context is ready
     renderer_ready: HashMap<String, bool>,
 
@@ -126,12 +136,29 @@ impl<U: 'static> WindowManager<U> {
             capabilities,
             preload_js,
             asset_provider,
+            binary_store: BinaryStore::new(),
+            ipc_router: None,
             renderer_ready: HashMap::new(),
             pending_messages: HashMap::new(),
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Share a binary IPC store with ext_ipc (a private one is used otherwise)
+    pub fn with_binary_store(mut self, binary_store: BinaryStore) -> Self {
+        self.binary_store = binary_store;
+        self
+    }
+
+    /// Cancel a window's pending invokes on this router when it is closed
+    ///
+    /// Windows closed by the user already send a `close` event through the
+    /// router; this covers `closeWindow()` and other programmatic closes.
+    pub fn with_ipc_router(mut self, ipc_router: IpcRouter) -> Self {
+        self.ipc_router = Some(ipc_router);
+        self
+    }
+
     /// Get the menu ID map for use in menu event thread
     pub fn menu_id_map(&self) -> Arc<Mutex<HashMap<muda::MenuId, (String, String)>>> {
         self.menu_id_map.clone()
@@ -271,6 +298,31 @@ impl<U: 'static> WindowManager<U> {
             }
         }
 
+        self.dispatch_to_renderer(window_id, channel, payload);
+    }
+
+    /// Send a message to every open window
+    ///
+    /// Each window applies its own channel allowlist, as with
+    /// [`send_to_renderer`](Self::send_to_renderer).
+    pub fn broadcast_to_renderers(&mut self, channel: &str, payload: &str) {
+        let window_ids: Vec<String> = self.webviews.keys().cloned().collect();
+        for window_id in window_ids {
+            self.send_to_renderer(&window_id, channel, payload);
+        }
+    }
+
+    /// Settle a pending `window.host.invoke` in a renderer
+    ///
+    /// Replies skip the per-window channel filter: the invoke's channel was
+    /// checked when it arrived, and the reply travels on the reserved
+    /// `__invoke_reply__` channel the renderer intercepts.
+    pub fn send_invoke_reply(&mut self, window_id: &str, reply: &serde_json::Value) {
+        self.dispatch_to_renderer(window_id, INVOKE_REPLY_CHANNEL, &reply.to_string());
+    }
+
+    /// Deliver a message to a renderer, queueing it until the renderer is ready
+    fn dispatch_to_renderer(&mut self, window_id: &str, channel: &str, payload: &str) {
         // Check if renderer is ready to receive messages
         let is_ready = self.renderer_ready.get(window_id).copied().unwrap_or(false);
 
@@ -413,24 +465,59 @@ impl<U: 'static> WindowManager<U> {
                     return;
                 }
 
-                let allowed = if let Some(ref caps) = capabilities {
+                let invoke_id = val
+                    .get("invokeId")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+
+                // Cancellations refer to an invoke whose channel was already
+                // checked; ext_ipc drops them if the invoke is not pending.
+                if channel == INVOKE_CANCEL_CHANNEL {
+                    if invoke_id.is_some() {
+                        let _ = to_deno_tx_clone.try_send(IpcEvent {
+                            window_id: win_id_for_ipc.clone(),
+                            channel,
+                            payload: serde_json::json!(null),
+                            event_type: None,
+                            invoke_id,
+                        });
+                    }
+                    return;
+                }
+
+                let check = if let Some(ref caps) = capabilities {
                     caps.check_channel(&channel, ipc_allowed_channels.as_deref())
-                        .is_ok()
                 } else {
-                    true
+                    Ok(())
                 };
 
-                if allowed {
-                    let payload = val
-                        .get("payload")
-                        .cloned()
-                        .unwrap_or(serde_json::json!(null));
-                    let _ = to_deno_tx_clone.try_send(IpcEvent {
-                        window_id: win_id_for_ipc.clone(),
-                        channel,
-                        payload,
-                        event_type: None,
-                    });
+                match check {
+                    Ok(()) => {
+                        let payload = val
+                            .get("payload")
+                            .cloned()
+                            .unwrap_or(serde_json::json!(null));
+                        let _ = to_deno_tx_clone.try_send(IpcEvent {
+                            window_id: win_id_for_ipc.clone(),
+                            channel,
+                            payload,
+                            event_type: None,
+                            invoke_id,
+                        });
+                    }
+                    Err(reason) => {
+                        // Reject a denied invoke right away instead of letting it time out
+                        if let Some(invoke_id) = invoke_id {
+                            let _ = window_cmd_tx_clone.try_send(WindowCmd::InvokeReply {
+                                window_id: win_id_for_ipc.clone(),
+                                reply: invoke_error_reply(
+                                    &invoke_id,
+                                    IpcErrorCode::PermissionDenied,
+                                    reason,
+                                ),
+                            });
+                        }
+                    }
                 }
             }
         });
@@ -439,7 +526,7 @@ impl<U: 'static> WindowManager<U> {
         let app_dir = self.config.app_dir.clone();
         let is_dev_mode = self.config.dev_mode;
         let asset_provider = self.asset_provider.clone();
-        wv_builder = wv_builder.with_custom_protocol("app".into(), move |_ctx, request| {
+        let serve_asset = move |request: wry::http::Request<Vec<u8>>| {
             let uri = request.uri().to_string();
             let mut path = uri
                 .strip_prefix("app://")
@@ -512,7 +599,26 @@ impl<U: 'static> WindowManager<U> {
                 .header("Content-Type", "text/plain; charset=utf-8")
                 .body(Cow::Owned(format!("Not found: {}", path).into_bytes()))
                 .unwrap()
-        });
+        };
+
+        // Asynchronous so binary IPC stream reads can wait for the next chunk
+        let binary_store = self.binary_store.clone();
+        let win_id_for_protocol = win_id.clone();
+        wv_builder = wv_builder.with_asynchronous_custom_protocol(
+            "app".into(),
+            move |_ctx, request, responder| {
+                let uri = request.uri().to_string();
+                let binary_path = uri
+                    .strip_prefix("app://")
+                    .and_then(|path| path.strip_prefix(BINARY_IPC_HOST));
+                match binary_path {
+                    Some(path) => {
+                        serve_binary_ipc(&binary_store, &win_id_for_protocol, path, responder)
+                    }
+                    None => responder.respond(serve_asset(request)),
+                }
+            },
+        );
 
         // Set URL
         let start_url = opts.url.as_deref().unwrap_or("app://index.html");
@@ -554,6 +660,10 @@ impl<U: 'static> WindowManager<U> {
             // Clean up renderer ready state and any queued messages
             self.renderer_ready.remove(window_id);
             self.pending_messages.remove(window_id);
+            self.binary_store.remove_window(window_id);
+            if let Some(router) = &self.ipc_router {
+                router.forget_window(window_id);
+            }
 
             let _ = self.window_events_tx.try_send(WindowSystemEvent {
                 window_id: window_id.to_string(),
@@ -1352,6 +1462,7 @@ impl<U: 'static> WindowManager<U> {
                 channel: "__window__".to_string(),
                 payload: serde_json::json!({}),
                 event_type: Some("close".to_string()),
+                invoke_id: None,
             });
             self.webviews.remove(&win_id);
             self.tao_windows.remove(&win_id);
@@ -1372,6 +1483,7 @@ impl<U: 'static> WindowManager<U> {
                 channel: "__window__".to_string(),
                 payload: serde_json::json!({}),
                 event_type: Some(event_type.to_string()),
+                invoke_id: None,
             });
             tracing::debug!("Window {} {}", win_id, event_type);
         }
@@ -1388,6 +1500,7 @@ impl<U: 'static> WindowManager<U> {
                     "height": height
                 }),
                 event_type: Some("resize".to_string()),
+                invoke_id: None,
             });
             tracing::debug!("Window {} resized to {}x{}", win_id, width, height);
         }
@@ -1404,6 +1517,7 @@ impl<U: 'static> WindowManager<U> {
                     "y": y
                 }),
                 event_type: Some("move".to_string()),
+                invoke_id: None,
             });
             tracing::debug!("Window {} moved to ({}, {})", win_id, x, y);
         }
@@ -1618,6 +1732,9 @@ impl<U: 'static> WindowManager<U> {
                 tracing::debug!("WindowCmd::RendererReady {}", window_id);
                 self.mark_renderer_ready(&window_id);
             }
+            WindowCmd::InvokeReply { window_id, reply } => {
+                self.send_invoke_reply(&window_id, &reply);
+            }
         }
     }
 }
@@ -1626,6 +1743,59 @@ impl<U: 'static> WindowManager<U> {
 // Helper Functions
 // ============================================================================
 
+/// Serve `app://__forge_ipc__/...` requests from the binary IPC store
+///
+/// - `blob/<id>`: a payload sent with `sendBinary()`, removed once fetched
+/// - `stream/<id>`: the next chunk (200), end of stream (204) or gone (410)
+/// - `stream/<id>/cancel`: the renderer cancelled its `ReadableStream`
+fn serve_binary_ipc(
+    store: &BinaryStore,
+    window_id: &str,
+    path: &str,
+    responder: wry::RequestAsyncResponder,
+) {
+    let mut parts = path.trim_matches('/').split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("blob"), Some(id), None) => {
+            let response = match store.take_blob(id, window_id) {
+                Some(data) => binary_ipc_response(StatusCode::OK, data),
+                None => binary_ipc_response(StatusCode::NOT_FOUND, Vec::new()),
+            };
+            responder.respond(response);
+        }
+        (Some("stream"), Some(id), None) => {
+            store.read_stream(
+                id,
+                window_id,
+                Box::new(move |read| {
+                    let response = match read {
+                        StreamRead::Chunk(data) => binary_ipc_response(StatusCode::OK, data),
+                        StreamRead::End => binary_ipc_response(StatusCode::NO_CONTENT, Vec::new()),
+                        StreamRead::Gone => binary_ipc_response(StatusCode::GONE, Vec::new()),
+                    };
+                    responder.respond(response);
+                }),
+            );
+        }
+        (Some("stream"), Some(id), Some("cancel")) => {
+            store.cancel_stream(id, Some(window_id));
+            responder.respond(binary_ipc_response(StatusCode::NO_CONTENT, Vec::new()));
+        }
+        _ => responder.respond(binary_ipc_response(StatusCode::NOT_FOUND, Vec::new())),
+    }
+}
+
+fn binary_ipc_response(status: StatusCode, body: Vec<u8>) -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/octet-stream")
+        .header("Cache-Control", "no-store")
+        // Pages loaded from a dev server fetch these cross-origin
+        .header("Access-Control-Allow-Origin", "*")
+        .body(Cow::Owned(body))
+        .unwrap()
+}
+
 fn is_ts_path(path: &str) -> bool {
     matches!(
         Path::new(path)
@@ -0,0 +1,396 @@
+//! Capability audit log
+//!
+//! Every allow/deny decision made by [`Capabilities`](crate::capabilities::Capabilities)
+//! for filesystem, network, process, environment, WASM, IPC channel and
+//! code signing checks is recorded as an [`AuditEvent`]. Events are appended
+//! as JSON lines to `<data dir>/<identifier>/audit/audit.jsonl` and broadcast
+//! to `auditEvents()` streams in `runtime:app`.
+//!
+//! Capability checks sit on hot paths, so they never touch the disk: events
+//! are handed to a background writer thread, which appends them through a
+//! buffer and flushes after each batch. [`AuditLog::flush`] waits for
+//! everything recorded so far to reach the file; the runtime calls it on
+//! shutdown.
+//!
+//! When the log would grow past `max_file_size_kb` it is rotated to
+//! `audit.1.jsonl` (the previous `audit.1.jsonl` becomes `audit.2.jsonl`, and
+//! so on); at most `max_files` rotated files are kept. `forge audit <app-dir>`
+//! reads these files and suggests a minimal `[permissions]` block from what a
+//! dev session actually used.
+//!
+//! # Attribution
+//!
+//! Each event names the op and extension that triggered the check. The
+//! runtime installs [`op_metrics_factory`] so the most recently dispatched op
+//! on the JS thread is known while its synchronous part runs. Checks made
+//! after an op's first `.await`, or from other threads, may carry no op.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [audit]
+//! enabled = true           # default
+//! max_file_size_kb = 5120  # rotate after 5 MiB
+//! max_files = 5            # rotated files to keep
+//! ```
+
+use deno_core::{Extension, OpMetricsEvent, OpMetricsFactoryFn, OpMetricsFn};
+use ext_app::{AuditEvent, AuditEventSource};
+use serde::Deserialize;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{mpsc, OnceLock};
+use tokio::sync::broadcast;
+
+/// Name of the active log file inside the audit directory
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+const DEFAULT_MAX_FILE_SIZE_KB: u64 = 5 * 1024;
+const DEFAULT_MAX_FILES: usize = 5;
+
+/// `[audit]` section of manifest.app.toml
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AuditConfig {
+    /// Record capability decisions (default: true)
+    pub enabled: Option<bool>,
+    /// Rotate the log once it reaches this size (default: 5120 KiB)
+    pub max_file_size_kb: Option<u64>,
+    /// Number of rotated files to keep (default: 5)
+    pub max_files: Option<usize>,
+}
+
+thread_local! {
+    /// Op currently being dispatched on this thread
+    static CURRENT_OP: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+/// Op metrics hook that tracks which op is running for attribution
+pub fn op_metrics_factory() -> OpMetricsFactoryFn {
+    Box::new(|_, _, decl| {
+        let name = decl.name;
+        let metrics: OpMetricsFn = Rc::new(move |_, event, _| match event {
+            OpMetricsEvent::Dispatched => CURRENT_OP.with(|op| op.set(Some(name))),
+            _ => CURRENT_OP.with(|op| {
+                if op.get() == Some(name) {
+                    op.set(None);
+                }
+            }),
+        });
+        Some(metrics)
+    })
+}
+
+/// Appends decisions to a size-rotated JSONL file
+struct RotatingWriter {
+    dir: PathBuf,
+    file: Option<BufWriter<File>>,
+    size: u64,
+    max_file_size: u64,
+    max_files: usize,
+}
+
+impl RotatingWriter {
+    fn path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(AUDIT_FILE)
+        } else {
+            self.dir.join(format!("audit.{}.jsonl", index))
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_file_size {
+            self.rotate()?;
+        }
+
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path(0))?;
+            self.size = file.metadata()?.len();
+            self.file = Some(BufWriter::new(file));
+        }
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{}", line)?;
+        }
+        self.size += len;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.flush()?;
+        self.file = None;
+        self.size = 0;
+        if self.max_files == 0 {
+            return std::fs::remove_file(self.path(0));
+        }
+
+        let _ = std::fs::remove_file(self.path(self.max_files));
+        for index in (0..self.max_files).rev() {
+            let from = self.path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.path(index + 1))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Work for the background writer thread
+enum WriterCommand {
+    Line(String),
+    /// Flush everything queued so far, then acknowledge
+    Flush(mpsc::Sender<()>),
+}
+
+/// Background writer loop: write whatever is queued, then flush once
+fn run_writer(mut writer: RotatingWriter, commands: mpsc::Receiver<WriterCommand>) {
+    while let Ok(first) = commands.recv() {
+        let mut acks = Vec::new();
+        for command in std::iter::once(first).chain(commands.try_iter()) {
+            match command {
+                WriterCommand::Line(line) => {
+                    if let Err(e) = writer.write_line(&line) {
+                        tracing::debug!(
+                            "Failed to write audit log {}: {}",
+                            writer.dir.display(),
+                            e
+                        );
+                    }
+                }
+                WriterCommand::Flush(ack) => acks.push(ack),
+            }
+        }
+        if let Err(e) = writer.flush() {
+            tracing::debug!("Failed to flush audit log {}: {}", writer.dir.display(), e);
+        }
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+}
+
+/// Records capability decisions to disk and to JS subscribers
+pub struct AuditLog {
+    dir: PathBuf,
+    writer: mpsc::Sender<WriterCommand>,
+    sender: broadcast::Sender<AuditEvent>,
+    /// Op name to extension name, filled once the extensions are built
+    op_extensions: OnceLock<HashMap<&'static str, String>>,
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog").field("dir", &self.dir).finish()
+    }
+}
+
+impl AuditLog {
+    /// Open the audit log in `dir`, creating the directory if needed
+    pub fn open(dir: PathBuf, max_file_size: u64, max_files: usize) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let (sender, _) = broadcast::channel(256);
+        let (writer, commands) = mpsc::channel();
+        let rotating = RotatingWriter {
+            dir: dir.clone(),
+            file: None,
+            size: 0,
+            max_file_size,
+            max_files,
+        };
+        std::thread::Builder::new()
+            .name("forge-audit".to_string())
+            .spawn(move || run_writer(rotating, commands))?;
+        Ok(Self {
+            writer,
+            dir,
+            sender,
+            op_extensions: OnceLock::new(),
+        })
+    }
+
+    /// Open the log for an app according to its `[audit]` section
+    ///
+    /// Returns `None` when auditing is disabled.
+    pub fn from_config(
+        identifier: &str,
+        config: Option<&AuditConfig>,
+    ) -> std::io::Result<Option<Self>> {
+        let config = config.cloned().unwrap_or_default();
+        if !config.enabled.unwrap_or(true) {
+            return Ok(None);
+        }
+        Self::open(
+            Self::default_dir(identifier),
+            config.max_file_size_kb.unwrap_or(DEFAULT_MAX_FILE_SIZE_KB) * 1024,
+            config.max_files.unwrap_or(DEFAULT_MAX_FILES),
+        )
+        .map(Some)
+    }
+
+    /// Default log directory: `<user data dir>/<identifier>/audit`
+    pub fn default_dir(identifier: &str) -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(identifier)
+            .join("audit")
+    }
+
+    /// Directory holding `audit.jsonl` and its rotations
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Learn which extension provides each op
+    pub fn register_extensions(&self, extensions: &[Extension]) {
+        let mut map = HashMap::new();
+        for extension in extensions {
+            let name = extension.name.trim_start_matches("runtime_");
+            for op in extension.ops.iter() {
+                map.insert(op.name, name.to_string());
+            }
+        }
+        let _ = self.op_extensions.set(map);
+    }
+
+    /// Record one decision
+    pub fn record(&self, capability: &str, resource: &str, allowed: bool, dev_mode: bool) {
+        let op = CURRENT_OP.with(|op| op.get());
+        let extension = op.and_then(|op| {
+            self.op_extensions
+                .get()
+                .and_then(|map| map.get(op))
+                .cloned()
+        });
+        self.write(AuditEvent {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            capability: capability.to_string(),
+            resource: resource.to_string(),
+            allowed,
+            extension,
+            op: op.map(str::to_string),
+            dev_mode,
+        });
+    }
+
+    /// Wait until every event recorded so far has been written to disk
+    pub fn flush(&self) {
+        let (ack, done) = mpsc::channel();
+        if self.writer.send(WriterCommand::Flush(ack)).is_ok() {
+            let _ = done.recv();
+        }
+    }
+
+    fn write(&self, event: AuditEvent) {
+        match serde_json::to_string(&event) {
+            Ok(line) => {
+                let _ = self.writer.send(WriterCommand::Line(line));
+            }
+            Err(e) => tracing::debug!("Failed to serialize audit event: {}", e),
+        }
+        // No subscribers is not an error
+        let _ = self.sender.send(event);
+    }
+}
+
+impl AuditEventSource for AuditLog {
+    fn subscribe(&self) -> broadcast::Receiver<AuditEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_events(path: &Path) -> Vec<AuditEvent> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_record_appends_jsonl() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::open(dir.path().to_path_buf(), 1024 * 1024, 2).unwrap();
+
+        log.record("fs.read", "/data/a.txt", true, false);
+        log.record("net", "evil.com", false, false);
+        log.flush();
+
+        let events = read_events(&dir.path().join(AUDIT_FILE));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].capability, "fs.read");
+        assert!(events[0].allowed);
+        assert_eq!(events[1].resource, "evil.com");
+        assert!(!events[1].allowed);
+        assert_eq!(events[1].op, None);
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::open(dir.path().to_path_buf(), 200, 2).unwrap();
+
+        for i in 0..20 {
+            log.record("fs.read", &format!("/data/{}.txt", i), true, true);
+        }
+        log.flush();
+
+        assert!(dir.path().join(AUDIT_FILE).exists());
+        assert!(dir.path().join("audit.1.jsonl").exists());
+        assert!(dir.path().join("audit.2.jsonl").exists());
+        assert!(!dir.path().join("audit.3.jsonl").exists());
+        for name in [AUDIT_FILE, "audit.1.jsonl", "audit.2.jsonl"] {
+            assert!(std::fs::metadata(dir.path().join(name)).unwrap().len() <= 200);
+        }
+
+        let latest = read_events(&dir.path().join(AUDIT_FILE));
+        assert_eq!(latest.last().unwrap().resource, "/data/19.txt");
+    }
+
+    #[test]
+    fn test_subscribers_receive_events_with_op() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::open(dir.path().to_path_buf(), 1024 * 1024, 1).unwrap();
+        let _ = log
+            .op_extensions
+            .set(HashMap::from([("op_fs_read_text", "fs".to_string())]));
+        let mut receiver = log.subscribe();
+
+        CURRENT_OP.with(|op| op.set(Some("op_fs_read_text")));
+        log.record("fs.read", "/data/a.txt", false, false);
+        CURRENT_OP.with(|op| op.set(None));
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.op.as_deref(), Some("op_fs_read_text"));
+        assert_eq!(event.extension.as_deref(), Some("fs"));
+        assert!(!event.allowed);
+    }
+
+    #[test]
+    fn test_disabled_config() {
+        let config = AuditConfig {
+            enabled: Some(false),
+            ..Default::default()
+        };
+        assert!(AuditLog::from_config("com.example.demo", Some(&config))
+            .unwrap()
+            .is_none());
+    }
+}
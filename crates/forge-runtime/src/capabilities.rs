@@ -18,6 +18,11 @@
 //! - **Process** (`permissions.process`): Spawn child processes
 //! - **WASM** (`permissions.wasm`): Load and execute WebAssembly
 //! - **Code Signing** (`permissions.codesign`): Sign and verify code
+//! - **Storage** (`permissions.storage`): Key-value storage
+//! - **Database** (`permissions.database`): SQLite databases by name
+//! - **Crypto** (`permissions.crypto`): Hashing, encryption, random generation
+//! - **Shell** (`permissions.shell`): The `runtime:shell` command interpreter
+//! - **Monitor** (`permissions.monitor`): Enumerating system processes
 //!
 //! # Glob Pattern Syntax
 //!
@@ -61,6 +66,10 @@
 //! # listen = [0]            # Port 0 means any port allowed
 //! ```
 //!
+//! Raw TCP and UDP sockets check the remote as `host:port` (e.g.
+//! `"db.internal:5432"` or `"localhost:*"`). Unix domain socket paths are
+//! checked against the `fs.write` globs.
+//!
 //! ## Deny List Precedence
 //!
 //! Denied domains are checked **before** allowed domains. This enables
@@ -90,6 +99,32 @@
 //!     .allowed_channels(vec!["secure:*".to_string()])
 //! ```
 //!
+//! # Shell, Database and Monitor Permissions
+//!
+//! ```toml
+//! [permissions.shell]
+//! execute = true                 # allow the shell interpreter (default: false)
+//! commands = ["git", "echo", "/usr/bin/*"]
+//!
+//! [permissions.database]
+//! names = ["cache", "user-*"]    # database names the app may open
+//!
+//! [permissions.monitor]
+//! processes = true               # list other processes (default: false)
+//!
+//! [permissions.storage]
+//! enabled = true                 # default
+//!
+//! [permissions.crypto]
+//! enabled = true                 # default
+//! ```
+//!
+//! Every command a shell script runs is checked, builtins included. Patterns
+//! match the command name as written or the resolved executable path. When
+//! `commands` is omitted, builtins are allowed and external commands must
+//! match `permissions.process.allow`, so the interpreter never bypasses the
+//! process allowlist. Without `names`, any database may be opened.
+//!
 //! # Common Permission Patterns
 //!
 //! ## Data Directory Access
@@ -146,6 +181,19 @@
 //! }
 //! ```
 //!
+//! # Permission Prompts
+//!
+//! The `fs`, `net` and `process` sections accept `prompt = true`. A check that
+//! would be denied then asks the user through a native dialog, and decisions
+//! remembered in the attached [`GrantStore`] are consulted before the globs.
+//! See [`crate::permission_prompt`].
+//!
+//! # Audit Log
+//!
+//! With an [`AuditLog`] attached, every fs, net, process, env, wasm, IPC
+//! channel, codesign, storage, database, crypto, shell and monitor decision
+//! is recorded, in dev mode too. See [`crate::audit`].
+//!
 //! # Dev Mode vs Production
 //!
 //! **Dev Mode** (`forge dev`):
@@ -158,9 +206,12 @@
 //! - Missing permissions cause runtime errors
 //! - Apps must declare all required capabilities
 
+use crate::audit::AuditLog;
+use crate::permission_prompt::GrantStore;
 use globset::{GlobSet, GlobSetBuilder};
 use serde::Deserialize;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Permissions section from manifest.app.toml
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -172,12 +223,19 @@ pub struct Permissions {
     pub process: Option<ProcessPermissions>,
     pub wasm: Option<WasmPermissions>,
     pub codesign: Option<CodesignPermissions>,
+    pub storage: Option<StoragePermissions>,
+    pub database: Option<DatabasePermissions>,
+    pub crypto: Option<CryptoPermissions>,
+    pub shell: Option<ShellPermissions>,
+    pub monitor: Option<MonitorPermissions>,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct FsPermissions {
     pub read: Option<Vec<String>>,
     pub write: Option<Vec<String>>,
+    /// Ask the user instead of denying paths outside the globs
+    pub prompt: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -186,6 +244,8 @@ pub struct NetPermissions {
     pub deny: Option<Vec<String>>,
     /// Allowed ports for listening (binds to 0.0.0.0)
     pub listen: Option<Vec<u16>>,
+    /// Ask the user instead of denying hosts outside the allowlist
+    pub prompt: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -224,6 +284,8 @@ pub struct ProcessPermissions {
     pub env: Option<Vec<String>>,
     /// Maximum concurrent child processes (default: 10)
     pub max_processes: Option<usize>,
+    /// Ask the user instead of denying binaries outside the allowlist
+    pub prompt: Option<bool>,
 }
 
 /// WebAssembly permissions
@@ -246,6 +308,44 @@ pub struct CodesignPermissions {
     pub list_identities: Option<bool>,
 }
 
+/// Key-value storage permissions
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StoragePermissions {
+    /// Allow `runtime:storage` (default: true)
+    pub enabled: Option<bool>,
+}
+
+/// SQLite database permissions
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DatabasePermissions {
+    /// Glob patterns for database names that may be opened (default: any)
+    pub names: Option<Vec<String>>,
+}
+
+/// Cryptography permissions
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CryptoPermissions {
+    /// Allow `runtime:crypto` (default: true)
+    pub enabled: Option<bool>,
+}
+
+/// Shell interpreter permissions
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ShellPermissions {
+    /// Allow running scripts through the shell interpreter (default: false)
+    pub execute: Option<bool>,
+    /// Glob patterns for command names or executable paths scripts may run
+    /// (default: builtins plus `permissions.process.allow`)
+    pub commands: Option<Vec<String>>,
+}
+
+/// System monitor permissions
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MonitorPermissions {
+    /// Allow listing other processes on the system (default: false)
+    pub processes: Option<bool>,
+}
+
 /// Runtime capabilities checker
 #[derive(Debug, Clone)]
 pub struct Capabilities {
@@ -274,6 +374,19 @@ pub struct Capabilities {
     pub wasm_max_instances: usize,
     codesign_sign: bool,
     codesign_list_identities: bool,
+    storage_enabled: bool,
+    database_names: Option<GlobSet>,
+    crypto_enabled: bool,
+    shell_execute: bool,
+    shell_commands: Option<GlobSet>,
+    monitor_processes: bool,
+    fs_prompt: bool,
+    net_prompt: bool,
+    process_prompt: bool,
+    /// Remembered prompt decisions, consulted before the manifest globs
+    grants: Option<Arc<GrantStore>>,
+    /// Log of every allow/deny decision
+    audit: Option<Arc<AuditLog>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -286,6 +399,9 @@ pub enum CapabilityError {
 
     #[error("Invalid glob pattern: {0}")]
     InvalidPattern(String),
+
+    #[error("Invalid permission grants: {0}")]
+    Grants(String),
 }
 
 impl Capabilities {
@@ -356,6 +472,7 @@ impl Capabilities {
         let process = permissions.process.unwrap_or_default();
         let wasm = permissions.wasm.unwrap_or_default();
         let codesign = permissions.codesign.unwrap_or_default();
+        let shell = permissions.shell.unwrap_or_default();
 
         // Compile network host patterns (for wildcard matching like *.example.com)
         let net_allow_patterns = Self::compile_host_patterns(net.allow.as_ref())?;
@@ -375,6 +492,11 @@ impl Capabilities {
         let wasm_load_patterns = Self::compile_patterns(wasm.load.as_ref())?;
         let wasm_preopen_patterns = Self::compile_patterns(wasm.preopens.as_ref())?;
 
+        // Database names never contain separators, so * stays within a name
+        let database_names =
+            Self::compile_patterns(permissions.database.as_ref().and_then(|d| d.names.as_ref()))?;
+        let shell_commands = Self::compile_simple_patterns(shell.commands.as_ref())?;
+
         Ok(Self {
             dev_mode,
             fs_read,
@@ -400,9 +522,112 @@ impl Capabilities {
             wasm_max_instances: wasm.max_instances.unwrap_or(10),
             codesign_sign: codesign.sign.unwrap_or(false),
             codesign_list_identities: codesign.list_identities.unwrap_or(false),
+            storage_enabled: permissions
+                .storage
+                .as_ref()
+                .and_then(|s| s.enabled)
+                .unwrap_or(true),
+            database_names,
+            crypto_enabled: permissions
+                .crypto
+                .as_ref()
+                .and_then(|c| c.enabled)
+                .unwrap_or(true),
+            shell_execute: shell.execute.unwrap_or(false),
+            shell_commands,
+            monitor_processes: permissions
+                .monitor
+                .as_ref()
+                .and_then(|m| m.processes)
+                .unwrap_or(false),
+            fs_prompt: permissions
+                .fs
+                .as_ref()
+                .and_then(|f| f.prompt)
+                .unwrap_or(false),
+            net_prompt: net.prompt.unwrap_or(false),
+            process_prompt: process.prompt.unwrap_or(false),
+            grants: None,
+            audit: None,
         })
     }
 
+    /// Attach the store of remembered prompt decisions
+    ///
+    /// Grants are looked up before the manifest globs; sections declared
+    /// with `prompt = true` ask through the store's prompter instead of
+    /// denying.
+    pub fn with_grants(mut self, grants: Arc<GrantStore>) -> Self {
+        self.grants = Some(grants);
+        self
+    }
+
+    /// The attached grant store, if any
+    pub fn grants(&self) -> Option<Arc<GrantStore>> {
+        self.grants.clone()
+    }
+
+    /// Record every capability decision to `audit`, including the ones dev
+    /// mode allows
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// The attached audit log, if any
+    pub fn audit(&self) -> Option<Arc<AuditLog>> {
+        self.audit.clone()
+    }
+
+    /// Whether any permission section asks the user instead of denying
+    pub fn prompts_enabled(&self) -> bool {
+        self.fs_prompt || self.net_prompt || self.process_prompt
+    }
+
+    /// Apply remembered grants, then `check`, then prompt if enabled
+    fn check_with_grants(
+        &self,
+        capability: &str,
+        resource: &str,
+        prompt: bool,
+        check: impl FnOnce() -> Result<(), CapabilityError>,
+    ) -> Result<(), CapabilityError> {
+        let denied = || CapabilityError::Denied {
+            capability: capability.to_string(),
+            resource: resource.to_string(),
+        };
+
+        if let Some(grants) = &self.grants {
+            match grants.lookup(capability, resource) {
+                Some(true) => return Ok(()),
+                Some(false) => return Err(denied()),
+                None => {}
+            }
+        }
+
+        match check() {
+            Err(CapabilityError::Denied { .. }) if prompt => match &self.grants {
+                Some(grants) if grants.prompt(capability, resource) => Ok(()),
+                _ => Err(denied()),
+            },
+            result => result,
+        }
+    }
+
+    /// Run `check` and record its outcome in the audit log
+    fn audited(
+        &self,
+        capability: &str,
+        resource: &str,
+        check: impl FnOnce() -> Result<(), CapabilityError>,
+    ) -> Result<(), CapabilityError> {
+        let result = check();
+        if let Some(audit) = &self.audit {
+            audit.record(capability, resource, result.is_ok(), self.dev_mode);
+        }
+        result
+    }
+
     /// Compile glob patterns for filesystem paths (uses literal_separator)
     fn compile_patterns(
         patterns: Option<&Vec<String>>,
@@ -478,60 +703,73 @@ impl Capabilities {
 
     /// Check if filesystem read is allowed for the given path
     pub fn check_fs_read(&self, path: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("fs.read", path, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.fs_read {
-            None => Err(CapabilityError::Denied {
-                capability: "fs.read".to_string(),
-                resource: path.to_string(),
-            }),
-            Some(patterns) => {
-                let p = Path::new(path);
-                if patterns.is_match(p) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "fs.read".to_string(),
-                        resource: path.to_string(),
-                    })
+            self.check_with_grants("fs.read", path, self.fs_prompt, || match &self.fs_read {
+                None => Err(CapabilityError::Denied {
+                    capability: "fs.read".to_string(),
+                    resource: path.to_string(),
+                }),
+                Some(patterns) => {
+                    let p = Path::new(path);
+                    if patterns.is_match(p) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "fs.read".to_string(),
+                            resource: path.to_string(),
+                        })
+                    }
                 }
-            }
-        }
+            })
+        })
     }
 
     /// Check if filesystem write is allowed for the given path
     pub fn check_fs_write(&self, path: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("fs.write", path, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.fs_write {
-            None => Err(CapabilityError::Denied {
-                capability: "fs.write".to_string(),
-                resource: path.to_string(),
-            }),
-            Some(patterns) => {
-                let p = Path::new(path);
-                if patterns.is_match(p) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "fs.write".to_string(),
-                        resource: path.to_string(),
-                    })
+            self.check_with_grants("fs.write", path, self.fs_prompt, || match &self.fs_write {
+                None => Err(CapabilityError::Denied {
+                    capability: "fs.write".to_string(),
+                    resource: path.to_string(),
+                }),
+                Some(patterns) => {
+                    let p = Path::new(path);
+                    if patterns.is_match(p) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "fs.write".to_string(),
+                            resource: path.to_string(),
+                        })
+                    }
                 }
-            }
-        }
+            })
+        })
     }
 
     /// Check if network access is allowed for the given host
     pub fn check_net(&self, host: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("net", host, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
+            self.check_with_grants("net", host, self.net_prompt, || {
+                self.check_net_patterns(host)
+            })
+        })
+    }
+
+    /// Match a host against the manifest's deny and allow lists
+    fn check_net_patterns(&self, host: &str) -> Result<(), CapabilityError> {
         // Check deny patterns first (takes precedence)
         if let Some(deny_patterns) = &self.net_deny_patterns {
             if deny_patterns.is_match(host) {
@@ -563,27 +801,29 @@ impl Capabilities {
 
     /// Check if network listen is allowed for the given port
     pub fn check_net_listen(&self, port: u16) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("net.listen", &port.to_string(), || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.net_listen_ports {
-            None => Err(CapabilityError::Denied {
-                capability: "net.listen".to_string(),
-                resource: port.to_string(),
-            }),
-            Some(ports) => {
-                // Port 0 in the list means any port is allowed
-                if ports.contains(&0) || ports.contains(&port) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "net.listen".to_string(),
-                        resource: port.to_string(),
-                    })
+            match &self.net_listen_ports {
+                None => Err(CapabilityError::Denied {
+                    capability: "net.listen".to_string(),
+                    resource: port.to_string(),
+                }),
+                Some(ports) => {
+                    // Port 0 in the list means any port is allowed
+                    if ports.contains(&0) || ports.contains(&port) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "net.listen".to_string(),
+                            resource: port.to_string(),
+                        })
+                    }
                 }
             }
-        }
+        })
     }
 
     /// Check if window creation is allowed
@@ -672,57 +912,70 @@ impl Capabilities {
 
     /// Check if reading an environment variable is allowed
     pub fn check_env_read(&self, key: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("sys.env.read", key, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.env_read_patterns {
-            // If no env permissions specified, allow all reads by default (backwards compatible)
-            None => Ok(()),
-            Some(patterns) => {
-                if patterns.is_match(key) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "sys.env.read".to_string(),
-                        resource: key.to_string(),
-                    })
+            match &self.env_read_patterns {
+                // If no env permissions specified, allow all reads by default (backwards compatible)
+                None => Ok(()),
+                Some(patterns) => {
+                    if patterns.is_match(key) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "sys.env.read".to_string(),
+                            resource: key.to_string(),
+                        })
+                    }
                 }
             }
-        }
+        })
     }
 
     /// Check if writing an environment variable is allowed
     pub fn check_env_write(&self, key: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("sys.env.write", key, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.env_write_patterns {
-            // If no env permissions specified, deny all writes by default
-            None => Err(CapabilityError::Denied {
-                capability: "sys.env.write".to_string(),
-                resource: key.to_string(),
-            }),
-            Some(patterns) => {
-                if patterns.is_match(key) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "sys.env.write".to_string(),
-                        resource: key.to_string(),
-                    })
+            match &self.env_write_patterns {
+                // If no env permissions specified, deny all writes by default
+                None => Err(CapabilityError::Denied {
+                    capability: "sys.env.write".to_string(),
+                    resource: key.to_string(),
+                }),
+                Some(patterns) => {
+                    if patterns.is_match(key) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "sys.env.write".to_string(),
+                            resource: key.to_string(),
+                        })
+                    }
                 }
             }
-        }
+        })
     }
 
     /// Check if spawning a process is allowed
     pub fn check_process_spawn(&self, binary: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("process.spawn", binary, || {
+            if self.dev_mode {
+                return Ok(());
+            }
+
+            self.check_with_grants("process.spawn", binary, self.process_prompt, || {
+                self.check_process_patterns(binary)
+            })
+        })
+    }
 
+    /// Match a binary against the manifest's process allowlist
+    fn check_process_patterns(&self, binary: &str) -> Result<(), CapabilityError> {
         match &self.process_allow_patterns {
             None => Err(CapabilityError::Denied {
                 capability: "process.spawn".to_string(),
@@ -743,27 +996,29 @@ impl Capabilities {
 
     /// Check if passing an env var to a spawned process is allowed
     pub fn check_process_env(&self, key: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("process.env", key, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.process_env_patterns {
-            // If no process env permissions specified, allow inherited vars only
-            None => Err(CapabilityError::Denied {
-                capability: "process.env".to_string(),
-                resource: key.to_string(),
-            }),
-            Some(patterns) => {
-                if patterns.is_match(key) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "process.env".to_string(),
-                        resource: key.to_string(),
-                    })
+            match &self.process_env_patterns {
+                // If no process env permissions specified, allow inherited vars only
+                None => Err(CapabilityError::Denied {
+                    capability: "process.env".to_string(),
+                    resource: key.to_string(),
+                }),
+                Some(patterns) => {
+                    if patterns.is_match(key) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "process.env".to_string(),
+                            resource: key.to_string(),
+                        })
+                    }
                 }
             }
-        }
+        })
     }
 
     /// Check if a channel is allowed for IPC communication
@@ -773,38 +1028,40 @@ impl Capabilities {
         channel: &str,
         window_channels: Option<&[String]>,
     ) -> Result<(), CapabilityError> {
-        // In dev mode, all channels are allowed
-        if self.dev_mode {
-            return Ok(());
-        }
-
-        // If window has specific channel allowlist, use that
-        if let Some(window_channels) = window_channels {
-            if window_channels.iter().any(|c| c == "*" || c == channel) {
+        self.audited("ui.channel", channel, || {
+            // In dev mode, all channels are allowed
+            if self.dev_mode {
                 return Ok(());
             }
-            return Err(CapabilityError::Denied {
-                capability: "ui.channel".to_string(),
-                resource: channel.to_string(),
-            });
-        }
 
-        // Otherwise, use the default channel allowlist from manifest
-        if let Some(default_channels) = &self.ui_channels {
-            if default_channels.iter().any(|c| c == "*" || c == channel) {
-                return Ok(());
+            // If window has specific channel allowlist, use that
+            if let Some(window_channels) = window_channels {
+                if window_channels.iter().any(|c| c == "*" || c == channel) {
+                    return Ok(());
+                }
+                return Err(CapabilityError::Denied {
+                    capability: "ui.channel".to_string(),
+                    resource: channel.to_string(),
+                });
+            }
+
+            // Otherwise, use the default channel allowlist from manifest
+            if let Some(default_channels) = &self.ui_channels {
+                if default_channels.iter().any(|c| c == "*" || c == channel) {
+                    return Ok(());
+                }
+                return Err(CapabilityError::Denied {
+                    capability: "ui.channel".to_string(),
+                    resource: channel.to_string(),
+                });
             }
-            return Err(CapabilityError::Denied {
+
+            // Default-deny: If no channel list specified in manifest, deny all channels
+            // Apps must explicitly opt-in with ["*"] for all channels or specific channel names
+            Err(CapabilityError::Denied {
                 capability: "ui.channel".to_string(),
                 resource: channel.to_string(),
-            });
-        }
-
-        // Default-deny: If no channel list specified in manifest, deny all channels
-        // Apps must explicitly opt-in with ["*"] for all channels or specific channel names
-        Err(CapabilityError::Denied {
-            capability: "ui.channel".to_string(),
-            resource: channel.to_string(),
+            })
         })
     }
 
@@ -820,52 +1077,56 @@ impl Capabilities {
 
     /// Check if loading WASM from a path is allowed
     pub fn check_wasm_load(&self, path: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("wasm.load", path, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.wasm_load_patterns {
-            None => Err(CapabilityError::Denied {
-                capability: "wasm.load".to_string(),
-                resource: path.to_string(),
-            }),
-            Some(patterns) => {
-                let p = Path::new(path);
-                if patterns.is_match(p) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "wasm.load".to_string(),
-                        resource: path.to_string(),
-                    })
+            match &self.wasm_load_patterns {
+                None => Err(CapabilityError::Denied {
+                    capability: "wasm.load".to_string(),
+                    resource: path.to_string(),
+                }),
+                Some(patterns) => {
+                    let p = Path::new(path);
+                    if patterns.is_match(p) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "wasm.load".to_string(),
+                            resource: path.to_string(),
+                        })
+                    }
                 }
             }
-        }
+        })
     }
 
     /// Check if preopening a directory for WASI is allowed
     pub fn check_wasm_preopen(&self, host_path: &str) -> Result<(), CapabilityError> {
-        if self.dev_mode {
-            return Ok(());
-        }
+        self.audited("wasm.preopen", host_path, || {
+            if self.dev_mode {
+                return Ok(());
+            }
 
-        match &self.wasm_preopen_patterns {
-            None => Err(CapabilityError::Denied {
-                capability: "wasm.preopen".to_string(),
-                resource: host_path.to_string(),
-            }),
-            Some(patterns) => {
-                let p = Path::new(host_path);
-                if patterns.is_match(p) {
-                    Ok(())
-                } else {
-                    Err(CapabilityError::Denied {
-                        capability: "wasm.preopen".to_string(),
-                        resource: host_path.to_string(),
-                    })
+            match &self.wasm_preopen_patterns {
+                None => Err(CapabilityError::Denied {
+                    capability: "wasm.preopen".to_string(),
+                    resource: host_path.to_string(),
+                }),
+                Some(patterns) => {
+                    let p = Path::new(host_path);
+                    if patterns.is_match(p) {
+                        Ok(())
+                    } else {
+                        Err(CapabilityError::Denied {
+                            capability: "wasm.preopen".to_string(),
+                            resource: host_path.to_string(),
+                        })
+                    }
                 }
             }
-        }
+        })
     }
 
     /// Get the maximum number of concurrent WASM instances
@@ -875,26 +1136,147 @@ impl Capabilities {
 
     /// Check if code signing operations are allowed
     pub fn check_codesign_sign(&self) -> Result<(), CapabilityError> {
-        if self.dev_mode || self.codesign_sign {
-            Ok(())
-        } else {
-            Err(CapabilityError::Denied {
-                capability: "codesign.sign".to_string(),
-                resource: "signing operation".to_string(),
-            })
-        }
+        self.audited("codesign.sign", "signing operation", || {
+            if self.dev_mode || self.codesign_sign {
+                Ok(())
+            } else {
+                Err(CapabilityError::Denied {
+                    capability: "codesign.sign".to_string(),
+                    resource: "signing operation".to_string(),
+                })
+            }
+        })
     }
 
     /// Check if listing signing identities is allowed
     pub fn check_codesign_list_identities(&self) -> Result<(), CapabilityError> {
-        if self.dev_mode || self.codesign_list_identities {
-            Ok(())
-        } else {
-            Err(CapabilityError::Denied {
-                capability: "codesign.list_identities".to_string(),
-                resource: "identity listing".to_string(),
-            })
-        }
+        self.audited("codesign.list_identities", "identity listing", || {
+            if self.dev_mode || self.codesign_list_identities {
+                Ok(())
+            } else {
+                Err(CapabilityError::Denied {
+                    capability: "codesign.list_identities".to_string(),
+                    resource: "identity listing".to_string(),
+                })
+            }
+        })
+    }
+
+    /// Check if key-value storage is allowed
+    pub fn check_storage(&self) -> Result<(), CapabilityError> {
+        self.audited("storage", "key-value store", || {
+            if self.dev_mode || self.storage_enabled {
+                Ok(())
+            } else {
+                Err(CapabilityError::Denied {
+                    capability: "storage".to_string(),
+                    resource: "key-value store".to_string(),
+                })
+            }
+        })
+    }
+
+    /// Check if opening the named database is allowed
+    pub fn check_database(&self, name: &str) -> Result<(), CapabilityError> {
+        self.audited("database", name, || {
+            if self.dev_mode {
+                return Ok(());
+            }
+
+            match &self.database_names {
+                None => Ok(()),
+                Some(patterns) if patterns.is_match(name) => Ok(()),
+                Some(_) => Err(CapabilityError::Denied {
+                    capability: "database".to_string(),
+                    resource: name.to_string(),
+                }),
+            }
+        })
+    }
+
+    /// Check if cryptographic operations are allowed
+    pub fn check_crypto(&self) -> Result<(), CapabilityError> {
+        self.audited("crypto", "crypto operation", || {
+            if self.dev_mode || self.crypto_enabled {
+                Ok(())
+            } else {
+                Err(CapabilityError::Denied {
+                    capability: "crypto".to_string(),
+                    resource: "crypto operation".to_string(),
+                })
+            }
+        })
+    }
+
+    /// Check if running scripts through the shell interpreter is allowed
+    pub fn check_shell_execute(&self) -> Result<(), CapabilityError> {
+        self.audited("shell.execute", "shell interpreter", || {
+            if self.dev_mode || self.shell_execute {
+                Ok(())
+            } else {
+                Err(CapabilityError::Denied {
+                    capability: "shell.execute".to_string(),
+                    resource: "shell interpreter".to_string(),
+                })
+            }
+        })
+    }
+
+    /// Check if a shell script may run a command
+    ///
+    /// `path` is the resolved executable for external commands and `None`
+    /// for builtins. Without `permissions.shell.commands`, builtins are
+    /// allowed and external commands fall back to the process allowlist.
+    /// A name that is neither (`eval`, `source`) is always denied.
+    pub fn check_shell_command(
+        &self,
+        name: &str,
+        path: Option<&Path>,
+    ) -> Result<(), CapabilityError> {
+        let path = path.map(|p| p.to_string_lossy());
+        let resource = path.as_deref().unwrap_or(name);
+        self.audited("shell.command", resource, || {
+            if self.dev_mode {
+                return Ok(());
+            }
+
+            let denied = || CapabilityError::Denied {
+                capability: "shell.command".to_string(),
+                resource: resource.to_string(),
+            };
+            // Only builtins come without an executable; anything else can't be vetted
+            if path.is_none() && !ext_shell::builtin_commands().contains_key(name) {
+                return Err(denied());
+            }
+            match (&self.shell_commands, path.as_deref()) {
+                (Some(patterns), path) => {
+                    if patterns.is_match(name) || path.is_some_and(|p| patterns.is_match(p)) {
+                        Ok(())
+                    } else {
+                        Err(denied())
+                    }
+                }
+                (None, None) => Ok(()),
+                (None, Some(path)) => self
+                    .check_process_patterns(name)
+                    .or_else(|_| self.check_process_patterns(path))
+                    .map_err(|_| denied()),
+            }
+        })
+    }
+
+    /// Check if enumerating other processes is allowed
+    pub fn check_monitor_processes(&self) -> Result<(), CapabilityError> {
+        self.audited("monitor.processes", "process list", || {
+            if self.dev_mode || self.monitor_processes {
+                Ok(())
+            } else {
+                Err(CapabilityError::Denied {
+                    capability: "monitor.processes".to_string(),
+                    resource: "process list".to_string(),
+                })
+            }
+        })
     }
 }
 
@@ -902,8 +1284,6 @@ impl Capabilities {
 // Adapters implementing extension capability checker traits
 // ============================================================================
 
-use std::sync::Arc;
-
 /// Struct containing all capability adapter Arc pointers
 pub struct CapabilityAdapters {
     pub fs: Arc<dyn ext_fs::FsCapabilityChecker>,
@@ -914,6 +1294,11 @@ pub struct CapabilityAdapters {
     pub process: Arc<dyn ext_process::ProcessCapabilityChecker>,
     pub wasm: Arc<dyn ext_wasm::WasmCapabilityChecker>,
     pub codesign: Arc<dyn ext_codesign::CodesignCapabilityChecker>,
+    pub storage: Arc<dyn ext_storage::StorageCapabilityChecker>,
+    pub database: Arc<dyn ext_database::DatabaseCapabilityChecker>,
+    pub crypto: Arc<dyn ext_crypto::CryptoCapabilityChecker>,
+    pub shell: Arc<dyn ext_shell::ShellCapabilityChecker>,
+    pub monitor: Arc<dyn ext_monitor::MonitorCapabilityChecker>,
 }
 
 /// Adapter that implements ext_fs::FsCapabilityChecker using Capabilities
@@ -962,6 +1347,27 @@ impl ext_net::NetCapabilityChecker for NetCapabilityAdapter {
             .check_net_listen(port)
             .map_err(|e| e.to_string())
     }
+
+    /// Unix socket paths are files, so they follow the fs write globs
+    fn check_socket_path(&self, path: &str) -> Result<(), String> {
+        self.capabilities
+            .check_fs_write(path)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Multipart uploads read files under the fs read globs
+    fn check_read_path(&self, path: &str) -> Result<(), String> {
+        self.capabilities
+            .check_fs_read(path)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Downloads write files under the fs write globs
+    fn check_write_path(&self, path: &str) -> Result<(), String> {
+        self.capabilities
+            .check_fs_write(path)
+            .map_err(|e| e.to_string())
+    }
 }
 
 /// Adapter that implements ext_sys::SysCapabilityChecker using Capabilities
@@ -1158,6 +1564,99 @@ impl ext_codesign::CodesignCapabilityChecker for CodesignCapabilityAdapter {
     }
 }
 
+/// Adapter that implements ext_storage::StorageCapabilityChecker using Capabilities
+pub struct StorageCapabilityAdapter {
+    capabilities: Arc<Capabilities>,
+}
+
+impl StorageCapabilityAdapter {
+    pub fn new(capabilities: Arc<Capabilities>) -> Self {
+        Self { capabilities }
+    }
+}
+
+impl ext_storage::StorageCapabilityChecker for StorageCapabilityAdapter {
+    fn check_storage(&self) -> Result<(), String> {
+        self.capabilities.check_storage().map_err(|e| e.to_string())
+    }
+}
+
+/// Adapter that implements ext_database::DatabaseCapabilityChecker using Capabilities
+pub struct DatabaseCapabilityAdapter {
+    capabilities: Arc<Capabilities>,
+}
+
+impl DatabaseCapabilityAdapter {
+    pub fn new(capabilities: Arc<Capabilities>) -> Self {
+        Self { capabilities }
+    }
+}
+
+impl ext_database::DatabaseCapabilityChecker for DatabaseCapabilityAdapter {
+    fn check_database(&self, name: &str) -> Result<(), String> {
+        self.capabilities
+            .check_database(name)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Adapter that implements ext_crypto::CryptoCapabilityChecker using Capabilities
+pub struct CryptoCapabilityAdapter {
+    capabilities: Arc<Capabilities>,
+}
+
+impl CryptoCapabilityAdapter {
+    pub fn new(capabilities: Arc<Capabilities>) -> Self {
+        Self { capabilities }
+    }
+}
+
+impl ext_crypto::CryptoCapabilityChecker for CryptoCapabilityAdapter {
+    fn check_crypto(&self) -> Result<(), String> {
+        self.capabilities.check_crypto().map_err(|e| e.to_string())
+    }
+}
+
+/// Adapter that implements ext_shell::ShellCapabilityChecker using Capabilities
+pub struct ShellCapabilityAdapter {
+    capabilities: Arc<Capabilities>,
+}
+
+impl ShellCapabilityAdapter {
+    pub fn new(capabilities: Arc<Capabilities>) -> Self {
+        Self { capabilities }
+    }
+}
+
+impl ext_shell::ShellCapabilityChecker for ShellCapabilityAdapter {
+    fn can_execute(&self) -> bool {
+        self.capabilities.check_shell_execute().is_ok()
+    }
+
+    fn can_run_command(&self, name: &str, path: Option<&Path>) -> bool {
+        self.capabilities.check_shell_command(name, path).is_ok()
+    }
+}
+
+/// Adapter that implements ext_monitor::MonitorCapabilityChecker using Capabilities
+pub struct MonitorCapabilityAdapter {
+    capabilities: Arc<Capabilities>,
+}
+
+impl MonitorCapabilityAdapter {
+    pub fn new(capabilities: Arc<Capabilities>) -> Self {
+        Self { capabilities }
+    }
+}
+
+impl ext_monitor::MonitorCapabilityChecker for MonitorCapabilityAdapter {
+    fn check_processes(&self) -> Result<(), String> {
+        self.capabilities
+            .check_monitor_processes()
+            .map_err(|e| e.to_string())
+    }
+}
+
 /// Create all capability adapters from Capabilities
 pub fn create_capability_adapters(capabilities: Capabilities) -> CapabilityAdapters {
     let caps = Arc::new(capabilities);
@@ -1169,7 +1668,12 @@ pub fn create_capability_adapters(capabilities: Capabilities) -> CapabilityAdapt
         window: Arc::new(WindowCapabilityAdapter::new(caps.clone())),
         process: Arc::new(ProcessCapabilityAdapter::new(caps.clone())),
         wasm: Arc::new(WasmCapabilityAdapter::new(caps.clone())),
-        codesign: Arc::new(CodesignCapabilityAdapter::new(caps)),
+        codesign: Arc::new(CodesignCapabilityAdapter::new(caps.clone())),
+        storage: Arc::new(StorageCapabilityAdapter::new(caps.clone())),
+        database: Arc::new(DatabaseCapabilityAdapter::new(caps.clone())),
+        crypto: Arc::new(CryptoCapabilityAdapter::new(caps.clone())),
+        shell: Arc::new(ShellCapabilityAdapter::new(caps.clone())),
+        monitor: Arc::new(MonitorCapabilityAdapter::new(caps)),
     }
 }
 
@@ -1196,6 +1700,12 @@ mod tests {
         assert!(caps.check_process_spawn("ls").is_ok());
         assert!(caps.check_process_env("HOME").is_ok());
         assert!(caps.check_channel("any-channel", None).is_ok());
+        assert!(caps.check_database("any-db").is_ok());
+        assert!(caps.check_shell_execute().is_ok());
+        assert!(caps
+            .check_shell_command("rm", Some(Path::new("/bin/rm")))
+            .is_ok());
+        assert!(caps.check_monitor_processes().is_ok());
     }
 
     #[test]
@@ -1220,6 +1730,12 @@ mod tests {
         // process spawn denied without permissions
         assert!(caps.check_process_spawn("ls").is_err());
         assert!(caps.check_process_env("HOME").is_err());
+        // storage, crypto and databases allowed; shell and process list denied
+        assert!(caps.check_storage().is_ok());
+        assert!(caps.check_crypto().is_ok());
+        assert!(caps.check_database("any-db").is_ok());
+        assert!(caps.check_shell_execute().is_err());
+        assert!(caps.check_monitor_processes().is_err());
     }
 
     #[test]
@@ -1228,6 +1744,7 @@ mod tests {
             fs: Some(FsPermissions {
                 read: Some(vec!["./data/**".to_string()]),
                 write: Some(vec!["./data/*.txt".to_string()]),
+                prompt: None,
             }),
             ..Default::default()
         };
@@ -1251,6 +1768,7 @@ mod tests {
                 ]),
                 deny: Some(vec!["evil.com".to_string()]),
                 listen: None,
+                prompt: None,
             }),
             ..Default::default()
         };
@@ -1270,6 +1788,7 @@ mod tests {
                 allow: None,
                 deny: None,
                 listen: Some(vec![8080, 3000]),
+                prompt: None,
             }),
             ..Default::default()
         };
@@ -1287,6 +1806,7 @@ mod tests {
                 allow: None,
                 deny: None,
                 listen: Some(vec![0]), // 0 means any port
+                prompt: None,
             }),
             ..Default::default()
         };
@@ -1304,6 +1824,7 @@ mod tests {
                 allow: Some(vec!["*".to_string()]),       // allow all
                 deny: Some(vec!["evil.com".to_string()]), // but deny evil.com
                 listen: None,
+                prompt: None,
             }),
             ..Default::default()
         };
@@ -1373,6 +1894,7 @@ mod tests {
                 ]),
                 env: Some(vec!["PATH".to_string(), "HOME".to_string()]),
                 max_processes: Some(5),
+                prompt: None,
             }),
             ..Default::default()
         };
@@ -1393,6 +1915,76 @@ mod tests {
         assert_eq!(caps.process_max_processes, 5);
     }
 
+    #[test]
+    fn test_shell_permissions() {
+        let git = Path::new("/usr/bin/git");
+        let rm = Path::new("/bin/rm");
+
+        // Without a command list, builtins run and externals need process.allow
+        let perms = Permissions {
+            shell: Some(ShellPermissions {
+                execute: Some(true),
+                commands: None,
+            }),
+            process: Some(ProcessPermissions {
+                allow: Some(vec!["git".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let caps = Capabilities::from_permissions(Some(&perms), false).unwrap();
+        assert!(caps.check_shell_execute().is_ok());
+        assert!(caps.check_shell_command("echo", None).is_ok());
+        assert!(caps.check_shell_command("git", Some(git)).is_ok());
+        assert!(caps.check_shell_command("rm", Some(rm)).is_err());
+        // Names that are neither builtins nor executables are never allowed
+        for name in ["eval", "exec", "command", ".", "source"] {
+            assert!(caps.check_shell_command(name, None).is_err());
+        }
+
+        // An explicit command list covers builtins too
+        let perms = Permissions {
+            shell: Some(ShellPermissions {
+                execute: Some(true),
+                commands: Some(vec!["echo".to_string(), "/usr/bin/*".to_string()]),
+            }),
+            ..Default::default()
+        };
+        let caps = Capabilities::from_permissions(Some(&perms), false).unwrap();
+        assert!(caps.check_shell_command("echo", None).is_ok());
+        assert!(caps.check_shell_command("cd", None).is_err());
+        assert!(caps.check_shell_command("git", Some(git)).is_ok());
+        assert!(caps.check_shell_command("rm", Some(rm)).is_err());
+    }
+
+    #[test]
+    fn test_storage_database_crypto_monitor_permissions() {
+        let perms = Permissions {
+            storage: Some(StoragePermissions {
+                enabled: Some(false),
+            }),
+            database: Some(DatabasePermissions {
+                names: Some(vec!["cache".to_string(), "user-*".to_string()]),
+            }),
+            crypto: Some(CryptoPermissions {
+                enabled: Some(false),
+            }),
+            monitor: Some(MonitorPermissions {
+                processes: Some(true),
+            }),
+            ..Default::default()
+        };
+        let caps = Capabilities::from_permissions(Some(&perms), false).unwrap();
+
+        assert!(caps.check_storage().is_err());
+        assert!(caps.check_crypto().is_err());
+        assert!(caps.check_monitor_processes().is_ok());
+        assert!(caps.check_database("cache").is_ok());
+        assert!(caps.check_database("user-42").is_ok());
+        assert!(caps.check_database("secrets").is_err());
+        assert!(caps.check_database("user-42/../secrets").is_err());
+    }
+
     #[test]
     fn test_ui_permissions() {
         let perms = Permissions {
@@ -1467,6 +2059,90 @@ mod tests {
         assert!(err.to_string().contains("blocked.com"));
     }
 
+    #[test]
+    fn test_prompt_mode_and_grants() {
+        use crate::permission_prompt::{PermissionPrompter, PromptResponse};
+
+        struct AlwaysAllow;
+        impl PermissionPrompter for AlwaysAllow {
+            fn prompt(&self, _capability: &str, _resource: &str) -> PromptResponse {
+                PromptResponse::AllowAlways
+            }
+        }
+
+        let perms = Permissions {
+            fs: Some(FsPermissions {
+                read: Some(vec!["./data/**".to_string()]),
+                write: None,
+                prompt: Some(true),
+            }),
+            ..Default::default()
+        };
+        let grants = Arc::new(GrantStore::load(None, Some(Box::new(AlwaysAllow))).unwrap());
+        grants.record("fs.read", "./data/secret.txt", PromptResponse::Deny);
+        let caps = Capabilities::from_permissions(Some(&perms), false)
+            .unwrap()
+            .with_grants(grants.clone());
+
+        // Persisted decisions win over the manifest globs
+        assert!(caps.check_fs_read("./data/secret.txt").is_err());
+        assert!(caps.check_fs_read("./data/notes.txt").is_ok());
+
+        // Outside the globs the user is asked, and the answer is remembered
+        assert!(caps.check_fs_read("/home/user/report.pdf").is_ok());
+        assert_eq!(
+            grants.lookup("fs.read", "/home/user/report.pdf"),
+            Some(true)
+        );
+
+        // Sections without prompt = true still deny
+        assert!(caps.check_net("example.com").is_err());
+        assert_eq!(grants.lookup("net", "example.com"), None);
+    }
+
+    #[test]
+    fn test_audit_records_decisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = Arc::new(AuditLog::open(dir.path().to_path_buf(), 1024 * 1024, 1).unwrap());
+        let mut receiver = ext_app::AuditEventSource::subscribe(audit.as_ref());
+
+        let perms = Permissions {
+            net: Some(NetPermissions {
+                allow: Some(vec!["api.example.com".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let caps = Capabilities::from_permissions(Some(&perms), false)
+            .unwrap()
+            .with_audit(audit.clone());
+
+        assert!(caps.check_net("api.example.com").is_ok());
+        assert!(caps.check_process_spawn("/bin/sh").is_err());
+        // Checks outside the audited categories are not recorded
+        assert!(caps.check_ui_windows().is_ok());
+
+        let allowed = receiver.try_recv().unwrap();
+        assert_eq!(
+            (allowed.capability.as_str(), allowed.resource.as_str()),
+            ("net", "api.example.com")
+        );
+        assert!(allowed.allowed && !allowed.dev_mode);
+        let denied = receiver.try_recv().unwrap();
+        assert_eq!(denied.capability, "process.spawn");
+        assert!(!denied.allowed);
+        assert!(receiver.try_recv().is_err());
+
+        // Dev mode allows everything but still records what was used
+        let dev = Capabilities::from_permissions(None, true)
+            .unwrap()
+            .with_audit(audit);
+        assert!(dev.check_env_read("HOME").is_ok());
+        let used = receiver.try_recv().unwrap();
+        assert_eq!(used.capability, "sys.env.read");
+        assert!(used.allowed && used.dev_mode);
+    }
+
     #[test]
     fn test_adapters() {
         let caps = Capabilities::from_permissions(None, true).unwrap();
@@ -1479,6 +2155,9 @@ mod tests {
         // Test Net adapter
         assert!(adapters.net.check_connect("any.host.com").is_ok());
         assert!(adapters.net.check_listen(8080).is_ok());
+        assert!(adapters.net.check_socket_path("/tmp/app.sock").is_ok());
+        assert!(adapters.net.check_read_path("/tmp/upload.bin").is_ok());
+        assert!(adapters.net.check_write_path("/tmp/download.bin").is_ok());
 
         // Test Sys adapter
         assert!(adapters.sys.check_clipboard_read().is_ok());
@@ -1501,5 +2180,13 @@ mod tests {
         // Test WASM adapter
         assert!(adapters.wasm.check_load("/any/path.wasm").is_ok());
         assert!(adapters.wasm.check_preopen("/any/dir").is_ok());
+
+        // Test storage, database, crypto, shell and monitor adapters
+        assert!(adapters.storage.check_storage().is_ok());
+        assert!(adapters.database.check_database("app").is_ok());
+        assert!(adapters.crypto.check_crypto().is_ok());
+        assert!(adapters.shell.can_execute());
+        assert!(adapters.shell.can_run_command("ls", None));
+        assert!(adapters.monitor.check_processes().is_ok());
     }
 }
@@ -15,7 +15,7 @@ use tokio::sync::mpsc;
 
 // Re-export extension types needed for context
 pub use ext_app::AppInfo;
-pub use ext_ipc::{IpcEvent, ToRendererCmd};
+pub use ext_ipc::{BinaryStore, IpcEvent, IpcRouter, ToRendererCmd};
 pub use ext_window::{MenuEvent as WinMenuEvent, WindowCmd, WindowSystemEvent};
 
 // ============================================================================
@@ -88,6 +88,9 @@ pub struct ExtensionInitContext {
     /// App information (for ext_app, ext_storage)
     pub app_info: Option<AppInfo>,
 
+    /// HTTP client configuration from the manifest (for ext_net)
+    pub http: Option<ext_net::HttpClientConfig>,
+
     /// Whether running in dev mode
     pub dev_mode: bool,
 }
@@ -272,13 +275,6 @@ fn create_all_descriptors() -> Vec<ExtensionDescriptor> {
         // =====================================================================
         // Tier 1: Simple State (no external dependencies) - moved from Tier 0
         // =====================================================================
-        ExtensionDescriptor {
-            name: "monitor",
-            specifier: "runtime:monitor",
-            tier: ExtensionTier::SimpleState,
-            extension_fn: ext_monitor::monitor_extension,
-            required: false,
-        },
         ExtensionDescriptor {
             name: "os_compat",
             specifier: "runtime:os_compat",
@@ -419,6 +415,13 @@ fn create_all_descriptors() -> Vec<ExtensionDescriptor> {
             extension_fn: ext_storage::storage_extension,
             required: false,
         },
+        ExtensionDescriptor {
+            name: "monitor",
+            specifier: "runtime:monitor",
+            tier: ExtensionTier::CapabilityBased,
+            extension_fn: ext_monitor::monitor_extension,
+            required: false,
+        },
         ExtensionDescriptor {
             name: "codesign",
             specifier: "runtime:codesign",
@@ -526,9 +529,6 @@ fn init_simple_state(name: &str, state: &mut OpState) -> Result<(), InitError> {
         "image_tools" => {
             ext_image_tools::init_image_tools_state(state);
         }
-        "monitor" => {
-            ext_monitor::init_monitor_state(state);
-        }
         "display" => {
             ext_display::init_display_state(state);
         }
@@ -562,13 +562,23 @@ fn init_capability_state(
         }
         "net" => {
             ext_net::init_net_state(state, adapters.map(|a| a.net.clone()));
+            if let Some(config) = ctx.http.clone() {
+                // Persistent cookies live next to the app's other data
+                let cookie_path = ctx.app_info.as_ref().and_then(|a| {
+                    dirs::data_dir().map(|d| d.join(&a.identifier).join("cookies.json"))
+                });
+                // Failing here would skip the remaining extensions, so keep
+                // the default client from init_net_state instead
+                if let Err(e) = ext_net::configure_http_client(state, config, cookie_path) {
+                    tracing::warn!("Invalid [http] configuration, using defaults: {}", e);
+                }
+            }
         }
         "sys" => {
             ext_sys::init_sys_state(state, adapters.map(|a| a.sys.clone()));
         }
         "crypto" => {
-            // Crypto has no capability checker - all ops are safe
-            ext_crypto::init_crypto_state(state, None);
+            ext_crypto::init_crypto_state(state, adapters.map(|a| a.crypto.clone()));
         }
         "storage" => {
             let app_id = ctx
@@ -576,7 +586,10 @@ fn init_capability_state(
                 .as_ref()
                 .map(|a| a.identifier.clone())
                 .unwrap_or_else(|| "forge-app".to_string());
-            ext_storage::init_storage_state(state, app_id, None);
+            ext_storage::init_storage_state(state, app_id, adapters.map(|a| a.storage.clone()));
+        }
+        "monitor" => {
+            ext_monitor::init_monitor_state(state, adapters.map(|a| a.monitor.clone()));
         }
         "codesign" => {
             ext_codesign::init_codesign_state(state, adapters.map(|a| a.codesign.clone()));
@@ -648,9 +661,15 @@ fn init_complex_state(
             ext_app::init_app_state::<ext_app::DefaultAppCapabilityChecker>(
                 state, app_info, None, None,
             );
+            if let Some(grants) = ctx.capabilities.as_ref().and_then(|c| c.grants()) {
+                ext_app::init_permission_grants(state, grants);
+            }
+            if let Some(audit) = ctx.capabilities.as_ref().and_then(|c| c.audit()) {
+                ext_app::init_audit_log(state, audit);
+            }
         }
         "shell" => {
-            ext_shell::init_shell_state::<ext_shell::DefaultShellCapabilityChecker>(state, None);
+            ext_shell::init_shell_state(state, ctx.adapters.as_ref().map(|a| a.shell.clone()));
         }
         "debugger" => {
             // Initialize debugger state with broadcast channels for events
@@ -689,7 +708,12 @@ fn init_complex_state(
                 .as_ref()
                 .map(|a| a.identifier.clone())
                 .unwrap_or_else(|| "forge-app".to_string());
-            ext_database::init_database_state(state, app_id, None, None);
+            ext_database::init_database_state(
+                state,
+                app_id,
+                ctx.adapters.as_ref().map(|a| a.database.clone()),
+                None,
+            );
         }
         "shortcuts" => {
             // Initialize shortcuts state with app identifier for persistence
@@ -719,9 +743,11 @@ pub fn init_ipc_manually(
     state: &mut OpState,
     to_renderer_tx: mpsc::Sender<ToRendererCmd>,
     to_deno_rx: mpsc::Receiver<IpcEvent>,
+    binary_store: BinaryStore,
+    ipc_router: IpcRouter,
     adapters: Option<&CapabilityAdapters>,
 ) {
-    ext_ipc::init_ipc_state(state, to_renderer_tx, to_deno_rx);
+    ext_ipc::init_ipc_state(state, to_renderer_tx, to_deno_rx, binary_store, ipc_router);
     if let Some(adapters) = adapters {
         ext_ipc::init_ipc_capabilities(state, Some(adapters.ipc.clone()));
     }
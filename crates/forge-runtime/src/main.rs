@@ -72,6 +72,7 @@
 //!
 //! **Dev Mode:** Assets served from filesystem (`web/` directory)
 //! **Production:** Assets embedded in binary (via `FORGE_EMBED_DIR` at build time)
+//! **Compiled:** Assets read from the forge-smelt payload (see below)
 //!
 //! See [`ForgeAssetProvider`] for implementation.
 //!
@@ -80,7 +81,25 @@
 //! The [`ForgeModuleLoader`] maps specifiers:
 //! - `runtime:fs` → `ext:runtime_fs/init.js`
 //! - `runtime:window` → `ext:runtime_window/init.js`
-//! - File URLs → Filesystem paths
+//! - Bare specifiers/aliases → the app's import map (`deno.json` `imports`
+//!   and `scopes`, or `import_map.json`), applied before URL resolution
+//! - File URLs → Filesystem paths (not importable from remote modules)
+//! - `https:`, `jsr:`, `npm:` → fetched through [`remote_modules`] (cached,
+//!   locked in `forge.lock`, gated by `[permissions.net]`; `--offline`
+//!   resolves from the cache only)
+//!
+//! TypeScript/JSX modules are transpiled by [`transpile`] with inline source
+//! maps, which the loader registers with V8 so stack traces and breakpoints
+//! refer to the original sources. Transpiled output is cached on disk, keyed
+//! by source content and runtime version.
+//!
+//! # Standalone Binaries
+//!
+//! `forge compile` appends a [`forge_smelt::Payload`] (manifest, transpiled
+//! modules, web assets) to a copy of this binary. When started without
+//! `--app-dir`, the runtime checks its own executable for a payload first;
+//! if one is found the app directory is the executable's directory and
+//! modules/assets under it are served from the payload.
 //!
 //! # Environment Variables
 //!
@@ -95,14 +114,13 @@
 //! - `examples/text-editor` - Simple editor
 
 use anyhow::{Context, Result};
-use deno_ast::{MediaType, ParseParams};
+use deno_ast::MediaType;
 use deno_core::error::ModuleLoaderError;
 use deno_core::{
     JsRuntime, ModuleLoadOptions, ModuleLoadReferrer, ModuleLoadResponse, ModuleSourceCode,
     ModuleSpecifier, ResolutionKind, RuntimeOptions,
 };
 use futures_util::{SinkExt, StreamExt};
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use serde::Deserialize;
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
@@ -117,7 +135,7 @@ use tokio_tungstenite::tungstenite::Message;
 use wry::http::{Response, StatusCode};
 use wry::WebViewBuilder;
 
-use ext_ipc::{IpcEvent, ToRendererCmd};
+use ext_ipc::{BinaryStore, IpcEvent, IpcRouter, ToRendererCmd};
 use ext_window::{
     add_context_menu_items, add_menu_items_with_tracking, add_tray_menu_items,
     create_default_tray_icon, mime_for, AssetProvider, ChannelChecker,
@@ -126,12 +144,19 @@ use ext_window::{
     WindowManagerConfig, WindowOpts, WindowSystemEvent, CONTEXT_MENU_TIMEOUT_SECS,
 };
 
+mod audit;
 mod capabilities;
 mod crash;
 mod ext_registry;
+mod permission_prompt;
+mod remote_modules;
+mod transpile;
 
+use audit::{AuditConfig, AuditLog};
 use capabilities::{create_capability_adapters, Capabilities, Permissions};
 use ext_registry::{ExtensionInitContext, ExtensionRegistry};
+use remote_modules::{ModulesConfig, RemoteModules};
+use transpile::{TranspileCache, Transpiler};
 
 /// Application manifest (manifest.app.toml)
 ///
@@ -146,6 +171,12 @@ pub struct Manifest {
     /// Permissions/capabilities section. Accepts both `permissions` and `capabilities` keys.
     #[serde(alias = "capabilities")]
     pub permissions: Option<Permissions>,
+    /// Remote module resolution (cache, lockfile, offline mode)
+    pub modules: Option<ModulesConfig>,
+    /// Capability audit log (rotation, enable/disable)
+    pub audit: Option<AuditConfig>,
+    /// HTTP client for fetch (proxy, certificates, cookies, redirects)
+    pub http: Option<ext_net::HttpClientConfig>,
 }
 /// Application metadata
 ///
@@ -194,11 +225,19 @@ include!(concat!(env!("OUT_DIR"), "/assets.rs"));
 struct ForgeAssetProvider {
     /// Base directory for filesystem assets
     app_dir: PathBuf,
+    /// Payload of a compiled standalone binary
+    payload: Option<std::sync::Arc<forge_smelt::Payload>>,
 }
 
 impl AssetProvider for ForgeAssetProvider {
     fn get_asset(&self, path: &str) -> Option<Vec<u8>> {
-        // First try embedded assets
+        // Compiled binaries carry their assets in the payload
+        if let Some(payload) = &self.payload {
+            if let Some(bytes) = payload.asset(path) {
+                return Some(bytes.to_vec());
+            }
+        }
+        // Then try assets embedded at build time
         if ASSET_EMBEDDED {
             if let Some(bytes) = get_asset(path) {
                 return Some(bytes.to_vec());
@@ -210,7 +249,7 @@ impl AssetProvider for ForgeAssetProvider {
     }
 
     fn is_embedded(&self) -> bool {
-        ASSET_EMBEDDED
+        ASSET_EMBEDDED || self.payload.is_some()
     }
 }
 
@@ -309,13 +348,45 @@ fn warm_up_window_helpers(app_dir: &Path, app_name: &str, default_channels: &[St
 /// Handles both extension modules and file URLs.
 struct ForgeModuleLoader {
     /// Base directory for resolving file URLs
-    #[allow(dead_code)]
     app_dir: PathBuf,
+    /// Payload of a compiled standalone binary; modules found here are
+    /// served pre-transpiled instead of being read from disk
+    payload: Option<std::sync::Arc<forge_smelt::Payload>>,
+    /// Import map from deno.json / import_map.json, applied before URL resolution
+    import_map: Option<forge_smelt::ImportMap>,
+    /// Resolver/fetcher for `https:`, `jsr:` and `npm:` imports
+    remote: Rc<RemoteModules>,
+    /// TypeScript transpiler with on-disk cache and source map registry
+    transpiler: Rc<Transpiler>,
 }
 
 impl ForgeModuleLoader {
-    fn new(app_dir: PathBuf) -> Self {
-        Self { app_dir }
+    fn new(
+        app_dir: PathBuf,
+        payload: Option<std::sync::Arc<forge_smelt::Payload>>,
+        import_map: Option<forge_smelt::ImportMap>,
+        remote: RemoteModules,
+        transpiler: Transpiler,
+    ) -> Self {
+        Self {
+            app_dir,
+            payload,
+            import_map,
+            remote: Rc::new(remote),
+            transpiler: Rc::new(transpiler),
+        }
+    }
+
+    /// Look up a module in the payload by its path relative to `app_dir`
+    fn embedded_module(&self, path: &Path) -> Option<&forge_smelt::EmbeddedModule> {
+        let payload = self.payload.as_ref()?;
+        let relative = path.strip_prefix(&self.app_dir).ok()?;
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        payload.module(&key)
     }
 }
 
@@ -326,6 +397,15 @@ impl deno_core::ModuleLoader for ForgeModuleLoader {
         referrer: &str,
         _kind: ResolutionKind,
     ) -> Result<ModuleSpecifier, ModuleLoaderError> {
+        // Apply the app's import map (bare specifiers, path aliases, scopes)
+        let mapped = match (&self.import_map, ModuleSpecifier::parse(referrer)) {
+            (Some(import_map), Ok(referrer_url)) => import_map
+                .resolve(specifier, &referrer_url)
+                .map_err(|e| ModuleLoaderError::generic(e.to_string()))?,
+            _ => None,
+        };
+        let specifier = mapped.as_ref().map(|url| url.as_str()).unwrap_or(specifier);
+
         // Handle runtime:* imports by mapping to ext:runtime_*/init.js
         if let Some(module_name) = specifier.strip_prefix("runtime:") {
             let ext_specifier = format!("ext:runtime_{}/init.js", module_name);
@@ -333,9 +413,24 @@ impl deno_core::ModuleLoader for ForgeModuleLoader {
                 .map_err(|e| ModuleLoaderError::generic(format!("Invalid specifier: {}", e)));
         }
 
-        // For relative imports, resolve against referrer
-        deno_core::resolve_import(specifier, referrer)
-            .map_err(|e| ModuleLoaderError::generic(e.to_string()))
+        let resolved = match mapped {
+            Some(mapped) => mapped,
+            // For relative imports, resolve against referrer
+            None => deno_core::resolve_import(specifier, referrer)
+                .map_err(|e| ModuleLoaderError::generic(e.to_string()))?,
+        };
+
+        // As in Deno, remote code can't reach into the local filesystem;
+        // `load` reads file URLs without any fs capability check
+        if resolved.scheme() == "file"
+            && ModuleSpecifier::parse(referrer).is_ok_and(|url| remote_modules::is_remote(&url))
+        {
+            return Err(ModuleLoaderError::generic(format!(
+                "Remote module {} cannot import local module {}",
+                referrer, resolved
+            )));
+        }
+        Ok(resolved)
     }
 
     fn load(
@@ -352,6 +447,30 @@ impl deno_core::ModuleLoader for ForgeModuleLoader {
             ))));
         }
 
+        // Remote modules are fetched asynchronously through the module cache
+        if remote_modules::is_remote(module_specifier) {
+            let remote = self.remote.clone();
+            let transpiler = self.transpiler.clone();
+            let requested = module_specifier.clone();
+            return ModuleLoadResponse::Async(Box::pin(async move {
+                let module = remote
+                    .load(&requested)
+                    .await
+                    .map_err(|e| ModuleLoaderError::generic(e.to_string()))?;
+                let (module_type, code) =
+                    transpiler.transpile(&module.url, module.media_type, module.code)?;
+                // Relative imports inside the module resolve against the URL it
+                // was actually served from (after jsr:/npm: resolution and redirects)
+                Ok(deno_core::ModuleSource::new_with_redirect(
+                    module_type,
+                    ModuleSourceCode::String(code.into()),
+                    &requested,
+                    &module.url,
+                    None,
+                ))
+            }));
+        }
+
         let module_specifier = module_specifier.clone();
 
         ModuleLoadResponse::Sync((move || {
@@ -362,55 +481,30 @@ impl deno_core::ModuleLoader for ForgeModuleLoader {
                 ))
             })?;
 
-            let media_type = MediaType::from_path(&path);
-            let (module_type, should_transpile) = match media_type {
-                MediaType::JavaScript | MediaType::Mjs | MediaType::Cjs => {
-                    (deno_core::ModuleType::JavaScript, false)
-                }
-                MediaType::Jsx => (deno_core::ModuleType::JavaScript, true),
-                MediaType::TypeScript
-                | MediaType::Mts
-                | MediaType::Cts
-                | MediaType::Dts
-                | MediaType::Dmts
-                | MediaType::Dcts
-                | MediaType::Tsx => (deno_core::ModuleType::JavaScript, true),
-                MediaType::Json => (deno_core::ModuleType::Json, false),
-                _ => {
-                    return Err(ModuleLoaderError::generic(format!(
-                        "Unknown file extension: {:?}",
-                        path.extension()
-                    )));
+            if let Some(module) = self.embedded_module(&path) {
+                let module_type = match module.kind {
+                    forge_smelt::ModuleKind::JavaScript => deno_core::ModuleType::JavaScript,
+                    forge_smelt::ModuleKind::Json => deno_core::ModuleType::Json,
+                };
+                // forge-smelt emits inline source maps; register them for V8
+                if module.kind == forge_smelt::ModuleKind::JavaScript {
+                    self.transpiler
+                        .register_source_map(&module_specifier, &module.code);
                 }
-            };
+                return Ok(deno_core::ModuleSource::new(
+                    module_type,
+                    ModuleSourceCode::String(module.code.clone().into()),
+                    &module_specifier,
+                    None,
+                ));
+            }
 
             let code = std::fs::read_to_string(&path).map_err(|e| {
                 ModuleLoaderError::generic(format!("Failed to read {}: {}", path.display(), e))
             })?;
-
-            let code = if should_transpile {
-                let parsed = deno_ast::parse_module(ParseParams {
-                    specifier: module_specifier.clone(),
-                    text: code.into(),
-                    media_type,
-                    capture_tokens: false,
-                    scope_analysis: false,
-                    maybe_syntax: None,
-                })
-                .map_err(|e| ModuleLoaderError::generic(e.to_string()))?;
-
-                let transpiled = parsed
-                    .transpile(
-                        &deno_ast::TranspileOptions::default(),
-                        &deno_ast::TranspileModuleOptions::default(),
-                        &deno_ast::EmitOptions::default(),
-                    )
-                    .map_err(|e| ModuleLoaderError::generic(e.to_string()))?;
-
-                transpiled.into_source().text
-            } else {
-                code
-            };
+            let (module_type, code) =
+                self.transpiler
+                    .transpile(&module_specifier, MediaType::from_path(&path), code)?;
 
             let module = deno_core::ModuleSource::new(
                 module_type,
@@ -421,6 +515,51 @@ impl deno_core::ModuleLoader for ForgeModuleLoader {
             Ok(module)
         })())
     }
+
+    fn get_source_map(&self, file_name: &str) -> Option<Cow<'_, [u8]>> {
+        self.transpiler.source_map(file_name).map(Cow::Owned)
+    }
+}
+
+/// Watch for SIGINT, SIGTERM and SIGHUP (Ctrl+C on Windows) on a dedicated thread
+///
+/// `on_signal` gets the signal's name and the exit code a process killed by
+/// it reports (128 + signal number).
+fn watch_termination_signals(mut on_signal: impl FnMut(&'static str, i32) + Send + 'static) {
+    thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to create signal runtime");
+        rt.block_on(async move {
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{signal, SignalKind};
+
+                let (Ok(mut int), Ok(mut term), Ok(mut hup)) = (
+                    signal(SignalKind::interrupt()),
+                    signal(SignalKind::terminate()),
+                    signal(SignalKind::hangup()),
+                ) else {
+                    tracing::warn!("Failed to install termination signal handlers");
+                    return;
+                };
+                loop {
+                    let (name, code) = tokio::select! {
+                        _ = int.recv() => ("SIGINT", 130),
+                        _ = term.recv() => ("SIGTERM", 143),
+                        _ = hup.recv() => ("SIGHUP", 129),
+                    };
+                    on_signal(name, code);
+                }
+            }
+
+            #[cfg(not(unix))]
+            while tokio::signal::ctrl_c().await.is_ok() {
+                on_signal("SIGINT", 130);
+            }
+        });
+    });
 }
 
 /// HMR (Hot Module Replacement) server for dev mode
@@ -443,46 +582,43 @@ async fn run_hmr_server(port: u16, watch_dir: PathBuf) {
     let (tx, _) = broadcast::channel::<String>(16);
     let tx_for_watcher = tx.clone();
 
-    // File watcher thread
-    let watch_dir_clone = watch_dir.clone();
-    std::thread::spawn(move || {
-        let (notify_tx, notify_rx) = std::sync::mpsc::channel();
-
-        let mut watcher: RecommendedWatcher =
-            match notify::recommended_watcher(move |res: Result<notify::Event, notify::Error>| {
-                if let Ok(event) = res {
-                    if event.kind.is_modify() || event.kind.is_create() {
-                        for path in event.paths {
-                            let path_str = path.display().to_string();
-                            let _ = notify_tx.send(path_str);
-                        }
-                    }
-                }
-            }) {
-                Ok(w) => w,
-                Err(e) => {
-                    tracing::error!("Failed to create file watcher: {}", e);
-                    return;
+    // Debounced watcher: one message per save, however many raw events it
+    // makes, and at most one page reload per batch of changes
+    let opts = ext_fs::WatchOpts {
+        debounce_ms: Some(100),
+        ..Default::default()
+    };
+    let watcher = ext_fs::watch_debounced(&watch_dir, opts, move |events| {
+        let mut reload = None;
+        for event in events {
+            let path = match event.kind {
+                ext_fs::WatchEventKind::Create | ext_fs::WatchEventKind::Modify => {
+                    event.paths[0].clone()
                 }
+                ext_fs::WatchEventKind::Rename => event.paths[1].clone(),
+                ext_fs::WatchEventKind::Remove => continue,
             };
-
-        if let Err(e) = watcher.watch(&watch_dir_clone, RecursiveMode::Recursive) {
-            tracing::error!("Failed to watch directory: {}", e);
-            return;
-        }
-
-        tracing::debug!("HMR watching {}", watch_dir_clone.display());
-
-        // Forward file change events to broadcast channel
-        for path in notify_rx {
-            let msg = if path.ends_with(".css") {
-                format!("css:{}", path)
+            if path.ends_with(".css") {
+                let _ = tx_for_watcher.send(format!("css:{}", path));
             } else {
-                format!("reload:{}", path)
-            };
-            let _ = tx_for_watcher.send(msg);
+                reload.get_or_insert(path);
+            }
+        }
+        if let Some(path) = reload {
+            let _ = tx_for_watcher.send(format!("reload:{}", path));
         }
+        true
     });
+    let _watcher = match watcher {
+        Ok(watcher) => {
+            tracing::debug!("HMR watching {}", watch_dir.display());
+            Some(watcher)
+        }
+        Err(e) => {
+            tracing::error!("Failed to watch {}: {}", watch_dir.display(), e);
+            None
+        }
+    };
 
     // Accept WebSocket connections
     loop {
@@ -555,10 +691,11 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         .with_target(true)
         .init();
 
-    // Parse args: --app-dir <dir> --dev
+    // Parse args: --app-dir <dir> --dev --offline
     let mut args = env::args().skip(1);
     let mut app_dir: Option<PathBuf> = None;
     let mut dev_mode = false;
+    let mut offline = false;
     while let Some(a) = args.next() {
         match a.as_str() {
             "--app-dir" => {
@@ -569,13 +706,42 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
             "--dev" => {
                 dev_mode = true;
             }
+            "--offline" => {
+                offline = true;
+            }
             _ => {}
         }
     }
 
+    // A compiled standalone binary carries its app as a payload
+    let payload = if app_dir.is_none() {
+        env::current_exe()
+            .ok()
+            .and_then(|exe| match forge_smelt::read_payload(&exe) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::warn!("Ignoring unreadable app payload: {}", e);
+                    None
+                }
+            })
+            .map(std::sync::Arc::new)
+    } else {
+        None
+    };
+
     // Auto-detect app_dir from bundle if not provided via arguments
     let app_dir = match app_dir {
         Some(dir) => dir,
+        None if payload.is_some() => {
+            let exe_path = env::current_exe().context("locating executable")?;
+            let exe_path = exe_path.canonicalize().unwrap_or(exe_path);
+            let exe_dir = exe_path
+                .parent()
+                .context("executable has no parent directory")?
+                .to_path_buf();
+            tracing::info!("Running embedded app from {}", exe_path.display());
+            exe_dir
+        }
         None => {
             // Try to detect if running from a macOS/Linux bundle
             if let Ok(exe_path) = env::current_exe() {
@@ -617,10 +783,14 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         }
     };
 
-    let manifest_path = app_dir.join("manifest.app.toml");
-    let manifest_txt = rt
-        .block_on(tokio::fs::read_to_string(&manifest_path))
-        .with_context(|| format!("reading manifest at {}", manifest_path.display()))?;
+    let manifest_txt = match &payload {
+        Some(payload) => payload.manifest.clone(),
+        None => {
+            let manifest_path = app_dir.join("manifest.app.toml");
+            rt.block_on(tokio::fs::read_to_string(&manifest_path))
+                .with_context(|| format!("reading manifest at {}", manifest_path.display()))?
+        }
+    };
     let manifest: Manifest = toml::from_str(&manifest_txt).context("parsing manifest")?;
 
     tracing::info!(
@@ -652,19 +822,64 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
     }
 
     // Initialize capabilities from manifest permissions
-    let capabilities = Capabilities::from_permissions(manifest.permissions.as_ref(), dev_mode)
+    let mut capabilities = Capabilities::from_permissions(manifest.permissions.as_ref(), dev_mode)
         .context("initializing capabilities")?;
 
+    // Remembered prompt decisions apply in production only (dev allows everything)
+    if !dev_mode {
+        let prompter: Option<Box<dyn permission_prompt::PermissionPrompter>> =
+            if capabilities.prompts_enabled() {
+                Some(Box::new(permission_prompt::DialogPrompter::new(
+                    manifest.app.name.clone(),
+                )))
+            } else {
+                None
+            };
+        let grants = permission_prompt::GrantStore::load(
+            permission_prompt::GrantStore::default_path(&manifest.app.identifier),
+            prompter,
+        )
+        .context("loading permission grants")?;
+        capabilities = capabilities.with_grants(std::sync::Arc::new(grants));
+    }
+
+    // Every capability decision is audited, dev sessions included, so
+    // `forge audit` can suggest permissions from what the app really used
+    let audit_log = match AuditLog::from_config(&manifest.app.identifier, manifest.audit.as_ref()) {
+        Ok(log) => log.map(std::sync::Arc::new),
+        Err(e) => {
+            tracing::warn!("Capability audit log disabled: {}", e);
+            None
+        }
+    };
+    if let Some(audit_log) = &audit_log {
+        tracing::debug!(
+            "Auditing capability checks to {}",
+            audit_log.dir().display()
+        );
+        capabilities = capabilities.with_audit(audit_log.clone());
+    }
+
     if dev_mode {
         tracing::info!("Running in dev mode - all permissions allowed");
     }
 
+    // Certificate and key files named in [http] are relative to the app
+    let mut http_config = manifest.http.clone().unwrap_or_default();
+    http_config
+        .load_files(&app_dir)
+        .context("loading [http] certificates")?;
+
     // Create capability adapters for each extension
     let adapters = create_capability_adapters(capabilities.clone());
 
     // Create IPC channels for Deno <-> Host <-> Renderer communication
     let (to_deno_tx, to_deno_rx) = tokio::sync::mpsc::channel::<IpcEvent>(256);
     let (to_renderer_tx, mut to_renderer_rx) = tokio::sync::mpsc::channel::<ToRendererCmd>(256);
+    // Binary IPC payloads, written by ext_ipc ops and served by the app:// protocol
+    let binary_store = BinaryStore::new();
+    // Routes renderer events to ext_ipc subscriptions; closed windows' invokes are dropped
+    let ipc_router = IpcRouter::new();
 
     // Create channels for ext_window (native window operations)
     let (window_cmd_tx, mut window_cmd_rx) = tokio::sync::mpsc::channel::<WindowCmd>(64);
@@ -673,7 +888,45 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         tokio::sync::mpsc::channel::<WinMenuEvent>(64);
 
     // Build Deno runtime with extensions (runtime:*) using the extension registry
-    let module_loader = Rc::new(ForgeModuleLoader::new(app_dir.clone()));
+    let remote_modules = RemoteModules::new(
+        &app_dir,
+        manifest.modules.as_ref(),
+        offline,
+        capabilities.clone(),
+    )
+    .context("initializing remote module loader")?;
+    // Compiled binaries carry the import map normalized to the app directory
+    let import_map = match payload.as_ref().and_then(|p| p.import_map.as_deref()) {
+        Some(json) => {
+            let base = ModuleSpecifier::from_directory_path(&app_dir)
+                .map_err(|_| anyhow::anyhow!("Invalid path: {}", app_dir.display()))?;
+            Some(forge_smelt::ImportMap::parse(
+                json,
+                &base,
+                app_dir.join(forge_smelt::import_map::IMPORT_MAP_FILE),
+            )?)
+        }
+        None if payload.is_some() => None,
+        None => forge_smelt::load_import_map(&app_dir).context("loading import map")?,
+    };
+    if let Some(import_map) = &import_map {
+        tracing::debug!("Using import map {}", import_map.path.display());
+    }
+
+    let transpile_cache = manifest
+        .modules
+        .as_ref()
+        .and_then(|m| m.transpile_cache)
+        .unwrap_or(true)
+        .then(|| TranspileCache::new(TranspileCache::default_root()));
+
+    let module_loader = Rc::new(ForgeModuleLoader::new(
+        app_dir.clone(),
+        payload.clone(),
+        import_map,
+        remote_modules,
+        Transpiler::new(transpile_cache),
+    ));
     let registry = ExtensionRegistry::new();
 
     tracing::info!(
@@ -681,9 +934,16 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         registry.count()
     );
 
+    let extensions = registry.build_extensions(None); // None = all extensions enabled
+    if let Some(audit_log) = &audit_log {
+        audit_log.register_extensions(&extensions);
+    }
+
     let mut js = JsRuntime::new(RuntimeOptions {
         module_loader: Some(module_loader),
-        extensions: registry.build_extensions(None), // None = all extensions enabled
+        extensions,
+        // Tracks the running op so audit entries name who asked
+        op_metrics_factory_fn: audit_log.as_ref().map(|_| audit::op_metrics_factory()),
         ..Default::default()
     });
 
@@ -692,7 +952,7 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         name: manifest.app.name.clone(),
         version: manifest.app.version.clone(),
         identifier: manifest.app.identifier.clone(),
-        is_packaged: false, // TODO: detect packaged mode
+        is_packaged: payload.is_some(),
         exe_path: std::env::current_exe()
             .ok()
             .map(|p| p.to_string_lossy().to_string()),
@@ -709,6 +969,8 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
             &mut state,
             to_renderer_tx.clone(),
             to_deno_rx,
+            binary_store.clone(),
+            ipc_router.clone(),
             Some(&adapters),
         );
 
@@ -727,6 +989,7 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
             ipc: None,    // Already consumed above
             window: None, // Already consumed above
             app_info: Some(app_info.clone()),
+            http: Some(http_config),
             dev_mode,
         };
 
@@ -738,15 +1001,19 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
     }
 
     // Load the app's main.ts as an ES module (but don't evaluate yet)
-    let main_ts_path = app_dir
-        .join("src/main.ts")
-        .canonicalize()
-        .with_context(|| {
-            format!(
-                "Cannot find main.ts at {}",
-                app_dir.join("src/main.ts").display()
-            )
-        })?;
+    let main_ts_path = match &payload {
+        // Embedded modules don't exist on disk; the loader resolves them by path
+        Some(payload) => app_dir.join(&payload.entry),
+        None => app_dir
+            .join("src/main.ts")
+            .canonicalize()
+            .with_context(|| {
+                format!(
+                    "Cannot find main.ts at {}",
+                    app_dir.join("src/main.ts").display()
+                )
+            })?,
+    };
     let main_specifier = ModuleSpecifier::from_file_path(&main_ts_path)
         .map_err(|_| anyhow::anyhow!("Invalid path: {}", main_ts_path.display()))?;
 
@@ -762,6 +1029,8 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         ToRenderer(ToRendererCmd),
         // ext_window commands (runtime:window) - handled by WindowManager
         WindowCmd(WindowCmd),
+        // A termination signal arrived: exit with this code
+        Terminate(i32),
     }
 
     let event_loop: EventLoop<UserEvent> = EventLoopBuilder::with_user_event().build();
@@ -811,6 +1080,27 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         }
     });
 
+    // Termination signals would otherwise end the process without running
+    // atexit or the event loop's shutdown, leaving spawned children behind.
+    // Signals the app subscribed to through runtime:signals are left to it.
+    // With the handlers installed the OS no longer terminates the process
+    // itself, so a second signal while the event loop is stuck exits at once.
+    watch_termination_signals({
+        let proxy = proxy.clone();
+        let mut received = false;
+        move |signal, code| {
+            if ext_signals::is_subscribed(signal) {
+                return;
+            }
+            tracing::debug!("{} received, shutting down", signal);
+            ext_process::ProcessTreeTracker::global().kill_all();
+            if received || proxy.send_event(UserEvent::Terminate(code)).is_err() {
+                std::process::exit(code);
+            }
+            received = true;
+        }
+    });
+
     // Clone app_dir for use in the event loop closure
     let app_dir_clone = app_dir.clone();
 
@@ -833,6 +1123,7 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
     };
     let asset_provider = Arc::new(ForgeAssetProvider {
         app_dir: app_dir.clone(),
+        payload: payload.clone(),
     });
     let channel_checker: Option<Arc<dyn ChannelChecker>> = Some(Arc::new(ForgeChannelChecker {
         capabilities: capabilities.clone(),
@@ -845,7 +1136,9 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
         channel_checker,
         preload_js().to_string(),
         asset_provider,
-    );
+    )
+    .with_binary_store(binary_store)
+    .with_ipc_router(ipc_router);
 
     // Get shared state from WindowManager for menu event thread
     let menu_id_map = window_manager.menu_id_map();
@@ -987,6 +1280,15 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
                 // Use WindowManager's send_to_renderer (handles channel filtering internally)
                 window_manager.send_to_renderer(&window_id, &channel, &payload.to_string());
             }
+            Event::UserEvent(UserEvent::ToRenderer(ToRendererCmd::Reply { window_id, reply })) => {
+                window_manager.send_invoke_reply(&window_id, &reply);
+            }
+            Event::UserEvent(UserEvent::ToRenderer(ToRendererCmd::Broadcast {
+                channel,
+                payload,
+            })) => {
+                window_manager.broadcast_to_renderers(&channel, &payload.to_string());
+            }
 
             // =========================================================================
             // Window system events
@@ -1019,6 +1321,7 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
                         channel: "__window__".to_string(),
                         payload: serde_json::json!({}),
                         event_type: Some(event_type.to_string()),
+                        invoke_id: None,
                     });
                     tracing::debug!("Window {} {}", win_id, event_type);
                 }
@@ -1038,6 +1341,7 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
                             "height": size.height
                         }),
                         event_type: Some("resize".to_string()),
+                        invoke_id: None,
                     });
                     tracing::debug!(
                         "Window {} resized to {}x{}",
@@ -1062,6 +1366,7 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
                             "y": position.y
                         }),
                         event_type: Some("move".to_string()),
+                        invoke_id: None,
                     });
                     tracing::debug!(
                         "Window {} moved to ({}, {})",
@@ -1081,7 +1386,82 @@ fn sync_main(rt: tokio::runtime::Runtime) -> Result<()> {
                 window_manager.handle_cmd(cmd, event_loop_target);
             }
 
+            Event::UserEvent(UserEvent::Terminate(code)) => {
+                *control = ControlFlow::ExitWithCode(code);
+            }
+
+            // The process exits once the loop is gone; persist queued audit
+            // events and stop children, which atexit alone misses on signals
+            Event::LoopDestroyed => {
+                if let Some(audit_log) = &audit_log {
+                    audit_log.flush();
+                }
+                ext_process::ProcessTreeTracker::global().kill_all();
+            }
+
             _ => {}
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deno_core::ModuleLoader;
+
+    fn loader(app_dir: &Path) -> ForgeModuleLoader {
+        let remote = RemoteModules::new(
+            app_dir,
+            None,
+            true,
+            Capabilities::from_permissions(None, false).unwrap(),
+        )
+        .unwrap();
+        ForgeModuleLoader::new(
+            app_dir.to_path_buf(),
+            None,
+            None,
+            remote,
+            Transpiler::new(None),
+        )
+    }
+
+    #[test]
+    fn test_remote_modules_cannot_import_local_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = loader(dir.path());
+        let resolve = |specifier: &str, referrer: &str| {
+            loader.resolve(specifier, referrer, ResolutionKind::Import)
+        };
+
+        for referrer in [
+            "https://example.com/mod.ts",
+            "jsr:@std/path@1",
+            "npm:preact@10",
+        ] {
+            let err = resolve("file:///home/u/.ssh/config.json", referrer).unwrap_err();
+            assert!(err.to_string().contains("cannot import local module"));
+        }
+        assert_eq!(
+            resolve("./util.ts", "https://example.com/lib/mod.ts")
+                .unwrap()
+                .as_str(),
+            "https://example.com/lib/util.ts"
+        );
+        assert_eq!(
+            resolve("runtime:fs", "https://example.com/mod.ts")
+                .unwrap()
+                .as_str(),
+            "ext:runtime_fs/init.js"
+        );
+
+        // Local modules can still import local files and remote modules
+        assert_eq!(
+            resolve("./util.ts", "file:///app/src/main.ts")
+                .unwrap()
+                .as_str(),
+            "file:///app/src/util.ts"
+        );
+        assert!(resolve("https://example.com/mod.ts", "file:///app/src/main.ts").is_ok());
+    }
+}
@@ -0,0 +1,385 @@
+//! Interactive permission prompts and remembered grants
+//!
+//! A permission section can opt into prompting with `prompt = true`:
+//!
+//! ```toml
+//! [permissions.fs]
+//! read = ["./data/**"]
+//! prompt = true          # ask before denying reads/writes outside the globs
+//!
+//! [permissions.net]
+//! allow = ["api.example.com"]
+//! prompt = true
+//!
+//! [permissions.process]
+//! allow = ["git"]
+//! prompt = true
+//! ```
+//!
+//! When a check in a prompting section would be denied, the user is asked
+//! through a native dialog:
+//!
+//! - **Allow Once** - allowed until the app exits
+//! - **Always Allow** - allowed now and on later launches
+//! - **Deny** - denied now and on later launches, without asking again
+//!
+//! Decisions are looked up before the manifest globs, so a persisted
+//! "Deny" wins over a manifest allow. Persisted decisions live in
+//! `<app data dir>/<identifier>/permissions.json` and can be listed and
+//! revoked from JS through `runtime:app` (`permissionGrants()`,
+//! `revokePermission()`).
+
+use crate::capabilities::CapabilityError;
+use ext_app::{PermissionGrant, PermissionGrantStore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Grants file name inside the app data directory
+pub const GRANTS_FILE: &str = "permissions.json";
+
+/// Current grants file format version
+const GRANTS_VERSION: u32 = 1;
+
+/// The user's answer to a permission prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptResponse {
+    /// Allow for the rest of this session
+    AllowOnce,
+    /// Allow now and on later launches
+    AllowAlways,
+    /// Deny now and on later launches
+    Deny,
+}
+
+/// Asks the user whether to grant a capability
+pub trait PermissionPrompter: Send + Sync {
+    fn prompt(&self, capability: &str, resource: &str) -> PromptResponse;
+}
+
+/// Prompter showing a native message dialog
+pub struct DialogPrompter {
+    app_name: String,
+}
+
+impl DialogPrompter {
+    pub fn new(app_name: impl Into<String>) -> Self {
+        Self {
+            app_name: app_name.into(),
+        }
+    }
+}
+
+const ALLOW_ONCE: &str = "Allow Once";
+const ALLOW_ALWAYS: &str = "Always Allow";
+const DENY: &str = "Deny";
+
+impl PermissionPrompter for DialogPrompter {
+    fn prompt(&self, capability: &str, resource: &str) -> PromptResponse {
+        let description = format!(
+            "{} is requesting {} access to:\n\n{}",
+            self.app_name,
+            describe_capability(capability),
+            resource
+        );
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Permission Request")
+            .set_description(&description)
+            .set_buttons(rfd::MessageButtons::YesNoCancelCustom(
+                ALLOW_ONCE.to_string(),
+                ALLOW_ALWAYS.to_string(),
+                DENY.to_string(),
+            ))
+            .show();
+
+        match result {
+            rfd::MessageDialogResult::Custom(label) if label == ALLOW_ONCE => {
+                PromptResponse::AllowOnce
+            }
+            rfd::MessageDialogResult::Custom(label) if label == ALLOW_ALWAYS => {
+                PromptResponse::AllowAlways
+            }
+            // Some backends report custom buttons positionally
+            rfd::MessageDialogResult::Yes => PromptResponse::AllowOnce,
+            rfd::MessageDialogResult::No => PromptResponse::AllowAlways,
+            // Closing the dialog counts as a denial
+            _ => PromptResponse::Deny,
+        }
+    }
+}
+
+/// Human-readable name of a capability for the prompt text
+fn describe_capability(capability: &str) -> &str {
+    match capability {
+        "fs.read" => "read",
+        "fs.write" => "write",
+        "net" => "network",
+        "process.spawn" => "permission to run",
+        other => other,
+    }
+}
+
+/// A remembered decision as stored in the grants file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct StoredGrant {
+    capability: String,
+    resource: String,
+    allowed: bool,
+    granted_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GrantsFile {
+    version: u32,
+    grants: Vec<StoredGrant>,
+}
+
+/// Session and persisted prompt decisions for one app
+pub struct GrantStore {
+    /// Grants file; `None` keeps every decision in memory
+    path: Option<PathBuf>,
+    persisted: Mutex<Vec<StoredGrant>>,
+    /// "Allow Once" decisions, keyed by (capability, resource)
+    session: Mutex<HashMap<(String, String), String>>,
+    prompter: Option<Box<dyn PermissionPrompter>>,
+}
+
+impl std::fmt::Debug for GrantStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrantStore")
+            .field("path", &self.path)
+            .field("prompts", &self.prompter.is_some())
+            .finish()
+    }
+}
+
+impl GrantStore {
+    /// Load persisted grants from `path` (a missing file means no grants)
+    pub fn load(
+        path: Option<PathBuf>,
+        prompter: Option<Box<dyn PermissionPrompter>>,
+    ) -> Result<Self, CapabilityError> {
+        let persisted = match &path {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(path).map_err(|e| {
+                    CapabilityError::Grants(format!("reading {}: {}", path.display(), e))
+                })?;
+                let file: GrantsFile = serde_json::from_str(&text).map_err(|e| {
+                    CapabilityError::Grants(format!("parsing {}: {}", path.display(), e))
+                })?;
+                file.grants
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            path,
+            persisted: Mutex::new(persisted),
+            session: Mutex::new(HashMap::new()),
+            prompter,
+        })
+    }
+
+    /// Default grants file: `<app data dir>/<identifier>/permissions.json`
+    pub fn default_path(identifier: &str) -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(identifier).join(GRANTS_FILE))
+    }
+
+    /// Remembered decision for a resource, if any
+    pub fn lookup(&self, capability: &str, resource: &str) -> Option<bool> {
+        let key = (capability.to_string(), resource.to_string());
+        if self.session.lock().unwrap().contains_key(&key) {
+            return Some(true);
+        }
+        self.persisted
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|g| g.capability == capability && g.resource == resource)
+            .map(|g| g.allowed)
+    }
+
+    /// Ask the user and remember the answer; returns whether access is allowed
+    ///
+    /// Without a prompter (e.g. headless tests) the request is denied and
+    /// nothing is remembered.
+    pub fn prompt(&self, capability: &str, resource: &str) -> bool {
+        let Some(prompter) = &self.prompter else {
+            return false;
+        };
+        let response = prompter.prompt(capability, resource);
+        tracing::info!(
+            "Permission prompt for {} {}: {:?}",
+            capability,
+            resource,
+            response
+        );
+        self.record(capability, resource, response);
+        response != PromptResponse::Deny
+    }
+
+    /// Remember a decision
+    pub fn record(&self, capability: &str, resource: &str, response: PromptResponse) {
+        let granted_at = chrono::Utc::now().to_rfc3339();
+        if response == PromptResponse::AllowOnce {
+            self.session
+                .lock()
+                .unwrap()
+                .insert((capability.to_string(), resource.to_string()), granted_at);
+            return;
+        }
+
+        {
+            let mut persisted = self.persisted.lock().unwrap();
+            persisted.retain(|g| !(g.capability == capability && g.resource == resource));
+            persisted.push(StoredGrant {
+                capability: capability.to_string(),
+                resource: resource.to_string(),
+                allowed: response == PromptResponse::AllowAlways,
+                granted_at,
+            });
+        }
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to persist permission grant: {}", e);
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = GrantsFile {
+            version: GRANTS_VERSION,
+            grants: self.persisted.lock().unwrap().clone(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("creating {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(path, json).map_err(|e| format!("writing {}: {}", path.display(), e))
+    }
+}
+
+impl PermissionGrantStore for GrantStore {
+    fn grants(&self) -> Vec<PermissionGrant> {
+        let session = self.session.lock().unwrap();
+        let persisted = self.persisted.lock().unwrap();
+        let mut grants: Vec<_> = session
+            .iter()
+            .map(|((capability, resource), granted_at)| PermissionGrant {
+                capability: capability.clone(),
+                resource: resource.clone(),
+                allowed: true,
+                persisted: false,
+                granted_at: granted_at.clone(),
+            })
+            .chain(persisted.iter().map(|g| PermissionGrant {
+                capability: g.capability.clone(),
+                resource: g.resource.clone(),
+                allowed: g.allowed,
+                persisted: true,
+                granted_at: g.granted_at.clone(),
+            }))
+            .collect();
+        grants.sort_by(|a, b| a.granted_at.cmp(&b.granted_at));
+        grants
+    }
+
+    fn revoke(&self, capability: &str, resource: Option<&str>) -> Result<usize, String> {
+        let matches = |c: &str, r: &str| c == capability && resource.is_none_or(|res| res == r);
+
+        let mut removed = 0;
+        {
+            let mut session = self.session.lock().unwrap();
+            let before = session.len();
+            session.retain(|(c, r), _| !matches(c, r));
+            removed += before - session.len();
+        }
+        let persisted_removed = {
+            let mut persisted = self.persisted.lock().unwrap();
+            let before = persisted.len();
+            persisted.retain(|g| !matches(&g.capability, &g.resource));
+            before - persisted.len()
+        };
+        if persisted_removed > 0 {
+            self.save()?;
+        }
+        Ok(removed + persisted_removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedPrompter(PromptResponse);
+
+    impl PermissionPrompter for FixedPrompter {
+        fn prompt(&self, _capability: &str, _resource: &str) -> PromptResponse {
+            self.0
+        }
+    }
+
+    fn store(path: Option<PathBuf>, response: PromptResponse) -> GrantStore {
+        GrantStore::load(path, Some(Box::new(FixedPrompter(response)))).unwrap()
+    }
+
+    #[test]
+    fn test_allow_once_is_not_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(GRANTS_FILE);
+        let grants = store(Some(path.clone()), PromptResponse::AllowOnce);
+
+        assert_eq!(grants.lookup("fs.read", "/tmp/a"), None);
+        assert!(grants.prompt("fs.read", "/tmp/a"));
+        assert_eq!(grants.lookup("fs.read", "/tmp/a"), Some(true));
+        assert!(!path.exists());
+
+        let reloaded = store(Some(path), PromptResponse::Deny);
+        assert_eq!(reloaded.lookup("fs.read", "/tmp/a"), None);
+    }
+
+    #[test]
+    fn test_persisted_decisions_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app").join(GRANTS_FILE);
+        let grants = store(Some(path.clone()), PromptResponse::AllowAlways);
+        assert!(grants.prompt("net", "api.example.com"));
+        grants.record("process.spawn", "rm", PromptResponse::Deny);
+
+        let reloaded = store(Some(path), PromptResponse::AllowOnce);
+        assert_eq!(reloaded.lookup("net", "api.example.com"), Some(true));
+        assert_eq!(reloaded.lookup("process.spawn", "rm"), Some(false));
+        assert_eq!(reloaded.grants().len(), 2);
+    }
+
+    #[test]
+    fn test_revoke() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(GRANTS_FILE);
+        let grants = store(Some(path.clone()), PromptResponse::AllowAlways);
+        grants.record("fs.read", "/a", PromptResponse::AllowAlways);
+        grants.record("fs.read", "/b", PromptResponse::AllowOnce);
+        grants.record("net", "example.com", PromptResponse::Deny);
+
+        assert_eq!(grants.revoke("fs.read", Some("/a")).unwrap(), 1);
+        assert_eq!(grants.lookup("fs.read", "/a"), None);
+        assert_eq!(grants.revoke("fs.read", None).unwrap(), 1);
+        assert_eq!(grants.revoke("fs.read", None).unwrap(), 0);
+
+        let reloaded = store(Some(path), PromptResponse::Deny);
+        assert_eq!(reloaded.lookup("fs.read", "/a"), None);
+        assert_eq!(reloaded.lookup("net", "example.com"), Some(false));
+    }
+
+    #[test]
+    fn test_no_prompter_denies() {
+        let grants = GrantStore::load(None, None).unwrap();
+        assert!(!grants.prompt("fs.write", "/etc/passwd"));
+        assert!(grants.grants().is_empty());
+    }
+}
@@ -0,0 +1,935 @@
+//! Remote module support for the Deno-side module loader
+//!
+//! Resolves imports that don't live in the app directory:
+//!
+//! - `https://...` URLs are fetched directly (`http://` only in dev mode)
+//! - `jsr:@scope/name[@range][/export]` is resolved against the JSR registry:
+//!   `meta.json` picks the version, `<version>_meta.json` maps the export to
+//!   a file which is then fetched like any `https:` module
+//! - `npm:name[@range][/subpath]` is served as ESM through an npm CDN
+//!   (`https://esm.sh` by default)
+//!
+//! Fetched sources are stored in an on-disk [`ModuleCache`] and recorded in a
+//! [`Lockfile`] (`forge.lock` next to the manifest) with SHA-256 integrity
+//! hashes; a cached or downloaded module that doesn't match its locked hash is
+//! rejected. In offline mode nothing is downloaded and every module must
+//! already be in the cache.
+//!
+//! Every download, including each redirect hop, is checked with
+//! [`Capabilities::check_net`], so production apps can only load code from
+//! hosts allowlisted in `[permissions.net]`.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [modules]
+//! offline = false                  # or pass --offline to forge-runtime
+//! lockfile = "forge.lock"          # relative to the app directory
+//! cache_dir = "~/.cache/forge"     # default: <user cache dir>/forge/modules
+//! npm_cdn = "https://esm.sh"
+//! jsr_registry = "https://jsr.io"
+//! transpile_cache = true           # see crate::transpile
+//! ```
+
+use crate::capabilities::Capabilities;
+use deno_ast::MediaType;
+use deno_core::ModuleSpecifier;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Default lockfile name, relative to the app directory
+pub const DEFAULT_LOCKFILE: &str = "forge.lock";
+/// Default CDN serving `npm:` packages as ES modules
+pub const DEFAULT_NPM_CDN: &str = "https://esm.sh";
+/// Default JSR registry
+pub const DEFAULT_JSR_REGISTRY: &str = "https://jsr.io";
+
+/// Maximum number of redirects followed for a single module
+const MAX_REDIRECTS: usize = 10;
+
+/// `[modules]` section of manifest.app.toml
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ModulesConfig {
+    /// Resolve remote modules from the cache only
+    pub offline: Option<bool>,
+    /// Lockfile path relative to the app directory (default: forge.lock)
+    pub lockfile: Option<String>,
+    /// Module cache directory (default: <user cache dir>/forge/modules)
+    pub cache_dir: Option<String>,
+    /// Base URL of the ESM CDN serving `npm:` packages
+    pub npm_cdn: Option<String>,
+    /// Base URL of the JSR registry
+    pub jsr_registry: Option<String>,
+    /// Cache transpiled TypeScript on disk (default: true)
+    pub transpile_cache: Option<bool>,
+}
+
+/// Errors raised while resolving or fetching remote modules
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteModuleError {
+    #[error("Permission denied: net for {host} (importing {url})")]
+    Denied { host: String, url: String },
+
+    #[error("{url} is not in the module cache (offline mode)")]
+    Offline { url: String },
+
+    #[error("Integrity check failed for {url}: lockfile has {expected}, got {actual}")]
+    Integrity {
+        url: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Failed to fetch {url}: {message}")]
+    Fetch { url: String, message: String },
+
+    #[error("Cannot resolve {specifier}: {message}")]
+    Resolve { specifier: String, message: String },
+
+    #[error("Module cache error at {path}: {source}")]
+    Cache {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Returns true for specifiers handled by [`RemoteModules`]
+pub fn is_remote(specifier: &ModuleSpecifier) -> bool {
+    matches!(specifier.scheme(), "https" | "http" | "jsr" | "npm")
+}
+
+/// `sha256-<hex>` integrity string for a module source
+pub fn integrity(bytes: &[u8]) -> String {
+    format!("sha256-{:x}", Sha256::digest(bytes))
+}
+
+// ============================================================================
+// Lockfile
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct LockfileContent {
+    version: String,
+    /// `jsr:` specifiers and the module URL they resolved to
+    #[serde(default)]
+    specifiers: BTreeMap<String, String>,
+    /// Remote module URLs and their integrity hashes
+    #[serde(default)]
+    remote: BTreeMap<String, String>,
+}
+
+impl Default for LockfileContent {
+    fn default() -> Self {
+        Self {
+            version: "1".to_string(),
+            specifiers: BTreeMap::new(),
+            remote: BTreeMap::new(),
+        }
+    }
+}
+
+/// Lockfile pinning remote module contents and `jsr:` resolutions
+///
+/// New entries are written back immediately. Failing to write (e.g. a
+/// read-only install directory) is logged and doesn't fail the import.
+pub struct Lockfile {
+    path: PathBuf,
+    content: Mutex<LockfileContent>,
+}
+
+impl Lockfile {
+    /// Load a lockfile, starting empty if it doesn't exist yet
+    pub fn load(path: PathBuf) -> Result<Self, RemoteModuleError> {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| RemoteModuleError::Cache {
+                path: path.clone(),
+                source: std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => LockfileContent::default(),
+            Err(source) => return Err(RemoteModuleError::Cache { path, source }),
+        };
+        Ok(Self {
+            path,
+            content: Mutex::new(content),
+        })
+    }
+
+    /// URL a locked `jsr:` specifier resolved to
+    pub fn resolved(&self, specifier: &str) -> Option<String> {
+        self.content
+            .lock()
+            .unwrap()
+            .specifiers
+            .get(specifier)
+            .cloned()
+    }
+
+    /// Record the URL a `jsr:` specifier resolved to
+    pub fn record_resolution(&self, specifier: &str, url: &str) {
+        let mut content = self.content.lock().unwrap();
+        if content.specifiers.get(specifier).map(String::as_str) != Some(url) {
+            content
+                .specifiers
+                .insert(specifier.to_string(), url.to_string());
+            self.save(&content);
+        }
+    }
+
+    /// Verify `bytes` against the locked hash for `url`, locking it if new
+    pub fn check_integrity(&self, url: &str, bytes: &[u8]) -> Result<(), RemoteModuleError> {
+        let actual = integrity(bytes);
+        let mut content = self.content.lock().unwrap();
+        match content.remote.get(url) {
+            Some(expected) if *expected == actual => Ok(()),
+            Some(expected) => Err(RemoteModuleError::Integrity {
+                url: url.to_string(),
+                expected: expected.clone(),
+                actual,
+            }),
+            None => {
+                content.remote.insert(url.to_string(), actual);
+                self.save(&content);
+                Ok(())
+            }
+        }
+    }
+
+    fn save(&self, content: &LockfileContent) {
+        let result = serde_json::to_string_pretty(content)
+            .map_err(std::io::Error::other)
+            .and_then(|text| std::fs::write(&self.path, text + "\n"));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write lockfile {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+// ============================================================================
+// Module cache
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+struct CacheMetadata {
+    /// Final URL after redirects
+    url: String,
+    content_type: Option<String>,
+}
+
+/// A module read from the cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedModule {
+    /// Final URL after redirects
+    pub url: ModuleSpecifier,
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+/// On-disk cache of remote module sources
+///
+/// Entries live at `<root>/<scheme>/<host>/<sha256(url)>` with a
+/// `.metadata.json` sidecar recording the final URL and content type.
+pub struct ModuleCache {
+    root: PathBuf,
+}
+
+impl ModuleCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Default cache location: `<user cache dir>/forge/modules`
+    pub fn default_root() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("forge")
+            .join("modules")
+    }
+
+    fn entry_path(&self, url: &ModuleSpecifier) -> PathBuf {
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}_PORT{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => "_".to_string(),
+        };
+        self.root
+            .join(url.scheme())
+            .join(host)
+            .join(format!("{:x}", Sha256::digest(url.as_str().as_bytes())))
+    }
+
+    /// Read a cached module
+    pub fn get(&self, url: &ModuleSpecifier) -> Option<CachedModule> {
+        let path = self.entry_path(url);
+        let bytes = std::fs::read(&path).ok()?;
+        let metadata = std::fs::read_to_string(path.with_extension("metadata.json")).ok()?;
+        let metadata: CacheMetadata = serde_json::from_str(&metadata).ok()?;
+        Some(CachedModule {
+            url: ModuleSpecifier::parse(&metadata.url).ok()?,
+            content_type: metadata.content_type,
+            bytes,
+        })
+    }
+
+    /// Store a module under the URL it was requested as
+    pub fn put(
+        &self,
+        url: &ModuleSpecifier,
+        final_url: &ModuleSpecifier,
+        content_type: Option<&str>,
+        bytes: &[u8],
+    ) -> Result<(), RemoteModuleError> {
+        let path = self.entry_path(url);
+        let metadata = CacheMetadata {
+            url: final_url.to_string(),
+            content_type: content_type.map(str::to_string),
+        };
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, bytes)?;
+            std::fs::write(
+                path.with_extension("metadata.json"),
+                serde_json::to_vec(&metadata).map_err(std::io::Error::other)?,
+            )
+        };
+        write().map_err(|source| RemoteModuleError::Cache {
+            path: path.clone(),
+            source,
+        })
+    }
+}
+
+// ============================================================================
+// Resolution
+// ============================================================================
+
+/// Parsed `jsr:@scope/name[@range][/export]` specifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsrSpecifier {
+    /// `@scope/name`
+    pub name: String,
+    pub range: Option<String>,
+    /// Export path without leading `./` (`None` = package root)
+    pub subpath: Option<String>,
+}
+
+impl JsrSpecifier {
+    pub fn parse(specifier: &ModuleSpecifier) -> Option<Self> {
+        let rest = specifier.path().trim_start_matches('/');
+        let rest = rest.strip_prefix('@')?;
+        let (scope, rest) = rest.split_once('/')?;
+        let (package, subpath) = match rest.split_once('/') {
+            Some((package, subpath)) => (package, Some(subpath.to_string())),
+            None => (rest, None),
+        };
+        let (package, range) = match package.split_once('@') {
+            Some((package, range)) => (package, Some(range.to_string())),
+            None => (package, None),
+        };
+        if scope.is_empty() || package.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: format!("@{}/{}", scope, package),
+            range,
+            subpath: subpath.filter(|s| !s.is_empty()),
+        })
+    }
+
+    /// Key into a version's `exports` map
+    fn export_key(&self) -> String {
+        match &self.subpath {
+            Some(subpath) => format!("./{}", subpath),
+            None => ".".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsrPackageMeta {
+    latest: Option<String>,
+    versions: BTreeMap<String, JsrVersionInfo>,
+}
+
+#[derive(Deserialize, Default)]
+struct JsrVersionInfo {
+    #[serde(default)]
+    yanked: bool,
+}
+
+#[derive(Deserialize)]
+struct JsrVersionMeta {
+    exports: BTreeMap<String, String>,
+}
+
+/// Pick the highest non-yanked version satisfying `range`
+///
+/// A bare version (`1.2.3`) matches exactly, as in npm/JSR; anything else
+/// is parsed as a semver requirement (`^1.2`, `~1.0.4`, `>=2`). Yanked
+/// versions are never picked, not even on an exact match: the only way to
+/// keep using one is a resolution already pinned in the lockfile, which is
+/// consulted before the registry.
+fn select_version(meta: &JsrPackageMeta, range: Option<&str>) -> Option<String> {
+    let Some(range) = range else {
+        return meta.latest.clone();
+    };
+    if let Ok(exact) = semver::Version::parse(range) {
+        let exact = exact.to_string();
+        return meta
+            .versions
+            .get(&exact)
+            .is_some_and(|info| !info.yanked)
+            .then_some(exact);
+    }
+    let req = semver::VersionReq::parse(range).ok()?;
+    meta.versions
+        .iter()
+        .filter(|(_, info)| !info.yanked)
+        .filter_map(|(version, _)| semver::Version::parse(version).ok())
+        .filter(|version| req.matches(version))
+        .max()
+        .map(|version| version.to_string())
+}
+
+/// Map a `Content-Type` header to a media type
+fn media_type_from_content_type(content_type: &str) -> Option<MediaType> {
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "application/typescript"
+        | "text/typescript"
+        | "application/x-typescript"
+        | "video/vnd.dlna.mpeg-tts"
+        | "video/mp2t" => Some(MediaType::TypeScript),
+        "application/javascript"
+        | "text/javascript"
+        | "application/ecmascript"
+        | "text/ecmascript"
+        | "application/x-javascript"
+        | "application/node" => Some(MediaType::JavaScript),
+        "text/jsx" => Some(MediaType::Jsx),
+        "text/tsx" => Some(MediaType::Tsx),
+        "application/json" | "text/json" => Some(MediaType::Json),
+        _ => None,
+    }
+}
+
+/// Media type of a remote module from its URL and `Content-Type`
+///
+/// A recognised content type wins, except that a generic TypeScript type
+/// doesn't override a more specific `.tsx`/`.mts`/`.cts` extension.
+pub fn remote_media_type(url: &ModuleSpecifier, content_type: Option<&str>) -> MediaType {
+    let from_path = MediaType::from_specifier(url);
+    match (
+        content_type.and_then(media_type_from_content_type),
+        from_path,
+    ) {
+        (
+            Some(MediaType::TypeScript),
+            path @ (MediaType::Tsx | MediaType::Mts | MediaType::Cts),
+        ) => path,
+        (Some(media_type), _) => media_type,
+        (None, path) => path,
+    }
+}
+
+// ============================================================================
+// Loader
+// ============================================================================
+
+/// A fetched remote module
+#[derive(Debug, Clone)]
+pub struct RemoteModule {
+    /// URL the source was finally served from (after `jsr:`/`npm:`
+    /// resolution and redirects); relative imports resolve against it
+    pub url: ModuleSpecifier,
+    pub media_type: MediaType,
+    pub code: String,
+}
+
+/// Resolves and fetches `https:`, `jsr:` and `npm:` modules
+pub struct RemoteModules {
+    capabilities: Capabilities,
+    cache: ModuleCache,
+    lockfile: Lockfile,
+    offline: bool,
+    npm_cdn: ModuleSpecifier,
+    jsr_registry: ModuleSpecifier,
+    client: reqwest::Client,
+}
+
+impl RemoteModules {
+    /// Create the remote loader for an app
+    ///
+    /// `offline` forces offline mode regardless of the manifest.
+    pub fn new(
+        app_dir: &Path,
+        config: Option<&ModulesConfig>,
+        offline: bool,
+        capabilities: Capabilities,
+    ) -> Result<Self, RemoteModuleError> {
+        let config = config.cloned().unwrap_or_default();
+        let base_url = |value: Option<String>, default: &str| {
+            let mut value = value.unwrap_or_else(|| default.to_string());
+            if !value.ends_with('/') {
+                value.push('/');
+            }
+            ModuleSpecifier::parse(&value).map_err(|e| RemoteModuleError::Resolve {
+                specifier: value.clone(),
+                message: e.to_string(),
+            })
+        };
+
+        // reqwest runs the redirect policy synchronously on whichever thread
+        // polls the request, not necessarily the JS runtime thread. That's
+        // fine for `check_net`: the manifest patterns and grant store are
+        // shared behind `Arc`s, and with `prompt = true` a redirect to a new
+        // host blocks on the same permission dialog as the first request
+        // would, before anything is fetched from that host.
+        let redirect_caps = capabilities.clone();
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("forge-runtime/", env!("CARGO_PKG_VERSION")))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error("too many redirects");
+                }
+                match attempt.url().host_str() {
+                    Some(host) if redirect_caps.check_net(host).is_ok() => attempt.follow(),
+                    _ => {
+                        let message = format!("redirect to disallowed host: {}", attempt.url());
+                        attempt.error(message)
+                    }
+                }
+            }))
+            .build()
+            .map_err(|e| RemoteModuleError::Fetch {
+                url: String::new(),
+                message: e.to_string(),
+            })?;
+
+        Ok(Self {
+            cache: ModuleCache::new(
+                config
+                    .cache_dir
+                    .map(PathBuf::from)
+                    .unwrap_or_else(ModuleCache::default_root),
+            ),
+            lockfile: Lockfile::load(
+                app_dir.join(config.lockfile.as_deref().unwrap_or(DEFAULT_LOCKFILE)),
+            )?,
+            offline: offline || config.offline.unwrap_or(false),
+            npm_cdn: base_url(config.npm_cdn, DEFAULT_NPM_CDN)?,
+            jsr_registry: base_url(config.jsr_registry, DEFAULT_JSR_REGISTRY)?,
+            capabilities,
+            client,
+        })
+    }
+
+    /// Resolve and fetch a remote module
+    pub async fn load(
+        &self,
+        specifier: &ModuleSpecifier,
+    ) -> Result<RemoteModule, RemoteModuleError> {
+        let url = match specifier.scheme() {
+            "jsr" => self.resolve_jsr(specifier).await?,
+            "npm" => self.resolve_npm(specifier)?,
+            "http" if !self.capabilities.dev_mode => {
+                return Err(RemoteModuleError::Resolve {
+                    specifier: specifier.to_string(),
+                    message: "http: imports are only allowed in dev mode; use https:".into(),
+                })
+            }
+            _ => specifier.clone(),
+        };
+
+        let module = self.fetch(&url, true).await?;
+        let media_type = remote_media_type(&module.url, module.content_type.as_deref());
+        let code = String::from_utf8(module.bytes).map_err(|_| RemoteModuleError::Fetch {
+            url: url.to_string(),
+            message: "module source is not valid UTF-8".into(),
+        })?;
+
+        Ok(RemoteModule {
+            url: module.url,
+            media_type,
+            code,
+        })
+    }
+
+    /// Map `npm:name@range/subpath` onto the npm CDN
+    fn resolve_npm(
+        &self,
+        specifier: &ModuleSpecifier,
+    ) -> Result<ModuleSpecifier, RemoteModuleError> {
+        let package = specifier.path().trim_start_matches('/');
+        if package.is_empty() {
+            return Err(RemoteModuleError::Resolve {
+                specifier: specifier.to_string(),
+                message: "missing package name".into(),
+            });
+        }
+        self.npm_cdn
+            .join(package)
+            .map_err(|e| RemoteModuleError::Resolve {
+                specifier: specifier.to_string(),
+                message: e.to_string(),
+            })
+    }
+
+    /// Resolve `jsr:@scope/name@range/export` to a module URL on the registry
+    async fn resolve_jsr(
+        &self,
+        specifier: &ModuleSpecifier,
+    ) -> Result<ModuleSpecifier, RemoteModuleError> {
+        let resolve_error = |message: String| RemoteModuleError::Resolve {
+            specifier: specifier.to_string(),
+            message,
+        };
+
+        if let Some(url) = self.lockfile.resolved(specifier.as_str()) {
+            return ModuleSpecifier::parse(&url).map_err(|e| resolve_error(e.to_string()));
+        }
+
+        let jsr = JsrSpecifier::parse(specifier)
+            .ok_or_else(|| resolve_error("expected jsr:@scope/name[@version][/path]".into()))?;
+        let package_url = |path: &str| {
+            self.jsr_registry
+                .join(&format!("{}/{}", jsr.name, path))
+                .map_err(|e| resolve_error(e.to_string()))
+        };
+
+        let meta = self.fetch(&package_url("meta.json")?, false).await?;
+        let meta: JsrPackageMeta =
+            serde_json::from_slice(&meta.bytes).map_err(|e| resolve_error(e.to_string()))?;
+        let version = select_version(&meta, jsr.range.as_deref()).ok_or_else(|| {
+            let range = jsr.range.as_deref().unwrap_or("latest");
+            let yanked = meta.versions.get(range).is_some_and(|info| info.yanked);
+            resolve_error(if yanked {
+                format!("{}@{} has been yanked", jsr.name, range)
+            } else {
+                format!("no version of {} matches {}", jsr.name, range)
+            })
+        })?;
+
+        let version_meta = self
+            .fetch(&package_url(&format!("{}_meta.json", version))?, false)
+            .await?;
+        let version_meta: JsrVersionMeta = serde_json::from_slice(&version_meta.bytes)
+            .map_err(|e| resolve_error(e.to_string()))?;
+        let export = version_meta.exports.get(&jsr.export_key()).ok_or_else(|| {
+            resolve_error(format!(
+                "{}@{} has no export '{}'",
+                jsr.name,
+                version,
+                jsr.export_key()
+            ))
+        })?;
+
+        let url = package_url(&format!("{}/{}", version, export.trim_start_matches("./")))?;
+        self.lockfile
+            .record_resolution(specifier.as_str(), url.as_str());
+        Ok(url)
+    }
+
+    /// Fetch a URL through the cache
+    ///
+    /// Module sources (`locked`) are served from the cache when present and
+    /// verified against the lockfile. Registry metadata changes over time, so
+    /// it is only read from the cache in offline mode.
+    async fn fetch(
+        &self,
+        url: &ModuleSpecifier,
+        locked: bool,
+    ) -> Result<CachedModule, RemoteModuleError> {
+        let cached = if locked || self.offline {
+            self.cache.get(url)
+        } else {
+            None
+        };
+
+        let module = match cached {
+            Some(module) => module,
+            None if self.offline => {
+                return Err(RemoteModuleError::Offline {
+                    url: url.to_string(),
+                })
+            }
+            None => {
+                let module = self.download(url).await?;
+                self.cache.put(
+                    url,
+                    &module.url,
+                    module.content_type.as_deref(),
+                    &module.bytes,
+                )?;
+                module
+            }
+        };
+
+        if locked {
+            self.lockfile.check_integrity(url.as_str(), &module.bytes)?;
+        }
+        Ok(module)
+    }
+
+    async fn download(&self, url: &ModuleSpecifier) -> Result<CachedModule, RemoteModuleError> {
+        let fetch_error = |message: String| RemoteModuleError::Fetch {
+            url: url.to_string(),
+            message,
+        };
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| fetch_error("URL has no host".into()))?;
+        self.capabilities
+            .check_net(host)
+            .map_err(|_| RemoteModuleError::Denied {
+                host: host.to_string(),
+                url: url.to_string(),
+            })?;
+
+        tracing::info!("Downloading {}", url);
+        let response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| fetch_error(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(fetch_error(format!("HTTP {}", response.status())));
+        }
+
+        let final_url = response.url().clone();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| fetch_error(e.to_string()))?;
+
+        Ok(CachedModule {
+            url: final_url,
+            content_type,
+            bytes: bytes.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> ModuleSpecifier {
+        ModuleSpecifier::parse(s).unwrap()
+    }
+
+    fn remote_modules(dir: &Path, offline: bool, dev_mode: bool) -> RemoteModules {
+        let config = ModulesConfig {
+            cache_dir: Some(dir.join("cache").to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        RemoteModules::new(
+            dir,
+            Some(&config),
+            offline,
+            Capabilities::from_permissions(None, dev_mode).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_is_remote() {
+        assert!(is_remote(&url("https://deno.land/x/mod.ts")));
+        assert!(is_remote(&url("jsr:@std/path@1")));
+        assert!(is_remote(&url("npm:preact@10")));
+        assert!(!is_remote(&url("file:///app/src/main.ts")));
+        assert!(!is_remote(&url("ext:runtime_fs/init.js")));
+    }
+
+    #[test]
+    fn test_parse_jsr_specifier() {
+        assert_eq!(
+            JsrSpecifier::parse(&url("jsr:@std/path@^1.0.0/join")),
+            Some(JsrSpecifier {
+                name: "@std/path".into(),
+                range: Some("^1.0.0".into()),
+                subpath: Some("join".into()),
+            })
+        );
+        assert_eq!(
+            JsrSpecifier::parse(&url("jsr:@luca/flag")),
+            Some(JsrSpecifier {
+                name: "@luca/flag".into(),
+                range: None,
+                subpath: None,
+            })
+        );
+        assert_eq!(JsrSpecifier::parse(&url("jsr:path@1")), None);
+    }
+
+    #[test]
+    fn test_select_version() {
+        let meta: JsrPackageMeta = serde_json::from_str(
+            r#"{
+                "latest": "1.1.0",
+                "versions": {
+                    "0.9.0": {},
+                    "1.0.0": {},
+                    "1.1.0": {},
+                    "1.2.0": { "yanked": true },
+                    "2.0.0-rc.1": {}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(select_version(&meta, None).as_deref(), Some("1.1.0"));
+        assert_eq!(
+            select_version(&meta, Some("^1.0")).as_deref(),
+            Some("1.1.0")
+        );
+        assert_eq!(
+            select_version(&meta, Some("1.0.0")).as_deref(),
+            Some("1.0.0")
+        );
+        assert_eq!(
+            select_version(&meta, Some("~0.9")).as_deref(),
+            Some("0.9.0")
+        );
+        assert_eq!(select_version(&meta, Some("^3")), None);
+        // Yanked versions aren't picked, even when named exactly
+        assert_eq!(
+            select_version(&meta, Some(">=1.1")).as_deref(),
+            Some("1.1.0")
+        );
+        assert_eq!(select_version(&meta, Some("1.2.0")), None);
+    }
+
+    #[test]
+    fn test_remote_media_type() {
+        assert_eq!(
+            remote_media_type(
+                &url("https://esm.sh/preact@10"),
+                Some("application/javascript; charset=utf-8")
+            ),
+            MediaType::JavaScript
+        );
+        assert_eq!(
+            remote_media_type(&url("https://jsr.io/@std/path/1.0.8/join.ts"), None),
+            MediaType::TypeScript
+        );
+        assert_eq!(
+            remote_media_type(
+                &url("https://example.com/app.tsx"),
+                Some("application/typescript")
+            ),
+            MediaType::Tsx
+        );
+    }
+
+    #[test]
+    fn test_resolve_npm() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = remote_modules(dir.path(), true, false);
+        assert_eq!(
+            remote
+                .resolve_npm(&url("npm:@preact/signals@1.2/core"))
+                .unwrap(),
+            url("https://esm.sh/@preact/signals@1.2/core")
+        );
+        assert!(remote.resolve_npm(&url("npm:")).is_err());
+    }
+
+    #[test]
+    fn test_lockfile_integrity() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("forge.lock");
+
+        let lockfile = Lockfile::load(path.clone()).unwrap();
+        lockfile
+            .check_integrity("https://example.com/a.ts", b"export const a = 1;")
+            .unwrap();
+        lockfile.record_resolution("jsr:@std/path@1", "https://jsr.io/@std/path/1.0.8/mod.ts");
+
+        // Reload from disk: entries persisted
+        let lockfile = Lockfile::load(path).unwrap();
+        assert_eq!(
+            lockfile.resolved("jsr:@std/path@1").as_deref(),
+            Some("https://jsr.io/@std/path/1.0.8/mod.ts")
+        );
+        assert!(lockfile
+            .check_integrity("https://example.com/a.ts", b"export const a = 1;")
+            .is_ok());
+        assert!(matches!(
+            lockfile.check_integrity("https://example.com/a.ts", b"export const a = 2;"),
+            Err(RemoteModuleError::Integrity { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ModuleCache::new(dir.path().to_path_buf());
+        let requested = url("https://esm.sh/preact@10");
+        let served = url("https://esm.sh/preact@10.19.3/es2022/preact.mjs");
+
+        assert!(cache.get(&requested).is_none());
+        cache
+            .put(
+                &requested,
+                &served,
+                Some("application/javascript"),
+                b"export {}",
+            )
+            .unwrap();
+        assert_eq!(
+            cache.get(&requested),
+            Some(CachedModule {
+                url: served,
+                content_type: Some("application/javascript".into()),
+                bytes: b"export {}".to_vec(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_offline_serves_cache_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = remote_modules(dir.path(), true, false);
+        let cached = url("https://example.com/cached.ts");
+        remote
+            .cache
+            .put(&cached, &cached, None, b"export const x: number = 1;")
+            .unwrap();
+
+        let module = remote.load(&cached).await.unwrap();
+        assert_eq!(module.media_type, MediaType::TypeScript);
+        assert_eq!(module.code, "export const x: number = 1;");
+
+        assert!(matches!(
+            remote.load(&url("https://example.com/missing.ts")).await,
+            Err(RemoteModuleError::Offline { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_download_requires_net_permission() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = remote_modules(dir.path(), false, false);
+        assert!(matches!(
+            remote.load(&url("https://example.com/mod.ts")).await,
+            Err(RemoteModuleError::Denied { .. })
+        ));
+        assert!(matches!(
+            remote.load(&url("http://example.com/mod.ts")).await,
+            Err(RemoteModuleError::Resolve { .. })
+        ));
+    }
+}
@@ -0,0 +1,282 @@
+//! TypeScript/JSX transpilation for the Deno-side module loader
+//!
+//! Every transpiled module carries an inline source map
+//! (`//# sourceMappingURL=data:...`). The [`Transpiler`] also keeps the
+//! decoded map per specifier so `ForgeModuleLoader::get_source_map` can hand
+//! it to V8: stack traces and `ext_debugger` breakpoints then point at the
+//! original TypeScript instead of the emitted JavaScript.
+//!
+//! Transpiled output is stored in an on-disk [`TranspileCache`] keyed by a
+//! SHA-256 of the runtime version, specifier, media type and source text.
+//! Editing a module or upgrading the runtime produces a new key, so stale
+//! entries are never served.
+//!
+//! # Configuration
+//!
+//! ```toml
+//! [modules]
+//! transpile_cache = true   # default; false always re-transpiles
+//! ```
+
+use base64::Engine;
+use deno_ast::{MediaType, ParseParams};
+use deno_core::error::ModuleLoaderError;
+use deno_core::{ModuleSpecifier, ModuleType};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Runtime version mixed into every cache key
+pub const RUNTIME_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Prefix of an inline source map comment emitted by deno_ast
+const INLINE_SOURCE_MAP_PREFIX: &str = "//# sourceMappingURL=data:application/json;base64,";
+
+/// On-disk cache of transpiled module sources
+///
+/// Entries live at `<root>/<runtime version>/<sha256>.js`. Writes go through
+/// a temporary file and a rename so concurrent runtimes never read a
+/// partially written entry.
+pub struct TranspileCache {
+    root: PathBuf,
+}
+
+impl TranspileCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Default cache location: `<user cache dir>/forge/transpile`
+    pub fn default_root() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("forge")
+            .join("transpile")
+    }
+
+    /// Cache key for a module's source text
+    pub fn key(specifier: &ModuleSpecifier, media_type: MediaType, source: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            RUNTIME_VERSION,
+            specifier.as_str(),
+            &format!("{:?}", media_type),
+            source,
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.root.join(RUNTIME_VERSION).join(format!("{}.js", key))
+    }
+
+    /// Read a cached transpilation
+    pub fn get(&self, key: &str) -> Option<String> {
+        std::fs::read_to_string(self.entry_path(key)).ok()
+    }
+
+    /// Store a transpilation; failures only cost a re-transpile next launch
+    pub fn put(&self, key: &str, code: &str) {
+        let path = self.entry_path(key);
+        let tmp = path.with_extension(format!("js.{}.tmp", std::process::id()));
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&tmp, code)?;
+            std::fs::rename(&tmp, &path)
+        };
+        if let Err(e) = write() {
+            let _ = std::fs::remove_file(&tmp);
+            tracing::debug!("Failed to write transpile cache {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Transpiles modules and remembers their source maps
+pub struct Transpiler {
+    cache: Option<TranspileCache>,
+    source_maps: RefCell<HashMap<String, Vec<u8>>>,
+}
+
+impl Transpiler {
+    pub fn new(cache: Option<TranspileCache>) -> Self {
+        Self {
+            cache,
+            source_maps: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Determine how deno_core evaluates a module and transpile it if needed
+    pub fn transpile(
+        &self,
+        specifier: &ModuleSpecifier,
+        media_type: MediaType,
+        code: String,
+    ) -> Result<(ModuleType, String), ModuleLoaderError> {
+        let (module_type, should_transpile) = match media_type {
+            MediaType::JavaScript | MediaType::Mjs | MediaType::Cjs => {
+                (ModuleType::JavaScript, false)
+            }
+            MediaType::Jsx => (ModuleType::JavaScript, true),
+            MediaType::TypeScript
+            | MediaType::Mts
+            | MediaType::Cts
+            | MediaType::Dts
+            | MediaType::Dmts
+            | MediaType::Dcts
+            | MediaType::Tsx => (ModuleType::JavaScript, true),
+            MediaType::Json => (ModuleType::Json, false),
+            _ => {
+                return Err(ModuleLoaderError::generic(format!(
+                    "Unknown module type for {}",
+                    specifier
+                )));
+            }
+        };
+
+        if !should_transpile {
+            if module_type == ModuleType::JavaScript {
+                self.register_source_map(specifier, &code);
+            }
+            return Ok((module_type, code));
+        }
+
+        let key = self
+            .cache
+            .as_ref()
+            .map(|_| TranspileCache::key(specifier, media_type, &code));
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            if let Some(cached) = cache.get(key) {
+                self.register_source_map(specifier, &cached);
+                return Ok((module_type, cached));
+            }
+        }
+
+        let parsed = deno_ast::parse_module(ParseParams {
+            specifier: specifier.clone(),
+            text: code.into(),
+            media_type,
+            capture_tokens: false,
+            scope_analysis: false,
+            maybe_syntax: None,
+        })
+        .map_err(|e| ModuleLoaderError::generic(e.to_string()))?;
+
+        let transpiled = parsed
+            .transpile(
+                &deno_ast::TranspileOptions::default(),
+                &deno_ast::TranspileModuleOptions::default(),
+                &deno_ast::EmitOptions {
+                    source_map: deno_ast::SourceMapOption::Inline,
+                    inline_sources: true,
+                    ..Default::default()
+                },
+            )
+            .map_err(|e| ModuleLoaderError::generic(e.to_string()))?;
+        let code = transpiled.into_source().text;
+
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            cache.put(key, &code);
+        }
+        self.register_source_map(specifier, &code);
+        Ok((module_type, code))
+    }
+
+    /// Remember the inline source map of an already-emitted module, if any
+    pub fn register_source_map(&self, specifier: &ModuleSpecifier, code: &str) {
+        if let Some(map) = inline_source_map(code) {
+            self.source_maps
+                .borrow_mut()
+                .insert(specifier.to_string(), map);
+        }
+    }
+
+    /// Source map for a loaded module, as requested by V8
+    pub fn source_map(&self, specifier: &str) -> Option<Vec<u8>> {
+        self.source_maps.borrow().get(specifier).cloned()
+    }
+}
+
+/// Decode the trailing inline source map comment of emitted JavaScript
+pub fn inline_source_map(code: &str) -> Option<Vec<u8>> {
+    let comment = code.trim_end().lines().next_back()?;
+    let encoded = comment.strip_prefix(INLINE_SOURCE_MAP_PREFIX)?;
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specifier() -> ModuleSpecifier {
+        ModuleSpecifier::parse("file:///app/src/main.ts").unwrap()
+    }
+
+    #[test]
+    fn test_cache_key_changes_with_source() {
+        let a = TranspileCache::key(&specifier(), MediaType::TypeScript, "let a: number = 1;");
+        let b = TranspileCache::key(&specifier(), MediaType::TypeScript, "let a: number = 2;");
+        assert_ne!(a, b);
+        assert_eq!(
+            a,
+            TranspileCache::key(&specifier(), MediaType::TypeScript, "let a: number = 1;")
+        );
+    }
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TranspileCache::new(dir.path().to_path_buf());
+        let key = TranspileCache::key(&specifier(), MediaType::TypeScript, "x");
+
+        assert_eq!(cache.get(&key), None);
+        cache.put(&key, "export {};");
+        assert_eq!(cache.get(&key).as_deref(), Some("export {};"));
+        assert!(dir
+            .path()
+            .join(RUNTIME_VERSION)
+            .join(format!("{}.js", key))
+            .exists());
+    }
+
+    #[test]
+    fn test_inline_source_map() {
+        let map = br#"{"version":3,"sources":["main.ts"],"mappings":""}"#;
+        let code = format!(
+            "console.log(1);\n{}{}\n",
+            INLINE_SOURCE_MAP_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(map)
+        );
+        assert_eq!(inline_source_map(&code).as_deref(), Some(&map[..]));
+        assert_eq!(inline_source_map("console.log(1);"), None);
+    }
+
+    #[test]
+    fn test_transpile_registers_source_map_and_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        let transpiler = Transpiler::new(Some(TranspileCache::new(dir.path().to_path_buf())));
+        let source = "const answer: number = 42;\nthrow new Error(String(answer));\n";
+
+        let (module_type, code) = transpiler
+            .transpile(&specifier(), MediaType::TypeScript, source.to_string())
+            .unwrap();
+        assert_eq!(module_type, ModuleType::JavaScript);
+        assert!(!code.contains(": number"));
+        assert!(transpiler.source_map(specifier().as_str()).is_some());
+
+        let key = TranspileCache::key(&specifier(), MediaType::TypeScript, source);
+        assert_eq!(
+            TranspileCache::new(dir.path().to_path_buf())
+                .get(&key)
+                .as_deref(),
+            Some(code.as_str())
+        );
+    }
+}
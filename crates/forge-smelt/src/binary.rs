@@ -0,0 +1,504 @@
+//! Standalone binary layout
+//!
+//! A compiled app is a copy of `forge-runtime` with the app payload appended:
+//!
+//! ```text
+//! ┌────────────────────────┐
+//! │  forge-runtime binary  │  unchanged executable image
+//! ├────────────────────────┤
+//! │  header (JSON)         │  manifest, entry point, module/asset index
+//! ├────────────────────────┤
+//! │  data                  │  module sources and asset bytes
+//! ├────────────────────────┤
+//! │  trailer (24 bytes)    │  header len (u64 LE), data len (u64 LE), magic
+//! └────────────────────────┘
+//! ```
+//!
+//! The trailer sits at the very end of the file so the runtime can locate the
+//! payload by seeking from the end of its own executable without knowing the
+//! size of the original binary. Signed binaries are the exception: a macOS
+//! code signature or a Windows certificate table follows the payload, and the
+//! trailer ends where the signature starts.
+
+use crate::{macho, pe};
+use crate::{Result, SmeltError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Magic bytes terminating every standalone binary
+pub const PAYLOAD_MAGIC: &[u8; 8] = b"FRGSMELT";
+
+/// Payload format version; bumped on incompatible layout changes
+pub const PAYLOAD_VERSION: u32 = 1;
+
+/// Size of the fixed trailer: two u64 lengths plus the magic
+const TRAILER_LEN: u64 = 8 + 8 + 8;
+
+/// How an embedded module is evaluated by the runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleKind {
+    /// ES module source (already transpiled)
+    JavaScript,
+    /// JSON module
+    Json,
+}
+
+/// A module stored in the payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedModule {
+    pub kind: ModuleKind,
+    pub code: String,
+}
+
+/// Everything embedded into a standalone binary
+///
+/// Module and asset keys are `/`-separated paths relative to the app
+/// directory (`src/main.ts`) and the `web/` directory (`index.html`)
+/// respectively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Payload {
+    /// Raw contents of `manifest.app.toml`
+    pub manifest: String,
+    /// Path of the entry module, relative to the app directory
+    pub entry: String,
+    /// Import map JSON with `./` entries relative to the app directory
+    pub import_map: Option<String>,
+    /// Transpiled modules keyed by app-relative path
+    pub modules: BTreeMap<String, EmbeddedModule>,
+    /// Web assets keyed by path relative to `web/`
+    pub assets: BTreeMap<String, Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Span {
+    offset: u64,
+    len: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ModuleEntry {
+    kind: ModuleKind,
+    #[serde(flatten)]
+    span: Span,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    manifest: String,
+    entry: String,
+    #[serde(default)]
+    import_map: Option<String>,
+    modules: BTreeMap<String, ModuleEntry>,
+    assets: BTreeMap<String, Span>,
+}
+
+impl Payload {
+    /// Look up an embedded module by app-relative path
+    pub fn module(&self, path: &str) -> Option<&EmbeddedModule> {
+        self.modules.get(path)
+    }
+
+    /// Look up an embedded web asset by path relative to `web/`
+    pub fn asset(&self, path: &str) -> Option<&[u8]> {
+        self.assets
+            .get(path.trim_start_matches('/'))
+            .map(Vec::as_slice)
+    }
+
+    /// Serialize the payload (header, data and trailer)
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut push = |bytes: &[u8]| {
+            let span = Span {
+                offset: data.len() as u64,
+                len: bytes.len() as u64,
+            };
+            data.extend_from_slice(bytes);
+            span
+        };
+
+        let modules = self
+            .modules
+            .iter()
+            .map(|(path, module)| {
+                let entry = ModuleEntry {
+                    kind: module.kind,
+                    span: push(module.code.as_bytes()),
+                };
+                (path.clone(), entry)
+            })
+            .collect();
+        let assets = self
+            .assets
+            .iter()
+            .map(|(path, bytes)| (path.clone(), push(bytes)))
+            .collect();
+
+        let header = Header {
+            version: PAYLOAD_VERSION,
+            manifest: self.manifest.clone(),
+            entry: self.entry.clone(),
+            import_map: self.import_map.clone(),
+            modules,
+            assets,
+        };
+        let header = serde_json::to_vec(&header)
+            .map_err(|e| SmeltError::InvalidPayload(format!("encoding header: {}", e)))?;
+
+        let mut out = Vec::with_capacity(header.len() + data.len() + TRAILER_LEN as usize);
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&data);
+        out.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        out.extend_from_slice(PAYLOAD_MAGIC);
+        Ok(out)
+    }
+
+    /// Decode a payload from its header and data sections
+    fn from_sections(header: &[u8], data: &[u8]) -> Result<Self> {
+        let header: Header = serde_json::from_slice(header)
+            .map_err(|e| SmeltError::InvalidPayload(format!("decoding header: {}", e)))?;
+        if header.version != PAYLOAD_VERSION {
+            return Err(SmeltError::InvalidPayload(format!(
+                "unsupported payload version {} (expected {})",
+                header.version, PAYLOAD_VERSION
+            )));
+        }
+
+        let slice = |span: &Span| -> Result<&[u8]> {
+            let start = span.offset as usize;
+            let end = start.checked_add(span.len as usize);
+            end.and_then(|end| data.get(start..end))
+                .ok_or_else(|| SmeltError::InvalidPayload("entry out of bounds".to_string()))
+        };
+
+        let mut modules = BTreeMap::new();
+        for (path, entry) in &header.modules {
+            let code = String::from_utf8(slice(&entry.span)?.to_vec()).map_err(|_| {
+                SmeltError::InvalidPayload(format!("module {} is not valid UTF-8", path))
+            })?;
+            modules.insert(
+                path.clone(),
+                EmbeddedModule {
+                    kind: entry.kind,
+                    code,
+                },
+            );
+        }
+
+        let mut assets = BTreeMap::new();
+        for (path, span) in &header.assets {
+            assets.insert(path.clone(), slice(span)?.to_vec());
+        }
+
+        Ok(Self {
+            manifest: header.manifest,
+            entry: header.entry,
+            import_map: header.import_map,
+            modules,
+            assets,
+        })
+    }
+}
+
+/// Split a trailer into (header length, data length), or `None` if the magic is absent
+fn parse_trailer(trailer: &[u8; TRAILER_LEN as usize]) -> Option<(u64, u64)> {
+    if &trailer[16..] != PAYLOAD_MAGIC {
+        return None;
+    }
+    let header_len = u64::from_le_bytes(trailer[0..8].try_into().ok()?);
+    let data_len = u64::from_le_bytes(trailer[8..16].try_into().ok()?);
+    Some((header_len, data_len))
+}
+
+/// Return the length of `bytes` without any payload already appended to it
+///
+/// Lets a compiled binary be used as the runtime for another compilation
+/// without stacking payloads.
+fn image_len(bytes: &[u8]) -> usize {
+    let total = bytes.len() as u64;
+    if total < TRAILER_LEN {
+        return bytes.len();
+    }
+    let mut trailer = [0u8; TRAILER_LEN as usize];
+    trailer.copy_from_slice(&bytes[(total - TRAILER_LEN) as usize..]);
+    match parse_trailer(&trailer) {
+        Some((header_len, data_len)) => {
+            let payload_len = header_len
+                .saturating_add(data_len)
+                .saturating_add(TRAILER_LEN);
+            total.saturating_sub(payload_len) as usize
+        }
+        None => bytes.len(),
+    }
+}
+
+/// Append `payload` to a runtime image, replacing any payload it already carries
+///
+/// Mach-O images lose their code signature and get `__LINKEDIT` grown over
+/// the payload so they can be signed again; PE images lose their certificate
+/// table. Returns the binary and whether it still needs signing.
+fn assemble(mut image: Vec<u8>, payload: &Payload) -> Result<(Vec<u8>, bool)> {
+    macho::strip_signature(&mut image);
+    pe::strip_signature(&mut image);
+    let image_end = image_len(&image);
+    image.truncate(image_end);
+    let payload = payload.to_bytes()?;
+
+    let is_macho = macho::is_macho(&image);
+    let align = if is_macho {
+        macho::SIGNATURE_ALIGN
+    } else if pe::is_pe(&image) {
+        pe::CERTIFICATE_ALIGN
+    } else {
+        1
+    };
+    // Keep the end of the payload aligned so a signature can follow it directly
+    let padding = (align - (image.len() + payload.len()) % align) % align;
+    image.resize(image.len() + padding, 0);
+    image.extend_from_slice(&payload);
+    let needs_signing = is_macho && macho::cover_linkedit(&mut image);
+    Ok((image, needs_signing))
+}
+
+/// Write a standalone binary: a copy of `runtime` followed by `payload`
+///
+/// On Unix the output is marked executable. On macOS, Mach-O output is
+/// signed ad hoc after the payload is appended.
+pub fn write_standalone(runtime: &Path, payload: &Payload, output: &Path) -> Result<()> {
+    let image = std::fs::read(runtime)
+        .map_err(|e| SmeltError::io(format!("reading runtime {}", runtime.display()), e))?;
+    let (bytes, needs_signing) = assemble(image, payload)?;
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| SmeltError::io(format!("creating {}", parent.display()), e))?;
+    }
+
+    let write = || -> std::io::Result<()> {
+        let mut file = File::create(output)?;
+        file.write_all(&bytes)?;
+        file.sync_all()
+    };
+    write().map_err(|e| SmeltError::io(format!("writing {}", output.display()), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(output, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| SmeltError::io(format!("chmod {}", output.display()), e))?;
+    }
+
+    #[cfg(target_os = "macos")]
+    if needs_signing {
+        macho::sign_ad_hoc(output)?;
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = needs_signing;
+
+    Ok(())
+}
+
+/// File offset of the Mach-O code signature or PE certificate table, if any
+fn signature_offset(file: &mut File) -> std::io::Result<Option<u64>> {
+    let header = macho::read_header(file)?;
+    if !header.is_empty() {
+        return Ok(macho::signature_offset(&header));
+    }
+    Ok(pe::signature_offset(&pe::read_header(file)?))
+}
+
+/// Read the payload appended to a binary
+///
+/// Returns `Ok(None)` if the file carries no payload (a plain
+/// `forge-runtime` build).
+pub fn read_payload(exe: &Path) -> Result<Option<Payload>> {
+    let context = || format!("reading payload from {}", exe.display());
+    let mut file = File::open(exe).map_err(|e| SmeltError::io(context(), e))?;
+    let file_len = file
+        .metadata()
+        .map_err(|e| SmeltError::io(context(), e))?
+        .len();
+    // A signed binary carries its signature after the payload
+    let signature = signature_offset(&mut file).map_err(|e| SmeltError::io(context(), e))?;
+    let total = signature
+        .filter(|offset| *offset <= file_len)
+        .unwrap_or(file_len);
+    if total < TRAILER_LEN {
+        return Ok(None);
+    }
+
+    let mut trailer = [0u8; TRAILER_LEN as usize];
+    file.seek(SeekFrom::Start(total - TRAILER_LEN))
+        .and_then(|_| file.read_exact(&mut trailer))
+        .map_err(|e| SmeltError::io(context(), e))?;
+    let Some((header_len, data_len)) = parse_trailer(&trailer) else {
+        return Ok(None);
+    };
+
+    let payload_len = header_len
+        .checked_add(data_len)
+        .filter(|len| len.saturating_add(TRAILER_LEN) <= total)
+        .ok_or_else(|| SmeltError::InvalidPayload("trailer lengths exceed file size".into()))?;
+
+    let mut sections = vec![0u8; payload_len as usize];
+    file.seek(SeekFrom::Start(total - TRAILER_LEN - payload_len))
+        .and_then(|_| file.read_exact(&mut sections))
+        .map_err(|e| SmeltError::io(context(), e))?;
+    let (header, data) = sections.split_at(header_len as usize);
+
+    Payload::from_sections(header, data).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_payload() -> Payload {
+        let mut payload = Payload {
+            manifest: "[app]\nname = \"demo\"\n".to_string(),
+            entry: "src/main.ts".to_string(),
+            import_map: Some("{\"imports\":{\"@/\":\"./src/\"}}".to_string()),
+            ..Default::default()
+        };
+        payload.modules.insert(
+            "src/main.ts".to_string(),
+            EmbeddedModule {
+                kind: ModuleKind::JavaScript,
+                code: "import './util.ts';\nconsole.log('hi');\n".to_string(),
+            },
+        );
+        payload.modules.insert(
+            "src/data.json".to_string(),
+            EmbeddedModule {
+                kind: ModuleKind::Json,
+                code: "{\"a\":1}".to_string(),
+            },
+        );
+        payload
+            .assets
+            .insert("index.html".to_string(), b"<html></html>".to_vec());
+        payload
+            .assets
+            .insert("img/logo.png".to_string(), vec![0x89, 0x50, 0x4e, 0x47, 0]);
+        payload
+    }
+
+    #[test]
+    fn test_roundtrip_through_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = dir.path().join("forge-runtime");
+        let output = dir.path().join("app");
+        std::fs::write(&runtime, b"\x7fELF fake runtime image").unwrap();
+
+        let payload = sample_payload();
+        write_standalone(&runtime, &payload, &output).unwrap();
+
+        let bytes = std::fs::read(&output).unwrap();
+        assert!(bytes.starts_with(b"\x7fELF fake runtime image"));
+        assert!(bytes.ends_with(PAYLOAD_MAGIC));
+
+        let decoded = read_payload(&output).unwrap().expect("payload present");
+        assert_eq!(decoded, payload);
+        assert_eq!(decoded.asset("/index.html"), Some(&b"<html></html>"[..]));
+    }
+
+    #[test]
+    fn test_plain_binary_has_no_payload() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = dir.path().join("forge-runtime");
+        std::fs::write(&runtime, vec![0u8; 64]).unwrap();
+        assert!(read_payload(&runtime).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_recompiling_replaces_existing_payload() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = dir.path().join("forge-runtime");
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        std::fs::write(&runtime, b"runtime").unwrap();
+
+        write_standalone(&runtime, &sample_payload(), &first).unwrap();
+        let replacement = Payload {
+            manifest: "[app]\nname = \"other\"\n".to_string(),
+            entry: "src/main.ts".to_string(),
+            ..Default::default()
+        };
+        write_standalone(&first, &replacement, &second).unwrap();
+
+        let bytes = std::fs::read(&second).unwrap();
+        assert_eq!(image_len(&bytes), b"runtime".len());
+        assert_eq!(read_payload(&second).unwrap(), Some(replacement));
+    }
+
+    #[test]
+    fn test_macho_payload_survives_resigning() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("app");
+
+        let (mut bytes, needs_signing) =
+            assemble(macho::tests::signed_image(), &sample_payload()).unwrap();
+        assert!(needs_signing);
+        assert_eq!(macho::signature_offset(&bytes), None);
+        assert_eq!(bytes.len() % macho::SIGNATURE_ALIGN, 0);
+
+        // What codesign does: add a signature command and a blob after the payload
+        let signed_len = bytes.len() as u32;
+        let mut signed = macho::tests::signed_image();
+        signed.truncate(4096);
+        bytes[..4096].copy_from_slice(&signed);
+        bytes[32 + 72 + 8..32 + 72 + 12].copy_from_slice(&signed_len.to_le_bytes());
+        bytes.extend_from_slice(&[0xAA; 64]);
+        std::fs::write(&output, &bytes).unwrap();
+
+        assert_eq!(read_payload(&output).unwrap(), Some(sample_payload()));
+
+        // Recompiling a signed, compiled binary replaces its payload
+        let (again, _) = assemble(bytes, &Payload::default()).unwrap();
+        std::fs::write(&output, &again).unwrap();
+        assert_eq!(read_payload(&output).unwrap(), Some(Payload::default()));
+    }
+
+    #[test]
+    fn test_pe_payload_survives_signing() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("app.exe");
+
+        let (mut bytes, needs_signing) =
+            assemble(pe::tests::unsigned_image(), &sample_payload()).unwrap();
+        assert!(!needs_signing);
+        assert_eq!(bytes.len() % pe::CERTIFICATE_ALIGN, 0);
+
+        pe::tests::sign(&mut bytes);
+        std::fs::write(&output, &bytes).unwrap();
+        assert_eq!(read_payload(&output).unwrap(), Some(sample_payload()));
+
+        // Recompiling a signed, compiled binary replaces its payload
+        let (again, _) = assemble(bytes, &Payload::default()).unwrap();
+        assert_eq!(pe::signature_offset(&again), None);
+        std::fs::write(&output, &again).unwrap();
+        assert_eq!(read_payload(&output).unwrap(), Some(Payload::default()));
+    }
+
+    #[test]
+    fn test_truncated_payload_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken");
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&1_000u64.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(PAYLOAD_MAGIC);
+        std::fs::write(&path, bytes).unwrap();
+
+        assert!(matches!(
+            read_payload(&path),
+            Err(SmeltError::InvalidPayload(_))
+        ));
+    }
+}
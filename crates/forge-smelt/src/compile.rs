@@ -0,0 +1,352 @@
+//! End-to-end compilation of an app directory into a standalone binary
+//!
+//! Starting at `src/main.ts`, every local module reached through static
+//! imports or literal dynamic `import()`s is transpiled and embedded.
+//! Specifiers are first resolved through the app's import map, like the
+//! runtime loader does. `runtime:*` imports are served by the runtime's
+//! extensions and are left untouched. Remote (`https:`, `jsr:`, `npm:`) and
+//! unmapped bare specifiers cannot be embedded yet and fail the build with a
+//! hint, rather than failing later inside the compiled binary.
+
+use crate::binary::{write_standalone, EmbeddedModule, Payload};
+use crate::import_map::{load_import_map, ImportMap};
+use crate::parse::{parse_manifest, ParsedManifest};
+use crate::transpile::transpile_module;
+use crate::{Result, SmeltError};
+use deno_ast::ModuleSpecifier;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Entry module, relative to the app directory
+pub const ENTRY_MODULE: &str = "src/main.ts";
+
+/// Inputs for [`compile`]
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// App directory containing `manifest.app.toml`, `src/` and `web/`
+    pub app_dir: PathBuf,
+    /// `forge-runtime` executable used as the base image
+    pub runtime_binary: PathBuf,
+    /// Path of the standalone binary to write
+    pub output: PathBuf,
+}
+
+/// Summary of a successful compilation
+#[derive(Debug, Clone)]
+pub struct CompileOutput {
+    /// Path of the written binary
+    pub output: PathBuf,
+    /// Parsed app manifest
+    pub manifest: ParsedManifest,
+    /// Number of embedded modules
+    pub modules: usize,
+    /// Number of embedded web assets
+    pub assets: usize,
+}
+
+/// Compile an app directory into a standalone binary
+pub fn compile(options: CompileOptions) -> Result<CompileOutput> {
+    let app_dir = options.app_dir.canonicalize().map_err(|e| {
+        SmeltError::io(
+            format!("resolving app directory {}", options.app_dir.display()),
+            e,
+        )
+    })?;
+
+    let manifest = parse_manifest(&app_dir)?;
+    let payload = build_payload(&app_dir, &manifest)?;
+    write_standalone(&options.runtime_binary, &payload, &options.output)?;
+
+    Ok(CompileOutput {
+        output: options.output,
+        manifest,
+        modules: payload.modules.len(),
+        assets: payload.assets.len(),
+    })
+}
+
+/// Build the payload for an app directory without writing a binary
+pub fn build_payload(app_dir: &Path, manifest: &ParsedManifest) -> Result<Payload> {
+    let import_map = load_import_map(app_dir)?;
+    Ok(Payload {
+        manifest: manifest.raw.clone(),
+        entry: ENTRY_MODULE.to_string(),
+        import_map: import_map.as_ref().map(|map| map.to_json(app_dir)),
+        modules: collect_modules(app_dir, import_map.as_ref())?,
+        assets: collect_assets(&app_dir.join("web"))?,
+    })
+}
+
+/// Transpile the module graph rooted at [`ENTRY_MODULE`]
+fn collect_modules(
+    app_dir: &Path,
+    import_map: Option<&ImportMap>,
+) -> Result<BTreeMap<String, EmbeddedModule>> {
+    let entry = app_dir.join(ENTRY_MODULE);
+    let mut modules = BTreeMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([entry]);
+
+    while let Some(path) = queue.pop_front() {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+
+        let specifier = ModuleSpecifier::from_file_path(&path).map_err(|_| {
+            SmeltError::io(
+                format!("converting {} to a URL", path.display()),
+                std::io::Error::from(std::io::ErrorKind::InvalidInput),
+            )
+        })?;
+        let key = relative_key(app_dir, &path).ok_or_else(|| SmeltError::UnsupportedImport {
+            specifier: specifier.to_string(),
+            referrer: app_dir.display().to_string(),
+            message: "modules outside the app directory cannot be embedded".to_string(),
+        })?;
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| SmeltError::io(format!("reading {}", path.display()), e))?;
+
+        let module = transpile_module(&specifier, &path, source)?;
+        for import in &module.imports {
+            if let Some(dependency) = resolve_local(import, &specifier, import_map)? {
+                queue.push_back(dependency);
+            }
+        }
+
+        modules.insert(
+            key,
+            EmbeddedModule {
+                kind: module.kind,
+                code: module.code,
+            },
+        );
+    }
+
+    Ok(modules)
+}
+
+/// Resolve an import to a local file, or `None` for runtime-provided modules
+fn resolve_local(
+    specifier: &str,
+    referrer: &ModuleSpecifier,
+    import_map: Option<&ImportMap>,
+) -> Result<Option<PathBuf>> {
+    let unsupported = |message: &str| SmeltError::UnsupportedImport {
+        specifier: specifier.to_string(),
+        referrer: referrer.to_string(),
+        message: message.to_string(),
+    };
+
+    if specifier.starts_with("runtime:") || specifier.starts_with("ext:") {
+        return Ok(None);
+    }
+
+    let mapped = match import_map {
+        Some(map) => map.resolve(specifier, referrer)?,
+        None => None,
+    };
+
+    let is_relative =
+        specifier.starts_with("./") || specifier.starts_with("../") || specifier.starts_with('/');
+    let resolved = if let Some(mapped) = mapped {
+        if matches!(mapped.scheme(), "runtime" | "ext") {
+            return Ok(None);
+        }
+        mapped
+    } else if is_relative {
+        referrer
+            .join(specifier)
+            .map_err(|e| unsupported(&e.to_string()))?
+    } else {
+        match ModuleSpecifier::parse(specifier) {
+            Ok(url) => url,
+            Err(_) => {
+                return Err(unsupported(
+                    "bare specifiers cannot be embedded; map them to local files in deno.json",
+                ))
+            }
+        }
+    };
+
+    match resolved.scheme() {
+        "file" => {}
+        "http" | "https" | "jsr" | "npm" => {
+            return Err(unsupported(
+                "remote modules can't be embedded in a compiled binary yet; \
+                 vendor them into the app directory and map them in deno.json",
+            ))
+        }
+        _ => return Err(unsupported("only local modules can be embedded")),
+    }
+    resolved
+        .to_file_path()
+        .map(Some)
+        .map_err(|_| unsupported("invalid file URL"))
+}
+
+/// Collect every file under `web_dir`, keyed by `/`-separated relative path
+fn collect_assets(web_dir: &Path) -> Result<BTreeMap<String, Vec<u8>>> {
+    let mut assets = BTreeMap::new();
+    if !web_dir.exists() {
+        return Ok(assets);
+    }
+
+    for entry in walkdir::WalkDir::new(web_dir).follow_links(true) {
+        let entry = entry.map_err(|e| {
+            SmeltError::io(
+                format!("walking {}", web_dir.display()),
+                e.into_io_error()
+                    .unwrap_or_else(|| std::io::Error::other("filesystem loop")),
+            )
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(key) = relative_key(web_dir, entry.path()) else {
+            continue;
+        };
+        let bytes = std::fs::read(entry.path())
+            .map_err(|e| SmeltError::io(format!("reading {}", entry.path().display()), e))?;
+        assets.insert(key, bytes);
+    }
+
+    Ok(assets)
+}
+
+/// `/`-separated path of `path` relative to `base`
+fn relative_key(base: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(base).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::read_payload;
+    use crate::ModuleKind;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn sample_app(dir: &Path) {
+        write(
+            &dir.join("manifest.app.toml"),
+            "[app]\nname = \"Demo\"\nidentifier = \"com.example.demo\"\nversion = \"0.1.0\"\n",
+        );
+        write(
+            &dir.join("src/main.ts"),
+            "import { openWindow } from \"runtime:window\";\nimport { greet } from \"./lib/greet.ts\";\nimport config from \"./config.json\" with { type: \"json\" };\nopenWindow({ title: greet(config.name) });\n",
+        );
+        write(
+            &dir.join("src/lib/greet.ts"),
+            "export function greet(name: string): string { return `hi ${name}`; }\n",
+        );
+        write(&dir.join("src/config.json"), "{\"name\": \"demo\"}");
+        write(&dir.join("src/unused.ts"), "export const unused = 1;\n");
+        write(&dir.join("web/index.html"), "<html></html>");
+        write(&dir.join("web/assets/app.css"), "body {}");
+    }
+
+    #[test]
+    fn test_compile_app() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("app");
+        sample_app(&app);
+        let runtime = dir.path().join("forge-runtime");
+        std::fs::write(&runtime, b"runtime").unwrap();
+
+        let output = compile(CompileOptions {
+            app_dir: app.clone(),
+            runtime_binary: runtime,
+            output: dir.path().join("out/demo"),
+        })
+        .unwrap();
+
+        assert_eq!(output.manifest.app.name, "Demo");
+        assert_eq!(output.modules, 3);
+        assert_eq!(output.assets, 2);
+
+        let payload = read_payload(&output.output).unwrap().unwrap();
+        assert_eq!(payload.entry, ENTRY_MODULE);
+        assert!(payload.module("src/lib/greet.ts").is_some());
+        assert!(payload.module("src/unused.ts").is_none());
+        assert_eq!(
+            payload.module("src/config.json").unwrap().kind,
+            ModuleKind::Json
+        );
+        assert_eq!(payload.asset("assets/app.css"), Some(&b"body {}"[..]));
+    }
+
+    #[test]
+    fn test_remote_import_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        sample_app(dir.path());
+        let manifest = parse_manifest(dir.path()).unwrap();
+
+        for source in [
+            "import x from \"https://example.com/x.ts\";\n",
+            "import { z } from \"npm:zod@3\";\n",
+            "const std = await import(\"jsr:@std/path\");\n",
+        ] {
+            write(&dir.path().join("src/main.ts"), source);
+            match build_payload(&dir.path().canonicalize().unwrap(), &manifest) {
+                Err(SmeltError::UnsupportedImport { message, .. }) => {
+                    assert!(message.contains("remote modules"), "{}", message)
+                }
+                other => panic!("expected UnsupportedImport for {}: {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_dynamic_imports_are_embedded() {
+        let dir = tempfile::tempdir().unwrap();
+        sample_app(dir.path());
+        write(
+            &dir.path().join("src/main.ts"),
+            "export async function open() {\n  const { greet } = await import(\"./lib/greet.ts\");\n  return greet(\"x\");\n}\n",
+        );
+        let app_dir = dir.path().canonicalize().unwrap();
+        let manifest = parse_manifest(&app_dir).unwrap();
+
+        let payload = build_payload(&app_dir, &manifest).unwrap();
+        assert!(payload.module("src/lib/greet.ts").is_some());
+    }
+
+    #[test]
+    fn test_resolve_local() {
+        let referrer = ModuleSpecifier::parse("file:///app/src/main.ts").unwrap();
+        assert_eq!(resolve_local("runtime:fs", &referrer, None).unwrap(), None);
+        assert_eq!(
+            resolve_local("../shared/util.ts", &referrer, None).unwrap(),
+            Some(PathBuf::from("/app/shared/util.ts"))
+        );
+        assert!(resolve_local("react", &referrer, None).is_err());
+    }
+
+    #[test]
+    fn test_import_map_aliases_are_embedded() {
+        let dir = tempfile::tempdir().unwrap();
+        sample_app(dir.path());
+        write(
+            &dir.path().join("deno.json"),
+            r#"{ "imports": { "@/": "./src/lib/", "fs": "runtime:fs" } }"#,
+        );
+        write(
+            &dir.path().join("src/main.ts"),
+            "import { greet } from \"@/greet.ts\";\nimport { readTextFile } from \"fs\";\nconsole.log(greet(\"x\"), readTextFile);\n",
+        );
+        let app_dir = dir.path().canonicalize().unwrap();
+        let manifest = parse_manifest(&app_dir).unwrap();
+
+        let payload = build_payload(&app_dir, &manifest).unwrap();
+        assert!(payload.module("src/lib/greet.ts").is_some());
+        assert!(payload.import_map.unwrap().contains("./src/lib/"));
+    }
+}
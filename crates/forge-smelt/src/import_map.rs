@@ -0,0 +1,380 @@
+//! Import map resolution
+//!
+//! Implements the [import map] resolution algorithm (top-level `imports`
+//! plus `scopes`) for bare specifiers and path aliases such as `@/lib/`.
+//! Shared by the runtime module loader, `forge build` and [`crate::compile`]
+//! so every stage resolves modules the same way.
+//!
+//! The map is discovered in the app directory, in order:
+//! 1. `deno.json` with an `importMap` field pointing at a separate file
+//! 2. `deno.json` with inline `imports`/`scopes`
+//! 3. `import_map.json`
+//!
+//! [import map]: https://github.com/WICG/import-maps
+
+use crate::{Result, SmeltError};
+use deno_ast::ModuleSpecifier;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File name `forge build` writes the normalized import map to
+pub const IMPORT_MAP_FILE: &str = "import_map.json";
+
+#[derive(Deserialize, Default)]
+struct ImportMapJson {
+    #[serde(default)]
+    imports: BTreeMap<String, String>,
+    #[serde(default)]
+    scopes: BTreeMap<String, BTreeMap<String, String>>,
+    /// Only meaningful in deno.json
+    #[serde(rename = "importMap")]
+    import_map: Option<String>,
+}
+
+/// Specifier keys and their targets, longest key first
+type SpecifierMap = Vec<(String, ModuleSpecifier)>;
+
+/// A parsed import map with keys and targets resolved against its location
+#[derive(Debug, Clone)]
+pub struct ImportMap {
+    /// Where the map was loaded from
+    pub path: PathBuf,
+    imports: SpecifierMap,
+    /// Scope prefixes and their maps, longest scope first
+    scopes: Vec<(String, SpecifierMap)>,
+}
+
+impl ImportMap {
+    /// Parse import map JSON, resolving relative entries against `base`
+    ///
+    /// `base` is the URL of the file the map was read from (or a directory
+    /// URL ending in `/`).
+    pub fn parse(text: &str, base: &ModuleSpecifier, path: PathBuf) -> Result<Self> {
+        let json: ImportMapJson = serde_json::from_str(text).map_err(|e| SmeltError::ImportMap {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+        Self::from_json(json, base, path)
+    }
+
+    fn from_json(json: ImportMapJson, base: &ModuleSpecifier, path: PathBuf) -> Result<Self> {
+        let invalid = |message: String| SmeltError::ImportMap {
+            path: path.clone(),
+            message,
+        };
+
+        let parse_map = |map: BTreeMap<String, String>| -> Result<SpecifierMap> {
+            let mut entries = Vec::with_capacity(map.len());
+            for (key, value) in map {
+                let key = normalize_key(&key, base);
+                let target = parse_url_like(&value, base).ok_or_else(|| {
+                    invalid(format!("invalid target '{}' for '{}'", value, key))
+                })?;
+                if key.ends_with('/') && !target.as_str().ends_with('/') {
+                    return Err(invalid(format!(
+                        "target '{}' for '{}' must end with '/'",
+                        value, key
+                    )));
+                }
+                entries.push((key, target));
+            }
+            sort_longest_first(&mut entries);
+            Ok(entries)
+        };
+
+        let imports = parse_map(json.imports)?;
+        let mut scopes = Vec::with_capacity(json.scopes.len());
+        for (scope, map) in json.scopes {
+            let prefix = base
+                .join(&scope)
+                .map_err(|e| invalid(format!("invalid scope '{}': {}", scope, e)))?;
+            scopes.push((prefix.to_string(), parse_map(map)?));
+        }
+        sort_longest_first(&mut scopes);
+
+        Ok(Self {
+            path,
+            imports,
+            scopes,
+        })
+    }
+
+    /// Resolve `specifier` imported from `referrer`
+    ///
+    /// Returns `Ok(None)` when the map has no entry for the specifier, in
+    /// which case the caller falls back to normal URL resolution.
+    pub fn resolve(
+        &self,
+        specifier: &str,
+        referrer: &ModuleSpecifier,
+    ) -> Result<Option<ModuleSpecifier>> {
+        let as_url = parse_url_like(specifier, referrer);
+        let normalized = as_url.as_ref().map(|u| u.as_str()).unwrap_or(specifier);
+
+        for (scope, map) in &self.scopes {
+            let in_scope = scope == referrer.as_str()
+                || (scope.ends_with('/') && referrer.as_str().starts_with(scope.as_str()));
+            if in_scope {
+                if let Some(resolved) = self.resolve_in(map, normalized)? {
+                    return Ok(Some(resolved));
+                }
+            }
+        }
+        self.resolve_in(&self.imports, normalized)
+    }
+
+    fn resolve_in(&self, map: &SpecifierMap, normalized: &str) -> Result<Option<ModuleSpecifier>> {
+        for (key, target) in map {
+            if key == normalized {
+                return Ok(Some(target.clone()));
+            }
+            let Some(after) = normalized
+                .strip_prefix(key.as_str())
+                .filter(|_| key.ends_with('/'))
+            else {
+                continue;
+            };
+            // Opaque targets like `jsr:@std/` can't act as a URL base
+            let resolved = if target.cannot_be_a_base() {
+                ModuleSpecifier::parse(&format!("{}{}", target, after))
+            } else {
+                target.join(after)
+            };
+            return resolved.map(Some).map_err(|e| SmeltError::ImportMap {
+                path: self.path.clone(),
+                message: format!("cannot resolve '{}' via '{}': {}", normalized, key, e),
+            });
+        }
+        Ok(None)
+    }
+
+    /// Serialize as import map JSON rooted at `root`
+    ///
+    /// File URLs under `root` are written back as `./relative` paths so the
+    /// map stays valid when `root` is copied elsewhere (e.g. into `dist/`).
+    pub fn to_json(&self, root: &Path) -> String {
+        let root_url = ModuleSpecifier::from_directory_path(root).ok();
+        let relativize = |value: &str| -> String {
+            match &root_url {
+                Some(root) if value.starts_with(root.as_str()) => {
+                    format!("./{}", &value[root.as_str().len()..])
+                }
+                _ => value.to_string(),
+            }
+        };
+        let map_json = |map: &SpecifierMap| -> serde_json::Map<String, serde_json::Value> {
+            map.iter()
+                .map(|(key, target)| {
+                    (
+                        relativize(key),
+                        serde_json::Value::String(relativize(target.as_str())),
+                    )
+                })
+                .collect()
+        };
+
+        let scopes: serde_json::Map<_, _> = self
+            .scopes
+            .iter()
+            .map(|(scope, map)| (relativize(scope), serde_json::Value::Object(map_json(map))))
+            .collect();
+        let json = serde_json::json!({
+            "imports": map_json(&self.imports),
+            "scopes": scopes,
+        });
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    }
+}
+
+/// Find and parse the import map for an app directory
+pub fn load_import_map(app_dir: &Path) -> Result<Option<ImportMap>> {
+    let deno_json = app_dir.join("deno.json");
+    if deno_json.exists() {
+        let text = read(&deno_json)?;
+        let json: ImportMapJson =
+            serde_json::from_str(&text).map_err(|e| SmeltError::ImportMap {
+                path: deno_json.clone(),
+                message: e.to_string(),
+            })?;
+
+        if let Some(import_map) = &json.import_map {
+            let path = app_dir.join(import_map);
+            let text = read(&path)?;
+            return ImportMap::parse(&text, &file_url(&path)?, path).map(Some);
+        }
+        if !json.imports.is_empty() || !json.scopes.is_empty() {
+            let base = file_url(&deno_json)?;
+            return ImportMap::from_json(json, &base, deno_json).map(Some);
+        }
+    }
+
+    let path = app_dir.join(IMPORT_MAP_FILE);
+    if path.exists() {
+        let text = read(&path)?;
+        return ImportMap::parse(&text, &file_url(&path)?, path).map(Some);
+    }
+
+    Ok(None)
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| SmeltError::io(format!("reading {}", path.display()), e))
+}
+
+fn file_url(path: &Path) -> Result<ModuleSpecifier> {
+    let path = path
+        .canonicalize()
+        .map_err(|e| SmeltError::io(format!("resolving {}", path.display()), e))?;
+    ModuleSpecifier::from_file_path(&path).map_err(|_| SmeltError::ImportMap {
+        path,
+        message: "cannot convert path to a file URL".to_string(),
+    })
+}
+
+/// Parse a URL or a `/`, `./`, `../` relative reference; `None` for bare specifiers
+fn parse_url_like(specifier: &str, base: &ModuleSpecifier) -> Option<ModuleSpecifier> {
+    if specifier.starts_with('/') || specifier.starts_with("./") || specifier.starts_with("../")
+    {
+        return base.join(specifier).ok();
+    }
+    ModuleSpecifier::parse(specifier).ok()
+}
+
+fn normalize_key(key: &str, base: &ModuleSpecifier) -> String {
+    parse_url_like(key, base)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|| key.to_string())
+}
+
+fn sort_longest_first<T>(entries: &mut [(String, T)]) {
+    entries.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| b.cmp(a)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> ModuleSpecifier {
+        ModuleSpecifier::parse(s).unwrap()
+    }
+
+    fn sample() -> ImportMap {
+        ImportMap::parse(
+            r#"{
+                "imports": {
+                    "react": "https://esm.sh/react@18",
+                    "@/": "./src/",
+                    "@/lib/": "./src/shared/lib/",
+                    "std/": "jsr:@std/",
+                    "./src/old.ts": "./src/new.ts"
+                },
+                "scopes": {
+                    "./src/legacy/": {
+                        "react": "https://esm.sh/react@17"
+                    }
+                }
+            }"#,
+            &url("file:///app/deno.json"),
+            PathBuf::from("/app/deno.json"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bare_and_prefix_imports() {
+        let map = sample();
+        let referrer = url("file:///app/src/main.ts");
+
+        assert_eq!(
+            map.resolve("react", &referrer).unwrap(),
+            Some(url("https://esm.sh/react@18"))
+        );
+        assert_eq!(
+            map.resolve("@/util.ts", &referrer).unwrap(),
+            Some(url("file:///app/src/util.ts"))
+        );
+        // Longest prefix wins
+        assert_eq!(
+            map.resolve("@/lib/fmt.ts", &referrer).unwrap(),
+            Some(url("file:///app/src/shared/lib/fmt.ts"))
+        );
+        assert_eq!(
+            map.resolve("std/path", &referrer).unwrap(),
+            Some(url("jsr:@std/path"))
+        );
+        assert_eq!(map.resolve("./other.ts", &referrer).unwrap(), None);
+        assert_eq!(map.resolve("lodash", &referrer).unwrap(), None);
+    }
+
+    #[test]
+    fn test_url_keys_match_resolved_relative_imports() {
+        let map = sample();
+        assert_eq!(
+            map.resolve("./old.ts", &url("file:///app/src/main.ts"))
+                .unwrap(),
+            Some(url("file:///app/src/new.ts"))
+        );
+    }
+
+    #[test]
+    fn test_scopes() {
+        let map = sample();
+        assert_eq!(
+            map.resolve("react", &url("file:///app/src/legacy/widget.tsx"))
+                .unwrap(),
+            Some(url("https://esm.sh/react@17"))
+        );
+        // Falls back to top-level imports for keys the scope doesn't define
+        assert_eq!(
+            map.resolve("@/util.ts", &url("file:///app/src/legacy/widget.tsx"))
+                .unwrap(),
+            Some(url("file:///app/src/util.ts"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_prefix_target() {
+        let result = ImportMap::parse(
+            r#"{ "imports": { "@/": "./src" } }"#,
+            &url("file:///app/deno.json"),
+            PathBuf::from("/app/deno.json"),
+        );
+        assert!(matches!(result, Err(SmeltError::ImportMap { .. })));
+    }
+
+    #[test]
+    fn test_load_from_deno_json_and_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("deno.json"),
+            r#"{ "compilerOptions": {}, "imports": { "@/": "./src/", "react": "https://esm.sh/react@18" } }"#,
+        )
+        .unwrap();
+
+        let map = load_import_map(dir.path()).unwrap().unwrap();
+        let json = map.to_json(&dir.path().canonicalize().unwrap());
+        assert!(json.contains("\"@/\": \"./src/\""));
+
+        // The normalized map resolves identically from a copied directory
+        let copy = tempfile::tempdir().unwrap();
+        std::fs::write(copy.path().join(IMPORT_MAP_FILE), json).unwrap();
+        let copied = load_import_map(copy.path()).unwrap().unwrap();
+        let referrer =
+            ModuleSpecifier::from_file_path(copy.path().canonicalize().unwrap().join("src/main.ts"))
+                .unwrap();
+        assert_eq!(
+            copied.resolve("@/a.ts", &referrer).unwrap(),
+            ModuleSpecifier::from_file_path(copy.path().canonicalize().unwrap().join("src/a.ts"))
+                .ok()
+        );
+    }
+
+    #[test]
+    fn test_no_import_map() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("deno.json"), r#"{ "tasks": {} }"#).unwrap();
+        assert!(load_import_map(dir.path()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,121 @@
+//! forge-smelt: Standalone binary compilation for Forge apps
+//!
+//! This crate turns a Forge app directory into a single executable by:
+//! - Parsing `manifest.app.toml` for app metadata
+//! - Transpiling `src/main.ts` and every module it statically imports to JavaScript,
+//!   resolving bare specifiers through the app's import map
+//! - Collecting the `web/` assets served to the WebView
+//! - Appending everything as a payload to a copy of the `forge-runtime` binary
+//!   (re-signing it on macOS, and leaving room for an Authenticode certificate
+//!   on Windows)
+//!
+//! The resulting binary starts without `--app-dir`: `forge-runtime` looks for
+//! an embedded payload in its own executable before falling back to the
+//! filesystem.
+//!
+//! # Architecture
+//!
+//! ```text
+//! ┌──────────────────┐   ┌──────────────┐   ┌──────────────┐
+//! │ manifest.app.toml│   │ src/main.ts  │   │    web/      │
+//! │     (parse)      │   │ (transpile)  │   │  (assets)    │
+//! └────────┬─────────┘   └──────┬───────┘   └──────┬───────┘
+//!          └────────────────────┼──────────────────┘
+//!                               ▼
+//!                        ┌─────────────┐
+//!                        │   Payload   │
+//!                        └──────┬──────┘
+//!                               ▼
+//!               ┌───────────────────────────────┐
+//!               │ forge-runtime + payload (exe) │
+//!               └───────────────────────────────┘
+//! ```
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use forge_smelt::CompileOptions;
+//!
+//! let output = forge_smelt::compile(CompileOptions {
+//!     app_dir: "examples/text-editor".into(),
+//!     runtime_binary: "target/release/forge-runtime".into(),
+//!     output: "dist/text-editor".into(),
+//! })
+//! .expect("compile failed");
+//! println!("Compiled {} modules", output.modules);
+//! ```
+//!
+//! # Modules
+//!
+//! - [`import_map`]: Import map (`deno.json` `imports`/`scopes`) resolution
+//! - [`parse`]: Manifest parsing and static import discovery
+//! - [`transpile`]: TypeScript/JSX to JavaScript transpilation
+//! - [`binary`]: Payload layout, writing and reading standalone binaries
+//! - [`compile`]: Module graph walk and end-to-end compilation
+
+pub mod binary;
+pub mod compile;
+pub mod import_map;
+mod macho;
+pub mod parse;
+mod pe;
+pub mod transpile;
+
+pub use binary::{
+    read_payload, EmbeddedModule, ModuleKind, Payload, PAYLOAD_MAGIC, PAYLOAD_VERSION,
+};
+pub use compile::{compile, CompileOptions, CompileOutput};
+pub use import_map::{load_import_map, ImportMap};
+pub use parse::{parse_manifest, AppMetadata, ParsedManifest};
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors produced while compiling or loading a standalone binary
+#[derive(Debug, Error)]
+pub enum SmeltError {
+    /// Filesystem operation failed
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// `manifest.app.toml` is missing or malformed
+    #[error("Invalid manifest at {path}: {message}")]
+    Manifest { path: PathBuf, message: String },
+
+    /// A module could not be parsed or transpiled
+    #[error("Failed to transpile {specifier}: {message}")]
+    Transpile { specifier: String, message: String },
+
+    /// A module imports something that cannot be embedded
+    #[error("Unsupported import '{specifier}' in {referrer}: {message}")]
+    UnsupportedImport {
+        specifier: String,
+        referrer: String,
+        message: String,
+    },
+
+    /// The app's import map is malformed
+    #[error("Invalid import map at {path}: {message}")]
+    ImportMap { path: PathBuf, message: String },
+
+    /// The embedded payload is truncated or has an unknown layout
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(String),
+}
+
+impl SmeltError {
+    /// Wrap an I/O error with a description of the failed operation
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Self::Io {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+/// Result type for forge-smelt operations
+pub type Result<T> = std::result::Result<T, SmeltError>;
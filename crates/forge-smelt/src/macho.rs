@@ -0,0 +1,245 @@
+//! Mach-O fixups for standalone binaries
+//!
+//! Appending a payload to a signed Mach-O executable breaks it twice over:
+//! the code signature no longer matches (and arm64 macOS kills unsigned or
+//! mis-signed binaries at launch), and `codesign` refuses to re-sign a file
+//! with data past the end of its `__LINKEDIT` segment.
+//!
+//! So the runtime image's signature is stripped before the payload is
+//! appended, `__LINKEDIT` is then grown to cover the payload, and the result
+//! is re-signed ad hoc (`forge sign` or the bundler can replace that with a
+//! real identity). A signature written afterwards lands after the payload,
+//! so readers look for the payload trailer where the signature starts.
+//!
+//! Only thin 64-bit little-endian images are handled; anything else passes
+//! through unchanged.
+
+use std::io::{Read, Seek, SeekFrom};
+
+const MH_MAGIC_64: u32 = 0xfeed_facf;
+const LC_SEGMENT_64: u32 = 0x19;
+const LC_CODE_SIGNATURE: u32 = 0x1d;
+
+/// Size of `mach_header_64`
+const HEADER_LEN: usize = 32;
+
+/// Segment sizes are rounded to the largest page size in use (arm64)
+const PAGE_SIZE: u64 = 0x4000;
+
+/// Alignment of the signed content, which is where `codesign` places the signature
+pub(crate) const SIGNATURE_ALIGN: usize = 16;
+
+/// A load command: its offset in the file, type and size
+struct LoadCommand {
+    offset: usize,
+    cmd: u32,
+    size: usize,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn write_u64(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Whether `bytes` starts with a 64-bit Mach-O header
+pub(crate) fn is_macho(bytes: &[u8]) -> bool {
+    read_u32(bytes, 0) == Some(MH_MAGIC_64)
+}
+
+/// Parse the load commands, or `None` if `bytes` is not a well-formed Mach-O header
+fn load_commands(bytes: &[u8]) -> Option<Vec<LoadCommand>> {
+    if !is_macho(bytes) {
+        return None;
+    }
+    let ncmds = read_u32(bytes, 16)?;
+    let mut commands = Vec::with_capacity(ncmds as usize);
+    let mut offset = HEADER_LEN;
+    for _ in 0..ncmds {
+        let cmd = read_u32(bytes, offset)?;
+        let size = read_u32(bytes, offset + 4)? as usize;
+        if size < 8 || offset + size > bytes.len() {
+            return None;
+        }
+        commands.push(LoadCommand { offset, cmd, size });
+        offset += size;
+    }
+    Some(commands)
+}
+
+/// File offset where the code signature starts, if the image is signed
+///
+/// `header` only needs to hold the Mach-O header and its load commands.
+pub(crate) fn signature_offset(header: &[u8]) -> Option<u64> {
+    load_commands(header)?
+        .iter()
+        .find(|c| c.cmd == LC_CODE_SIGNATURE)
+        .and_then(|c| read_u32(header, c.offset + 8))
+        .map(u64::from)
+}
+
+/// Read the Mach-O header and load commands from the start of `file`
+///
+/// Returns an empty buffer for anything that isn't a 64-bit Mach-O image.
+pub(crate) fn read_header<R: Read + Seek>(file: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut header = vec![0u8; HEADER_LEN];
+    file.seek(SeekFrom::Start(0))?;
+    if file.read_exact(&mut header).is_err() || !is_macho(&header) {
+        return Ok(Vec::new());
+    }
+    let sizeofcmds = read_u32(&header, 20).unwrap_or(0) as usize;
+    header.resize(HEADER_LEN + sizeofcmds, 0);
+    file.read_exact(&mut header[HEADER_LEN..])?;
+    Ok(header)
+}
+
+/// Remove the code signature from a Mach-O image
+///
+/// Drops the `LC_CODE_SIGNATURE` command and truncates the signature blob at
+/// the end of `__LINKEDIT`. Returns whether a signature was removed.
+pub(crate) fn strip_signature(image: &mut Vec<u8>) -> bool {
+    let Some(commands) = load_commands(image) else {
+        return false;
+    };
+    let Some(index) = commands.iter().position(|c| c.cmd == LC_CODE_SIGNATURE) else {
+        return false;
+    };
+    let signature = &commands[index];
+    let Some(dataoff) = read_u32(image, signature.offset + 8) else {
+        return false;
+    };
+    let (offset, size) = (signature.offset, signature.size);
+    let end = commands.last().map_or(offset + size, |c| c.offset + c.size);
+
+    // Shift the following commands down and clear the freed space
+    image.copy_within(offset + size..end, offset);
+    image[end - size..end].fill(0);
+    write_u32(image, 16, commands.len() as u32 - 1);
+    let sizeofcmds = read_u32(image, 20).unwrap_or(0);
+    write_u32(image, 20, sizeofcmds - size as u32);
+
+    image.truncate(dataoff as usize);
+    true
+}
+
+/// Grow the `__LINKEDIT` segment to the end of `bytes`
+///
+/// Returns `false` if `bytes` has no `__LINKEDIT` segment.
+pub(crate) fn cover_linkedit(bytes: &mut [u8]) -> bool {
+    let Some(commands) = load_commands(bytes) else {
+        return false;
+    };
+    let linkedit = commands.iter().find(|c| {
+        c.cmd == LC_SEGMENT_64
+            && bytes
+                .get(c.offset + 8..c.offset + 24)
+                .is_some_and(|name| name.starts_with(b"__LINKEDIT\0"))
+    });
+    let Some(segment) = linkedit else {
+        return false;
+    };
+    let Some(fileoff) = read_u64(bytes, segment.offset + 40) else {
+        return false;
+    };
+
+    let filesize = (bytes.len() as u64).saturating_sub(fileoff);
+    let vmsize = filesize.div_ceil(PAGE_SIZE) * PAGE_SIZE;
+    write_u64(bytes, segment.offset + 32, vmsize);
+    write_u64(bytes, segment.offset + 48, filesize);
+    true
+}
+
+/// Sign a binary ad hoc so macOS will launch it
+#[cfg(target_os = "macos")]
+pub(crate) fn sign_ad_hoc(path: &std::path::Path) -> crate::Result<()> {
+    let output = std::process::Command::new("codesign")
+        .args(["--sign", "-", "--force"])
+        .arg(path)
+        .output()
+        .map_err(|e| crate::SmeltError::io(format!("running codesign on {}", path.display()), e))?;
+    if !output.status.success() {
+        return Err(crate::SmeltError::io(
+            format!("signing {}", path.display()),
+            std::io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A minimal signed Mach-O: header, `__LINKEDIT`, a signature command,
+    /// some code and a signature blob
+    pub(crate) fn signed_image() -> Vec<u8> {
+        let mut image = vec![0u8; 4096];
+        write_u32(&mut image, 0, MH_MAGIC_64);
+        write_u32(&mut image, 16, 2);
+        write_u32(&mut image, 20, 72 + 16);
+
+        let seg = HEADER_LEN;
+        write_u32(&mut image, seg, LC_SEGMENT_64);
+        write_u32(&mut image, seg + 4, 72);
+        image[seg + 8..seg + 19].copy_from_slice(b"__LINKEDIT\0");
+        write_u64(&mut image, seg + 40, 1024);
+
+        let sig = seg + 72;
+        write_u32(&mut image, sig, LC_CODE_SIGNATURE);
+        write_u32(&mut image, sig + 4, 16);
+        write_u32(&mut image, sig + 8, 4096);
+        write_u32(&mut image, sig + 12, 64);
+
+        image.extend_from_slice(&[0xAA; 64]);
+        cover_linkedit(&mut image);
+        image
+    }
+
+    #[test]
+    fn test_strip_signature() {
+        let mut image = signed_image();
+        assert_eq!(signature_offset(&image), Some(4096));
+
+        assert!(strip_signature(&mut image));
+        assert_eq!(image.len(), 4096);
+        assert_eq!(signature_offset(&image), None);
+        assert_eq!(read_u32(&image, 16), Some(1));
+        assert_eq!(read_u32(&image, 20), Some(72));
+        assert!(!strip_signature(&mut image));
+    }
+
+    #[test]
+    fn test_cover_linkedit() {
+        let mut image = signed_image();
+        strip_signature(&mut image);
+        image.extend_from_slice(&[1; 5000]);
+
+        assert!(cover_linkedit(&mut image));
+        let seg = HEADER_LEN;
+        assert_eq!(read_u64(&image, seg + 48), Some(image.len() as u64 - 1024));
+        assert_eq!(read_u64(&image, seg + 32), Some(PAGE_SIZE));
+    }
+
+    #[test]
+    fn test_other_formats_pass_through() {
+        let mut elf = b"\x7fELF not mach-o".to_vec();
+        assert!(!is_macho(&elf));
+        assert!(!strip_signature(&mut elf));
+        assert!(!cover_linkedit(&mut elf));
+        assert_eq!(elf, b"\x7fELF not mach-o");
+    }
+}
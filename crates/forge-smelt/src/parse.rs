@@ -0,0 +1,222 @@
+//! Manifest parsing and import discovery
+//!
+//! Only the fields needed to name the compiled binary are deserialized here;
+//! the raw manifest text is embedded verbatim so the runtime keeps parsing
+//! it with its own (richer) schema.
+
+use crate::{Result, SmeltError};
+use deno_ast::swc::ast as swc_ast;
+use deno_ast::swc::ecma_visit::{Visit, VisitWith};
+use deno_ast::ParsedSource;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// `[app]` table of `manifest.app.toml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppMetadata {
+    pub name: String,
+    pub identifier: String,
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+struct ManifestFile {
+    app: AppMetadata,
+}
+
+/// A parsed `manifest.app.toml`
+#[derive(Debug, Clone)]
+pub struct ParsedManifest {
+    /// Location the manifest was read from
+    pub path: PathBuf,
+    /// Manifest text, embedded as-is
+    pub raw: String,
+    /// App metadata from the `[app]` table
+    pub app: AppMetadata,
+}
+
+/// Read and validate `manifest.app.toml` from an app directory
+pub fn parse_manifest(app_dir: &Path) -> Result<ParsedManifest> {
+    let path = app_dir.join("manifest.app.toml");
+    let raw = std::fs::read_to_string(&path).map_err(|e| SmeltError::Manifest {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    let file: ManifestFile = toml::from_str(&raw).map_err(|e| SmeltError::Manifest {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    Ok(ParsedManifest {
+        path,
+        raw,
+        app: file.app,
+    })
+}
+
+/// Collect the specifiers a module statically depends on
+///
+/// Covers `import ... from`, side-effect `import "..."`, `export * from` and
+/// `export { ... } from`. Type-only imports and exports are skipped since
+/// they are erased by transpilation. See [`dynamic_imports`] for `import()`.
+pub fn static_imports(parsed: &ParsedSource) -> Vec<String> {
+    let deno_ast::ProgramRef::Module(module) = parsed.program_ref() else {
+        return Vec::new();
+    };
+
+    let mut specifiers = Vec::new();
+    for item in &module.body {
+        let swc_ast::ModuleItem::ModuleDecl(decl) = item else {
+            continue;
+        };
+        let src = match decl {
+            swc_ast::ModuleDecl::Import(import) if !import.type_only => Some(&import.src),
+            swc_ast::ModuleDecl::ExportAll(export) if !export.type_only => Some(&export.src),
+            swc_ast::ModuleDecl::ExportNamed(export) if !export.type_only => export.src.as_ref(),
+            _ => None,
+        };
+        if let Some(specifier) = src.and_then(|s| string_literal(s)) {
+            if !specifiers.contains(&specifier) {
+                specifiers.push(specifier);
+            }
+        }
+    }
+    specifiers
+}
+
+/// Collect the specifiers of `import()` calls with a literal argument
+///
+/// `import("./page.ts")` and `` import(`./page.ts`) `` are found anywhere in
+/// the module. Computed specifiers (`` import(`./pages/${name}.ts`) ``)
+/// can't be known ahead of time and are skipped.
+pub fn dynamic_imports(parsed: &ParsedSource) -> Vec<String> {
+    let mut collector = DynamicImports::default();
+    match parsed.program_ref() {
+        deno_ast::ProgramRef::Module(module) => module.visit_with(&mut collector),
+        deno_ast::ProgramRef::Script(script) => script.visit_with(&mut collector),
+    }
+    collector.specifiers
+}
+
+#[derive(Default)]
+struct DynamicImports {
+    specifiers: Vec<String>,
+}
+
+impl Visit for DynamicImports {
+    fn visit_call_expr(&mut self, call: &swc_ast::CallExpr) {
+        if let (swc_ast::Callee::Import(_), Some(arg)) = (&call.callee, call.args.first()) {
+            let specifier = match &*arg.expr {
+                swc_ast::Expr::Lit(swc_ast::Lit::Str(lit)) if arg.spread.is_none() => {
+                    string_literal(lit)
+                }
+                swc_ast::Expr::Tpl(tpl) if arg.spread.is_none() && tpl.exprs.is_empty() => {
+                    tpl.quasis.first().map(|quasi| quasi.raw.to_string())
+                }
+                _ => None,
+            };
+            if let Some(specifier) = specifier {
+                if !self.specifiers.contains(&specifier) {
+                    self.specifiers.push(specifier);
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+/// Text of a string literal, taken from its raw source form
+fn string_literal(lit: &swc_ast::Str) -> Option<String> {
+    let raw: &str = lit.raw.as_deref()?;
+    let inner = raw.get(1..raw.len().checked_sub(1)?)?;
+    Some(inner.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deno_ast::{MediaType, ModuleSpecifier, ParseParams};
+
+    fn parse(source: &str) -> ParsedSource {
+        deno_ast::parse_module(ParseParams {
+            specifier: ModuleSpecifier::parse("file:///app/src/main.ts").unwrap(),
+            text: source.into(),
+            media_type: MediaType::TypeScript,
+            capture_tokens: false,
+            scope_analysis: false,
+            maybe_syntax: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_static_imports() {
+        let parsed = parse(
+            r#"
+            import { readTextFile } from "runtime:fs";
+            import "./side-effect.ts";
+            import type { Config } from "./types.ts";
+            export * from './reexport.ts';
+            export { helper } from "./helper.ts";
+            export type { Other } from "./other.ts";
+            import { readTextFile as again } from "runtime:fs";
+            const config: Config = {};
+            "#,
+        );
+
+        assert_eq!(
+            static_imports(&parsed),
+            vec![
+                "runtime:fs",
+                "./side-effect.ts",
+                "./reexport.ts",
+                "./helper.ts"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dynamic_imports() {
+        let parsed = parse(
+            r#"
+            const page = await import("./pages/home.ts");
+            export async function load(name: string) {
+                const { render } = await import(`./pages/about.ts`);
+                const computed = await import(`./pages/${name}.ts`);
+                return [render, computed, import("./pages/home.ts")];
+            }
+            type Lazy = typeof import("./types.ts");
+            "#,
+        );
+
+        assert_eq!(
+            dynamic_imports(&parsed),
+            vec!["./pages/home.ts", "./pages/about.ts"]
+        );
+        assert!(static_imports(&parsed).is_empty());
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("manifest.app.toml"),
+            "[app]\nname = \"Demo\"\nidentifier = \"com.example.demo\"\nversion = \"1.2.3\"\n\n[windows]\nwidth = 800\n",
+        )
+        .unwrap();
+
+        let manifest = parse_manifest(dir.path()).unwrap();
+        assert_eq!(manifest.app.name, "Demo");
+        assert_eq!(manifest.app.identifier, "com.example.demo");
+        assert!(manifest.raw.contains("[windows]"));
+    }
+
+    #[test]
+    fn test_parse_manifest_missing_app_table() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("manifest.app.toml"), "[windows]\n").unwrap();
+        assert!(matches!(
+            parse_manifest(dir.path()),
+            Err(SmeltError::Manifest { .. })
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+//! PE fixups for standalone binaries
+//!
+//! Authenticode (`signtool`) appends a certificate table to the end of a PE
+//! file and points the security data directory at it, so in a signed Windows
+//! binary the payload is followed by the certificate. Readers look for the
+//! payload trailer where the certificate table starts.
+//!
+//! The table has to start on an 8-byte boundary, so the payload is padded to
+//! end on one and `signtool` has no reason to insert padding before it.
+//! Recompiling a signed binary drops the old certificate table first.
+
+use std::io::{Read, Seek, SeekFrom};
+
+/// Offset of `e_lfanew`, the file offset of the PE signature, in the DOS header
+const PE_POINTER: usize = 0x3c;
+
+/// Size of the PE signature and COFF file header
+const NT_HEADERS_LEN: usize = 4 + 20;
+
+/// Largest optional header: PE32+ with all 16 data directories
+const MAX_OPTIONAL_HEADER_LEN: usize = 240;
+
+const PE32_MAGIC: u16 = 0x10b;
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+
+/// Index of the certificate table in the data directories
+const SECURITY_DIRECTORY: u32 = 4;
+
+/// Alignment of the certificate table
+pub(crate) const CERTIFICATE_ALIGN: usize = 8;
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Offset of the PE signature, if `bytes` starts with a DOS header pointing at one
+fn nt_headers(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(b"MZ") {
+        return None;
+    }
+    let offset = read_u32(bytes, PE_POINTER)? as usize;
+    (bytes.get(offset..offset + 4)? == b"PE\0\0").then_some(offset)
+}
+
+/// Whether `bytes` starts with a PE header
+pub(crate) fn is_pe(bytes: &[u8]) -> bool {
+    nt_headers(bytes).is_some()
+}
+
+/// Offset of the security data directory entry, or `None` if it is missing
+fn security_directory(bytes: &[u8]) -> Option<usize> {
+    let optional = nt_headers(bytes)? + NT_HEADERS_LEN;
+    let (count_offset, directories) = match read_u16(bytes, optional)? {
+        PE32_MAGIC => (optional + 92, optional + 96),
+        PE32_PLUS_MAGIC => (optional + 108, optional + 112),
+        _ => return None,
+    };
+    if read_u32(bytes, count_offset)? <= SECURITY_DIRECTORY {
+        return None;
+    }
+    let entry = directories + SECURITY_DIRECTORY as usize * 8;
+    bytes.get(entry..entry + 8).map(|_| entry)
+}
+
+/// File offset where the certificate table starts, if the image is signed
+///
+/// `header` only needs to hold the DOS, COFF and optional headers.
+pub(crate) fn signature_offset(header: &[u8]) -> Option<u64> {
+    let entry = security_directory(header)?;
+    let offset = read_u32(header, entry)?;
+    let size = read_u32(header, entry + 4)?;
+    (offset != 0 && size != 0).then_some(u64::from(offset))
+}
+
+/// Read the headers up to the end of the data directories from the start of `file`
+///
+/// Returns an empty buffer for anything that isn't a PE image.
+pub(crate) fn read_header<R: Read + Seek>(file: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut header = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.by_ref()
+        .take(PE_POINTER as u64 + 4)
+        .read_to_end(&mut header)?;
+    if !header.starts_with(b"MZ") {
+        return Ok(Vec::new());
+    }
+    let len = read_u32(&header, PE_POINTER).unwrap_or(0) as usize
+        + NT_HEADERS_LEN
+        + MAX_OPTIONAL_HEADER_LEN;
+    // Short files simply yield fewer bytes; `is_pe` rejects what can't be parsed
+    file.by_ref()
+        .take(len.saturating_sub(header.len()) as u64)
+        .read_to_end(&mut header)?;
+    if !is_pe(&header) {
+        return Ok(Vec::new());
+    }
+    Ok(header)
+}
+
+/// Remove the certificate table from a PE image
+///
+/// Clears the security data directory and truncates the table. Returns
+/// whether a certificate table was removed.
+pub(crate) fn strip_signature(image: &mut Vec<u8>) -> bool {
+    let Some(entry) = security_directory(image) else {
+        return false;
+    };
+    let Some(offset) = signature_offset(image) else {
+        return false;
+    };
+    if offset as usize > image.len() {
+        return false;
+    }
+    write_u32(image, entry, 0);
+    write_u32(image, entry + 4, 0);
+    image.truncate(offset as usize);
+    true
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A minimal PE32+ image: DOS header, PE signature, COFF header and an
+    /// optional header with 16 data directories, padded to 1024 bytes
+    pub(crate) fn unsigned_image() -> Vec<u8> {
+        let mut image = vec![0u8; 1024];
+        image[..2].copy_from_slice(b"MZ");
+        write_u32(&mut image, PE_POINTER, 0x80);
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+        let optional = 0x80 + NT_HEADERS_LEN;
+        image[optional..optional + 2].copy_from_slice(&PE32_PLUS_MAGIC.to_le_bytes());
+        write_u32(&mut image, optional + 108, 16);
+        image
+    }
+
+    /// What `signtool` does: append a certificate table and point the
+    /// security directory at it
+    pub(crate) fn sign(image: &mut Vec<u8>) {
+        let entry = security_directory(image).unwrap();
+        let offset = image.len() as u32;
+        write_u32(image, entry, offset);
+        write_u32(image, entry + 4, 64);
+        image.extend_from_slice(&[0xCC; 64]);
+    }
+
+    #[test]
+    fn test_signature_offset() {
+        let mut image = unsigned_image();
+        assert!(is_pe(&image));
+        assert_eq!(signature_offset(&image), None);
+
+        sign(&mut image);
+        assert_eq!(signature_offset(&image), Some(1024));
+    }
+
+    #[test]
+    fn test_strip_signature() {
+        let mut image = unsigned_image();
+        sign(&mut image);
+
+        assert!(strip_signature(&mut image));
+        assert_eq!(image, unsigned_image());
+        assert!(!strip_signature(&mut image));
+    }
+
+    #[test]
+    fn test_read_header() {
+        let mut image = unsigned_image();
+        sign(&mut image);
+        let header = read_header(&mut std::io::Cursor::new(&image)).unwrap();
+        assert_eq!(signature_offset(&header), Some(1024));
+
+        let elf = b"\x7fELF not a PE image".to_vec();
+        assert!(read_header(&mut std::io::Cursor::new(&elf))
+            .unwrap()
+            .is_empty());
+        assert!(!is_pe(&elf));
+    }
+}
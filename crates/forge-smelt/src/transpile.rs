@@ -0,0 +1,142 @@
+//! TypeScript/JSX to JavaScript transpilation
+//!
+//! Uses the same deno_ast options as the runtime's module loader so a
+//! compiled app behaves exactly like one loaded from disk.
+
+use crate::binary::ModuleKind;
+use crate::parse::{dynamic_imports, static_imports};
+use crate::{Result, SmeltError};
+use deno_ast::{MediaType, ModuleSpecifier, ParseParams};
+use std::path::Path;
+
+/// Result of transpiling one module
+#[derive(Debug, Clone)]
+pub struct TranspiledModule {
+    /// How the runtime should evaluate the module
+    pub kind: ModuleKind,
+    /// JavaScript (or JSON) source
+    pub code: String,
+    /// Specifiers this module imports: static imports in source order, then
+    /// literal dynamic `import()`s
+    pub imports: Vec<String>,
+}
+
+/// Determine how a file is handled, or `None` for unsupported extensions
+///
+/// Returns the module kind and whether the source needs transpiling.
+pub fn classify(path: &Path) -> Option<(ModuleKind, bool)> {
+    match MediaType::from_path(path) {
+        MediaType::JavaScript | MediaType::Mjs | MediaType::Cjs => {
+            Some((ModuleKind::JavaScript, false))
+        }
+        MediaType::Jsx
+        | MediaType::TypeScript
+        | MediaType::Mts
+        | MediaType::Cts
+        | MediaType::Dts
+        | MediaType::Dmts
+        | MediaType::Dcts
+        | MediaType::Tsx => Some((ModuleKind::JavaScript, true)),
+        MediaType::Json => Some((ModuleKind::Json, false)),
+        _ => None,
+    }
+}
+
+/// Transpile a module's source text and collect its imports
+pub fn transpile_module(
+    specifier: &ModuleSpecifier,
+    path: &Path,
+    source: String,
+) -> Result<TranspiledModule> {
+    let error = |message: String| SmeltError::Transpile {
+        specifier: specifier.to_string(),
+        message,
+    };
+
+    let (kind, should_transpile) = classify(path)
+        .ok_or_else(|| error(format!("unknown file extension: {:?}", path.extension())))?;
+
+    if kind == ModuleKind::Json {
+        return Ok(TranspiledModule {
+            kind,
+            code: source,
+            imports: Vec::new(),
+        });
+    }
+
+    let parsed = deno_ast::parse_module(ParseParams {
+        specifier: specifier.clone(),
+        text: source.into(),
+        media_type: MediaType::from_path(path),
+        capture_tokens: false,
+        scope_analysis: false,
+        maybe_syntax: None,
+    })
+    .map_err(|e| error(e.to_string()))?;
+
+    let mut imports = static_imports(&parsed);
+    for specifier in dynamic_imports(&parsed) {
+        if !imports.contains(&specifier) {
+            imports.push(specifier);
+        }
+    }
+
+    let code = if should_transpile {
+        parsed
+            .transpile(
+                &deno_ast::TranspileOptions::default(),
+                &deno_ast::TranspileModuleOptions::default(),
+                &deno_ast::EmitOptions::default(),
+            )
+            .map_err(|e| error(e.to_string()))?
+            .into_source()
+            .text
+    } else {
+        parsed.text().to_string()
+    };
+
+    Ok(TranspiledModule {
+        kind,
+        code,
+        imports,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transpile_strips_types() {
+        let specifier = ModuleSpecifier::parse("file:///app/src/main.ts").unwrap();
+        let module = transpile_module(
+            &specifier,
+            Path::new("/app/src/main.ts"),
+            "import { add } from './math.ts';\nconst x: number = add(1, 2);\nconst lazy = () => import('./lazy.ts');\nexport default x;\n"
+                .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(module.kind, ModuleKind::JavaScript);
+        assert!(!module.code.contains(": number"));
+        assert_eq!(module.imports, vec!["./math.ts", "./lazy.ts"]);
+    }
+
+    #[test]
+    fn test_json_passthrough() {
+        let specifier = ModuleSpecifier::parse("file:///app/src/data.json").unwrap();
+        let module = transpile_module(
+            &specifier,
+            Path::new("/app/src/data.json"),
+            "{\"a\": 1}".to_string(),
+        )
+        .unwrap();
+        assert_eq!(module.kind, ModuleKind::Json);
+        assert_eq!(module.code, "{\"a\": 1}");
+    }
+
+    #[test]
+    fn test_unknown_extension() {
+        assert!(classify(Path::new("styles.css")).is_none());
+    }
+}
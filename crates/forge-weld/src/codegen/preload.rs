@@ -98,8 +98,23 @@ impl PreloadGenerator {
 
         output.push_str("type ListenerCallback = (payload: unknown) => void;\n\n");
 
+        output.push_str("interface InvokeOptions {\n");
+        output.push_str("  /** Reject with code 7004 if no reply arrives in time (ms) */\n");
+        output.push_str("  timeoutMs?: number;\n");
+        output.push_str("  /** Cancel the invoke; the Deno handler's signal is aborted too */\n");
+        output.push_str("  signal?: AbortSignal;\n");
+        output.push_str("}\n\n");
+
+        output.push_str("/** Error an invoke() promise rejects with */\n");
+        output.push_str("interface IpcInvokeError extends Error {\n");
+        output.push_str("  /** 7002 denied, 7004 timeout, 7005 cancelled, 7006 no handler, 7007 handler error, or the handler's own code */\n");
+        output.push_str("  code: number;\n");
+        output.push_str("  /** Extra data attached by the handler */\n");
+        output.push_str("  data?: unknown;\n");
+        output.push_str("}\n\n");
+
         output.push_str("interface HostBridge {\n");
-        output.push_str("  /** Register a listener for messages from Deno */\n");
+        output.push_str("  /** Register a listener for messages from Deno (binary sends arrive as Uint8Array or ReadableStream) */\n");
         output.push_str("  on(channel: string, cb: ListenerCallback): void;\n");
         output.push_str("  /** Remove a listener */\n");
         output.push_str("  off(channel: string, cb: ListenerCallback): void;\n");
@@ -107,6 +122,8 @@ impl PreloadGenerator {
         output.push_str("  emit(channel: string, payload?: unknown): void;\n");
         output.push_str("  /** Send a message to Deno (via IPC) */\n");
         output.push_str("  send(channel: string, payload?: unknown): void;\n");
+        output.push_str("  /** Call a Deno handle() and await its reply */\n");
+        output.push_str("  invoke<T = unknown>(channel: string, payload?: unknown, options?: InvokeOptions): Promise<T>;\n");
         output.push_str("}\n\n");
 
         output
@@ -305,12 +322,32 @@ impl PreloadGenerator {
         // Listeners map
         output.push_str("  const listeners = new Map<string, ListenerCallback[]>();\n\n");
 
-        // __host_dispatch
+        // Invoke bookkeeping
+        output.push_str("  // Pending invoke() calls awaiting a reply from Deno\n");
+        output.push_str("  type InvokeReply = {\n");
+        output.push_str("    invokeId: string;\n");
+        output.push_str("    ok: boolean;\n");
+        output.push_str("    value?: unknown;\n");
         output.push_str(
-            "  // Internal dispatch function called by host when sending messages to renderer\n",
+            "    error?: { name: string; code: number; message: string; data?: unknown };\n",
         );
-        output.push_str("  globalThis.__host_dispatch = function (msg: { channel: string; payload: unknown }) {\n");
-        output.push_str("    const { channel, payload } = msg;\n");
+        output.push_str("  };\n");
+        output.push_str(
+            "  const pendingInvokes = new Map<string, (reply: InvokeReply) => void>();\n",
+        );
+        output.push_str("  let invokeCounter = 0;\n\n");
+
+        output.push_str("  function invokeError(name: string, code: number, message: string, data?: unknown): IpcInvokeError {\n");
+        output.push_str("    const err = new Error(message) as IpcInvokeError;\n");
+        output.push_str("    err.name = name;\n");
+        output.push_str("    err.code = code;\n");
+        output.push_str("    err.data = data;\n");
+        output.push_str("    return err;\n");
+        output.push_str("  }\n\n");
+
+        // __host_dispatch and binary payload delivery
+        output.push_str("  // Deliver a message from Deno to local listeners\n");
+        output.push_str("  function deliver(channel: string, payload: unknown) {\n");
         output.push_str("    const arr = listeners.get(channel) || [];\n");
         output.push_str("    for (const cb of arr) {\n");
         output.push_str("      try {\n");
@@ -319,6 +356,69 @@ impl PreloadGenerator {
         output.push_str("        console.error(\"[host.dispatch] Error in listener:\", e);\n");
         output.push_str("      }\n");
         output.push_str("    }\n");
+        output.push_str("  }\n\n");
+
+        output
+            .push_str("  // Binary payloads arrive as descriptors whose bytes are served by the\n");
+        output.push_str(
+            "  // app:// protocol. Messages are still delivered in the order they were\n",
+        );
+        output.push_str("  // sent, so a message sent after a binary one waits for its fetch.\n");
+        output.push_str("  let delivery: Promise<void> = Promise.resolve();\n\n");
+
+        output.push_str("  function binaryUrl(path: string): string {\n");
+        output.push_str("    return `app://__forge_ipc__/${path}`;\n");
+        output.push_str("  }\n\n");
+
+        output.push_str("  function openBinaryStream(id: string): ReadableStream<Uint8Array> {\n");
+        output.push_str("    return new ReadableStream<Uint8Array>({\n");
+        output.push_str("      async pull(controller) {\n");
+        output.push_str("        const res = await fetch(binaryUrl(`stream/${id}`));\n");
+        output.push_str("        if (res.status === 200) {\n");
+        output.push_str("          controller.enqueue(new Uint8Array(await res.arrayBuffer()));\n");
+        output.push_str("        } else if (res.status === 204) {\n");
+        output.push_str("          controller.close();\n");
+        output.push_str("        } else {\n");
+        output.push_str("          controller.error(new Error(`IPC stream ${id} was aborted`));\n");
+        output.push_str("        }\n");
+        output.push_str("      },\n");
+        output.push_str("      cancel() {\n");
+        output.push_str("        fetch(binaryUrl(`stream/${id}/cancel`)).catch(() => {});\n");
+        output.push_str("      },\n");
+        output.push_str("    });\n");
+        output.push_str("  }\n\n");
+
+        output.push_str(
+            "  // Internal dispatch function called by host when sending messages to renderer\n",
+        );
+        output.push_str("  globalThis.__host_dispatch = function (msg: { channel: string; payload: unknown }) {\n");
+        output.push_str("    const { channel, payload } = msg;\n");
+        output.push_str(
+            "    // Replies to invoke() are routed to the waiting promise, not to listeners\n",
+        );
+        output.push_str("    if (channel === \"__invoke_reply__\") {\n");
+        output.push_str("      const reply = payload as InvokeReply;\n");
+        output.push_str("      pendingInvokes.get(reply.invokeId)?.(reply);\n");
+        output.push_str("      return;\n");
+        output.push_str("    }\n\n");
+
+        output.push_str("    const ref = payload as { __forgeBinary?: unknown; __forgeStream?: unknown } | null;\n");
+        output.push_str("    if (ref && typeof ref.__forgeBinary === \"string\") {\n");
+        output.push_str("      const bytes = fetch(binaryUrl(`blob/${ref.__forgeBinary}`))\n");
+        output.push_str("        .then((res) => res.arrayBuffer())\n");
+        output.push_str("        .then((buf) => new Uint8Array(buf));\n");
+        output.push_str("      delivery = delivery\n");
+        output.push_str("        .then(() => bytes)\n");
+        output.push_str("        .then(\n");
+        output.push_str("          (data) => deliver(channel, data),\n");
+        output.push_str("          (e) => console.error(\"[host.dispatch] Failed to fetch binary payload:\", e)\n");
+        output.push_str("        );\n");
+        output.push_str("    } else if (ref && typeof ref.__forgeStream === \"string\") {\n");
+        output.push_str("      const stream = openBinaryStream(ref.__forgeStream);\n");
+        output.push_str("      delivery = delivery.then(() => deliver(channel, stream));\n");
+        output.push_str("    } else {\n");
+        output.push_str("      delivery = delivery.then(() => deliver(channel, payload));\n");
+        output.push_str("    }\n");
         output.push_str("  };\n\n");
 
         // host bridge
@@ -356,6 +456,55 @@ impl PreloadGenerator {
         output.push_str("    send(channel: string, payload?: unknown) {\n");
         output.push_str("      const msg = JSON.stringify({ channel, payload });\n");
         output.push_str("      sendViaIpc(msg);\n");
+        output.push_str("    },\n\n");
+
+        output.push_str("    invoke<T = unknown>(channel: string, payload?: unknown, options: InvokeOptions = {}): Promise<T> {\n");
+        output
+            .push_str("      const invokeId = `${Date.now().toString(36)}-${++invokeCounter}`;\n");
+        output.push_str("      return new Promise<T>((resolve, reject) => {\n");
+        output.push_str("        if (options.signal?.aborted) {\n");
+        output.push_str("          reject(invokeError(\"IpcInvokeError\", 7005, `Invoke on '${channel}' was cancelled`));\n");
+        output.push_str("          return;\n");
+        output.push_str("        }\n\n");
+
+        output.push_str("        let timer: ReturnType<typeof setTimeout> | undefined;\n");
+        output.push_str("        const settle = () => {\n");
+        output.push_str("          pendingInvokes.delete(invokeId);\n");
+        output.push_str("          if (timer !== undefined) clearTimeout(timer);\n");
+        output.push_str("          options.signal?.removeEventListener(\"abort\", onAbort);\n");
+        output.push_str("        };\n");
+        output.push_str(
+            "        // Give up locally and tell Deno so the handler's signal is aborted\n",
+        );
+        output.push_str("        const cancel = (err: IpcInvokeError) => {\n");
+        output.push_str("          settle();\n");
+        output.push_str(
+            "          sendViaIpc(JSON.stringify({ channel: \"__invoke_cancel__\", invokeId }));\n",
+        );
+        output.push_str("          reject(err);\n");
+        output.push_str("        };\n");
+        output.push_str("        const onAbort = () =>\n");
+        output.push_str("          cancel(invokeError(\"IpcInvokeError\", 7005, `Invoke on '${channel}' was cancelled`));\n\n");
+
+        output.push_str("        pendingInvokes.set(invokeId, (reply) => {\n");
+        output.push_str("          settle();\n");
+        output.push_str("          if (reply.ok) {\n");
+        output.push_str("            resolve(reply.value as T);\n");
+        output.push_str("          } else {\n");
+        output.push_str("            const e = reply.error!;\n");
+        output.push_str("            reject(invokeError(e.name, e.code, e.message, e.data));\n");
+        output.push_str("          }\n");
+        output.push_str("        });\n");
+        output.push_str("        options.signal?.addEventListener(\"abort\", onAbort);\n");
+        output.push_str("        if (options.timeoutMs !== undefined) {\n");
+        output.push_str("          const ms = options.timeoutMs;\n");
+        output.push_str("          timer = setTimeout(\n");
+        output.push_str("            () => cancel(invokeError(\"IpcInvokeError\", 7004, `Invoke on '${channel}' timed out after ${ms}ms`)),\n");
+        output.push_str("            ms\n");
+        output.push_str("          );\n");
+        output.push_str("        }\n");
+        output.push_str("        sendViaIpc(JSON.stringify({ channel, payload, invokeId }));\n");
+        output.push_str("      });\n");
         output.push_str("    },\n");
         output.push_str("  };\n\n");
 
@@ -457,6 +606,24 @@ mod tests {
         assert!(output.contains("\"fs\""));
     }
 
+    #[test]
+    fn test_generate_invoke_bridge() {
+        let output = PreloadGenerator::new().generate();
+
+        assert!(output.contains("invoke<T = unknown>(channel: string"));
+        assert!(output.contains("channel === \"__invoke_reply__\""));
+        assert!(output.contains("channel: \"__invoke_cancel__\", invokeId"));
+    }
+
+    #[test]
+    fn test_generate_binary_delivery() {
+        let output = PreloadGenerator::new().generate();
+
+        assert!(output.contains("app://__forge_ipc__/"));
+        assert!(output.contains("ref.__forgeBinary"));
+        assert!(output.contains("openBinaryStream(ref.__forgeStream)"));
+    }
+
     #[test]
     fn test_disable_hmr() {
         let gen = PreloadGenerator::new().disable_hmr();
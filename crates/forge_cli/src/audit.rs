@@ -0,0 +1,429 @@
+//! Capability audit command for Forge CLI
+//!
+//! `forge audit` reads the JSONL audit log the runtime writes for every
+//! capability decision (`<data dir>/<identifier>/audit/audit*.jsonl`),
+//! summarizes allowed and denied checks, and prints a minimal
+//! `[permissions]` block built from what dev sessions actually used.
+
+use crate::bundler::manifest::AppManifest;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Run the audit command with the given arguments
+pub fn run(args: &[String]) -> Result<()> {
+    let cmd = AuditCommand::parse(args)?;
+
+    let log_dir = match cmd.log {
+        Some(dir) => dir,
+        None => {
+            let manifest = AppManifest::from_app_dir(&cmd.app_dir)?;
+            default_log_dir(&manifest.app.identifier)
+        }
+    };
+
+    let entries = read_log(&log_dir)?;
+    if entries.is_empty() {
+        bail!(
+            "No audit entries in {}\n\nRun the app with `forge dev` first; every capability check is logged there.",
+            log_dir.display()
+        );
+    }
+
+    println!("Audit log: {}", log_dir.display());
+    print!("{}", summarize(&entries));
+
+    let dev: Vec<AuditEntry> = entries.iter().filter(|e| e.dev_mode).cloned().collect();
+    println!();
+    if dev.is_empty() {
+        println!("No dev session entries; suggesting permissions from allowed production checks.");
+        let allowed: Vec<AuditEntry> = entries.into_iter().filter(|e| e.allowed).collect();
+        print!("{}", suggest_permissions(&allowed));
+    } else {
+        println!("Suggested permissions (from dev sessions):");
+        println!();
+        print!("{}", suggest_permissions(&dev));
+    }
+
+    Ok(())
+}
+
+pub fn usage() {
+    eprintln!("forge audit [options] [app-dir]");
+    eprintln!();
+    eprintln!("Summarize the capability audit log and suggest a minimal [permissions] block.");
+    eprintln!();
+    eprintln!("Arguments:");
+    eprintln!("  [app-dir]                          App directory (default: .)");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!(
+        "  --log, -l <dir>                    Audit log directory (default: from identifier)"
+    );
+    eprintln!();
+    eprintln!("Examples:");
+    eprintln!("  forge dev my-app && forge audit my-app");
+}
+
+/// Audit command configuration
+struct AuditCommand {
+    /// App directory containing manifest.app.toml
+    app_dir: PathBuf,
+    /// Explicit log directory
+    log: Option<PathBuf>,
+}
+
+impl AuditCommand {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut cmd = AuditCommand {
+            app_dir: PathBuf::from("."),
+            log: None,
+        };
+
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--log" | "-l" => {
+                    if i + 1 < args.len() {
+                        cmd.log = Some(PathBuf::from(&args[i + 1]));
+                        i += 2;
+                    } else {
+                        bail!("--log requires a value");
+                    }
+                }
+                arg if !arg.starts_with('-') => {
+                    cmd.app_dir = PathBuf::from(arg);
+                    i += 1;
+                }
+                _ => {
+                    bail!("Unknown flag: {}", args[i]);
+                }
+            }
+        }
+
+        Ok(cmd)
+    }
+}
+
+/// One line of the runtime's audit log
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditEntry {
+    timestamp: String,
+    capability: String,
+    resource: String,
+    allowed: bool,
+    #[serde(default)]
+    extension: Option<String>,
+    #[serde(default)]
+    op: Option<String>,
+    #[serde(default)]
+    dev_mode: bool,
+}
+
+/// Where the runtime writes the audit log for an app identifier
+fn default_log_dir(identifier: &str) -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(identifier)
+        .join("audit")
+}
+
+/// Read `audit.N.jsonl` … `audit.1.jsonl`, then `audit.jsonl` (oldest first)
+fn read_log(dir: &Path) -> Result<Vec<AuditEntry>> {
+    let mut rotated: Vec<(usize, PathBuf)> = Vec::new();
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let index = match name {
+                "audit.jsonl" => Some(0),
+                _ => name
+                    .strip_prefix("audit.")
+                    .and_then(|n| n.strip_suffix(".jsonl"))
+                    .and_then(|n| n.parse().ok()),
+            };
+            if let Some(index) = index {
+                rotated.push((index, path));
+            }
+        }
+    }
+    rotated.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+
+    let mut entries = Vec::new();
+    for (_, path) in rotated {
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        // A crash can leave a truncated last line; skip anything unparsable
+        entries.extend(
+            text.lines()
+                .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok()),
+        );
+    }
+    Ok(entries)
+}
+
+/// Per-capability counts and the denied resources
+fn summarize(entries: &[AuditEntry]) -> String {
+    let mut out = String::new();
+    let dev = entries.iter().filter(|e| e.dev_mode).count();
+    out.push_str(&format!(
+        "Entries: {} ({} dev, {} production) from {} to {}\n\n",
+        entries.len(),
+        dev,
+        entries.len() - dev,
+        entries.first().map(|e| e.timestamp.as_str()).unwrap_or("-"),
+        entries.last().map(|e| e.timestamp.as_str()).unwrap_or("-"),
+    ));
+
+    let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    let mut denied: BTreeMap<(&str, &str), (usize, BTreeSet<String>)> = BTreeMap::new();
+    for entry in entries {
+        let count = counts.entry(&entry.capability).or_default();
+        if entry.allowed {
+            count.0 += 1;
+        } else {
+            count.1 += 1;
+            let slot = denied
+                .entry((&entry.capability, &entry.resource))
+                .or_default();
+            slot.0 += 1;
+            if let Some(op) = &entry.op {
+                slot.1.insert(match &entry.extension {
+                    Some(ext) => format!("{}/{}", ext, op),
+                    None => op.clone(),
+                });
+            }
+        }
+    }
+
+    out.push_str(&format!(
+        "{:<26} {:>8} {:>8}\n",
+        "Capability", "Allowed", "Denied"
+    ));
+    for (capability, (allowed, denied)) in &counts {
+        out.push_str(&format!(
+            "{:<26} {:>8} {:>8}\n",
+            capability, allowed, denied
+        ));
+    }
+
+    if !denied.is_empty() {
+        out.push_str("\nDenied:\n");
+        for ((capability, resource), (count, ops)) in &denied {
+            out.push_str(&format!("  {} {} ({}x)", capability, resource, count));
+            if !ops.is_empty() {
+                let ops: Vec<&str> = ops.iter().map(String::as_str).collect();
+                out.push_str(&format!(" via {}", ops.join(", ")));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Manifest `[permissions]` TOML covering exactly the given entries
+fn suggest_permissions(entries: &[AuditEntry]) -> String {
+    let mut used: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for entry in entries {
+        used.entry(&entry.capability)
+            .or_default()
+            .insert(&entry.resource);
+    }
+
+    let list = |capability: &str| -> Option<String> {
+        let resources = used.get(capability)?;
+        let quoted: Vec<String> = resources
+            .iter()
+            .map(|r| toml::Value::String(r.to_string()).to_string())
+            .collect();
+        Some(format!("[{}]", quoted.join(", ")))
+    };
+    let flag = |capability: &str| used.contains_key(capability).then(|| "true".to_string());
+    let ports = || -> Option<String> {
+        let mut ports: Vec<u16> = used
+            .get("net.listen")?
+            .iter()
+            .filter_map(|p| p.parse().ok())
+            .collect();
+        ports.sort_unstable();
+        let ports: Vec<String> = ports.iter().map(u16::to_string).collect();
+        Some(format!("[{}]", ports.join(", ")))
+    };
+
+    let sections = [
+        (
+            "permissions.fs",
+            vec![("read", list("fs.read")), ("write", list("fs.write"))],
+        ),
+        (
+            "permissions.net",
+            vec![("allow", list("net")), ("listen", ports())],
+        ),
+        (
+            "permissions.process",
+            vec![
+                ("allow", list("process.spawn")),
+                ("env", list("process.env")),
+            ],
+        ),
+        (
+            "permissions.sys.env",
+            vec![
+                ("read", list("sys.env.read")),
+                ("write", list("sys.env.write")),
+            ],
+        ),
+        (
+            "permissions.wasm",
+            vec![
+                ("load", list("wasm.load")),
+                ("preopens", list("wasm.preopen")),
+            ],
+        ),
+        ("permissions.ui", vec![("channels", list("ui.channel"))]),
+        (
+            "permissions.codesign",
+            vec![
+                ("sign", flag("codesign.sign")),
+                ("list_identities", flag("codesign.list_identities")),
+            ],
+        ),
+        ("permissions.database", vec![("names", list("database"))]),
+        (
+            "permissions.shell",
+            vec![
+                ("execute", flag("shell.execute")),
+                ("commands", list("shell.command")),
+            ],
+        ),
+        (
+            "permissions.monitor",
+            vec![("processes", flag("monitor.processes"))],
+        ),
+    ];
+
+    let mut out = String::new();
+    for (table, keys) in sections {
+        let keys: Vec<_> = keys
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|v| (key, v)))
+            .collect();
+        if keys.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("[{}]\n", table));
+        for (key, value) in keys {
+            out.push_str(&format!("{} = {}\n", key, value));
+        }
+    }
+    if out.is_empty() {
+        out.push_str("# No audited capabilities were used\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(capability: &str, resource: &str, allowed: bool, dev_mode: bool) -> String {
+        format!(
+            r#"{{"timestamp":"2026-01-01T00:00:00Z","capability":"{}","resource":"{}","allowed":{},"extension":"fs","op":"op_fs_read_text","devMode":{}}}"#,
+            capability, resource, allowed, dev_mode
+        )
+    }
+
+    #[test]
+    fn test_read_log_orders_rotations() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("audit.2.jsonl"),
+            entry("fs.read", "/a", true, true) + "\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("audit.1.jsonl"),
+            entry("fs.read", "/b", true, true) + "\n{\"trunc",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("audit.jsonl"),
+            entry("fs.read", "/c", true, true) + "\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let resources: Vec<String> = read_log(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|e| e.resource)
+            .collect();
+        assert_eq!(resources, ["/a", "/b", "/c"]);
+    }
+
+    #[test]
+    fn test_summarize_counts_denials() {
+        let entries: Vec<AuditEntry> = [
+            entry("net", "api.example.com", true, false),
+            entry("net", "evil.com", false, false),
+            entry("net", "evil.com", false, false),
+        ]
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+        let summary = summarize(&entries);
+        assert!(summary.contains("Entries: 3 (0 dev, 3 production)"));
+        assert!(summary.contains("net evil.com (2x) via fs/op_fs_read_text"));
+    }
+
+    #[test]
+    fn test_suggest_permissions() {
+        let entries: Vec<AuditEntry> = [
+            entry("fs.read", "/app/data/b.json", true, true),
+            entry("fs.read", "/app/data/a.json", true, true),
+            entry("fs.read", "/app/data/a.json", true, true),
+            entry("net", "api.example.com", true, true),
+            entry("net.listen", "8080", true, true),
+            entry("sys.env.read", "HOME", true, true),
+            entry("ui.channel", "app:save", true, true),
+            entry("codesign.sign", "signing operation", true, true),
+            entry("shell.execute", "shell interpreter", true, true),
+            entry("shell.command", "/usr/bin/git", true, true),
+        ]
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+        let suggestion = suggest_permissions(&entries);
+        assert_eq!(
+            suggestion,
+            "[permissions.fs]\n\
+             read = [\"/app/data/a.json\", \"/app/data/b.json\"]\n\
+             \n\
+             [permissions.net]\n\
+             allow = [\"api.example.com\"]\n\
+             listen = [8080]\n\
+             \n\
+             [permissions.sys.env]\n\
+             read = [\"HOME\"]\n\
+             \n\
+             [permissions.ui]\n\
+             channels = [\"app:save\"]\n\
+             \n\
+             [permissions.codesign]\n\
+             sign = true\n\
+             \n\
+             [permissions.shell]\n\
+             execute = true\n\
+             commands = [\"/usr/bin/git\"]\n"
+        );
+        // The suggestion is valid manifest TOML
+        assert!(toml::from_str::<toml::Value>(&suggestion).is_ok());
+    }
+}
@@ -91,7 +91,7 @@ impl<'a> LinuxBundler<'a> {
 
         println!("  Creating AppDir at {}", appdir_path.display());
 
-        // 1. Build forge-runtime with embedded assets
+        // 1. Build standalone forge-runtime with the embedded app
         let binary_path = build_embedded_binary(self.dist_dir)?;
 
         // 2. Copy binary to usr/bin
@@ -23,7 +23,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::{build_embedded_binary, copy_dir_recursive, sanitize_name, AppManifest, IconProcessor};
+use super::{build_embedded_binary, sanitize_name, AppManifest, IconProcessor};
 
 /// macOS bundler supporting multiple output formats
 pub struct MacosBundler<'a> {
@@ -112,7 +112,7 @@ impl<'a> MacosBundler<'a> {
 
         println!("  Creating .app bundle...");
 
-        // 1. Build forge-runtime with embedded assets
+        // 1. Build standalone forge-runtime with the embedded app
         let binary_path = build_embedded_binary(self.dist_dir)?;
 
         // 2. Create bundle structure
@@ -160,22 +160,6 @@ impl<'a> MacosBundler<'a> {
         let icns_path = resources_dir.join("AppIcon.icns");
         icon_processor.convert_to_icns(&icns_path)?;
 
-        // 7. Copy app resources (manifest, src for Deno runtime)
-        let app_resources = resources_dir.join("app");
-        fs::create_dir_all(&app_resources)?;
-
-        // Copy manifest
-        fs::copy(
-            self.dist_dir.join("manifest.app.toml"),
-            app_resources.join("manifest.app.toml"),
-        )?;
-
-        // Copy src/ directory (Deno runtime code)
-        let src_dir = self.dist_dir.join("src");
-        if src_dir.exists() {
-            copy_dir_recursive(&src_dir, &app_resources.join("src"))?;
-        }
-
         println!("  Created app bundle: {}", bundle_path.display());
         Ok(bundle_path)
     }
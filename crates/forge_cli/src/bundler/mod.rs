@@ -48,24 +48,12 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-/// Build forge-runtime binary with embedded assets
-///
-/// This compiles the forge-runtime crate in release mode with the
-/// FORGE_EMBED_DIR environment variable set to embed web assets.
-pub fn build_embedded_binary(dist_dir: &Path) -> Result<PathBuf> {
-    let web_dir = dist_dir.join("web");
-    if !web_dir.exists() {
-        bail!(
-            "Web assets not found at {}. Run 'forge build' first.",
-            web_dir.display()
-        );
-    }
-
-    println!("  Building release binary with embedded assets...");
+/// Build the release forge-runtime binary used as the base for compiled apps
+pub fn build_runtime_binary() -> Result<PathBuf> {
+    println!("  Building release forge-runtime...");
 
     let status = Command::new("cargo")
         .args(["build", "-p", "forge-runtime", "--release"])
-        .env("FORGE_EMBED_DIR", web_dir.display().to_string())
         .status()
         .context("Failed to execute cargo build")?;
 
@@ -90,6 +78,58 @@ pub fn build_embedded_binary(dist_dir: &Path) -> Result<PathBuf> {
     Ok(binary_path)
 }
 
+/// Compile an app directory into a standalone binary
+///
+/// Uses forge-smelt to transpile `src/main.ts` and its imports and embed them,
+/// together with `web/` and `manifest.app.toml`, into a copy of `runtime_binary`.
+pub fn compile_standalone(app_dir: &Path, runtime_binary: &Path, output: &Path) -> Result<PathBuf> {
+    let compiled = forge_smelt::compile(forge_smelt::CompileOptions {
+        app_dir: app_dir.to_path_buf(),
+        runtime_binary: runtime_binary.to_path_buf(),
+        output: output.to_path_buf(),
+    })
+    .with_context(|| format!("Failed to compile {}", app_dir.display()))?;
+
+    println!(
+        "  Embedded {} modules and {} assets into {}",
+        compiled.modules,
+        compiled.assets,
+        compiled.output.display()
+    );
+
+    Ok(compiled.output)
+}
+
+/// Build a self-contained forge-runtime binary for a built app
+///
+/// The app in `dist/` (manifest, modules and web assets) is embedded into
+/// the binary, so bundles ship a single executable instead of loose app
+/// files and the binary starts without `--app-dir`.
+pub fn build_embedded_binary(dist_dir: &Path) -> Result<PathBuf> {
+    let web_dir = dist_dir.join("web");
+    if !web_dir.exists() {
+        bail!(
+            "Web assets not found at {}. Run 'forge build' first.",
+            web_dir.display()
+        );
+    }
+
+    let runtime_binary = build_runtime_binary()?;
+
+    println!("  Compiling standalone binary...");
+
+    #[cfg(target_os = "windows")]
+    let binary_name = "forge-runtime.exe";
+    #[cfg(not(target_os = "windows"))]
+    let binary_name = "forge-runtime";
+
+    compile_standalone(
+        dist_dir,
+        &runtime_binary,
+        &dist_dir.join("bin").join(binary_name),
+    )
+}
+
 /// Copy directory recursively
 #[allow(dead_code)]
 pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
@@ -47,7 +47,7 @@ impl<'a> MsixBundler<'a> {
         }
         fs::create_dir_all(&stage_dir)?;
 
-        // 2. Build forge-runtime with embedded assets
+        // 2. Build standalone forge-runtime with the embedded app
         let binary_path = build_embedded_binary(self.dist_dir)?;
 
         // 3. Copy executable to staging with proper name
@@ -2,14 +2,14 @@
 //!
 //! Creates:
 //! - MSIX packages (default, for Windows 10+ Store/sideload distribution)
-//! - Portable EXE directory (standalone executable with resources)
+//! - Portable EXE directory (standalone executable)
 //! - NSIS installer (optional, requires NSIS to be installed)
 //!
 //! ## Bundle Formats
 //!
 //! - **msix**: Windows App Package format for modern Windows apps. Best for
 //!   Windows Store distribution or enterprise sideloading.
-//! - **portable**: Standalone directory containing the exe and resources.
+//! - **portable**: Standalone directory containing the self-contained exe.
 //!   Can be zipped for distribution without installation requirements.
 //! - **nsis**: Traditional installer using NSIS (Nullsoft Scriptable Install System).
 //!   Best for users expecting classic Windows installers.
@@ -19,7 +19,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::{build_embedded_binary, copy_dir_recursive, sanitize_name, AppManifest, IconProcessor};
+use super::{build_embedded_binary, sanitize_name, AppManifest, IconProcessor};
 
 /// Windows bundler supporting multiple output formats
 pub struct WindowsBundler<'a> {
@@ -88,7 +88,7 @@ impl<'a> WindowsBundler<'a> {
         }
         fs::create_dir_all(&portable_dir)?;
 
-        // 1. Build forge-runtime with embedded assets
+        // 1. Build standalone forge-runtime with the embedded app
         let binary_path = build_embedded_binary(self.dist_dir)?;
 
         // 2. Copy executable
@@ -97,23 +97,7 @@ impl<'a> WindowsBundler<'a> {
         fs::copy(&binary_path, &dest_exe)
             .with_context(|| format!("Failed to copy binary to {}", dest_exe.display()))?;
 
-        // 3. Copy app resources (manifest, src for Deno runtime)
-        let resources_dir = portable_dir.join("resources");
-        fs::create_dir_all(&resources_dir)?;
-
-        // Copy manifest
-        fs::copy(
-            self.dist_dir.join("manifest.app.toml"),
-            resources_dir.join("manifest.app.toml"),
-        )?;
-
-        // Copy src/ directory (Deno runtime code)
-        let src_dir = self.dist_dir.join("src");
-        if src_dir.exists() {
-            copy_dir_recursive(&src_dir, &resources_dir.join("src"))?;
-        }
-
-        // 4. Generate icon (optional for portable, but nice to have)
+        // 3. Generate icon (optional for portable, but nice to have)
         println!("  Generating icons...");
         let icon_base = self.manifest.bundle.icon.as_deref();
         if let Ok(icon_processor) = IconProcessor::find_icon(self.app_dir, icon_base) {
@@ -126,7 +110,7 @@ impl<'a> WindowsBundler<'a> {
             }
         }
 
-        // 5. Create README for the portable package
+        // 4. Create README for the portable package
         let readme_content = format!(
             r#"{app_name} v{version}
 ========================
@@ -151,7 +135,7 @@ Built with Forge (https://forge-deno.com)
         );
         fs::write(portable_dir.join("README.txt"), readme_content)?;
 
-        // 6. Optional code signing
+        // 5. Optional code signing
         let should_sign = self
             .manifest
             .bundle
@@ -222,27 +206,14 @@ Built with Forge (https://forge-deno.com)
         let version = &self.manifest.app.version;
         let exe_name = format!("{}.exe", sanitize_name(app_name));
 
-        // 1. Build forge-runtime with embedded assets
+        // 1. Build standalone forge-runtime with the embedded app
         let binary_path = build_embedded_binary(self.dist_dir)?;
 
         // 2. Copy executable to staging
         let staged_exe = staging_dir.join(&exe_name);
         fs::copy(&binary_path, &staged_exe)?;
 
-        // 3. Copy resources
-        let resources_dir = staging_dir.join("resources");
-        fs::create_dir_all(&resources_dir)?;
-        fs::copy(
-            self.dist_dir.join("manifest.app.toml"),
-            resources_dir.join("manifest.app.toml"),
-        )?;
-
-        let src_dir = self.dist_dir.join("src");
-        if src_dir.exists() {
-            copy_dir_recursive(&src_dir, &resources_dir.join("src"))?;
-        }
-
-        // 4. Generate icon
+        // 3. Generate icon
         let icon_base = self.manifest.bundle.icon.as_deref();
         let ico_path = if let Ok(icon_processor) = IconProcessor::find_icon(self.app_dir, icon_base)
         {
@@ -252,12 +223,12 @@ Built with Forge (https://forge-deno.com)
             staging_dir.join("icon.ico")
         };
 
-        // 5. Generate NSIS script
+        // 4. Generate NSIS script
         let nsi_content = self.generate_nsis_script(&exe_name, &ico_path)?;
         let nsi_path = staging_dir.join("installer.nsi");
         fs::write(&nsi_path, &nsi_content)?;
 
-        // 6. Run NSIS
+        // 5. Run NSIS
         let installer_name = format!("{}-{}-setup.exe", sanitize_name(app_name), version);
         let installer_path = self.output_dir.join(&installer_name);
 
@@ -283,7 +254,7 @@ Built with Forge (https://forge-deno.com)
         // Clean up staging
         let _ = fs::remove_dir_all(&staging_dir);
 
-        // 7. Optional code signing
+        // 6. Optional code signing
         let should_sign = self
             .manifest
             .bundle
@@ -444,7 +415,6 @@ Section "Install"
 
     ; Copy files
     File "{exe_name}"
-    File /r "resources"
 
     ; Create uninstaller
     WriteUninstaller "$INSTDIR\Uninstall.exe"
@@ -479,7 +449,6 @@ SectionEnd
 Section "Uninstall"
     ; Remove files
     Delete "$INSTDIR\{exe_name}"
-    RMDir /r "$INSTDIR\resources"
     Delete "$INSTDIR\Uninstall.exe"
     RMDir "$INSTDIR"
 
@@ -8,8 +8,12 @@
 //!
 //! 1. **Development** (`forge dev`) - Run app with hot reload and full debugging
 //! 2. **Build** (`forge build`) - Compile and bundle web assets for production
-//! 3. **Bundle** (`forge bundle`) - Create platform-specific distributables
-//! 4. **Sign** (`forge sign`) - Code sign the bundled artifacts
+//! 3. **Compile** (`forge compile`) - Produce a single self-contained executable
+//! 4. **Bundle** (`forge bundle`) - Create platform-specific distributables
+//! 5. **Sign** (`forge sign`) - Code sign the bundled artifacts
+//!
+//! `forge audit` reads the runtime's capability audit log and suggests a
+//! minimal `[permissions]` block from what dev sessions actually used.
 //!
 //! # Architecture
 //!
@@ -53,12 +57,19 @@
 //! 5. **Transform** (if needed):
 //!    - Svelte: Compile `.svelte` → `.js` with TypeScript support
 //!    - Vue: Compile SFCs with `@vue/compiler-sfc`
-//! 6. **Bundle**: Run esbuild via Deno to create `bundle.js`
+//! 6. **Bundle**: Run esbuild via Deno to create `bundle.js`, resolving
+//!    imports through the app's import map (written to `dist/import_map.json`)
 //! 7. **HTML Update**: Rewrite `index.html` to reference `bundle.js`
 //!
+//! ### Compile (`cmd_compile`)
+//! 1. Ensure `dist/` exists (runs build if missing)
+//! 2. Locate `forge-runtime` (release build when running from the workspace)
+//! 3. Embed manifest, transpiled `src/` module graph and `web/` assets with
+//!    forge-smelt, writing `bundle/<app-name>` (starts without `--app-dir`)
+//!
 //! ### Bundling (`cmd_bundle`)
 //! 1. Ensure `dist/` exists (runs build if missing)
-//! 2. Compile `forge-runtime` with `FORGE_EMBED_DIR` set
+//! 2. Compile a standalone `forge-runtime` with the app embedded
 //! 3. Call platform-specific bundler:
 //!    - **macOS**: Create `.app` bundle + DMG (see [`bundler::macos`])
 //!    - **Windows**: Generate MSIX package (see [`bundler::windows`])
@@ -81,15 +92,22 @@
 //! # Build production assets
 //! forge build my-app
 //!
+//! # Compile a single executable
+//! forge compile my-app --output my-app-bin
+//!
 //! # Create distributable
 //! forge bundle my-app
 //!
+//! # Suggest permissions after a dev session
+//! forge audit my-app
+//!
 //! # Sign for macOS distribution
 //! forge sign --identity "Developer ID Application: Name (TEAM)" my-app/bundle/MyApp.dmg
 //! ```
 //!
 //! # Module Organization
 //!
+//! - [`audit`] - Capability audit log summary
 //! - [`bundler`] - Platform-specific packaging backends
 //! - [`docs`] - API documentation generation
 //! - Main module - Command dispatch and build orchestration
@@ -101,19 +119,24 @@ use std::{
     process::Command,
 };
 
+mod audit;
 mod bundler;
 mod docs;
 
 fn usage() {
-    eprintln!("forge <dev|build|bundle|sign|icon|docs> [options] <app-dir>");
+    eprintln!("forge <dev|build|compile|bundle|sign|icon|docs|audit> [options] <app-dir>");
     eprintln!();
     eprintln!("Commands:");
     eprintln!("  dev <app-dir>                       Run in development mode");
     eprintln!("  build <app-dir>                     Build for production");
+    eprintln!("  compile <app-dir> [--output <path>] Compile into a single executable");
     eprintln!("  bundle <app-dir>                    Package into distributable");
     eprintln!("  sign <artifact>                     Sign a package artifact");
     eprintln!("  icon <subcommand>                   Manage app icons");
     eprintln!("  docs [options] [target]             Generate API documentation");
+    eprintln!(
+        "  audit [--log <dir>] [app-dir]       Summarize capability use, suggest permissions"
+    );
     eprintln!();
     eprintln!("Icon subcommands:");
     eprintln!("  icon create <path>                  Create a placeholder icon");
@@ -349,6 +372,14 @@ fn bundle_with_esbuild(app_dir: &Path, dist_dir: &Path, framework: &Framework) -
 
     let out_file = dist_dir.join("web/bundle.js");
     let deno_json = app_dir.join("deno.json");
+    let import_map = dist_dir.join(forge_smelt::import_map::IMPORT_MAP_FILE);
+    let deno_plugins = if import_map.exists() {
+        let url = url::Url::from_file_path(import_map.canonicalize()?)
+            .map_err(|_| anyhow!("Invalid import map path: {}", import_map.display()))?;
+        format!("denoPlugins({{ importMapURL: {:?} }})", url.as_str())
+    } else {
+        "denoPlugins()".to_string()
+    };
 
     println!(
         "  Bundling {} with esbuild...",
@@ -403,7 +434,7 @@ const result = await esbuild.build({{
   outfile: {:?},
   {jsx}
   {svelte}
-  plugins: [...denoPlugins()],
+  plugins: [...{deno_plugins}],
   define: {{
     "process.env.NODE_ENV": '"production"'
   }},
@@ -420,7 +451,8 @@ console.log("Bundle complete:", result);
         } else {
             ""
         },
-        svelte = svelte_config
+        svelte = svelte_config,
+        deno_plugins = deno_plugins
     );
 
     let script_path = dist_dir.join("_esbuild_bundle.ts");
@@ -847,6 +879,17 @@ fn cmd_build(app_dir: &Path) -> Result<()> {
     fs::create_dir_all(dist_dir.join("src"))?;
     copy_dir_recursive(&src_dir, &dist_dir.join("src"))?;
 
+    // Write the app's import map (deno.json imports/scopes or import_map.json)
+    // normalized to dist/, so the runtime and esbuild resolve the same aliases
+    let app_root = app_dir.canonicalize()?;
+    if let Some(import_map) = forge_smelt::load_import_map(&app_root)? {
+        println!("  Writing import map...");
+        fs::write(
+            dist_dir.join(forge_smelt::import_map::IMPORT_MAP_FILE),
+            import_map.to_json(&app_root),
+        )?;
+    }
+
     // Bundle based on framework
     match framework {
         Framework::React | Framework::Minimal => {
@@ -890,6 +933,49 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+fn cmd_compile(app_dir: &Path, output: Option<&Path>) -> Result<()> {
+    println!("Compiling app at {}", app_dir.display());
+
+    // 1. Verify dist/ exists, or run build first
+    let dist_dir = app_dir.join("dist");
+    if !dist_dir.exists() {
+        println!("  dist/ not found, running build first...");
+        cmd_build(app_dir)?;
+    }
+
+    let manifest = bundler::parse_manifest(app_dir)?;
+    println!("  App: {} v{}", manifest.app.name, manifest.app.version);
+
+    // 2. Locate the runtime to embed the app into
+    let forge_host = find_forge_host()?;
+    let runtime_binary = if forge_host.to_string_lossy() == "__cargo_run__" {
+        bundler::build_runtime_binary()?
+    } else {
+        forge_host
+    };
+
+    // 3. Embed the app
+    let output = match output {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let name = bundler::sanitize_name(&manifest.app.name);
+            let name = if cfg!(target_os = "windows") {
+                format!("{}.exe", name)
+            } else {
+                name
+            };
+            app_dir.join("bundle").join(name)
+        }
+    };
+    let output = bundler::compile_standalone(&dist_dir, &runtime_binary, &output)?;
+
+    println!("\nCompile complete!");
+    println!("  Output: {}", output.display());
+
+    Ok(())
+}
+
 fn cmd_bundle(app_dir: &Path) -> Result<()> {
     println!("Bundling app at {}", app_dir.display());
 
@@ -1220,6 +1306,31 @@ fn main() -> Result<()> {
                 .ok_or_else(|| anyhow!("Usage: forge build <app-dir>"))?;
             cmd_build(&app_dir)?;
         }
+        "compile" => {
+            let mut app_dir = None;
+            let mut output = None;
+
+            let mut i = 0;
+            while i < args.len() {
+                if args[i] == "--output" || args[i] == "-o" {
+                    if i + 1 < args.len() {
+                        output = Some(PathBuf::from(&args[i + 1]));
+                        i += 2;
+                    } else {
+                        return Err(anyhow!("--output requires a value"));
+                    }
+                } else if !args[i].starts_with('-') {
+                    app_dir = Some(PathBuf::from(&args[i]));
+                    i += 1;
+                } else {
+                    return Err(anyhow!("Unknown flag: {}", args[i]));
+                }
+            }
+
+            let app_dir = app_dir
+                .ok_or_else(|| anyhow!("Usage: forge compile [--output <path>] <app-dir>"))?;
+            cmd_compile(&app_dir, output.as_deref())?;
+        }
         "bundle" => {
             let app_dir = args
                 .first()
@@ -1256,6 +1367,13 @@ fn main() -> Result<()> {
         "icon" => {
             cmd_icon(&args)?;
         }
+        "audit" => {
+            if args.iter().any(|a| a == "--help" || a == "-h") {
+                audit::usage();
+            } else {
+                audit::run(&args)?;
+            }
+        }
         "docs" => {
             if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
                 docs::usage();
@@ -11,8 +11,23 @@ declare global {
 
 type ListenerCallback = (payload: unknown) => void;
 
+interface InvokeOptions {
+  /** Reject with code 7004 if no reply arrives in time (ms) */
+  timeoutMs?: number;
+  /** Cancel the invoke; the Deno handler's signal is aborted too */
+  signal?: AbortSignal;
+}
+
+/** Error an invoke() promise rejects with */
+interface IpcInvokeError extends Error {
+  /** 7002 denied, 7004 timeout, 7005 cancelled, 7006 no handler, 7007 handler error, or the handler's own code */
+  code: number;
+  /** Extra data attached by the handler */
+  data?: unknown;
+}
+
 interface HostBridge {
-  /** Register a listener for messages from Deno */
+  /** Register a listener for messages from Deno (binary sends arrive as Uint8Array or ReadableStream) */
   on(channel: string, cb: ListenerCallback): void;
   /** Remove a listener */
   off(channel: string, cb: ListenerCallback): void;
@@ -20,6 +35,8 @@ interface HostBridge {
   emit(channel: string, payload?: unknown): void;
   /** Send a message to Deno (via IPC) */
   send(channel: string, payload?: unknown): void;
+  /** Call a Deno handle() and await its reply */
+  invoke<T = unknown>(channel: string, payload?: unknown, options?: InvokeOptions): Promise<T>;
 }
 
 // HMR connection function (hoisted to avoid inner declaration warning)
@@ -125,9 +142,26 @@ function connectHMR(
 
   const listeners = new Map<string, ListenerCallback[]>();
 
-  // Internal dispatch function called by host when sending messages to renderer
-  globalThis.__host_dispatch = function (msg: { channel: string; payload: unknown }) {
-    const { channel, payload } = msg;
+  // Pending invoke() calls awaiting a reply from Deno
+  type InvokeReply = {
+    invokeId: string;
+    ok: boolean;
+    value?: unknown;
+    error?: { name: string; code: number; message: string; data?: unknown };
+  };
+  const pendingInvokes = new Map<string, (reply: InvokeReply) => void>();
+  let invokeCounter = 0;
+
+  function invokeError(name: string, code: number, message: string, data?: unknown): IpcInvokeError {
+    const err = new Error(message) as IpcInvokeError;
+    err.name = name;
+    err.code = code;
+    err.data = data;
+    return err;
+  }
+
+  // Deliver a message from Deno to local listeners
+  function deliver(channel: string, payload: unknown) {
     const arr = listeners.get(channel) || [];
     for (const cb of arr) {
       try {
@@ -136,6 +170,62 @@ function connectHMR(
         console.error("[host.dispatch] Error in listener:", e);
       }
     }
+  }
+
+  // Binary payloads arrive as descriptors whose bytes are served by the
+  // app:// protocol. Messages are still delivered in the order they were
+  // sent, so a message sent after a binary one waits for its fetch.
+  let delivery: Promise<void> = Promise.resolve();
+
+  function binaryUrl(path: string): string {
+    return `app://__forge_ipc__/${path}`;
+  }
+
+  function openBinaryStream(id: string): ReadableStream<Uint8Array> {
+    return new ReadableStream<Uint8Array>({
+      async pull(controller) {
+        const res = await fetch(binaryUrl(`stream/${id}`));
+        if (res.status === 200) {
+          controller.enqueue(new Uint8Array(await res.arrayBuffer()));
+        } else if (res.status === 204) {
+          controller.close();
+        } else {
+          controller.error(new Error(`IPC stream ${id} was aborted`));
+        }
+      },
+      cancel() {
+        fetch(binaryUrl(`stream/${id}/cancel`)).catch(() => {});
+      },
+    });
+  }
+
+  // Internal dispatch function called by host when sending messages to renderer
+  globalThis.__host_dispatch = function (msg: { channel: string; payload: unknown }) {
+    const { channel, payload } = msg;
+    // Replies to invoke() are routed to the waiting promise, not to listeners
+    if (channel === "__invoke_reply__") {
+      const reply = payload as InvokeReply;
+      pendingInvokes.get(reply.invokeId)?.(reply);
+      return;
+    }
+
+    const ref = payload as { __forgeBinary?: unknown; __forgeStream?: unknown } | null;
+    if (ref && typeof ref.__forgeBinary === "string") {
+      const bytes = fetch(binaryUrl(`blob/${ref.__forgeBinary}`))
+        .then((res) => res.arrayBuffer())
+        .then((buf) => new Uint8Array(buf));
+      delivery = delivery
+        .then(() => bytes)
+        .then(
+          (data) => deliver(channel, data),
+          (e) => console.error("[host.dispatch] Failed to fetch binary payload:", e)
+        );
+    } else if (ref && typeof ref.__forgeStream === "string") {
+      const stream = openBinaryStream(ref.__forgeStream);
+      delivery = delivery.then(() => deliver(channel, stream));
+    } else {
+      delivery = delivery.then(() => deliver(channel, payload));
+    }
   };
 
   // Public API exposed to renderer scripts
@@ -173,6 +263,50 @@ function connectHMR(
       const msg = JSON.stringify({ channel, payload });
       sendViaIpc(msg);
     },
+
+    invoke<T = unknown>(channel: string, payload?: unknown, options: InvokeOptions = {}): Promise<T> {
+      const invokeId = `${Date.now().toString(36)}-${++invokeCounter}`;
+      return new Promise<T>((resolve, reject) => {
+        if (options.signal?.aborted) {
+          reject(invokeError("IpcInvokeError", 7005, `Invoke on '${channel}' was cancelled`));
+          return;
+        }
+
+        let timer: ReturnType<typeof setTimeout> | undefined;
+        const settle = () => {
+          pendingInvokes.delete(invokeId);
+          if (timer !== undefined) clearTimeout(timer);
+          options.signal?.removeEventListener("abort", onAbort);
+        };
+        // Give up locally and tell Deno so the handler's signal is aborted
+        const cancel = (err: IpcInvokeError) => {
+          settle();
+          sendViaIpc(JSON.stringify({ channel: "__invoke_cancel__", invokeId }));
+          reject(err);
+        };
+        const onAbort = () =>
+          cancel(invokeError("IpcInvokeError", 7005, `Invoke on '${channel}' was cancelled`));
+
+        pendingInvokes.set(invokeId, (reply) => {
+          settle();
+          if (reply.ok) {
+            resolve(reply.value as T);
+          } else {
+            const e = reply.error!;
+            reject(invokeError(e.name, e.code, e.message, e.data));
+          }
+        });
+        options.signal?.addEventListener("abort", onAbort);
+        if (options.timeoutMs !== undefined) {
+          const ms = options.timeoutMs;
+          timer = setTimeout(
+            () => cancel(invokeError("IpcInvokeError", 7004, `Invoke on '${channel}' timed out after ${ms}ms`)),
+            ms
+          );
+        }
+        sendViaIpc(JSON.stringify({ channel, payload, invokeId }));
+      });
+    },
   };
 
   // Signal to backend that renderer is ready to receive messages.
@@ -20,6 +20,11 @@ declare const Deno: {
       op_app_show(): Promise<void>;
       op_app_set_badge_count(count: number | null): Promise<void>;
       op_app_set_user_model_id(appId: string): void;
+      op_app_permission_grants(): PermissionGrant[];
+      op_app_revoke_permission(capability: string, resource: string | null): number;
+      op_app_audit_subscribe(): number;
+      op_app_audit_next(id: number): Promise<AuditEvent | null>;
+      op_app_audit_unsubscribe(id: number): void;
     };
   };
 };
@@ -44,6 +49,42 @@ export interface LocaleInfo {
   locale: string;
 }
 
+/**
+ * A decision remembered from a runtime permission prompt
+ */
+export interface PermissionGrant {
+  /** Capability name (e.g., "fs.read", "fs.write", "net", "process.spawn") */
+  capability: string;
+  /** Path, host or binary the decision applies to */
+  resource: string;
+  /** Whether access was allowed or denied */
+  allowed: boolean;
+  /** true for "Always Allow"/"Deny", false for "Allow Once" (this session only) */
+  persisted: boolean;
+  /** RFC 3339 timestamp of the decision */
+  grantedAt: string;
+}
+
+/**
+ * One allow/deny decision made by the runtime's capability checks
+ */
+export interface AuditEvent {
+  /** RFC 3339 timestamp of the decision */
+  timestamp: string;
+  /** Capability name (e.g., "fs.read", "net", "process.spawn", "ui.channel") */
+  capability: string;
+  /** Path, host, binary, variable or channel that was checked */
+  resource: string;
+  /** Whether access was allowed */
+  allowed: boolean;
+  /** Extension whose op triggered the check, when known (e.g., "fs") */
+  extension?: string;
+  /** Op that triggered the check, when known (e.g., "op_fs_read_text") */
+  op?: string;
+  /** true when recorded under `forge dev`, where every check is allowed */
+  devMode: boolean;
+}
+
 /**
  * Types of special paths that can be requested
  */
@@ -199,6 +240,46 @@ export function setUserModelId(appId: string): void {
   return core.ops.op_app_set_user_model_id(appId);
 }
 
+/**
+ * List decisions remembered from permission prompts.
+ * Prompts are enabled per permission with `prompt = true` in manifest.app.toml.
+ * @returns Session and persisted grants
+ */
+export function permissionGrants(): PermissionGrant[] {
+  return core.ops.op_app_permission_grants();
+}
+
+/**
+ * Revoke remembered permission decisions so the user is prompted again.
+ * @param capability - Capability name (e.g., "fs.read", "net")
+ * @param resource - Only revoke the decision for this path/host/binary
+ * @returns Number of decisions removed
+ */
+export function revokePermission(capability: string, resource?: string): number {
+  return core.ops.op_app_revoke_permission(capability, resource ?? null);
+}
+
+/**
+ * Stream capability decisions as they are made.
+ * Every decision is also appended to the rotating audit log on disk.
+ * @example
+ * for await (const event of auditEvents()) {
+ *   if (!event.allowed) console.warn(`denied ${event.capability} ${event.resource}`);
+ * }
+ */
+export async function* auditEvents(): AsyncGenerator<AuditEvent, void, unknown> {
+  const id = core.ops.op_app_audit_subscribe();
+  try {
+    while (true) {
+      const event = await core.ops.op_app_audit_next(id);
+      if (event === null) return;
+      yield event;
+    }
+  } finally {
+    core.ops.op_app_audit_unsubscribe(id);
+  }
+}
+
 // Convenience exports
 export const version = getVersion;
 export const name = getName;
@@ -227,6 +308,11 @@ interface OpRegistry {
   show: { args: []; result: void };
   setBadgeCount: { args: []; result: void };
   setUserModelId: { args: []; result: void };
+  permissionGrants: { args: []; result: void };
+  revokePermission: { args: []; result: void };
+  auditSubscribe: { args: []; result: void };
+  auditNext: { args: []; result: void };
+  auditUnsubscribe: { args: []; result: void };
 }
 
 /** Extract argument types for an operation */
@@ -236,7 +322,7 @@ type OpArgs<T extends keyof OpRegistry> = OpRegistry[T]['args'];
 type OpResult<T extends keyof OpRegistry> = OpRegistry[T]['result'];
 
 /** Valid operation names for this extension */
-type OpName = "quit" | "exit" | "relaunch" | "getVersion" | "getName" | "getIdentifier" | "getPath" | "isPackaged" | "getLocale" | "requestSingleInstanceLock" | "releaseSingleInstanceLock" | "focus" | "hide" | "show" | "setBadgeCount" | "setUserModelId";
+type OpName = "quit" | "exit" | "relaunch" | "getVersion" | "getName" | "getIdentifier" | "getPath" | "isPackaged" | "getLocale" | "requestSingleInstanceLock" | "releaseSingleInstanceLock" | "focus" | "hide" | "show" | "setBadgeCount" | "setUserModelId" | "permissionGrants" | "revokePermission" | "auditSubscribe" | "auditNext" | "auditUnsubscribe";
 
 /** Hook callback types */
 type BeforeHookCallback<T extends OpName> = (args: OpArgs<T>) => void | Promise<void>;
@@ -50,6 +50,12 @@
  * - Rollback support for failed migrations
  * - Migration status inspection
  *
+ * ### Encryption, Backups and Dumps
+ * - SQLCipher-compatible encryption at rest with an optional key, and rekeying
+ * - Online backups through the SQLite backup API, with progress reporting
+ * - ATTACH/DETACH between the app's databases
+ * - Export to and import from a SQL dump
+ *
  * ## Database Location
  *
  * Databases are stored at:
@@ -76,6 +82,8 @@
  * - `8413` - Migration error (invalid version, failed migration)
  * - `8414` - Invalid parameter (wrong count, null where not allowed)
  * - `8415` - Stream error (invalid stream, already closed)
+ * - `8416` - Encryption error (wrong or missing key, key operation on a plain database)
+ * - `8417` - Backup error (target open or being written, backup not found)
  *
  * ## Performance Tips
  *
@@ -101,7 +109,7 @@ declare const Deno: {
   core: {
     ops: {
       // Connection Management
-      op_database_open(name: string, opts?: OpenOptions): Promise<RawOpenResult>;
+      op_database_open(name: string, opts?: RawOpenOptions): Promise<RawOpenResult>;
       op_database_close(dbId: string): Promise<void>;
       op_database_list(): Promise<RawDatabaseInfo[]>;
       op_database_delete(name: string): Promise<boolean>;
@@ -144,6 +152,16 @@ declare const Deno: {
       op_database_migrate(dbId: string, migrations: Migration[]): Promise<RawMigrationStatus>;
       op_database_migration_status(dbId: string): Promise<RawMigrationStatus>;
       op_database_migrate_down(dbId: string, targetVersion?: number): Promise<RawMigrationStatus>;
+
+      // Encryption, Backup and Dumps
+      op_database_rekey(dbId: string, key: string): Promise<void>;
+      op_database_backup(dbId: string, target: string, opts?: RawBackupOptions): Promise<string>;
+      op_database_backup_progress(backupId: string): Promise<RawBackupProgress | null>;
+      op_database_backup_finish(backupId: string): Promise<RawBackupProgress>;
+      op_database_attach(dbId: string, name: string, alias: string, key?: string): Promise<void>;
+      op_database_detach(dbId: string, alias: string): Promise<void>;
+      op_database_export(dbId: string, opts?: RawExportOptions): Promise<string>;
+      op_database_import(dbId: string, sql: string): Promise<void>;
     };
   };
 };
@@ -152,6 +170,15 @@ declare const Deno: {
 // Raw Types (from Rust, snake_case)
 // =============================================================================
 
+export interface RawOpenOptions {
+  create?: boolean;
+  readonly?: boolean;
+  wal_mode?: boolean;
+  busy_timeout_ms?: number;
+  foreign_keys?: boolean;
+  key?: string;
+}
+
 export interface RawOpenResult {
   id: string;
   path: string;
@@ -164,6 +191,7 @@ export interface RawDatabaseInfo {
   size_bytes: number;
   tables: string[];
   readonly: boolean;
+  encrypted: boolean;
 }
 
 export interface RawColumnInfo {
@@ -235,6 +263,22 @@ export interface RawMigrationStatus {
   applied: RawAppliedMigration[];
 }
 
+export interface RawBackupOptions {
+  key?: string;
+  pages_per_step?: number;
+  step_delay_ms?: number;
+}
+
+export interface RawBackupProgress {
+  pages_done: number;
+  pages_total: number;
+}
+
+export interface RawExportOptions {
+  tables?: string[];
+  schema_only?: boolean;
+}
+
 // =============================================================================
 // Public Types (camelCase, user-facing)
 // =============================================================================
@@ -260,6 +304,9 @@ export interface RawMigrationStatus {
  *
  * // Fail if database doesn't exist
  * const db3 = await open("existing", { create: false });
+ *
+ * // Encrypted at rest with SQLCipher
+ * const db4 = await open("customers", { key: secret });
  * ```
  */
 export interface OpenOptions {
@@ -273,6 +320,13 @@ export interface OpenOptions {
   busyTimeoutMs?: number;
   /** Enable foreign keys (default: true) */
   foreignKeys?: boolean;
+  /**
+   * SQLCipher encryption key. A new database is created encrypted; an existing
+   * one must have been encrypted with this key. `x'<64 hex digits>'` is used as
+   * a raw key instead of a passphrase. Needs the runtime built with database
+   * encryption; otherwise any key fails with `8416`.
+   */
+  key?: string;
 }
 
 /**
@@ -337,6 +391,8 @@ export interface DatabaseInfo {
   tables: string[];
   /** True if opened in read-only mode */
   readonly: boolean;
+  /** True if the file is encrypted; its tables aren't listed without the key */
+  encrypted: boolean;
 }
 
 /**
@@ -626,6 +682,51 @@ export interface MigrationStatus {
   applied: AppliedMigration[];
 }
 
+/**
+ * Options for an online backup.
+ *
+ * @example
+ * ```typescript
+ * await db.backup("myapp-backup", {
+ *   pagesPerStep: 500,
+ *   onProgress: (p) => console.log(`${p.pagesDone}/${p.pagesTotal} pages`),
+ * });
+ * ```
+ */
+export interface BackupOptions {
+  /**
+   * Encryption key for the copy (default: the source database's key). Only an
+   * encrypted database can be backed up with a key.
+   */
+  key?: string;
+  /** Pages copied per step (default: 100) */
+  pagesPerStep?: number;
+  /** Pause between steps in milliseconds, giving writers room (default: 0) */
+  stepDelayMs?: number;
+  /** Called as pages are copied. Updates may be skipped if the callback is slow */
+  onProgress?: (progress: BackupProgress) => void;
+}
+
+/**
+ * Progress of an online backup, in database pages.
+ */
+export interface BackupProgress {
+  /** Pages copied so far */
+  pagesDone: number;
+  /** Pages in the source database */
+  pagesTotal: number;
+}
+
+/**
+ * Options for exporting a SQL dump.
+ */
+export interface ExportOptions {
+  /** Only dump these tables, with their indexes and triggers (default: all) */
+  tables?: string[];
+  /** Leave out rows, dumping only the schema (default: false) */
+  schemaOnly?: boolean;
+}
+
 // =============================================================================
 // Database Interface
 // =============================================================================
@@ -1210,6 +1311,114 @@ export interface Database {
    */
   migrateDown(targetVersion?: number): Promise<MigrationStatus>;
 
+  // Encryption, backup and dumps
+
+  /**
+   * Re-encrypt the database with a new key.
+   *
+   * Only works on a database opened with a key. To encrypt a plain database,
+   * import a dump of it into a database opened with a key.
+   *
+   * @param key - New encryption key
+   *
+   * @throws Error [8416] if the database is not encrypted
+   *
+   * @example
+   * ```typescript
+   * const db = await open("customers", { key: oldSecret });
+   * await db.rekey(newSecret);
+   * ```
+   */
+  rekey(key: string): Promise<void>;
+
+  /**
+   * Copy the database into another of the app's databases while it stays in use.
+   *
+   * Uses the SQLite backup API through a separate connection, so queries keep
+   * running during the backup. The target is replaced once the copy is
+   * complete and must not be open. Plain and encrypted databases can't be
+   * backed up into each other; use `exportSql()` and `importSql()` instead.
+   *
+   * @param target - Name of the database to write the backup to
+   * @param opts - Encryption key, step size and progress callback
+   * @returns Final progress
+   *
+   * @throws Error [8416] if a key is given for a plain database
+   * @throws Error [8417] if the target database is open or another backup is writing to it
+   *
+   * @example
+   * ```typescript
+   * await db.backup(`customers-${new Date().toISOString().slice(0, 10)}`, {
+   *   onProgress: (p) => updateProgressBar(p.pagesDone / p.pagesTotal),
+   * });
+   * ```
+   */
+  backup(target: string, opts?: BackupOptions): Promise<BackupProgress>;
+
+  /**
+   * Attach another of the app's databases under an alias.
+   *
+   * Its tables are then available as `alias.table` on this connection. Without
+   * a key, an encrypted connection assumes its own key; pass `""` to attach a
+   * plain database to an encrypted one.
+   *
+   * @param name - Database name (without .db extension)
+   * @param alias - Schema name to attach it as
+   * @param key - Encryption key of the attached database
+   *
+   * @throws Error [8401] if the database doesn't exist
+   * @throws Error [8416] if the key is wrong
+   *
+   * @example
+   * ```typescript
+   * await db.attach("archive", "archive");
+   * await db.execute("INSERT INTO archive.orders SELECT * FROM orders WHERE year < 2020");
+   * await db.detach("archive");
+   * ```
+   */
+  attach(name: string, alias: string, key?: string): Promise<void>;
+
+  /**
+   * Detach a database attached with `attach()`.
+   *
+   * @param alias - Schema name it was attached as
+   */
+  detach(alias: string): Promise<void>;
+
+  /**
+   * Export the database as a SQL dump.
+   *
+   * The dump recreates tables, rows, indexes, triggers and views. It is plain
+   * text even when the database is encrypted.
+   *
+   * @param opts - Tables to include and whether to leave out rows
+   * @returns SQL text
+   *
+   * @example
+   * ```typescript
+   * const sql = await db.exportSql();
+   * await writeTextFile("backup.sql", sql);
+   * ```
+   */
+  exportSql(opts?: ExportOptions): Promise<string>;
+
+  /**
+   * Run a SQL dump against the database, rolling back if any statement fails.
+   *
+   * Dumps create their tables, so import into an empty database.
+   *
+   * @param sql - SQL text, typically from `exportSql()`
+   *
+   * @example
+   * ```typescript
+   * // Encrypt an existing plain database
+   * const plain = await open("legacy");
+   * const encrypted = await open("legacy-encrypted", { key: secret });
+   * await encrypted.importSql(await plain.exportSql());
+   * ```
+   */
+  importSql(sql: string): Promise<void>;
+
   // Maintenance
 
   /**
@@ -1394,6 +1603,11 @@ function toTableInfo(raw: RawTableInfo): TableInfo {
   };
 }
 
+/** Convert raw backup progress to public format */
+function toBackupProgress(raw: RawBackupProgress): BackupProgress {
+  return { pagesDone: raw.pages_done, pagesTotal: raw.pages_total };
+}
+
 /** Convert raw migration status to public format */
 function toMigrationStatus(raw: RawMigrationStatus): MigrationStatus {
   return {
@@ -1562,6 +1776,49 @@ function createDatabase(result: RawOpenResult, name: string): Database {
       return toMigrationStatus(raw);
     },
 
+    async rekey(key: string): Promise<void> {
+      await core.ops.op_database_rekey(dbId, key);
+    },
+
+    async backup(target: string, opts: BackupOptions = {}): Promise<BackupProgress> {
+      const backupId = await core.ops.op_database_backup(dbId, target, {
+        key: opts.key,
+        pages_per_step: opts.pagesPerStep,
+        step_delay_ms: opts.stepDelayMs,
+      });
+      if (opts.onProgress) {
+        try {
+          let progress: RawBackupProgress | null;
+          while ((progress = await core.ops.op_database_backup_progress(backupId)) !== null) {
+            opts.onProgress(toBackupProgress(progress));
+          }
+        } catch (e) {
+          await core.ops.op_database_backup_finish(backupId).catch(() => {});
+          throw e;
+        }
+      }
+      return toBackupProgress(await core.ops.op_database_backup_finish(backupId));
+    },
+
+    async attach(name: string, alias: string, key?: string): Promise<void> {
+      await core.ops.op_database_attach(dbId, name, alias, key);
+    },
+
+    async detach(alias: string): Promise<void> {
+      await core.ops.op_database_detach(dbId, alias);
+    },
+
+    async exportSql(opts?: ExportOptions): Promise<string> {
+      return await core.ops.op_database_export(dbId, {
+        tables: opts?.tables,
+        schema_only: opts?.schemaOnly,
+      });
+    },
+
+    async importSql(sql: string): Promise<void> {
+      await core.ops.op_database_import(dbId, sql);
+    },
+
     async vacuum(): Promise<void> {
       await core.ops.op_database_vacuum(dbId);
     },
@@ -1634,6 +1891,7 @@ function createPreparedStatement(
  * @throws Error [8401] if database doesn't exist and `create: false`
  * @throws Error [8408] if permission denied
  * @throws Error [8412] if I/O error (disk full, etc.)
+ * @throws Error [8416] if the database is encrypted and the key is missing or wrong
  *
  * @example
  * ```typescript
@@ -1705,7 +1963,15 @@ function createPreparedStatement(
  * ```
  */
 export async function open(name: string, opts?: OpenOptions): Promise<Database> {
-  const result = await core.ops.op_database_open(name, opts);
+  // Convert camelCase to snake_case for Rust
+  const result = await core.ops.op_database_open(name, opts && {
+    create: opts.create,
+    readonly: opts.readonly,
+    wal_mode: opts.walMode,
+    busy_timeout_ms: opts.busyTimeoutMs,
+    foreign_keys: opts.foreignKeys,
+    key: opts.key,
+  });
   return createDatabase(result, name);
 }
 
@@ -1749,6 +2015,7 @@ export async function list(): Promise<DatabaseInfo[]> {
     sizeBytes: db.size_bytes,
     tables: db.tables,
     readonly: db.readonly,
+    encrypted: db.encrypted,
   }));
 }
 
@@ -1903,6 +2170,14 @@ interface OpRegistry {
   migrate: { args: []; result: void };
   migrationStatus: { args: []; result: void };
   migrateDown: { args: []; result: void };
+  rekey: { args: []; result: void };
+  backup: { args: []; result: void };
+  backupProgress: { args: []; result: void };
+  backupFinish: { args: []; result: void };
+  attach: { args: []; result: void };
+  detach: { args: []; result: void };
+  export: { args: []; result: void };
+  import: { args: []; result: void };
 }
 
 /** Extract argument types for an operation */
@@ -1912,7 +2187,7 @@ type OpArgs<T extends keyof OpRegistry> = OpRegistry[T]['args'];
 type OpResult<T extends keyof OpRegistry> = OpRegistry[T]['result'];
 
 /** Valid operation names for this extension */
-type OpName = "open" | "close" | "list" | "delete" | "exists" | "path" | "vacuum" | "query" | "execute" | "executeBatch" | "queryRow" | "queryValue" | "prepare" | "stmtQuery" | "stmtExecute" | "stmtFinalize" | "begin" | "commit" | "rollback" | "savepoint" | "release" | "rollbackTo" | "tables" | "tableInfo" | "tableExists" | "streamOpen" | "streamNext" | "streamClose" | "migrate" | "migrationStatus" | "migrateDown";
+type OpName = "open" | "close" | "list" | "delete" | "exists" | "path" | "vacuum" | "query" | "execute" | "executeBatch" | "queryRow" | "queryValue" | "prepare" | "stmtQuery" | "stmtExecute" | "stmtFinalize" | "begin" | "commit" | "rollback" | "savepoint" | "release" | "rollbackTo" | "tables" | "tableInfo" | "tableExists" | "streamOpen" | "streamNext" | "streamClose" | "migrate" | "migrationStatus" | "migrateDown" | "rekey" | "backup" | "backupProgress" | "backupFinish" | "attach" | "detach" | "export" | "import";
 
 /** Hook callback types */
 type BeforeHookCallback<T extends OpName> = (args: OpArgs<T>) => void | Promise<void>;
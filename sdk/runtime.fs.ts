@@ -9,9 +9,13 @@
  *
  * ## Features
  * - File I/O (text and binary)
+ * - Random-access file handles
+ * - Chunked file streams with backpressure
  * - Directory operations (create, read, remove)
- * - File watching with async iteration
- * - Symbolic link management
+ * - Recursive walk, glob, and directory copy/move with progress
+ * - Debounced, filtered and rename-aware file watching
+ * - Symbolic and hard link management
+ * - Permissions, ownership, timestamps and advisory file locks
  * - File metadata and statistics
  * - Temporary file/directory creation
  * - Cross-platform path resolution
@@ -35,6 +39,10 @@
  * - 3007: Invalid watch ID
  * - 3008: Symbolic link error
  * - 3009: Temporary file/directory creation error
+ * - 3010: Invalid or closed file handle
+ * - 3011: Invalid glob pattern
+ * - 3012: Lock not acquired before the timeout
+ * - 3013: Operation not supported on this platform
  *
  * @example
  * ```typescript
@@ -64,7 +72,7 @@ declare const Deno: {
       op_fs_rename(from: string, to: string): Promise<void>;
       op_fs_copy(from: string, to: string): Promise<void>;
       op_fs_exists(path: string): Promise<boolean>;
-      op_fs_watch(path: string): Promise<number>;
+      op_fs_watch(path: string, opts: WatchOptions): Promise<number>;
       op_fs_watch_next(watchId: number): Promise<WatchEvent | null>;
       op_fs_watch_close(watchId: number): Promise<void>;
       // Enhanced operations
@@ -76,6 +84,39 @@ declare const Deno: {
       op_fs_real_path(path: string): Promise<string>;
       op_fs_temp_file(prefix: string | null, suffix: string | null): Promise<TempFileResult>;
       op_fs_temp_dir(prefix: string | null): Promise<TempDirResult>;
+      // File handles
+      op_fs_open(path: string, opts: OpenOptions): Promise<number>;
+      op_fs_file_read(rid: number, buffer: Uint8Array): Promise<number | null>;
+      op_fs_file_write(rid: number, data: Uint8Array): Promise<number>;
+      op_fs_file_seek(rid: number, offset: number, whence: SeekWhence | null): Promise<number>;
+      op_fs_file_truncate(rid: number, len: number): Promise<void>;
+      op_fs_file_sync(rid: number): Promise<void>;
+      op_fs_file_stat(rid: number): Promise<FileStat>;
+      op_fs_file_close(rid: number): void;
+      // File streams
+      op_fs_read_stream_open(path: string, opts: ReadStreamOptions): Promise<number>;
+      op_fs_read_stream_next(rid: number): Promise<Uint8Array>;
+      op_fs_read_stream_close(rid: number): void;
+      op_fs_write_stream_open(path: string, opts: WriteStreamOptions): Promise<number>;
+      op_fs_write_stream_write(rid: number, chunk: Uint8Array): Promise<void>;
+      op_fs_write_stream_close(rid: number): Promise<void>;
+      op_fs_write_stream_abort(rid: number): void;
+      // Walk, glob and bulk operations
+      op_fs_walk_open(path: string, opts: WalkOptions): Promise<number>;
+      op_fs_walk_next(rid: number): Promise<WalkEntry[]>;
+      op_fs_walk_close(rid: number): void;
+      op_fs_glob(pattern: string): Promise<string[]>;
+      op_fs_copy_dir(from: string, to: string, opts: { overwrite?: boolean }): Promise<number>;
+      op_fs_move(from: string, to: string, opts: { overwrite?: boolean }): Promise<number>;
+      op_fs_transfer_progress(rid: number): Promise<TransferProgress | null>;
+      op_fs_transfer_finish(rid: number): Promise<TransferProgress>;
+      // Permissions, ownership, timestamps and locks
+      op_fs_chmod(path: string, mode: number): Promise<void>;
+      op_fs_chown(path: string, uid: number | null, gid: number | null): Promise<void>;
+      op_fs_utime(path: string, atime: number, mtime: number): Promise<void>;
+      op_fs_link(target: string, path: string): Promise<void>;
+      op_fs_lock(path: string, opts: LockOptions & { wait?: boolean }): Promise<number | null>;
+      op_fs_unlock(rid: number): void;
     };
   };
 };
@@ -140,19 +181,39 @@ export interface RemoveOptions {
 }
 
 /**
- * Event emitted by a file watcher.
+ * Options for `watch()`.
+ */
+export interface WatchOptions {
+  /** Quiet period before a path's changes are reported, in ms (default: 50) */
+  debounceMs?: number;
+  /** Only report paths, relative to the watched path, matching one of these globs */
+  include?: string[];
+  /** Never report paths matching one of these globs */
+  exclude?: string[];
+  /** Watch subdirectories too (default: true) */
+  recursive?: boolean;
+}
+
+/**
+ * Kind of a watch event.
+ */
+export type WatchEventKind = "create" | "modify" | "remove" | "rename";
+
+/**
+ * Debounced event emitted by a file watcher.
  */
 export interface WatchEvent {
+  /** What happened to the path */
+  kind: WatchEventKind;
   /**
-   * Type of filesystem event.
-   * Common values: "create", "modify", "remove", "rename"
-   */
-  kind: string;
-  /**
-   * Paths affected by this event.
-   * May contain one or more paths depending on the event type.
+   * Affected paths.
+   * One path, or for a rename the old path then the new one.
    */
   paths: string[];
+  /** Old path of a rename */
+  from?: string;
+  /** New path of a rename */
+  to?: string;
 }
 
 /**
@@ -198,6 +259,190 @@ export interface Watcher {
   close(): Promise<void>;
 }
 
+/**
+ * Options for opening a file handle.
+ *
+ * With no flags set, the file is opened read-only.
+ */
+export interface OpenOptions {
+  /** Allow reading. Defaults to true unless `write` or `append` is set */
+  read?: boolean;
+  /** Allow writing */
+  write?: boolean;
+  /** Write at the end of the file */
+  append?: boolean;
+  /** Create the file if it does not exist */
+  create?: boolean;
+  /** Create the file, failing if it already exists */
+  createNew?: boolean;
+  /** Truncate the file to zero length when opened */
+  truncate?: boolean;
+}
+
+/**
+ * Origin of a seek: the start of the file, the current position, or the end.
+ */
+export type SeekWhence = "start" | "current" | "end";
+
+/**
+ * Options for `openReadStream()`.
+ */
+export interface ReadStreamOptions {
+  /** Bytes per chunk (default: 65536) */
+  chunkSize?: number;
+  /** Byte offset to start reading at (default: 0) */
+  start?: number;
+  /** Byte offset to stop reading at, exclusive (default: end of file) */
+  end?: number;
+}
+
+/**
+ * Options for `openWriteStream()` and `writeStream()`.
+ */
+export interface WriteStreamOptions {
+  /** Append to the file instead of replacing its contents */
+  append?: boolean;
+  /** Create the file if it does not exist (default: true) */
+  create?: boolean;
+  /** Fail if the file already exists */
+  createNew?: boolean;
+}
+
+/**
+ * Chunked reader over a file's contents.
+ *
+ * Chunks are read from disk only when pulled, so a slow consumer never
+ * causes the file to be buffered in memory. Iterating with for-await closes
+ * the stream when the loop ends.
+ */
+export interface FileReadStream extends AsyncIterable<Uint8Array> {
+  /** Resource ID of the stream */
+  readonly rid: number;
+  /** Read the next chunk, or null at the end of the file or range */
+  read(): Promise<Uint8Array | null>;
+  /** Stop reading and close the file */
+  cancel(): void;
+  /**
+   * Wrap the stream in a Web `ReadableStream`.
+   * Without a `ReadableStream` global, returns a minimal stand-in that
+   * supports `getReader()`, `pipeTo()`, `cancel()` and for-await.
+   */
+  toReadableStream(): ReadableStream<Uint8Array>;
+}
+
+/**
+ * Chunked writer into a file.
+ *
+ * `write()` resolves once the chunk is on its way to disk; awaiting each
+ * write applies backpressure to the producer.
+ */
+export interface FileWriteStream {
+  /** Resource ID of the stream */
+  readonly rid: number;
+  /** Write a chunk */
+  write(chunk: Uint8Array): Promise<void>;
+  /** Flush everything to disk and close the file */
+  close(): Promise<void>;
+  /** Close the file without flushing or syncing; data already written stays */
+  abort(): void;
+  /**
+   * Wrap the stream in a Web `WritableStream`.
+   * Without a `WritableStream` global, returns a minimal stand-in that
+   * supports `getWriter()`, `close()` and `abort()`.
+   */
+  toWritableStream(): WritableStream<Uint8Array>;
+}
+
+/**
+ * Options for `walk()`.
+ *
+ * Globs are matched against paths relative to the walk root, with `/` as
+ * separator; `*` does not cross directories, `**` does.
+ */
+export interface WalkOptions {
+  /** Deepest level to descend to; the root's children are at depth 1 */
+  maxDepth?: number;
+  /** Only yield entries matching one of these globs, e.g. `["**\/*.ts"]` */
+  include?: string[];
+  /** Skip entries matching one of these globs, without descending into them */
+  exclude?: string[];
+  /** Descend into symlinked directories (default: false) */
+  followSymlinks?: boolean;
+  /** Skip paths ignored by `.gitignore`, `.ignore` and `.git/info/exclude` (default: false) */
+  gitignore?: boolean;
+  /** Yield files and symlinks (default: true) */
+  includeFiles?: boolean;
+  /** Yield directories (default: true) */
+  includeDirs?: boolean;
+}
+
+/**
+ * Entry yielded by `walk()`.
+ */
+export interface WalkEntry {
+  /** Path of the entry, starting with the walk root */
+  path: string;
+  /** File name of the entry */
+  name: string;
+  /** Depth below the root (1 for the root's children) */
+  depth: number;
+  /** Whether this entry is a regular file */
+  isFile: boolean;
+  /** Whether this entry is a directory */
+  isDir: boolean;
+  /** Whether this entry is a symbolic link */
+  isSymlink: boolean;
+}
+
+/**
+ * Progress of `copyDir()` or `move()`.
+ */
+export interface TransferProgress {
+  /** Files (and symlinks) copied so far */
+  filesDone: number;
+  /** Files (and symlinks) to copy */
+  filesTotal: number;
+  /** Bytes copied so far */
+  bytesDone: number;
+  /** Bytes to copy */
+  bytesTotal: number;
+  /** Source path of the file last copied */
+  current: string | null;
+}
+
+/**
+ * Options for `copyDir()` and `move()`.
+ */
+export interface TransferOptions {
+  /**
+   * Replace an existing destination instead of failing. `copyDir()` merges
+   * into it, replacing files; `move()` removes it first.
+   * @default false
+   */
+  overwrite?: boolean;
+  /** Called as files are copied. Updates may be skipped if the callback is slow */
+  onProgress?: (progress: TransferProgress) => void;
+}
+
+/**
+ * Options for `lock()` and `tryLock()`.
+ */
+export interface LockOptions {
+  /**
+   * Take an exclusive (writer) lock. Pass false for a shared (reader) lock,
+   * which any number of holders can have at once.
+   * @default true
+   */
+  exclusive?: boolean;
+  /** Give up waiting after this many milliseconds (`lock()` only) */
+  timeout?: number;
+  /**
+   * Create the lock file if it does not exist (needs write permission).
+   * @default true
+   */
+  create?: boolean;
+}
+
 // Enhanced types
 export interface FileMetadataResult {
   is_file: boolean;
@@ -590,9 +835,18 @@ export async function exists(path: string): Promise<boolean> {
  * Returns a Watcher that can be used with async iteration or manual polling.
  * The watcher emits events for file creation, modification, deletion, and renaming.
  *
+ * Changes to a path are held until it has been quiet for `debounceMs`, then
+ * merged into one event, so a single save yields one `"modify"`. A path
+ * that keeps changing is still reported every 10 windows. A file
+ * created and removed within the window is not reported at all. Renames
+ * within the watched tree are reported as one `"rename"` event with `from`
+ * and `to`; a file moved out of the tree is a `"remove"`, one moved in is a
+ * `"create"`.
+ *
  * **Important:** Always call `watcher.close()` when done to clean up resources.
  *
  * @param path - Path to watch (file or directory)
+ * @param options - Debounce interval, include/exclude globs and recursion
  * @returns Watcher for receiving filesystem events
  *
  * @throws Error (3000) if I/O error occurs
@@ -618,6 +872,21 @@ export async function exists(path: string): Promise<boolean> {
  *
  * @example
  * ```typescript
+ * // Only TypeScript sources, ignoring build output
+ * const watcher = await watch("./src", {
+ *   include: ["**\/*.ts"],
+ *   exclude: ["dist/**"],
+ *   debounceMs: 100,
+ * });
+ * for await (const event of watcher) {
+ *   if (event.kind === "rename") {
+ *     console.log(`${event.from} -> ${event.to}`);
+ *   }
+ * }
+ * ```
+ *
+ * @example
+ * ```typescript
  * // Manual polling
  * const watcher = await watch("./config.json");
  * while (true) {
@@ -644,8 +913,8 @@ export async function exists(path: string): Promise<boolean> {
  * clearTimeout(timeout);
  * ```
  */
-export async function watch(path: string): Promise<Watcher> {
-  const watchId = await core.ops.op_fs_watch(path);
+export async function watch(path: string, options: WatchOptions = {}): Promise<Watcher> {
+  const watchId = await core.ops.op_fs_watch(path, options);
   return {
     id: watchId,
     async next(): Promise<WatchEvent | null> {
@@ -664,6 +933,784 @@ export async function watch(path: string): Promise<Watcher> {
   };
 }
 
+// ============================================================================
+// File Handles
+// ============================================================================
+
+// Close handles whose FsFile was garbage-collected without close()
+const fileRegistry = new FinalizationRegistry<number>((rid) => {
+  try {
+    core.ops.op_fs_file_close(rid);
+  } catch {
+    // Already closed
+  }
+});
+
+/**
+ * Handle to an open file for random-access reads and writes.
+ *
+ * Obtained from `open()`. Reads and writes happen at the current position,
+ * which `seek()` moves. Call `close()` when done; a handle that is
+ * garbage-collected while still open is closed automatically.
+ */
+export class FsFile {
+  #closed = false;
+
+  constructor(
+    /** Resource ID of the open file */
+    readonly rid: number
+  ) {
+    fileRegistry.register(this, rid, this);
+  }
+
+  /**
+   * Read into `buffer` at the current position.
+   * @returns Number of bytes read, or null at end of file
+   */
+  async read(buffer: Uint8Array): Promise<number | null> {
+    return await core.ops.op_fs_file_read(this.rid, buffer);
+  }
+
+  /**
+   * Write all of `data` at the current position.
+   * @returns Number of bytes written
+   */
+  async write(data: Uint8Array): Promise<number> {
+    return await core.ops.op_fs_file_write(this.rid, data);
+  }
+
+  /**
+   * Move the current position.
+   * @param offset - Byte offset relative to `whence` (may be negative for "current" and "end")
+   * @param whence - Origin of the offset (default: "start")
+   * @returns New position from the start of the file
+   */
+  async seek(offset: number, whence: SeekWhence = "start"): Promise<number> {
+    return await core.ops.op_fs_file_seek(this.rid, offset, whence);
+  }
+
+  /**
+   * Truncate or extend the file.
+   * @param len - New length in bytes (default: 0)
+   */
+  async truncate(len = 0): Promise<void> {
+    return await core.ops.op_fs_file_truncate(this.rid, len);
+  }
+
+  /** Flush file contents and metadata to disk */
+  async sync(): Promise<void> {
+    return await core.ops.op_fs_file_sync(this.rid);
+  }
+
+  /** Get statistics for the open file */
+  async stat(): Promise<FileStat> {
+    return await core.ops.op_fs_file_stat(this.rid);
+  }
+
+  /** Close the file. Operations still in flight finish first. */
+  close(): void {
+    if (this.#closed) return;
+    this.#closed = true;
+    fileRegistry.unregister(this);
+    core.ops.op_fs_file_close(this.rid);
+  }
+}
+
+/**
+ * Opens a file for random-access reads and writes.
+ *
+ * Reads need `read` permission for the path; writing, appending, creating
+ * or truncating needs `write` permission.
+ *
+ * @param path - Path to the file
+ * @param options - Access and creation flags (default: read-only)
+ * @returns Handle to the open file
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3002) if file not found
+ * @throws Error (3003) if `createNew` is set and the file exists
+ * @throws Error (3010) when used after `close()`
+ *
+ * @example
+ * ```typescript
+ * // Read the last 64 KiB of a large log without loading the whole file
+ * const file = await open("./logs/app.log");
+ * try {
+ *   const { size } = await file.stat();
+ *   await file.seek(Math.max(0, size - 65536));
+ *   const buffer = new Uint8Array(65536);
+ *   const read = await file.read(buffer);
+ *   console.log(new TextDecoder().decode(buffer.subarray(0, read ?? 0)));
+ * } finally {
+ *   file.close();
+ * }
+ * ```
+ *
+ * @example
+ * ```typescript
+ * // Patch bytes in place
+ * const file = await open("./data.bin", { read: true, write: true });
+ * await file.seek(16);
+ * await file.write(new Uint8Array([0xff, 0xff]));
+ * await file.sync();
+ * file.close();
+ * ```
+ */
+export async function open(path: string, options: OpenOptions = {}): Promise<FsFile> {
+  const rid = await core.ops.op_fs_open(path, options);
+  return new FsFile(rid);
+}
+
+// ============================================================================
+// File Streams
+// ============================================================================
+
+function webGlobal<T>(name: string): T | undefined {
+  return (globalThis as Record<string, unknown>)[name] as T | undefined;
+}
+
+/**
+ * Wraps a pull function in a `ReadableStream`, or in a minimal stand-in when
+ * the runtime has no Web streams. The stand-in covers what runtime modules
+ * consume: readers, `pipeTo()`, `cancel()` and async iteration.
+ */
+function readableFrom(
+  read: () => Promise<Uint8Array | null>,
+  cancel: () => void
+): ReadableStream<Uint8Array> {
+  const Readable = webGlobal<typeof ReadableStream>("ReadableStream");
+  if (Readable) {
+    return new Readable<Uint8Array>({
+      async pull(controller) {
+        const chunk = await read();
+        if (chunk === null) {
+          controller.close();
+        } else {
+          controller.enqueue(chunk);
+        }
+      },
+      cancel,
+    });
+  }
+
+  let locked = false;
+  const lock = (): void => {
+    if (locked) throw new TypeError("ReadableStream is locked");
+    locked = true;
+  };
+  const stream = {
+    get locked(): boolean {
+      return locked;
+    },
+    getReader() {
+      lock();
+      return {
+        async read(): Promise<ReadableStreamReadResult<Uint8Array>> {
+          const value = await read();
+          return value === null ? { done: true, value: undefined } : { done: false, value };
+        },
+        async cancel(): Promise<void> {
+          cancel();
+        },
+        releaseLock(): void {
+          locked = false;
+        },
+      };
+    },
+    async cancel(): Promise<void> {
+      cancel();
+    },
+    async pipeTo(destination: WritableStream<Uint8Array>): Promise<void> {
+      lock();
+      const writer = destination.getWriter();
+      try {
+        while (true) {
+          const chunk = await read();
+          if (chunk === null) break;
+          await writer.write(chunk);
+        }
+        await writer.close();
+      } catch (e) {
+        cancel();
+        await writer.abort(e).catch(() => {});
+        throw e;
+      } finally {
+        writer.releaseLock();
+      }
+    },
+    async *[Symbol.asyncIterator](): AsyncGenerator<Uint8Array, void, unknown> {
+      lock();
+      try {
+        while (true) {
+          const chunk = await read();
+          if (chunk === null) break;
+          yield chunk;
+        }
+      } finally {
+        cancel();
+      }
+    },
+  };
+  return stream as unknown as ReadableStream<Uint8Array>;
+}
+
+/**
+ * Wraps write/close/abort in a `WritableStream`, or in a minimal stand-in
+ * supporting `getWriter()`, `close()` and `abort()` when the runtime has no
+ * Web streams.
+ */
+function writableFrom(
+  write: (chunk: Uint8Array) => Promise<void>,
+  close: () => Promise<void>,
+  abort: () => void
+): WritableStream<Uint8Array> {
+  const Writable = webGlobal<typeof WritableStream>("WritableStream");
+  if (Writable) {
+    return new Writable<Uint8Array>({ write, close, abort });
+  }
+
+  let locked = false;
+  const stream = {
+    get locked(): boolean {
+      return locked;
+    },
+    getWriter() {
+      if (locked) throw new TypeError("WritableStream is locked");
+      locked = true;
+      return {
+        // Each write is awaited by the caller, so there is always room for one
+        desiredSize: 1,
+        ready: Promise.resolve(),
+        write,
+        close,
+        async abort(): Promise<void> {
+          abort();
+        },
+        releaseLock(): void {
+          locked = false;
+        },
+      };
+    },
+    close,
+    async abort(): Promise<void> {
+      abort();
+    },
+  };
+  return stream as unknown as WritableStream<Uint8Array>;
+}
+
+/**
+ * Opens a file for chunked reading.
+ *
+ * @param path - Path to the file
+ * @param options - Chunk size and optional byte range
+ * @returns Async-iterable stream of chunks
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3002) if file not found
+ * @throws Error (3010) when read after `cancel()`
+ *
+ * @example
+ * ```typescript
+ * import { openReadStream } from "runtime:fs";
+ * import { sendStream } from "runtime:ipc";
+ *
+ * // Send a large video to a renderer without loading it into memory
+ * const video = await openReadStream("./media/intro.mp4", { chunkSize: 256 * 1024 });
+ * await sendStream("main", "video", video);
+ * ```
+ *
+ * @example
+ * ```typescript
+ * // Count lines in a multi-GB log
+ * let lines = 0;
+ * for await (const chunk of await openReadStream("./logs/app.log")) {
+ *   for (const byte of chunk) if (byte === 0x0a) lines++;
+ * }
+ * ```
+ */
+export async function openReadStream(
+  path: string,
+  options: ReadStreamOptions = {}
+): Promise<FileReadStream> {
+  const rid = await core.ops.op_fs_read_stream_open(path, options);
+  let done = false;
+
+  const cancel = (): void => {
+    if (done) return;
+    done = true;
+    core.ops.op_fs_read_stream_close(rid);
+  };
+  const read = async (): Promise<Uint8Array | null> => {
+    if (done) return null;
+    const chunk = await core.ops.op_fs_read_stream_next(rid);
+    if (chunk.length === 0) {
+      cancel();
+      return null;
+    }
+    return chunk;
+  };
+
+  return {
+    rid,
+    read,
+    cancel,
+    async *[Symbol.asyncIterator](): AsyncGenerator<Uint8Array, void, unknown> {
+      try {
+        while (true) {
+          const chunk = await read();
+          if (chunk === null) break;
+          yield chunk;
+        }
+      } finally {
+        cancel();
+      }
+    },
+    toReadableStream(): ReadableStream<Uint8Array> {
+      return readableFrom(read, cancel);
+    },
+  };
+}
+
+/**
+ * Opens a file for chunked writing.
+ *
+ * By default the file is created if missing and truncated otherwise; pass
+ * `append: true` to add to it instead.
+ *
+ * @param path - Path to the file
+ * @param options - Append and creation flags
+ * @returns Stream accepting chunks
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3003) if `createNew` is set and the file exists
+ * @throws Error (3010) when written after `close()` or `abort()`
+ *
+ * @example
+ * ```typescript
+ * import { openWriteStream } from "runtime:fs";
+ *
+ * const out = await openWriteStream("./export.csv");
+ * try {
+ *   const encoder = new TextEncoder();
+ *   for (const row of rows) {
+ *     await out.write(encoder.encode(row.join(",") + "\n"));
+ *   }
+ *   await out.close();
+ * } catch (e) {
+ *   out.abort();
+ *   throw e;
+ * }
+ * ```
+ */
+export async function openWriteStream(
+  path: string,
+  options: WriteStreamOptions = {}
+): Promise<FileWriteStream> {
+  const rid = await core.ops.op_fs_write_stream_open(path, options);
+  let closed = false;
+
+  const write = async (chunk: Uint8Array): Promise<void> => {
+    return await core.ops.op_fs_write_stream_write(rid, chunk);
+  };
+  const close = async (): Promise<void> => {
+    if (closed) return;
+    closed = true;
+    return await core.ops.op_fs_write_stream_close(rid);
+  };
+  const abort = (): void => {
+    if (closed) return;
+    closed = true;
+    core.ops.op_fs_write_stream_abort(rid);
+  };
+
+  return {
+    rid,
+    write,
+    close,
+    abort,
+    toWritableStream(): WritableStream<Uint8Array> {
+      return writableFrom(write, close, abort);
+    },
+  };
+}
+
+/**
+ * Writes every chunk of an (async) iterable to a file, then closes it.
+ *
+ * Each chunk is written before the next one is requested, so generators
+ * and other lazy sources are consumed at the speed of the disk.
+ *
+ * @param path - Path to the file
+ * @param source - Chunks to write, e.g. a `FileReadStream`
+ * @param options - Append and creation flags
+ *
+ * @example
+ * ```typescript
+ * import { openReadStream, writeStream } from "runtime:fs";
+ *
+ * // Copy the first 1 MiB of a file
+ * await writeStream("./head.bin", await openReadStream("./big.bin", { end: 1024 * 1024 }));
+ * ```
+ */
+export async function writeStream(
+  path: string,
+  source: AsyncIterable<Uint8Array> | Iterable<Uint8Array>,
+  options: WriteStreamOptions = {}
+): Promise<void> {
+  const stream = await openWriteStream(path, options);
+  try {
+    for await (const chunk of source) {
+      await stream.write(chunk);
+    }
+  } catch (e) {
+    stream.abort();
+    throw e;
+  }
+  await stream.close();
+}
+
+// ============================================================================
+// Walk, Glob and Bulk Operations
+// ============================================================================
+
+/**
+ * Recursively walks a directory, yielding every entry below it.
+ *
+ * The walk runs natively and entries arrive in batches, so large trees do
+ * not cost one op call per directory. Entries outside the app's `fs.read`
+ * permissions are skipped; with `followSymlinks`, entries reached through a
+ * link are also checked at the link's target. Breaking out of the loop
+ * stops the walk.
+ *
+ * @param path - Directory to walk (not itself yielded)
+ * @param options - Depth limit, glob filters, symlink and .gitignore handling
+ * @returns Async iterator of entries, parents before their children
+ *
+ * @throws Error (3001) if reading the root is not permitted
+ * @throws Error (3011) if an include or exclude glob is invalid
+ *
+ * @example
+ * ```typescript
+ * import { walk } from "runtime:fs";
+ *
+ * for await (const entry of walk("./src", { include: ["**\/*.ts"], gitignore: true })) {
+ *   console.log(entry.path);
+ * }
+ * ```
+ *
+ * @example
+ * ```typescript
+ * // Top-level directories only
+ * for await (const dir of walk("./data", { maxDepth: 1, includeFiles: false })) {
+ *   console.log(dir.name);
+ * }
+ * ```
+ */
+export async function* walk(
+  path: string,
+  options: WalkOptions = {}
+): AsyncGenerator<WalkEntry, void, unknown> {
+  const rid = await core.ops.op_fs_walk_open(path, options);
+  try {
+    while (true) {
+      const batch = await core.ops.op_fs_walk_next(rid);
+      if (batch.length === 0) return;
+      yield* batch;
+    }
+  } finally {
+    core.ops.op_fs_walk_close(rid);
+  }
+}
+
+/**
+ * Lists the paths matching a glob pattern.
+ *
+ * The search starts at the pattern's leading directories (`./src` for
+ * `./src/**\/*.ts`). Matches outside the app's `fs.read` permissions are
+ * left out.
+ *
+ * @param pattern - Glob such as `./assets/*.png` or `src/**\/*.test.ts`
+ * @returns Matching file and directory paths
+ *
+ * @throws Error (3001) if reading the pattern's base directory is not permitted
+ * @throws Error (3011) if the pattern is invalid
+ *
+ * @example
+ * ```typescript
+ * import { glob } from "runtime:fs";
+ *
+ * const images = await glob("./assets/**\/*.{png,jpg}");
+ * ```
+ */
+export async function glob(pattern: string): Promise<string[]> {
+  return await core.ops.op_fs_glob(pattern);
+}
+
+async function finishTransfer(
+  rid: number,
+  onProgress?: (progress: TransferProgress) => void
+): Promise<TransferProgress> {
+  if (onProgress) {
+    try {
+      let progress: TransferProgress | null;
+      while ((progress = await core.ops.op_fs_transfer_progress(rid)) !== null) {
+        onProgress(progress);
+      }
+    } catch (e) {
+      await core.ops.op_fs_transfer_finish(rid).catch(() => {});
+      throw e;
+    }
+  }
+  return await core.ops.op_fs_transfer_finish(rid);
+}
+
+/**
+ * Recursively copies a directory.
+ *
+ * Every source path needs read permission and every destination path write
+ * permission; all of them are checked before anything is written. Symlinks
+ * are recreated rather than followed.
+ *
+ * @param from - Directory to copy
+ * @param to - Destination directory
+ * @param options - Overwrite behavior and progress callback
+ * @returns Final progress, with totals
+ *
+ * @throws Error (3001) if any path is not permitted
+ * @throws Error (3003) if `to` exists and `overwrite` is not set
+ * @throws Error (3005) if `from` is a file
+ *
+ * @example
+ * ```typescript
+ * import { copyDir } from "runtime:fs";
+ *
+ * await copyDir("./data", "./backups/data", {
+ *   onProgress: (p) => console.log(`${p.bytesDone}/${p.bytesTotal} bytes`),
+ * });
+ * ```
+ */
+export async function copyDir(
+  from: string,
+  to: string,
+  options: TransferOptions = {}
+): Promise<TransferProgress> {
+  const rid = await core.ops.op_fs_copy_dir(from, to, { overwrite: options.overwrite });
+  return await finishTransfer(rid, options.onProgress);
+}
+
+/**
+ * Moves a file or directory tree.
+ *
+ * Renames in place when possible; across filesystems the tree is copied
+ * and the source removed, reporting progress as it goes. Every source path
+ * needs read and write permission, and every destination path write
+ * permission.
+ *
+ * @param from - File or directory to move
+ * @param to - New path
+ * @param options - Overwrite behavior and progress callback
+ * @returns Final progress, with totals
+ *
+ * @throws Error (3001) if any path is not permitted
+ * @throws Error (3003) if `to` exists and `overwrite` is not set
+ *
+ * @example
+ * ```typescript
+ * import { move } from "runtime:fs";
+ *
+ * await move("./downloads/project", "/Volumes/Backup/project", {
+ *   onProgress: (p) => console.log(`${p.filesDone}/${p.filesTotal} files`),
+ * });
+ * ```
+ */
+export async function move(
+  from: string,
+  to: string,
+  options: TransferOptions = {}
+): Promise<TransferProgress> {
+  const rid = await core.ops.op_fs_move(from, to, { overwrite: options.overwrite });
+  return await finishTransfer(rid, options.onProgress);
+}
+
+// ============================================================================
+// Permissions, Ownership, Timestamps and Locks
+// ============================================================================
+
+/**
+ * Changes the permission bits of a file or directory.
+ *
+ * On Windows only the write bits matter: clearing all of them makes the
+ * file read-only, setting any makes it writable.
+ *
+ * @param path - File or directory
+ * @param mode - Permission bits, e.g. `0o644`
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3002) if path not found
+ *
+ * @example
+ * ```typescript
+ * import { chmod } from "runtime:fs";
+ *
+ * await chmod("./scripts/deploy.sh", 0o755);
+ * ```
+ */
+export async function chmod(path: string, mode: number): Promise<void> {
+  return await core.ops.op_fs_chmod(path, mode);
+}
+
+/**
+ * Changes the owner and/or group of a file or directory. Unix only.
+ *
+ * @param path - File or directory
+ * @param uid - New owner user ID, or null to keep the current owner
+ * @param gid - New group ID, or null to keep the current group
+ *
+ * @throws Error (3001) if permission denied (by the manifest or the OS)
+ * @throws Error (3002) if path not found
+ * @throws Error (3013) on Windows
+ *
+ * @example
+ * ```typescript
+ * import { chown } from "runtime:fs";
+ *
+ * await chown("./backups/db.sqlite", 1000, null);
+ * ```
+ */
+export async function chown(path: string, uid: number | null, gid: number | null): Promise<void> {
+  return await core.ops.op_fs_chown(path, uid, gid);
+}
+
+/**
+ * Sets the access and modification times of a file or directory.
+ *
+ * @param path - File or directory
+ * @param atime - Access time, as a Date or milliseconds since the Unix epoch
+ * @param mtime - Modification time, as a Date or milliseconds since the Unix epoch
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3002) if path not found
+ *
+ * @example
+ * ```typescript
+ * import { utime } from "runtime:fs";
+ *
+ * // Backdate an archived log
+ * await utime("./archive/2020.log", Date.now(), new Date("2020-12-31T23:59:59Z"));
+ * ```
+ */
+export async function utime(path: string, atime: number | Date, mtime: number | Date): Promise<void> {
+  const ms = (time: number | Date): number => (time instanceof Date ? time.getTime() : time);
+  return await core.ops.op_fs_utime(path, ms(atime), ms(mtime));
+}
+
+/**
+ * Creates a hard link: a second name for the same file.
+ *
+ * @param target - Existing file
+ * @param path - New link path
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3002) if target not found
+ * @throws Error (3003) if path already exists
+ *
+ * @example
+ * ```typescript
+ * import { link } from "runtime:fs";
+ *
+ * // Snapshot without copying data
+ * await link("./data/archive.tar", "./snapshots/2024-06-01/archive.tar");
+ * ```
+ */
+export async function link(target: string, path: string): Promise<void> {
+  return await core.ops.op_fs_link(target, path);
+}
+
+/**
+ * Advisory lock on a file, held until released.
+ *
+ * Locks coordinate cooperating processes; they do not stop other reads or
+ * writes of the file. They are also released when the app exits.
+ */
+export class FileLock {
+  #released = false;
+
+  constructor(
+    /** Resource ID of the lock */
+    readonly rid: number,
+    /** Path of the locked file */
+    readonly path: string,
+    /** Whether the lock is exclusive */
+    readonly exclusive: boolean
+  ) {}
+
+  /** Release the lock. Calling it again does nothing. */
+  release(): void {
+    if (this.#released) return;
+    this.#released = true;
+    core.ops.op_fs_unlock(this.rid);
+  }
+}
+
+/**
+ * Acquires a `flock`-style advisory lock on a file, waiting while another
+ * process holds a conflicting one.
+ *
+ * Waiting does not block the event loop. An exclusive lock conflicts with
+ * every other lock; shared locks only conflict with exclusive ones.
+ *
+ * @param path - Lock file, created if missing unless `create` is false
+ * @param options - Lock kind, timeout and creation
+ * @returns The held lock
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3002) if the file is missing and `create` is false
+ * @throws Error (3012) if `timeout` elapses first
+ *
+ * @example
+ * ```typescript
+ * import { lock } from "runtime:fs";
+ *
+ * // Only one backup process at a time
+ * const held = await lock("./backups/.lock", { timeout: 5000 });
+ * try {
+ *   await runBackup();
+ * } finally {
+ *   held.release();
+ * }
+ * ```
+ */
+export async function lock(path: string, options: LockOptions = {}): Promise<FileLock> {
+  const rid = await core.ops.op_fs_lock(path, options);
+  return new FileLock(rid!, path, options.exclusive ?? true);
+}
+
+/**
+ * Acquires an advisory lock only if it is free right now.
+ *
+ * @param path - Lock file, created if missing unless `create` is false
+ * @param options - Lock kind and creation (`timeout` is ignored)
+ * @returns The held lock, or null if another process holds a conflicting lock
+ *
+ * @throws Error (3001) if permission denied
+ * @throws Error (3002) if the file is missing and `create` is false
+ *
+ * @example
+ * ```typescript
+ * import { tryLock } from "runtime:fs";
+ *
+ * const held = await tryLock("./data/.sync.lock");
+ * if (!held) {
+ *   console.log("Another instance is already syncing");
+ * }
+ * ```
+ */
+export async function tryLock(path: string, options: LockOptions = {}): Promise<FileLock | null> {
+  const rid = await core.ops.op_fs_lock(path, { ...options, wait: false });
+  return rid === null ? null : new FileLock(rid, path, options.exclusive ?? true);
+}
+
 // ============================================================================
 // Enhanced Operations
 // ============================================================================
@@ -1078,6 +2125,35 @@ interface OpRegistry {
   realPath: { args: []; result: void };
   tempFile: { args: []; result: void };
   tempDir: { args: []; result: void };
+  open: { args: []; result: void };
+  fileRead: { args: []; result: void };
+  fileWrite: { args: []; result: void };
+  fileSeek: { args: []; result: void };
+  fileTruncate: { args: []; result: void };
+  fileSync: { args: []; result: void };
+  fileStat: { args: []; result: void };
+  fileClose: { args: []; result: void };
+  readStreamOpen: { args: []; result: void };
+  readStreamNext: { args: []; result: void };
+  readStreamClose: { args: []; result: void };
+  writeStreamOpen: { args: []; result: void };
+  writeStreamWrite: { args: []; result: void };
+  writeStreamClose: { args: []; result: void };
+  writeStreamAbort: { args: []; result: void };
+  walkOpen: { args: []; result: void };
+  walkNext: { args: []; result: void };
+  walkClose: { args: []; result: void };
+  glob: { args: []; result: void };
+  copyDir: { args: []; result: void };
+  move: { args: []; result: void };
+  transferProgress: { args: []; result: void };
+  transferFinish: { args: []; result: void };
+  chmod: { args: []; result: void };
+  chown: { args: []; result: void };
+  utime: { args: []; result: void };
+  link: { args: []; result: void };
+  lock: { args: []; result: void };
+  unlock: { args: []; result: void };
 }
 
 /** Extract argument types for an operation */
@@ -1087,7 +2163,7 @@ type OpArgs<T extends keyof OpRegistry> = OpRegistry[T]['args'];
 type OpResult<T extends keyof OpRegistry> = OpRegistry[T]['result'];
 
 /** Valid operation names for this extension */
-type OpName = "readText" | "writeText" | "readBytes" | "writeBytes" | "stat" | "readDir" | "mkdir" | "remove" | "rename" | "copy" | "exists" | "watch" | "watchNext" | "watchClose" | "symlink" | "readLink" | "appendText" | "appendBytes" | "metadata" | "realPath" | "tempFile" | "tempDir";
+type OpName = "readText" | "writeText" | "readBytes" | "writeBytes" | "stat" | "readDir" | "mkdir" | "remove" | "rename" | "copy" | "exists" | "watch" | "watchNext" | "watchClose" | "symlink" | "readLink" | "appendText" | "appendBytes" | "metadata" | "realPath" | "tempFile" | "tempDir" | "open" | "fileRead" | "fileWrite" | "fileSeek" | "fileTruncate" | "fileSync" | "fileStat" | "fileClose" | "readStreamOpen" | "readStreamNext" | "readStreamClose" | "writeStreamOpen" | "writeStreamWrite" | "writeStreamClose" | "writeStreamAbort" | "walkOpen" | "walkNext" | "walkClose" | "glob" | "copyDir" | "move" | "transferProgress" | "transferFinish" | "chmod" | "chown" | "utime" | "link" | "lock" | "unlock";
 
 /** Hook callback types */
 type BeforeHookCallback<T extends OpName> = (args: OpArgs<T>) => void | Promise<void>;
@@ -7,6 +7,17 @@ declare const Deno: {
     ops: {
       op_ipc_send(windowId: string, channel: string, payload: unknown): Promise<void>;
       op_ipc_recv(): Promise<IpcEvent | null>;
+      op_ipc_handle(channel: string): void;
+      op_ipc_reply(windowId: string, invokeId: string, reply: InvokeReply): Promise<void>;
+      op_ipc_send_binary(windowId: string, channel: string, data: Uint8Array): Promise<void>;
+      op_ipc_stream_open(windowId: string, channel: string, highWaterMark?: number): Promise<string>;
+      op_ipc_stream_write(streamId: string, chunk: Uint8Array): Promise<void>;
+      op_ipc_stream_close(streamId: string): void;
+      op_ipc_stream_abort(streamId: string): void;
+      op_ipc_subscribe(options: SubscribeOptions): number;
+      op_ipc_subscription_recv(id: number): Promise<IpcEvent | null>;
+      op_ipc_unsubscribe(id: number): void;
+      op_ipc_broadcast(channel: string, payload: unknown): Promise<void>;
     };
   };
 };
@@ -22,8 +33,12 @@ export interface IpcEvent {
   channel: string;
   /** Event payload data */
   payload: unknown;
-  /** Event type for window system events */
-  type?: "close" | "focus" | "blur" | "resize" | "move";
+  /** Event type for window system events, or "cancel" for a cancelled invoke */
+  type?: "close" | "focus" | "blur" | "resize" | "move" | "cancel";
+  /** Correlation ID when the renderer called `window.host.invoke()` */
+  invokeId?: string;
+  /** Events this subscription's overflow policy discarded before this one */
+  dropped?: number;
 }
 
 /**
@@ -36,6 +51,108 @@ export type IpcEventCallback = (event: IpcEvent) => void;
  */
 export type ChannelCallback = (payload: unknown, windowId: string) => void;
 
+/**
+ * Context passed to invoke handlers
+ */
+export interface InvokeContext {
+  /** Window ID that made the invoke */
+  windowId: string;
+  /** Aborted when the renderer cancels the invoke or it times out */
+  signal: AbortSignal;
+}
+
+/**
+ * Handler for `window.host.invoke(channel, payload)` calls.
+ * The resolved value is sent back to the renderer; a thrown error rejects
+ * the renderer's promise with the same `name`, `message`, `code` and `data`.
+ */
+export type InvokeHandler = (payload: unknown, context: InvokeContext) => unknown | Promise<unknown>;
+
+/**
+ * Options for `handle()`
+ */
+export interface HandleOptions {
+  /** Fail the invoke with IPC_INVOKE_TIMEOUT if the handler takes longer (ms) */
+  timeoutMs?: number;
+}
+
+/** Error codes carried by rejected invokes */
+export const IPC_INVOKE_TIMEOUT = 7004;
+export const IPC_INVOKE_CANCELLED = 7005;
+export const IPC_NO_HANDLER = 7006;
+export const IPC_HANDLER_ERROR = 7007;
+
+/** Error code of a subscription closed by the "close" overflow policy */
+export const IPC_SUBSCRIPTION_OVERFLOW = 7010;
+
+/**
+ * What a full subscription queue does with a new event:
+ * - `"dropOldest"` discards the oldest queued event (default)
+ * - `"dropNewest"` discards the new event
+ * - `"close"` closes the subscription; its next receive rejects
+ */
+export type OverflowPolicy = "dropOldest" | "dropNewest" | "close";
+
+/**
+ * Options for `subscribe()`
+ */
+export interface SubscribeOptions {
+  /** Only receive events on this channel */
+  channel?: string;
+  /** Only receive events from this window */
+  windowId?: string;
+  /** Maximum queued events (default: 256) */
+  capacity?: number;
+  /** Behavior when the queue is full (default: "dropOldest") */
+  overflow?: OverflowPolicy;
+}
+
+/**
+ * A filtered view of renderer events with its own bounded queue.
+ * Iterating it with for-await yields events until it is closed.
+ */
+export interface Subscription extends AsyncIterable<IpcEvent> {
+  /** Subscription ID */
+  readonly id: number;
+  /** Receive the next event, or null once the subscription is closed */
+  recv(): Promise<IpcEvent | null>;
+  /** Stop receiving; a pending `recv()` resolves to null */
+  close(): void;
+}
+
+/**
+ * Options for `openStream()` and `sendStream()`
+ */
+export interface StreamOptions {
+  /** Chunks buffered for the renderer before `write()` waits (default: 8) */
+  highWaterMark?: number;
+}
+
+/**
+ * Writable side of a byte stream to a renderer.
+ * The renderer receives a `ReadableStream<Uint8Array>` on the stream's channel.
+ */
+export interface BinaryStreamWriter {
+  /** Stream ID */
+  readonly id: string;
+  /**
+   * Queue a chunk. Resolves once the chunk is buffered; waits while the
+   * renderer is `highWaterMark` chunks behind. Rejects if the renderer
+   * cancelled its stream.
+   */
+  write(chunk: Uint8Array | ArrayBuffer): Promise<void>;
+  /** End the stream after the renderer reads the queued chunks */
+  close(): void;
+  /** Abort the stream; the renderer's reader errors */
+  abort(): void;
+}
+
+export interface InvokeReply {
+  ok: boolean;
+  value?: unknown;
+  error?: { name: string; code: number; message: string; data?: unknown };
+}
+
 const core = Deno.core;
 
 // ============================================================================
@@ -120,7 +237,62 @@ export async function* windowEvents(): AsyncGenerator<IpcEvent, void, unknown> {
 }
 
 /**
- * Filter events for a specific window.
+ * Subscribe to renderer events.
+ *
+ * Every subscription has its own bounded queue, and each event is delivered
+ * to all subscriptions whose filters match, so a slow consumer never holds
+ * up another one. When the queue is full, `overflow` decides what happens;
+ * the next event received reports how many were dropped in `dropped`.
+ *
+ * @param options - Channel/window filters and queue settings
+ * @returns A subscription that can be iterated with for-await
+ *
+ * @example
+ * ```ts
+ * import { subscribe } from "runtime:ipc";
+ *
+ * const progress = subscribe({ channel: "progress", capacity: 16, overflow: "dropOldest" });
+ * for await (const event of progress) {
+ *   if (event.dropped) console.log(`Skipped ${event.dropped} updates`);
+ *   render(event.payload);
+ * }
+ * ```
+ */
+export function subscribe(options: SubscribeOptions = {}): Subscription {
+  const id = core.ops.op_ipc_subscribe(options);
+  let closed = false;
+
+  const recv = async (): Promise<IpcEvent | null> => {
+    if (closed) return null;
+    return await core.ops.op_ipc_subscription_recv(id);
+  };
+  const close = (): void => {
+    if (closed) return;
+    closed = true;
+    core.ops.op_ipc_unsubscribe(id);
+  };
+
+  return {
+    id,
+    recv,
+    close,
+    async *[Symbol.asyncIterator]() {
+      try {
+        while (true) {
+          const event = await recv();
+          if (event === null) break;
+          yield event;
+        }
+      } finally {
+        close();
+      }
+    },
+  };
+}
+
+/**
+ * Events from a specific window.
+ * Uses its own subscription, so it does not take events from other consumers.
  *
  * @param windowId - The window ID to filter for
  *
@@ -137,15 +309,12 @@ export async function* windowEvents(): AsyncGenerator<IpcEvent, void, unknown> {
 export async function* windowEventsFor(
   windowId: string
 ): AsyncGenerator<IpcEvent, void, unknown> {
-  for await (const event of windowEvents()) {
-    if (event.windowId === windowId) {
-      yield event;
-    }
-  }
+  yield* subscribe({ windowId });
 }
 
 /**
- * Filter events for a specific channel.
+ * Events on a specific channel.
+ * Uses its own subscription, so it does not take events from other consumers.
  *
  * @param channel - The channel name to filter for
  *
@@ -162,11 +331,7 @@ export async function* windowEventsFor(
 export async function* channelEvents(
   channel: string
 ): AsyncGenerator<IpcEvent, void, unknown> {
-  for await (const event of windowEvents()) {
-    if (event.channel === channel) {
-      yield event;
-    }
-  }
+  yield* subscribe({ channel });
 }
 
 // ============================================================================
@@ -176,6 +341,8 @@ export async function* channelEvents(
 let listenerActive = false;
 const eventCallbacks: IpcEventCallback[] = [];
 const channelCallbacks: Map<string, ChannelCallback[]> = new Map();
+const invokeHandlers: Map<string, { handler: InvokeHandler; options: HandleOptions }> = new Map();
+const runningInvokes: Map<string, AbortController> = new Map();
 
 /**
  * Register a callback for all IPC events.
@@ -246,6 +413,136 @@ export function onChannel(channel: string, callback: ChannelCallback): () => voi
   };
 }
 
+/**
+ * Handle `window.host.invoke(channel, payload)` calls from renderers.
+ * Only one handler per channel; registering again replaces it.
+ * Returns a function that removes the handler.
+ *
+ * Invokes on channels without a handler are rejected in the renderer with
+ * code `IPC_NO_HANDLER`. The channel must be allowed by the app's IPC
+ * capabilities, otherwise this throws.
+ *
+ * @param channel - The channel name to answer
+ * @param handler - Called with (payload, { windowId, signal }); its result is the reply
+ * @param options - Optional handler timeout
+ * @returns Function that removes the handler
+ *
+ * @example
+ * ```ts
+ * import { handle } from "runtime:ipc";
+ *
+ * handle("get-user", async (payload, { signal }) => {
+ *   const { id } = payload as { id: number };
+ *   const user = await loadUser(id, signal);
+ *   if (!user) {
+ *     throw Object.assign(new Error(`No user ${id}`), { name: "NotFound", code: 404 });
+ *   }
+ *   return user;
+ * });
+ *
+ * // In the renderer:
+ * // const user = await window.host.invoke("get-user", { id: 1 }, { timeoutMs: 5000 });
+ * ```
+ */
+export function handle(
+  channel: string,
+  handler: InvokeHandler,
+  options: HandleOptions = {}
+): () => void {
+  core.ops.op_ipc_handle(channel);
+  const entry = { handler, options };
+  invokeHandlers.set(channel, entry);
+  startEventLoop();
+
+  return () => {
+    if (invokeHandlers.get(channel) === entry) {
+      invokeHandlers.delete(channel);
+    }
+  };
+}
+
+function invokeKey(event: IpcEvent): string {
+  return `${event.windowId}\u0000${event.invokeId}`;
+}
+
+function errorReply(error: unknown): InvokeReply {
+  const err = error as { name?: unknown; code?: unknown; message?: unknown; data?: unknown } | null;
+  return {
+    ok: false,
+    error: {
+      name: typeof err?.name === "string" ? err.name : "Error",
+      code: typeof err?.code === "number" ? err.code : IPC_HANDLER_ERROR,
+      message: typeof err?.message === "string" ? err.message : String(error),
+      data: err?.data,
+    },
+  };
+}
+
+async function sendReply(event: IpcEvent, reply: InvokeReply): Promise<void> {
+  try {
+    await core.ops.op_ipc_reply(event.windowId, event.invokeId!, reply);
+  } catch (_e) {
+    // The invoke was cancelled or already answered
+  }
+}
+
+/**
+ * Run the handler for an invoke and send its result to the renderer.
+ */
+async function dispatchInvoke(event: IpcEvent): Promise<void> {
+  const entry = invokeHandlers.get(event.channel);
+  if (!entry) {
+    await sendReply(event, {
+      ok: false,
+      error: {
+        name: "IpcInvokeError",
+        code: IPC_NO_HANDLER,
+        message: `No handler registered for channel '${event.channel}'`,
+      },
+    });
+    return;
+  }
+
+  const key = invokeKey(event);
+  const controller = new AbortController();
+  runningInvokes.set(key, controller);
+
+  let timer: number | undefined;
+  const timeout = new Promise<InvokeReply>((resolve) => {
+    if (entry.options.timeoutMs === undefined) return;
+    timer = setTimeout(() => {
+      controller.abort();
+      resolve({
+        ok: false,
+        error: {
+          name: "IpcInvokeError",
+          code: IPC_INVOKE_TIMEOUT,
+          message: `Handler for '${event.channel}' timed out after ${entry.options.timeoutMs}ms`,
+        },
+      });
+    }, entry.options.timeoutMs);
+  });
+
+  const run = (async (): Promise<InvokeReply> => {
+    try {
+      const value = await entry.handler(event.payload, {
+        windowId: event.windowId,
+        signal: controller.signal,
+      });
+      return { ok: true, value: value ?? null };
+    } catch (e) {
+      return errorReply(e);
+    }
+  })();
+
+  const reply = await Promise.race([run, timeout]);
+  clearTimeout(timer);
+  runningInvokes.delete(key);
+  if (!controller.signal.aborted || reply.error?.code === IPC_INVOKE_TIMEOUT) {
+    await sendReply(event, reply);
+  }
+}
+
 /**
  * Start the internal event loop if not already running.
  * This is called automatically when registering callbacks.
@@ -256,6 +553,16 @@ function startEventLoop(): void {
   listenerActive = true;
   (async () => {
     for await (const event of windowEvents()) {
+      // Invokes go to their handler, cancellations abort it
+      if (event.invokeId !== undefined) {
+        if (event.type === "cancel") {
+          runningInvokes.get(invokeKey(event))?.abort();
+        } else {
+          dispatchInvoke(event);
+        }
+        continue;
+      }
+
       // Dispatch to global callbacks
       for (const cb of eventCallbacks) {
         try {
@@ -281,6 +588,113 @@ function startEventLoop(): void {
   })();
 }
 
+// ============================================================================
+// Binary API
+// ============================================================================
+
+function toBytes(data: Uint8Array | ArrayBuffer): Uint8Array {
+  return data instanceof Uint8Array ? data : new Uint8Array(data);
+}
+
+/**
+ * Send bytes to a window without JSON or base64 encoding.
+ * Listeners registered with `window.host.on(channel, ...)` receive a `Uint8Array`.
+ *
+ * @param windowId - The window to send to
+ * @param channel - The channel name for the message
+ * @param data - The bytes to send
+ *
+ * @example
+ * ```ts
+ * import { sendBinary } from "runtime:ipc";
+ * import { readBytes } from "runtime:fs";
+ *
+ * await sendBinary("main", "thumbnail", await readBytes("./thumb.png"));
+ * ```
+ */
+export async function sendBinary(
+  windowId: string,
+  channel: string,
+  data: Uint8Array | ArrayBuffer
+): Promise<void> {
+  return await core.ops.op_ipc_send_binary(windowId, channel, toBytes(data));
+}
+
+/**
+ * Open a byte stream to a window.
+ * Listeners registered with `window.host.on(channel, ...)` receive a
+ * `ReadableStream<Uint8Array>` that yields each written chunk.
+ *
+ * @param windowId - The window to send to
+ * @param channel - The channel name for the stream
+ * @param options - Buffering options
+ * @returns Writer for the stream
+ *
+ * @example
+ * ```ts
+ * import { openStream } from "runtime:ipc";
+ *
+ * const stream = await openStream("main", "audio");
+ * for (const frame of frames) {
+ *   await stream.write(frame); // waits while the renderer is behind
+ * }
+ * stream.close();
+ * ```
+ */
+export async function openStream(
+  windowId: string,
+  channel: string,
+  options: StreamOptions = {}
+): Promise<BinaryStreamWriter> {
+  const id = await core.ops.op_ipc_stream_open(windowId, channel, options.highWaterMark);
+  return {
+    id,
+    write: (chunk) => core.ops.op_ipc_stream_write(id, toBytes(chunk)),
+    close: () => core.ops.op_ipc_stream_close(id),
+    abort: () => core.ops.op_ipc_stream_abort(id),
+  };
+}
+
+/**
+ * Stream every chunk of an (async) iterable to a window, then close the stream.
+ * Aborts the stream if the source throws.
+ *
+ * @param windowId - The window to send to
+ * @param channel - The channel name for the stream
+ * @param source - Chunks to send
+ * @param options - Buffering options
+ *
+ * @example
+ * ```ts
+ * import { sendStream } from "runtime:ipc";
+ *
+ * async function* frames() {
+ *   for (let i = 0; i < 100; i++) {
+ *     yield await renderFrame(i);
+ *   }
+ * }
+ *
+ * await sendStream("main", "frames", frames());
+ * ```
+ */
+export async function sendStream(
+  windowId: string,
+  channel: string,
+  source: AsyncIterable<Uint8Array | ArrayBuffer> | Iterable<Uint8Array | ArrayBuffer>,
+  options: StreamOptions = {}
+): Promise<void> {
+  const stream = await openStream(windowId, channel, options);
+  try {
+    for await (const chunk of source) {
+      await stream.write(chunk);
+    }
+  } catch (e) {
+    stream.abort();
+    throw e;
+  }
+  stream.close();
+}
+
 // ============================================================================
 // Broadcast API
 // ============================================================================
@@ -310,6 +724,24 @@ export async function broadcast(
   );
 }
 
+/**
+ * Send a message to every open window.
+ * Windows whose channel allowlist excludes the channel do not receive it.
+ *
+ * @param channel - The channel name for the message
+ * @param payload - Optional payload data to send
+ *
+ * @example
+ * ```ts
+ * import { broadcastAll } from "runtime:ipc";
+ *
+ * await broadcastAll("theme-changed", { theme: "dark" });
+ * ```
+ */
+export async function broadcastAll(channel: string, payload?: unknown): Promise<void> {
+  return await core.ops.op_ipc_broadcast(channel, payload ?? null);
+}
+
 
 // ============================================================================
 // Extensibility API (auto-generated)
@@ -319,6 +751,17 @@ export async function broadcast(
 interface OpRegistry {
   send: { args: []; result: void };
   recv: { args: []; result: void };
+  handle: { args: []; result: void };
+  reply: { args: []; result: void };
+  sendBinary: { args: []; result: void };
+  streamOpen: { args: []; result: void };
+  streamWrite: { args: []; result: void };
+  streamClose: { args: []; result: void };
+  streamAbort: { args: []; result: void };
+  subscribe: { args: []; result: void };
+  subscriptionRecv: { args: []; result: void };
+  unsubscribe: { args: []; result: void };
+  broadcast: { args: []; result: void };
 }
 
 /** Extract argument types for an operation */
@@ -328,7 +771,7 @@ type OpArgs<T extends keyof OpRegistry> = OpRegistry[T]['args'];
 type OpResult<T extends keyof OpRegistry> = OpRegistry[T]['result'];
 
 /** Valid operation names for this extension */
-type OpName = "send" | "recv";
+type OpName = "send" | "recv" | "handle" | "reply" | "sendBinary" | "streamOpen" | "streamWrite" | "streamClose" | "streamAbort" | "subscribe" | "subscriptionRecv" | "unsubscribe" | "broadcast";
 
 /** Hook callback types */
 type BeforeHookCallback<T extends OpName> = (args: OpArgs<T>) => void | Promise<void>;
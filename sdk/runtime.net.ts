@@ -3,23 +3,213 @@
 declare const Deno: {
   core: {
     ops: {
-      op_net_fetch(url: string, opts: FetchOptions): Promise<RawFetchResponse>;
-      op_net_fetch_bytes(url: string, opts: FetchOptions): Promise<RawFetchBytesResponse>;
+      op_net_fetch(url: string, opts: RawFetchOptions): Promise<RawFetchResponse>;
+      op_net_fetch_bytes(url: string, opts: RawFetchOptions): Promise<RawFetchBytesResponse>;
       // WebSocket operations
       op_net_ws_connect(url: string, opts: WebSocketConnectOptions): Promise<WebSocketConnectionResult>;
       op_net_ws_send(id: bigint, message: WebSocketMessageData): Promise<void>;
       op_net_ws_recv(id: bigint): Promise<WebSocketMessageData | null>;
       op_net_ws_close(id: bigint): Promise<void>;
       // Streaming fetch
-      op_net_fetch_stream(url: string, opts: FetchOptions): Promise<StreamResponseResult>;
+      op_net_fetch_stream(url: string, opts: RawFetchOptions): Promise<StreamResponseResult>;
+      // Uploads, progress and downloads
+      op_net_body_open(): Promise<bigint>;
+      op_net_body_write(streamId: bigint, data: Uint8Array): Promise<void>;
+      op_net_body_bytes(data: Uint8Array): Promise<bigint>;
+      op_net_body_close(streamId: bigint, error: string | null): Promise<void>;
+      op_net_progress_open(): Promise<bigint>;
+      op_net_progress_next(progressId: bigint): Promise<RawFetchProgress | null>;
+      op_net_progress_close(progressId: bigint): Promise<RawFetchProgress | null>;
+      op_net_download(
+        url: string,
+        path: string,
+        opts: RawFetchOptions,
+        download: { resume?: boolean; if_range?: string },
+      ): Promise<RawDownloadResult>;
+      // Cookies
+      op_net_cookies_clear(domain: string | null): Promise<void>;
+      // HTTP server
+      op_net_serve(opts: ServeOptions): Promise<ServerInfo>;
+      op_net_server_next(serverId: bigint): Promise<RawServerRequest | null>;
+      op_net_server_body_read(requestId: bigint): Promise<Uint8Array>;
+      op_net_server_respond(requestId: bigint, init: RawResponseInit, body: Uint8Array): Promise<void>;
+      op_net_server_response_write(requestId: bigint, chunk: Uint8Array): Promise<void>;
+      op_net_server_response_close(requestId: bigint): Promise<void>;
+      op_net_server_shutdown(serverId: bigint, graceMs: number | null): Promise<void>;
+      // Sockets
+      op_net_tcp_connect(opts: TcpConnectOptions): Promise<RawConnInfo>;
+      op_net_tcp_listen(opts: ListenOptions): Promise<RawListenerInfo>;
+      op_net_unix_connect(path: string): Promise<RawConnInfo>;
+      op_net_unix_listen(path: string): Promise<RawListenerInfo>;
+      op_net_listener_accept(listenerId: bigint): Promise<RawConnInfo | null>;
+      op_net_listener_close(listenerId: bigint): Promise<void>;
+      op_net_conn_read(connId: bigint, maxBytes: number | null): Promise<Uint8Array>;
+      op_net_conn_write(connId: bigint, data: Uint8Array): Promise<void>;
+      op_net_conn_close_write(connId: bigint): Promise<void>;
+      op_net_conn_close(connId: bigint): Promise<void>;
+      op_net_udp_bind(opts: UdpBindOptions): Promise<RawListenerInfo>;
+      op_net_udp_send(socketId: bigint, hostname: string, port: number, data: Uint8Array): Promise<number>;
+      op_net_udp_recv(socketId: bigint, maxBytes: number | null): Promise<Datagram | null>;
+      op_net_udp_close(socketId: bigint): Promise<void>;
     };
   };
 };
 
+/**
+ * A request body: text, bytes, or chunks streamed to the server as they are
+ * produced (sent with chunked encoding)
+ */
+export type FetchBody =
+  | string
+  | Uint8Array
+  | ArrayBuffer
+  | ReadableStream<Uint8Array>
+  | AsyncIterable<Uint8Array | string>;
+
 export interface FetchOptions {
+  method?: string;
+  headers?: Record<string, string>;
+  body?: FetchBody;
+  /** multipart/form-data parts; cannot be combined with body */
+  multipart?: MultipartPart[];
+  /** Overrides the manifest's [http] settings for this request */
+  client?: HttpClientConfig;
+  /** Called as the request body is sent and the response body received */
+  onProgress?: (progress: FetchProgress) => void;
+}
+
+export interface RawFetchOptions {
   method?: string;
   headers?: Record<string, string>;
   body?: string;
+  body_buffer?: number;
+  body_stream?: number;
+  multipart?: RawMultipartPart[];
+  progress_id?: number;
+  client?: HttpClientConfig;
+}
+
+/**
+ * A multipart/form-data part. Set exactly one of value, data and path.
+ */
+export interface MultipartPart {
+  name: string;
+  /** Text field value */
+  value?: string;
+  /** File contents */
+  data?: Uint8Array;
+  /** File on disk, read while the request is sent (needs fs read permission) */
+  path?: string;
+  /** File name for data and path parts (default: the path's file name) */
+  filename?: string;
+  /** Defaults to application/octet-stream for files */
+  contentType?: string;
+}
+
+export interface RawMultipartPart {
+  name: string;
+  value?: string;
+  buffer?: number;
+  path?: string;
+  filename?: string;
+  content_type?: string;
+}
+
+/**
+ * Bytes a request has sent and received so far
+ */
+export interface FetchProgress {
+  uploaded: number;
+  /** null when the body length is unknown (streams) */
+  uploadTotal: number | null;
+  /** Includes the part of a resumed download already on disk */
+  downloaded: number;
+  /** null when the response has no length */
+  downloadTotal: number | null;
+}
+
+export interface RawFetchProgress {
+  uploaded: number;
+  upload_total: number | null;
+  downloaded: number;
+  download_total: number | null;
+}
+
+/**
+ * Options for download()
+ */
+export interface DownloadOptions extends FetchOptions {
+  /** Continue from the length of an existing file with a Range request */
+  resume?: boolean;
+  /** ETag or Last-Modified from an earlier attempt; a changed file is downloaded again in full */
+  ifRange?: string;
+}
+
+/**
+ * A finished download
+ */
+export interface DownloadResult {
+  path: string;
+  status: number;
+  url: string;
+  /** File size after the download */
+  size: number;
+  /** Bytes received by this request */
+  received: number;
+  /** Whether an earlier partial file was continued */
+  resumed: boolean;
+  /** Validators to pass as ifRange when resuming later */
+  etag: string | null;
+  lastModified: string | null;
+}
+
+export interface RawDownloadResult {
+  path: string;
+  status: number;
+  url: string;
+  size: number;
+  received: number;
+  resumed: boolean;
+  etag: string | null;
+  last_modified: string | null;
+}
+
+/** Proxy settings (manifest `[http.proxy]` or `client.proxy`) */
+export interface ProxyConfig {
+  /** Defaults to "manual" with a proxy URL, "pac" with pac_url, else "system" */
+  mode?: "system" | "direct" | "manual" | "pac";
+  /** Proxy for every scheme, e.g. "http://proxy.corp:8080" */
+  url?: string;
+  http?: string;
+  https?: string;
+  /** Hosts reached directly (domains, IPs, CIDR ranges) */
+  no_proxy?: string[];
+  /** PAC script URL; detected from the OS in "pac" mode when omitted */
+  pac_url?: string;
+  username?: string;
+  password?: string;
+}
+
+/** HTTP client settings (manifest `[http]` or a fetch's `client` option) */
+export interface HttpClientConfig {
+  user_agent?: string;
+  proxy?: ProxyConfig;
+  /** Extra trusted root certificates as PEM text */
+  ca_certs?: string[];
+  /** Trust the OS certificate store (default: true) */
+  system_certs?: boolean;
+  /** Client certificate chain for mutual TLS, as PEM text */
+  client_cert?: string;
+  /** Private key for client_cert, as PEM text */
+  client_key?: string;
+  /** Send and store cookies using the app's jar (default: false) */
+  cookies?: boolean;
+  /** Redirect handling (default: "follow") */
+  redirect?: "follow" | "manual" | "error";
+  /** Most redirects followed (default: 10) */
+  max_redirects?: number;
+  /** HTTP version preference (default: "auto") */
+  http2?: "auto" | "http1" | "http2";
 }
 
 export interface RawFetchResponse {
@@ -114,10 +304,267 @@ export interface StreamResponse {
   ok: boolean;
 }
 
+// HTTP server types
+export interface RawServerRequest {
+  id: bigint;
+  method: string;
+  url: string;
+  path: string;
+  query: string | null;
+  headers: Record<string, string>;
+  remoteAddr: string;
+}
+
+export interface RawResponseInit {
+  status?: number;
+  headers?: Record<string, string>;
+  stream?: boolean;
+}
+
+/**
+ * Options for serve()
+ */
+export interface ServeOptions {
+  /** Port to listen on; 0 picks a free port (default: 0) */
+  port?: number;
+  /** Interface to bind (default: "127.0.0.1") */
+  hostname?: string;
+  /**
+   * PEM certificate chain; serves HTTPS together with key. Pass the PEM
+   * text (e.g. from runtime:fs readTextFile), not a file path.
+   */
+  cert?: string;
+  /** PEM private key (PKCS#8, PKCS#1 or SEC1) */
+  key?: string;
+}
+
+/**
+ * Information about a running server
+ */
+export interface ServerInfo {
+  id: bigint;
+  hostname: string;
+  port: number;
+  tls: boolean;
+  /** Base URL, e.g. "http://127.0.0.1:8080" */
+  url: string;
+}
+
+/**
+ * Response returned by a serve() handler. The body may be a string, bytes,
+ * or an async iterable of chunks to stream.
+ */
+export interface ServerResponse {
+  status?: number;
+  headers?: Record<string, string>;
+  body?: string | Uint8Array | AsyncIterable<string | Uint8Array> | null;
+}
+
+/**
+ * Handler for incoming requests
+ */
+export type ServeHandler = (request: ServerRequest) => ServerResponse | Promise<ServerResponse>;
+
+/**
+ * A running HTTP server
+ */
+export interface Server extends ServerInfo {
+  /**
+   * Stop accepting connections and wait for in-flight requests to finish.
+   * @param opts.gracefulTimeoutMs - How long to wait before dropping connections (default: 10000)
+   */
+  shutdown(opts?: { gracefulTimeoutMs?: number }): Promise<void>;
+  /** Resolves once the server has stopped and every handler has finished */
+  finished: Promise<void>;
+}
+
+// Socket types
+export interface RawConnInfo {
+  id: bigint;
+  localAddr: NetAddr;
+  remoteAddr: NetAddr;
+}
+
+export interface RawListenerInfo {
+  id: bigint;
+  addr: NetAddr;
+}
+
+/**
+ * Address of a socket endpoint
+ */
+export interface NetAddr {
+  transport: "tcp" | "udp" | "unix";
+  hostname: string | null;
+  port: number | null;
+  /** Socket path for Unix sockets */
+  path: string | null;
+}
+
+/**
+ * Options for connectTcp()
+ */
+export interface TcpConnectOptions {
+  hostname: string;
+  port: number;
+  /** Give up connecting after this many milliseconds */
+  timeoutMs?: number;
+  /** Disable Nagle's algorithm (default: true) */
+  noDelay?: boolean;
+}
+
+/**
+ * Options for listenTcp()
+ */
+export interface ListenOptions {
+  /** Port to bind; 0 picks a free port (default: 0) */
+  port?: number;
+  /** Interface to bind (default: "127.0.0.1") */
+  hostname?: string;
+}
+
+/**
+ * Options for bindUdp()
+ */
+export interface UdpBindOptions extends ListenOptions {
+  /** Allow sending broadcasts */
+  broadcast?: boolean;
+}
+
+/**
+ * A received UDP datagram
+ */
+export interface Datagram {
+  data: Uint8Array;
+  remoteAddr: NetAddr;
+}
+
 const core = Deno.core;
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+function toProgress(raw: RawFetchProgress): FetchProgress {
+  return {
+    uploaded: raw.uploaded,
+    uploadTotal: raw.upload_total,
+    downloaded: raw.downloaded,
+    downloadTotal: raw.download_total,
+  };
+}
+
+async function* readableChunks(stream: ReadableStream<Uint8Array>): AsyncIterable<Uint8Array> {
+  const reader = stream.getReader();
+  try {
+    while (true) {
+      const { done, value } = await reader.read();
+      if (done) return;
+      yield value;
+    }
+  } finally {
+    reader.releaseLock();
+  }
+}
+
+async function pumpBody(id: bigint, chunks: AsyncIterable<Uint8Array | string>): Promise<void> {
+  try {
+    for await (const chunk of chunks) {
+      await core.ops.op_net_body_write(id, toBuffer(chunk));
+    }
+  } catch (e) {
+    // Fails the request rather than sending a truncated body
+    await core.ops.op_net_body_close(id, String(e));
+    return;
+  }
+  await core.ops.op_net_body_close(id, null);
+}
+
+async function watchProgress(
+  id: bigint,
+  onProgress: (progress: FetchProgress) => void,
+): Promise<RawFetchProgress | null> {
+  let last: RawFetchProgress | null = null;
+  let raw: RawFetchProgress | null;
+  while ((raw = await core.ops.op_net_progress_next(id)) !== null) {
+    last = raw;
+    onProgress(toProgress(raw));
+  }
+  return last;
+}
+
+/**
+ * Run a request op with the options' body stream and progress channel
+ */
+async function withTransfer<T>(
+  opts: FetchOptions,
+  run: (raw: RawFetchOptions) => Promise<T>,
+): Promise<T> {
+  const { body, multipart, onProgress, ...rest } = opts;
+  const raw: RawFetchOptions = { ...rest };
+  // Binary data is staged as buffers and passed by id
+  const staged: bigint[] = [];
+  const stage = async (data: Uint8Array): Promise<number> => {
+    const id = await core.ops.op_net_body_bytes(data);
+    staged.push(id);
+    return Number(id);
+  };
+  let chunks: AsyncIterable<Uint8Array | string> | null = null;
+  if (typeof body === "string") {
+    raw.body = body;
+  } else if (body instanceof Uint8Array) {
+    raw.body_buffer = await stage(body);
+  } else if (body instanceof ArrayBuffer) {
+    raw.body_buffer = await stage(new Uint8Array(body));
+  } else if (isAsyncIterable(body)) {
+    chunks = body;
+  } else if (body != null) {
+    chunks = readableChunks(body);
+  }
+  if (multipart) {
+    raw.multipart = [];
+    for (const part of multipart) {
+      raw.multipart.push({
+        name: part.name,
+        value: part.value,
+        buffer: part.data ? await stage(part.data) : undefined,
+        path: part.path,
+        filename: part.filename,
+        content_type: part.contentType,
+      });
+    }
+  }
+
+  const streamId = chunks ? await core.ops.op_net_body_open() : null;
+  const progressId = onProgress ? await core.ops.op_net_progress_open() : null;
+  if (streamId !== null) raw.body_stream = Number(streamId);
+  if (progressId !== null) raw.progress_id = Number(progressId);
+
+  const request = run(raw);
+  const pumping = streamId !== null && chunks ? pumpBody(streamId, chunks) : null;
+  const watching = progressId !== null && onProgress ? watchProgress(progressId, onProgress) : null;
+  try {
+    return await request;
+  } finally {
+    // Drops staged bytes the request never took
+    for (const id of staged) {
+      await core.ops.op_net_body_close(id, null);
+    }
+    if (streamId !== null) {
+      // No-op once the body was sent; otherwise stops the pump
+      await core.ops.op_net_body_close(streamId, "request finished");
+      await pumping?.catch(() => {});
+    }
+    if (progressId !== null && onProgress) {
+      const final = await core.ops.op_net_progress_close(progressId);
+      const last = await watching;
+      if (final && (last === null || final.uploaded !== last.uploaded || final.downloaded !== last.downloaded)) {
+        onProgress(toProgress(final));
+      }
+    }
+  }
+}
 
 export async function fetch(url: string, opts: FetchOptions = {}): Promise<FetchResponse> {
-  const response = await core.ops.op_net_fetch(url, opts);
+  const response = await withTransfer(opts, (raw) => core.ops.op_net_fetch(url, raw));
   return {
     ok: response.ok,
     status: response.status,
@@ -128,7 +575,7 @@ export async function fetch(url: string, opts: FetchOptions = {}): Promise<Fetch
 }
 
 export async function fetchBytes(url: string, opts: FetchOptions = {}): Promise<FetchBytesResponse> {
-  const response = await core.ops.op_net_fetch_bytes(url, opts);
+  const response = await withTransfer(opts, (raw) => core.ops.op_net_fetch_bytes(url, raw));
   return {
     ok: response.ok,
     status: response.status,
@@ -270,7 +717,7 @@ export const ws = {
  * @returns Stream response with ID for reading chunks
  */
 export async function fetchStream(url: string, opts: FetchOptions = {}): Promise<StreamResponse> {
-  const result = await core.ops.op_net_fetch_stream(url, opts);
+  const result = await withTransfer(opts, (raw) => core.ops.op_net_fetch_stream(url, raw));
   return {
     id: result.id,
     status: result.status,
@@ -281,12 +728,389 @@ export async function fetchStream(url: string, opts: FetchOptions = {}): Promise
   };
 }
 
+/**
+ * Download a URL to a file. Needs fs write permission for the path.
+ * @param url - The URL to download
+ * @param path - Destination file; replaced unless resuming
+ * @param opts - Fetch options, plus resume and ifRange
+ * @returns Where the file ended up and how much was received
+ *
+ * @example
+ * ```typescript
+ * const first = await download(url, "./cache/model.bin", { resume: true });
+ * // After an interruption, continue where the file ends
+ * await download(url, "./cache/model.bin", {
+ *   resume: true,
+ *   ifRange: first.etag ?? undefined,
+ *   onProgress: (p) => console.log(`${p.downloaded}/${p.downloadTotal}`),
+ * });
+ * ```
+ */
+export async function download(url: string, path: string, opts: DownloadOptions = {}): Promise<DownloadResult> {
+  const { resume, ifRange, ...fetchOpts } = opts;
+  const result = await withTransfer(fetchOpts, (raw) =>
+    core.ops.op_net_download(url, path, raw, { resume, if_range: ifRange })
+  );
+  return {
+    path: result.path,
+    status: result.status,
+    url: result.url,
+    size: result.size,
+    received: result.received,
+    resumed: result.resumed,
+    etag: result.etag,
+    lastModified: result.last_modified,
+  };
+}
+
+/**
+ * Remove cookies from the app's cookie jar.
+ * @param domain - Only remove cookies for this domain and its subdomains
+ */
+export async function clearCookies(domain?: string): Promise<void> {
+  await core.ops.op_net_cookies_clear(domain ?? null);
+}
+
 // Legacy aliases
 export const connectWebSocket = ws.connect;
 export const sendWebSocket = ws.send;
 export const recvWebSocket = ws.recv;
 export const closeWebSocket = ws.close;
 
+// ============================================================================
+// HTTP Server API
+// ============================================================================
+
+/**
+ * An incoming request to a server started with serve()
+ */
+export class ServerRequest {
+  readonly method: string;
+  readonly url: string;
+  readonly path: string;
+  readonly query: URLSearchParams;
+  /** Lowercase header names; repeated headers are joined with ", " */
+  readonly headers: Record<string, string>;
+  readonly remoteAddr: string;
+  #id: bigint;
+  #bodyUsed = false;
+
+  constructor(raw: RawServerRequest) {
+    this.#id = raw.id;
+    this.method = raw.method;
+    this.url = raw.url;
+    this.path = raw.path;
+    this.query = new URLSearchParams(raw.query ?? "");
+    this.headers = raw.headers;
+    this.remoteAddr = raw.remoteAddr;
+  }
+
+  /**
+   * Read the request body chunk by chunk. The body can be read once.
+   */
+  async *body(): AsyncGenerator<Uint8Array, void, unknown> {
+    if (this.#bodyUsed) {
+      throw new Error("Request body already read");
+    }
+    this.#bodyUsed = true;
+    while (true) {
+      const chunk = await core.ops.op_net_server_body_read(this.#id);
+      if (chunk.length === 0) break;
+      yield chunk;
+    }
+  }
+
+  /** Read the whole body as bytes */
+  async bytes(): Promise<Uint8Array> {
+    const chunks: Uint8Array[] = [];
+    let length = 0;
+    for await (const chunk of this.body()) {
+      chunks.push(chunk);
+      length += chunk.length;
+    }
+    const result = new Uint8Array(length);
+    let offset = 0;
+    for (const chunk of chunks) {
+      result.set(chunk, offset);
+      offset += chunk.length;
+    }
+    return result;
+  }
+
+  /** Read the whole body as UTF-8 text */
+  async text(): Promise<string> {
+    return decoder.decode(await this.bytes());
+  }
+
+  /** Read the whole body as JSON */
+  async json<T = unknown>(): Promise<T> {
+    return JSON.parse(await this.text()) as T;
+  }
+}
+
+function toBuffer(chunk: string | Uint8Array): Uint8Array {
+  return typeof chunk === "string" ? encoder.encode(chunk) : chunk;
+}
+
+function isAsyncIterable(value: unknown): value is AsyncIterable<string | Uint8Array> {
+  return value != null && typeof (value as AsyncIterable<unknown>)[Symbol.asyncIterator] === "function";
+}
+
+async function respond(id: bigint, response: ServerResponse): Promise<void> {
+  const { status, headers, body } = response;
+  if (!isAsyncIterable(body)) {
+    await core.ops.op_net_server_respond(
+      id,
+      { status, headers },
+      body == null ? new Uint8Array() : toBuffer(body),
+    );
+    return;
+  }
+
+  await core.ops.op_net_server_respond(id, { status, headers, stream: true }, new Uint8Array());
+  try {
+    for await (const chunk of body) {
+      await core.ops.op_net_server_response_write(id, toBuffer(chunk));
+    }
+  } finally {
+    await core.ops.op_net_server_response_close(id);
+  }
+}
+
+async function handle(raw: RawServerRequest, handler: ServeHandler): Promise<void> {
+  const request = new ServerRequest(raw);
+  let response: ServerResponse;
+  try {
+    response = await handler(request);
+  } catch (err) {
+    response = { status: 500, body: String(err instanceof Error ? err.message : err) };
+  }
+  try {
+    await respond(raw.id, response);
+  } catch (_err) {
+    // The client went away while the response was being sent
+  }
+}
+
+/**
+ * Start an HTTP server. Requires the port to be allowed by
+ * `permissions.net.listen` in the manifest.
+ * @param options - Port number, or server options
+ * @param handler - Called for each request
+ * @returns The running server
+ *
+ * @example
+ * const server = await serve(8080, async (req) => {
+ *   if (req.path === "/callback") {
+ *     return { status: 200, body: `code=${req.query.get("code")}` };
+ *   }
+ *   return { status: 404, body: "Not found" };
+ * });
+ * await server.shutdown();
+ */
+export async function serve(options: number | ServeOptions, handler: ServeHandler): Promise<Server> {
+  const opts = typeof options === "number" ? { port: options } : options;
+  const info = await core.ops.op_net_serve(opts);
+
+  const handlers = new Set<Promise<void>>();
+  const finished = (async () => {
+    while (true) {
+      const raw = await core.ops.op_net_server_next(info.id);
+      if (!raw) break;
+      const pending = handle(raw, handler);
+      handlers.add(pending);
+      pending.finally(() => handlers.delete(pending));
+    }
+    await Promise.all(handlers);
+  })();
+
+  return {
+    ...info,
+    finished,
+    async shutdown(opts: { gracefulTimeoutMs?: number } = {}): Promise<void> {
+      await core.ops.op_net_server_shutdown(info.id, opts.gracefulTimeoutMs ?? null);
+      await finished;
+    },
+  };
+}
+
+// ============================================================================
+// Socket API
+// ============================================================================
+
+/**
+ * A TCP or Unix stream connection
+ */
+export class Conn {
+  readonly id: bigint;
+  readonly localAddr: NetAddr;
+  readonly remoteAddr: NetAddr;
+
+  constructor(info: RawConnInfo) {
+    this.id = info.id;
+    this.localAddr = info.localAddr;
+    this.remoteAddr = info.remoteAddr;
+  }
+
+  /**
+   * Read the next chunk of data.
+   * @param maxBytes - Largest chunk to return (default: 65536)
+   * @returns The data, or null at end of stream or after close()
+   */
+  async read(maxBytes?: number): Promise<Uint8Array | null> {
+    const chunk = await core.ops.op_net_conn_read(this.id, maxBytes ?? null);
+    return chunk.length === 0 ? null : chunk;
+  }
+
+  /**
+   * Write data; strings are sent as UTF-8.
+   */
+  async write(data: Uint8Array | string): Promise<void> {
+    return await core.ops.op_net_conn_write(this.id, toBuffer(data));
+  }
+
+  /**
+   * Shut down the sending side; the peer sees end of stream.
+   */
+  async closeWrite(): Promise<void> {
+    return await core.ops.op_net_conn_close_write(this.id);
+  }
+
+  /**
+   * Close the connection. Pending reads resolve with null.
+   */
+  async close(): Promise<void> {
+    return await core.ops.op_net_conn_close(this.id);
+  }
+
+  async *[Symbol.asyncIterator](): AsyncGenerator<Uint8Array, void, unknown> {
+    while (true) {
+      const chunk = await this.read();
+      if (chunk === null) break;
+      yield chunk;
+    }
+  }
+}
+
+/**
+ * A TCP or Unix listener
+ */
+export class Listener {
+  readonly id: bigint;
+  readonly addr: NetAddr;
+
+  constructor(info: RawListenerInfo) {
+    this.id = info.id;
+    this.addr = info.addr;
+  }
+
+  /**
+   * Wait for the next connection.
+   * @returns The connection, or null once the listener is closed
+   */
+  async accept(): Promise<Conn | null> {
+    const info = await core.ops.op_net_listener_accept(this.id);
+    return info ? new Conn(info) : null;
+  }
+
+  /**
+   * Stop listening. Unix socket files are removed.
+   */
+  async close(): Promise<void> {
+    return await core.ops.op_net_listener_close(this.id);
+  }
+
+  async *[Symbol.asyncIterator](): AsyncGenerator<Conn, void, unknown> {
+    while (true) {
+      const conn = await this.accept();
+      if (conn === null) break;
+      yield conn;
+    }
+  }
+}
+
+/**
+ * A bound UDP socket
+ */
+export class UdpSocket {
+  readonly id: bigint;
+  readonly addr: NetAddr;
+
+  constructor(info: RawListenerInfo) {
+    this.id = info.id;
+    this.addr = info.addr;
+  }
+
+  /**
+   * Send a datagram. The destination is checked against the net allow list
+   * as "hostname:port".
+   * @returns The number of bytes sent
+   */
+  async send(data: Uint8Array | string, hostname: string, port: number): Promise<number> {
+    return await core.ops.op_net_udp_send(this.id, hostname, port, toBuffer(data));
+  }
+
+  /**
+   * Wait for the next datagram.
+   * @param maxBytes - Largest datagram to accept; longer ones are truncated (default: 65535)
+   * @returns The datagram, or null once the socket is closed
+   */
+  async receive(maxBytes?: number): Promise<Datagram | null> {
+    return await core.ops.op_net_udp_recv(this.id, maxBytes ?? null);
+  }
+
+  /**
+   * Close the socket. Pending receives resolve with null.
+   */
+  async close(): Promise<void> {
+    return await core.ops.op_net_udp_close(this.id);
+  }
+}
+
+/**
+ * Open a TCP connection. The target is checked against the net allow list
+ * as "hostname:port".
+ *
+ * @example
+ * const conn = await connectTcp({ hostname: "localhost", port: 6379 });
+ * await conn.write("PING\r\n");
+ * const reply = await conn.read();
+ * await conn.close();
+ */
+export async function connectTcp(opts: TcpConnectOptions): Promise<Conn> {
+  return new Conn(await core.ops.op_net_tcp_connect(opts));
+}
+
+/**
+ * Listen for TCP connections. The port must be allowed by
+ * `permissions.net.listen`.
+ */
+export async function listenTcp(opts: ListenOptions = {}): Promise<Listener> {
+  return new Listener(await core.ops.op_net_tcp_listen(opts));
+}
+
+/**
+ * Connect to a Unix domain socket. The path must match the fs write globs.
+ */
+export async function connectUnix(path: string): Promise<Conn> {
+  return new Conn(await core.ops.op_net_unix_connect(path));
+}
+
+/**
+ * Listen on a Unix domain socket. The path must match the fs write globs;
+ * the socket file is removed when the listener is closed.
+ */
+export async function listenUnix(path: string): Promise<Listener> {
+  return new Listener(await core.ops.op_net_unix_listen(path));
+}
+
+/**
+ * Bind a UDP socket. The port must be allowed by `permissions.net.listen`.
+ */
+export async function bindUdp(opts: UdpBindOptions = {}): Promise<UdpSocket> {
+  return new UdpSocket(await core.ops.op_net_udp_bind(opts));
+}
+
 
 // ============================================================================
 // Extensibility API (auto-generated)
@@ -301,6 +1125,36 @@ interface OpRegistry {
   wsRecv: { args: []; result: void };
   wsClose: { args: []; result: void };
   fetchStream: { args: []; result: void };
+  bodyOpen: { args: []; result: void };
+  bodyWrite: { args: []; result: void };
+  bodyBytes: { args: []; result: void };
+  bodyClose: { args: []; result: void };
+  progressOpen: { args: []; result: void };
+  progressNext: { args: []; result: void };
+  progressClose: { args: []; result: void };
+  download: { args: []; result: void };
+  cookiesClear: { args: []; result: void };
+  serve: { args: []; result: void };
+  serverNext: { args: []; result: void };
+  serverBodyRead: { args: []; result: void };
+  serverRespond: { args: []; result: void };
+  serverResponseWrite: { args: []; result: void };
+  serverResponseClose: { args: []; result: void };
+  serverShutdown: { args: []; result: void };
+  tcpConnect: { args: []; result: void };
+  tcpListen: { args: []; result: void };
+  unixConnect: { args: []; result: void };
+  unixListen: { args: []; result: void };
+  listenerAccept: { args: []; result: void };
+  listenerClose: { args: []; result: void };
+  connRead: { args: []; result: void };
+  connWrite: { args: []; result: void };
+  connCloseWrite: { args: []; result: void };
+  connClose: { args: []; result: void };
+  udpBind: { args: []; result: void };
+  udpSend: { args: []; result: void };
+  udpRecv: { args: []; result: void };
+  udpClose: { args: []; result: void };
 }
 
 /** Extract argument types for an operation */
@@ -310,7 +1164,7 @@ type OpArgs<T extends keyof OpRegistry> = OpRegistry[T]['args'];
 type OpResult<T extends keyof OpRegistry> = OpRegistry[T]['result'];
 
 /** Valid operation names for this extension */
-type OpName = "fetch" | "fetchBytes" | "wsConnect" | "wsSend" | "wsRecv" | "wsClose" | "fetchStream";
+type OpName = "fetch" | "fetchBytes" | "wsConnect" | "wsSend" | "wsRecv" | "wsClose" | "fetchStream" | "bodyOpen" | "bodyWrite" | "bodyBytes" | "bodyClose" | "progressOpen" | "progressNext" | "progressClose" | "download" | "cookiesClear" | "serve" | "serverNext" | "serverBodyRead" | "serverRespond" | "serverResponseWrite" | "serverResponseClose" | "serverShutdown" | "tcpConnect" | "tcpListen" | "unixConnect" | "unixListen" | "listenerAccept" | "listenerClose" | "connRead" | "connWrite" | "connCloseWrite" | "connClose" | "udpBind" | "udpSend" | "udpRecv" | "udpClose";
 
 /** Hook callback types */
 type BeforeHookCallback<T extends OpName> = (args: OpArgs<T>) => void | Promise<void>;